@@ -0,0 +1,247 @@
+//! Running gensonnet generation from a `build.rs`
+//!
+//! [`Build`] generates the libraries of a configuration into `OUT_DIR`,
+//! leaving the configured output and the lockfile untouched, and tells cargo
+//! to rerun the build script only when the configuration, the lockfile or a
+//! local library recorded in it changes. Outputs keep their place relative to
+//! `output.base_path`, and the directory holding them is passed to the crate
+//! as `GENSONNET_DIR`, ready to embed with `include_dir!("$GENSONNET_DIR")`.
+//!
+//! ```no_run
+//! // build.rs
+//! fn main() -> anyhow::Result<()> {
+//!     gensonnet_build::Build::new().run()?;
+//!     Ok(())
+//! }
+//! ```
+
+use anyhow::{anyhow, Result};
+use gensonnet_core::{Config, GenerationResult, JsonnetGen, Lockfile};
+use std::path::{Path, PathBuf};
+
+/// Environment variable the generated libraries' directory is passed to the crate in
+pub const DIR_ENV: &str = "GENSONNET_DIR";
+
+/// Generation run from a build script
+#[derive(Debug, Clone)]
+pub struct Build {
+    config: PathBuf,
+    lockfile: PathBuf,
+    out_dir: Option<PathBuf>,
+    locked: bool,
+}
+
+impl Build {
+    /// Generate from the configuration file and `gensonnet.lock` in the
+    /// package directory, at the commits the lockfile records
+    ///
+    /// The configuration file is found by the names the CLI looks for,
+    /// `.gensonnet.yaml` first.
+    pub fn new() -> Self {
+        let config = Config::FILE_NAMES
+            .iter()
+            .map(PathBuf::from)
+            .find(|path| path.exists())
+            .unwrap_or_else(|| PathBuf::from(Config::FILE_NAMES[0]));
+        Self {
+            config,
+            lockfile: gensonnet_core::LockfileManager::default_path(),
+            out_dir: None,
+            locked: true,
+        }
+    }
+
+    /// Read a different configuration file
+    pub fn with_config(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config = path.into();
+        self
+    }
+
+    /// Read a different lockfile
+    pub fn with_lockfile(mut self, path: impl Into<PathBuf>) -> Self {
+        self.lockfile = path.into();
+        self
+    }
+
+    /// Generate below `dir` instead of `$OUT_DIR/gensonnet`
+    pub fn with_out_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.out_dir = Some(dir.into());
+        self
+    }
+
+    /// Whether sources are pinned to the commits recorded in the lockfile
+    ///
+    /// On by default, so builds are reproducible; without it, sources are
+    /// fetched at their configured refs on every run of the build script.
+    pub fn with_locked(mut self, locked: bool) -> Self {
+        self.locked = locked;
+        self
+    }
+
+    /// Files and directories whose changes make generation outdated
+    ///
+    /// The configuration and its fragments, the lockfile and freeze file, and
+    /// every source the lockfile records at a local path.
+    pub fn rerun_paths(&self) -> Result<Vec<PathBuf>> {
+        let mut paths = vec![self.config.clone()];
+        let config_dir = self.config.parent().unwrap_or(Path::new(""));
+        let fragments_dir = config_dir.join(Config::FRAGMENTS_DIR);
+        if fragments_dir.is_dir() {
+            paths.push(fragments_dir);
+        }
+
+        paths.push(self.lockfile.clone());
+        let freeze_path = self
+            .lockfile
+            .with_file_name(gensonnet_core::freeze::FREEZE_FILE);
+        if freeze_path.exists() {
+            paths.push(freeze_path);
+        }
+
+        if self.lockfile.exists() {
+            let lockfile = Lockfile::from_file(&self.lockfile)?;
+            let mut local: Vec<PathBuf> = lockfile
+                .sources
+                .values()
+                .filter(|entry| !entry.url.contains("://"))
+                .map(|entry| PathBuf::from(&entry.url))
+                .filter(|path| path.exists())
+                .collect();
+            local.sort();
+            local.dedup();
+            paths.extend(local);
+        }
+        Ok(paths)
+    }
+
+    /// Generate the libraries, print the cargo instructions and return the
+    /// directory holding them
+    ///
+    /// Fails if any source fails to generate; warnings are passed on to cargo.
+    pub fn run(&self) -> Result<PathBuf> {
+        let out_dir = match &self.out_dir {
+            Some(dir) => dir.clone(),
+            None => std::env::var_os("OUT_DIR")
+                .map(|dir| PathBuf::from(dir).join("gensonnet"))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "OUT_DIR is not set; run from a build script or set an output directory"
+                    )
+                })?,
+        };
+        for path in self.rerun_paths()? {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+
+        let result = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?
+            .block_on(self.generate(&out_dir))?;
+
+        let errors: Vec<String> = result
+            .results
+            .iter()
+            .flat_map(|source| {
+                source
+                    .errors
+                    .iter()
+                    .map(move |error| format!("{}: {}", source.source_name, error))
+            })
+            .collect();
+        if !errors.is_empty() {
+            return Err(anyhow!("Generation failed:\n{}", errors.join("\n")));
+        }
+        for source in &result.results {
+            for warning in &source.warnings {
+                println!("cargo:warning={}: {}", source.source_name, warning);
+            }
+        }
+
+        println!("cargo:rustc-env={}={}", DIR_ENV, out_dir.display());
+        Ok(out_dir)
+    }
+
+    async fn generate(&self, out_dir: &Path) -> Result<GenerationResult> {
+        let config = Config::from_file(&self.config)?;
+        let app = JsonnetGen::new(config)?
+            .with_lockfile_path(self.lockfile.clone())
+            .with_locked(self.locked);
+        app.initialize_plugins().await?;
+        let result = app.generate_into(out_dir).await;
+        app.shutdown_plugins().await?;
+        result
+    }
+}
+
+impl Default for Build {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gensonnet_core::LockfileEntry;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_build() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().join("generated");
+        let mut config = Config::default();
+        config.output.base_path = base.clone();
+        config.sources.push(
+            serde_yaml::from_str(&format!(
+                "type: fixture\nname: demo\nfixture: crds\nfilters: []\noutput_path: {}\n",
+                base.join("demo").display()
+            ))
+            .unwrap(),
+        );
+        let config_path = temp_dir.path().join("gensonnet.yaml");
+        config.save_to_file(&config_path).unwrap();
+
+        let lockfile_path = temp_dir.path().join("gensonnet.lock");
+        let library = temp_dir.path().join("lib");
+        std::fs::create_dir_all(&library).unwrap();
+        let mut lockfile = Lockfile::new();
+        lockfile.add_source(
+            "platform".to_string(),
+            LockfileEntry::new(
+                library.display().to_string(),
+                String::new(),
+                "sha256:0".to_string(),
+                Vec::new(),
+            ),
+        );
+        lockfile.add_source(
+            "upstream".to_string(),
+            LockfileEntry::new(
+                "https://github.com/example/operator.git".to_string(),
+                "main".to_string(),
+                "abc123".to_string(),
+                Vec::new(),
+            ),
+        );
+        lockfile.save_to_file(&lockfile_path).unwrap();
+        let before = std::fs::read_to_string(&lockfile_path).unwrap();
+
+        let out_dir = temp_dir.path().join("out");
+        let build = Build::new()
+            .with_config(&config_path)
+            .with_lockfile(&lockfile_path)
+            .with_out_dir(&out_dir)
+            .with_locked(false);
+        assert_eq!(
+            build.rerun_paths().unwrap(),
+            [config_path.clone(), lockfile_path.clone(), library]
+        );
+
+        assert_eq!(build.run().unwrap(), out_dir);
+        assert!(out_dir.join("demo").join("index.libsonnet").exists());
+
+        // Neither the configured output nor the lockfile are written
+        assert!(!base.exists());
+        assert_eq!(std::fs::read_to_string(&lockfile_path).unwrap(), before);
+    }
+}
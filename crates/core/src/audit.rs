@@ -0,0 +1,394 @@
+//! Audit manifest of generation runs
+//!
+//! With `audit.enabled`, every generation run appends to a JSONL manifest a
+//! record of the run and its operator, followed by one record per file the
+//! run created, modified or deleted. Changes are found by comparing content
+//! digests of the output paths before and after the run. Each record is
+//! signed over the previous record's signature and its own content, so
+//! editing, removing or reordering records breaks the chain.
+//!
+//! Without a signing key the chain is plain SHA-256, which anyone can
+//! recompute after rewriting records. Records removed from the end of the
+//! manifest leave a valid chain in either case; keep the signature of the
+//! last record elsewhere to detect that.
+
+use crate::config::AuditConfig;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Environment variable naming the operator recorded for a run
+pub const OPERATOR_ENV: &str = "GENSONNET_OPERATOR";
+
+/// Content digests of the files below a set of paths
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    files: BTreeMap<PathBuf, String>,
+}
+
+impl Snapshot {
+    /// Digest every file below `roots`, which may be files, directories or
+    /// missing, leaving out `exclude`
+    pub fn capture(roots: &[PathBuf], exclude: &Path) -> Result<Self> {
+        let mut files = BTreeMap::new();
+        for root in roots {
+            for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
+                if !entry.file_type().is_file() || entry.path() == exclude {
+                    continue;
+                }
+                let content = std::fs::read(entry.path())?;
+                files.insert(
+                    entry.path().to_path_buf(),
+                    hex::encode(Sha256::digest(&content)),
+                );
+            }
+        }
+        Ok(Self { files })
+    }
+
+    /// Files created, modified or deleted between this snapshot and `after`
+    pub fn changes(&self, after: &Snapshot) -> Vec<FileChange> {
+        let mut changes = Vec::new();
+        for (path, digest) in &after.files {
+            match self.files.get(path) {
+                None => changes.push(FileChange {
+                    action: FileAction::Created,
+                    path: path.clone(),
+                    before: None,
+                    after: Some(digest.clone()),
+                }),
+                Some(before) if before != digest => changes.push(FileChange {
+                    action: FileAction::Modified,
+                    path: path.clone(),
+                    before: Some(before.clone()),
+                    after: Some(digest.clone()),
+                }),
+                Some(_) => {}
+            }
+        }
+        for (path, digest) in &self.files {
+            if !after.files.contains_key(path) {
+                changes.push(FileChange {
+                    action: FileAction::Deleted,
+                    path: path.clone(),
+                    before: Some(digest.clone()),
+                    after: None,
+                });
+            }
+        }
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        changes
+    }
+}
+
+/// What a run did to a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileAction {
+    Created,
+    Modified,
+    Deleted,
+}
+
+/// A file changed by a run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileChange {
+    /// What the run did to the file
+    pub action: FileAction,
+
+    /// Path of the file
+    pub path: PathBuf,
+
+    /// SHA-256 of the content before the run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+
+    /// SHA-256 of the content after the run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+}
+
+/// A generation run being audited
+#[derive(Debug)]
+pub struct AuditRun {
+    run_id: String,
+    command: String,
+    operator: String,
+    started_at: DateTime<Utc>,
+    roots: Vec<PathBuf>,
+    before: Snapshot,
+}
+
+/// Record of a run, written before the records of its file changes
+#[derive(Serialize)]
+struct RunRecord<'a> {
+    record: &'static str,
+    run_id: &'a str,
+    command: &'a str,
+    operator: &'a str,
+    tool_version: &'static str,
+    started_at: DateTime<Utc>,
+    finished_at: DateTime<Utc>,
+    success: bool,
+    files_changed: usize,
+}
+
+/// Record of a file changed by a run
+#[derive(Serialize)]
+struct FileRecord<'a> {
+    record: &'static str,
+    run_id: &'a str,
+    #[serde(flatten)]
+    change: &'a FileChange,
+}
+
+/// Append-only manifest of audited runs
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+    key: Option<Vec<u8>>,
+}
+
+impl AuditLog {
+    /// A manifest at `path` whose records are chained with SHA-256
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            key: None,
+        }
+    }
+
+    /// Sign records with HMAC-SHA256 under `key`
+    pub fn with_key(mut self, key: impl Into<Vec<u8>>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// The manifest configured by `audit`, resolving its signing key
+    pub fn from_config(config: &AuditConfig) -> Result<Self> {
+        let log = Self::new(&config.path);
+        match &config.signing_key {
+            Some(key) => Ok(log.with_key(key.resolve()?)),
+            None => Ok(log),
+        }
+    }
+
+    /// Path of the manifest
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Start auditing a run that writes below `roots`
+    pub fn begin(&self, command: &str, operator: String, roots: Vec<PathBuf>) -> Result<AuditRun> {
+        let started_at = Utc::now();
+        let before = Snapshot::capture(&roots, &self.path)?;
+        Ok(AuditRun {
+            run_id: format!(
+                "{}-{:08x}",
+                started_at.format("%Y%m%dT%H%M%S%.3fZ"),
+                fastrand::u32(..)
+            ),
+            command: command.to_string(),
+            operator,
+            started_at,
+            roots,
+            before,
+        })
+    }
+
+    /// Record a finished run and the files it changed
+    pub fn finish(&self, run: AuditRun, success: bool) -> Result<Vec<FileChange>> {
+        let after = Snapshot::capture(&run.roots, &self.path)?;
+        let changes = run.before.changes(&after);
+
+        let mut records = vec![serde_json::to_value(RunRecord {
+            record: "run",
+            run_id: &run.run_id,
+            command: &run.command,
+            operator: &run.operator,
+            tool_version: env!("CARGO_PKG_VERSION"),
+            started_at: run.started_at,
+            finished_at: Utc::now(),
+            success,
+            files_changed: changes.len(),
+        })?];
+        for change in &changes {
+            records.push(serde_json::to_value(FileRecord {
+                record: "file",
+                run_id: &run.run_id,
+                change,
+            })?);
+        }
+        self.append(records)?;
+
+        Ok(changes)
+    }
+
+    /// Check the signature chain of the manifest, returning the number of records
+    pub fn verify(&self) -> Result<usize> {
+        let content = std::fs::read_to_string(&self.path)
+            .map_err(|e| anyhow!("Failed to read audit manifest {:?}: {}", self.path, e))?;
+
+        let mut previous = String::new();
+        let mut count = 0;
+        for (index, line) in content.lines().enumerate() {
+            let mut record: serde_json::Value = serde_json::from_str(line)
+                .map_err(|e| anyhow!("Invalid audit record on line {}: {}", index + 1, e))?;
+            let signature = record
+                .as_object_mut()
+                .and_then(|record| record.remove("signature"))
+                .and_then(|signature| signature.as_str().map(str::to_string))
+                .ok_or_else(|| anyhow!("Audit record on line {} is not signed", index + 1))?;
+            if !self.is_signed(&previous, &serde_json::to_string(&record)?, &signature) {
+                return Err(anyhow!(
+                    "Audit record on line {} does not match its signature",
+                    index + 1
+                ));
+            }
+            previous = signature;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Sign records and append them to the manifest
+    fn append(&self, records: Vec<serde_json::Value>) -> Result<()> {
+        let mut previous = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content
+                .lines()
+                .last()
+                .and_then(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+                .and_then(|record| record["signature"].as_str().map(str::to_string))
+                .unwrap_or_default(),
+            Err(_) => String::new(),
+        };
+
+        let mut lines = String::new();
+        for mut record in records {
+            let signature = self.sign(&previous, &serde_json::to_string(&record)?);
+            record["signature"] = serde_json::Value::String(signature.clone());
+            lines.push_str(&serde_json::to_string(&record)?);
+            lines.push('\n');
+            previous = signature;
+        }
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(lines.as_bytes())?;
+        Ok(())
+    }
+
+    /// Signature of a record chained to the previous record's signature
+    fn sign(&self, previous: &str, record: &str) -> String {
+        let message = format!("{previous}\n{record}");
+        match &self.key {
+            Some(key) => hex::encode(hmac_sha256(key, &message).finalize().into_bytes()),
+            None => hex::encode(Sha256::digest(message.as_bytes())),
+        }
+    }
+
+    /// Whether `signature` signs a record chained to the previous record's signature
+    fn is_signed(&self, previous: &str, record: &str, signature: &str) -> bool {
+        let message = format!("{previous}\n{record}");
+        match &self.key {
+            Some(key) => hex::decode(signature)
+                .is_ok_and(|signature| hmac_sha256(key, &message).verify_slice(&signature).is_ok()),
+            None => signature == hex::encode(Sha256::digest(message.as_bytes())),
+        }
+    }
+}
+
+/// Operator of a run: the configured one, `GENSONNET_OPERATOR` or the current user
+pub fn operator(configured: Option<&str>) -> String {
+    configured
+        .map(str::to_string)
+        .or_else(|| std::env::var(OPERATOR_ENV).ok())
+        .or_else(|| std::env::var("USER").ok())
+        .or_else(|| std::env::var("USERNAME").ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// HMAC-SHA256 of a message under `key`
+fn hmac_sha256(key: &[u8], message: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message.as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_audit_run_is_recorded_and_verified() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("output");
+        std::fs::create_dir_all(&output).unwrap();
+        std::fs::write(output.join("kept.libsonnet"), "{}").unwrap();
+        std::fs::write(output.join("changed.libsonnet"), "{}").unwrap();
+        std::fs::write(output.join("removed.libsonnet"), "{}").unwrap();
+
+        let manifest = temp_dir.path().join("audit/audit.jsonl");
+        let log = AuditLog::new(&manifest).with_key("s3cret");
+        let run = log
+            .begin("generate", "alice".to_string(), vec![output.clone()])
+            .unwrap();
+        std::fs::write(output.join("changed.libsonnet"), "{ a: 1 }").unwrap();
+        std::fs::remove_file(output.join("removed.libsonnet")).unwrap();
+        std::fs::write(output.join("added.libsonnet"), "{}").unwrap();
+        let changes = log.finish(run, true).unwrap();
+
+        let actions: Vec<(FileAction, String)> = changes
+            .iter()
+            .map(|change| {
+                (
+                    change.action,
+                    change
+                        .path
+                        .file_name()
+                        .unwrap()
+                        .to_string_lossy()
+                        .to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                (FileAction::Created, "added.libsonnet".to_string()),
+                (FileAction::Modified, "changed.libsonnet".to_string()),
+                (FileAction::Deleted, "removed.libsonnet".to_string()),
+            ]
+        );
+
+        // A second run continues the chain
+        let run = log
+            .begin("generate", "alice".to_string(), vec![output.clone()])
+            .unwrap();
+        assert!(log.finish(run, true).unwrap().is_empty());
+        assert_eq!(log.verify().unwrap(), 5);
+
+        let content = std::fs::read_to_string(&manifest).unwrap();
+        let run: serde_json::Value = serde_json::from_str(content.lines().next().unwrap()).unwrap();
+        assert_eq!(run["record"], "run");
+        assert_eq!(run["operator"], "alice");
+        assert_eq!(run["files_changed"], 3);
+
+        // Verifying with another key, or after tampering, fails
+        assert!(AuditLog::new(&manifest).with_key("other").verify().is_err());
+        std::fs::write(&manifest, content.replace("alice", "mallory")).unwrap();
+        assert!(log.verify().is_err());
+    }
+}
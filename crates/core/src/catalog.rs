@@ -0,0 +1,212 @@
+//! Discovery of logical sources inside catalog repositories
+
+use crate::config::CatalogDiscovery;
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+use walkdir::WalkDir;
+
+/// A directory of a catalog repository selected as a logical source
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogEntry {
+    /// Name of the logical source (the directory name)
+    pub name: String,
+
+    /// Path of the directory relative to the repository root
+    pub path: PathBuf,
+}
+
+impl CatalogEntry {
+    /// Identifier of the logical source within its catalog
+    pub fn source_id(&self, catalog_name: &str) -> String {
+        format!("{}/{}", catalog_name, self.name)
+    }
+}
+
+/// Find the directories of a catalog repository matching the discovery rules
+pub fn discover(repo_path: &Path, discovery: &CatalogDiscovery) -> Result<Vec<CatalogEntry>> {
+    let mut entries: Vec<CatalogEntry> = Vec::new();
+    let mut seen: HashMap<String, PathBuf> = HashMap::new();
+
+    for pattern in &discovery.paths {
+        let full_pattern = repo_path.join(pattern);
+        let matches = glob::glob(&full_pattern.to_string_lossy())
+            .map_err(|e| anyhow!("Invalid discovery path '{}': {}", pattern, e))?;
+
+        for dir in matches.filter_map(|m| m.ok()).filter(|p| p.is_dir()) {
+            let relative = dir.strip_prefix(repo_path).unwrap_or(&dir).to_path_buf();
+            if seen.values().any(|path| path == &relative) {
+                continue;
+            }
+
+            if !matches_rules(&dir, discovery) {
+                debug!(
+                    "Catalog directory {:?} does not match discovery rules",
+                    relative
+                );
+                continue;
+            }
+
+            let name = dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .ok_or_else(|| anyhow!("Cannot name catalog directory {:?}", relative))?;
+
+            if let Some(existing) = seen.get(&name) {
+                return Err(anyhow!(
+                    "Catalog directories {:?} and {:?} would both be named '{}'; narrow the discovery paths",
+                    existing,
+                    relative,
+                    name
+                ));
+            }
+
+            seen.insert(name.clone(), relative.clone());
+            entries.push(CatalogEntry {
+                name,
+                path: relative,
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+/// Whether a directory holds at least one CRD matching the category, label and annotation rules
+fn matches_rules(dir: &Path, discovery: &CatalogDiscovery) -> bool {
+    if discovery.categories.is_empty()
+        && discovery.labels.is_empty()
+        && discovery.annotations.is_empty()
+    {
+        return true;
+    }
+
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| {
+            matches!(
+                e.path().extension().and_then(|ext| ext.to_str()),
+                Some("yaml" | "yml")
+            )
+        })
+        .filter_map(|e| std::fs::read_to_string(e.path()).ok())
+        .any(|content| {
+            serde_yaml::Deserializer::from_str(&content).any(|document| {
+                serde::Deserialize::deserialize(document)
+                    .map(|crd: serde_yaml::Value| crd_matches(&crd, discovery))
+                    .unwrap_or(false)
+            })
+        })
+}
+
+/// Whether a single YAML document is a CRD matching the discovery rules
+fn crd_matches(document: &serde_yaml::Value, discovery: &CatalogDiscovery) -> bool {
+    if document.get("kind").and_then(|kind| kind.as_str()) != Some("CustomResourceDefinition") {
+        return false;
+    }
+
+    let metadata = document.get("metadata");
+    let has_all = |field: &str, expected: &BTreeMap<String, String>| {
+        expected.iter().all(|(key, value)| {
+            metadata
+                .and_then(|m| m.get(field))
+                .and_then(|map| map.get(key.as_str()))
+                .and_then(|v| v.as_str())
+                == Some(value.as_str())
+        })
+    };
+
+    let categories_match = discovery.categories.is_empty()
+        || document
+            .get("spec")
+            .and_then(|spec| spec.get("names"))
+            .and_then(|names| names.get("categories"))
+            .and_then(|categories| categories.as_sequence())
+            .is_some_and(|categories| {
+                categories
+                    .iter()
+                    .filter_map(|c| c.as_str())
+                    .any(|category| discovery.categories.iter().any(|wanted| wanted == category))
+            });
+
+    categories_match
+        && has_all("labels", &discovery.labels)
+        && has_all("annotations", &discovery.annotations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_crd(dir: &Path, category: &str, labels: &str) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(
+            dir.join("crd.yaml"),
+            format!(
+                r#"apiVersion: apiextensions.k8s.io/v1
+kind: CustomResourceDefinition
+metadata:
+  name: widgets.example.com
+  labels: {labels}
+spec:
+  group: example.com
+  names:
+    kind: Widget
+    categories: [{category}]
+"#
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_discover_by_path_category_and_label() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path();
+        write_crd(&repo.join("operators/alpha"), "networking", "{tier: core}");
+        write_crd(&repo.join("operators/beta"), "storage", "{tier: core}");
+        write_crd(&repo.join("operators/gamma"), "networking", "{tier: extra}");
+        std::fs::create_dir_all(repo.join("docs/alpha")).unwrap();
+
+        let discovery = CatalogDiscovery {
+            paths: vec!["operators/*".to_string()],
+            ..Default::default()
+        };
+        let names: Vec<_> = discover(repo, &discovery)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        assert_eq!(names, vec!["alpha", "beta", "gamma"]);
+
+        let discovery = CatalogDiscovery {
+            paths: vec!["operators/*".to_string()],
+            categories: vec!["networking".to_string()],
+            labels: [("tier".to_string(), "core".to_string())].into(),
+            ..Default::default()
+        };
+        let entries = discover(repo, &discovery).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, PathBuf::from("operators/alpha"));
+        assert_eq!(entries[0].source_id("fleet"), "fleet/alpha");
+    }
+
+    #[test]
+    fn test_discover_rejects_name_collisions() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path();
+        std::fs::create_dir_all(repo.join("a/operator")).unwrap();
+        std::fs::create_dir_all(repo.join("b/operator")).unwrap();
+
+        let discovery = CatalogDiscovery {
+            paths: vec!["*/operator".to_string()],
+            ..Default::default()
+        };
+        assert!(discover(repo, &discovery).is_err());
+    }
+}
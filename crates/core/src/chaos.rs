@@ -0,0 +1,179 @@
+//! Failure injection for exercising error recovery
+//!
+//! A `ChaosInjector` makes selected operations fail at random so that
+//! `process_source_with_recovery`, partial results and lockfile updates can be
+//! checked against injected git, parse and write failures. It is enabled with
+//! the hidden `--chaos` flag, the `GENSONNET_CHAOS` environment variable, or
+//! `JsonnetGen::with_chaos`.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Mutex;
+use tracing::warn;
+
+/// Environment variable holding the chaos spec
+pub const CHAOS_ENV: &str = "GENSONNET_CHAOS";
+
+/// Environment variable holding the seed for reproducible runs
+pub const CHAOS_SEED_ENV: &str = "GENSONNET_CHAOS_SEED";
+
+/// Operation a failure can be injected into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FaultPoint {
+    /// Cloning, fetching or resolving a source repository
+    Git,
+
+    /// Parsing schemas from a source
+    Parse,
+
+    /// Publishing generated files to the output path
+    Write,
+}
+
+impl FaultPoint {
+    /// All fault points
+    pub const ALL: [FaultPoint; 3] = [FaultPoint::Git, FaultPoint::Parse, FaultPoint::Write];
+}
+
+impl FromStr for FaultPoint {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "git" => Ok(FaultPoint::Git),
+            "parse" => Ok(FaultPoint::Parse),
+            "write" => Ok(FaultPoint::Write),
+            other => Err(anyhow!("Unknown chaos fault point: {}", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for FaultPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            FaultPoint::Git => "git",
+            FaultPoint::Parse => "parse",
+            FaultPoint::Write => "write",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Randomly fails operations at the enabled fault points
+#[derive(Debug)]
+pub struct ChaosInjector {
+    rate: f64,
+    points: HashSet<FaultPoint>,
+    rng: Mutex<fastrand::Rng>,
+}
+
+impl ChaosInjector {
+    /// Fail operations at every fault point with the given probability (0.0 to 1.0)
+    pub fn new(rate: f64) -> Self {
+        Self {
+            rate: rate.clamp(0.0, 1.0),
+            points: FaultPoint::ALL.into_iter().collect(),
+            rng: Mutex::new(fastrand::Rng::new()),
+        }
+    }
+
+    /// Only inject failures at the given fault points
+    pub fn with_points(mut self, points: impl IntoIterator<Item = FaultPoint>) -> Self {
+        self.points = points.into_iter().collect();
+        self
+    }
+
+    /// Seed the random generator for reproducible runs
+    pub fn with_seed(self, seed: u64) -> Self {
+        *self.rng.lock().unwrap() = fastrand::Rng::with_seed(seed);
+        self
+    }
+
+    /// Parse a spec of the form `<rate>[:<point>,...]`, e.g. `0.2` or `1.0:parse,write`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (rate, points) = match spec.split_once(':') {
+            Some((rate, points)) => (rate, Some(points)),
+            None => (spec, None),
+        };
+
+        let rate: f64 = rate
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("Invalid chaos rate '{}': expected 0.0 to 1.0", rate))?;
+        if !(0.0..=1.0).contains(&rate) {
+            return Err(anyhow!("Chaos rate {} is not between 0.0 and 1.0", rate));
+        }
+
+        let injector = Self::new(rate);
+        match points {
+            Some(points) => Ok(injector.with_points(
+                points
+                    .split(',')
+                    .map(|point| point.trim().parse())
+                    .collect::<Result<Vec<FaultPoint>>>()?,
+            )),
+            None => Ok(injector),
+        }
+    }
+
+    /// Create an injector from `GENSONNET_CHAOS`, if set
+    pub fn from_env() -> Result<Option<Self>> {
+        let Ok(spec) = std::env::var(CHAOS_ENV) else {
+            return Ok(None);
+        };
+
+        Self::parse(&spec)?.with_env_seed().map(Some)
+    }
+
+    /// Seed the injector from `GENSONNET_CHAOS_SEED`, if set
+    pub fn with_env_seed(self) -> Result<Self> {
+        let Ok(seed) = std::env::var(CHAOS_SEED_ENV) else {
+            return Ok(self);
+        };
+        let seed = seed
+            .parse()
+            .map_err(|_| anyhow!("Invalid {}: {}", CHAOS_SEED_ENV, seed))?;
+        Ok(self.with_seed(seed))
+    }
+
+    /// Fail with an injected error at `point` for `target`, at the configured rate
+    pub fn inject(&self, point: FaultPoint, target: &str) -> Result<()> {
+        if !self.points.contains(&point) || self.rng.lock().unwrap().f64() >= self.rate {
+            return Ok(());
+        }
+
+        warn!("Chaos: injecting {} failure for {}", point, target);
+        Err(anyhow!("Injected {} failure for {}", point, target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chaos_spec_and_injection() {
+        let always = ChaosInjector::parse("1.0:parse").unwrap();
+        assert!(always.inject(FaultPoint::Parse, "crds").is_err());
+        assert!(always.inject(FaultPoint::Git, "crds").is_ok());
+
+        let never = ChaosInjector::parse("0").unwrap();
+        for point in FaultPoint::ALL {
+            assert!(never.inject(point, "crds").is_ok());
+        }
+
+        // The same seed fails the same operations
+        let outcomes = |injector: ChaosInjector| -> Vec<bool> {
+            (0..32)
+                .map(|_| injector.inject(FaultPoint::Write, "crds").is_err())
+                .collect()
+        };
+        let first = outcomes(ChaosInjector::new(0.5).with_seed(7));
+        assert_eq!(first, outcomes(ChaosInjector::new(0.5).with_seed(7)));
+        assert!(first.contains(&true) && first.contains(&false));
+
+        assert!(ChaosInjector::parse("1.5").is_err());
+        assert!(ChaosInjector::parse("0.5:network").is_err());
+    }
+}
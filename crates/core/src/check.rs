@@ -0,0 +1,204 @@
+//! Read-only verification of generated output
+//!
+//! `generate --check` regenerates every source into a scratch directory, with
+//! a scratch copy of the lockfile, and compares the result with the output
+//! on disk. Nothing in the workspace changes, so CI can fail a change whose
+//! committed libraries are out of date with their configuration or lockfile.
+//! Files the output has but the run does not generate are not reported, as
+//! generation never removes them either.
+//!
+//! The run leaves timestamps out, so the lines of the output recording when it
+//! was generated are skipped when comparing.
+
+use crate::GenerationResult;
+use anyhow::Result;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// How a generated file differs from the output on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MismatchKind {
+    /// Generation would write a file the output does not have
+    Missing,
+
+    /// Generation would change the file
+    Changed,
+}
+
+/// A generated file that differs from the output on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMismatch {
+    /// Path of the file in the output
+    pub path: PathBuf,
+
+    /// How the file differs
+    pub kind: MismatchKind,
+}
+
+impl fmt::Display for FileMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            MismatchKind::Missing => "missing",
+            MismatchKind::Changed => "changed",
+        };
+        write!(f, "{kind:<8} {}", self.path.display())
+    }
+}
+
+/// Result of checking the output on disk against a fresh generation
+#[derive(Debug)]
+pub struct CheckResult {
+    /// The generation run into the scratch directory
+    pub generation: GenerationResult,
+
+    /// Number of generated files compared
+    pub files_checked: usize,
+
+    /// Generated files differing from the output, ordered by path
+    pub mismatches: Vec<FileMismatch>,
+}
+
+impl CheckResult {
+    /// Whether the output is up to date and every source generated cleanly
+    pub fn is_up_to_date(&self) -> bool {
+        self.mismatches.is_empty() && self.generation.statistics.error_count == 0
+    }
+}
+
+/// Compare the files generated below `generated` with their copies below `output`
+///
+/// Returns the number of files compared and the mismatches, with their paths
+/// below `output`. Lines of the output holding a date that the generated file
+/// does not have are taken for timestamps and skipped.
+pub fn compare_trees(generated: &Path, output: &Path) -> Result<(usize, Vec<FileMismatch>)> {
+    let mut files_checked = 0;
+    let mut mismatches = Vec::new();
+    if !generated.exists() {
+        return Ok((files_checked, mismatches));
+    }
+
+    for entry in WalkDir::new(generated).sort_by_file_name() {
+        let entry = entry?;
+        // Bookkeeping of publishing, not part of the library
+        if !entry.file_type().is_file() || entry.file_name() == crate::workspace::PUBLISHED_FILE {
+            continue;
+        }
+        files_checked += 1;
+        let path = output.join(entry.path().strip_prefix(generated)?);
+        let kind = match std::fs::read(&path) {
+            Ok(existing) if matches_untimestamped(&std::fs::read(entry.path())?, &existing) => {
+                continue
+            }
+            Ok(_) => MismatchKind::Changed,
+            Err(_) => MismatchKind::Missing,
+        };
+        mismatches.push(FileMismatch { path, kind });
+    }
+    Ok((files_checked, mismatches))
+}
+
+/// Whether a file of the output matches its copy generated without timestamps
+fn matches_untimestamped(generated: &[u8], existing: &[u8]) -> bool {
+    if generated == existing {
+        return true;
+    }
+    let (Ok(generated), Ok(existing)) = (
+        std::str::from_utf8(generated),
+        std::str::from_utf8(existing),
+    ) else {
+        return false;
+    };
+
+    let mut generated = generated.split_inclusive('\n').peekable();
+    for line in existing.split_inclusive('\n') {
+        if generated.peek() == Some(&line) {
+            generated.next();
+        } else if !has_date(line) {
+            return false;
+        }
+    }
+    generated.next().is_none()
+}
+
+/// Whether a line holds a `YYYY-MM-DD` date, as timestamps of generated files do
+fn has_date(line: &str) -> bool {
+    const PATTERN: &[u8] = b"dddd-dd-dd";
+    line.as_bytes().windows(PATTERN.len()).any(|window| {
+        window
+            .iter()
+            .zip(PATTERN)
+            .all(|(byte, expected)| match expected {
+                b'd' => byte.is_ascii_digit(),
+                _ => byte == expected,
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_compare_trees() {
+        let temp_dir = TempDir::new().unwrap();
+        let generated = temp_dir.path().join("generated");
+        let output = temp_dir.path().join("output");
+        for (root, files) in [
+            (
+                &generated,
+                &[("a.libsonnet", "a"), ("v1/b.libsonnet", "b")][..],
+            ),
+            (
+                &output,
+                &[
+                    ("a.libsonnet", "a"),
+                    ("v1/b.libsonnet", "old"),
+                    ("extra", ""),
+                ][..],
+            ),
+        ] {
+            for (path, content) in files {
+                std::fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+                std::fs::write(root.join(path), content).unwrap();
+            }
+        }
+        std::fs::write(generated.join("c.libsonnet"), "c").unwrap();
+
+        let (files_checked, mismatches) = compare_trees(&generated, &output).unwrap();
+        assert_eq!(files_checked, 3);
+        assert_eq!(
+            mismatches,
+            [
+                FileMismatch {
+                    path: output.join("c.libsonnet"),
+                    kind: MismatchKind::Missing
+                },
+                FileMismatch {
+                    path: output.join("v1/b.libsonnet"),
+                    kind: MismatchKind::Changed
+                },
+            ]
+        );
+        assert!(mismatches[1].to_string().starts_with("changed  "));
+    }
+
+    #[test]
+    fn test_timestamps_are_skipped() {
+        let generated = "// Source: widgets\n{\n  tool_version: \"1.0\",\n}\n";
+        assert!(matches_untimestamped(
+            generated.as_bytes(),
+            b"// Source: widgets\n// Generated on 2024-05-01\n{\n  generated_at: \"2024-05-01T10:00:00+00:00\",\n  tool_version: \"1.0\",\n}\n"
+        ));
+        // Other changes still count
+        assert!(!matches_untimestamped(
+            generated.as_bytes(),
+            b"// Source: widgets\n{\n  tool_version: \"0.9\",\n}\n"
+        ));
+        assert!(!matches_untimestamped(
+            generated.as_bytes(),
+            b"// Source: widgets\n{\n}\n"
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+//! Submitting the sample resources of generated libraries to a cluster
+//!
+//! Every library carries a `_samples.libsonnet` instantiating each resource
+//! with its minimal required fields. The samples are evaluated and applied
+//! with server-side dry-run, so the API server's validation and admission
+//! webhooks judge the objects without persisting them.
+
+use anyhow::{anyhow, Result};
+use jsonnet_generator::SAMPLES_FILE;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use walkdir::WalkDir;
+
+/// Outcome of submitting one sample resource
+#[derive(Debug, Clone)]
+pub struct AdmissionOutcome {
+    /// The `_samples.libsonnet` holding the resource
+    pub file: PathBuf,
+
+    /// Name of the resource's schema
+    pub resource: String,
+
+    /// Whether the server accepted the resource
+    pub accepted: bool,
+
+    /// Server output: the object name when accepted, the admission error otherwise
+    pub message: String,
+}
+
+/// Evaluates sample files and submits the resources with `kubectl`
+pub struct ClusterDryRun {
+    evaluator: String,
+    jpaths: Vec<PathBuf>,
+    kubectl: String,
+    context: Option<String>,
+    namespace: Option<String>,
+}
+
+impl ClusterDryRun {
+    /// Create a dry-run using the `jsonnet` and `kubectl` binaries on the PATH
+    pub fn new() -> Self {
+        Self {
+            evaluator: "jsonnet".to_string(),
+            jpaths: Vec::new(),
+            kubectl: "kubectl".to_string(),
+            context: None,
+            namespace: None,
+        }
+    }
+
+    /// Use a different evaluator binary (e.g. `jrsonnet`)
+    pub fn with_evaluator(mut self, evaluator: impl Into<String>) -> Self {
+        self.evaluator = evaluator.into();
+        self
+    }
+
+    /// Add library search paths, passed to the evaluator as `-J`
+    pub fn with_jpaths(mut self, jpaths: Vec<PathBuf>) -> Self {
+        self.jpaths = jpaths;
+        self
+    }
+
+    /// Use a different `kubectl` binary
+    pub fn with_kubectl(mut self, kubectl: impl Into<String>) -> Self {
+        self.kubectl = kubectl.into();
+        self
+    }
+
+    /// Submit to a kubeconfig context other than the current one
+    pub fn with_context(mut self, context: Option<String>) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Namespace for namespaced resources, instead of the context's default
+    pub fn with_namespace(mut self, namespace: Option<String>) -> Self {
+        self.namespace = namespace;
+        self
+    }
+
+    /// Evaluate a sample file and submit each of its resources
+    pub async fn run(&self, file: &Path) -> Result<Vec<AdmissionOutcome>> {
+        let samples = self.evaluate(file).await?;
+
+        let mut outcomes = Vec::new();
+        for (resource, object) in samples {
+            let (accepted, message) = self.submit(&object).await?;
+            outcomes.push(AdmissionOutcome {
+                file: file.to_path_buf(),
+                resource,
+                accepted,
+                message,
+            });
+        }
+        Ok(outcomes)
+    }
+
+    /// Evaluate a sample file into its resources, by schema name
+    pub async fn evaluate(&self, file: &Path) -> Result<BTreeMap<String, serde_yaml::Value>> {
+        let mut command = tokio::process::Command::new(&self.evaluator);
+        for jpath in &self.jpaths {
+            command.arg("-J").arg(jpath);
+        }
+
+        let output = command.arg(file).output().await.map_err(|e| {
+            anyhow!(
+                "Failed to run Jsonnet evaluator '{}': {}",
+                self.evaluator,
+                e
+            )
+        })?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to evaluate {}: {}",
+                file.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        // JSON is valid YAML
+        serde_yaml::from_slice(&output.stdout)
+            .map_err(|e| anyhow!("{} did not evaluate to an object: {}", file.display(), e))
+    }
+
+    /// Apply a resource with server-side dry-run, returning whether it was accepted
+    async fn submit(&self, object: &serde_yaml::Value) -> Result<(bool, String)> {
+        let mut command = tokio::process::Command::new(&self.kubectl);
+        command.args(["apply", "--dry-run=server", "-o", "name", "-f", "-"]);
+        if let Some(context) = &self.context {
+            command.arg("--context").arg(context);
+        }
+        if let Some(namespace) = &self.namespace {
+            command.arg("--namespace").arg(namespace);
+        }
+
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| anyhow!("Failed to run '{}': {}", self.kubectl, e))?;
+
+        // A kubectl that exits without reading the object reports through its status
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin
+                .write_all(serde_yaml::to_string(object)?.as_bytes())
+                .await;
+        }
+
+        let output = child.wait_with_output().await?;
+        let message = if output.status.success() {
+            String::from_utf8_lossy(&output.stdout)
+        } else {
+            String::from_utf8_lossy(&output.stderr)
+        };
+        Ok((output.status.success(), message.trim().to_string()))
+    }
+}
+
+impl Default for ClusterDryRun {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Find the sample files below a directory
+pub fn find_samples(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.file_name() == SAMPLES_FILE)
+        .map(|e| e.into_path())
+        .collect();
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_cluster_dry_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("widgets");
+        std::fs::create_dir_all(&dir).unwrap();
+        // JSON is valid Jsonnet, so `cat` can stand in for the evaluator
+        std::fs::write(
+            dir.join(SAMPLES_FILE),
+            r#"{"Gadget": {"kind": "Gadget"}, "Widget": {"kind": "Widget"}}"#,
+        )
+        .unwrap();
+
+        let files = find_samples(temp_dir.path());
+        assert_eq!(files.len(), 1);
+
+        let accepting = ClusterDryRun::new()
+            .with_evaluator("cat")
+            .with_kubectl("true");
+        let outcomes = accepting.run(&files[0]).await.unwrap();
+        let resources: Vec<_> = outcomes.iter().map(|o| o.resource.as_str()).collect();
+        assert_eq!(resources, vec!["Gadget", "Widget"]);
+        assert!(outcomes.iter().all(|o| o.accepted));
+
+        let rejecting = ClusterDryRun::new()
+            .with_evaluator("cat")
+            .with_kubectl("false");
+        let outcomes = rejecting.run(&files[0]).await.unwrap();
+        assert!(outcomes.iter().all(|o| !o.accepted));
+
+        let failing = ClusterDryRun::new().with_evaluator("false");
+        assert!(failing.run(&files[0]).await.is_err());
+    }
+}
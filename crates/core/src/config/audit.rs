@@ -0,0 +1,50 @@
+//! Audit manifest configuration
+
+use super::Secret;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Audit manifest of the files written by generation runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditConfig {
+    /// Whether generation runs are recorded
+    pub enabled: bool,
+
+    /// JSONL manifest the records are appended to
+    pub path: PathBuf,
+
+    /// Key signing the records with HMAC-SHA256; records are only chained
+    /// with plain SHA-256 when unset, which anyone can recompute
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<Secret>,
+
+    /// Operator recorded for each run; `GENSONNET_OPERATOR` or the current
+    /// user when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operator: Option<String>,
+}
+
+impl AuditConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.path.as_os_str().is_empty() {
+            return Err(anyhow!("Audit manifest path cannot be empty"));
+        }
+        if let Some(signing_key) = &self.signing_key {
+            signing_key.validate()?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: PathBuf::from(".gensonnet/audit.jsonl"),
+            signing_key: None,
+            operator: None,
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Platform bundle configuration
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// Aggregation of every source's library into one bundle file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BundleConfig {
+    /// Whether to write the bundle after generation
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Bundle file path (defaults to `<output.base_path>/platform.libsonnet`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+
+    /// Bundle keys by source name, overriding the default derived from the name
+    #[serde(default)]
+    pub keys: HashMap<String, String>,
+}
+
+impl BundleConfig {
+    pub fn validate(&self) -> Result<()> {
+        let mut seen = HashSet::new();
+        for key in self.keys.values() {
+            if key.is_empty() {
+                return Err(anyhow!("Bundle keys cannot be empty"));
+            }
+            if !seen.insert(key) {
+                return Err(anyhow!("Bundle key '{}' is used by several sources", key));
+            }
+        }
+        Ok(())
+    }
+
+    /// Key a source's library is exposed under
+    pub fn key(&self, source_name: &str) -> String {
+        self.keys
+            .get(source_name)
+            .cloned()
+            .unwrap_or_else(|| jsonnet_generator::bundle::default_key(source_name))
+    }
+}
@@ -0,0 +1,125 @@
+//! Compatibility shim configuration
+
+use anyhow::{anyhow, Result};
+use chrono::{Duration, NaiveDate};
+use jsonnet_generator::compat::Rename;
+use serde::{Deserialize, Serialize};
+
+/// Upstream renames to generate deprecation shims for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompatibilityConfig {
+    /// Days a shim is generated for after the rename's `since` date
+    #[serde(default = "default_window_days")]
+    pub window_days: u32,
+
+    /// Date the windows are measured on instead of today
+    ///
+    /// Required by hermetic runs when a rename has a `since` date.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<NaiveDate>,
+
+    /// Kinds and fields renamed upstream
+    #[serde(default)]
+    pub renames: Vec<RenameConfig>,
+}
+
+/// A kind or field renamed upstream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RenameConfig {
+    /// A renamed kind
+    Kind {
+        from: String,
+        to: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        since: Option<NaiveDate>,
+    },
+
+    /// A renamed spec field of a kind
+    Field {
+        kind: String,
+        from: String,
+        to: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        since: Option<NaiveDate>,
+    },
+}
+
+fn default_window_days() -> u32 {
+    90
+}
+
+impl Default for CompatibilityConfig {
+    fn default() -> Self {
+        Self {
+            window_days: default_window_days(),
+            as_of: None,
+            renames: Vec::new(),
+        }
+    }
+}
+
+impl CompatibilityConfig {
+    pub fn validate(&self) -> Result<()> {
+        for rename in &self.renames {
+            let (from, to) = match rename {
+                RenameConfig::Kind { from, to, .. } => (from, to),
+                RenameConfig::Field { kind, from, to, .. } => {
+                    if kind.is_empty() {
+                        return Err(anyhow!("Field rename {} -> {} needs a kind", from, to));
+                    }
+                    (from, to)
+                }
+            };
+            if from.is_empty() || to.is_empty() {
+                return Err(anyhow!("Rename names cannot be empty"));
+            }
+            if from == to {
+                return Err(anyhow!("Rename of {} must change the name", from));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the renames shimmed depend on the date of a run
+    pub fn is_windowed(&self) -> bool {
+        self.as_of.is_none()
+            && self.renames.iter().any(|rename| match rename {
+                RenameConfig::Kind { since, .. } | RenameConfig::Field { since, .. } => {
+                    since.is_some()
+                }
+            })
+    }
+
+    /// Renames whose compatibility window is still open on `as_of`, or `today`
+    /// when it is not set
+    pub fn renames_on(&self, today: NaiveDate) -> Vec<Rename> {
+        self.active_renames(self.as_of.unwrap_or(today))
+    }
+
+    /// Renames whose compatibility window is still open on `today`
+    pub fn active_renames(&self, today: NaiveDate) -> Vec<Rename> {
+        let window = Duration::days(i64::from(self.window_days));
+
+        self.renames
+            .iter()
+            .filter(|rename| {
+                let since = match rename {
+                    RenameConfig::Kind { since, .. } | RenameConfig::Field { since, .. } => since,
+                };
+                since.is_none_or(|since| today <= since + window)
+            })
+            .map(|rename| match rename {
+                RenameConfig::Kind { from, to, .. } => Rename::Kind {
+                    from: from.clone(),
+                    to: to.clone(),
+                },
+                RenameConfig::Field { kind, from, to, .. } => Rename::Field {
+                    kind: kind.clone(),
+                    from: from.clone(),
+                    to: to.clone(),
+                },
+            })
+            .collect()
+    }
+}
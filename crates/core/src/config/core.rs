@@ -0,0 +1,390 @@
+//! Main configuration structure and implementation
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::{
+    AuditConfig, BundleConfig, CompatibilityConfig, GenerationConfig, GitConfig, HooksConfig,
+    LicenseConfig, MetricsConfig, NotificationsConfig, PluginConfig, PolicyConfig, Source,
+    ValidationConfig, WarningsConfig,
+};
+use jsonnet_generator::config::OutputConfig;
+
+/// Main configuration structure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// Configuration version
+    pub version: String,
+
+    /// List of sources to process
+    #[serde(default)]
+    pub sources: Vec<Source>,
+
+    /// Output configuration
+    pub output: OutputConfig,
+
+    /// Generation settings
+    pub generation: GenerationConfig,
+
+    /// Plugin configuration
+    pub plugins: PluginConfig,
+
+    /// Metrics endpoint configuration
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+
+    /// Git operation settings
+    #[serde(default)]
+    pub git: GitConfig,
+
+    /// Policy rules evaluated over extracted schemas
+    #[serde(default)]
+    pub policies: PolicyConfig,
+
+    /// Deprecation shims for kinds and fields renamed upstream
+    #[serde(default)]
+    pub compatibility: CompatibilityConfig,
+
+    /// Platform bundle aggregating every source's library
+    #[serde(default)]
+    pub bundle: BundleConfig,
+
+    /// Licenses sources may be generated from
+    #[serde(default)]
+    pub licenses: LicenseConfig,
+
+    /// Audit manifest of the files written by generation runs
+    #[serde(default)]
+    pub audit: AuditConfig,
+
+    /// Commands run before and after every generation run
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// Webhooks notified when a generation run finishes
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+
+    /// Generated validation settings
+    #[serde(default)]
+    pub validation: ValidationConfig,
+
+    /// Warnings suppressed, and warning categories failing the run
+    #[serde(default)]
+    pub warnings: WarningsConfig,
+}
+
+impl Config {
+    /// Directory next to the main config whose `*.yaml` files contribute sources
+    pub const FRAGMENTS_DIR: &'static str = ".gensonnet.d";
+
+    /// Default configuration file names, in order of precedence
+    pub const FILE_NAMES: &'static [&'static str] = &[
+        ".gensonnet.yaml",
+        ".gensonnet.yml",
+        "gensonnet.yaml",
+        "gensonnet.yml",
+    ];
+
+    /// Find the configuration file of every workspace below `root`
+    ///
+    /// Each directory contributes at most one file, picked by `FILE_NAMES`
+    /// precedence. Hidden directories, `target` and `node_modules` are skipped.
+    pub fn discover(root: &Path) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = walkdir::WalkDir::new(root)
+            .into_iter()
+            .filter_entry(|entry| {
+                let name = entry.file_name().to_string_lossy();
+                entry.depth() == 0
+                    || !entry.file_type().is_dir()
+                    || !(name.starts_with('.') || name == "target" || name == "node_modules")
+            })
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_dir())
+            .filter_map(|dir| {
+                Self::FILE_NAMES
+                    .iter()
+                    .map(|name| dir.path().join(name))
+                    .find(|path| path.is_file())
+            })
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    /// Load configuration from a YAML file
+    pub fn from_file(path: &PathBuf) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let mut config: Config = serde_yaml::from_str(&content)?;
+
+        let fragments_dir = path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join(Self::FRAGMENTS_DIR);
+        if fragments_dir.is_dir() {
+            config.merge_fragments(&fragments_dir)?;
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Resolve the relative paths of the configuration against `dir`, so it
+    /// reads and writes the same files as when run from there
+    ///
+    /// Covers output paths, mirrors and the bundle, Jsonnet library sources,
+    /// the audit manifest and plugin directories. Paths starting with `~` or
+    /// an environment variable are expanded later and left as they are.
+    pub fn rebase_paths(&mut self, dir: &Path) {
+        let rebase = |path: &mut PathBuf| {
+            if path.is_relative()
+                && !path.starts_with("~")
+                && !path.to_string_lossy().starts_with('$')
+            {
+                *path = dir.join(&*path);
+            }
+        };
+
+        rebase(&mut self.output.base_path);
+        self.output.mirrors.iter_mut().for_each(rebase);
+        if let Some(path) = &mut self.bundle.path {
+            rebase(path);
+        }
+        for source in &mut self.sources {
+            rebase(source.output_path_mut());
+            if let Source::Jsonnet(jsonnet) = source {
+                rebase(&mut jsonnet.path);
+                jsonnet.jpaths.iter_mut().for_each(rebase);
+            }
+        }
+        if let Some(verify) = &mut self.generation.verify_examples {
+            verify.jpaths.iter_mut().for_each(rebase);
+        }
+        rebase(&mut self.audit.path);
+        self.plugins.plugin_directories.iter_mut().for_each(rebase);
+        rebase(&mut self.plugins.cache_directory);
+    }
+
+    /// The `*.yaml` fragments in `dir`, in file name order
+    pub fn fragment_paths(dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut fragment_paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.is_file()
+                    && matches!(
+                        path.extension().and_then(|ext| ext.to_str()),
+                        Some("yaml" | "yml")
+                    )
+            })
+            .collect();
+        fragment_paths.sort();
+        Ok(fragment_paths)
+    }
+
+    /// Merge the sources of every `*.yaml` fragment in `dir`, in file name order
+    pub fn merge_fragments(&mut self, dir: &Path) -> Result<()> {
+        let fragment_paths = Self::fragment_paths(dir)?;
+
+        let mut origins: HashMap<String, String> = self
+            .sources
+            .iter()
+            .map(|source| (source.name().to_string(), "main config".to_string()))
+            .collect();
+
+        for fragment_path in fragment_paths {
+            let content = std::fs::read_to_string(&fragment_path)?;
+            let fragment: ConfigFragment = serde_yaml::from_str(&content)
+                .map_err(|e| anyhow!("Invalid config fragment {:?}: {}", fragment_path, e))?;
+            let origin = fragment_path.display().to_string();
+
+            for source in fragment.sources {
+                if let Some(existing) = origins.get(source.name()) {
+                    return Err(anyhow!(
+                        "Duplicate source '{}' in {} (already defined in {})",
+                        source.name(),
+                        origin,
+                        existing
+                    ));
+                }
+                origins.insert(source.name().to_string(), origin.clone());
+                self.sources.push(source);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Keep only the sources carrying any of the given tags
+    ///
+    /// Every tag must be carried by at least one source, so a mistyped tag
+    /// fails instead of silently selecting nothing.
+    pub fn select_tags(&mut self, tags: &[String]) -> Result<()> {
+        let unknown: Vec<&str> = tags
+            .iter()
+            .filter(|tag| {
+                !self
+                    .sources
+                    .iter()
+                    .any(|source| source.tags().contains(tag))
+            })
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            return Err(anyhow!("No source is tagged {}", unknown.join(", ")));
+        }
+
+        self.sources.retain(|source| source.has_any_tag(tags));
+        Ok(())
+    }
+
+    /// Groups of sources whose output paths resolve to the same directory
+    ///
+    /// Their libraries would overwrite each other's files, e.g. when both
+    /// define a `Certificate` kind.
+    pub fn output_collisions(&self) -> Vec<OutputCollision> {
+        let mut groups: Vec<(PathBuf, OutputCollision)> = Vec::new();
+        for source in &self.sources {
+            let path = source.output_path();
+            let resolved = crate::utils::resolve_path(path).unwrap_or_else(|_| path.to_path_buf());
+            match groups
+                .iter_mut()
+                .find(|(existing, _)| *existing == resolved)
+            {
+                Some((_, collision)) => collision.sources.push(source.name().to_string()),
+                None => groups.push((
+                    resolved,
+                    OutputCollision {
+                        path: path.to_path_buf(),
+                        sources: vec![source.name().to_string()],
+                    },
+                )),
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(_, collision)| collision)
+            .filter(|collision| collision.sources.len() > 1)
+            .collect()
+    }
+
+    /// Move every source of an output collision to a subdirectory of its
+    /// output path named after the source, returning the collisions resolved
+    pub fn namespace_output_collisions(&mut self) -> Vec<OutputCollision> {
+        let collisions = self.output_collisions();
+        for source in &mut self.sources {
+            if collisions
+                .iter()
+                .any(|collision| collision.sources.iter().any(|name| name == source.name()))
+            {
+                let namespaced = source.output_path().join(source.name());
+                *source.output_path_mut() = namespaced;
+            }
+        }
+        collisions
+    }
+
+    /// Save configuration to a YAML file
+    pub fn save_to_file(&self, path: &PathBuf) -> Result<()> {
+        let content = serde_yaml::to_string(self)?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Validate the configuration
+    pub fn validate(&self) -> Result<()> {
+        if self.version != "1.0" {
+            return Err(anyhow!(
+                "Unsupported configuration version: {}",
+                self.version
+            ));
+        }
+
+        if self.sources.is_empty() {
+            return Err(anyhow!("At least one source must be configured"));
+        }
+
+        // Validate each source
+        for source in &self.sources {
+            source.validate()?;
+        }
+
+        // Validate output configuration
+        self.output.validate()?;
+
+        // Validate metrics configuration
+        self.metrics.validate()?;
+
+        // Validate git configuration
+        self.git.validate()?;
+
+        // Validate policy rules
+        self.policies.validate()?;
+
+        // Validate compatibility renames
+        self.compatibility.validate()?;
+
+        // Validate bundle keys
+        self.bundle.validate()?;
+
+        // Validate license identifiers
+        self.licenses.validate()?;
+
+        // Validate audit settings
+        self.audit.validate()?;
+
+        // Validate global hooks
+        self.hooks.validate()?;
+
+        // Validate notification webhooks
+        self.notifications.validate()?;
+
+        // Validate warning suppressions
+        self.warnings.validate()?;
+
+        Ok(())
+    }
+}
+
+/// Sources configured with output paths resolving to the same directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputCollision {
+    /// The shared output path, as configured for the first of the sources
+    pub path: PathBuf,
+
+    /// Names of the sources, in configuration order
+    pub sources: Vec<String>,
+}
+
+/// A config fragment contributing additional sources
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFragment {
+    /// Sources defined by this fragment
+    #[serde(default)]
+    sources: Vec<Source>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: "1.0".to_string(),
+            sources: Vec::new(),
+            output: OutputConfig::default(),
+            generation: GenerationConfig::default(),
+            plugins: PluginConfig::default(),
+            metrics: MetricsConfig::default(),
+            git: GitConfig::default(),
+            policies: PolicyConfig::default(),
+            compatibility: CompatibilityConfig::default(),
+            bundle: BundleConfig::default(),
+            licenses: LicenseConfig::default(),
+            audit: AuditConfig::default(),
+            hooks: HooksConfig::default(),
+            notifications: NotificationsConfig::default(),
+            validation: ValidationConfig::default(),
+            warnings: WarningsConfig::default(),
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Generation configuration and merge strategies
+
+use super::PolicyAction;
+use crate::utils::format_bytes;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Generation configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationConfig {
+    /// Whether to fail fast on errors
+    pub fail_fast: bool,
+
+    /// Deep merge strategy
+    pub deep_merge_strategy: MergeStrategy,
+
+    /// Whether to fail instead of emitting stubs for unresolved schema types
+    #[serde(default)]
+    pub fail_on_unresolved: bool,
+
+    /// Whether to keep schema files whose schema digest is unchanged since the last run
+    #[serde(default = "default_reuse_unchanged_schemas")]
+    pub reuse_unchanged_schemas: bool,
+
+    /// Maximum total size in bytes of the library generated for one source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_bytes: Option<u64>,
+
+    /// Maximum size in bytes of a single generated file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_bytes: Option<u64>,
+
+    /// Whether a library over budget is published with a warning or fails its source
+    #[serde(default)]
+    pub on_budget_exceeded: PolicyAction,
+
+    /// Whether sources configured with the same output path fail generation or
+    /// are each moved to a subdirectory named after them
+    #[serde(default)]
+    pub on_output_collision: OutputCollisionPolicy,
+
+    /// Whether schema constructs that cannot be represented fail the source
+    /// instead of falling back to plain objects
+    #[serde(default)]
+    pub strict_schemas: bool,
+
+    /// Whether invalid CRD manifests fail the source instead of being recovered
+    /// with a warning, or skipped when nothing can be recovered
+    #[serde(default)]
+    pub strict_crds: bool,
+
+    /// Limits applied when parsing source code into syntax trees
+    #[serde(default)]
+    pub parse_limits: ParseLimits,
+
+    /// Limits on what a single run may use
+    #[serde(default)]
+    pub resource_limits: ResourceLimits,
+
+    /// Number of runs in a row a source may fail before it is quarantined
+    ///
+    /// Quarantined sources are skipped until a run with `--retry-quarantined`
+    /// generates them again. While set, a source whose upstream cannot be
+    /// reached fails on its own instead of failing the run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quarantine_after: Option<u32>,
+
+    /// Evaluate the generated usage examples once each source is published
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify_examples: Option<VerifyExamples>,
+}
+
+fn default_reuse_unchanged_schemas() -> bool {
+    true
+}
+
+impl GenerationConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_output_bytes == Some(0) || self.max_file_bytes == Some(0) {
+            return Err(anyhow!("Output size budgets must be greater than zero"));
+        }
+        if self.parse_limits.max_file_bytes == 0 || self.parse_limits.parse_timeout_ms == 0 {
+            return Err(anyhow!("Parse limits must be greater than zero"));
+        }
+        if self.resource_limits.max_open_repositories == Some(0)
+            || self.resource_limits.max_schema_bytes == Some(0)
+            || self.resource_limits.max_generated_files == Some(0)
+        {
+            return Err(anyhow!("Resource limits must be greater than zero"));
+        }
+        if self.quarantine_after == Some(0) {
+            return Err(anyhow!(
+                "generation.quarantine_after must be greater than zero"
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Describe how a source's library, given as file sizes, exceeds the output budgets
+    pub fn check_output_budget(&self, source: &str, sizes: &BTreeMap<PathBuf, u64>) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        if let Some(max) = self.max_output_bytes {
+            let total: u64 = sizes.values().sum();
+            if total > max {
+                let mut largest: Vec<(&PathBuf, &u64)> = sizes.iter().collect();
+                largest.sort_by(|a, b| b.1.cmp(a.1));
+                let largest: Vec<String> = largest
+                    .iter()
+                    .take(3)
+                    .map(|(path, size)| format!("{} ({})", path.display(), format_bytes(**size)))
+                    .collect();
+                violations.push(format!(
+                    "Source {} generated {} in {} files, over generation.max_output_bytes ({}); largest: {}. Add filters to narrow the source",
+                    source,
+                    format_bytes(total),
+                    sizes.len(),
+                    format_bytes(max),
+                    largest.join(", ")
+                ));
+            }
+        }
+
+        if let Some(max) = self.max_file_bytes {
+            for (path, size) in sizes {
+                if *size > max {
+                    violations.push(format!(
+                        "Source {} generated {} of {}, over generation.max_file_bytes ({}). Add filters to narrow the source",
+                        source,
+                        path.display(),
+                        format_bytes(*size),
+                        format_bytes(max)
+                    ));
+                }
+            }
+        }
+
+        violations
+    }
+}
+
+impl Default for GenerationConfig {
+    fn default() -> Self {
+        Self {
+            fail_fast: false,
+            deep_merge_strategy: MergeStrategy::Default,
+            fail_on_unresolved: false,
+            reuse_unchanged_schemas: default_reuse_unchanged_schemas(),
+            max_output_bytes: None,
+            max_file_bytes: None,
+            on_budget_exceeded: PolicyAction::default(),
+            on_output_collision: OutputCollisionPolicy::default(),
+            strict_schemas: false,
+            strict_crds: false,
+            parse_limits: ParseLimits::default(),
+            resource_limits: ResourceLimits::default(),
+            quarantine_after: None,
+            verify_examples: None,
+        }
+    }
+}
+
+/// Handling of sources configured with the same output path
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputCollisionPolicy {
+    /// Refuse to generate until the output paths are made distinct
+    #[default]
+    Error,
+
+    /// Write each colliding source to `<output_path>/<source name>`
+    Namespace,
+}
+
+/// Evaluation of the generated usage examples
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VerifyExamples {
+    /// Jsonnet evaluator binary (e.g. `jsonnet` or `jrsonnet`)
+    pub evaluator: String,
+
+    /// Library search paths besides the library itself, e.g. for k.libsonnet
+    pub jpaths: Vec<PathBuf>,
+}
+
+impl Default for VerifyExamples {
+    fn default() -> Self {
+        Self {
+            evaluator: "jsonnet".to_string(),
+            jpaths: Vec::new(),
+        }
+    }
+}
+
+/// Limits that turn pathological source files into warnings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParseLimits {
+    /// Files larger than this many bytes are skipped
+    pub max_file_bytes: u64,
+
+    /// Parsing a single file is abandoned after this many milliseconds
+    pub parse_timeout_ms: u64,
+}
+
+impl ParseLimits {
+    /// Read the limits handed to a parser plugin as its configuration
+    pub fn from_plugin_config(config: &serde_yaml::Value) -> Result<Self> {
+        if config.is_null() {
+            return Ok(Self::default());
+        }
+        serde_yaml::from_value(config.clone()).map_err(|e| anyhow!("Invalid parse limits: {}", e))
+    }
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: 2 * 1024 * 1024,
+            parse_timeout_ms: 5_000,
+        }
+    }
+}
+
+/// Limits on what a single run may use, protecting shared machines from
+/// runaway configurations; unset limits are not enforced
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// Maximum number of distinct git repositories the run's sources use
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_open_repositories: Option<usize>,
+
+    /// Maximum total size in bytes of the schemas parsed in the run, an
+    /// estimate of the memory they take
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_schema_bytes: Option<u64>,
+
+    /// Maximum number of files generated in the run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_generated_files: Option<usize>,
+}
+
+/// Merge strategy for deep merging
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// Default merge strategy
+    Default,
+
+    /// Replace strategy (overwrite existing values)
+    Replace,
+
+    /// Append strategy (add to existing arrays)
+    Append,
+}
@@ -0,0 +1,158 @@
+//! Git and network operation configuration
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Global Git configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitConfig {
+    /// Retry policy for clone/fetch operations
+    #[serde(default)]
+    pub retry: RetryPolicy,
+
+    /// Maximum clones and fetches running at once against the same host
+    #[serde(default = "default_max_concurrent_per_host")]
+    pub max_concurrent_per_host: usize,
+
+    /// Seconds the refs listed by `ls-remote` are reused by status-style
+    /// commands; 0 lists them on every call
+    #[serde(default = "default_ls_remote_cache_seconds")]
+    pub ls_remote_cache_seconds: u64,
+}
+
+fn default_max_concurrent_per_host() -> usize {
+    4
+}
+
+fn default_ls_remote_cache_seconds() -> u64 {
+    60
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            retry: RetryPolicy::default(),
+            max_concurrent_per_host: default_max_concurrent_per_host(),
+            ls_remote_cache_seconds: default_ls_remote_cache_seconds(),
+        }
+    }
+}
+
+impl GitConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_concurrent_per_host == 0 {
+            return Err(anyhow!(
+                "Git max_concurrent_per_host must be greater than 0"
+            ));
+        }
+        self.retry.validate()
+    }
+}
+
+/// Retry policy with exponential backoff and jitter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first attempt
+    pub max_retries: u32,
+
+    /// Delay before the first retry in milliseconds
+    pub initial_backoff_ms: u64,
+
+    /// Upper bound for the delay between retries in milliseconds
+    pub max_backoff_ms: u64,
+
+    /// Multiplier applied to the delay after each retry
+    pub multiplier: f64,
+
+    /// Whether to randomize delays to avoid synchronized retries
+    pub jitter: bool,
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.multiplier < 1.0 {
+            return Err(anyhow!("Retry multiplier must be at least 1.0"));
+        }
+        if self.initial_backoff_ms > self.max_backoff_ms {
+            return Err(anyhow!(
+                "Retry initial_backoff_ms cannot exceed max_backoff_ms"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Delay to wait before the given retry (1-based)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(32) as i32;
+        let delay = (self.initial_backoff_ms as f64 * self.multiplier.powi(exponent))
+            .min(self.max_backoff_ms as f64) as u64;
+
+        if self.jitter && delay > 0 {
+            // Keep at least half of the delay and randomize the rest
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.subsec_nanos() as u64)
+                .unwrap_or(0);
+            let half = delay / 2;
+            Duration::from_millis(half + nanos % (delay - half + 1))
+        } else {
+            Duration::from_millis(delay)
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+            multiplier: 2.0,
+            jitter: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 1000,
+            multiplier: 2.0,
+            jitter: false,
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(5), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_backoff_jitter_bounds() {
+        let policy = RetryPolicy {
+            initial_backoff_ms: 1000,
+            jitter: true,
+            ..RetryPolicy::default()
+        };
+
+        let delay = policy.backoff(1);
+        assert!(delay >= Duration::from_millis(500));
+        assert!(delay <= Duration::from_millis(1000));
+    }
+}
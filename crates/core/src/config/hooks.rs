@@ -0,0 +1,50 @@
+//! Global generation hook configuration
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// Commands run before and after a generation run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Shell commands run before any source is processed; they receive the
+    /// plan on stdin and abort generation by exiting non-zero
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub before_all: Vec<String>,
+
+    /// Shell commands run once every source is processed, before the
+    /// lockfile is updated; they receive the result on stdin and abort the
+    /// run by exiting non-zero
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub after_all: Vec<String>,
+
+    /// Seconds a hook may run before it is killed and the run aborted
+    pub timeout_seconds: u64,
+}
+
+impl HooksConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self
+            .before_all
+            .iter()
+            .chain(&self.after_all)
+            .any(|command| command.trim().is_empty())
+        {
+            return Err(anyhow!("Hook commands cannot be empty"));
+        }
+        if self.timeout_seconds == 0 {
+            return Err(anyhow!("Hook timeout_seconds must be greater than 0"));
+        }
+        Ok(())
+    }
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            before_all: Vec::new(),
+            after_all: Vec::new(),
+            timeout_seconds: 300,
+        }
+    }
+}
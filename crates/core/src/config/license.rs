@@ -0,0 +1,51 @@
+//! License compliance configuration
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Licenses sources may be generated from
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LicenseConfig {
+    /// SPDX identifiers sources must be licensed under; empty allows any license
+    #[serde(default)]
+    pub allowed: Vec<String>,
+
+    /// SPDX identifiers by source name, for sources whose license is not detected
+    #[serde(default)]
+    pub overrides: HashMap<String, String>,
+}
+
+impl LicenseConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self
+            .allowed
+            .iter()
+            .chain(self.overrides.values())
+            .any(|id| id.trim().is_empty())
+        {
+            return Err(anyhow!("License identifiers cannot be empty"));
+        }
+        Ok(())
+    }
+
+    /// Fail if a source's license is not allowed
+    pub fn check(&self, source_name: &str, license: Option<&str>) -> Result<()> {
+        if self.allowed.is_empty() {
+            return Ok(());
+        }
+
+        match license {
+            Some(license) if self.allowed.iter().any(|allowed| allowed == license) => Ok(()),
+            Some(license) => Err(anyhow!(
+                "Source {} is licensed under {}, which is not in licenses.allowed",
+                source_name,
+                license
+            )),
+            None => Err(anyhow!(
+                "Source {} has no detectable license; add it to licenses.overrides",
+                source_name
+            )),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! Metrics endpoint configuration
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+/// Metrics configuration for long-running (watch) mode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    /// Whether to expose the Prometheus metrics endpoint
+    pub enabled: bool,
+
+    /// Address the metrics endpoint listens on
+    pub listen_address: String,
+}
+
+impl MetricsConfig {
+    pub fn validate(&self) -> Result<()> {
+        self.socket_addr()?;
+        Ok(())
+    }
+
+    /// Parse the listen address into a socket address
+    pub fn socket_addr(&self) -> Result<SocketAddr> {
+        self.listen_address.parse().map_err(|e| {
+            anyhow!(
+                "Invalid metrics listen address {}: {}",
+                self.listen_address,
+                e
+            )
+        })
+    }
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen_address: "127.0.0.1:9464".to_string(),
+        }
+    }
+}
@@ -0,0 +1,282 @@
+//! Migration of configuration files written for older formats
+//!
+//! `config migrate` rewrites a configuration file to the current format:
+//! sources keyed by name become a list, old spellings of source types are
+//! renamed, sections that became required are added with their defaults and
+//! the version is set to the current one. The migrated file must load as a
+//! [`Config`], so a migration never writes a configuration that fails later.
+//! Fragments in `.gensonnet.d/` only hold sources, so only those are migrated.
+
+use anyhow::{anyhow, Result};
+use serde_yaml::{Mapping, Value};
+
+use super::{Config, GenerationConfig, PluginConfig, Source};
+use jsonnet_generator::config::OutputConfig;
+
+/// Version of the current configuration format
+pub const CURRENT_VERSION: &str = "1.0";
+
+/// File names used before the project was renamed, in order of precedence
+pub const LEGACY_FILE_NAMES: &[&str] = &[".jsonnet-gen.yaml", ".jsonnet-gen.yml"];
+
+/// Old spellings of source types and their current names
+const RENAMED_SOURCE_TYPES: &[(&str, &str)] = &[
+    ("openapi", "open_api"),
+    ("open-api", "open_api"),
+    ("goast", "go_ast"),
+    ("go-ast", "go_ast"),
+];
+
+/// A configuration file migrated to the current format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigMigration {
+    /// Content of the migrated file; the original content if nothing changed
+    pub content: String,
+
+    /// What was changed, in the order applied
+    pub changes: Vec<String>,
+}
+
+impl ConfigMigration {
+    /// Whether the file already had the current format
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Migrate the content of a configuration file to the current format
+///
+/// Migrated files are re-serialized, so comments and formatting are only
+/// kept when nothing needs to change.
+pub fn migrate(content: &str) -> Result<ConfigMigration> {
+    let mut config: Value = serde_yaml::from_str(content)?;
+    let root = config
+        .as_mapping_mut()
+        .ok_or_else(|| anyhow!("Configuration is not a mapping"))?;
+
+    let mut changes = Vec::new();
+    migrate_version(root, &mut changes)?;
+    migrate_sources(root, &mut changes)?;
+    add_required_sections(root, &mut changes)?;
+
+    if changes.is_empty() {
+        return Ok(ConfigMigration {
+            content: content.to_string(),
+            changes,
+        });
+    }
+    serde_yaml::from_value::<Config>(config.clone())
+        .map_err(|e| anyhow!("Migrated configuration is still invalid: {}", e))?;
+    Ok(ConfigMigration {
+        content: serde_yaml::to_string(&config)?,
+        changes,
+    })
+}
+
+/// Migrate the content of a config fragment, which only holds sources
+pub fn migrate_fragment(content: &str) -> Result<ConfigMigration> {
+    let mut fragment: Value = serde_yaml::from_str(content)?;
+    let root = fragment
+        .as_mapping_mut()
+        .ok_or_else(|| anyhow!("Config fragment is not a mapping"))?;
+
+    let mut changes = Vec::new();
+    migrate_sources(root, &mut changes)?;
+
+    if changes.is_empty() {
+        return Ok(ConfigMigration {
+            content: content.to_string(),
+            changes,
+        });
+    }
+    if let Some(sources) = root.get("sources") {
+        serde_yaml::from_value::<Vec<Source>>(sources.clone())
+            .map_err(|e| anyhow!("Migrated fragment is still invalid: {}", e))?;
+    }
+    Ok(ConfigMigration {
+        content: serde_yaml::to_string(&fragment)?,
+        changes,
+    })
+}
+
+/// Set the version of files without one, or with an older or unquoted one
+fn migrate_version(root: &mut Mapping, changes: &mut Vec<String>) -> Result<()> {
+    let version = match root.get("version") {
+        None => None,
+        Some(Value::String(version)) => Some(version.clone()),
+        Some(Value::Number(version)) => Some(version.to_string()),
+        Some(other) => return Err(anyhow!("Invalid configuration version: {:?}", other)),
+    };
+    match version.as_deref() {
+        Some(CURRENT_VERSION) if root.get("version").is_some_and(Value::is_string) => {}
+        Some(version @ (CURRENT_VERSION | "1")) => {
+            changes.push(format!("Quoted version {version} as \"{CURRENT_VERSION}\""));
+        }
+        Some(version) if version.starts_with("0.") => {
+            changes.push(format!("Upgraded version {version} to {CURRENT_VERSION}"));
+        }
+        Some(version) => {
+            return Err(anyhow!(
+                "Configuration version {} is not supported by this version of gensonnet",
+                version
+            ))
+        }
+        None => changes.push(format!("Set the version to {CURRENT_VERSION}")),
+    }
+    root.insert("version".into(), CURRENT_VERSION.into());
+    Ok(())
+}
+
+/// Turn sources keyed by name into a list and rename old source types
+fn migrate_sources(root: &mut Mapping, changes: &mut Vec<String>) -> Result<()> {
+    let Some(sources) = root.get_mut("sources") else {
+        return Ok(());
+    };
+
+    if let Value::Mapping(by_name) = sources {
+        let mut list = Vec::with_capacity(by_name.len());
+        for (name, source) in std::mem::take(by_name) {
+            let Value::Mapping(mut source) = source else {
+                return Err(anyhow!("Source {:?} is not a mapping", name));
+            };
+            if !source.contains_key("name") {
+                source.insert("name".into(), name);
+            }
+            list.push(Value::Mapping(source));
+        }
+        *sources = Value::Sequence(list);
+        changes.push("Turned the sources keyed by name into a list".to_string());
+    }
+
+    for source in sources.as_sequence_mut().into_iter().flatten() {
+        let Some(source_type) = source.get_mut("type") else {
+            continue;
+        };
+        let renamed = RENAMED_SOURCE_TYPES
+            .iter()
+            .find(|(old, _)| source_type.as_str() == Some(old));
+        if let Some((old, new)) = renamed {
+            *source_type = (*new).into();
+            let name = source.get("name").and_then(Value::as_str).unwrap_or("?");
+            changes.push(format!("Renamed type {old} of source {name} to {new}"));
+        }
+    }
+    Ok(())
+}
+
+/// Add the sections that became required, with their defaults
+fn add_required_sections(root: &mut Mapping, changes: &mut Vec<String>) -> Result<()> {
+    let sections = [
+        ("output", serde_yaml::to_value(OutputConfig::default())?),
+        (
+            "generation",
+            serde_yaml::to_value(GenerationConfig::default())?,
+        ),
+        ("plugins", serde_yaml::to_value(PluginConfig::default())?),
+    ];
+    for (section, default) in sections {
+        if !root.contains_key(section) {
+            root.insert(section.into(), default);
+            changes.push(format!("Added the {section} section with its defaults"));
+        }
+    }
+    Ok(())
+}
+
+/// Line diff from `old` to `new`, prefixing removed lines with `-`, added
+/// lines with `+` and unchanged ones with a space
+pub fn diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Length of the longest common subsequence of the suffixes
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(format!(" {}", old[i]));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] > common[i + 1][j]) {
+            lines.push(format!("+{}", new[j]));
+            j += 1;
+        } else {
+            lines.push(format!("-{}", old[i]));
+            i += 1;
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate() {
+        let legacy = r#"
+version: 1.0
+sources:
+  user-api:
+    type: openapi
+    git:
+      url: https://github.com/example/user-api.git
+      ref: main
+    include_patterns: ["**/*.yaml"]
+    exclude_patterns: []
+    output_path: ./generated/openapi
+output:
+  base_path: ./generated
+  organization: flat
+generation:
+  fail_fast: false
+  deep_merge_strategy: default
+"#;
+        let migration = migrate(legacy).unwrap();
+        assert_eq!(
+            migration.changes,
+            [
+                "Quoted version 1.0 as \"1.0\"",
+                "Turned the sources keyed by name into a list",
+                "Renamed type openapi of source user-api to open_api",
+                "Added the plugins section with its defaults",
+            ]
+        );
+        let config: Config = serde_yaml::from_str(&migration.content).unwrap();
+        assert_eq!(config.version, CURRENT_VERSION);
+        assert_eq!(config.sources[0].name(), "user-api");
+
+        // Migrating again changes nothing
+        let again = migrate(&migration.content).unwrap();
+        assert!(again.is_empty());
+        assert_eq!(again.content, migration.content);
+
+        assert!(migrate("version: \"2.0\"\n").is_err());
+
+        let fragment = migrate_fragment(
+            "sources:\n  billing:\n    type: goast\n    git:\n      url: https://github.com/example/billing.git\n      ref: main\n    include_patterns: [\"**/*.go\"]\n    exclude_patterns: []\n    output_path: ./generated/billing\n",
+        )
+        .unwrap();
+        assert_eq!(
+            fragment.changes,
+            [
+                "Turned the sources keyed by name into a list",
+                "Renamed type goast of source billing to go_ast",
+            ]
+        );
+        assert!(!fragment.content.contains("version"));
+        assert!(migrate_fragment(&fragment.content).unwrap().is_empty());
+        assert_eq!(diff("a\nb\nc\n", "a\nc\nd\n"), " a\n-b\n c\n+d\n");
+    }
+}
@@ -0,0 +1,44 @@
+//! Configuration management for JsonnetGen
+
+pub mod audit;
+pub mod bundle;
+pub mod compatibility;
+pub mod core;
+pub mod generation;
+pub mod git;
+pub mod hooks;
+pub mod license;
+pub mod metrics;
+pub mod migrate;
+pub mod notifications;
+pub mod plugins;
+pub mod policy;
+pub mod secret;
+pub mod source;
+pub mod validation;
+pub mod warnings;
+
+#[cfg(test)]
+mod tests;
+
+// Re-export main types for convenience
+pub use audit::AuditConfig;
+pub use bundle::BundleConfig;
+pub use compatibility::{CompatibilityConfig, RenameConfig};
+pub use core::{Config, OutputCollision};
+pub use generation::{
+    GenerationConfig, MergeStrategy, OutputCollisionPolicy, ParseLimits, ResourceLimits,
+    VerifyExamples,
+};
+pub use git::{GitConfig, RetryPolicy};
+pub use hooks::HooksConfig;
+pub use license::LicenseConfig;
+pub use metrics::MetricsConfig;
+pub use migrate::ConfigMigration;
+pub use notifications::{NotificationEvent, NotificationsConfig, WebhookConfig};
+pub use plugins::{PluginConfig, PluginOutputConfig, PluginValidationConfig};
+pub use policy::{PolicyAction, PolicyConfig, PolicyRule, PolicyScope};
+pub use secret::{Secret, SecretRef};
+pub use source::*;
+pub use validation::ValidationConfig;
+pub use warnings::{WarningSuppression, WarningsConfig};
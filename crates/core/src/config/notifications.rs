@@ -0,0 +1,110 @@
+//! Notification configuration
+
+use super::Secret;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Webhooks notified when a generation run finishes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Webhooks the summary of each run is posted to
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
+
+    /// Seconds to wait for a webhook to respond
+    pub timeout_seconds: u64,
+}
+
+impl NotificationsConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.timeout_seconds == 0 {
+            return Err(anyhow!(
+                "Notification timeout_seconds must be greater than 0"
+            ));
+        }
+        for webhook in &self.webhooks {
+            webhook.validate()?;
+        }
+        if !self.webhooks.is_empty() && !cfg!(feature = "http") {
+            return Err(anyhow!(
+                "Notification webhooks require the `http` feature, which this build was compiled without"
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            webhooks: Vec::new(),
+            timeout_seconds: 10,
+        }
+    }
+}
+
+/// A webhook notified of finished runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// URL the payload is posted to; chat webhook URLs carry their token, so
+    /// it can be referenced with `secretRef`
+    pub url: Secret,
+
+    /// Outcomes of a run that are notified
+    #[serde(default = "default_events")]
+    pub on: Vec<NotificationEvent>,
+
+    /// JSON payload with `{placeholder}`s for the fields of the run summary;
+    /// the summary itself when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<String>,
+
+    /// HTTP headers sent with the payload, e.g. `Authorization`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, Secret>,
+}
+
+fn default_events() -> Vec<NotificationEvent> {
+    vec![NotificationEvent::Success, NotificationEvent::Failure]
+}
+
+impl WebhookConfig {
+    pub fn validate(&self) -> Result<()> {
+        self.url.validate()?;
+        for value in self.headers.values() {
+            value.validate()?;
+        }
+        if self.on.is_empty() {
+            return Err(anyhow!(
+                "Webhook `on` must list at least one of success and failure"
+            ));
+        }
+        if let Some(payload) = &self.payload {
+            crate::notify::render_payload(payload, &crate::notify::RunSummary::example())
+                .map_err(|e| anyhow!("Invalid webhook payload: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    /// Every source generated without errors
+    Success,
+
+    /// The run failed, or a source failed in it
+    Failure,
+}
+
+impl NotificationEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationEvent::Success => "success",
+            NotificationEvent::Failure => "failure",
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //! Plugin configuration and validation
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// Plugin configuration
@@ -18,8 +19,28 @@ pub struct PluginConfig {
     /// Plugin cache directory
     pub cache_directory: PathBuf,
 
+    /// Whether plugins may keep state across runs in `<cache_directory>/state`
+    #[serde(default = "default_persistent_state")]
+    pub persistent_state: bool,
+
     /// Plugin validation settings
     pub validation: PluginValidationConfig,
+
+    /// Priorities for choosing between plugins that claim the same source (default 0)
+    #[serde(default)]
+    pub priorities: HashMap<String, i32>,
+
+    /// Sources each plugin may process at once (default 4)
+    #[serde(default)]
+    pub concurrency: HashMap<String, usize>,
+
+    /// Checks run on plugin-produced files before they are published
+    #[serde(default)]
+    pub output_verification: PluginOutputConfig,
+}
+
+fn default_persistent_state() -> bool {
+    true
 }
 
 impl Default for PluginConfig {
@@ -32,7 +53,11 @@ impl Default for PluginConfig {
             enable_external_discovery: true,
             registry_url: None,
             cache_directory: PathBuf::from("~/.cache/gensonnet/plugins"),
+            persistent_state: default_persistent_state(),
             validation: PluginValidationConfig::default(),
+            priorities: HashMap::new(),
+            concurrency: HashMap::new(),
+            output_verification: PluginOutputConfig::default(),
         }
     }
 }
@@ -63,3 +88,29 @@ impl Default for PluginValidationConfig {
         }
     }
 }
+
+/// Verification of plugin-produced files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginOutputConfig {
+    /// Whether plugin output is verified and invalid files quarantined
+    #[serde(default = "default_output_verification")]
+    pub enabled: bool,
+
+    /// Command that must accept each generated Jsonnet file, run with the
+    /// file as its last argument, e.g. `jsonnetfmt`
+    #[serde(default)]
+    pub syntax_check: Option<String>,
+}
+
+fn default_output_verification() -> bool {
+    true
+}
+
+impl Default for PluginOutputConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_output_verification(),
+            syntax_check: None,
+        }
+    }
+}
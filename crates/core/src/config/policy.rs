@@ -0,0 +1,76 @@
+//! Policy rule configuration
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Policy rules evaluated over every extracted schema
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicyConfig {
+    /// Rules to evaluate
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+}
+
+impl PolicyConfig {
+    pub fn validate(&self) -> Result<()> {
+        let mut names = HashSet::new();
+        for rule in &self.rules {
+            if rule.name.is_empty() {
+                return Err(anyhow!("Policy rule name cannot be empty"));
+            }
+            if !names.insert(rule.name.as_str()) {
+                return Err(anyhow!("Duplicate policy rule: {}", rule.name));
+            }
+            crate::policy::cel::parse(&rule.expression)
+                .map_err(|e| anyhow!("Invalid expression for policy rule {}: {}", rule.name, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// A single policy rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    /// Name of the rule
+    pub name: String,
+
+    /// What `self` refers to when evaluating the expression
+    #[serde(default)]
+    pub scope: PolicyScope,
+
+    /// CEL expression that must evaluate to true
+    pub expression: String,
+
+    /// Message reported when the rule is violated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// What to do when the rule is violated
+    #[serde(default)]
+    pub action: PolicyAction,
+}
+
+/// Scope a policy rule is evaluated in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyScope {
+    /// Once per schema; `self` has name, group, version, kind, categories and schema
+    #[default]
+    Schema,
+
+    /// Once per schema field; `self` is the field's OpenAPI schema and `path` its dotted path
+    Field,
+}
+
+/// Action taken when a policy rule is violated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyAction {
+    /// Report the violation as a warning
+    #[default]
+    Warn,
+
+    /// Refuse to generate the source
+    Fail,
+}
@@ -0,0 +1,200 @@
+//! Secrets referenced from the configuration
+//!
+//! Credentials can be written inline, or referenced with `secretRef:` and
+//! resolved when they are used: from an environment variable, a
+//! SOPS-encrypted file or the output of a command. Only the reference is
+//! kept in the configuration; resolved values are never serialized, logged
+//! or written to the lockfile.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// A secret value, given inline or referenced
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Secret {
+    /// Value written in the configuration
+    Inline(String),
+
+    /// Value resolved from outside the configuration
+    Ref {
+        #[serde(rename = "secretRef")]
+        secret_ref: SecretRef,
+    },
+}
+
+/// Where a referenced secret is resolved from; exactly one of `env`, `sops`
+/// and `command` is set
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecretRef {
+    /// Environment variable holding the value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<String>,
+
+    /// SOPS-encrypted file holding the value, decrypted with `sops --decrypt`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sops: Option<PathBuf>,
+
+    /// Dotted path of the value in the decrypted file, e.g. `git.token`;
+    /// the whole file when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+
+    /// Shell command printing the value on stdout
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+impl Secret {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Secret::Inline(_) => Ok(()),
+            Secret::Ref { secret_ref } => secret_ref.validate(),
+        }
+    }
+
+    /// Resolve the value of the secret
+    pub fn resolve(&self) -> Result<String> {
+        match self {
+            Secret::Inline(value) => Ok(value.clone()),
+            Secret::Ref { secret_ref } => secret_ref.resolve(),
+        }
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Secret::Inline(_) => f.write_str("Secret(<redacted>)"),
+            Secret::Ref { secret_ref } => f.debug_tuple("Secret").field(secret_ref).finish(),
+        }
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Secret::Inline(value.to_string())
+    }
+}
+
+impl SecretRef {
+    pub fn validate(&self) -> Result<()> {
+        let sources = [
+            self.env.is_some(),
+            self.sops.is_some(),
+            self.command.is_some(),
+        ];
+        if sources.iter().filter(|set| **set).count() != 1 {
+            return Err(anyhow!(
+                "secretRef must set exactly one of env, sops or command"
+            ));
+        }
+        if self.key.is_some() && self.sops.is_none() {
+            return Err(anyhow!("secretRef key can only be used with sops"));
+        }
+        Ok(())
+    }
+
+    /// Resolve the referenced value
+    pub fn resolve(&self) -> Result<String> {
+        self.validate()?;
+
+        if let Some(name) = &self.env {
+            return std::env::var(name)
+                .map_err(|_| anyhow!("Secret environment variable {} is not set", name));
+        }
+
+        if let Some(path) = &self.sops {
+            let decrypted = run(
+                Command::new("sops").arg("--decrypt").arg(path),
+                &format!("sops --decrypt {}", path.display()),
+            )?;
+            return match &self.key {
+                Some(key) => lookup(&decrypted, key)
+                    .with_context(|| format!("Failed to read secret from {}", path.display())),
+                None => Ok(decrypted.trim_end().to_string()),
+            };
+        }
+
+        let command = self.command.as_deref().unwrap_or_default();
+        let output = run(Command::new("sh").arg("-c").arg(command), command)?;
+        Ok(output.trim_end().to_string())
+    }
+}
+
+/// Run a command, returning its stdout; stdout is left out of errors
+fn run(command: &mut Command, description: &str) -> Result<String> {
+    let output = command
+        .output()
+        .map_err(|e| anyhow!("Failed to run secret command `{}`: {}", description, e))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Secret command `{}` failed ({}): {}",
+            description,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|_| anyhow!("Secret command `{}` printed invalid UTF-8", description))
+}
+
+/// Value at a dotted path of a YAML or JSON document
+fn lookup(document: &str, key: &str) -> Result<String> {
+    let mut value: serde_yaml::Value = serde_yaml::from_str(document)?;
+    for part in key.split('.') {
+        value = value
+            .get(part)
+            .cloned()
+            .ok_or_else(|| anyhow!("Secret key {} not found", key))?;
+    }
+    match value {
+        serde_yaml::Value::String(value) => Ok(value),
+        serde_yaml::Value::Number(value) => Ok(value.to_string()),
+        serde_yaml::Value::Bool(value) => Ok(value.to_string()),
+        _ => Err(anyhow!("Secret key {} does not hold a scalar value", key)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_secrets() {
+        let inline: Secret = serde_yaml::from_str("hunter2").unwrap();
+        assert_eq!(inline.resolve().unwrap(), "hunter2");
+        assert!(!format!("{inline:?}").contains("hunter2"));
+
+        std::env::set_var("GENSONNET_TEST_SECRET", "from-env");
+        let env: Secret = serde_yaml::from_str("secretRef:\n  env: GENSONNET_TEST_SECRET").unwrap();
+        assert_eq!(env.resolve().unwrap(), "from-env");
+        assert_eq!(
+            serde_yaml::to_string(&env).unwrap(),
+            "secretRef:\n  env: GENSONNET_TEST_SECRET\n"
+        );
+
+        let command: Secret =
+            serde_yaml::from_str("secretRef:\n  command: echo from-command").unwrap();
+        assert_eq!(command.resolve().unwrap(), "from-command");
+
+        let failing: Secret =
+            serde_yaml::from_str("secretRef:\n  command: echo $((6 * 7)); exit 3").unwrap();
+        let err = failing.resolve().unwrap_err().to_string();
+        assert!(err.contains("failed") && !err.contains("42"));
+
+        let ambiguous: Secret =
+            serde_yaml::from_str("secretRef:\n  env: A\n  command: echo b").unwrap();
+        assert!(ambiguous.validate().is_err());
+
+        assert_eq!(
+            lookup("git:\n  token: abc\n  port: 22\n", "git.token").unwrap(),
+            "abc"
+        );
+        assert!(lookup("git: {}\n", "git.token").is_err());
+    }
+}
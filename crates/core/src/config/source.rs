@@ -0,0 +1,1337 @@
+//! Source configuration definitions
+
+use crate::config::Secret;
+use crate::transform::Transform;
+use anyhow::{anyhow, Result};
+use gensonnet_plugin::PluginBinding;
+use jsonnet_crd::VersionSelection;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Source types that can be processed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Source {
+    /// Kubernetes CustomResourceDefinition source
+    Crd(CrdSource),
+
+    /// Go AST source for processing Go source files
+    GoAst(GoAstSource),
+
+    /// OpenAPI specification source for processing OpenAPI/Swagger files
+    OpenApi(OpenApiSource),
+
+    /// CRD manifest fetched from a URL
+    Url(UrlSource),
+
+    /// CRD bundle or OpenAPI spec published as a GitHub or GitLab release asset
+    Release(ReleaseSource),
+
+    /// CRDs built from a kustomize base or overlay
+    Kustomize(KustomizeSource),
+
+    /// Repository of many operators, expanded into one logical source per match
+    Catalog(CatalogSource),
+
+    /// Repository scanned for CRDs, OpenAPI specs and Go packages, expanded into
+    /// one logical source per kind of content found
+    Auto(AutoSource),
+
+    /// Example schemas embedded in the binary, for demos and offline tests
+    Fixture(FixtureSource),
+
+    /// Hand-written Jsonnet library re-exported next to the generated ones
+    Jsonnet(JsonnetSource),
+}
+
+impl Source {
+    /// Get the name of the source
+    pub fn name(&self) -> &str {
+        match self {
+            Source::Crd(crd) => &crd.name,
+            Source::GoAst(go_ast) => &go_ast.name,
+            Source::OpenApi(openapi) => &openapi.name,
+            Source::Url(url) => &url.name,
+            Source::Release(release) => &release.name,
+            Source::Kustomize(kustomize) => &kustomize.name,
+            Source::Catalog(catalog) => &catalog.name,
+            Source::Auto(auto) => &auto.name,
+            Source::Fixture(fixture) => &fixture.name,
+            Source::Jsonnet(jsonnet) => &jsonnet.name,
+        }
+    }
+
+    /// Time the source may take to be processed, if limited
+    pub fn timeout(&self) -> Option<std::time::Duration> {
+        let timeout_seconds = match self {
+            Source::Crd(crd) => crd.timeout_seconds,
+            Source::GoAst(go_ast) => go_ast.timeout_seconds,
+            Source::OpenApi(openapi) => openapi.timeout_seconds,
+            Source::Url(url) => url.timeout_seconds,
+            Source::Release(release) => release.timeout_seconds,
+            Source::Kustomize(kustomize) => kustomize.timeout_seconds,
+            Source::Catalog(catalog) => catalog.timeout_seconds,
+            Source::Auto(auto) => auto.timeout_seconds,
+            Source::Fixture(fixture) => fixture.timeout_seconds,
+            Source::Jsonnet(jsonnet) => jsonnet.timeout_seconds,
+        };
+        timeout_seconds.map(std::time::Duration::from_secs)
+    }
+
+    /// Tags the source is selected by
+    pub fn tags(&self) -> &[String] {
+        match self {
+            Source::Crd(crd) => &crd.tags,
+            Source::GoAst(go_ast) => &go_ast.tags,
+            Source::OpenApi(openapi) => &openapi.tags,
+            Source::Url(url) => &url.tags,
+            Source::Release(release) => &release.tags,
+            Source::Kustomize(kustomize) => &kustomize.tags,
+            Source::Catalog(catalog) => &catalog.tags,
+            Source::Auto(auto) => &auto.tags,
+            Source::Fixture(fixture) => &fixture.tags,
+            Source::Jsonnet(jsonnet) => &jsonnet.tags,
+        }
+    }
+
+    /// Whether the source carries any of the given tags
+    pub fn has_any_tag(&self, tags: &[String]) -> bool {
+        self.tags().iter().any(|tag| tags.contains(tag))
+    }
+
+    /// Transforms applied to the source's schemas
+    pub fn transforms(&self) -> &[Transform] {
+        match self {
+            Source::Crd(crd) => &crd.transforms,
+            Source::GoAst(go_ast) => &go_ast.transforms,
+            Source::OpenApi(openapi) => &openapi.transforms,
+            Source::Url(url) => &url.transforms,
+            Source::Release(release) => &release.transforms,
+            Source::Kustomize(kustomize) => &kustomize.transforms,
+            Source::Catalog(catalog) => &catalog.transforms,
+            Source::Auto(auto) => &auto.transforms,
+            Source::Fixture(fixture) => &fixture.transforms,
+            // Libraries are re-exported as written, without schemas to transform
+            Source::Jsonnet(_) => &[],
+        }
+    }
+
+    /// Whether the source is pinned by the digest of its content rather than
+    /// by a commit
+    pub fn pins_content(&self) -> bool {
+        matches!(
+            self,
+            Source::Url(_) | Source::Release(_) | Source::Fixture(_) | Source::Jsonnet(_)
+        )
+    }
+
+    /// Cargo feature needed to process this source, if it was compiled out
+    pub fn missing_feature(&self) -> Option<&'static str> {
+        match self {
+            Source::GoAst(_) if !cfg!(feature = "go-ast") => Some("go-ast"),
+            Source::OpenApi(_) if !cfg!(feature = "openapi") => Some("openapi"),
+            Source::Url(url) if !url.url.starts_with("file://") && !cfg!(feature = "http") => {
+                Some("http")
+            }
+            Source::Release(_) if !cfg!(feature = "http") => Some("http"),
+            Source::Release(release)
+                if release.format == ReleaseFormat::OpenApi && !cfg!(feature = "openapi") =>
+            {
+                Some("openapi")
+            }
+            Source::Fixture(fixture)
+                if fixture.fixture == FixtureSet::OpenApi && !cfg!(feature = "openapi") =>
+            {
+                Some("openapi")
+            }
+            _ => None,
+        }
+    }
+
+    /// Validate the source configuration
+    pub fn validate(&self) -> Result<()> {
+        if let Some(feature) = self.missing_feature() {
+            return Err(anyhow!(
+                "Source {} requires the `{}` feature, which this build was compiled without",
+                self.name(),
+                feature
+            ));
+        }
+        if self.timeout() == Some(std::time::Duration::ZERO) {
+            return Err(anyhow!(
+                "Source {} timeout_seconds must be greater than 0",
+                self.name()
+            ));
+        }
+
+        if let Some(tag) = self
+            .tags()
+            .iter()
+            .find(|tag| tag.is_empty() || tag.contains(|c: char| c == ',' || c.is_whitespace()))
+        {
+            return Err(anyhow!(
+                "Source {} has an invalid tag {:?}: tags cannot be empty or contain commas or whitespace",
+                self.name(),
+                tag
+            ));
+        }
+
+        for transform in self.transforms() {
+            transform
+                .validate()
+                .map_err(|e| anyhow!("Source {} has an invalid transform: {}", self.name(), e))?;
+        }
+
+        match self {
+            Source::Crd(crd) => crd.validate(),
+            Source::GoAst(go_ast) => go_ast.validate(),
+            Source::OpenApi(openapi) => openapi.validate(),
+            Source::Url(url) => url.validate(),
+            Source::Release(release) => release.validate(),
+            Source::Kustomize(kustomize) => kustomize.validate(),
+            Source::Catalog(catalog) => catalog.validate(),
+            Source::Auto(auto) => auto.validate(),
+            Source::Fixture(fixture) => fixture.validate(),
+            Source::Jsonnet(jsonnet) => jsonnet.validate(),
+        }
+    }
+}
+
+/// CRD source configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrdSource {
+    /// Name of the source
+    pub name: String,
+
+    /// Git repository configuration
+    pub git: GitSource,
+
+    /// Filters for CRDs (API group patterns)
+    pub filters: Vec<String>,
+
+    /// CRD versions generated: `all`, only `served` ones, or only the `storage` version
+    #[serde(default, skip_serializing_if = "VersionSelection::is_all")]
+    pub versions: VersionSelection,
+
+    /// Output path for generated files
+    pub output_path: PathBuf,
+
+    /// Plugin that processes the source: a plugin id, or `auto` to pick by priority and score
+    #[serde(default, skip_serializing_if = "PluginBinding::is_auto")]
+    pub plugin: PluginBinding,
+
+    /// Seconds the source may spend cloning, parsing and generating before it fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+
+    /// Tags selecting the source in commands run with `--tags`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Transforms applied to the extracted schemas before generation, in order
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "serde_yaml::with::singleton_map_recursive"
+    )]
+    pub transforms: Vec<Transform>,
+
+    /// Globs of sample resources in the repository, e.g. `config/samples/*.yaml`,
+    /// turned into examples and presets of the generated library
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub samples: Vec<String>,
+}
+
+impl CrdSource {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(anyhow!("CRD source name cannot be empty"));
+        }
+
+        self.git.validate()?;
+
+        if self.output_path.to_string_lossy().is_empty() {
+            return Err(anyhow!("CRD output path cannot be empty"));
+        }
+
+        // A plugin bound to a CRD source generates the library itself, so the
+        // schemas never pass through the transforms
+        if !self.transforms.is_empty() && !self.plugin.is_auto() {
+            return Err(anyhow!(
+                "CRD source {} cannot combine transforms with a plugin binding",
+                self.name
+            ));
+        }
+
+        if !self.samples.is_empty() && !self.plugin.is_auto() {
+            return Err(anyhow!(
+                "CRD source {} cannot combine samples with a plugin binding",
+                self.name
+            ));
+        }
+        for pattern in &self.samples {
+            let relative = Path::new(pattern).is_relative()
+                && !Path::new(pattern)
+                    .components()
+                    .any(|c| c == std::path::Component::ParentDir);
+            if !relative {
+                return Err(anyhow!(
+                    "CRD source {} samples pattern {} must be relative to the repository",
+                    self.name,
+                    pattern
+                ));
+            }
+            glob::Pattern::new(pattern).map_err(|e| {
+                anyhow!(
+                    "CRD source {} has invalid samples pattern {}: {}",
+                    self.name,
+                    pattern,
+                    e
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Git source configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitSource {
+    /// Git repository URL
+    pub url: String,
+
+    /// Git reference (branch, tag, or commit SHA)
+    #[serde(rename = "ref")]
+    pub ref_name: Option<String>,
+
+    /// Authentication configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<GitAuth>,
+}
+
+impl GitSource {
+    pub fn validate(&self) -> Result<()> {
+        if self.url.is_empty() {
+            return Err(anyhow!("Git URL cannot be empty"));
+        }
+
+        // Basic URL validation
+        if !self.url.starts_with("http") && !self.url.starts_with("git@") {
+            return Err(anyhow!("Invalid Git URL format: {}", self.url));
+        }
+
+        if let Some(auth) = &self.auth {
+            auth.validate()?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the reference name, defaulting to "main" if not specified
+    pub fn ref_name(&self) -> &str {
+        self.ref_name.as_deref().unwrap_or("main")
+    }
+
+    /// Get a unique identifier for this source
+    pub fn identifier(&self) -> String {
+        let ref_name = self.ref_name();
+        format!("{}@{}", self.url, ref_name)
+    }
+}
+
+/// Git authentication configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GitAuth {
+    /// SSH key authentication
+    Ssh {
+        /// Path to SSH private key
+        key_path: PathBuf,
+        /// SSH key passphrase (optional)
+        passphrase: Option<Secret>,
+    },
+
+    /// Personal access token authentication
+    Token {
+        /// The access token
+        token: Secret,
+    },
+
+    /// Username/password authentication
+    Basic {
+        /// Username
+        username: String,
+        /// Password
+        password: Secret,
+    },
+
+    /// The credentials another source of the repository's host declares
+    ///
+    /// Credentials are only ever reused by sources that ask for them this way.
+    Host,
+}
+
+impl GitAuth {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            GitAuth::Ssh { passphrase, .. } => passphrase.as_ref().map_or(Ok(()), Secret::validate),
+            GitAuth::Token { token } => token.validate(),
+            GitAuth::Basic { password, .. } => password.validate(),
+            GitAuth::Host => Ok(()),
+        }
+    }
+}
+
+/// Go AST source configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoAstSource {
+    /// Name of the source
+    pub name: String,
+
+    /// Git repository configuration
+    pub git: GitSource,
+
+    /// File patterns to include (e.g., ["**/*.go"])
+    pub include_patterns: Vec<String>,
+
+    /// File patterns to exclude (e.g., ["**/*_test.go", "vendor/**"])
+    pub exclude_patterns: Vec<String>,
+
+    /// Output path for generated files
+    pub output_path: PathBuf,
+
+    /// Package filters (optional, for specific packages)
+    pub package_filters: Option<Vec<String>>,
+
+    /// Plugin that processes the source: a plugin id, or `auto` to pick by priority and score
+    #[serde(default, skip_serializing_if = "PluginBinding::is_auto")]
+    pub plugin: PluginBinding,
+
+    /// Seconds the source may spend cloning, parsing and generating before it fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+
+    /// Tags selecting the source in commands run with `--tags`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Transforms applied to the extracted schemas before generation, in order
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "serde_yaml::with::singleton_map_recursive"
+    )]
+    pub transforms: Vec<Transform>,
+}
+
+impl GoAstSource {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(anyhow!("Go AST source name cannot be empty"));
+        }
+
+        self.git.validate()?;
+
+        if self.output_path.to_string_lossy().is_empty() {
+            return Err(anyhow!("Go AST output path cannot be empty"));
+        }
+
+        if self.include_patterns.is_empty() {
+            return Err(anyhow!(
+                "Go AST source must have at least one include pattern"
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// OpenAPI source configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenApiSource {
+    /// Name of the source
+    pub name: String,
+
+    /// Git repository configuration
+    pub git: GitSource,
+
+    /// File patterns to include (e.g., ["**/*.yaml", "**/*.json"])
+    pub include_patterns: Vec<String>,
+
+    /// File patterns to exclude (e.g., ["**/*_test.yaml", "vendor/**"])
+    pub exclude_patterns: Vec<String>,
+
+    /// Output path for generated files
+    pub output_path: PathBuf,
+
+    /// OpenAPI version to target (2.0, 3.0, etc.)
+    pub openapi_version: Option<String>,
+
+    /// Whether to include examples in generated code
+    pub include_examples: Option<bool>,
+
+    /// Whether to include descriptions in generated code
+    pub include_descriptions: Option<bool>,
+
+    /// Custom base URL for the API
+    pub base_url: Option<String>,
+
+    /// Also generate builders for the payloads of `components.callbacks` and
+    /// (OpenAPI 3.1) `webhooks`
+    #[serde(default)]
+    pub include_callbacks: bool,
+
+    /// Plugin that processes the source: a plugin id, or `auto` to pick by priority and score
+    #[serde(default, skip_serializing_if = "PluginBinding::is_auto")]
+    pub plugin: PluginBinding,
+
+    /// Seconds the source may spend cloning, parsing and generating before it fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+
+    /// Tags selecting the source in commands run with `--tags`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Transforms applied to the extracted schemas before generation, in order
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "serde_yaml::with::singleton_map_recursive"
+    )]
+    pub transforms: Vec<Transform>,
+}
+
+impl OpenApiSource {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(anyhow!("OpenAPI source name cannot be empty"));
+        }
+
+        self.git.validate()?;
+
+        if self.output_path.to_string_lossy().is_empty() {
+            return Err(anyhow!("OpenAPI output path cannot be empty"));
+        }
+
+        if self.include_patterns.is_empty() {
+            return Err(anyhow!(
+                "OpenAPI source must have at least one include pattern"
+            ));
+        }
+
+        // Validate OpenAPI version if specified
+        if let Some(version) = &self.openapi_version {
+            if !matches!(version.as_str(), "2.0" | "3.0" | "3.1") {
+                return Err(anyhow!("Unsupported OpenAPI version: {}", version));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Options handed to the OpenAPI plugin as its configuration
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenApiOptions {
+    /// Extract the payloads of callbacks and webhooks besides the component schemas
+    pub include_callbacks: bool,
+}
+
+impl OpenApiOptions {
+    /// Read the options handed to the OpenAPI plugin as its configuration
+    pub fn from_plugin_config(config: &serde_yaml::Value) -> Result<Self> {
+        if config.is_null() {
+            return Ok(Self::default());
+        }
+        serde_yaml::from_value(config.clone())
+            .map_err(|e| anyhow!("Invalid OpenAPI options: {}", e))
+    }
+}
+
+/// URL source configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlSource {
+    /// Name of the source
+    pub name: String,
+
+    /// URL of the CRD manifest, or of a `.tar`, `.tar.gz` or `.tgz` archive of
+    /// manifests (http, https or file)
+    pub url: String,
+
+    /// Expected SHA-256 digest of the manifest or archive (hex, optionally prefixed with `sha256:`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+
+    /// Filters for CRDs (API group patterns)
+    #[serde(default)]
+    pub filters: Vec<String>,
+
+    /// CRD versions generated: `all`, only `served` ones, or only the `storage` version
+    #[serde(default, skip_serializing_if = "VersionSelection::is_all")]
+    pub versions: VersionSelection,
+
+    /// Output path for generated files
+    pub output_path: PathBuf,
+
+    /// Seconds the source may spend cloning, parsing and generating before it fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+
+    /// Tags selecting the source in commands run with `--tags`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Transforms applied to the extracted schemas before generation, in order
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "serde_yaml::with::singleton_map_recursive"
+    )]
+    pub transforms: Vec<Transform>,
+}
+
+impl UrlSource {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(anyhow!("URL source name cannot be empty"));
+        }
+
+        if !self.url.starts_with("http://")
+            && !self.url.starts_with("https://")
+            && !self.url.starts_with("file://")
+        {
+            return Err(anyhow!("Invalid URL format: {}", self.url));
+        }
+
+        if let Some(sha256) = &self.sha256 {
+            let hex = sha256.strip_prefix("sha256:").unwrap_or(sha256);
+            if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(anyhow!(
+                    "Invalid sha256 pin for URL source {}: {}",
+                    self.name,
+                    sha256
+                ));
+            }
+        }
+
+        if self.output_path.to_string_lossy().is_empty() {
+            return Err(anyhow!("URL source output path cannot be empty"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Built-in fixture source configuration
+///
+/// Generates from example schemas shipped with the tool, so a demo
+/// configuration or a test needs neither network access nor a local clone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixtureSource {
+    /// Name of the source
+    pub name: String,
+
+    /// Fixture set to generate from
+    #[serde(default)]
+    pub fixture: FixtureSet,
+
+    /// Filters for CRDs (API group patterns)
+    #[serde(default)]
+    pub filters: Vec<String>,
+
+    /// CRD versions generated: `all`, only `served` ones, or only the `storage` version
+    #[serde(default, skip_serializing_if = "VersionSelection::is_all")]
+    pub versions: VersionSelection,
+
+    /// Output path for generated files
+    pub output_path: PathBuf,
+
+    /// Plugin that processes the OpenAPI fixture: a plugin id, or `auto` to pick by priority and score
+    #[serde(default, skip_serializing_if = "PluginBinding::is_auto")]
+    pub plugin: PluginBinding,
+
+    /// Seconds the source may spend parsing and generating before it fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+
+    /// Tags selecting the source in commands run with `--tags`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Transforms applied to the extracted schemas before generation, in order
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "serde_yaml::with::singleton_map_recursive"
+    )]
+    pub transforms: Vec<Transform>,
+}
+
+/// Example schemas a fixture source generates from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FixtureSet {
+    /// Example CRDs in the `demo.gensonnet.dev` group
+    #[default]
+    Crds,
+
+    /// Example OpenAPI v3 specification
+    OpenApi,
+}
+
+impl FixtureSet {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FixtureSet::Crds => "crds",
+            FixtureSet::OpenApi => "openapi",
+        }
+    }
+
+    /// Pseudo-URL identifying the fixture set in lockfiles and plans
+    pub fn url(&self) -> &'static str {
+        match self {
+            FixtureSet::Crds => "fixture://crds",
+            FixtureSet::OpenApi => "fixture://openapi",
+        }
+    }
+}
+
+impl FixtureSource {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(anyhow!("Fixture source name cannot be empty"));
+        }
+
+        if self.output_path.to_string_lossy().is_empty() {
+            return Err(anyhow!("Fixture source output path cannot be empty"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Hand-written Jsonnet library source configuration
+///
+/// The library is copied to the output path as written, next to an
+/// `index.libsonnet` re-exporting it, so it is part of the platform bundle like
+/// the generated libraries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonnetSource {
+    /// Name of the source
+    pub name: String,
+
+    /// The library: a `.jsonnet` or `.libsonnet` file, or a directory of them
+    pub path: PathBuf,
+
+    /// Entry file of a library directory, relative to it; `main.libsonnet` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry: Option<PathBuf>,
+
+    /// Output path for the re-exported library
+    pub output_path: PathBuf,
+
+    /// Jsonnet evaluator binary introspecting the library (e.g. `jsonnet` or `jrsonnet`)
+    #[serde(default = "default_evaluator")]
+    pub evaluator: String,
+
+    /// Library search paths the library's imports need, e.g. for k.libsonnet
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub jpaths: Vec<PathBuf>,
+
+    /// Seconds the source may spend copying and introspecting before it fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+
+    /// Tags selecting the source in commands run with `--tags`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+fn default_evaluator() -> String {
+    "jsonnet".to_string()
+}
+
+impl JsonnetSource {
+    /// Whether the library is a directory, as paths without an extension
+    /// are taken to be until they exist
+    pub fn is_directory(&self) -> bool {
+        self.path.is_dir() || (!self.path.exists() && self.path.extension().is_none())
+    }
+
+    /// Entry file of the library, relative to the library directory
+    pub fn entry_file(&self) -> PathBuf {
+        if self.is_directory() {
+            self.entry
+                .clone()
+                .unwrap_or_else(|| PathBuf::from("main.libsonnet"))
+        } else {
+            PathBuf::from(self.path.file_name().unwrap_or_default())
+        }
+    }
+
+    /// Location of the library, recorded in lockfiles and the bundle
+    pub fn url(&self) -> &str {
+        self.path.to_str().unwrap_or_default()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(anyhow!("Jsonnet source name cannot be empty"));
+        }
+
+        if self.path.to_string_lossy().is_empty() {
+            return Err(anyhow!("Jsonnet source {} path cannot be empty", self.name));
+        }
+
+        if self.output_path.to_string_lossy().is_empty() {
+            return Err(anyhow!("Jsonnet source output path cannot be empty"));
+        }
+
+        let entry = self.entry.as_deref().unwrap_or(&self.path);
+        let is_jsonnet = entry
+            .extension()
+            .is_some_and(|extension| extension == "jsonnet" || extension == "libsonnet");
+        if (self.entry.is_some() || !self.is_directory()) && !is_jsonnet {
+            return Err(anyhow!(
+                "Jsonnet source {} must point to a .jsonnet or .libsonnet file, not {}",
+                self.name,
+                entry.display()
+            ));
+        }
+
+        // Publishing into the library would overwrite it
+        let library_dir = if self.is_directory() {
+            self.path.as_path()
+        } else {
+            self.path.parent().unwrap_or(Path::new(""))
+        };
+        if self.output_path == library_dir {
+            return Err(anyhow!(
+                "Jsonnet source {} cannot be published into its own directory",
+                self.name
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Release asset source configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseSource {
+    /// Name of the source
+    pub name: String,
+
+    /// GitHub repository (`org/repo`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github: Option<String>,
+
+    /// GitLab project path (`group/project`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gitlab: Option<String>,
+
+    /// API base URL, for GitHub Enterprise or self-hosted GitLab
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+
+    /// API token; `GITHUB_TOKEN` or `GITLAB_TOKEN` from the environment when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<Secret>,
+
+    /// Name of the release asset (glob pattern, e.g. "*-crds.yaml")
+    pub asset: String,
+
+    /// Release tag (glob pattern, e.g. "v1.2.*"); the newest release when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+
+    /// Whether pre-releases may be selected
+    #[serde(default)]
+    pub prereleases: bool,
+
+    /// What the asset contains
+    #[serde(default)]
+    pub format: ReleaseFormat,
+
+    /// Filters for CRDs (API group patterns)
+    #[serde(default)]
+    pub filters: Vec<String>,
+
+    /// CRD versions generated: `all`, only `served` ones, or only the `storage` version
+    #[serde(default, skip_serializing_if = "VersionSelection::is_all")]
+    pub versions: VersionSelection,
+
+    /// Output path for generated files
+    pub output_path: PathBuf,
+
+    /// Plugin that processes OpenAPI assets: a plugin id, or `auto` to pick by priority and score
+    #[serde(default, skip_serializing_if = "PluginBinding::is_auto")]
+    pub plugin: PluginBinding,
+
+    /// Seconds the source may spend cloning, parsing and generating before it fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+
+    /// Tags selecting the source in commands run with `--tags`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Transforms applied to the extracted schemas before generation, in order
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "serde_yaml::with::singleton_map_recursive"
+    )]
+    pub transforms: Vec<Transform>,
+}
+
+/// Contents of a release asset
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseFormat {
+    /// Kubernetes CRD manifests
+    #[default]
+    Crd,
+
+    /// OpenAPI/Swagger specification
+    OpenApi,
+}
+
+impl ReleaseSource {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(anyhow!("Release source name cannot be empty"));
+        }
+
+        let repository = match (&self.github, &self.gitlab) {
+            (Some(repository), None) | (None, Some(repository)) => repository,
+            _ => {
+                return Err(anyhow!(
+                    "Release source {} must set exactly one of github or gitlab",
+                    self.name
+                ))
+            }
+        };
+        if !repository.contains('/') {
+            return Err(anyhow!(
+                "Invalid repository for release source {}: {}",
+                self.name,
+                repository
+            ));
+        }
+
+        glob::Pattern::new(&self.asset)
+            .map_err(|e| anyhow!("Invalid asset pattern '{}': {}", self.asset, e))?;
+        if let Some(tag) = &self.tag {
+            glob::Pattern::new(tag).map_err(|e| anyhow!("Invalid tag pattern '{}': {}", tag, e))?;
+        }
+        if let Some(token) = &self.token {
+            token.validate()?;
+        }
+
+        if self.output_path.to_string_lossy().is_empty() {
+            return Err(anyhow!("Release source output path cannot be empty"));
+        }
+
+        Ok(())
+    }
+
+    /// Repository the releases are published in
+    pub fn repository(&self) -> &str {
+        self.github
+            .as_deref()
+            .or(self.gitlab.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// Base URL of the provider's REST API
+    pub fn api_base(&self) -> &str {
+        let default = if self.gitlab.is_some() {
+            "https://gitlab.com/api/v4"
+        } else {
+            "https://api.github.com"
+        };
+        self.api_url
+            .as_deref()
+            .unwrap_or(default)
+            .trim_end_matches('/')
+    }
+}
+
+/// Kustomize source configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KustomizeSource {
+    /// Name of the source
+    pub name: String,
+
+    /// Git repository configuration
+    pub git: GitSource,
+
+    /// Kustomization directory relative to the repository root
+    #[serde(default = "default_kustomize_path")]
+    pub path: PathBuf,
+
+    /// Command building the kustomization, e.g. ["kustomize", "build"]; the
+    /// directory is appended. The built-in builder is used when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<Vec<String>>,
+
+    /// Filters for CRDs (API group patterns)
+    #[serde(default)]
+    pub filters: Vec<String>,
+
+    /// CRD versions generated: `all`, only `served` ones, or only the `storage` version
+    #[serde(default, skip_serializing_if = "VersionSelection::is_all")]
+    pub versions: VersionSelection,
+
+    /// Output path for generated files
+    pub output_path: PathBuf,
+
+    /// Seconds the source may spend cloning, parsing and generating before it fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+
+    /// Tags selecting the source in commands run with `--tags`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Transforms applied to the extracted schemas before generation, in order
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "serde_yaml::with::singleton_map_recursive"
+    )]
+    pub transforms: Vec<Transform>,
+}
+
+fn default_kustomize_path() -> PathBuf {
+    PathBuf::from(".")
+}
+
+impl KustomizeSource {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(anyhow!("Kustomize source name cannot be empty"));
+        }
+
+        self.git.validate()?;
+
+        if self.path.is_absolute()
+            || self
+                .path
+                .components()
+                .any(|c| c == std::path::Component::ParentDir)
+        {
+            return Err(anyhow!(
+                "Kustomize path must stay inside the repository: {}",
+                self.path.display()
+            ));
+        }
+
+        if self
+            .command
+            .as_ref()
+            .is_some_and(|command| command.is_empty())
+        {
+            return Err(anyhow!(
+                "Kustomize command of source {} cannot be empty",
+                self.name
+            ));
+        }
+
+        if self.output_path.to_string_lossy().is_empty() {
+            return Err(anyhow!("Kustomize output path cannot be empty"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Catalog source configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogSource {
+    /// Name of the source
+    pub name: String,
+
+    /// Git repository configuration
+    pub git: GitSource,
+
+    /// Rules selecting which directories become logical sources
+    pub discovery: CatalogDiscovery,
+
+    /// Filters for CRDs (API group patterns)
+    #[serde(default)]
+    pub filters: Vec<String>,
+
+    /// CRD versions generated: `all`, only `served` ones, or only the `storage` version
+    #[serde(default, skip_serializing_if = "VersionSelection::is_all")]
+    pub versions: VersionSelection,
+
+    /// Output path; each discovered source is generated into a subdirectory
+    pub output_path: PathBuf,
+
+    /// Seconds the source may spend cloning, parsing and generating before it fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+
+    /// Tags selecting the source in commands run with `--tags`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Transforms applied to the extracted schemas before generation, in order
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "serde_yaml::with::singleton_map_recursive"
+    )]
+    pub transforms: Vec<Transform>,
+}
+
+impl CatalogSource {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(anyhow!("Catalog source name cannot be empty"));
+        }
+
+        self.git.validate()?;
+
+        if self.output_path.to_string_lossy().is_empty() {
+            return Err(anyhow!("Catalog output path cannot be empty"));
+        }
+
+        if self.discovery.paths.is_empty() {
+            return Err(anyhow!(
+                "Catalog source must have at least one discovery path"
+            ));
+        }
+
+        for pattern in &self.discovery.paths {
+            glob::Pattern::new(pattern)
+                .map_err(|e| anyhow!("Invalid discovery path '{}': {}", pattern, e))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Discovery rules for catalog sources
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CatalogDiscovery {
+    /// Directory globs relative to the repository root (e.g., ["operators/*"])
+    pub paths: Vec<String>,
+
+    /// Only keep directories with a CRD in one of these categories
+    #[serde(default)]
+    pub categories: Vec<String>,
+
+    /// Only keep directories with a CRD carrying all of these labels
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+
+    /// Only keep directories with a CRD carrying all of these annotations
+    #[serde(default)]
+    pub annotations: BTreeMap<String, String>,
+}
+
+/// Auto-detected source configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoSource {
+    /// Name of the source
+    pub name: String,
+
+    /// Git repository configuration
+    pub git: GitSource,
+
+    /// File patterns skipped while scanning the repository
+    #[serde(default = "default_auto_exclude_patterns")]
+    pub exclude_patterns: Vec<String>,
+
+    /// Lowest plugin score a file needs to be classified; 1 also counts files
+    /// a plugin only claims by their extension
+    #[serde(default = "default_auto_min_score")]
+    pub min_score: u32,
+
+    /// Filters for CRDs (API group patterns)
+    #[serde(default)]
+    pub filters: Vec<String>,
+
+    /// CRD versions generated: `all`, only `served` ones, or only the `storage` version
+    #[serde(default, skip_serializing_if = "VersionSelection::is_all")]
+    pub versions: VersionSelection,
+
+    /// Output path; each detected kind of content is generated into a subdirectory
+    pub output_path: PathBuf,
+
+    /// Seconds the source may spend cloning, parsing and generating before it fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+
+    /// Tags selecting the source in commands run with `--tags`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Transforms applied to the extracted schemas before generation, in order
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "serde_yaml::with::singleton_map_recursive"
+    )]
+    pub transforms: Vec<Transform>,
+}
+
+fn default_auto_exclude_patterns() -> Vec<String> {
+    vec![
+        "vendor/**".to_string(),
+        "**/testdata/**".to_string(),
+        "**/*_test.go".to_string(),
+    ]
+}
+
+fn default_auto_min_score() -> u32 {
+    2
+}
+
+impl AutoSource {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(anyhow!("Auto source name cannot be empty"));
+        }
+
+        self.git.validate()?;
+
+        if self.output_path.to_string_lossy().is_empty() {
+            return Err(anyhow!("Auto source output path cannot be empty"));
+        }
+
+        if self.min_score == 0 {
+            return Err(anyhow!(
+                "Auto source {} min_score must be greater than 0",
+                self.name
+            ));
+        }
+
+        for pattern in &self.exclude_patterns {
+            glob::Pattern::new(pattern)
+                .map_err(|e| anyhow!("Invalid exclude pattern '{}': {}", pattern, e))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crd_source_validation() {
+        let valid_source = CrdSource {
+            name: "test".to_string(),
+            git: GitSource {
+                url: "https://github.com/test/repo.git".to_string(),
+                ref_name: Some("main".to_string()),
+                auth: None,
+            },
+            filters: vec!["test.com/v1".to_string()],
+            versions: Default::default(),
+            output_path: PathBuf::from("./output"),
+            plugin: PluginBinding::Auto,
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+            samples: Vec::new(),
+        };
+
+        assert!(valid_source.validate().is_ok());
+    }
+
+    #[test]
+    fn test_git_source_validation() {
+        let valid_git = GitSource {
+            url: "https://github.com/test/repo.git".to_string(),
+            ref_name: Some("main".to_string()),
+            auth: None,
+        };
+
+        assert!(valid_git.validate().is_ok());
+        assert_eq!(valid_git.ref_name(), "main");
+    }
+
+    #[test]
+    fn test_git_source_default_ref() {
+        let git = GitSource {
+            url: "https://github.com/test/repo.git".to_string(),
+            ref_name: None,
+            auth: None,
+        };
+
+        assert_eq!(git.ref_name(), "main");
+    }
+
+    #[test]
+    fn test_invalid_git_url() {
+        let invalid_git = GitSource {
+            url: "invalid-url".to_string(),
+            ref_name: None,
+            auth: None,
+        };
+
+        assert!(invalid_git.validate().is_err());
+    }
+
+    #[test]
+    fn test_url_source_sha256_pin() {
+        let mut source = UrlSource {
+            name: "test".to_string(),
+            url: "https://example.com/crds.yaml".to_string(),
+            sha256: Some(format!("sha256:{}", "a".repeat(64))),
+            filters: vec![],
+            versions: Default::default(),
+            output_path: PathBuf::from("./output"),
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+        };
+        assert!(source.validate().is_ok());
+
+        source.sha256 = Some("abc123".to_string());
+        assert!(source.validate().is_err());
+
+        // Only downloads need the `http` feature
+        assert_eq!(
+            Source::Url(source.clone()).missing_feature(),
+            (!cfg!(feature = "http")).then_some("http")
+        );
+        source.url = "file:///crds/crds.yaml".to_string();
+        assert_eq!(Source::Url(source).missing_feature(), None);
+    }
+
+    #[test]
+    fn test_release_source_yaml() {
+        let source: Source = serde_yaml::from_str(
+            r#"
+type: release
+name: cert-manager
+github: cert-manager/cert-manager
+asset: cert-manager.crds.yaml
+tag: "v1.14.*"
+output_path: ./generated/cert-manager
+"#,
+        )
+        .unwrap();
+        let Source::Release(mut release) = source else {
+            panic!("expected a release source");
+        };
+        assert!(release.validate().is_ok());
+        assert_eq!(release.repository(), "cert-manager/cert-manager");
+        assert_eq!(release.api_base(), "https://api.github.com");
+        assert_eq!(release.format, ReleaseFormat::Crd);
+
+        release.gitlab = Some("group/project".to_string());
+        assert!(release.validate().is_err());
+    }
+}
@@ -0,0 +1,511 @@
+//! Configuration tests
+
+use super::*;
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+#[test]
+fn test_config_serialization() {
+    let config = Config::default();
+    let yaml = serde_yaml::to_string(&config).unwrap();
+    let parsed: Config = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(config.version, parsed.version);
+}
+
+#[test]
+fn test_config_from_file() {
+    let mut config = Config::default();
+    config
+        .sources
+        .push(crate::config::Source::Crd(crate::config::CrdSource {
+            name: "test".to_string(),
+            git: crate::config::GitSource {
+                url: "https://github.com/test/repo.git".to_string(),
+                ref_name: Some("main".to_string()),
+                auth: None,
+            },
+            filters: vec!["test.com/v1".to_string()],
+            versions: Default::default(),
+            output_path: PathBuf::from("./output"),
+            plugin: Default::default(),
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+            samples: Vec::new(),
+        }));
+
+    let temp_file = NamedTempFile::new().unwrap();
+    config
+        .save_to_file(&temp_file.path().to_path_buf())
+        .unwrap();
+
+    let loaded = Config::from_file(&temp_file.path().to_path_buf()).unwrap();
+    assert_eq!(config.version, loaded.version);
+}
+
+#[test]
+fn test_config_validation() {
+    let mut config = Config::default();
+    config.sources.push(Source::Crd(CrdSource {
+        name: "test".to_string(),
+        git: GitSource {
+            url: "https://github.com/test/repo.git".to_string(),
+            ref_name: Some("main".to_string()),
+            auth: None,
+        },
+        filters: vec!["test.com/v1".to_string()],
+        versions: Default::default(),
+        output_path: PathBuf::from("./output"),
+        plugin: Default::default(),
+        timeout_seconds: None,
+        tags: Vec::new(),
+        transforms: Vec::new(),
+        samples: Vec::new(),
+    }));
+
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_config_merges_fragments() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let config_path = temp_dir.path().join(".gensonnet.yaml");
+    Config::default().save_to_file(&config_path).unwrap();
+
+    let fragments_dir = temp_dir.path().join(Config::FRAGMENTS_DIR);
+    std::fs::create_dir(&fragments_dir).unwrap();
+    for name in ["b-team", "a-team"] {
+        std::fs::write(
+            fragments_dir.join(format!("{name}.yaml")),
+            format!(
+                r#"sources:
+  - type: crd
+    name: {name}
+    git:
+      url: https://github.com/test/{name}.git
+      ref: main
+    filters: []
+    output_path: ./generated/{name}
+"#
+            ),
+        )
+        .unwrap();
+    }
+
+    let loaded = Config::from_file(&config_path).unwrap();
+    let names: Vec<_> = loaded.sources.iter().map(|s| s.name()).collect();
+    assert_eq!(names, vec!["a-team", "b-team"]);
+
+    std::fs::copy(
+        fragments_dir.join("a-team.yaml"),
+        fragments_dir.join("c-team.yaml"),
+    )
+    .unwrap();
+    let err = Config::from_file(&config_path).unwrap_err();
+    assert!(err.to_string().contains("Duplicate source 'a-team'"));
+}
+
+#[test]
+fn test_compatibility_window() {
+    let compatibility: CompatibilityConfig = serde_yaml::from_str(
+        r#"
+window_days: 30
+renames:
+  - type: kind
+    from: Gadget
+    to: Widget
+    since: 2026-01-01
+  - type: field
+    kind: Widget
+    from: size
+    to: replicas
+"#,
+    )
+    .unwrap();
+    assert!(compatibility.validate().is_ok());
+
+    let date = |s: &str| s.parse::<chrono::NaiveDate>().unwrap();
+    assert_eq!(compatibility.active_renames(date("2026-01-31")).len(), 2);
+
+    let renames = compatibility.active_renames(date("2026-02-01"));
+    assert_eq!(
+        renames,
+        vec![jsonnet_generator::compat::Rename::Field {
+            kind: "Widget".to_string(),
+            from: "size".to_string(),
+            to: "replicas".to_string(),
+        }]
+    );
+    assert!(compatibility.is_windowed());
+
+    let pinned = CompatibilityConfig {
+        as_of: Some(date("2026-01-31")),
+        ..compatibility
+    };
+    assert!(!pinned.is_windowed());
+    assert_eq!(pinned.renames_on(date("2027-01-01")).len(), 2);
+}
+
+#[test]
+fn test_license_allowlist() {
+    let licenses: LicenseConfig = serde_yaml::from_str(
+        r#"
+allowed: [Apache-2.0, MIT]
+overrides:
+  vendor: MIT
+"#,
+    )
+    .unwrap();
+    assert!(licenses.validate().is_ok());
+    assert!(licenses.check("crds", Some("Apache-2.0")).is_ok());
+    assert!(licenses.check("crds", Some("GPL-3.0")).is_err());
+    assert!(licenses.check("crds", None).is_err());
+
+    // Without an allowlist any license, or none, is accepted
+    assert!(LicenseConfig::default().check("crds", None).is_ok());
+}
+
+#[test]
+fn test_discover_workspace_configs() {
+    let root = tempfile::TempDir::new().unwrap();
+    for path in [
+        "gensonnet.yaml",
+        "platform/.gensonnet.yaml",
+        "platform/gensonnet.yaml",
+        "teams/payments/gensonnet.yml",
+        "node_modules/pkg/gensonnet.yaml",
+        ".cache/gensonnet.yaml",
+    ] {
+        let path = root.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "").unwrap();
+    }
+
+    assert_eq!(
+        Config::discover(root.path()),
+        vec![
+            root.path().join("gensonnet.yaml"),
+            root.path().join("platform/.gensonnet.yaml"),
+            root.path().join("teams/payments/gensonnet.yml"),
+        ]
+    );
+}
+
+#[test]
+fn test_rebase_paths() {
+    let mut config = Config::default();
+    config.output.base_path = PathBuf::from("./generated");
+    config.output.mirrors = vec![PathBuf::from("/srv/mirror")];
+    config.sources = vec![serde_yaml::from_str(
+        r#"
+type: jsonnet
+name: platform
+path: ./lib/platform
+output_path: ./generated/platform
+jpaths: [vendor]
+"#,
+    )
+    .unwrap()];
+    config.plugins.plugin_directories = vec![
+        PathBuf::from("~/.gensonnet/plugins"),
+        PathBuf::from("$XDG_DATA_HOME/plugins"),
+        PathBuf::from("plugins"),
+    ];
+    config.rebase_paths(Path::new("teams/payments"));
+
+    assert_eq!(
+        config.output.base_path,
+        PathBuf::from("teams/payments/generated")
+    );
+    assert_eq!(config.output.mirrors, [PathBuf::from("/srv/mirror")]);
+    assert_eq!(
+        config.sources[0].output_path(),
+        Path::new("teams/payments/generated/platform")
+    );
+    let Source::Jsonnet(jsonnet) = &config.sources[0] else {
+        panic!("expected a jsonnet source");
+    };
+    assert_eq!(jsonnet.path, PathBuf::from("teams/payments/lib/platform"));
+    assert_eq!(jsonnet.jpaths, [PathBuf::from("teams/payments/vendor")]);
+    assert_eq!(
+        config.plugins.plugin_directories,
+        [
+            PathBuf::from("~/.gensonnet/plugins"),
+            PathBuf::from("$XDG_DATA_HOME/plugins"),
+            PathBuf::from("teams/payments/plugins"),
+        ]
+    );
+    assert_eq!(
+        config.audit.path,
+        PathBuf::from("teams/payments/.gensonnet/audit.jsonl")
+    );
+}
+
+#[test]
+fn test_output_budget() {
+    let generation: GenerationConfig = serde_yaml::from_str(
+        "fail_fast: false\ndeep_merge_strategy: default\nmax_output_bytes: 1000\nmax_file_bytes: 600\non_budget_exceeded: fail\n",
+    )
+    .unwrap();
+    assert_eq!(generation.on_budget_exceeded, PolicyAction::Fail);
+
+    let sizes = [
+        (PathBuf::from("index.libsonnet"), 100),
+        (PathBuf::from("v1/huge.libsonnet"), 700),
+        (PathBuf::from("v1/small.libsonnet"), 300),
+    ]
+    .into_iter()
+    .collect();
+    let violations = generation.check_output_budget("api", &sizes);
+    assert_eq!(violations.len(), 2);
+    assert!(violations[0].contains("max_output_bytes"));
+    assert!(violations[0].contains("largest: v1/huge.libsonnet"));
+    assert!(violations[1].contains("v1/huge.libsonnet"));
+
+    assert!(GenerationConfig::default()
+        .check_output_budget("api", &sizes)
+        .is_empty());
+}
+
+#[test]
+fn test_source_timeout() {
+    let mut source: Source = serde_yaml::from_str(
+        "type: url\nname: crds\nurl: file:///crds/crds.yaml\noutput_path: ./out\ntimeout_seconds: 300\n",
+    )
+    .unwrap();
+    assert_eq!(source.timeout(), Some(std::time::Duration::from_secs(300)));
+    assert!(source.validate().is_ok());
+
+    if let Source::Url(url) = &mut source {
+        url.timeout_seconds = Some(0);
+    }
+    assert!(source.validate().is_err());
+}
+
+#[test]
+fn test_select_tags() {
+    let mut config = Config {
+        sources: serde_yaml::from_str(
+            r#"
+- type: url
+  name: prometheus
+  url: file:///crds/prometheus.yaml
+  output_path: ./out/prometheus
+  tags: [observability]
+- type: url
+  name: cilium
+  url: file:///crds/cilium.yaml
+  output_path: ./out/cilium
+  tags: [networking, observability]
+- type: url
+  name: cert-manager
+  url: file:///crds/cert-manager.yaml
+  output_path: ./out/cert-manager
+"#,
+        )
+        .unwrap(),
+        ..Default::default()
+    };
+    assert!(config.validate().is_ok());
+
+    let mut networking = config.clone();
+    networking.select_tags(&["networking".to_string()]).unwrap();
+    let names: Vec<&str> = networking.sources.iter().map(|s| s.name()).collect();
+    assert_eq!(names, ["cilium"]);
+
+    config.select_tags(&["observability".to_string()]).unwrap();
+    assert_eq!(config.sources.len(), 2);
+
+    let err = config
+        .select_tags(&["observability".to_string(), "storage".to_string()])
+        .unwrap_err();
+    assert!(err.to_string().contains("storage"));
+}
+
+#[test]
+fn test_notification_webhooks() {
+    let notifications: NotificationsConfig = serde_yaml::from_str(
+        r#"
+webhooks:
+  - url: {secretRef: {env: SLACK_WEBHOOK_URL}}
+    on: [failure]
+    payload: '{"text": "gensonnet {status}: {failed_sources}"}'
+  - url: https://ci.example.com/hooks/gensonnet
+"#,
+    )
+    .unwrap();
+    assert_eq!(notifications.validate().is_ok(), cfg!(feature = "http"));
+    assert_eq!(
+        notifications.webhooks[1].on,
+        [NotificationEvent::Success, NotificationEvent::Failure]
+    );
+
+    let mut invalid = notifications.clone();
+    invalid.webhooks[0].payload = Some("{\"text\": {status}}".to_string());
+    let err = invalid.validate().unwrap_err().to_string();
+    assert!(err.contains("Invalid webhook payload"), "{err}");
+}
+
+#[test]
+fn test_warning_suppression() {
+    use jsonnet_generator::warning::{Warning, WarningCategory};
+
+    let warnings: WarningsConfig = serde_yaml::from_str(
+        r#"
+suppress:
+  - code: unresolved-type
+    path: "Certificate.spec.*"
+  - code: git-retries
+    source: flaky
+deny: [policy]
+"#,
+    )
+    .unwrap();
+    assert!(warnings.validate().is_ok());
+
+    let unresolved = |path: &str| {
+        Warning::new(
+            WarningCategory::Schema,
+            "unresolved-type",
+            "Unresolved type",
+        )
+        .with_path(path)
+    };
+    assert!(warnings.is_suppressed(&unresolved("Certificate.spec.issuerRef")));
+    assert!(!warnings.is_suppressed(&unresolved("Issuer.spec.acme")));
+
+    let mut retries = Warning::new(WarningCategory::Fetch, "git-retries", "needed 2 retries");
+    assert!(!warnings.is_suppressed(&retries));
+    retries.source = "flaky".to_string();
+    assert!(warnings.is_suppressed(&retries));
+
+    assert!(warnings.is_denied(&Warning::new(
+        WarningCategory::Policy,
+        "policy/require-descriptions",
+        "Missing description"
+    )));
+    assert!(!warnings.is_denied(&retries));
+
+    let invalid: WarningsConfig = serde_yaml::from_str("suppress:\n  - source: flaky\n").unwrap();
+    let err = invalid.validate().unwrap_err().to_string();
+    assert!(err.contains("need a code or a path"), "{err}");
+}
+
+#[test]
+fn test_source_version_selection() {
+    let source: Source = serde_yaml::from_str(
+        r#"
+type: fixture
+name: widgets
+fixture: crds
+filters: []
+versions: storage
+output_path: ./generated/widgets
+"#,
+    )
+    .unwrap();
+    let Source::Fixture(fixture) = &source else {
+        panic!("expected a fixture source");
+    };
+    assert_eq!(fixture.versions, jsonnet_crd::VersionSelection::Storage);
+
+    // The default is left out when serializing
+    let mut fixture = fixture.clone();
+    fixture.versions = Default::default();
+    let yaml = serde_yaml::to_string(&Source::Fixture(fixture)).unwrap();
+    assert!(!yaml.contains("versions"), "{yaml}");
+}
+
+#[test]
+fn test_output_style() {
+    use jsonnet_generator::config::OutputConfig;
+    use jsonnet_generator::style::{FieldOrder, QuoteStyle};
+
+    let output: OutputConfig = serde_yaml::from_str(
+        "base_path: ./generated\norganization: flat\nstyle:\n  indent: 4\n  quotes: single\n  field_order: alphabetical\n  trailing_commas: false\n",
+    )
+    .unwrap();
+    assert!(output.validate().is_ok());
+    assert_eq!(output.style.indent, 4);
+    assert_eq!(output.style.quotes, QuoteStyle::Single);
+    assert_eq!(output.style.field_order, FieldOrder::Alphabetical);
+    assert!(!output.style.trailing_commas);
+
+    // Unset knobs keep the style generators emit
+    let output: OutputConfig =
+        serde_yaml::from_str("base_path: ./generated\norganization: flat\nstyle:\n  indent: 4\n")
+            .unwrap();
+    assert!(output.style.trailing_commas);
+    assert_eq!(output.style.quotes, QuoteStyle::Double);
+
+    let output: OutputConfig =
+        serde_yaml::from_str("base_path: ./generated\norganization: flat\nstyle:\n  indent: 0\n")
+            .unwrap();
+    assert!(output.validate().is_err());
+}
+
+#[test]
+fn test_jsonnet_source() {
+    let source: Source = serde_yaml::from_str(
+        r#"
+type: jsonnet
+name: platform
+path: ./lib/platform
+output_path: ./generated/platform
+jpaths:
+  - ./vendor
+"#,
+    )
+    .unwrap();
+    assert!(source.validate().is_ok());
+    let Source::Jsonnet(jsonnet) = &source else {
+        panic!("expected a jsonnet source");
+    };
+    assert_eq!(jsonnet.evaluator, "jsonnet");
+    assert_eq!(jsonnet.entry_file(), PathBuf::from("main.libsonnet"));
+    assert_eq!(jsonnet.url(), "./lib/platform");
+
+    let mut invalid = jsonnet.clone();
+    invalid.entry = Some(PathBuf::from("README.md"));
+    assert!(invalid.validate().is_err());
+
+    let mut invalid = jsonnet.clone();
+    invalid.output_path = invalid.path.clone();
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn test_crd_source_samples() {
+    let source: Source = serde_yaml::from_str(
+        r#"
+type: crd
+name: operator
+git:
+  url: https://github.com/example/operator.git
+filters: []
+output_path: ./generated/operator
+samples:
+  - config/samples/*.yaml
+"#,
+    )
+    .unwrap();
+    assert!(source.validate().is_ok());
+    let Source::Crd(crd) = &source else {
+        panic!("expected a CRD source");
+    };
+    assert_eq!(crd.samples, ["config/samples/*.yaml"]);
+
+    for pattern in [
+        "/etc/samples/*.yaml",
+        "../samples/*.yaml",
+        "config/[samples",
+    ] {
+        let mut invalid = crd.clone();
+        invalid.samples = vec![pattern.to_string()];
+        assert!(invalid.validate().is_err(), "{pattern}");
+    }
+
+    let mut invalid = crd.clone();
+    invalid.plugin = serde_yaml::from_str("crd-plugin").unwrap();
+    assert!(invalid.validate().is_err());
+}
@@ -0,0 +1,12 @@
+//! Generated validation configuration
+
+use serde::{Deserialize, Serialize};
+
+/// Settings of the generated `_validation.libsonnet`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValidationConfig {
+    /// Check patterns, CIDRs and versions with the `std.native` functions of
+    /// the gensonnet-natives pack, which the Jsonnet VM must register
+    pub native_functions: bool,
+}
@@ -0,0 +1,86 @@
+//! Warning suppression and denial configuration
+
+use anyhow::{anyhow, Result};
+use jsonnet_generator::warning::{Warning, WarningCategory};
+use serde::{Deserialize, Serialize};
+
+/// Which warnings are dropped and which fail the run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WarningsConfig {
+    /// Known-benign warnings left out of results
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suppress: Vec<WarningSuppression>,
+
+    /// Categories whose warnings fail the run, once it has generated
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<WarningCategory>,
+}
+
+impl WarningsConfig {
+    pub fn validate(&self) -> Result<()> {
+        for suppression in &self.suppress {
+            suppression.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Whether a warning is suppressed
+    pub fn is_suppressed(&self, warning: &Warning) -> bool {
+        self.suppress
+            .iter()
+            .any(|suppression| suppression.matches(warning))
+    }
+
+    /// Whether a warning fails the run
+    pub fn is_denied(&self, warning: &Warning) -> bool {
+        self.deny.contains(&warning.category)
+    }
+}
+
+/// Warnings to suppress, matched by code and schema path; a suppression
+/// matches the warnings satisfying all of its fields
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WarningSuppression {
+    /// Code of the warnings, e.g. `unresolved-type`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+
+    /// Glob over the schema path of the warnings, e.g. `Certificate.spec.*`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// Source that raised the warnings; any source when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+impl WarningSuppression {
+    pub fn validate(&self) -> Result<()> {
+        if self.code.is_none() && self.path.is_none() {
+            return Err(anyhow!(
+                "Warning suppressions need a code or a path, so they cannot hide every warning"
+            ));
+        }
+        if let Some(path) = &self.path {
+            glob::Pattern::new(path)
+                .map_err(|e| anyhow!("Invalid warning suppression path '{}': {}", path, e))?;
+        }
+        Ok(())
+    }
+
+    /// Whether the suppression covers a warning
+    pub fn matches(&self, warning: &Warning) -> bool {
+        self.code.as_ref().is_none_or(|code| *code == warning.code)
+            && self
+                .source
+                .as_ref()
+                .is_none_or(|source| *source == warning.source)
+            && self.path.as_ref().is_none_or(|pattern| {
+                warning.path.as_deref().is_some_and(|path| {
+                    glob::Pattern::new(pattern).is_ok_and(|pattern| pattern.matches(path))
+                })
+            })
+    }
+}
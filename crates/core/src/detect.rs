@@ -0,0 +1,323 @@
+//! Detection of the kinds of content in a repository
+//!
+//! `type: auto` sources scan their repository and let the loaded plugins
+//! vote on every file: each file goes to the plugin that would process it,
+//! as with `plugin: auto`, if that plugin's score reaches the source's
+//! `min_score`. Every kind of content found becomes a logical source named
+//! `<source>/<kind>`, generated into a subdirectory of the output path.
+
+use crate::config::{AutoSource, CrdSource, GoAstSource, OpenApiSource, Source};
+use crate::plugin::{PluginBinding, PluginId, PluginManager};
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+use walkdir::WalkDir;
+
+/// Kind of content a logical source is created for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ContentKind {
+    /// Kubernetes CustomResourceDefinitions
+    Crd,
+
+    /// Go packages
+    GoAst,
+
+    /// OpenAPI/Swagger specifications
+    OpenApi,
+}
+
+impl ContentKind {
+    /// Kind of the content a plugin supporting these source types processes
+    fn from_supported_types(supported_types: &[String]) -> Option<Self> {
+        let supports = |names: &[&str]| {
+            supported_types
+                .iter()
+                .any(|supported| names.contains(&supported.as_str()))
+        };
+        if supports(&["crd"]) {
+            Some(ContentKind::Crd)
+        } else if supports(&["go", "golang"]) {
+            Some(ContentKind::GoAst)
+        } else if supports(&["openapi", "swagger"]) {
+            Some(ContentKind::OpenApi)
+        } else {
+            None
+        }
+    }
+
+    /// Name of the kind, as used for source types and subdirectories
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentKind::Crd => "crd",
+            ContentKind::GoAst => "go_ast",
+            ContentKind::OpenApi => "openapi",
+        }
+    }
+}
+
+impl fmt::Display for ContentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Content of one kind found in a repository
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedContent {
+    /// Kind of the content
+    pub kind: ContentKind,
+
+    /// Files of this kind relative to the repository root, sorted
+    pub files: Vec<PathBuf>,
+
+    /// Plugins that claimed the files
+    pub plugins: BTreeSet<PluginId>,
+}
+
+impl DetectedContent {
+    /// Identifier of the logical source within its auto source
+    pub fn source_id(&self, source_name: &str) -> String {
+        format!("{}/{}", source_name, self.kind)
+    }
+}
+
+/// Classify the files of a repository by the plugin that claims them
+pub async fn detect(
+    repo_path: &Path,
+    plugins: &PluginManager,
+    exclude_patterns: &[String],
+    min_score: u32,
+) -> Result<Vec<DetectedContent>> {
+    let excludes = exclude_patterns
+        .iter()
+        .map(|pattern| {
+            glob::Pattern::new(pattern)
+                .map_err(|e| anyhow!("Invalid exclude pattern '{}': {}", pattern, e))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut kinds: HashMap<PluginId, Option<ContentKind>> = HashMap::new();
+    let mut detected: BTreeMap<ContentKind, DetectedContent> = BTreeMap::new();
+
+    let files = WalkDir::new(repo_path)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file());
+    for entry in files {
+        let relative = entry
+            .path()
+            .strip_prefix(repo_path)
+            .unwrap_or(entry.path())
+            .to_path_buf();
+        if excludes
+            .iter()
+            .any(|pattern| pattern.matches_path(&relative))
+        {
+            continue;
+        }
+
+        let Some((plugin_id, score)) = plugins.best_plugin(entry.path()).await? else {
+            continue;
+        };
+        if score < min_score {
+            debug!(
+                "Not classifying {:?}: {} scored {} (below {})",
+                relative, plugin_id, score, min_score
+            );
+            continue;
+        }
+
+        if !kinds.contains_key(&plugin_id) {
+            let kind = match plugins.get_plugin(&plugin_id).await {
+                Some(plugin) => {
+                    ContentKind::from_supported_types(&plugin.metadata().supported_types)
+                }
+                None => None,
+            };
+            if kind.is_none() {
+                debug!("Plugin {} does not produce a known source type", plugin_id);
+            }
+            kinds.insert(plugin_id.clone(), kind);
+        }
+        let Some(kind) = kinds[&plugin_id] else {
+            continue;
+        };
+
+        let content = detected.entry(kind).or_insert_with(|| DetectedContent {
+            kind,
+            files: Vec::new(),
+            plugins: BTreeSet::new(),
+        });
+        content.files.push(relative);
+        content.plugins.insert(plugin_id);
+    }
+
+    Ok(detected
+        .into_values()
+        .map(|mut content| {
+            content.files.sort();
+            content
+        })
+        .collect())
+}
+
+/// The logical source generating detected content
+pub fn logical_source(auto_source: &AutoSource, content: &DetectedContent) -> Source {
+    let name = content.source_id(&auto_source.name);
+    let output_path = auto_source.output_path.join(content.kind.as_str());
+    let include_patterns: Vec<String> = content
+        .files
+        .iter()
+        .map(|file| glob::Pattern::escape(&file.to_string_lossy()))
+        .collect();
+
+    match content.kind {
+        ContentKind::Crd => Source::Crd(CrdSource {
+            name,
+            git: auto_source.git.clone(),
+            filters: auto_source.filters.clone(),
+            versions: auto_source.versions,
+            output_path,
+            plugin: PluginBinding::Auto,
+            timeout_seconds: None,
+            tags: auto_source.tags.clone(),
+            transforms: auto_source.transforms.clone(),
+            samples: Vec::new(),
+        }),
+        ContentKind::GoAst => Source::GoAst(GoAstSource {
+            name,
+            git: auto_source.git.clone(),
+            include_patterns,
+            exclude_patterns: Vec::new(),
+            output_path,
+            package_filters: None,
+            plugin: PluginBinding::Auto,
+            timeout_seconds: None,
+            tags: auto_source.tags.clone(),
+            transforms: auto_source.transforms.clone(),
+        }),
+        ContentKind::OpenApi => Source::OpenApi(OpenApiSource {
+            name,
+            git: auto_source.git.clone(),
+            include_patterns,
+            exclude_patterns: Vec::new(),
+            output_path,
+            openapi_version: None,
+            include_examples: None,
+            include_descriptions: None,
+            base_url: None,
+            include_callbacks: false,
+            plugin: PluginBinding::Auto,
+            timeout_seconds: None,
+            tags: auto_source.tags.clone(),
+            transforms: auto_source.transforms.clone(),
+        }),
+    }
+}
+
+#[cfg(all(test, feature = "go-ast", feature = "openapi"))]
+mod tests {
+    use super::*;
+    use crate::plugin::PluginConfig;
+    use tempfile::TempDir;
+
+    async fn builtin_plugins() -> PluginManager {
+        let manager = PluginManager::new();
+        manager
+            .register_factory(
+                "crd".to_string(),
+                Box::new(crate::plugin::crd::CrdPluginFactory),
+            )
+            .await;
+        manager
+            .register_factory(
+                "go-ast".to_string(),
+                Box::new(crate::plugin::ast::GoAstPluginFactory),
+            )
+            .await;
+        manager
+            .register_factory(
+                "openapi".to_string(),
+                Box::new(crate::plugin::openapi::OpenApiPluginFactory),
+            )
+            .await;
+        for plugin_type in ["crd", "go-ast", "openapi"] {
+            let config = PluginConfig {
+                plugin_id: format!("{plugin_type}:builtin"),
+                config: serde_yaml::Value::Null,
+                enabled_capabilities: Vec::new(),
+            };
+            manager.create_plugin(plugin_type, config).await.unwrap();
+        }
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_detect_repository_contents() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path();
+        let write = |path: &str, content: &str| {
+            let path = repo.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "config/crd/widgets.yaml",
+            "apiVersion: apiextensions.k8s.io/v1\nkind: CustomResourceDefinition\n",
+        );
+        write("api/openapi.yaml", "openapi: 3.0.0\ninfo: {title: API}\n");
+        write("api/v1/types.go", "// Package v1\npackage v1\n");
+        write("api/v1/types_test.go", "package v1\n");
+        write("vendor/k8s.io/types.go", "package types\n");
+        write(".github/workflows/ci.yaml", "openapi: 3.0.0\n");
+        write("deploy/values.yaml", "replicas: 1\n");
+
+        let plugins = builtin_plugins().await;
+        let exclude = vec!["vendor/**".to_string(), "**/*_test.go".to_string()];
+        let detected = detect(repo, &plugins, &exclude, 2).await.unwrap();
+        let files: Vec<(ContentKind, Vec<PathBuf>)> = detected
+            .iter()
+            .map(|content| (content.kind, content.files.clone()))
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                (
+                    ContentKind::Crd,
+                    vec![PathBuf::from("config/crd/widgets.yaml")]
+                ),
+                (ContentKind::GoAst, vec![PathBuf::from("api/v1/types.go")]),
+                (
+                    ContentKind::OpenApi,
+                    vec![PathBuf::from("api/openapi.yaml")]
+                ),
+            ]
+        );
+        assert_eq!(detected[0].source_id("upstream"), "upstream/crd");
+
+        // Files only claimed by their extension count with a lower min_score
+        let detected = detect(repo, &plugins, &exclude, 1).await.unwrap();
+        assert!(detected[2]
+            .files
+            .contains(&PathBuf::from("deploy/values.yaml")));
+
+        let auto_source: AutoSource = serde_yaml::from_str(
+            "name: upstream\ngit: {url: https://github.com/example/repo.git}\noutput_path: ./generated/upstream\n",
+        )
+        .unwrap();
+        assert!(auto_source.validate().is_ok());
+        assert_eq!(auto_source.min_score, 2);
+        let Source::GoAst(go_source) = logical_source(&auto_source, &detected[1]) else {
+            panic!("expected a Go AST source");
+        };
+        assert_eq!(go_source.name, "upstream/go_ast");
+        assert_eq!(go_source.include_patterns, vec!["api/v1/types.go"]);
+        assert_eq!(
+            go_source.output_path,
+            PathBuf::from("./generated/upstream/go_ast")
+        );
+    }
+}
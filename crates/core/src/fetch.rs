@@ -0,0 +1,400 @@
+//! Fetching of schema artifacts from URLs with checksum verification
+//!
+//! Artifacts are single manifests or `.tar`, `.tar.gz` and `.tgz` archives of
+//! manifests. Archives are verified as downloaded, then unpacked next to the
+//! archive file.
+
+use crate::config::{RetryPolicy, UrlSource};
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::info;
+
+/// A downloaded artifact whose digest has been verified
+#[derive(Debug, Clone)]
+pub struct FetchedArtifact {
+    /// Directory containing the artifact
+    pub dir: PathBuf,
+
+    /// Path of the artifact file, the archive itself for archives
+    pub file: PathBuf,
+
+    /// Digest of the artifact contents (`sha256:<hex>`), of the archive itself for archives
+    pub digest: String,
+}
+
+/// A header authenticating a download
+///
+/// The token is never printed, so credentials can be passed around freely.
+#[derive(Clone)]
+pub struct Credentials {
+    /// Header carrying the token; `Authorization` sends it as a bearer token
+    pub header: &'static str,
+
+    /// The token
+    pub token: String,
+
+    /// Media type to accept, for API URLs serving an asset's metadata by default
+    pub accept: Option<&'static str>,
+}
+
+pub struct UrlFetcher {
+    cache_dir: PathBuf,
+    #[cfg_attr(not(feature = "http"), allow(dead_code))]
+    retry_policy: RetryPolicy,
+    retry_counts: Mutex<HashMap<String, u32>>,
+    fetched: Mutex<HashMap<String, FetchedArtifact>>,
+}
+
+impl UrlFetcher {
+    /// Create a new UrlFetcher instance
+    pub fn new() -> Result<Self> {
+        let cache_dir = dirs::cache_dir()
+            .ok_or_else(|| anyhow!("Could not determine cache directory"))?
+            .join("gensonnet")
+            .join("url");
+        std::fs::create_dir_all(&cache_dir)?;
+
+        Ok(Self {
+            cache_dir,
+            retry_policy: RetryPolicy::default(),
+            retry_counts: Mutex::new(HashMap::new()),
+            fetched: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Use the given retry policy for downloads
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Take the number of retries performed for a URL since the last call
+    pub fn take_retry_count(&self, url: &str) -> u32 {
+        self.retry_counts.lock().unwrap().remove(url).unwrap_or(0)
+    }
+
+    /// Fetch the artifact for a URL source, verifying it against `expected` when given
+    ///
+    /// Artifacts are downloaded at most once per fetcher. Nothing is written to
+    /// the cache unless the digest matches.
+    pub async fn fetch(
+        &self,
+        source: &UrlSource,
+        expected: Option<&str>,
+    ) -> Result<FetchedArtifact> {
+        self.fetch_url(&source.url, expected).await
+    }
+
+    /// Fetch the artifact behind a URL, verifying it against `expected` when given
+    pub async fn fetch_url(&self, url: &str, expected: Option<&str>) -> Result<FetchedArtifact> {
+        self.fetch_authenticated(url, url, None, expected).await
+    }
+
+    /// Fetch the artifact behind a URL with credentials, naming it after `name`
+    ///
+    /// `name` is the URL or file name the artifact is stored under, and decides
+    /// whether it is unpacked as an archive.
+    pub async fn fetch_authenticated(
+        &self,
+        url: &str,
+        name: &str,
+        credentials: Option<&Credentials>,
+        expected: Option<&str>,
+    ) -> Result<FetchedArtifact> {
+        let cached = self.fetched.lock().unwrap().get(url).cloned();
+        if let Some(artifact) = cached {
+            if let Some(expected) = expected {
+                verify_digest(url, expected, &artifact.digest)?;
+            }
+            return Ok(artifact);
+        }
+
+        info!("Fetching {}", jsonnet_lockfile::redact_url(url));
+        let bytes = self.download(url, credentials).await?;
+        let digest = sha256_digest(&bytes);
+        if let Some(expected) = expected {
+            verify_digest(url, expected, &digest)?;
+        }
+
+        let dir = self.artifact_dir(url);
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)?;
+        }
+        std::fs::create_dir_all(&dir)?;
+        let file = dir.join(artifact_file_name(name));
+        std::fs::write(&file, &bytes)?;
+        if let Some(format) = ArchiveFormat::detect(name) {
+            format
+                .unpack(&bytes, &dir)
+                .map_err(|e| anyhow!("Failed to unpack {}: {}", url, e))?;
+        }
+
+        let artifact = FetchedArtifact { dir, file, digest };
+        self.fetched
+            .lock()
+            .unwrap()
+            .insert(url.to_string(), artifact.clone());
+
+        Ok(artifact)
+    }
+
+    /// Get the cache directory for a URL
+    fn artifact_dir(&self, url: &str) -> PathBuf {
+        let hash = hex::encode(Sha256::digest(url.as_bytes()));
+        self.cache_dir.join(&hash[..16])
+    }
+
+    /// Read the raw bytes behind a URL
+    async fn download(&self, url: &str, credentials: Option<&Credentials>) -> Result<Vec<u8>> {
+        if let Some(path) = url.strip_prefix("file://") {
+            return std::fs::read(Path::new(path))
+                .map_err(|e| anyhow!("Failed to read {}: {}", url, e));
+        }
+
+        self.download_http(url, credentials).await
+    }
+
+    #[cfg(feature = "http")]
+    async fn download_http(&self, url: &str, credentials: Option<&Credentials>) -> Result<Vec<u8>> {
+        let client = reqwest::Client::new();
+        let mut retries = 0;
+
+        loop {
+            let mut request = client.get(url).header("User-Agent", "gensonnet");
+            if let Some(credentials) = credentials {
+                request = match credentials.header {
+                    "Authorization" => request.bearer_auth(&credentials.token),
+                    header => request.header(header, &credentials.token),
+                };
+                if let Some(accept) = credentials.accept {
+                    request = request.header("Accept", accept);
+                }
+            }
+            let attempt = async {
+                let response = request.send().await?.error_for_status()?;
+                response.bytes().await
+            };
+
+            match attempt.await {
+                Ok(bytes) => return Ok(bytes.to_vec()),
+                Err(e) if retries < self.retry_policy.max_retries && is_transient(&e) => {
+                    retries += 1;
+                    *self
+                        .retry_counts
+                        .lock()
+                        .unwrap()
+                        .entry(url.to_string())
+                        .or_default() += 1;
+
+                    let delay = self.retry_policy.backoff(retries);
+                    tracing::warn!(
+                        "Download of {} failed ({}), retry {}/{} in {:?}",
+                        jsonnet_lockfile::redact_url(url),
+                        e,
+                        retries,
+                        self.retry_policy.max_retries,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => {
+                    return Err(anyhow!(
+                        "Failed to download {}: {}",
+                        jsonnet_lockfile::redact_url(url),
+                        e
+                    ))
+                }
+            }
+        }
+    }
+
+    #[cfg(not(feature = "http"))]
+    async fn download_http(
+        &self,
+        url: &str,
+        _credentials: Option<&Credentials>,
+    ) -> Result<Vec<u8>> {
+        Err(anyhow!(
+            "Cannot download {}: gensonnet was built without the `http` feature",
+            url
+        ))
+    }
+}
+
+/// Whether an HTTP error is likely to succeed on retry
+#[cfg(feature = "http")]
+pub(crate) fn is_transient(error: &reqwest::Error) -> bool {
+    error.is_connect()
+        || error.is_timeout()
+        || error
+            .status()
+            .is_some_and(|status| status.is_server_error())
+}
+
+/// Compute the `sha256:<hex>` digest of some bytes
+pub fn sha256_digest(bytes: &[u8]) -> String {
+    format!("sha256:{}", hex::encode(Sha256::digest(bytes)))
+}
+
+/// Check a digest against an expected value, with or without the `sha256:` prefix
+pub fn verify_digest(url: &str, expected: &str, actual: &str) -> Result<()> {
+    let expected_hex = expected.strip_prefix("sha256:").unwrap_or(expected);
+    let actual_hex = actual.strip_prefix("sha256:").unwrap_or(actual);
+
+    if !expected_hex.eq_ignore_ascii_case(actual_hex) {
+        return Err(anyhow!(
+            "Checksum mismatch for {}: expected sha256:{}, got sha256:{}. Refusing to generate from unverified input",
+            url,
+            expected_hex.to_ascii_lowercase(),
+            actual_hex
+        ));
+    }
+
+    Ok(())
+}
+
+/// Archive formats an artifact can be unpacked from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Tar,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Format of the artifact behind a URL or file name, judging by the name
+    pub fn detect(url: &str) -> Option<Self> {
+        let name = url_file_name(url);
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
+
+    /// Unpack an archive into a directory
+    ///
+    /// Entries that would land outside the directory are skipped.
+    fn unpack(self, bytes: &[u8], dir: &Path) -> std::io::Result<()> {
+        match self {
+            Self::Tar => tar::Archive::new(bytes).unpack(dir),
+            Self::TarGz => tar::Archive::new(flate2::read::GzDecoder::new(bytes)).unpack(dir),
+        }
+    }
+}
+
+/// Last path segment of a URL, without query or fragment
+fn url_file_name(url: &str) -> &str {
+    url.split(['?', '#'])
+        .next()
+        .and_then(|path| path.rsplit('/').next())
+        .unwrap_or_default()
+}
+
+/// File name to store a downloaded artifact named after a URL or file name under
+fn artifact_file_name(url: &str) -> String {
+    let name = url_file_name(url);
+
+    if name.ends_with(".yaml")
+        || name.ends_with(".yml")
+        || name.ends_with(".json")
+        || ArchiveFormat::detect(url).is_some()
+    {
+        name.to_string()
+    } else {
+        "artifact.yaml".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn url_source(url: String, sha256: Option<String>) -> UrlSource {
+        UrlSource {
+            name: "test".to_string(),
+            url,
+            sha256,
+            filters: vec![],
+            versions: Default::default(),
+            output_path: PathBuf::from("./output"),
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_verifies_pin() {
+        let temp_dir = TempDir::new().unwrap();
+        let artifact = temp_dir.path().join("crds.yaml");
+        std::fs::write(&artifact, "kind: CustomResourceDefinition\n").unwrap();
+        let url = format!("file://{}", artifact.display());
+        let digest = sha256_digest(b"kind: CustomResourceDefinition\n");
+
+        let fetcher = UrlFetcher::new().unwrap();
+        let fetched = fetcher
+            .fetch(&url_source(url.clone(), None), Some(&digest))
+            .await
+            .unwrap();
+        assert_eq!(fetched.digest, digest);
+        assert_eq!(fetched.file, fetched.dir.join("crds.yaml"));
+        assert!(fetched.file.exists());
+
+        let tampered = UrlFetcher::new().unwrap();
+        let wrong = sha256_digest(b"something else");
+        let err = tampered
+            .fetch(&url_source(url, None), Some(&wrong))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_unpacks_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest = b"kind: CustomResourceDefinition\n";
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(manifest.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "crds/widgets.yaml", &manifest[..])
+            .unwrap();
+        let archive = builder.into_inner().unwrap().finish().unwrap();
+        let path = temp_dir.path().join("crds.tar.gz");
+        std::fs::write(&path, &archive).unwrap();
+        let url = format!("file://{}", path.display());
+        assert_eq!(ArchiveFormat::detect(&url), Some(ArchiveFormat::TarGz));
+
+        let fetcher = UrlFetcher::new().unwrap();
+        let fetched = fetcher
+            .fetch(&url_source(url, None), Some(&sha256_digest(&archive)))
+            .await
+            .unwrap();
+        assert_eq!(fetched.digest, sha256_digest(&archive));
+        assert_eq!(fetched.file, fetched.dir.join("crds.tar.gz"));
+        assert_eq!(
+            std::fs::read(fetched.dir.join("crds/widgets.yaml")).unwrap(),
+            manifest
+        );
+    }
+
+    #[test]
+    fn test_verify_digest_prefix() {
+        let digest = sha256_digest(b"abc");
+        let hex = digest.strip_prefix("sha256:").unwrap();
+        assert!(verify_digest("file:///x", hex, &digest).is_ok());
+        assert!(verify_digest("file:///x", &hex.to_uppercase(), &digest).is_ok());
+        assert!(verify_digest("file:///x", &sha256_digest(b"abd"), &digest).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+//! Built-in fixture schemas
+//!
+//! `fixture` sources generate from a few example CRDs or an example OpenAPI
+//! specification embedded in the binary. `init --demo` configures them, so
+//! a first `generate` works offline, and tests use them instead of cloning
+//! repositories.
+
+use crate::config::FixtureSet;
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Example CRDs, as (file name, contents)
+const CRDS: &[(&str, &str)] = &[
+    ("widget.yaml", include_str!("../fixtures/crds/widget.yaml")),
+    ("gadget.yaml", include_str!("../fixtures/crds/gadget.yaml")),
+];
+
+/// Example OpenAPI specifications, as (file name, contents)
+const OPENAPI: &[(&str, &str)] = &[(
+    "petstore.yaml",
+    include_str!("../fixtures/openapi/petstore.yaml"),
+)];
+
+/// A fixture set written out to disk
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    /// Directory holding the fixture files
+    pub dir: PathBuf,
+
+    /// Paths of the fixture files
+    pub files: Vec<PathBuf>,
+
+    /// Digest of the fixture set (`sha256:<hex>`), standing in for a commit
+    pub digest: String,
+}
+
+/// Files of a fixture set, as (file name, contents)
+pub fn files(set: FixtureSet) -> &'static [(&'static str, &'static str)] {
+    match set {
+        FixtureSet::Crds => CRDS,
+        FixtureSet::OpenApi => OPENAPI,
+    }
+}
+
+/// Digest of a fixture set's file names and contents
+pub fn digest(set: FixtureSet) -> String {
+    let mut hasher = Sha256::new();
+    for (name, contents) in files(set) {
+        hasher.update(name.as_bytes());
+        hasher.update([0]);
+        hasher.update(contents.as_bytes());
+        hasher.update([0]);
+    }
+    format!("sha256:{}", hex::encode(hasher.finalize()))
+}
+
+/// Write a fixture set under `root`, in a directory named after its digest
+///
+/// Sets already written are reused.
+pub fn materialize(set: FixtureSet, root: &Path) -> Result<Fixture> {
+    let digest = digest(set);
+    let dir = root
+        .join(set.as_str())
+        .join(&digest["sha256:".len()..][..16]);
+    std::fs::create_dir_all(&dir)?;
+
+    let mut paths = Vec::new();
+    for (name, contents) in files(set) {
+        let path = dir.join(name);
+        if std::fs::read_to_string(&path).ok().as_deref() != Some(*contents) {
+            std::fs::write(&path, contents)?;
+        }
+        paths.push(path);
+    }
+    Ok(Fixture {
+        dir,
+        files: paths,
+        digest,
+    })
+}
+
+/// Directory fixture sets are written to
+pub fn cache_root() -> Result<PathBuf> {
+    Ok(dirs::cache_dir()
+        .ok_or_else(|| anyhow!("Could not determine cache directory"))?
+        .join("gensonnet")
+        .join("fixtures"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonnet_crd::CrdParser;
+
+    #[test]
+    fn test_materialize_crds() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let fixture = materialize(FixtureSet::Crds, temp_dir.path()).unwrap();
+        assert_eq!(fixture.files.len(), 2);
+        assert_eq!(fixture.digest, digest(FixtureSet::Crds));
+        assert_ne!(fixture.digest, digest(FixtureSet::OpenApi));
+
+        let schemas = CrdParser::new()
+            .parse_from_directory(&fixture.dir, &["demo.gensonnet.dev/v1".to_string()])
+            .unwrap();
+        assert_eq!(schemas.len(), 1);
+        assert_eq!(schemas[0].kind, "Widget");
+
+        // Writing the set again reuses the same directory
+        let again = materialize(FixtureSet::Crds, temp_dir.path()).unwrap();
+        assert_eq!(again.dir, fixture.dir);
+    }
+}
@@ -0,0 +1,261 @@
+//! Frozen public API of the generated libraries
+//!
+//! `freeze` records the functions and fields of every source's library,
+//! read from its `_arguments.json` and `_fields.json`, in `api-freeze.json`
+//! next to the lockfile. Later generations compare each library against it
+//! before publishing it and fail on breaking changes, so an upstream change
+//! cannot silently break downstream users; additions are always allowed.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Name of the freeze file, next to the lockfile
+pub const FREEZE_FILE: &str = "api-freeze.json";
+
+/// Public API of the generated libraries, by source
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApiSnapshot {
+    /// When the snapshot was frozen
+    pub frozen_at: DateTime<Utc>,
+
+    /// API of each source's library
+    pub sources: BTreeMap<String, SourceApi>,
+}
+
+/// Public API of one source's library
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceApi {
+    /// Parameters of each function, by `<apiVersion> <Kind>.<function>`
+    pub functions: BTreeMap<String, Vec<String>>,
+
+    /// Fields, by `<apiVersion> <Kind> <path>`
+    pub fields: BTreeMap<String, FieldApi>,
+}
+
+/// A field of the public API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldApi {
+    /// Schema type of the field
+    #[serde(rename = "type")]
+    pub field_type: String,
+
+    /// Whether resources must set the field
+    pub required: bool,
+}
+
+/// Entry of `_arguments.json`
+#[derive(Deserialize)]
+struct FunctionEntry {
+    function: String,
+    kind: String,
+    #[serde(rename = "apiVersion")]
+    api_version: String,
+    parameters: Vec<String>,
+}
+
+/// Entry of `_fields.json`
+#[derive(Deserialize)]
+struct FieldEntry {
+    kind: String,
+    #[serde(rename = "apiVersion")]
+    api_version: String,
+    path: String,
+    #[serde(rename = "type")]
+    field_type: String,
+    required: bool,
+}
+
+impl SourceApi {
+    /// Read the API of the libraries below a source's output path
+    pub fn read(output_path: &Path) -> Result<Self> {
+        let mut api = Self::default();
+        api.read_dir(output_path, None)?;
+        Ok(api)
+    }
+
+    /// Read the API a source's library will have once the files staged in
+    /// `staged` are published to `target`, a directory below its output path
+    ///
+    /// The files published below `target` are replaced by the staged ones.
+    pub fn read_staged(output_path: &Path, target: &Path, staged: &Path) -> Result<Self> {
+        let mut api = Self::default();
+        if output_path != target {
+            api.read_dir(output_path, Some(target))?;
+        }
+        api.read_dir(staged, None)?;
+        Ok(api)
+    }
+
+    /// Add the API listed below a directory, skipping the `skip` directory
+    fn read_dir(&mut self, dir: &Path, skip: Option<&Path>) -> Result<()> {
+        for entry in WalkDir::new(dir)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| Some(entry.path()) != skip)
+            .filter_map(|entry| entry.ok())
+        {
+            let path = entry.path();
+            match entry.file_name().to_str() {
+                Some(jsonnet_generator::arguments::ARGUMENTS_FILE) => {
+                    let entries: Vec<FunctionEntry> =
+                        serde_json::from_str(&std::fs::read_to_string(path)?)?;
+                    for entry in entries {
+                        self.functions.insert(
+                            format!("{} {}.{}", entry.api_version, entry.kind, entry.function),
+                            entry.parameters,
+                        );
+                    }
+                }
+                Some(jsonnet_generator::fields::FIELDS_INDEX_FILE) => {
+                    let entries: Vec<FieldEntry> =
+                        serde_json::from_str(&std::fs::read_to_string(path)?)?;
+                    for entry in entries {
+                        self.fields.insert(
+                            format!("{} {} {}", entry.api_version, entry.kind, entry.path),
+                            FieldApi {
+                                field_type: entry.field_type,
+                                required: entry.required,
+                            },
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ApiSnapshot {
+    /// Load a freeze file, if it exists
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&std::fs::read_to_string(path)?)?))
+    }
+
+    /// Write the snapshot to a freeze file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+
+    /// Changes from this frozen API to `current` that break its users
+    ///
+    /// Removed functions and fields, removed parameters, changed field types
+    /// and fields becoming required are breaking. Sources missing from
+    /// `current`, e.g. because they were removed from the configuration, are
+    /// not compared.
+    pub fn breaking_changes(&self, current: &ApiSnapshot) -> Vec<String> {
+        let mut changes = Vec::new();
+        for (source, frozen) in &self.sources {
+            let Some(api) = current.sources.get(source) else {
+                continue;
+            };
+
+            for (function, parameters) in &frozen.functions {
+                match api.functions.get(function) {
+                    None => changes.push(format!("{source}: function {function} was removed")),
+                    Some(current) => {
+                        for parameter in parameters.iter().filter(|p| !current.contains(p)) {
+                            changes.push(format!(
+                                "{source}: function {function} no longer takes {parameter}"
+                            ));
+                        }
+                    }
+                }
+            }
+
+            for (field, frozen) in &frozen.fields {
+                match api.fields.get(field) {
+                    None => changes.push(format!("{source}: field {field} was removed")),
+                    Some(current) => {
+                        if current.field_type != frozen.field_type {
+                            changes.push(format!(
+                                "{source}: field {field} changed type from {} to {}",
+                                frozen.field_type, current.field_type
+                            ));
+                        }
+                        if current.required && !frozen.required {
+                            changes.push(format!("{source}: field {field} became required"));
+                        }
+                    }
+                }
+            }
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breaking_changes() {
+        let field = |field_type: &str, required: bool| FieldApi {
+            field_type: field_type.to_string(),
+            required,
+        };
+        let frozen = ApiSnapshot {
+            frozen_at: Utc::now(),
+            sources: BTreeMap::from([
+                (
+                    "widgets".to_string(),
+                    SourceApi {
+                        functions: BTreeMap::from([
+                            ("v1 Widget.withSize".to_string(), vec!["size".to_string()]),
+                            ("v1 Widget.withColor".to_string(), vec!["color".to_string()]),
+                        ]),
+                        fields: BTreeMap::from([
+                            ("v1 Widget spec.size".to_string(), field("string", false)),
+                            ("v1 Widget spec.count".to_string(), field("integer", false)),
+                            ("v1 Widget spec.color".to_string(), field("string", false)),
+                        ]),
+                    },
+                ),
+                ("removed".to_string(), SourceApi::default()),
+            ]),
+        };
+
+        // Additions are not breaking
+        let mut current = frozen.clone();
+        current.sources.remove("removed");
+        let widgets = current.sources.get_mut("widgets").unwrap();
+        widgets
+            .functions
+            .insert("v1 Widget.withShape".to_string(), vec!["shape".to_string()]);
+        widgets
+            .fields
+            .insert("v1 Widget spec.shape".to_string(), field("string", true));
+        assert!(frozen.breaking_changes(&current).is_empty());
+
+        let widgets = current.sources.get_mut("widgets").unwrap();
+        widgets.functions.remove("v1 Widget.withColor");
+        widgets.fields.remove("v1 Widget spec.color");
+        widgets
+            .functions
+            .insert("v1 Widget.withSize".to_string(), vec!["value".to_string()]);
+        widgets
+            .fields
+            .insert("v1 Widget spec.size".to_string(), field("integer", false));
+        widgets
+            .fields
+            .insert("v1 Widget spec.count".to_string(), field("integer", true));
+        assert_eq!(
+            frozen.breaking_changes(&current),
+            [
+                "widgets: function v1 Widget.withColor was removed",
+                "widgets: function v1 Widget.withSize no longer takes size",
+                "widgets: field v1 Widget spec.color was removed",
+                "widgets: field v1 Widget spec.count became required",
+                "widgets: field v1 Widget spec.size changed type from string to integer",
+            ]
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//! Global generation hooks
+//!
+//! `hooks.before_all` commands run before any source is processed and get
+//! the plan of the run as JSON on stdin; `hooks.after_all` commands run once
+//! every source is processed and get the result. A hook exiting non-zero
+//! aborts the run, so organizations can gate generation on their own checks,
+//! such as requiring a change ticket.
+
+use crate::config::{HooksConfig, Source};
+use anyhow::{anyhow, Result};
+use jsonnet_generator::result::GenerationResult;
+use serde_json::json;
+use std::fmt;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tracing::info;
+
+/// Environment variable telling a hook which point of the run it is called at
+pub const HOOK_ENV: &str = "GENSONNET_HOOK";
+
+/// Point of a run at which hooks are called
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookPoint {
+    BeforeAll,
+    AfterAll,
+}
+
+impl HookPoint {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookPoint::BeforeAll => "before_all",
+            HookPoint::AfterAll => "after_all",
+        }
+    }
+}
+
+impl fmt::Display for HookPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Runs the configured global hooks
+#[derive(Debug, Clone, Default)]
+pub struct HookRunner {
+    config: HooksConfig,
+}
+
+impl HookRunner {
+    pub fn new(config: HooksConfig) -> Self {
+        Self { config }
+    }
+
+    /// Run the hooks of a point in order, stopping at the first that fails
+    pub async fn run(&self, point: HookPoint, context: &serde_json::Value) -> Result<()> {
+        let commands = match point {
+            HookPoint::BeforeAll => &self.config.before_all,
+            HookPoint::AfterAll => &self.config.after_all,
+        };
+        if commands.is_empty() {
+            return Ok(());
+        }
+
+        let mut input = serde_json::to_vec(context)?;
+        input.push(b'\n');
+        for command in commands {
+            info!("Running {} hook: {}", point, command);
+            self.run_command(point, command, &input).await?;
+        }
+        Ok(())
+    }
+
+    async fn run_command(&self, point: HookPoint, command: &str, input: &[u8]) -> Result<()> {
+        let mut child = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .env(HOOK_ENV, point.as_str())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| anyhow!("Failed to run {} hook `{}`: {}", point, command, e))?;
+
+        // A hook may exit without reading its input
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(input).await;
+        }
+
+        let timeout = Duration::from_secs(self.config.timeout_seconds);
+        let output = tokio::time::timeout(timeout, child.wait_with_output())
+            .await
+            .map_err(|_| {
+                anyhow!(
+                    "{} hook `{}` timed out after {}s",
+                    point,
+                    command,
+                    timeout.as_secs()
+                )
+            })??;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !stdout.trim().is_empty() {
+            info!("{} hook `{}`: {}", point, command, stdout.trim());
+        }
+        if !output.status.success() {
+            return Err(anyhow!(
+                "{} hook `{}` aborted generation ({}): {}",
+                point,
+                command,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Plan handed to `before_all` hooks
+///
+/// `mode` is `full`, `incremental` or `source`; `changed_sources` lists the
+/// sources an incremental run regenerates.
+pub fn plan_context(
+    command: &str,
+    mode: &str,
+    sources: &[&Source],
+    changed_sources: &[String],
+) -> serde_json::Value {
+    json!({
+        "hook": HookPoint::BeforeAll.as_str(),
+        "command": command,
+        "mode": mode,
+        "sources": sources
+            .iter()
+            .map(|source| json!({
+                "name": source.name(),
+                "type": source.source_type(),
+                "url": jsonnet_lockfile::redact_url(source.git_url()),
+                "ref": source.git_ref(),
+                "output_path": source.output_path(),
+            }))
+            .collect::<Vec<_>>(),
+        "changed_sources": changed_sources,
+    })
+}
+
+/// Result handed to `after_all` hooks
+pub fn result_context(command: &str, result: &GenerationResult) -> serde_json::Value {
+    json!({
+        "hook": HookPoint::AfterAll.as_str(),
+        "command": command,
+        "sources_processed": result.sources_processed,
+        "total_sources": result.total_sources,
+        "results": result
+            .results
+            .iter()
+            .map(|source_result| json!({
+                "name": source_result.source_name,
+                "type": source_result.source_type,
+                "output_path": source_result.output_path,
+                "files_generated": source_result.files_generated,
+                "errors": source_result.errors,
+                "warnings": source_result.warnings,
+            }))
+            .collect::<Vec<_>>(),
+        "statistics": result.statistics,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_hooks_receive_context_and_veto() {
+        let temp_dir = TempDir::new().unwrap();
+        let captured = temp_dir.path().join("plan.json");
+        let runner = HookRunner::new(HooksConfig {
+            before_all: vec![
+                format!("cat > {}", captured.display()),
+                "test \"$GENSONNET_HOOK\" = before_all".to_string(),
+            ],
+            after_all: vec!["echo 'missing change ticket' >&2; exit 1".to_string()],
+            timeout_seconds: 10,
+        });
+
+        let source: Source = serde_yaml::from_str(
+            "type: crd\nname: widgets\ngit: {url: \"https://token@github.com/example/widgets.git\", ref: v1}\nfilters: []\noutput_path: ./out\n",
+        )
+        .unwrap();
+        let context = plan_context("generate", "full", &[&source], &[]);
+        runner.run(HookPoint::BeforeAll, &context).await.unwrap();
+
+        let plan: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&captured).unwrap()).unwrap();
+        assert_eq!(plan["sources"][0]["name"], "widgets");
+        assert_eq!(
+            plan["sources"][0]["url"],
+            "https://github.com/example/widgets.git"
+        );
+        assert_eq!(plan["sources"][0]["ref"], "v1");
+
+        let err = runner
+            .run(HookPoint::AfterAll, &json!({}))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("after_all hook"));
+        assert!(err.contains("missing change ticket"));
+    }
+}
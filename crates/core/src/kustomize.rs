@@ -0,0 +1,298 @@
+//! Kustomize builds of CRD overlays
+//!
+//! Some upstreams only publish their CRDs through kustomize bases. The
+//! built-in builder follows a kustomization's `resources` (and legacy `bases`
+//! and `crds`) recursively, applies `commonLabels`, `commonAnnotations` and
+//! strategic-merge `patches` by merging maps, and returns the resulting
+//! documents. Anything it does not understand, such as remote bases or
+//! JSON 6902 patches, needs an external `kustomize build` instead.
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// File names a kustomization is read from, in order of precedence
+pub const KUSTOMIZATION_FILES: &[&str] =
+    &["kustomization.yaml", "kustomization.yml", "Kustomization"];
+
+/// The parts of a kustomization the built-in builder applies
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Kustomization {
+    #[serde(default)]
+    resources: Vec<String>,
+    #[serde(default)]
+    bases: Vec<String>,
+    #[serde(default)]
+    crds: Vec<String>,
+    #[serde(default)]
+    common_labels: Mapping,
+    #[serde(default)]
+    common_annotations: Mapping,
+    #[serde(default)]
+    patches: Vec<Patch>,
+    #[serde(default)]
+    patches_strategic_merge: Vec<String>,
+    #[serde(default)]
+    patches_json6902: Vec<Value>,
+}
+
+/// A `patches` entry, given inline or as a file
+#[derive(Debug, Deserialize)]
+struct Patch {
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    patch: Option<String>,
+}
+
+/// Build a kustomization directory with the built-in builder
+pub fn build(dir: &Path) -> Result<Vec<Value>> {
+    build_dir(dir, &mut HashSet::new())
+}
+
+fn build_dir(dir: &Path, visiting: &mut HashSet<PathBuf>) -> Result<Vec<Value>> {
+    let dir = dir
+        .canonicalize()
+        .map_err(|e| anyhow!("Kustomization directory {} not found: {}", dir.display(), e))?;
+    if !visiting.insert(dir.clone()) {
+        return Err(anyhow!("Kustomization cycle at {}", dir.display()));
+    }
+
+    let file = KUSTOMIZATION_FILES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| anyhow!("No kustomization file in {}", dir.display()))?;
+    let kustomization: Kustomization = serde_yaml::from_str(&std::fs::read_to_string(&file)?)
+        .map_err(|e| anyhow!("Invalid kustomization {}: {}", file.display(), e))?;
+
+    let mut documents = Vec::new();
+    let entries = kustomization
+        .crds
+        .iter()
+        .chain(&kustomization.bases)
+        .chain(&kustomization.resources);
+    for entry in entries {
+        if entry.contains("://") || entry.starts_with("github.com/") {
+            return Err(anyhow!(
+                "Remote resource {} in {} needs an external kustomize (set `command`)",
+                entry,
+                file.display()
+            ));
+        }
+
+        let path = dir.join(entry);
+        if path.is_dir() {
+            documents.extend(build_dir(&path, visiting)?);
+        } else {
+            documents.extend(read_documents(&path)?);
+        }
+    }
+
+    let mut patches = Vec::new();
+    for patch in &kustomization.patches {
+        match (&patch.path, &patch.patch) {
+            (Some(path), _) => patches.extend(read_documents(&dir.join(path))?),
+            (None, Some(inline)) => patches.extend(parse_documents(inline)?),
+            (None, None) => {}
+        }
+    }
+    for path in &kustomization.patches_strategic_merge {
+        patches.extend(read_documents(&dir.join(path))?);
+    }
+    for patch in patches {
+        // JSON 6902 operations have no kind and name, so they match nothing here
+        let Some(index) = documents.iter().position(|doc| same_resource(doc, &patch)) else {
+            warn!(
+                "Skipping patch in {} that matches no resource",
+                file.display()
+            );
+            continue;
+        };
+        merge(&mut documents[index], patch);
+    }
+    if !kustomization.patches_json6902.is_empty() {
+        warn!(
+            "Skipping patchesJson6902 in {}, which the built-in builder does not apply",
+            file.display()
+        );
+    }
+
+    for document in &mut documents {
+        add_metadata(document, "labels", &kustomization.common_labels);
+        add_metadata(document, "annotations", &kustomization.common_annotations);
+    }
+
+    visiting.remove(&dir);
+    Ok(documents)
+}
+
+/// Build a kustomization directory with an external command, e.g. `kustomize build`
+pub async fn build_with_command(command: &[String], dir: &Path) -> Result<Vec<Value>> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| anyhow!("Kustomize command cannot be empty"))?;
+
+    let output = tokio::process::Command::new(program)
+        .args(args)
+        .arg(dir)
+        // Don't leave the build running when the source times out
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| anyhow!("Failed to run kustomize command '{}': {}", program, e))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "'{}' failed for {}: {}",
+            command.join(" "),
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    parse_documents(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Write the CustomResourceDefinitions among built documents, one file each
+pub fn write_crds(documents: &[Value], dir: &Path) -> Result<usize> {
+    std::fs::create_dir_all(dir)?;
+
+    let crds: Vec<&Value> = documents
+        .iter()
+        .filter(|doc| doc.get("kind").and_then(Value::as_str) == Some("CustomResourceDefinition"))
+        .collect();
+    for (index, crd) in crds.iter().enumerate() {
+        std::fs::write(
+            dir.join(format!("{index:04}.yaml")),
+            serde_yaml::to_string(crd)?,
+        )?;
+    }
+
+    Ok(crds.len())
+}
+
+fn read_documents(path: &Path) -> Result<Vec<Value>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    parse_documents(&content).map_err(|e| anyhow!("Invalid YAML in {}: {}", path.display(), e))
+}
+
+/// Parse a multi-document YAML stream, skipping empty documents
+pub(crate) fn parse_documents(content: &str) -> Result<Vec<Value>> {
+    let mut documents = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(content) {
+        let value = Value::deserialize(document)?;
+        if !value.is_null() {
+            documents.push(value);
+        }
+    }
+    Ok(documents)
+}
+
+/// Whether two documents have the same kind and name
+fn same_resource(a: &Value, b: &Value) -> bool {
+    let key = |doc: &Value| {
+        (
+            doc.get("kind").and_then(Value::as_str).map(str::to_string),
+            doc.get("metadata")
+                .and_then(|metadata| metadata.get("name"))
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        )
+    };
+    let (kind, name) = key(b);
+    kind.is_some() && name.is_some() && key(a) == (kind, name)
+}
+
+/// Merge a patch into a document: maps merge recursively, null removes a key
+/// and any other value replaces the original
+fn merge(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Mapping(target), Value::Mapping(patch)) => {
+            for (key, value) in patch {
+                if value.is_null() {
+                    target.remove(&key);
+                } else if let Some(existing) = target.get_mut(&key) {
+                    merge(existing, value);
+                } else {
+                    target.insert(key, value);
+                }
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+/// Add entries to `metadata.<field>` without overwriting existing ones
+fn add_metadata(document: &mut Value, field: &str, entries: &Mapping) {
+    if entries.is_empty() {
+        return;
+    }
+    let Some(metadata) = document.get_mut("metadata").and_then(Value::as_mapping_mut) else {
+        return;
+    };
+
+    let values = metadata
+        .entry(Value::from(field))
+        .or_insert_with(|| Value::Mapping(Mapping::new()));
+    if let Value::Mapping(values) = values {
+        for (key, value) in entries {
+            values.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_build_overlay() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().join("base");
+        let overlay = temp_dir.path().join("overlay");
+        std::fs::create_dir_all(&base).unwrap();
+        std::fs::create_dir_all(&overlay).unwrap();
+
+        std::fs::write(
+            base.join("kustomization.yaml"),
+            "resources:\n  - crds.yaml\n",
+        )
+        .unwrap();
+        std::fs::write(
+            base.join("crds.yaml"),
+            "apiVersion: apiextensions.k8s.io/v1\nkind: CustomResourceDefinition\nmetadata:\n  name: widgets.example.com\nspec:\n  group: example.com\n  scope: Namespaced\n---\napiVersion: v1\nkind: ConfigMap\nmetadata:\n  name: settings\n",
+        )
+        .unwrap();
+        std::fs::write(
+            overlay.join("kustomization.yaml"),
+            "resources:\n  - ../base\ncommonLabels:\n  app: widgets\npatches:\n  - patch: |\n      kind: CustomResourceDefinition\n      metadata:\n        name: widgets.example.com\n      spec:\n        scope: Cluster\n",
+        )
+        .unwrap();
+
+        let documents = build(&overlay).unwrap();
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0]["spec"]["scope"].as_str(), Some("Cluster"));
+        assert_eq!(documents[0]["spec"]["group"].as_str(), Some("example.com"));
+        assert_eq!(
+            documents[1]["metadata"]["labels"]["app"].as_str(),
+            Some("widgets")
+        );
+
+        let out = temp_dir.path().join("out");
+        assert_eq!(write_crds(&documents, &out).unwrap(), 1);
+        assert!(out.join("0000.yaml").exists());
+
+        std::fs::write(
+            base.join("kustomization.yaml"),
+            "resources:\n  - ../overlay\n",
+        )
+        .unwrap();
+        assert!(build(&overlay).unwrap_err().to_string().contains("cycle"));
+    }
+}
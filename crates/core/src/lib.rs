@@ -0,0 +1,5402 @@
+//! Gensonnet Core Library
+//!
+//! The generation engine behind the `gensonnet` CLI: configuration, git and URL
+//! fetching, lockfile handling, the plugin host and Jsonnet generation. It has no
+//! CLI dependencies so it can be embedded in other tools, such as operators.
+
+pub mod audit;
+pub mod catalog;
+pub mod chaos;
+pub mod check;
+pub mod cluster;
+pub mod config;
+pub mod detect;
+pub mod fetch;
+pub mod fixture;
+pub mod freeze;
+pub mod git;
+pub mod hooks;
+pub mod kustomize;
+pub mod library;
+pub mod license;
+pub mod limits;
+pub mod metrics;
+pub mod notify;
+pub mod plugin;
+pub mod plugin_output;
+pub mod policy;
+pub mod profile;
+pub mod release;
+pub mod samples;
+pub mod selftest;
+pub mod transform;
+pub mod utils;
+pub mod workspace;
+
+pub use config::{Config, GenerationConfig, Source};
+pub use fetch::UrlFetcher;
+pub use git::{GitFetcher, GitManager};
+pub use jsonnet_crd::{ConversionInfo, CrdParser, CrdSchema, SchemaAnalysis, ValidationRules};
+pub use jsonnet_generator::clock::{Clock, FixedClock, SystemClock};
+pub use jsonnet_generator::config::OutputConfig;
+pub use jsonnet_generator::naming::FieldNaming;
+pub use jsonnet_generator::{
+    GenerationResult, GenerationStatistics, JsonnetGenerator, PluginMetrics, SourceResult,
+    SourceStatistics, Warning, WarningCategory,
+};
+pub use jsonnet_lockfile::{IncrementalPlan, Lockfile, LockfileEntry, LockfileManager};
+pub use plugin::{ExtractedSchema, PluginConfig, PluginContext, PluginManager, PluginResult};
+pub use release::ReleaseResolver;
+pub use workspace::{Workspace, WorkspaceManager};
+
+use anyhow::Result;
+use chrono::Utc;
+use jsonnet_generator::result::generation_statistics;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{debug, error, info, warn};
+
+/// Main application context that coordinates all components
+pub struct JsonnetGen {
+    config: Config,
+    git_fetcher: Arc<dyn GitFetcher>,
+    url_fetcher: UrlFetcher,
+    release_resolver: ReleaseResolver,
+    crd_parser: CrdParser,
+    generator: JsonnetGenerator,
+    lockfile_manager: LockfileManager,
+    plugin_manager: Arc<PluginManager>,
+    policy_engine: policy::PolicyEngine,
+    workspace_manager: WorkspaceManager,
+    chaos: Option<chaos::ChaosInjector>,
+    hook_runner: hooks::HookRunner,
+    notifier: notify::Notifier,
+    output_verifier: plugin_output::OutputVerifier,
+    resource_tracker: limits::ResourceTracker,
+    source_statistics: std::sync::Mutex<HashMap<String, SourceStatistics>>,
+    shared_state: plugin::SharedState,
+    skipped_sources: std::sync::Mutex<SkippedSources>,
+    locked: bool,
+    allow_breaking: bool,
+    hermetic: bool,
+    retry_quarantined: bool,
+    clock: Arc<dyn Clock>,
+}
+
+/// Sources a run does not process, decided as it plans and probes them
+#[derive(Debug, Default)]
+struct SkippedSources {
+    /// Sources quarantined by earlier runs
+    quarantined: BTreeSet<String>,
+
+    /// Sources whose upstream could not be reached, with the error
+    unreachable: BTreeMap<String, String>,
+}
+
+/// A timed phase of processing a source
+#[derive(Debug, Clone, Copy)]
+enum Phase {
+    /// Cloning or fetching the upstream
+    Clone,
+    /// Parsing schemas from the upstream files
+    Parse,
+}
+
+impl JsonnetGen {
+    /// Create a new JsonnetGen instance with the given configuration
+    pub fn new(mut config: Config) -> Result<Self> {
+        if config.generation.on_output_collision == config::OutputCollisionPolicy::Namespace {
+            for collision in config.namespace_output_collisions() {
+                info!(
+                    "Sources {} share {:?}; each writes to a subdirectory named after it",
+                    collision.sources.join(", "),
+                    collision.path
+                );
+            }
+        }
+
+        let git_manager = GitManager::new()?
+            .with_retry_policy(config.git.retry.clone())
+            .with_max_concurrent_per_host(config.git.max_concurrent_per_host)
+            .with_ls_remote_cache(std::time::Duration::from_secs(
+                config.git.ls_remote_cache_seconds,
+            ));
+        for git_source in config.sources.iter().filter_map(Source::git_source) {
+            git_manager.declare_credentials(git_source);
+        }
+        let url_fetcher = UrlFetcher::new()?.with_retry_policy(config.git.retry.clone());
+        let release_resolver = ReleaseResolver::new().with_retry_policy(config.git.retry.clone());
+        let crd_parser = CrdParser::new()
+            .with_strict_schemas(config.generation.strict_schemas)
+            .with_strict_crds(config.generation.strict_crds);
+        let generator = JsonnetGenerator::new(config.output.clone())
+            .with_fail_on_unresolved(config.generation.fail_on_unresolved)
+            .with_native_functions(config.validation.native_functions)
+            .with_renames(config.compatibility.renames_on(Utc::now().date_naive()));
+        let lockfile_manager = LockfileManager::new(LockfileManager::default_path());
+        let plugin_manager = Arc::new(PluginManager::new());
+        let policy_engine = policy::PolicyEngine::new(&config.policies)?;
+        let workspace_manager = WorkspaceManager::new()?;
+        let chaos = chaos::ChaosInjector::from_env()?;
+        let hook_runner = hooks::HookRunner::new(config.hooks.clone());
+        let notifier = notify::Notifier::new(config.notifications.clone());
+        let output_verifier = plugin_output::OutputVerifier::new(
+            config.plugins.output_verification.clone(),
+            &config.output.base_path,
+        );
+        let resource_tracker =
+            limits::ResourceTracker::new(config.generation.resource_limits.clone());
+
+        Ok(Self {
+            config,
+            git_fetcher: Arc::new(git_manager),
+            url_fetcher,
+            release_resolver,
+            crd_parser,
+            generator,
+            lockfile_manager,
+            plugin_manager,
+            policy_engine,
+            workspace_manager,
+            chaos,
+            hook_runner,
+            notifier,
+            output_verifier,
+            resource_tracker,
+            source_statistics: Default::default(),
+            shared_state: Default::default(),
+            skipped_sources: Default::default(),
+            locked: false,
+            allow_breaking: false,
+            hermetic: false,
+            retry_quarantined: false,
+            clock: Arc::new(SystemClock),
+        })
+    }
+
+    /// Inject random failures into git, parse and write operations
+    pub fn with_chaos(mut self, chaos: chaos::ChaosInjector) -> Self {
+        self.chaos = Some(chaos);
+        self
+    }
+
+    /// Fetch the repositories of git-backed sources with a fetcher other than [`GitManager`]
+    pub fn with_git_fetcher(mut self, git_fetcher: Arc<dyn GitFetcher>) -> Self {
+        self.git_fetcher = git_fetcher;
+        self
+    }
+
+    /// Read the time from `clock` instead of the system clock
+    ///
+    /// Covers the timestamps of generated files and the bundle, the freeze
+    /// file, and the date deciding which renames are shimmed.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        let renames = self
+            .config
+            .compatibility
+            .renames_on(clock.now().date_naive());
+        self.generator = self
+            .generator
+            .with_renames(renames)
+            .with_clock(Arc::clone(&clock));
+        self.clock = clock;
+        self
+    }
+
+    /// Use a lockfile other than `gensonnet.lock` in the working directory
+    pub fn with_lockfile_path(mut self, path: PathBuf) -> Self {
+        self.lockfile_manager = LockfileManager::new(path);
+        self
+    }
+
+    /// Generate from exactly the commits and artifacts recorded in the lockfile
+    ///
+    /// Repositories are checked out at their locked commit, fetched only when
+    /// it is missing locally, and release sources stay on their locked tag.
+    pub fn with_locked(mut self, locked: bool) -> Self {
+        self.locked = locked;
+        self
+    }
+
+    /// Only warn about breaking changes to the API frozen in `api-freeze.json`
+    pub fn with_allow_breaking(mut self, allow_breaking: bool) -> Self {
+        self.allow_breaking = allow_breaking;
+        self
+    }
+
+    /// Keep the machine and time of a run out of its outputs and the lockfile
+    ///
+    /// Timestamps are left out, source paths are recorded relative to the
+    /// directory the source was read from, lockfile paths relative to the
+    /// lockfile, and fault injection from the environment is ignored, so runs
+    /// on different machines produce identical files. Renames are shimmed by
+    /// their window on `compatibility.as_of`, which must be set when a rename
+    /// has a `since` date.
+    pub fn with_hermetic(mut self, hermetic: bool) -> Self {
+        self.hermetic = hermetic;
+        self.crd_parser = self.crd_parser.with_relative_paths(hermetic);
+        if hermetic {
+            self.config.output.timestamps = false;
+            self.generator = self.generator.with_timestamps(false);
+            self.chaos = None;
+        }
+        self
+    }
+
+    /// Generate quarantined sources again instead of skipping them
+    ///
+    /// A quarantined source that succeeds leaves quarantine; one that fails
+    /// stays in it.
+    pub fn with_retry_quarantined(mut self, retry_quarantined: bool) -> Self {
+        self.retry_quarantined = retry_quarantined;
+        self
+    }
+
+    /// A source file's path relative to `root` in hermetic runs, else as is
+    fn source_file_path(&self, path: &Path, root: &Path) -> PathBuf {
+        match path.strip_prefix(root) {
+            Ok(relative) if self.hermetic => relative.to_path_buf(),
+            _ => path.to_path_buf(),
+        }
+    }
+
+    /// Initialize the plugin system
+    pub async fn initialize_plugins(&self) -> Result<()> {
+        info!("Initializing plugin system");
+
+        // Load built-in plugins
+        self.load_builtin_plugins().await?;
+
+        // Discover and load external plugins
+        self.discover_external_plugins().await?;
+
+        // Apply configured priorities for plugins claiming the same sources
+        for (plugin_id, priority) in &self.config.plugins.priorities {
+            self.plugin_manager.set_priority(plugin_id, *priority).await;
+        }
+        for (plugin_id, concurrency) in &self.config.plugins.concurrency {
+            self.plugin_manager
+                .set_concurrency(plugin_id, *concurrency)
+                .await;
+        }
+
+        info!("Plugin system initialized successfully");
+        Ok(())
+    }
+
+    /// Load built-in plugins
+    async fn load_builtin_plugins(&self) -> Result<()> {
+        info!("Loading built-in plugins");
+
+        // Register Go AST plugin factory
+        #[cfg(feature = "go-ast")]
+        self.plugin_manager
+            .register_factory(
+                "go-ast".to_string(),
+                Box::new(plugin::ast::GoAstPluginFactory),
+            )
+            .await;
+
+        // Register CRD plugin factory
+        let crd_factory = Box::new(plugin::crd::CrdPluginFactory);
+        self.plugin_manager
+            .register_factory("crd".to_string(), crd_factory)
+            .await;
+
+        // Register OpenAPI plugin factory
+        #[cfg(feature = "openapi")]
+        self.plugin_manager
+            .register_factory(
+                "openapi".to_string(),
+                Box::new(plugin::openapi::OpenApiPluginFactory),
+            )
+            .await;
+
+        // Register CloudFormation plugin factory
+        #[cfg(feature = "cloudformation")]
+        self.plugin_manager
+            .register_factory(
+                "cloudformation".to_string(),
+                Box::new(plugin::cloudformation::CloudFormationPluginFactory),
+            )
+            .await;
+
+        // Create Go AST plugin
+        #[cfg(feature = "go-ast")]
+        {
+            let go_ast_config = PluginConfig {
+                plugin_id: "go-ast:builtin".to_string(),
+                config: serde_yaml::Value::Null,
+                enabled_capabilities: vec![
+                    plugin::PluginCapability::Parse,
+                    plugin::PluginCapability::SchemaExtraction,
+                    plugin::PluginCapability::AstProcessing,
+                ],
+            };
+
+            self.plugin_manager
+                .create_plugin("go-ast", go_ast_config)
+                .await?;
+        }
+
+        // Create CRD plugin
+        let crd_config = PluginConfig {
+            plugin_id: "crd:builtin".to_string(),
+            config: serde_yaml::Value::Null,
+            enabled_capabilities: vec![
+                plugin::PluginCapability::Parse,
+                plugin::PluginCapability::SchemaExtraction,
+                plugin::PluginCapability::Validation,
+            ],
+        };
+
+        self.plugin_manager.create_plugin("crd", crd_config).await?;
+
+        // Create OpenAPI plugin
+        #[cfg(feature = "openapi")]
+        {
+            let openapi_config = PluginConfig {
+                plugin_id: "openapi:builtin".to_string(),
+                config: serde_yaml::Value::Null,
+                enabled_capabilities: vec![
+                    plugin::PluginCapability::Parse,
+                    plugin::PluginCapability::SchemaExtraction,
+                    plugin::PluginCapability::Validation,
+                ],
+            };
+
+            self.plugin_manager
+                .create_plugin("openapi", openapi_config)
+                .await?;
+        }
+
+        // Create CloudFormation plugin
+        #[cfg(feature = "cloudformation")]
+        {
+            let cloudformation_config = PluginConfig {
+                plugin_id: "cloudformation:builtin".to_string(),
+                config: serde_yaml::Value::Null,
+                enabled_capabilities: vec![
+                    plugin::PluginCapability::Parse,
+                    plugin::PluginCapability::SchemaExtraction,
+                ],
+            };
+
+            self.plugin_manager
+                .create_plugin("cloudformation", cloudformation_config)
+                .await?;
+        }
+
+        info!("Built-in plugins loaded successfully");
+        Ok(())
+    }
+
+    /// Discover and load external plugins
+    async fn discover_external_plugins(&self) -> Result<()> {
+        info!("Discovering external plugins");
+
+        if !self.config.plugins.enable_external_discovery {
+            info!("External plugin discovery is disabled");
+            return Ok(());
+        }
+
+        // Create plugin registry
+        let registry = Arc::new(plugin::registry::PluginRegistry::new(Arc::clone(
+            &self.plugin_manager,
+        )));
+
+        // Add plugin directories to registry
+        for plugin_dir in &self.config.plugins.plugin_directories {
+            let expanded_dir = self.expand_plugin_directory(plugin_dir)?;
+            if expanded_dir.exists() {
+                info!("Adding plugin directory: {:?}", expanded_dir);
+                registry.add_plugin_directory(expanded_dir).await;
+            } else {
+                info!(
+                    "Plugin directory does not exist, skipping: {:?}",
+                    expanded_dir
+                );
+            }
+        }
+
+        // Create discovery service
+        let discovery_service = plugin::registry::PluginDiscoveryService::new(registry);
+
+        // Discover and load plugins
+        match discovery_service.discover_and_load().await {
+            Ok(_) => {
+                info!("External plugin discovery completed successfully");
+            }
+            Err(e) => {
+                warn!("External plugin discovery failed: {}", e);
+                // Don't fail the entire process if plugin discovery fails
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Expand plugin directory path (handle ~ and environment variables)
+    fn expand_plugin_directory(&self, path: &Path) -> Result<PathBuf> {
+        let path_str = path.to_string_lossy();
+
+        if let Some(stripped) = path_str.strip_prefix("~/") {
+            let home = std::env::var("HOME")
+                .or_else(|_| std::env::var("USERPROFILE"))
+                .map_err(|_| anyhow::anyhow!("Could not determine home directory"))?;
+            return Ok(PathBuf::from(home).join(stripped));
+        }
+
+        // Handle environment variables like $XDG_CONFIG_HOME
+        if path_str.contains('$') {
+            let expanded = shellexpand::env(&path_str)
+                .map_err(|e| anyhow::anyhow!("Failed to expand environment variables: {}", e))?;
+            return Ok(PathBuf::from(expanded.as_ref()));
+        }
+
+        Ok(path.to_path_buf())
+    }
+
+    /// Generate Jsonnet libraries from all configured sources
+    pub async fn generate(&self) -> Result<GenerationResult> {
+        self.check_hermetic_compatibility()?;
+        let audit = self.begin_audit("generate")?;
+        let start_time = Instant::now();
+        let lockfile_before = self
+            .notifier
+            .is_enabled()
+            .then(|| self.lockfile_manager.load_or_create().unwrap_or_default());
+        let result = self.generate_all().await;
+        self.finish_audit(audit, result.is_ok())?;
+
+        if let Some(before) = lockfile_before {
+            let after = self.lockfile_manager.load_or_create().unwrap_or_default();
+            let summary = notify::RunSummary::new(
+                "generate",
+                &result,
+                &before,
+                &after,
+                start_time.elapsed().as_millis() as u64,
+            );
+            self.notifier.notify(&summary).await;
+        }
+        result
+    }
+
+    /// Generate every source below `base` instead of `output.base_path`
+    ///
+    /// A bundle outside the base path goes to `base` too. The configured
+    /// output, the lockfile and the freeze file are left untouched; sources
+    /// are generated from a copy of the lockfile, as by [`check`](Self::check).
+    pub async fn generate_into(&self, base: &Path) -> Result<GenerationResult> {
+        let scratch = tempfile::TempDir::new()?;
+        let (generator, _) = self.rebased(base, base, scratch.path())?;
+        generator.generate_all().await
+    }
+
+    /// A copy of this generator writing below `base`, with a bundle outside
+    /// `output.base_path` written to `bundle_dir`, and reading the lockfile
+    /// and freeze file from copies in `scratch`
+    ///
+    /// Output paths keep their place relative to the base path. Side effects
+    /// outside it (mirrors, audit records, hooks and notifications) are
+    /// disabled. Returns the directory an outside bundle was moved from.
+    fn rebased(
+        &self,
+        base: &Path,
+        bundle_dir: &Path,
+        scratch: &Path,
+    ) -> Result<(Self, Option<PathBuf>)> {
+        let configured_base = utils::resolve_path(&self.config.output.base_path)?;
+
+        // Output paths move below the new base; a bundle outside the base
+        // gets a directory of its own
+        let mut config = self.config.clone();
+        config.output.base_path = base.to_path_buf();
+        config.output.mirrors.clear();
+        config.audit.enabled = false;
+        config.hooks = Default::default();
+        config.notifications = Default::default();
+        for source in &mut config.sources {
+            let relative = utils::resolve_path(source.output_path())?
+                .strip_prefix(&configured_base)
+                .map(Path::to_path_buf)
+                .map_err(|_| JsonnetGenError::OutputOutsideBase {
+                    source_name: source.name().to_string(),
+                    path: source.output_path().to_path_buf(),
+                    base: self.config.output.base_path.clone(),
+                })?;
+            *source.output_path_mut() = base.join(relative);
+        }
+        let outside_bundle = match &self.config.bundle.path {
+            Some(path) if !utils::is_within_base(path, &configured_base) => {
+                let file_name = path.file_name().unwrap_or_default();
+                config.bundle.path = Some(bundle_dir.join(file_name));
+                path.parent().map(Path::to_path_buf)
+            }
+            Some(path) => {
+                let relative = utils::resolve_path(path)?
+                    .strip_prefix(&configured_base)
+                    .map(Path::to_path_buf)?;
+                config.bundle.path = Some(base.join(relative));
+                None
+            }
+            None => None,
+        };
+
+        let lockfile_path = scratch.join("gensonnet.lock");
+        if self.lockfile_manager.path().exists() {
+            std::fs::copy(self.lockfile_manager.path(), &lockfile_path)?;
+        }
+        if self.freeze_path().exists() {
+            std::fs::copy(
+                self.freeze_path(),
+                lockfile_path.with_file_name(freeze::FREEZE_FILE),
+            )?;
+        }
+
+        let mut generator = Self::new(config)?
+            .with_git_fetcher(Arc::clone(&self.git_fetcher))
+            .with_clock(Arc::clone(&self.clock))
+            .with_lockfile_path(lockfile_path)
+            .with_locked(self.locked)
+            .with_allow_breaking(self.allow_breaking)
+            .with_hermetic(self.hermetic)
+            .with_retry_quarantined(self.retry_quarantined);
+        generator.plugin_manager = Arc::clone(&self.plugin_manager);
+        Ok((generator, outside_bundle))
+    }
+
+    /// Regenerate every source into a scratch directory and compare the result
+    /// with the output on disk, leaving the workspace and lockfile untouched
+    ///
+    /// Every source is generated in full, from a copy of the lockfile, and
+    /// schema files are never reused, so each generated file is compared.
+    /// Sources are generated at the commits the lockfile records, as with
+    /// [`with_locked`](Self::with_locked), once there is a lockfile, and
+    /// without timestamps, whose lines in the output are not compared.
+    pub async fn check(&self) -> Result<check::CheckResult> {
+        self.check_hermetic_compatibility()?;
+        let scratch = tempfile::TempDir::new()?;
+        let scratch_base = scratch.path().join("output");
+        let scratch_bundle = scratch.path().join("bundle");
+        let (mut checker, outside_bundle) =
+            self.rebased(&scratch_base, &scratch_bundle, scratch.path())?;
+        checker.config.generation.reuse_unchanged_schemas = false;
+        checker.config.output.timestamps = false;
+        checker.generator = checker.generator.with_timestamps(false);
+        checker.locked |= self.lockfile_manager.path().exists();
+
+        let start_time = Instant::now();
+        checker.check_output_collisions()?;
+        let quarantined = checker.plan_quarantine()?;
+        checker.check_resource_plan(&checker.config.sources)?;
+        checker.git_fetcher.forget_fetched();
+        checker.shared_state.clear();
+        if checker.locked {
+            checker.pin_locked_sources()?;
+        }
+        checker.get_current_source_commits().await?;
+        let mut results = checker.generate_full().await?;
+        results.extend(checker.unreachable_results().await?);
+        let generation = GenerationResult {
+            sources_processed: results.len(),
+            total_sources: checker.config.sources.len(),
+            statistics: generation_statistics(&results, start_time.elapsed().as_millis() as u64),
+            results,
+            quarantined,
+        };
+        checker
+            .update_lockfile(&generation, checker.get_current_source_commits().await?)
+            .await?;
+        if checker.config.bundle.enabled {
+            checker.write_bundle()?;
+        }
+
+        let (mut files_checked, mut mismatches) =
+            check::compare_trees(&scratch_base, &self.config.output.base_path)?;
+        if let Some(bundle_dir) = outside_bundle {
+            let (files, bundle_mismatches) = check::compare_trees(&scratch_bundle, &bundle_dir)?;
+            files_checked += files;
+            mismatches.extend(bundle_mismatches);
+        }
+        info!(
+            "Checked {} generated files, {} out of date",
+            files_checked,
+            mismatches.len()
+        );
+
+        Ok(check::CheckResult {
+            generation,
+            files_checked,
+            mismatches,
+        })
+    }
+
+    async fn generate_all(&self) -> Result<GenerationResult> {
+        info!("Starting Jsonnet library generation");
+
+        let start_time = Instant::now();
+        self.check_output_collisions()?;
+        let quarantined = self.plan_quarantine()?;
+        self.check_resource_plan(&self.config.sources)?;
+        self.git_fetcher.forget_fetched();
+        self.shared_state.clear();
+        if self.locked {
+            self.pin_locked_sources()?;
+        }
+
+        // Check if incremental generation is possible
+        let current_sources = self.get_current_source_commits().await?;
+        let incremental_plan = self
+            .lockfile_manager
+            .get_incremental_plan(&current_sources.keys().cloned().collect::<Vec<_>>())?;
+
+        let incremental =
+            incremental_plan.can_incremental && !incremental_plan.changed_sources.is_empty();
+        let plan = hooks::plan_context(
+            "generate",
+            if incremental { "incremental" } else { "full" },
+            &self.config.sources.iter().collect::<Vec<_>>(),
+            if incremental {
+                &incremental_plan.changed_sources
+            } else {
+                &[]
+            },
+        );
+        self.hook_runner
+            .run(hooks::HookPoint::BeforeAll, &plan)
+            .await?;
+
+        let mut results = if incremental {
+            info!(
+                "Using incremental generation for {} changed sources",
+                incremental_plan.changed_sources.len()
+            );
+            self.generate_incremental(&incremental_plan).await?
+        } else {
+            info!(
+                "Performing full generation for {} sources",
+                self.config.sources.len()
+            );
+            self.generate_full().await?
+        };
+        results.extend(self.unreachable_results().await?);
+
+        let generation_time = start_time.elapsed();
+        info!("Generation completed in {:?}", generation_time);
+
+        let result = GenerationResult {
+            sources_processed: results.len(),
+            total_sources: self.config.sources.len(),
+            results: results.clone(),
+            statistics: GenerationStatistics {
+                cache_hit_rate: self.calculate_cache_hit_rate(&incremental_plan),
+                ..generation_statistics(&results, generation_time.as_millis() as u64)
+            },
+            quarantined,
+        };
+
+        self.hook_runner
+            .run(
+                hooks::HookPoint::AfterAll,
+                &hooks::result_context("generate", &result),
+            )
+            .await?;
+
+        // Update lockfile with new generation data
+        self.update_lockfile(&result, self.get_current_source_commits().await?)
+            .await?;
+
+        if self.config.bundle.enabled {
+            self.write_bundle()?;
+        }
+        self.check_denied_warnings(&result.results)?;
+
+        Ok(result)
+    }
+
+    /// Generate a single source and update its lockfile entry
+    ///
+    /// The entries of other sources are left as they are.
+    pub async fn generate_source(&self, source_id: &str) -> Result<SourceResult> {
+        self.check_hermetic_compatibility()?;
+        let audit = self.begin_audit(&format!("generate {source_id}"))?;
+        let result = self.generate_one(source_id).await;
+        self.finish_audit(audit, result.is_ok())?;
+        result
+    }
+
+    async fn generate_one(&self, source_id: &str) -> Result<SourceResult> {
+        let source = self
+            .find_source_by_id(source_id)
+            .ok_or_else(|| JsonnetGenError::UnknownSource(source_id.to_string()))?;
+        info!("Generating source {}", source_id);
+        self.check_output_collisions()?;
+        self.check_resource_plan([source])?;
+        self.git_fetcher.forget_fetched();
+        self.shared_state.clear();
+
+        let command = format!("generate {source_id}");
+        let plan = hooks::plan_context(&command, "source", &[source], &[]);
+        self.hook_runner
+            .run(hooks::HookPoint::BeforeAll, &plan)
+            .await?;
+
+        let source_result = self.process_source_with_recovery(source).await?;
+        let result = GenerationResult {
+            sources_processed: 1,
+            total_sources: self.config.sources.len(),
+            results: vec![source_result.clone()],
+            statistics: generation_statistics(
+                std::slice::from_ref(&source_result),
+                source_result.processing_time_ms,
+            ),
+            quarantined: Vec::new(),
+        };
+
+        self.hook_runner
+            .run(
+                hooks::HookPoint::AfterAll,
+                &hooks::result_context(&command, &result),
+            )
+            .await?;
+
+        let commit_sha = self.upstream_commit(source).await?;
+        self.update_lockfile(
+            &result,
+            HashMap::from([(source_id.to_string(), commit_sha)]),
+        )
+        .await?;
+
+        if self.config.bundle.enabled {
+            self.write_bundle()?;
+        }
+        self.check_denied_warnings(&result.results)?;
+
+        Ok(source_result)
+    }
+
+    /// Refuse a hermetic run whose shimmed renames depend on the day it runs
+    fn check_hermetic_compatibility(&self) -> Result<()> {
+        if self.hermetic && self.config.compatibility.is_windowed() {
+            return Err(anyhow::anyhow!(
+                "Hermetic runs cannot measure rename windows against today; set compatibility.as_of"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Start auditing a run when `audit.enabled` is set
+    fn begin_audit(&self, command: &str) -> Result<Option<(audit::AuditLog, audit::AuditRun)>> {
+        if !self.config.audit.enabled {
+            return Ok(None);
+        }
+        if self.hermetic {
+            return Err(anyhow::anyhow!(
+                "The audit manifest records timestamps and the operator; disable audit for hermetic runs"
+            ));
+        }
+
+        let mut roots = vec![
+            self.config.output.base_path.clone(),
+            self.lockfile_manager.path().clone(),
+        ];
+        roots.extend(
+            self.config
+                .sources
+                .iter()
+                .map(|source| source.output_path().to_path_buf()),
+        );
+        roots.extend(self.config.bundle.path.clone());
+        roots.sort();
+        roots.dedup();
+
+        let log = audit::AuditLog::from_config(&self.config.audit)?;
+        let operator = audit::operator(self.config.audit.operator.as_deref());
+        let run = log.begin(command, operator, roots)?;
+        Ok(Some((log, run)))
+    }
+
+    /// Record an audited run in the audit manifest
+    fn finish_audit(
+        &self,
+        audit: Option<(audit::AuditLog, audit::AuditRun)>,
+        success: bool,
+    ) -> Result<()> {
+        if let Some((log, run)) = audit {
+            let changes = log.finish(run, success)?;
+            info!(
+                "Recorded {} file changes in audit manifest {:?}",
+                changes.len(),
+                log.path()
+            );
+        }
+        Ok(())
+    }
+
+    /// Write the platform bundle importing every generated source library
+    ///
+    /// Source versions come from the lockfile, so sources skipped by an
+    /// incremental run keep the version they were generated from.
+    pub fn write_bundle(&self) -> Result<PathBuf> {
+        let bundle_path = self.config.bundle.path.clone().unwrap_or_else(|| {
+            self.config
+                .output
+                .base_path
+                .join(jsonnet_generator::bundle::BUNDLE_FILE)
+        });
+        let bundle_dir = bundle_path.parent().unwrap_or(Path::new("."));
+        let lockfile = self.lockfile_manager.load_or_create()?;
+
+        // Catalogs and auto sources contribute one library per logical source
+        let mut libraries = Vec::new();
+        for source in &self.config.sources {
+            if let Source::Catalog(_) | Source::Auto(_) = source {
+                let Ok(entries) = std::fs::read_dir(source.output_path()) else {
+                    continue;
+                };
+                let mut names: Vec<String> = entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.path().is_dir())
+                    .map(|entry| entry.file_name().to_string_lossy().to_string())
+                    .collect();
+                names.sort();
+                for name in names {
+                    let source_id = format!("{}/{}", source.name(), name);
+                    libraries.push((source_id, source.output_path().join(&name)));
+                }
+            } else {
+                libraries.push((
+                    source.name().to_string(),
+                    source.output_path().to_path_buf(),
+                ));
+            }
+        }
+
+        let mut members = Vec::new();
+        let mut keys = HashMap::new();
+        for (source_id, output_path) in libraries {
+            let index_path = output_path.join("index.libsonnet");
+            if !index_path.exists() {
+                warn!(
+                    "Source {} has no index.libsonnet, leaving it out of the bundle",
+                    source_id
+                );
+                continue;
+            }
+
+            let key = self.config.bundle.key(&source_id);
+            if let Some(other) = keys.insert(key.clone(), source_id.clone()) {
+                return Err(anyhow::anyhow!(
+                    "Sources {} and {} both use bundle key '{}'; set bundle.keys to disambiguate",
+                    other,
+                    source_id,
+                    key
+                ));
+            }
+
+            let entry = lockfile.sources.get(&source_id);
+            members.push(jsonnet_generator::bundle::BundleMember {
+                key,
+                source: source_id.clone(),
+                import_path: relative_import(bundle_dir, &index_path),
+                url: entry.map(|e| e.url.clone()).unwrap_or_default(),
+                ref_name: entry.map(|e| e.ref_name.clone()).unwrap_or_default(),
+                commit: entry.map(|e| e.commit_sha.clone()).unwrap_or_default(),
+                digest: entry.and_then(|e| e.digest.clone()).unwrap_or_default(),
+                version: std::fs::read_to_string(&index_path)
+                    .ok()
+                    .and_then(|content| jsonnet_generator::version::read_version(&content))
+                    .unwrap_or_default(),
+            });
+        }
+
+        std::fs::create_dir_all(bundle_dir)?;
+        jsonnet_generator::write::write_generated(
+            &bundle_path,
+            &self
+                .config
+                .output
+                .style
+                .apply(&jsonnet_generator::bundle::generate_bundle_file(
+                    &members,
+                    self.config.output.timestamps.then(|| self.clock.now()),
+                )),
+            self.config.output.newline,
+        )?;
+        info!(
+            "Wrote platform bundle with {} sources to {:?}",
+            members.len(),
+            bundle_path
+        );
+
+        // A bundle outside the base path has no place in the mirrors
+        if utils::is_within_base(&bundle_path, &self.config.output.base_path) {
+            for mirror in self.mirror_paths(&bundle_path)? {
+                utils::ensure_directory(mirror.parent().unwrap_or(Path::new(".")))?;
+                std::fs::copy(&bundle_path, &mirror)?;
+            }
+        }
+
+        Ok(bundle_path)
+    }
+
+    /// Generate libraries incrementally
+    async fn generate_incremental(&self, plan: &IncrementalPlan) -> Result<Vec<SourceResult>> {
+        let mut results = Vec::new();
+
+        // Process changed sources first
+        for source_id in &plan.changed_sources {
+            if let Some(source) = self.find_runnable_source(source_id) {
+                match self.process_source_with_recovery(source).await {
+                    Ok(result) => {
+                        info!("Successfully processed changed source: {}", source_id);
+                        results.push(result);
+                    }
+                    Err(e) => {
+                        error!("Failed to process changed source {}: {}", source_id, e);
+                        if self.config.generation.fail_fast || is_fatal(&e) {
+                            return Err(e);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Process dependent sources
+        for source_id in &plan.dependent_sources {
+            if let Some(source) = self.find_runnable_source(source_id) {
+                match self.process_source_with_recovery(source).await {
+                    Ok(result) => {
+                        info!("Successfully processed dependent source: {}", source_id);
+                        results.push(result);
+                    }
+                    Err(e) if is_fatal(&e) => return Err(e),
+                    Err(e) => {
+                        warn!("Failed to process dependent source {}: {}", source_id, e);
+                        // Don't fail fast for dependent sources
+                    }
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Generate libraries for all sources
+    async fn generate_full(&self) -> Result<Vec<SourceResult>> {
+        let mut results = Vec::new();
+
+        for source in &self.config.sources {
+            if self.is_skipped(source.name()) {
+                continue;
+            }
+            match self.process_source_with_recovery(source).await {
+                Ok(result) => {
+                    info!("Successfully processed source: {}", source.name());
+                    results.push(result);
+                }
+                Err(e) => {
+                    error!("Failed to process source {}: {}", source.name(), e);
+                    if self.config.generation.fail_fast || is_fatal(&e) {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Process a single source with error recovery
+    pub async fn process_source_with_recovery(&self, source: &Source) -> Result<SourceResult> {
+        let start_time = Instant::now();
+        self.source_statistics.lock().unwrap().remove(source.name());
+
+        let processing = git::for_source(source.name(), self.process_source(source));
+        let processed = match source.timeout() {
+            Some(timeout) => tokio::time::timeout(timeout, processing)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(anyhow::anyhow!(
+                        "Source {} timed out after {}s",
+                        source.name(),
+                        timeout.as_secs()
+                    ))
+                }),
+            None => processing.await,
+        };
+
+        let mut result = match processed {
+            Ok(mut result) => {
+                let processing_time = start_time.elapsed();
+                result.source_name = source.name().to_string();
+                result.processing_time_ms = processing_time.as_millis() as u64;
+                result.warnings.extend(self.retry_warning(source));
+                result
+            }
+            // Going over a run's limits or breaking the frozen API is not recovered from
+            Err(e) if is_fatal(&e) => return Err(e),
+            Err(e) => {
+                // Try to recover by generating partial results
+                warn!(
+                    "Attempting error recovery for source {}: {}",
+                    source.name(),
+                    e
+                );
+                let mut result = self.generate_partial_result(source, &e).await?;
+                result.warnings.extend(self.retry_warning(source));
+                result
+            }
+        };
+
+        for warning in &mut result.warnings {
+            warning.source = source.name().to_string();
+        }
+        let raised = result.warnings.len();
+        result
+            .warnings
+            .retain(|warning| !self.config.warnings.is_suppressed(warning));
+        if result.warnings.len() < raised {
+            info!(
+                "Suppressed {} warnings of source {}",
+                raised - result.warnings.len(),
+                source.name()
+            );
+        }
+
+        let mut statistics = self
+            .source_statistics
+            .lock()
+            .unwrap()
+            .remove(source.name())
+            .unwrap_or_default();
+        statistics.generate_ms = (start_time.elapsed().as_millis() as u64)
+            .saturating_sub(statistics.clone_ms + statistics.parse_ms);
+        statistics.files_generated = result.files_generated;
+        statistics.schemas_skipped = result.schemas_reused;
+        statistics.error_count = result.errors.len();
+        statistics.warning_count = result.warnings.len();
+        statistics.plugin_metrics = std::mem::take(&mut result.statistics.plugin_metrics);
+        result.statistics = statistics;
+        Ok(result)
+    }
+
+    /// Decide which sources the run skips as quarantined, and forget what
+    /// earlier runs skipped
+    ///
+    /// Returns the quarantined sources, sorted. None are skipped unless
+    /// `generation.quarantine_after` is set, or when they are retried.
+    fn plan_quarantine(&self) -> Result<Vec<String>> {
+        let mut skipped = self.skipped_sources.lock().unwrap();
+        *skipped = SkippedSources::default();
+        if self.config.generation.quarantine_after.is_none() || self.retry_quarantined {
+            return Ok(Vec::new());
+        }
+
+        let lockfile = self.lockfile_manager.load_or_create()?;
+        let mut quarantined = lockfile.quarantined_sources();
+        quarantined.retain(|source_id| self.find_source_by_id(source_id).is_some());
+        for source_id in &quarantined {
+            let health = &lockfile.health[source_id];
+            warn!(
+                "Skipping quarantined source {} after {} consecutive failures (last error: {}); pass --retry-quarantined to retry it",
+                source_id, health.consecutive_failures, health.last_error
+            );
+        }
+        skipped.quarantined = quarantined.iter().cloned().collect();
+        Ok(quarantined)
+    }
+
+    /// Whether the run skips a source, quarantined or with an unreachable upstream
+    fn is_skipped(&self, source_id: &str) -> bool {
+        let skipped = self.skipped_sources.lock().unwrap();
+        skipped.quarantined.contains(source_id) || skipped.unreachable.contains_key(source_id)
+    }
+
+    /// A configured source the run does not skip
+    fn find_runnable_source(&self, source_id: &str) -> Option<&Source> {
+        self.find_source_by_id(source_id)
+            .filter(|source| !self.is_skipped(source.name()))
+    }
+
+    /// Failed results of the sources whose upstream could not be reached
+    async fn unreachable_results(&self) -> Result<Vec<SourceResult>> {
+        let unreachable = self.skipped_sources.lock().unwrap().unreachable.clone();
+        let mut results = Vec::new();
+        for (source_id, error) in unreachable {
+            if let Some(source) = self.find_source_by_id(&source_id) {
+                results.push(
+                    self.generate_partial_result(source, &anyhow::anyhow!(error))
+                        .await?,
+                );
+            }
+        }
+        Ok(results)
+    }
+
+    /// Refuse to plan a run in which sources share an output path
+    ///
+    /// Collisions left with `generation.on_output_collision: namespace` are
+    /// those namespacing could not resolve.
+    fn check_output_collisions(&self) -> Result<()> {
+        match self.config.output_collisions().into_iter().next() {
+            Some(collision) => Err(JsonnetGenError::OutputCollision {
+                path: collision.path,
+                sources: collision.sources,
+            }
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Refuse to plan a run over its resource limits, and start accounting it
+    fn check_resource_plan<'a>(&self, sources: impl IntoIterator<Item = &'a Source>) -> Result<()> {
+        self.resource_tracker.reset();
+        self.resource_tracker.check_plan(sources)
+    }
+
+    /// Resources used by the last run, or the current one
+    pub fn resource_usage(&self) -> limits::ResourceUsage {
+        self.resource_tracker.usage()
+    }
+
+    /// Path of the freeze file, next to the lockfile
+    pub fn freeze_path(&self) -> PathBuf {
+        self.lockfile_manager
+            .path()
+            .with_file_name(freeze::FREEZE_FILE)
+    }
+
+    /// Public API of the libraries currently generated for each source
+    pub fn current_api(&self) -> Result<freeze::ApiSnapshot> {
+        let mut sources = BTreeMap::new();
+        for source in &self.config.sources {
+            if source.output_path().exists() {
+                sources.insert(
+                    source.name().to_string(),
+                    freeze::SourceApi::read(source.output_path())?,
+                );
+            }
+        }
+        Ok(freeze::ApiSnapshot {
+            frozen_at: self.clock.now(),
+            sources,
+        })
+    }
+
+    /// Freeze the current public API, returning the path of the freeze file
+    pub fn freeze_api(&self) -> Result<PathBuf> {
+        let path = self.freeze_path();
+        self.current_api()?.save(&path)?;
+        info!("Froze the generated API in {:?}", path);
+        Ok(path)
+    }
+
+    /// Refuse to publish a library that breaks the frozen API
+    ///
+    /// The API is read from the staged files as they will be published, so a
+    /// refused library never reaches its output path. Nothing is checked until
+    /// `freeze` has written `api-freeze.json`.
+    fn check_staged_api(
+        &self,
+        workspace: &Workspace,
+        metadata: &plugin::SourceMetadata,
+        output_path: &Path,
+    ) -> Result<()> {
+        let Some(frozen) = freeze::ApiSnapshot::load(&self.freeze_path())? else {
+            return Ok(());
+        };
+        // Logical sources are named `<source>/<entry>` and belong to their source's API
+        let name = metadata.name.split('/').next().unwrap_or_default();
+        let Some(source) = self
+            .config
+            .sources
+            .iter()
+            .find(|source| source.name() == name)
+        else {
+            return Ok(());
+        };
+        let current = freeze::ApiSnapshot {
+            frozen_at: self.clock.now(),
+            sources: BTreeMap::from([(
+                name.to_string(),
+                freeze::SourceApi::read_staged(
+                    source.output_path(),
+                    output_path,
+                    &workspace.output_dir(),
+                )?,
+            )]),
+        };
+        let changes = frozen.breaking_changes(&current);
+        if changes.is_empty() {
+            return Ok(());
+        }
+        if self.allow_breaking {
+            for change in &changes {
+                warn!("Breaking API change: {}", change);
+            }
+            return Ok(());
+        }
+        Err(JsonnetGenError::BreakingChanges(changes).into())
+    }
+
+    /// Fail a run that raised warnings in the categories of `warnings.deny`
+    ///
+    /// Checked once the run has generated and updated the lockfile, so the
+    /// output stays as generated.
+    fn check_denied_warnings(&self, results: &[SourceResult]) -> Result<()> {
+        let denied: Vec<String> = results
+            .iter()
+            .flat_map(|result| &result.warnings)
+            .filter(|warning| self.config.warnings.is_denied(warning))
+            .map(|warning| format!("{} ({}): {}", warning.source, warning.category, warning))
+            .collect();
+        if denied.is_empty() {
+            return Ok(());
+        }
+        Err(JsonnetGenError::DeniedWarnings(denied).into())
+    }
+
+    /// Refuse sources whose output path resolves outside `output.base_path`
+    fn check_output_path(&self, source: &Source) -> Result<()> {
+        let base = &self.config.output.base_path;
+        if !utils::is_within_base(source.output_path(), base) {
+            return Err(JsonnetGenError::OutputOutsideBase {
+                source_name: source.name().to_string(),
+                path: source.output_path().to_path_buf(),
+                base: base.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Refuse plugin results naming files outside `output.base_path`
+    ///
+    /// Covers the files a plugin reports as generated, which may also be in
+    /// the source's staging directory, and the library files its schema names
+    /// map to, which must stay inside the output directory.
+    fn check_plugin_output(
+        &self,
+        source_name: &str,
+        plugin_id: &str,
+        staging: &Path,
+        schemas: &[crate::plugin::ExtractedSchema],
+        generated_files: &[PathBuf],
+    ) -> Result<()> {
+        let base = &self.config.output.base_path;
+        let outside = |path: PathBuf| JsonnetGenError::PluginPathOutsideBase {
+            plugin_id: plugin_id.to_string(),
+            source_name: source_name.to_string(),
+            path,
+            base: base.clone(),
+        };
+
+        for schema in schemas {
+            let file_name = PathBuf::from(schema_file_name(schema));
+            let mut components = file_name.components();
+            if !matches!(
+                (components.next(), components.next()),
+                (Some(std::path::Component::Normal(_)), None)
+            ) {
+                return Err(outside(file_name).into());
+            }
+        }
+        for path in generated_files {
+            let resolved = staging.join(path);
+            if !utils::is_within_base(&resolved, staging) && !utils::is_within_base(&resolved, base)
+            {
+                return Err(outside(path.clone()).into());
+            }
+        }
+        self.resource_tracker.add_schema_bytes(
+            source_name,
+            schemas
+                .iter()
+                .map(|schema| limits::schema_size(&schema.content))
+                .sum(),
+        )
+    }
+
+    /// Describe the network retries performed for a source, if any
+    fn retry_warning(&self, source: &Source) -> Option<Warning> {
+        let (code, operations, retries) = match source {
+            Source::Url(url_source) => (
+                "fetch-retries",
+                "Downloads",
+                self.url_fetcher.take_retry_count(&url_source.url),
+            ),
+            Source::Release(release_source) => (
+                "fetch-retries",
+                "Release requests",
+                self.release_resolver.take_retry_count(release_source)
+                    + self
+                        .release_resolver
+                        .resolved(release_source)
+                        .map_or(0, |asset| {
+                            asset
+                                .urls()
+                                .map(|url| self.url_fetcher.take_retry_count(url))
+                                .sum()
+                        }),
+            ),
+            _ => (
+                "git-retries",
+                "Git operations",
+                self.git_fetcher.take_retry_count(source.name()),
+            ),
+        };
+        (retries > 0).then(|| {
+            Warning::new(
+                WarningCategory::Fetch,
+                code,
+                format!(
+                    "{} for {} needed {} retries",
+                    operations,
+                    jsonnet_lockfile::redact_url(source.git_url()),
+                    retries
+                ),
+            )
+        })
+    }
+
+    /// Generate a partial result when processing fails
+    async fn generate_partial_result(
+        &self,
+        source: &Source,
+        error: &anyhow::Error,
+    ) -> Result<SourceResult> {
+        // Create a minimal result with error information
+        Ok(SourceResult {
+            source_name: source.name().to_string(),
+            source_type: source.source_type().to_string(),
+            files_generated: 0,
+            errors: vec![error.to_string()],
+            output_path: source.output_path().to_path_buf(),
+            processing_time_ms: 0,
+            warnings: vec![Warning::new(
+                WarningCategory::Source,
+                "partial-generation",
+                "Partial generation due to processing error",
+            )],
+            plugin: None,
+            file_plugins: Default::default(),
+            schema_digests: Default::default(),
+            schemas_reused: 0,
+            statistics: Default::default(),
+        })
+    }
+
+    /// Process a single source
+    async fn process_source(&self, source: &Source) -> Result<SourceResult> {
+        self.check_output_path(source)?;
+        self.inject_fault(chaos::FaultPoint::Git, source.name())?;
+
+        match source {
+            Source::Crd(crd_source) => {
+                // Try to use plugin first, fall back to built-in CRD parser unless
+                // the source is bound to a specific plugin. Sources with transforms
+                // use the built-in parser, which lets the transforms see the schemas.
+                if crd_source.transforms.is_empty() {
+                    match self.process_with_plugins(crd_source).await {
+                        Ok(plugin_result) => return Ok(plugin_result),
+                        Err(e) if !crd_source.plugin.is_auto() || is_fatal(&e) => return Err(e),
+                        Err(e) => info!(
+                            "No plugin processed CRD source {} ({}), using the built-in parser",
+                            crd_source.name, e
+                        ),
+                    }
+                }
+
+                // Fall back to built-in CRD processing
+                let repo_path = self
+                    .timed(
+                        &crd_source.name,
+                        Phase::Clone,
+                        self.git_fetcher.ensure_repository(&crd_source.git),
+                    )
+                    .await?;
+                self.inject_fault(chaos::FaultPoint::Parse, &crd_source.name)?;
+                let schemas = self
+                    .timed(&crd_source.name, Phase::Parse, async {
+                        self.crd_parser_for(crd_source.versions)
+                            .parse_from_directory(&repo_path, &crd_source.filters)
+                    })
+                    .await?;
+                let samples = samples::Samples::load(&repo_path, &crd_source.samples)?;
+                let (workspace, metadata) = self.prepare_git_workspace(
+                    &crd_source.name,
+                    &crd_source.git,
+                    &crd_source.filters,
+                    &repo_path,
+                )?;
+                self.generate_crd_output(
+                    schemas,
+                    &crd_source.transforms,
+                    samples,
+                    &workspace,
+                    &metadata,
+                    &crd_source.output_path,
+                )
+                .await
+            }
+            Source::GoAst(go_ast_source) => {
+                // Use Go AST plugin
+                self.process_go_source(go_ast_source).await
+            }
+            Source::OpenApi(openapi_source) => {
+                // Use OpenAPI plugin
+                self.process_openapi_source(openapi_source).await
+            }
+            Source::Url(url_source) => {
+                let artifact = self
+                    .timed(
+                        &url_source.name,
+                        Phase::Clone,
+                        self.fetch_url_source(url_source),
+                    )
+                    .await?;
+                let license = self.resolve_license(&url_source.name, &artifact.dir)?;
+                self.inject_fault(chaos::FaultPoint::Parse, &url_source.name)?;
+                let schemas = self
+                    .timed(&url_source.name, Phase::Parse, async {
+                        self.crd_parser_for(url_source.versions)
+                            .parse_from_directory(&artifact.dir, &url_source.filters)
+                    })
+                    .await?;
+                let workspace = self
+                    .workspace_manager
+                    .prepare(&url_source.name, &artifact.digest)?;
+                let metadata = plugin::SourceMetadata {
+                    name: url_source.name.clone(),
+                    repository: Some(jsonnet_lockfile::redact_url(&url_source.url)),
+                    git_ref: None,
+                    commit: Some(artifact.digest.clone()),
+                    filters: url_source.filters.clone(),
+                    license,
+                    tag: None,
+                };
+                self.generate_crd_output(
+                    schemas,
+                    &url_source.transforms,
+                    Default::default(),
+                    &workspace,
+                    &metadata,
+                    &url_source.output_path,
+                )
+                .await
+            }
+            Source::Release(release_source) => self.process_release_source(release_source).await,
+            Source::Fixture(fixture_source) => self.process_fixture_source(fixture_source).await,
+            Source::Jsonnet(jsonnet_source) => self.process_jsonnet_source(jsonnet_source).await,
+            Source::Kustomize(kustomize_source) => {
+                let repo_path = self
+                    .timed(
+                        &kustomize_source.name,
+                        Phase::Clone,
+                        self.git_fetcher.ensure_repository(&kustomize_source.git),
+                    )
+                    .await?;
+                self.inject_fault(chaos::FaultPoint::Parse, &kustomize_source.name)?;
+                let schemas = self
+                    .timed(&kustomize_source.name, Phase::Parse, async {
+                        let build_dir = self.kustomize_build(kustomize_source, &repo_path).await?;
+                        self.crd_parser_for(kustomize_source.versions)
+                            .parse_from_directory(build_dir.path(), &kustomize_source.filters)
+                    })
+                    .await?;
+                let (workspace, metadata) = self.prepare_git_workspace(
+                    &kustomize_source.name,
+                    &kustomize_source.git,
+                    &kustomize_source.filters,
+                    &repo_path,
+                )?;
+                self.generate_crd_output(
+                    schemas,
+                    &kustomize_source.transforms,
+                    Default::default(),
+                    &workspace,
+                    &metadata,
+                    &kustomize_source.output_path,
+                )
+                .await
+            }
+            Source::Catalog(catalog_source) => self.process_catalog_source(catalog_source).await,
+            Source::Auto(auto_source) => self.process_auto_source(auto_source).await,
+        }
+    }
+
+    /// Process the release asset a release source resolves to
+    async fn process_release_source(
+        &self,
+        release_source: &crate::config::ReleaseSource,
+    ) -> Result<SourceResult> {
+        let start_time = Instant::now();
+        let (asset, artifact) = self
+            .timed(
+                &release_source.name,
+                Phase::Clone,
+                self.fetch_release_source(release_source),
+            )
+            .await?;
+        let license = self.resolve_license(&release_source.name, &artifact.dir)?;
+        self.inject_fault(chaos::FaultPoint::Parse, &release_source.name)?;
+        let workspace = self
+            .workspace_manager
+            .prepare(&release_source.name, &artifact.digest)?;
+        let metadata = plugin::SourceMetadata {
+            name: release_source.name.clone(),
+            repository: Some(jsonnet_lockfile::redact_url(&asset.url)),
+            git_ref: Some(asset.tag.clone()),
+            commit: Some(artifact.digest.clone()),
+            filters: release_source.filters.clone(),
+            license,
+            tag: Some(asset.tag.clone()),
+        };
+
+        match release_source.format {
+            crate::config::ReleaseFormat::Crd => {
+                let schemas = self
+                    .timed(&release_source.name, Phase::Parse, async {
+                        self.crd_parser_for(release_source.versions)
+                            .parse_from_directory(&artifact.dir, &release_source.filters)
+                    })
+                    .await?;
+                self.generate_crd_output(
+                    schemas,
+                    &release_source.transforms,
+                    Default::default(),
+                    &workspace,
+                    &metadata,
+                    &release_source.output_path,
+                )
+                .await
+            }
+            crate::config::ReleaseFormat::OpenApi => {
+                self.generate_openapi_output(
+                    &artifact.dir,
+                    std::slice::from_ref(&artifact.file),
+                    &workspace,
+                    &metadata,
+                    &release_source.plugin,
+                    &release_source.transforms,
+                    &release_source.output_path,
+                    &config::OpenApiOptions::default(),
+                    start_time,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Process the embedded schemas a fixture source names
+    async fn process_fixture_source(
+        &self,
+        fixture_source: &crate::config::FixtureSource,
+    ) -> Result<SourceResult> {
+        let start_time = Instant::now();
+        let fixture = fixture::materialize(fixture_source.fixture, &fixture::cache_root()?)?;
+        self.inject_fault(chaos::FaultPoint::Parse, &fixture_source.name)?;
+        let workspace = self
+            .workspace_manager
+            .prepare(&fixture_source.name, &fixture.digest)?;
+        let metadata = plugin::SourceMetadata {
+            name: fixture_source.name.clone(),
+            repository: Some(fixture_source.fixture.url().to_string()),
+            git_ref: None,
+            commit: Some(fixture.digest.clone()),
+            filters: fixture_source.filters.clone(),
+            license: None,
+            tag: None,
+        };
+
+        match fixture_source.fixture {
+            crate::config::FixtureSet::Crds => {
+                let schemas = self
+                    .timed(&fixture_source.name, Phase::Parse, async {
+                        self.crd_parser_for(fixture_source.versions)
+                            .parse_from_directory(&fixture.dir, &fixture_source.filters)
+                    })
+                    .await?;
+                self.generate_crd_output(
+                    schemas,
+                    &fixture_source.transforms,
+                    Default::default(),
+                    &workspace,
+                    &metadata,
+                    &fixture_source.output_path,
+                )
+                .await
+            }
+            crate::config::FixtureSet::OpenApi => {
+                self.generate_openapi_output(
+                    &fixture.dir,
+                    &fixture.files,
+                    &workspace,
+                    &metadata,
+                    &fixture_source.plugin,
+                    &fixture_source.transforms,
+                    &fixture_source.output_path,
+                    &config::OpenApiOptions::default(),
+                    start_time,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Re-export the hand-written Jsonnet library a jsonnet source names
+    async fn process_jsonnet_source(
+        &self,
+        jsonnet_source: &crate::config::JsonnetSource,
+    ) -> Result<SourceResult> {
+        let start_time = Instant::now();
+        let library = library::Library::open(jsonnet_source)?;
+        let digest = library.digest()?;
+        self.inject_fault(chaos::FaultPoint::Parse, &jsonnet_source.name)?;
+        let workspace = self
+            .workspace_manager
+            .prepare(&jsonnet_source.name, &digest)?;
+        let metadata = plugin::SourceMetadata {
+            name: jsonnet_source.name.clone(),
+            repository: Some(jsonnet_source.url().to_string()),
+            git_ref: None,
+            commit: Some(digest),
+            filters: Vec::new(),
+            license: None,
+            tag: None,
+        };
+
+        let mut warnings = Vec::new();
+        let introspector = library::LibraryIntrospector::new()
+            .with_evaluator(jsonnet_source.evaluator.clone())
+            .with_jpaths(jsonnet_source.jpaths.clone());
+        let shape = match self
+            .timed(
+                &jsonnet_source.name,
+                Phase::Parse,
+                introspector.shape(&library.entry_path()),
+            )
+            .await
+        {
+            Ok(shape) => {
+                std::fs::write(
+                    workspace.output_dir().join(library::SHAPE_FILE),
+                    format!("{}\n", serde_json::to_string_pretty(&shape)?),
+                )?;
+                Some(shape)
+            }
+            Err(e) => {
+                warnings.push(Warning::new(
+                    WarningCategory::Source,
+                    "library-introspection-failed",
+                    format!(
+                        "Could not introspect library of {}: {}",
+                        jsonnet_source.name, e
+                    ),
+                ));
+                None
+            }
+        };
+
+        // A library bringing its own index keeps it
+        if !library.files.contains(&PathBuf::from("index.libsonnet")) {
+            std::fs::write(
+                workspace.output_dir().join("index.libsonnet"),
+                library::index_file(&library.entry, shape.as_ref()),
+            )?;
+        }
+
+        // Copied as written, without the header and style generated files get
+        let copied = library.copy_to(&jsonnet_source.output_path)?;
+        let staged = workspace.staged_files();
+        warnings.extend(
+            self.publish(
+                &workspace,
+                &metadata,
+                &jsonnet_source.output_path,
+                &BTreeSet::new(),
+            )
+            .await?,
+        );
+
+        Ok(SourceResult {
+            source_name: jsonnet_source.name.clone(),
+            source_type: "jsonnet".to_string(),
+            files_generated: copied + staged,
+            errors: Vec::new(),
+            output_path: jsonnet_source.output_path.clone(),
+            processing_time_ms: start_time.elapsed().as_millis() as u64,
+            warnings,
+            plugin: None,
+            file_plugins: Default::default(),
+            schema_digests: Default::default(),
+            schemas_reused: 0,
+            statistics: Default::default(),
+        })
+    }
+
+    /// Build a kustomize source into a scratch directory holding one file per CRD
+    async fn kustomize_build(
+        &self,
+        kustomize_source: &crate::config::KustomizeSource,
+        repo_path: &Path,
+    ) -> Result<tempfile::TempDir> {
+        let dir = repo_path.join(&kustomize_source.path);
+        let documents = match &kustomize_source.command {
+            Some(command) => kustomize::build_with_command(command, &dir).await?,
+            None => kustomize::build(&dir)?,
+        };
+
+        let build_dir = tempfile::TempDir::new()?;
+        let crds = kustomize::write_crds(&documents, build_dir.path())?;
+        info!(
+            "Kustomize build of {} produced {} CRDs",
+            kustomize_source.name, crds
+        );
+        Ok(build_dir)
+    }
+
+    /// Process every logical source discovered in a catalog repository
+    async fn process_catalog_source(
+        &self,
+        catalog_source: &crate::config::CatalogSource,
+    ) -> Result<SourceResult> {
+        let start_time = Instant::now();
+        let repo_path = self
+            .timed(
+                &catalog_source.name,
+                Phase::Clone,
+                self.git_fetcher.ensure_repository(&catalog_source.git),
+            )
+            .await?;
+        let commit_sha = self.git_fetcher.get_current_commit(&repo_path)?;
+        let tag = self
+            .git_fetcher
+            .current_tag(&repo_path, catalog_source.git.ref_name.as_deref())?;
+        let license = self.resolve_license(&catalog_source.name, &repo_path)?;
+        let entries = catalog::discover(&repo_path, &catalog_source.discovery)?;
+        info!(
+            "Discovered {} sources in catalog {}",
+            entries.len(),
+            catalog_source.name
+        );
+
+        let mut files_generated = 0;
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        let mut schema_digests = std::collections::BTreeMap::new();
+        let mut file_plugins = BTreeMap::new();
+        let mut schemas_reused = 0;
+
+        if entries.is_empty() {
+            warnings.push(Warning::new(
+                WarningCategory::Source,
+                "catalog-empty",
+                format!(
+                    "No directories in catalog {} matched the discovery rules",
+                    catalog_source.name
+                ),
+            ));
+        }
+
+        for entry in &entries {
+            let parsed = self.timed(&catalog_source.name, Phase::Parse, async {
+                self.inject_fault(
+                    chaos::FaultPoint::Parse,
+                    &entry.source_id(&catalog_source.name),
+                )?;
+                self.crd_parser_for(catalog_source.versions)
+                    .parse_from_directory(&repo_path.join(&entry.path), &catalog_source.filters)
+            });
+            let schemas = match parsed.await {
+                Ok(schemas) => schemas,
+                Err(e) => {
+                    errors.push(format!("{}: {}", entry.source_id(&catalog_source.name), e));
+                    continue;
+                }
+            };
+
+            let source_id = entry.source_id(&catalog_source.name);
+            let output_path = catalog_source.output_path.join(&entry.name);
+            let metadata = plugin::SourceMetadata {
+                license: license.clone(),
+                tag: tag.clone(),
+                ..git_source_metadata(
+                    &source_id,
+                    &catalog_source.git,
+                    &catalog_source.filters,
+                    &commit_sha,
+                )
+            };
+            let generated = async {
+                let workspace = self.workspace_manager.prepare(&source_id, &commit_sha)?;
+                self.generate_crd_output(
+                    schemas,
+                    &catalog_source.transforms,
+                    Default::default(),
+                    &workspace,
+                    &metadata,
+                    &output_path,
+                )
+                .await
+            }
+            .await;
+
+            match generated {
+                Ok(result) => {
+                    files_generated += result.files_generated;
+                    schemas_reused += result.schemas_reused;
+                    schema_digests.extend(
+                        result
+                            .schema_digests
+                            .into_iter()
+                            .map(|(path, digest)| (format!("{}/{}", entry.name, path), digest)),
+                    );
+                    file_plugins.extend(
+                        result
+                            .file_plugins
+                            .into_iter()
+                            .map(|(path, plugin)| (Path::new(&entry.name).join(path), plugin)),
+                    );
+                    errors.extend(result.errors);
+                    warnings.extend(result.warnings);
+                }
+                Err(e) if is_fatal(&e) => return Err(e),
+                Err(e) => {
+                    errors.push(format!("{source_id}: {e}"));
+                }
+            }
+        }
+
+        Ok(SourceResult {
+            source_name: catalog_source.name.clone(),
+            source_type: "catalog".to_string(),
+            files_generated,
+            errors,
+            output_path: catalog_source.output_path.clone(),
+            processing_time_ms: start_time.elapsed().as_millis() as u64,
+            warnings,
+            plugin: None,
+            file_plugins,
+            schema_digests,
+            schemas_reused,
+            statistics: Default::default(),
+        })
+    }
+
+    /// Logical sources for the kinds of content detected in an auto source's repository
+    pub async fn detect_auto_source(
+        &self,
+        auto_source: &crate::config::AutoSource,
+    ) -> Result<Vec<Source>> {
+        let repo_path = self
+            .timed(
+                &auto_source.name,
+                Phase::Clone,
+                self.git_fetcher.ensure_repository(&auto_source.git),
+            )
+            .await?;
+        let detected = detect::detect(
+            &repo_path,
+            &self.plugin_manager,
+            &auto_source.exclude_patterns,
+            auto_source.min_score,
+        )
+        .await?;
+        for content in &detected {
+            info!(
+                "Detected {} {} files in {} (plugins: {})",
+                content.files.len(),
+                content.kind,
+                auto_source.name,
+                content
+                    .plugins
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+
+        Ok(detected
+            .iter()
+            .map(|content| detect::logical_source(auto_source, content))
+            .collect())
+    }
+
+    /// Process every logical source detected in an auto source's repository
+    async fn process_auto_source(
+        &self,
+        auto_source: &crate::config::AutoSource,
+    ) -> Result<SourceResult> {
+        let start_time = Instant::now();
+        let sources = self.detect_auto_source(auto_source).await?;
+
+        let mut files_generated = 0;
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        let mut schema_digests = std::collections::BTreeMap::new();
+        let mut file_plugins = BTreeMap::new();
+        let mut schemas_reused = 0;
+
+        if sources.is_empty() {
+            warnings.push(Warning::new(
+                WarningCategory::Source,
+                "auto-empty",
+                format!("No plugin recognised any file in {}", auto_source.name),
+            ));
+        }
+
+        for source in &sources {
+            match Box::pin(self.process_source(source)).await {
+                Ok(result) => {
+                    files_generated += result.files_generated;
+                    schemas_reused += result.schemas_reused;
+                    schema_digests.extend(result.schema_digests.into_iter().map(
+                        |(path, digest)| (format!("{}/{}", source.source_type(), path), digest),
+                    ));
+                    file_plugins.extend(result.file_plugins.into_iter().map(|(path, plugin)| {
+                        (Path::new(source.source_type()).join(path), plugin)
+                    }));
+                    errors.extend(result.errors);
+                    warnings.extend(result.warnings);
+                }
+                Err(e) if is_fatal(&e) => return Err(e),
+                Err(e) => {
+                    errors.push(format!("{}: {}", source.name(), e));
+                }
+            }
+        }
+
+        Ok(SourceResult {
+            source_name: auto_source.name.clone(),
+            source_type: "auto".to_string(),
+            files_generated,
+            errors,
+            output_path: auto_source.output_path.clone(),
+            processing_time_ms: start_time.elapsed().as_millis() as u64,
+            warnings,
+            plugin: None,
+            file_plugins,
+            schema_digests,
+            schemas_reused,
+            statistics: Default::default(),
+        })
+    }
+
+    /// Evaluate policy rules, failing if any rule with the `fail` action is violated
+    fn check_policies<'a>(
+        &self,
+        subjects: impl IntoIterator<Item = (&'a str, serde_yaml::Value)>,
+    ) -> Result<Vec<Warning>> {
+        if self.policy_engine.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let violations: Vec<_> = subjects
+            .into_iter()
+            .flat_map(|(name, subject)| self.policy_engine.evaluate(name, &subject))
+            .collect();
+
+        let failures: Vec<String> = violations
+            .iter()
+            .filter(|violation| violation.action == config::PolicyAction::Fail)
+            .map(|violation| violation.to_string())
+            .collect();
+        if !failures.is_empty() {
+            return Err(anyhow::anyhow!(
+                "{} policy violations:\n  {}",
+                failures.len(),
+                failures.join("\n  ")
+            ));
+        }
+
+        Ok(violations
+            .iter()
+            .map(|violation| {
+                let path = match &violation.path {
+                    Some(path) => format!("{}.{}", violation.schema, path),
+                    None => violation.schema.clone(),
+                };
+                Warning::new(
+                    WarningCategory::Policy,
+                    &format!("policy/{}", violation.rule),
+                    violation.to_string(),
+                )
+                .with_path(path)
+            })
+            .collect())
+    }
+
+    /// Apply a source's transforms to its schemas, in declared order
+    async fn apply_transforms<T: transform::TransformTarget>(
+        &self,
+        schemas: &mut [T],
+        transforms: &[transform::Transform],
+        workspace: &Workspace,
+        metadata: &plugin::SourceMetadata,
+    ) -> Result<()> {
+        for step in transforms {
+            let transform::Transform::Plugin(plugin_transform) = step else {
+                for schema in schemas.iter_mut() {
+                    transform::apply_builtin(step, schema);
+                }
+                continue;
+            };
+
+            let context = self.plugin_context(
+                workspace,
+                PluginConfig {
+                    plugin_id: plugin_transform.id.clone(),
+                    config: plugin_transform.config.clone(),
+                    enabled_capabilities: vec![plugin::PluginCapability::Transform],
+                },
+                metadata,
+            );
+            let extracted = schemas.iter().map(|schema| schema.to_extracted()).collect();
+            let transformed = self
+                .plugin_manager
+                .transform_schemas(
+                    &plugin_transform.id,
+                    &plugin_transform.name,
+                    extracted,
+                    &context,
+                )
+                .await?;
+            for (schema, transformed) in schemas.iter_mut().zip(transformed) {
+                schema.update_from(transformed);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Generate the Jsonnet library for parsed CRDs, including plugin-contributed validators
+    ///
+    /// Files are staged in the workspace and published to `output_path` at the end.
+    async fn generate_crd_output(
+        &self,
+        mut schemas: Vec<CrdSchema>,
+        transforms: &[transform::Transform],
+        samples: samples::Samples,
+        workspace: &Workspace,
+        metadata: &plugin::SourceMetadata,
+        output_path: &Path,
+    ) -> Result<SourceResult> {
+        if !transforms.is_empty() {
+            self.apply_transforms(&mut schemas, transforms, workspace, metadata)
+                .await?;
+            for schema in &mut schemas {
+                self.crd_parser.refresh_analysis(schema)?;
+            }
+        }
+
+        self.resource_tracker.add_schema_bytes(
+            &metadata.name,
+            schemas
+                .iter()
+                .map(|schema| limits::schema_size(&schema.schema))
+                .sum(),
+        )?;
+
+        let staging = workspace.output_dir();
+        let policy_warnings = self.check_policies(
+            schemas
+                .iter()
+                .map(|schema| (schema.name.as_str(), policy::crd_subject(schema))),
+        )?;
+
+        // Schema files generated from unchanged schemas stay as published
+        let mut cache = jsonnet_generator::SchemaCache {
+            published_path: output_path.to_path_buf(),
+            header: self.file_header(metadata).unwrap_or_default(),
+            license: metadata.license.clone(),
+            ..Default::default()
+        };
+        if self.config.generation.reuse_unchanged_schemas {
+            if let Some(entry) = self
+                .lockfile_manager
+                .load_or_create()?
+                .sources
+                .get(&metadata.name)
+            {
+                cache.digests = entry.schema_digests.clone();
+            }
+        }
+
+        let generator_schemas: Vec<_> = schemas.iter().map(convert_crd_schema).collect();
+        let mut result = self
+            .generator
+            .generate_crd_library_cached(&generator_schemas, &staging, &cache)
+            .await?;
+        result.warnings.extend(policy_warnings);
+
+        // Examples and presets from the upstream's sample resources
+        let (presets, sample_warnings) = samples.ingest(&schemas);
+        result.warnings.extend(sample_warnings);
+        if let Err(e) = self
+            .generator
+            .generate_presets(&generator_schemas, &presets, &staging)
+        {
+            result
+                .errors
+                .push(format!("Failed to generate presets from samples: {e}"));
+        }
+        result.warnings.extend(schemas.iter().flat_map(|schema| {
+            schema.recovered.iter().map(|problem| {
+                Warning::new(WarningCategory::Schema, "recovered-crd", problem.clone())
+                    .with_path(schema.name.clone())
+            })
+        }));
+
+        let extracted: Vec<_> = schemas.iter().map(to_extracted_schema).collect();
+        let context = self.plugin_context(
+            workspace,
+            PluginConfig {
+                plugin_id: "validators".to_string(),
+                config: serde_yaml::Value::Null,
+                enabled_capabilities: vec![plugin::PluginCapability::Validation],
+            },
+            metadata,
+        );
+        match self
+            .plugin_manager
+            .generate_validators(&extracted, &context)
+            .await
+        {
+            Ok(validators) => {
+                let custom: Vec<_> = validators
+                    .into_iter()
+                    .flat_map(|(schema, snippets)| {
+                        snippets.into_iter().map(move |snippet| {
+                            jsonnet_generator::validation::CustomValidator {
+                                schema: schema.clone(),
+                                name: snippet.name,
+                                code: snippet.code,
+                            }
+                        })
+                    })
+                    .collect();
+                if let Err(e) = self.generator.append_custom_validators(&staging, &custom) {
+                    result
+                        .errors
+                        .push(format!("Failed to append plugin validators: {e}"));
+                }
+            }
+            Err(e) => {
+                result
+                    .errors
+                    .push(format!("Failed to collect plugin validators: {e}"));
+            }
+        }
+
+        // Schema files of unchanged schemas are left as published instead of staged
+        let kept = result.schema_digests.keys().map(PathBuf::from).collect();
+        let budget_warnings = self
+            .publish(workspace, metadata, output_path, &kept)
+            .await?;
+        result.warnings.extend(budget_warnings);
+        result.output_path = output_path.to_path_buf();
+
+        Ok(result)
+    }
+
+    /// Evaluate the usage examples of a published library, failing on the first
+    /// that does not evaluate
+    async fn verify_examples(
+        &self,
+        verify: &config::VerifyExamples,
+        output_path: &Path,
+    ) -> Result<()> {
+        let mut jpaths = vec![output_path.to_path_buf()];
+        jpaths.extend(verify.jpaths.iter().cloned());
+        let runner = selftest::SelftestRunner::new()
+            .with_evaluator(verify.evaluator.clone())
+            .with_jpaths(jpaths);
+
+        let examples = selftest::find_examples(output_path);
+        let mut failures = Vec::new();
+        for example in &examples {
+            let outcome = runner.run(example).await?;
+            if !outcome.passed {
+                failures.push(format!("{}: {}", example.display(), outcome.message));
+            }
+        }
+        if !failures.is_empty() {
+            return Err(anyhow::anyhow!(
+                "{} of {} examples failed to evaluate:\n{}",
+                failures.len(),
+                examples.len(),
+                failures.join("\n")
+            ));
+        }
+        info!("Verified {} examples in {:?}", examples.len(), output_path);
+        Ok(())
+    }
+
+    /// Copy a source's staged files to its output path, returning warnings
+    ///
+    /// A library over the output size budgets is not published when the
+    /// budgets are configured to fail. Files published before that are neither
+    /// staged nor in `kept` are removed.
+    async fn publish(
+        &self,
+        workspace: &Workspace,
+        metadata: &plugin::SourceMetadata,
+        output_path: &Path,
+        kept: &BTreeSet<PathBuf>,
+    ) -> Result<Vec<Warning>> {
+        self.inject_fault(chaos::FaultPoint::Write, &metadata.name)?;
+
+        let generation = &self.config.generation;
+        let violations =
+            if generation.max_output_bytes.is_some() || generation.max_file_bytes.is_some() {
+                generation.check_output_budget(
+                    &metadata.name,
+                    &workspace.published_sizes(output_path, kept)?,
+                )
+            } else {
+                Vec::new()
+            };
+        if !violations.is_empty() && generation.on_budget_exceeded == config::PolicyAction::Fail {
+            return Err(anyhow::anyhow!("{}", violations.join("\n")));
+        }
+        for violation in &violations {
+            warn!("{}", violation);
+        }
+
+        // Stamp the library with the version of the schemas it was generated from
+        let index_path = workspace.output_dir().join("index.libsonnet");
+        if let Some(version) = jsonnet_generator::version::library_version(
+            metadata.tag.as_deref(),
+            metadata.commit.as_deref(),
+        ) {
+            if index_path.exists() {
+                let content = std::fs::read_to_string(&index_path)?;
+                std::fs::write(
+                    &index_path,
+                    jsonnet_generator::version::stamp_index(&content, &version),
+                )?;
+            }
+        }
+
+        self.check_staged_api(workspace, metadata, output_path)?;
+        self.resource_tracker
+            .add_generated_files(&metadata.name, workspace.staged_files())?;
+
+        let header = self.file_header(metadata);
+        workspace.publish(
+            output_path,
+            kept,
+            self.config.output.newline,
+            &self.config.output.style,
+            header.as_deref(),
+        )?;
+
+        // Built from the published library, which includes files kept from earlier runs
+        if self.config.output.bundle_single_file {
+            let content = jsonnet_generator::single_file::generate_single_file(
+                output_path,
+                &self.config.output.style,
+            )?;
+            jsonnet_generator::write::write_generated(
+                &output_path.join(jsonnet_generator::single_file::SINGLE_FILE),
+                &format!("{}{}", header.unwrap_or_default(), content),
+                self.config.output.newline,
+            )?;
+        }
+
+        if let Some(verify) = &self.config.generation.verify_examples {
+            self.verify_examples(verify, output_path).await?;
+        }
+
+        for mirror in self.mirror_paths(output_path)? {
+            utils::replace_directory(output_path, &mirror)?;
+            info!("Mirrored {:?} to {:?}", output_path, mirror);
+        }
+
+        // Logical sources are named `<source>/<entry>` and count towards their source
+        let bytes = workspace.published_bytes(output_path)?;
+        let source = metadata.name.split('/').next().unwrap_or_default();
+        self.source_statistics
+            .lock()
+            .unwrap()
+            .entry(source.to_string())
+            .or_default()
+            .bytes_written += bytes;
+        Ok(violations
+            .into_iter()
+            .map(|violation| Warning::new(WarningCategory::Budget, "output-budget", violation))
+            .collect())
+    }
+
+    /// Where a path below `output.base_path` goes in each of `output.mirrors`
+    fn mirror_paths(&self, path: &Path) -> Result<Vec<PathBuf>> {
+        if self.config.output.mirrors.is_empty() {
+            return Ok(Vec::new());
+        }
+        let base = utils::resolve_path(&self.config.output.base_path)?;
+        let relative = utils::resolve_path(path)?
+            .strip_prefix(&base)
+            .map(Path::to_path_buf)
+            .map_err(|_| anyhow::anyhow!("{:?} is outside output.base_path {:?}", path, base))?;
+        Ok(self
+            .config
+            .output
+            .mirrors
+            .iter()
+            .map(|mirror| mirror.join(&relative))
+            .collect())
+    }
+
+    /// Rendered header of a source's files, if configured
+    fn file_header(&self, metadata: &plugin::SourceMetadata) -> Option<String> {
+        let template =
+            jsonnet_generator::header::source_template(&self.config.output, &metadata.name)?;
+        let values = jsonnet_generator::header::HeaderValues {
+            source: metadata.name.clone(),
+            source_url: metadata.repository.clone().unwrap_or_default(),
+            git_ref: metadata.git_ref.clone().unwrap_or_default(),
+            commit: metadata.commit.clone().unwrap_or_default(),
+        };
+        Some(jsonnet_generator::header::render_header(
+            &template,
+            &values,
+            self.config.output.timestamps.then(|| self.clock.now()),
+        ))
+    }
+
+    /// The CRD parser, restricted to the versions a source selects
+    fn crd_parser_for(&self, versions: jsonnet_crd::VersionSelection) -> CrdParser {
+        self.crd_parser.clone().with_versions(versions)
+    }
+
+    /// Run a step of processing a source, adding its duration to the source's statistics
+    async fn timed<T>(
+        &self,
+        source: &str,
+        phase: Phase,
+        step: impl std::future::Future<Output = T>,
+    ) -> T {
+        let started = Instant::now();
+        let output = step.await;
+        let elapsed = started.elapsed().as_millis() as u64;
+
+        // Logical sources are named `<source>/<entry>` and count towards their source
+        let source = source.split('/').next().unwrap_or_default();
+        let mut statistics = self.source_statistics.lock().unwrap();
+        let statistics = statistics.entry(source.to_string()).or_default();
+        match phase {
+            Phase::Clone => statistics.clone_ms += elapsed,
+            Phase::Parse => statistics.parse_ms += elapsed,
+        }
+        output
+    }
+
+    /// Fail with an injected error when chaos testing is enabled
+    fn inject_fault(&self, point: chaos::FaultPoint, target: &str) -> Result<()> {
+        match &self.chaos {
+            Some(chaos) => chaos.inject(point, target),
+            None => Ok(()),
+        }
+    }
+
+    /// Plugin context for processing a source in its workspace
+    fn plugin_context(
+        &self,
+        workspace: &Workspace,
+        plugin_config: PluginConfig,
+        metadata: &plugin::SourceMetadata,
+    ) -> PluginContext {
+        let context = PluginContext::new(
+            workspace.scratch_dir(),
+            workspace.output_dir(),
+            plugin_config,
+        )
+        .with_source(metadata.clone())
+        .with_shared_state(self.shared_state.clone())
+        .with_strict_schemas(self.config.generation.strict_schemas)
+        .with_strict_crds(self.config.generation.strict_crds);
+
+        match self.plugin_state_dir() {
+            Some(state_dir) => context.with_state_dir(state_dir),
+            None => context,
+        }
+    }
+
+    /// Directory plugins keep state in across runs, if enabled
+    fn plugin_state_dir(&self) -> Option<PathBuf> {
+        if !self.config.plugins.persistent_state {
+            return None;
+        }
+
+        match self.expand_plugin_directory(&self.config.plugins.cache_directory) {
+            Ok(cache_directory) => Some(cache_directory.join("state")),
+            Err(e) => {
+                warn!("Persistent plugin state disabled: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Prepare the workspace of a git-backed source at its checked-out commit
+    ///
+    /// Also returns the source metadata handed to plugins.
+    fn prepare_git_workspace(
+        &self,
+        source_id: &str,
+        git: &crate::config::GitSource,
+        filters: &[String],
+        repo_path: &Path,
+    ) -> Result<(Workspace, plugin::SourceMetadata)> {
+        let commit_sha = self.git_fetcher.get_current_commit(repo_path)?;
+        let license = self.resolve_license(source_id, repo_path)?;
+        let workspace = self.workspace_manager.prepare(source_id, &commit_sha)?;
+        Ok((
+            workspace,
+            plugin::SourceMetadata {
+                license,
+                tag: self
+                    .git_fetcher
+                    .current_tag(repo_path, git.ref_name.as_deref())?,
+                ..git_source_metadata(source_id, git, filters, &commit_sha)
+            },
+        ))
+    }
+
+    /// License of a source: its configured override, or the one detected in `dir`
+    fn source_license(&self, source_name: &str, dir: &Path) -> Option<String> {
+        self.config
+            .licenses
+            .overrides
+            .get(source_name)
+            .cloned()
+            .or_else(|| license::detect(dir))
+    }
+
+    /// License of a source, failing if it is not in `licenses.allowed`
+    fn resolve_license(&self, source_name: &str, dir: &Path) -> Result<Option<String>> {
+        let license = self.source_license(source_name, dir);
+        self.config
+            .licenses
+            .check(source_name, license.as_deref())?;
+        Ok(license)
+    }
+
+    /// Directory a source's license file is looked up in
+    async fn license_dir(&self, source: &Source) -> Result<PathBuf> {
+        match source {
+            Source::Crd(crd_source) => self.git_fetcher.ensure_repository(&crd_source.git).await,
+            Source::GoAst(go_ast_source) => {
+                self.git_fetcher.ensure_repository(&go_ast_source.git).await
+            }
+            Source::OpenApi(openapi_source) => {
+                self.git_fetcher
+                    .ensure_repository(&openapi_source.git)
+                    .await
+            }
+            Source::Url(url_source) => Ok(self.fetch_url_source(url_source).await?.dir),
+            Source::Release(release_source) => {
+                Ok(self.fetch_release_source(release_source).await?.1.dir)
+            }
+            Source::Fixture(fixture_source) => {
+                Ok(fixture::materialize(fixture_source.fixture, &fixture::cache_root()?)?.dir)
+            }
+            Source::Jsonnet(jsonnet_source) => Ok(library::Library::open(jsonnet_source)?.dir),
+            Source::Kustomize(kustomize_source) => {
+                self.git_fetcher
+                    .ensure_repository(&kustomize_source.git)
+                    .await
+            }
+            Source::Catalog(catalog_source) => {
+                self.git_fetcher
+                    .ensure_repository(&catalog_source.git)
+                    .await
+            }
+            Source::Auto(auto_source) => self.git_fetcher.ensure_repository(&auto_source.git).await,
+        }
+    }
+
+    /// Fetch a URL source, verifying it against its pin or the digest recorded in the lockfile
+    async fn fetch_url_source(
+        &self,
+        url_source: &crate::config::UrlSource,
+    ) -> Result<fetch::FetchedArtifact> {
+        let expected = match &url_source.sha256 {
+            Some(sha256) => Some(sha256.clone()),
+            None => self
+                .lockfile_manager
+                .load_or_create()?
+                .sources
+                .get(&url_source.name)
+                .filter(|entry| entry.url == jsonnet_lockfile::redact_url(&url_source.url))
+                .and_then(|entry| entry.digest.clone()),
+        };
+        if self.locked && expected.is_none() {
+            return Err(JsonnetGenError::NotLocked(url_source.name.clone()).into());
+        }
+
+        self.url_fetcher
+            .fetch(url_source, expected.as_deref())
+            .await
+    }
+
+    /// Resolve and fetch a release source's asset
+    ///
+    /// The asset is verified against the digest recorded in the lockfile while
+    /// the source still resolves to the locked release tag. Generating locked,
+    /// the source is resolved at that tag.
+    async fn fetch_release_source(
+        &self,
+        release_source: &crate::config::ReleaseSource,
+    ) -> Result<(release::ResolvedAsset, fetch::FetchedArtifact)> {
+        let lockfile = self.lockfile_manager.load_or_create()?;
+        let locked_entry = lockfile.sources.get(&release_source.name);
+        let asset = if self.locked {
+            let entry = locked_entry
+                .filter(|entry| entry.url == release_source.repository())
+                .ok_or_else(|| JsonnetGenError::NotLocked(release_source.name.clone()))?;
+            let mut locked_source = release_source.clone();
+            locked_source.tag = Some(entry.ref_name.clone());
+            self.release_resolver.resolve(&locked_source).await?
+        } else {
+            self.release_resolver.resolve(release_source).await?
+        };
+        let expected = locked_entry
+            .filter(|entry| entry.url == release_source.repository() && entry.ref_name == asset.tag)
+            .and_then(|entry| entry.digest.clone());
+
+        let (url, credentials) = self
+            .release_resolver
+            .download_request(release_source, &asset)?;
+        let artifact = self
+            .url_fetcher
+            .fetch_authenticated(&url, &asset.name, credentials.as_ref(), expected.as_deref())
+            .await?;
+        Ok((asset, artifact))
+    }
+
+    /// Process source with plugins
+    async fn process_with_plugins(
+        &self,
+        crd_source: &crate::config::CrdSource,
+    ) -> Result<SourceResult> {
+        // Create plugin context
+        let plugin_config = PluginConfig {
+            plugin_id: "crd:builtin".to_string(),
+            config: serde_yaml::Value::Null,
+            enabled_capabilities: vec![
+                plugin::PluginCapability::Parse,
+                plugin::PluginCapability::SchemaExtraction,
+            ],
+        };
+
+        let repo_path = self
+            .timed(
+                &crd_source.name,
+                Phase::Clone,
+                self.git_fetcher.ensure_repository(&crd_source.git),
+            )
+            .await?;
+        self.inject_fault(chaos::FaultPoint::Parse, &crd_source.name)?;
+        let (workspace, metadata) = self.prepare_git_workspace(
+            &crd_source.name,
+            &crd_source.git,
+            &crd_source.filters,
+            &repo_path,
+        )?;
+        let context = self.plugin_context(&workspace, plugin_config, &metadata);
+
+        // Process with plugin manager
+        let (plugin_id, plugin_result) = self
+            .timed(
+                &crd_source.name,
+                Phase::Parse,
+                self.plugin_manager
+                    .process_source_with(&crd_source.plugin, &repo_path, &context),
+            )
+            .await?;
+        self.check_plugin_output(
+            &crd_source.name,
+            &plugin_id,
+            &workspace.output_dir(),
+            &plugin_result.schemas,
+            &plugin_result.generated_files,
+        )?;
+        let produced: Vec<(String, PathBuf)> = plugin_result
+            .generated_files
+            .iter()
+            .map(|file| (plugin_id.clone(), file.clone()))
+            .collect();
+        let verification = self
+            .output_verifier
+            .verify(&crd_source.name, &workspace.output_dir(), &produced)
+            .await?;
+        let budget_warnings = self
+            .publish(
+                &workspace,
+                &metadata,
+                &crd_source.output_path,
+                &BTreeSet::new(),
+            )
+            .await?;
+
+        // Convert plugin result to source result
+        let mut warnings: Vec<Warning> = plugin_warnings(plugin_result.warnings).collect();
+        warnings.extend(budget_warnings);
+        let mut errors = plugin_result.errors;
+        errors.extend(verification.errors());
+        Ok(SourceResult {
+            source_name: crd_source.name.clone(),
+            source_type: "crd".to_string(),
+            files_generated: verification.kept.len(),
+            errors,
+            output_path: crd_source.output_path.clone(),
+            processing_time_ms: plugin_result.statistics.processing_time_ms,
+            warnings,
+            plugin: Some(plugin_id),
+            file_plugins: verification.file_plugins(),
+            schema_digests: Default::default(),
+            schemas_reused: 0,
+            statistics: SourceStatistics {
+                plugin_metrics: plugin_result.statistics.metrics,
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Process Go source with AST plugin
+    async fn process_go_source(
+        &self,
+        go_ast_source: &crate::config::GoAstSource,
+    ) -> Result<SourceResult> {
+        let start_time = std::time::Instant::now();
+
+        // Ensure repository is available
+        let repo_path = self
+            .timed(
+                &go_ast_source.name,
+                Phase::Clone,
+                self.git_fetcher.ensure_repository(&go_ast_source.git),
+            )
+            .await?;
+
+        // Find Go source files
+        let go_files = self
+            .find_go_files(
+                &repo_path,
+                &go_ast_source.include_patterns,
+                &go_ast_source.exclude_patterns,
+            )
+            .await?;
+
+        if go_files.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No Go source files found matching the patterns"
+            ));
+        }
+
+        let (workspace, metadata) = self.prepare_git_workspace(
+            &go_ast_source.name,
+            &go_ast_source.git,
+            &go_ast_source.include_patterns,
+            &repo_path,
+        )?;
+
+        // Process each Go file with the plugin
+        let mut all_schemas = Vec::new();
+        let mut plugins_used = BTreeSet::new();
+        let mut file_errors = Vec::new();
+        let mut parse_warnings = Vec::new();
+        let mut produced = Vec::new();
+        let mut plugin_metrics = PluginMetrics::default();
+
+        for go_file in &go_files {
+            let processed = self.process_go_file_with_plugin(
+                go_file,
+                &workspace,
+                &metadata,
+                &go_ast_source.plugin,
+            );
+            match self
+                .timed(&go_ast_source.name, Phase::Parse, processed)
+                .await
+            {
+                Ok((plugin_id, result)) => {
+                    self.check_plugin_output(
+                        &go_ast_source.name,
+                        &plugin_id,
+                        &workspace.output_dir(),
+                        &result.schemas,
+                        &result.generated_files,
+                    )?;
+                    produced.extend(
+                        result
+                            .generated_files
+                            .into_iter()
+                            .map(|file| (plugin_id.clone(), file)),
+                    );
+                    plugins_used.insert(plugin_id);
+                    all_schemas.extend(result.schemas.into_iter().map(|mut schema| {
+                        schema.source_file = self.source_file_path(&schema.source_file, &repo_path);
+                        schema
+                    }));
+                    parse_warnings.extend(plugin_warnings(result.warnings));
+                    plugin_metrics.merge(&result.statistics.metrics);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to process Go file {}: {}", go_file.display(), e);
+                    file_errors.push(format!("Failed to process {}: {}", go_file.display(), e));
+                }
+            }
+        }
+
+        self.apply_transforms(
+            &mut all_schemas,
+            &go_ast_source.transforms,
+            &workspace,
+            &metadata,
+        )
+        .await?;
+
+        // Evaluate policy rules before generating
+        let policy_warnings = self.check_policies(
+            all_schemas
+                .iter()
+                .map(|schema| (schema.name.as_str(), policy::extracted_subject(schema))),
+        )?;
+
+        // Generate Jsonnet code from schemas
+        let generated_files = self
+            .generate_jsonnet_from_schemas(&all_schemas, &workspace.output_dir())
+            .await?;
+        let verification = self
+            .output_verifier
+            .verify(&go_ast_source.name, &workspace.output_dir(), &produced)
+            .await?;
+        file_errors.extend(verification.errors());
+        let budget_warnings = self
+            .publish(
+                &workspace,
+                &metadata,
+                &go_ast_source.output_path,
+                &BTreeSet::new(),
+            )
+            .await?;
+
+        let processing_time = start_time.elapsed();
+
+        let mut result = SourceResult {
+            source_name: go_ast_source.name.clone(),
+            source_type: "go_ast".to_string(),
+            files_generated: generated_files.len(),
+            errors: file_errors,
+            output_path: go_ast_source.output_path.clone(),
+            processing_time_ms: processing_time.as_millis() as u64,
+            warnings: parse_warnings,
+            plugin: joined_plugin_ids(&plugins_used),
+            file_plugins: verification.file_plugins(),
+            schema_digests: Default::default(),
+            schemas_reused: 0,
+            statistics: SourceStatistics {
+                plugin_metrics,
+                ..Default::default()
+            },
+        };
+        result.warnings.extend(policy_warnings);
+        result.warnings.extend(budget_warnings);
+
+        Ok(result)
+    }
+
+    /// Find Go source files matching the patterns
+    async fn find_go_files(
+        &self,
+        repo_path: &Path,
+        include_patterns: &[String],
+        exclude_patterns: &[String],
+    ) -> Result<Vec<PathBuf>> {
+        let mut go_files = Vec::new();
+
+        for pattern in include_patterns {
+            let glob_pattern = repo_path.join(pattern);
+            let entries = glob::glob(&glob_pattern.to_string_lossy())?;
+
+            for entry in entries {
+                match entry {
+                    Ok(path) => {
+                        // Check if file should be excluded
+                        let should_exclude = exclude_patterns.iter().any(|exclude_pattern| {
+                            let exclude_glob = repo_path.join(exclude_pattern);
+                            if let Ok(mut exclude_entries) =
+                                glob::glob(&exclude_glob.to_string_lossy())
+                            {
+                                exclude_entries.any(|exclude_entry| {
+                                    exclude_entry.is_ok_and(|exclude_path| exclude_path == path)
+                                })
+                            } else {
+                                false
+                            }
+                        });
+
+                        if !should_exclude && path.is_file() {
+                            go_files.push(path);
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to match pattern {}: {}", pattern, e);
+                    }
+                }
+            }
+        }
+
+        Ok(go_files)
+    }
+
+    /// Process a single Go file with the plugin
+    async fn process_go_file_with_plugin(
+        &self,
+        go_file: &Path,
+        workspace: &Workspace,
+        metadata: &plugin::SourceMetadata,
+        binding: &plugin::PluginBinding,
+    ) -> Result<(plugin::PluginId, crate::plugin::PluginResult)> {
+        self.inject_fault(
+            chaos::FaultPoint::Parse,
+            &format!("{} ({})", metadata.name, go_file.display()),
+        )?;
+
+        // Create plugin context
+        let plugin_config = crate::plugin::PluginConfig {
+            plugin_id: "go-ast:builtin".to_string(),
+            config: serde_yaml::to_value(&self.config.generation.parse_limits)?,
+            enabled_capabilities: vec![
+                crate::plugin::PluginCapability::Parse,
+                crate::plugin::PluginCapability::SchemaExtraction,
+                crate::plugin::PluginCapability::AstProcessing,
+            ],
+        };
+
+        let context = self.plugin_context(workspace, plugin_config, metadata);
+
+        // Process with plugin manager
+        self.plugin_manager
+            .process_source_with(binding, go_file, &context)
+            .await
+    }
+
+    /// Generate Jsonnet code from extracted schemas
+    async fn generate_jsonnet_from_schemas(
+        &self,
+        schemas: &[crate::plugin::ExtractedSchema],
+        output_path: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        let mut generated_files = Vec::new();
+
+        // Ensure output directory exists
+        tokio::fs::create_dir_all(output_path).await?;
+
+        for schema in schemas {
+            generated_files.push(self.write_schema_file(schema, output_path)?);
+        }
+
+        Ok(generated_files)
+    }
+
+    /// Generate the Jsonnet file of one schema into an existing directory
+    fn write_schema_file(
+        &self,
+        schema: &crate::plugin::ExtractedSchema,
+        output_path: &Path,
+    ) -> Result<PathBuf> {
+        let mut schema = schema.clone();
+        self.config.output.style.order_fields(&mut schema.content);
+        let schema = &schema;
+
+        // Callbacks, webhooks, parameters and servers get their own directories
+        let (output_file, jsonnet_code) = match schema_group(schema) {
+            Some(group) => {
+                let dir = output_path.join(group);
+                std::fs::create_dir_all(&dir)?;
+                let code = match group {
+                    "parameters" => generate_parameter_builder(schema),
+                    "servers" => generate_servers_library(schema),
+                    _ => generate_event_builder(schema, self.config.output.field_naming)?,
+                };
+                (dir.join(schema_file_name(schema)), code)
+            }
+            None => (
+                output_path.join(schema_file_name(schema)),
+                self.generate_jsonnet_code(schema)?,
+            ),
+        };
+        jsonnet_generator::write::write_generated(
+            &output_file,
+            &jsonnet_code,
+            self.config.output.newline,
+        )?;
+
+        Ok(output_file)
+    }
+
+    /// Generate Jsonnet code from schema
+    fn generate_jsonnet_code(&self, schema: &crate::plugin::ExtractedSchema) -> Result<String> {
+        let mut code = String::new();
+
+        code.push_str(&format!("// Generated from Go AST: {}\n", schema.name));
+        code.push_str(&format!("// Source: {}\n\n", schema.source_file.display()));
+
+        // Add imports
+        code.push_str("local k = import \"k.libsonnet\";\n");
+        code.push_str("local validate = import \"_validation.libsonnet\";\n\n");
+
+        // Generate the main function
+        code.push_str(&format!("// Create a new {} resource\n", schema.name));
+        code.push_str("function(metadata, spec={}) {\n");
+        code.push_str(&format!(
+            "  apiVersion: \"{}\",\n",
+            schema.name.to_lowercase()
+        ));
+        code.push_str(&format!("  kind: \"{}\",\n", schema.name));
+        code.push_str("  metadata: metadata,\n");
+        code.push_str("  spec: spec,\n");
+        code.push_str("}\n");
+
+        Ok(code)
+    }
+
+    /// Process OpenAPI source with plugin
+    async fn process_openapi_source(
+        &self,
+        openapi_source: &crate::config::OpenApiSource,
+    ) -> Result<SourceResult> {
+        let start_time = std::time::Instant::now();
+
+        // Ensure repository is available
+        let repo_path = self
+            .timed(
+                &openapi_source.name,
+                Phase::Clone,
+                self.git_fetcher.ensure_repository(&openapi_source.git),
+            )
+            .await?;
+
+        // Find OpenAPI specification files
+        let openapi_files = self
+            .find_openapi_files(
+                &repo_path,
+                &openapi_source.include_patterns,
+                &openapi_source.exclude_patterns,
+            )
+            .await?;
+
+        if openapi_files.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No OpenAPI specification files found matching the patterns"
+            ));
+        }
+
+        let (workspace, metadata) = self.prepare_git_workspace(
+            &openapi_source.name,
+            &openapi_source.git,
+            &openapi_source.include_patterns,
+            &repo_path,
+        )?;
+
+        self.generate_openapi_output(
+            &repo_path,
+            &openapi_files,
+            &workspace,
+            &metadata,
+            &openapi_source.plugin,
+            &openapi_source.transforms,
+            &openapi_source.output_path,
+            &config::OpenApiOptions {
+                include_callbacks: openapi_source.include_callbacks,
+            },
+            start_time,
+        )
+        .await
+    }
+
+    /// Extract schemas from OpenAPI files below `source_dir` and generate a
+    /// source's libraries from them
+    #[allow(clippy::too_many_arguments)]
+    async fn generate_openapi_output(
+        &self,
+        source_dir: &Path,
+        openapi_files: &[PathBuf],
+        workspace: &Workspace,
+        metadata: &plugin::SourceMetadata,
+        binding: &plugin::PluginBinding,
+        transforms: &[transform::Transform],
+        output_path: &Path,
+        options: &config::OpenApiOptions,
+        start_time: Instant,
+    ) -> Result<SourceResult> {
+        // Files are written as the plugins stream schemas, unless a plugin
+        // transform has to see all of them at once
+        let buffered = transforms
+            .iter()
+            .any(|step| matches!(step, transform::Transform::Plugin(_)));
+        let staging = workspace.output_dir();
+        tokio::fs::create_dir_all(&staging).await?;
+
+        let mut buffer = Vec::new();
+        let mut generated_files = Vec::new();
+        let mut policy_warnings = Vec::new();
+        let mut plugins_used = BTreeSet::new();
+        let mut file_errors = Vec::new();
+        let mut plugin_metrics = PluginMetrics::default();
+
+        for openapi_file in openapi_files {
+            let opened = self.stream_openapi_file_with_plugin(
+                openapi_file,
+                workspace,
+                metadata,
+                binding,
+                options,
+            );
+            let (plugin_id, mut stream) = match opened.await {
+                Ok(opened) => opened,
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to process OpenAPI file {}: {}",
+                        openapi_file.display(),
+                        e
+                    );
+                    file_errors.push(format!(
+                        "Failed to process {}: {}",
+                        openapi_file.display(),
+                        e
+                    ));
+                    continue;
+                }
+            };
+
+            while let Some(mut schema) = self
+                .timed(&metadata.name, Phase::Parse, stream.next())
+                .await
+            {
+                schema.source_file = self.source_file_path(&schema.source_file, source_dir);
+                self.check_plugin_output(
+                    &metadata.name,
+                    &plugin_id,
+                    &staging,
+                    std::slice::from_ref(&schema),
+                    &[],
+                )?;
+                if buffered {
+                    buffer.push(schema);
+                    continue;
+                }
+
+                for step in transforms {
+                    transform::apply_builtin(step, &mut schema);
+                }
+                // Evaluate policy rules before generating
+                policy_warnings.extend(self.check_policies([(
+                    schema.name.as_str(),
+                    policy::extracted_subject(&schema),
+                )])?);
+                generated_files.push(self.write_schema_file(&schema, &staging)?);
+            }
+
+            match stream.finish().await {
+                Ok(statistics) => {
+                    plugins_used.insert(plugin_id);
+                    plugin_metrics.merge(&statistics.metrics);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to process OpenAPI file {}: {}",
+                        openapi_file.display(),
+                        e
+                    );
+                    file_errors.push(format!(
+                        "Failed to process {}: {}",
+                        openapi_file.display(),
+                        e
+                    ));
+                }
+            }
+        }
+
+        if buffered {
+            self.apply_transforms(&mut buffer, transforms, workspace, metadata)
+                .await?;
+            policy_warnings.extend(
+                self.check_policies(
+                    buffer
+                        .iter()
+                        .map(|schema| (schema.name.as_str(), policy::extracted_subject(schema))),
+                )?,
+            );
+            generated_files = self
+                .generate_jsonnet_from_schemas(&buffer, &staging)
+                .await?;
+        }
+
+        let budget_warnings = self
+            .publish(workspace, metadata, output_path, &BTreeSet::new())
+            .await?;
+
+        let processing_time = start_time.elapsed();
+
+        let mut result = SourceResult {
+            source_name: metadata.name.clone(),
+            source_type: "openapi".to_string(),
+            files_generated: generated_files.len(),
+            errors: file_errors,
+            output_path: output_path.to_path_buf(),
+            processing_time_ms: processing_time.as_millis() as u64,
+            warnings: Vec::new(),
+            plugin: joined_plugin_ids(&plugins_used),
+            file_plugins: Default::default(),
+            schema_digests: Default::default(),
+            schemas_reused: 0,
+            statistics: SourceStatistics {
+                plugin_metrics,
+                ..Default::default()
+            },
+        };
+        result.warnings.extend(policy_warnings);
+        result.warnings.extend(budget_warnings);
+
+        Ok(result)
+    }
+
+    /// Get current source commit information
+    /// Pin every source to the commit or digest recorded in the lockfile
+    fn pin_locked_sources(&self) -> Result<()> {
+        let lockfile = self.lockfile_manager.load_or_create()?;
+        for source in &self.config.sources {
+            let locked = pinned_commit(&lockfile, source)
+                .ok_or_else(|| JsonnetGenError::NotLocked(source.name().to_string()))?;
+            match source {
+                Source::Url(_) | Source::Release(_) => {}
+                Source::Fixture(fixture_source) => {
+                    if fixture::digest(fixture_source.fixture) != locked {
+                        return Err(anyhow::anyhow!(
+                            "Fixture set {} of source {} changed since it was locked",
+                            fixture_source.fixture.as_str(),
+                            fixture_source.name
+                        ));
+                    }
+                }
+                Source::Jsonnet(jsonnet_source) => {
+                    if library::Library::open(jsonnet_source)?.digest()? != locked {
+                        return Err(anyhow::anyhow!(
+                            "Library {} of source {} changed since it was locked",
+                            jsonnet_source.path.display(),
+                            jsonnet_source.name
+                        ));
+                    }
+                }
+                _ => {
+                    if let Some(git) = source.git_source() {
+                        self.git_fetcher.pin(git, &locked)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetch the upstream of every source that is not skipped
+    ///
+    /// Sources are fetched concurrently, at most `git.max_concurrent_per_host`
+    /// at a time against the same Git host.
+    async fn get_current_source_commits(&self) -> Result<HashMap<String, String>> {
+        let mut commits = HashMap::new();
+
+        let sources: Vec<&Source> = self
+            .config
+            .sources
+            .iter()
+            .filter(|source| !self.is_skipped(source.name()))
+            .collect();
+        let upstream_commits = futures::future::join_all(
+            sources
+                .iter()
+                .map(|source| git::for_source(source.name(), self.upstream_commit(source))),
+        )
+        .await;
+        for (source, upstream_commit) in sources.into_iter().zip(upstream_commits) {
+            match upstream_commit {
+                Ok(commit_sha) => {
+                    commits.insert(source.name().to_string(), commit_sha);
+                }
+                // With quarantine on, an unreachable upstream fails its source
+                // rather than the run
+                Err(e)
+                    if self.config.generation.quarantine_after.is_some()
+                        && !is_resource_limit(&e) =>
+                {
+                    warn!(
+                        "Cannot reach the upstream of source {}: {}",
+                        source.name(),
+                        e
+                    );
+                    self.skipped_sources
+                        .lock()
+                        .unwrap()
+                        .unreachable
+                        .insert(source.name().to_string(), e.to_string());
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(commits)
+    }
+
+    /// Current upstream commit SHA (or artifact digest for URL sources) of a source
+    ///
+    /// Fetches the source's repository or artifact, so the result reflects the
+    /// configured ref rather than the commit pinned in the lockfile.
+    pub async fn upstream_commit(&self, source: &Source) -> Result<String> {
+        match source {
+            Source::Crd(crd_source) => {
+                let repo_path = self.git_fetcher.ensure_repository(&crd_source.git).await?;
+                self.git_fetcher.get_current_commit(&repo_path)
+            }
+            Source::GoAst(go_ast_source) => {
+                let repo_path = self
+                    .git_fetcher
+                    .ensure_repository(&go_ast_source.git)
+                    .await?;
+                self.git_fetcher.get_current_commit(&repo_path)
+            }
+            Source::OpenApi(openapi_source) => {
+                let repo_path = self
+                    .git_fetcher
+                    .ensure_repository(&openapi_source.git)
+                    .await?;
+                self.git_fetcher.get_current_commit(&repo_path)
+            }
+            Source::Url(url_source) => Ok(self.fetch_url_source(url_source).await?.digest),
+            Source::Fixture(fixture_source) => Ok(fixture::digest(fixture_source.fixture)),
+            Source::Jsonnet(jsonnet_source) => library::Library::open(jsonnet_source)?.digest(),
+            Source::Release(release_source) => {
+                Ok(self.fetch_release_source(release_source).await?.1.digest)
+            }
+            Source::Kustomize(kustomize_source) => {
+                let repo_path = self
+                    .git_fetcher
+                    .ensure_repository(&kustomize_source.git)
+                    .await?;
+                self.git_fetcher.get_current_commit(&repo_path)
+            }
+            Source::Catalog(catalog_source) => {
+                let repo_path = self
+                    .git_fetcher
+                    .ensure_repository(&catalog_source.git)
+                    .await?;
+                self.git_fetcher.get_current_commit(&repo_path)
+            }
+            Source::Auto(auto_source) => {
+                let repo_path = self.git_fetcher.ensure_repository(&auto_source.git).await?;
+                self.git_fetcher.get_current_commit(&repo_path)
+            }
+        }
+    }
+
+    /// Upstream commit of a source, read with `ls-remote` where possible
+    ///
+    /// Cheaper than [`Self::upstream_commit`] for status-style commands: the
+    /// ref of a git source is looked up among the remote's refs, cached for
+    /// `git.ls_remote_cache_seconds`, and the repository is only fetched when
+    /// the ref cannot be resolved that way.
+    pub async fn remote_commit(&self, source: &Source) -> Result<String> {
+        if let Some(git_source) = source.git_source() {
+            match self.git_fetcher.remote_commit(git_source).await {
+                Ok(Some(commit)) => return Ok(commit),
+                Ok(None) => info!(
+                    "Ref {} of source {} not listed by the remote, fetching",
+                    git_source.ref_name(),
+                    source.name()
+                ),
+                Err(e) => warn!(
+                    "Listing refs of source {} failed, fetching: {}",
+                    source.name(),
+                    e
+                ),
+            }
+        }
+        self.upstream_commit(source).await
+    }
+
+    /// Configuration this instance was created with
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Lockfile manager used for incremental generation
+    pub fn lockfile_manager(&self) -> &LockfileManager {
+        &self.lockfile_manager
+    }
+
+    /// Find source by ID
+    pub fn find_source_by_id(&self, source_id: &str) -> Option<&Source> {
+        self.config.sources.iter().find(|s| s.name() == source_id)
+    }
+
+    /// Lockfile entries of sources no longer in the configuration
+    pub fn lockfile_orphans(&self) -> Result<jsonnet_lockfile::LockfileOrphans> {
+        let lockfile = self.lockfile_manager.load_or_create()?;
+        Ok(lockfile.orphans(|source_id| {
+            self.config
+                .sources
+                .iter()
+                .any(|source| owns_source_id(source, source_id))
+        }))
+    }
+
+    /// Remove the lockfile entries of sources no longer in the configuration
+    pub fn prune_lockfile(&self) -> Result<jsonnet_lockfile::LockfileOrphans> {
+        let orphans = self.lockfile_orphans()?;
+        if !orphans.is_empty() {
+            let mut lockfile = self.lockfile_manager.load_or_create()?;
+            lockfile.prune(&orphans);
+            self.lockfile_manager.save(&lockfile)?;
+            info!(
+                "Pruned {} sources and {} files from the lockfile",
+                orphans.sources.len(),
+                orphans.files.len()
+            );
+        }
+        Ok(orphans)
+    }
+
+    /// Calculate cache hit rate
+    pub fn calculate_cache_hit_rate(&self, plan: &IncrementalPlan) -> f64 {
+        if plan.requires_full_regeneration() {
+            0.0
+        } else {
+            let total_sources = self.config.sources.len();
+            let cached_sources = total_sources - plan.total_sources();
+            cached_sources as f64 / total_sources as f64
+        }
+    }
+
+    /// Update the lockfile entries of sources at their current commits with generation results
+    async fn update_lockfile(
+        &self,
+        result: &GenerationResult,
+        current_sources: HashMap<String, String>,
+    ) -> Result<()> {
+        let mut lockfile = self.lockfile_manager.load_or_create()?;
+
+        // Schema digests of the sources processed in this run; the others keep
+        // the digests recorded when they were last generated
+        let processed: HashMap<&str, &SourceResult> = result
+            .results
+            .iter()
+            .map(|source_result| (source_result.source_name.as_str(), source_result))
+            .collect();
+        let schema_digests =
+            |lockfile: &jsonnet_lockfile::Lockfile,
+             source_id: &str,
+             processed: Option<(&SourceResult, &str)>| {
+                match processed {
+                    Some((source_result, prefix)) => source_result
+                        .schema_digests
+                        .iter()
+                        .filter_map(|(path, digest)| {
+                            path.strip_prefix(prefix)
+                                .map(|path| (path.to_string(), digest.clone()))
+                        })
+                        .collect(),
+                    None => lockfile
+                        .sources
+                        .get(source_id)
+                        .map(|entry| entry.schema_digests.clone())
+                        .unwrap_or_default(),
+                }
+            };
+
+        // Update sources
+        for (source_id, commit_sha) in current_sources {
+            // A source renamed or removed since its commit was resolved has no
+            // entry to update
+            let Some(source) = self.find_source_by_id(&source_id) else {
+                warn!(
+                    "Skipping lockfile entry of {}: {}",
+                    source_id,
+                    JsonnetGenError::UnknownSource(source_id.clone())
+                );
+                continue;
+            };
+            let license_dir = self.license_dir(source).await?;
+            let license = self.source_license(&source_id, &license_dir);
+
+            // A source that failed keeps the entry of its last successful
+            // generation, so the next run retries it
+            if processed
+                .get(source_id.as_str())
+                .is_some_and(|source_result| source_result.is_failure())
+            {
+                warn!(
+                    "Keeping previous lockfile entry for failed source {}",
+                    source_id
+                );
+                continue;
+            }
+
+            // Catalogs get one entry per discovered logical source
+            if let Source::Catalog(catalog_source) = source {
+                let repo_path = self
+                    .git_fetcher
+                    .ensure_repository(&catalog_source.git)
+                    .await?;
+                for catalog_entry in catalog::discover(&repo_path, &catalog_source.discovery)? {
+                    let entry_id = catalog_entry.source_id(&source_id);
+                    let prefix = format!("{}/", catalog_entry.name);
+                    let mut entry = LockfileEntry::new(
+                        catalog_source.git.url.clone(),
+                        catalog_source.git.ref_name().to_string(),
+                        commit_sha.clone(),
+                        vec![catalog_entry.path.to_string_lossy().to_string()],
+                    );
+                    entry.schema_digests = schema_digests(
+                        &lockfile,
+                        &entry_id,
+                        processed
+                            .get(source_id.as_str())
+                            .map(|source_result| (*source_result, prefix.as_str())),
+                    );
+                    entry.license = license.clone();
+                    lockfile.add_source(entry_id, entry);
+                }
+                continue;
+            }
+
+            // Auto sources get one entry per detected kind of content
+            if let Source::Auto(auto_source) = source {
+                for logical_source in self.detect_auto_source(auto_source).await? {
+                    let prefix = format!("{}/", logical_source.source_type());
+                    let mut entry = LockfileEntry::new(
+                        auto_source.git.url.clone(),
+                        auto_source.git.ref_name().to_string(),
+                        commit_sha.clone(),
+                        logical_source.filters().to_vec(),
+                    );
+                    entry.schema_digests = schema_digests(
+                        &lockfile,
+                        logical_source.name(),
+                        processed
+                            .get(source_id.as_str())
+                            .map(|source_result| (*source_result, prefix.as_str())),
+                    );
+                    entry.license = license.clone();
+                    lockfile.add_source(logical_source.name().to_string(), entry);
+                }
+                continue;
+            }
+
+            let ref_name = match source {
+                Source::Url(_) | Source::Jsonnet(_) => String::new(),
+                Source::Fixture(fixture_source) => fixture_source.fixture.as_str().to_string(),
+                Source::Release(release_source) => {
+                    self.release_resolver.resolve(release_source).await?.tag
+                }
+                _ => source.git_ref().unwrap_or("main").to_string(),
+            };
+            let mut entry = if source.pins_content() {
+                LockfileEntry::with_digest(
+                    source.git_url().to_string(),
+                    ref_name,
+                    commit_sha,
+                    source.filters().to_vec(),
+                )
+            } else {
+                LockfileEntry::new(
+                    source.git_url().to_string(),
+                    ref_name,
+                    commit_sha,
+                    source.filters().to_vec(),
+                )
+            };
+            entry.schema_digests = schema_digests(
+                &lockfile,
+                &source_id,
+                processed
+                    .get(source_id.as_str())
+                    .map(|source_result| (*source_result, "")),
+            );
+            entry.license = license;
+            lockfile.add_source(source_id, entry);
+        }
+
+        // Update files, hashing them in parallel off the async runtime; a file
+        // under the output paths of several sources belongs to the last one
+        let owners: Vec<_> = result
+            .results
+            .iter()
+            .map(|source_result| {
+                (
+                    source_result.output_path.clone(),
+                    source_result.source_name.clone(),
+                    source_result.plugin.clone(),
+                    source_result.file_plugins.clone(),
+                )
+            })
+            .collect();
+        let mut lockfile = tokio::task::spawn_blocking(move || {
+            let mut files: HashMap<PathBuf, usize> = HashMap::new();
+            for (owner, (output_path, ..)) in owners.iter().enumerate() {
+                for file_path in generated_files(output_path) {
+                    files.insert(file_path, owner);
+                }
+            }
+
+            let checksums = lockfile.checksum_files(files.keys().cloned().collect());
+            let reused = checksums
+                .iter()
+                .filter(|(path, checksum)| {
+                    lockfile
+                        .files
+                        .get(path)
+                        .is_some_and(|recorded| recorded.sha256 == checksum.sha256)
+                })
+                .count();
+            debug!(
+                "Checksummed {} generated files, {} unchanged",
+                checksums.len(),
+                reused
+            );
+            lockfile.add_files(checksums.into_iter().map(|(file_path, mut checksum)| {
+                // Files a plugin reported are recorded as that plugin's, the
+                // rest as the source's
+                let (output_path, source_name, plugin, file_plugins) = &owners[files[&file_path]];
+                checksum.metadata.source_id = Some(source_name.clone());
+                checksum.metadata.plugin = file_path
+                    .strip_prefix(output_path)
+                    .ok()
+                    .and_then(|relative| file_plugins.get(relative))
+                    .or(plugin.as_ref())
+                    .cloned();
+                (file_path, checksum)
+            }));
+            lockfile
+        })
+        .await?;
+
+        // Count the runs in a row each processed source failed in
+        for source_result in &result.results {
+            let error = source_result
+                .is_failure()
+                .then(|| source_result.errors.join("; "));
+            let quarantine_after = self.config.generation.quarantine_after;
+            if lockfile.record_health(
+                &source_result.source_name,
+                error.as_deref(),
+                quarantine_after,
+            ) {
+                warn!(
+                    "Quarantined source {} after {} consecutive failures; runs skip it until one with --retry-quarantined generates it",
+                    source_result.source_name,
+                    quarantine_after.unwrap_or_default()
+                );
+            }
+        }
+
+        // Update statistics
+        lockfile.statistics = result.statistics.clone();
+        lockfile.record_timings(&result.statistics);
+
+        if self.hermetic {
+            let root = utils::resolve_path(
+                self.lockfile_manager
+                    .path()
+                    .parent()
+                    .unwrap_or(Path::new("")),
+            )?;
+            lockfile.files = lockfile
+                .files
+                .into_iter()
+                .map(|(file_path, checksum)| {
+                    let resolved = utils::resolve_path(&file_path)?;
+                    let relative = resolved.strip_prefix(&root).map_err(|_| {
+                        anyhow::anyhow!(
+                            "Hermetic lockfile cannot record {:?} outside of {:?}",
+                            file_path,
+                            root
+                        )
+                    })?;
+                    Ok((relative.to_path_buf(), checksum))
+                })
+                .collect::<Result<_>>()?;
+            lockfile.make_hermetic();
+        }
+
+        self.lockfile_manager.save(&lockfile)?;
+        Ok(())
+    }
+
+    /// Initialize the application (create directories, validate config, etc.)
+    pub async fn initialize(&self) -> Result<()> {
+        info!("Initializing JsonnetGen application");
+
+        // Ensure output directories exist
+        std::fs::create_dir_all(&self.config.output.base_path)?;
+
+        // Validate configuration
+        self.config.validate()?;
+
+        // Initialize plugin system
+        self.initialize_plugins().await?;
+
+        // Initialize lockfile if it doesn't exist
+        if !LockfileManager::default_path().exists() {
+            let lockfile = jsonnet_lockfile::Lockfile::new();
+            self.lockfile_manager.save(&lockfile)?;
+        }
+
+        info!("Initialization completed successfully");
+        Ok(())
+    }
+
+    /// Clean up stale entries
+    pub fn cleanup(&self, max_age_hours: u64) -> Result<()> {
+        info!(
+            "Cleaning up stale entries older than {} hours",
+            max_age_hours
+        );
+        self.lockfile_manager.cleanup_stale_entries(max_age_hours)?;
+        info!("Cleanup completed successfully");
+        Ok(())
+    }
+
+    /// Perform a dry run of cleanup to show what would be cleaned
+    pub fn cleanup_dry_run(&self, max_age_hours: u64) -> Result<CleanupDryRunResult> {
+        info!(
+            "Dry run: Checking for stale entries older than {} hours",
+            max_age_hours
+        );
+
+        let lockfile = self.lockfile_manager.load_or_create()?;
+        let mut stale_sources = Vec::new();
+        let mut stale_files = Vec::new();
+        let mut total_size_freed = 0u64;
+
+        // Check for stale sources
+        for (source_id, entry) in &lockfile.sources {
+            if entry.is_stale(max_age_hours) {
+                stale_sources.push(CleanupSourceEntry {
+                    source_id: source_id.clone(),
+                    git_url: entry.url.clone(),
+                    git_ref: entry.ref_name.clone(),
+                    fetched_at: entry.fetched_at,
+                    age_hours: (Utc::now()
+                        .signed_duration_since(entry.fetched_at)
+                        .num_hours() as u64),
+                });
+            }
+        }
+
+        // Check for stale files
+        for (file_path, checksum) in &lockfile.files {
+            if checksum.is_stale(max_age_hours) {
+                stale_files.push(CleanupFileEntry {
+                    file_path: file_path.clone(),
+                    size: checksum.size,
+                    modified_at: checksum.modified_at,
+                    age_hours: (Utc::now()
+                        .signed_duration_since(checksum.modified_at)
+                        .num_hours() as u64),
+                });
+                total_size_freed += checksum.size;
+            }
+        }
+
+        let total_sources_removed = stale_sources.len();
+        let total_files_removed = stale_files.len();
+
+        let result = CleanupDryRunResult {
+            max_age_hours,
+            stale_sources,
+            stale_files,
+            total_sources_removed,
+            total_files_removed,
+            total_size_freed,
+            lockfile_path: self.lockfile_manager.path().clone(),
+        };
+
+        info!(
+            "Dry run: Would remove {} sources and {} files ({} bytes)",
+            result.total_sources_removed, result.total_files_removed, result.total_size_freed
+        );
+
+        Ok(result)
+    }
+
+    /// Get generation status
+    pub async fn get_status(&self) -> Result<GenerationStatus> {
+        let lockfile = self.lockfile_manager.load_or_create()?;
+        let mut current_sources = HashMap::new();
+        for source in &self.config.sources {
+            current_sources.insert(source.name().to_string(), self.remote_commit(source).await?);
+        }
+        let incremental_plan = self
+            .lockfile_manager
+            .get_incremental_plan(&current_sources.keys().cloned().collect::<Vec<_>>())?;
+
+        Ok(GenerationStatus {
+            last_generation: lockfile.generated_at,
+            tool_version: lockfile.tool_version,
+            sources_count: self.config.sources.len(),
+            changed_sources: incremental_plan.changed_sources,
+            dependent_sources: incremental_plan.dependent_sources,
+            can_incremental: incremental_plan.can_incremental,
+            estimated_time_ms: incremental_plan.estimated_time_ms,
+            source_estimates_ms: incremental_plan.source_estimates_ms,
+            statistics: lockfile.statistics,
+        })
+    }
+
+    /// Sources whose upstream commit differs from the one pinned in the lockfile
+    ///
+    /// Sources that were never generated are outdated too.
+    pub async fn outdated_sources(&self) -> Result<Vec<OutdatedSource>> {
+        let lockfile = self.lockfile_manager.load_or_create()?;
+        self.git_fetcher.forget_fetched();
+        let mut outdated = Vec::new();
+
+        for source in &self.config.sources {
+            let upstream = self.remote_commit(source).await?;
+            let pinned = pinned_commit(&lockfile, source);
+            if pinned.as_deref() != Some(upstream.as_str()) {
+                outdated.push(OutdatedSource {
+                    name: source.name().to_string(),
+                    pinned,
+                    upstream,
+                });
+            }
+        }
+
+        Ok(outdated)
+    }
+
+    /// Inspect one source: where it comes from, which plugin processes it,
+    /// what its filters select and which libraries it would generate
+    ///
+    /// CRD sources are fetched and parsed to count the CRDs each filter
+    /// matches; other sources report their configuration only.
+    pub async fn inspect_source(&self, name: &str) -> Result<SourceInspection> {
+        let source = self
+            .config
+            .sources
+            .iter()
+            .find(|source| source.name() == name)
+            .ok_or_else(|| anyhow::anyhow!("Source '{}' not found in configuration", name))?;
+        let lockfile = self.lockfile_manager.load_or_create()?;
+
+        let mut inspection = SourceInspection {
+            name: source.name().to_string(),
+            source_type: source.source_type().to_string(),
+            url: jsonnet_lockfile::redact_url(source.git_url()),
+            git_ref: source.git_ref().map(str::to_string),
+            pinned: pinned_commit(&lockfile, source),
+            upstream: self.remote_commit(source).await.map_err(|e| e.to_string()),
+            plugin_binding: source
+                .plugin_binding()
+                .map(|binding| binding.clone().into()),
+            plugin: None,
+            crds_found: None,
+            filters: source
+                .filters()
+                .iter()
+                .map(|filter| FilterMatches {
+                    filter: filter.clone(),
+                    matched: None,
+                })
+                .collect(),
+            excluded: Vec::new(),
+            output_path: source.output_path().to_path_buf(),
+            layout: Vec::new(),
+        };
+
+        let Source::Crd(crd_source) = source else {
+            return Ok(inspection);
+        };
+        let repo_path = self.git_fetcher.ensure_repository(&crd_source.git).await?;
+        let parser = CrdParser::new();
+        let schemas = parser.parse_from_directory(&repo_path, &[])?;
+        inspection.crds_found = Some(schemas.len());
+        for filter in &mut inspection.filters {
+            filter.matched = Some(
+                schemas
+                    .iter()
+                    .filter(|schema| parser.matches_filter(schema, &filter.filter))
+                    .count(),
+            );
+        }
+
+        // Same choice as generation: transforms need the built-in parser,
+        // otherwise the bound plugin or the best one handling the repository
+        let plugin = match &crd_source.plugin {
+            _ if !crd_source.transforms.is_empty() => None,
+            plugin::PluginBinding::Plugin(plugin_id) => Some(plugin_id.clone()),
+            plugin::PluginBinding::Auto => self
+                .plugin_manager
+                .best_plugin(&repo_path)
+                .await?
+                .map(|(plugin_id, _)| plugin_id),
+        };
+
+        let mut layout = BTreeSet::new();
+        for schema in &schemas {
+            let selected = crd_source.filters.is_empty()
+                || crd_source
+                    .filters
+                    .iter()
+                    .any(|filter| parser.matches_filter(schema, filter));
+            if !selected {
+                inspection.excluded.push(format!(
+                    "{} {} ({})",
+                    schema.api_version,
+                    schema.kind,
+                    schema
+                        .source_path
+                        .strip_prefix(&repo_path)
+                        .unwrap_or(&schema.source_path)
+                        .display()
+                ));
+            } else if plugin.is_some() {
+                layout.insert(schema_file_name(&to_extracted_schema(schema)));
+            } else {
+                layout.insert(self.generator.library_path(&convert_crd_schema(schema)));
+            }
+        }
+        inspection.plugin = Some(plugin.unwrap_or_else(|| "built-in parser".to_string()));
+        inspection.layout = layout.into_iter().collect();
+        Ok(inspection)
+    }
+
+    /// Perform a dry run of generation to show what would be generated
+    pub async fn dry_run(&self) -> Result<DryRunResult> {
+        info!("Starting dry run generation");
+
+        let start_time = Instant::now();
+        self.check_output_collisions()?;
+        self.plan_quarantine()?;
+        self.check_resource_plan(&self.config.sources)?;
+        self.git_fetcher.forget_fetched();
+        self.shared_state.clear();
+        if self.locked {
+            self.pin_locked_sources()?;
+        }
+        let mut total_errors = 0;
+        let mut total_warnings = 0;
+        let mut results = Vec::new();
+
+        // Check if incremental generation is possible
+        let current_sources = self.get_current_source_commits().await?;
+        let incremental_plan = self
+            .lockfile_manager
+            .get_incremental_plan(&current_sources.keys().cloned().collect::<Vec<_>>())?;
+
+        let sources_to_process =
+            if incremental_plan.can_incremental && !incremental_plan.changed_sources.is_empty() {
+                info!(
+                    "Dry run: Would use incremental generation for {} changed sources",
+                    incremental_plan.changed_sources.len()
+                );
+                // Get changed sources
+                let mut sources = Vec::new();
+                for source_id in &incremental_plan.changed_sources {
+                    if let Some(source) = self.find_runnable_source(source_id) {
+                        sources.push(source);
+                    }
+                }
+                // Get dependent sources
+                for source_id in &incremental_plan.dependent_sources {
+                    if let Some(source) = self.find_runnable_source(source_id) {
+                        sources.push(source);
+                    }
+                }
+                sources
+            } else {
+                info!(
+                    "Dry run: Would perform full generation for {} sources",
+                    self.config.sources.len()
+                );
+                self.config
+                    .sources
+                    .iter()
+                    .filter(|source| !self.is_skipped(source.name()))
+                    .collect::<Vec<_>>()
+            };
+
+        // Process each source in dry run mode
+        for source in &sources_to_process {
+            match self.process_source_dry_run(source).await {
+                Ok(result) => {
+                    info!("Dry run: Successfully processed source: {}", source.name());
+                    results.push(result);
+                }
+                Err(e) => {
+                    error!("Dry run: Failed to process source {}: {}", source.name(), e);
+                    if self.config.generation.fail_fast {
+                        return Err(e);
+                    }
+                    // Add error result
+                    results.push(DryRunSourceResult {
+                        source_name: source.name().to_string(),
+                        source_type: source.source_type().to_string(),
+                        files_would_generate: 0,
+                        errors: vec![e.to_string()],
+                        warnings: Vec::new(),
+                        output_path: source.output_path().to_path_buf(),
+                    });
+                }
+            }
+        }
+        for source_result in self.unreachable_results().await? {
+            results.push(DryRunSourceResult {
+                source_name: source_result.source_name,
+                source_type: source_result.source_type,
+                files_would_generate: 0,
+                errors: source_result.errors,
+                warnings: Vec::new(),
+                output_path: source_result.output_path,
+            });
+        }
+
+        // Calculate statistics
+        for result in &results {
+            total_errors += result.errors.len();
+            total_warnings += result.warnings.len();
+        }
+
+        let generation_time = start_time.elapsed();
+        info!("Dry run completed in {:?}", generation_time);
+
+        let result = DryRunResult {
+            sources_processed: results.len(),
+            total_sources: self.config.sources.len(),
+            results: results.clone(),
+            statistics: DryRunStatistics {
+                total_processing_time_ms: generation_time.as_millis() as u64,
+                sources_processed: results.len(),
+                files_would_generate: results.iter().map(|r| r.files_would_generate).sum(),
+                error_count: total_errors,
+                warning_count: total_warnings,
+                cache_hit_rate: self.calculate_cache_hit_rate(&incremental_plan),
+                incremental_mode: incremental_plan.can_incremental
+                    && !incremental_plan.changed_sources.is_empty(),
+                changed_sources_count: incremental_plan.changed_sources.len(),
+                dependent_sources_count: incremental_plan.dependent_sources.len(),
+            },
+        };
+
+        Ok(result)
+    }
+
+    /// Process a single source in dry run mode
+    async fn process_source_dry_run(&self, source: &Source) -> Result<DryRunSourceResult> {
+        let start_time = Instant::now();
+        let source_name = source.name();
+
+        info!("Dry run: Processing source: {}", source_name);
+
+        // Simulate the processing without actually writing files
+        let mut files_would_generate = 0;
+        let mut errors = Vec::new();
+        let warnings = Vec::new();
+
+        match source {
+            Source::Crd(crd_source) => {
+                // Simulate CRD processing
+                match self.git_fetcher.ensure_repository(&crd_source.git).await {
+                    Ok(repo_path) => {
+                        // Parse CRDs from the repository
+                        match self
+                            .crd_parser_for(crd_source.versions)
+                            .parse_from_directory(&repo_path, &crd_source.filters)
+                        {
+                            Ok(schemas) => {
+                                // Calculate how many files would be generated
+                                let grouped_schemas = self.group_schemas_by_version(&schemas);
+                                files_would_generate = grouped_schemas.len() + 3; // +3 for index, metadata, and validation files
+
+                                info!(
+                                    "Dry run: Would generate {} files for CRD source {}",
+                                    files_would_generate, source_name
+                                );
+                            }
+                            Err(e) => {
+                                errors.push(format!("Failed to parse CRDs: {e}"));
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        errors.push(format!("Failed to clone repository: {e}"));
+                    }
+                }
+            }
+            Source::GoAst(go_ast_source) => {
+                // Simulate Go AST processing
+                match self.git_fetcher.ensure_repository(&go_ast_source.git).await {
+                    Ok(_) => {
+                        // Estimate files based on Go files found
+                        files_would_generate = 2; // At least lib.jsonnet and metadata
+                        info!(
+                            "Dry run: Would generate {} files for Go AST source {}",
+                            files_would_generate, source_name
+                        );
+                    }
+                    Err(e) => {
+                        errors.push(format!("Failed to clone repository: {e}"));
+                    }
+                }
+            }
+            Source::OpenApi(openapi_source) => {
+                // Simulate OpenAPI processing
+                match self
+                    .git_fetcher
+                    .ensure_repository(&openapi_source.git)
+                    .await
+                {
+                    Ok(_) => {
+                        // Estimate files based on OpenAPI specs found
+                        files_would_generate = 2; // At least lib.jsonnet and metadata
+                        info!(
+                            "Dry run: Would generate {} files for OpenAPI source {}",
+                            files_would_generate, source_name
+                        );
+                    }
+                    Err(e) => {
+                        errors.push(format!("Failed to clone repository: {e}"));
+                    }
+                }
+            }
+            Source::Catalog(catalog_source) => {
+                match self
+                    .git_fetcher
+                    .ensure_repository(&catalog_source.git)
+                    .await
+                {
+                    Ok(repo_path) => {
+                        match catalog::discover(&repo_path, &catalog_source.discovery) {
+                            Ok(entries) => {
+                                for entry in &entries {
+                                    match self
+                                        .crd_parser_for(catalog_source.versions)
+                                        .parse_from_directory(
+                                            &repo_path.join(&entry.path),
+                                            &catalog_source.filters,
+                                        ) {
+                                        Ok(schemas) => {
+                                            let grouped_schemas =
+                                                self.group_schemas_by_version(&schemas);
+                                            files_would_generate += grouped_schemas.len() + 3;
+                                        }
+                                        Err(e) => {
+                                            errors.push(format!(
+                                                "Failed to parse CRDs for {}: {e}",
+                                                entry.source_id(source_name)
+                                            ));
+                                        }
+                                    }
+                                }
+                                info!(
+                                    "Dry run: Would generate {} files for {} sources in catalog {}",
+                                    files_would_generate,
+                                    entries.len(),
+                                    source_name
+                                );
+                            }
+                            Err(e) => {
+                                errors.push(format!("Failed to discover catalog sources: {e}"));
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        errors.push(format!("Failed to clone repository: {e}"));
+                    }
+                }
+            }
+            Source::Url(url_source) => match self.fetch_url_source(url_source).await {
+                Ok(artifact) => {
+                    match self
+                        .crd_parser_for(url_source.versions)
+                        .parse_from_directory(&artifact.dir, &url_source.filters)
+                    {
+                        Ok(schemas) => {
+                            let grouped_schemas = self.group_schemas_by_version(&schemas);
+                            files_would_generate = grouped_schemas.len() + 3;
+                            info!(
+                                "Dry run: Would generate {} files for URL source {}",
+                                files_would_generate, source_name
+                            );
+                        }
+                        Err(e) => {
+                            errors.push(format!("Failed to parse CRDs: {e}"));
+                        }
+                    }
+                }
+                Err(e) => {
+                    errors.push(format!("Failed to fetch artifact: {e}"));
+                }
+            },
+            Source::Release(release_source) => {
+                match self.fetch_release_source(release_source).await {
+                    Ok((asset, artifact)) => match release_source.format {
+                        crate::config::ReleaseFormat::Crd => match self
+                            .crd_parser_for(release_source.versions)
+                            .parse_from_directory(&artifact.dir, &release_source.filters)
+                        {
+                            Ok(schemas) => {
+                                let grouped_schemas = self.group_schemas_by_version(&schemas);
+                                files_would_generate = grouped_schemas.len() + 3;
+                                info!(
+                                    "Dry run: Would generate {} files for {} of release {}",
+                                    files_would_generate, asset.name, asset.tag
+                                );
+                            }
+                            Err(e) => {
+                                errors.push(format!("Failed to parse CRDs: {e}"));
+                            }
+                        },
+                        crate::config::ReleaseFormat::OpenApi => {
+                            files_would_generate = 2; // At least lib.jsonnet and metadata
+                            info!(
+                                "Dry run: Would generate {} files for {} of release {}",
+                                files_would_generate, asset.name, asset.tag
+                            );
+                        }
+                    },
+                    Err(e) => {
+                        errors.push(format!("Failed to fetch release asset: {e}"));
+                    }
+                }
+            }
+            Source::Fixture(fixture_source) => match fixture_source.fixture {
+                crate::config::FixtureSet::Crds => {
+                    match fixture::cache_root()
+                        .and_then(|root| fixture::materialize(fixture_source.fixture, &root))
+                        .and_then(|fixture| {
+                            self.crd_parser_for(fixture_source.versions)
+                                .parse_from_directory(&fixture.dir, &fixture_source.filters)
+                        }) {
+                        Ok(schemas) => {
+                            let grouped_schemas = self.group_schemas_by_version(&schemas);
+                            files_would_generate = grouped_schemas.len() + 3;
+                            info!(
+                                "Dry run: Would generate {} files for fixture source {}",
+                                files_would_generate, source_name
+                            );
+                        }
+                        Err(e) => {
+                            errors.push(format!("Failed to parse CRDs: {e}"));
+                        }
+                    }
+                }
+                crate::config::FixtureSet::OpenApi => {
+                    files_would_generate = 2; // At least lib.jsonnet and metadata
+                    info!(
+                        "Dry run: Would generate {} files for fixture source {}",
+                        files_would_generate, source_name
+                    );
+                }
+            },
+            Source::Jsonnet(jsonnet_source) => match library::Library::open(jsonnet_source) {
+                Ok(library) => {
+                    files_would_generate = library.files.len() + 2; // Plus index and shape
+                    info!(
+                        "Dry run: Would re-export {} files for jsonnet source {}",
+                        files_would_generate, source_name
+                    );
+                }
+                Err(e) => {
+                    errors.push(format!("Failed to read library: {e}"));
+                }
+            },
+            Source::Kustomize(kustomize_source) => {
+                match self
+                    .git_fetcher
+                    .ensure_repository(&kustomize_source.git)
+                    .await
+                {
+                    Ok(repo_path) => {
+                        let schemas = match self.kustomize_build(kustomize_source, &repo_path).await
+                        {
+                            Ok(build_dir) => self
+                                .crd_parser_for(kustomize_source.versions)
+                                .parse_from_directory(build_dir.path(), &kustomize_source.filters),
+                            Err(e) => Err(e),
+                        };
+                        match schemas {
+                            Ok(schemas) => {
+                                let grouped_schemas = self.group_schemas_by_version(&schemas);
+                                files_would_generate = grouped_schemas.len() + 3;
+                                info!(
+                                    "Dry run: Would generate {} files for kustomize source {}",
+                                    files_would_generate, source_name
+                                );
+                            }
+                            Err(e) => {
+                                errors.push(format!("Failed to build kustomization: {e}"));
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        errors.push(format!("Failed to clone repository: {e}"));
+                    }
+                }
+            }
+            Source::Auto(auto_source) => match self.detect_auto_source(auto_source).await {
+                Ok(sources) => {
+                    for source in &sources {
+                        let result = Box::pin(self.process_source_dry_run(source)).await?;
+                        files_would_generate += result.files_would_generate;
+                        errors.extend(result.errors);
+                    }
+                    info!(
+                        "Dry run: Would generate {} files for {} sources detected in {}",
+                        files_would_generate,
+                        sources.len(),
+                        source_name
+                    );
+                }
+                Err(e) => {
+                    errors.push(format!("Failed to detect source contents: {e}"));
+                }
+            },
+        }
+
+        let processing_time = start_time.elapsed();
+        info!(
+            "Dry run: Processed source {} in {:?}",
+            source_name, processing_time
+        );
+
+        Ok(DryRunSourceResult {
+            source_name: source_name.to_string(),
+            source_type: source.source_type().to_string(),
+            files_would_generate,
+            errors,
+            warnings,
+            output_path: source.output_path().to_path_buf(),
+        })
+    }
+
+    /// Group schemas by API version (helper method for dry run)
+    fn group_schemas_by_version<'a>(
+        &self,
+        schemas: &'a [jsonnet_crd::CrdSchema],
+    ) -> std::collections::HashMap<String, Vec<&'a jsonnet_crd::CrdSchema>> {
+        let mut grouped = std::collections::HashMap::new();
+
+        for schema in schemas {
+            let api_version = schema.api_version.clone();
+            grouped
+                .entry(api_version)
+                .or_insert_with(Vec::new)
+                .push(schema);
+        }
+
+        grouped
+    }
+
+    /// Get plugin information
+    pub async fn get_plugin_info(&self) -> Result<Vec<plugin::PluginMetadata>> {
+        // Return the metadata of the built-in plugins compiled into this build
+        let plugins = vec![
+            plugin::PluginMetadata {
+                id: "go-ast:builtin".to_string(),
+                name: "Go AST Plugin".to_string(),
+                version: "1.0.0".to_string(),
+                description: "Plugin for processing Go source code and extracting type information"
+                    .to_string(),
+                supported_types: vec!["go".to_string(), "golang".to_string()],
+                capabilities: vec![
+                    plugin::PluginCapability::Parse,
+                    plugin::PluginCapability::SchemaExtraction,
+                    plugin::PluginCapability::AstProcessing,
+                ],
+                commands: Vec::new(),
+            },
+            plugin::PluginMetadata {
+                id: "crd:builtin".to_string(),
+                name: "CRD Plugin".to_string(),
+                version: "1.0.0".to_string(),
+                description: "Plugin for processing Kubernetes CustomResourceDefinitions"
+                    .to_string(),
+                supported_types: vec!["crd".to_string(), "yaml".to_string(), "yml".to_string()],
+                capabilities: vec![
+                    plugin::PluginCapability::Parse,
+                    plugin::PluginCapability::SchemaExtraction,
+                    plugin::PluginCapability::Validation,
+                ],
+                commands: Vec::new(),
+            },
+            plugin::PluginMetadata {
+                id: "openapi:builtin".to_string(),
+                name: "OpenAPI Plugin".to_string(),
+                version: "1.0.0".to_string(),
+                description: "Plugin for processing OpenAPI/Swagger specifications and extracting type information"
+                    .to_string(),
+                supported_types: vec!["openapi".to_string(), "swagger".to_string(), "yaml".to_string(), "json".to_string()],
+                capabilities: vec![
+                    plugin::PluginCapability::Parse,
+                    plugin::PluginCapability::SchemaExtraction,
+                    plugin::PluginCapability::Validation,
+                ],
+                commands: Vec::new(),
+            },
+            plugin::PluginMetadata {
+                id: "cloudformation:builtin".to_string(),
+                name: "CloudFormation Plugin".to_string(),
+                version: "1.0.0".to_string(),
+                description: "Plugin for generating builders from CloudFormation resource specifications"
+                    .to_string(),
+                supported_types: vec!["cloudformation".to_string(), "json".to_string()],
+                capabilities: vec![
+                    plugin::PluginCapability::Parse,
+                    plugin::PluginCapability::SchemaExtraction,
+                ],
+                commands: Vec::new(),
+            },
+        ];
+
+        // Subcommands are reported by the loaded plugins
+        let mut commands = self.plugin_manager.cli_commands().await;
+        Ok(plugins
+            .into_iter()
+            .filter(|metadata| plugin::builtin_plugin_available(&metadata.id))
+            .map(|mut metadata| {
+                if let Some(commands) = commands.remove(&metadata.id) {
+                    metadata.capabilities.push(plugin::PluginCapability::Cli);
+                    metadata.commands = commands;
+                }
+                metadata
+            })
+            .collect())
+    }
+
+    /// CLI subcommands contributed by plugins, by plugin id
+    pub async fn plugin_commands(&self) -> BTreeMap<plugin::PluginId, Vec<plugin::CliCommandSpec>> {
+        self.plugin_manager.cli_commands().await
+    }
+
+    /// Run a CLI subcommand contributed by a plugin
+    ///
+    /// Relative paths in arguments are resolved against the current directory.
+    pub async fn run_plugin_command(
+        &self,
+        plugin_id: &str,
+        command: &str,
+        args: BTreeMap<String, String>,
+    ) -> Result<plugin::CommandOutput> {
+        let context = PluginContext::new(
+            std::env::current_dir()?,
+            self.config.output.base_path.clone(),
+            PluginConfig {
+                plugin_id: plugin_id.to_string(),
+                config: serde_yaml::Value::Null,
+                enabled_capabilities: vec![plugin::PluginCapability::Cli],
+            },
+        );
+        let context = match self.plugin_state_dir() {
+            Some(state_dir) => context.with_state_dir(state_dir),
+            None => context,
+        };
+
+        self.plugin_manager
+            .run_command(plugin_id, command, args, &context)
+            .await
+    }
+
+    /// Clean up the plugins initialized during this run
+    pub async fn shutdown_plugins(&self) -> Result<()> {
+        let context = PluginContext::new(
+            std::env::current_dir()?,
+            self.config.output.base_path.clone(),
+            PluginConfig {
+                plugin_id: String::new(),
+                config: serde_yaml::Value::Null,
+                enabled_capabilities: Vec::new(),
+            },
+        );
+        let context = match self.plugin_state_dir() {
+            Some(state_dir) => context.with_state_dir(state_dir),
+            None => context,
+        };
+
+        self.plugin_manager.cleanup(&context).await
+    }
+
+    /// Enable a plugin
+    pub async fn enable_plugin(&self, plugin_id: &str) -> Result<()> {
+        info!("Enabling plugin: {}", plugin_id);
+
+        // For now, we only support built-in plugins
+        // In the future, this would interact with a plugin registry
+        match plugin_id {
+            "go-ast:builtin" | "openapi:builtin" | "crd:builtin"
+                if plugin::builtin_plugin_available(plugin_id) =>
+            {
+                info!("Plugin {} is already enabled (built-in)", plugin_id);
+                Ok(())
+            }
+            _ => {
+                warn!("Plugin {} not found or not supported", plugin_id);
+                Err(anyhow::anyhow!("Plugin {} not found", plugin_id))
+            }
+        }
+    }
+
+    /// Disable a plugin
+    pub async fn disable_plugin(&self, plugin_id: &str) -> Result<()> {
+        info!("Disabling plugin: {}", plugin_id);
+
+        // For now, we only support built-in plugins which cannot be disabled
+        // In the future, this would interact with a plugin registry
+        match plugin_id {
+            "go-ast:builtin" | "openapi:builtin" | "crd:builtin"
+                if plugin::builtin_plugin_available(plugin_id) =>
+            {
+                warn!("Cannot disable built-in plugin: {}", plugin_id);
+                Err(anyhow::anyhow!(
+                    "Cannot disable built-in plugin: {}",
+                    plugin_id
+                ))
+            }
+            _ => {
+                warn!("Plugin {} not found", plugin_id);
+                Err(anyhow::anyhow!("Plugin {} not found", plugin_id))
+            }
+        }
+    }
+
+    /// Install a plugin
+    pub async fn install_plugin(
+        &self,
+        source: &str,
+        _version: Option<&str>,
+        _target_dir: Option<&Path>,
+    ) -> Result<()> {
+        info!("Installing plugin from: {}", source);
+
+        // For now, we only support built-in plugins
+        // In the future, this would:
+        // 1. Parse the source (file path, URL, or registry name)
+        // 2. Download/validate the plugin
+        // 3. Install it to the target directory
+        // 4. Register it with the plugin manager
+
+        if source.starts_with("http") || source.starts_with("https") {
+            return Err(anyhow::anyhow!(
+                "Plugin installation from URLs not yet implemented"
+            ));
+        }
+
+        if source.contains("://") {
+            return Err(anyhow::anyhow!(
+                "Plugin installation from registry not yet implemented"
+            ));
+        }
+
+        // Check if it's a local file
+        let source_path = Path::new(source);
+        if source_path.exists() && source_path.is_file() {
+            return Err(anyhow::anyhow!(
+                "Plugin installation from local files not yet implemented"
+            ));
+        }
+
+        // Check if it's a built-in plugin name
+        match source {
+            "go-ast" | "openapi" | "crd" => {
+                info!(
+                    "Plugin {} is already available as a built-in plugin",
+                    source
+                );
+                Ok(())
+            }
+            _ => Err(anyhow::anyhow!(
+                "Plugin installation not yet implemented for: {}",
+                source
+            )),
+        }
+    }
+
+    /// Uninstall a plugin
+    pub async fn uninstall_plugin(&self, plugin_id: &str, _remove_files: bool) -> Result<()> {
+        info!("Uninstalling plugin: {}", plugin_id);
+
+        // For now, we only support built-in plugins which cannot be uninstalled
+        // In the future, this would:
+        // 1. Remove the plugin from the plugin manager
+        // 2. Optionally remove plugin files
+        // 3. Update the plugin registry
+
+        match plugin_id {
+            "go-ast:builtin" | "openapi:builtin" | "crd:builtin"
+                if plugin::builtin_plugin_available(plugin_id) =>
+            {
+                warn!("Cannot uninstall built-in plugin: {}", plugin_id);
+                Err(anyhow::anyhow!(
+                    "Cannot uninstall built-in plugin: {}",
+                    plugin_id
+                ))
+            }
+            _ => {
+                warn!("Plugin {} not found", plugin_id);
+                Err(anyhow::anyhow!("Plugin {} not found", plugin_id))
+            }
+        }
+    }
+
+    /// Find OpenAPI specification files matching the patterns
+    async fn find_openapi_files(
+        &self,
+        repo_path: &Path,
+        include_patterns: &[String],
+        exclude_patterns: &[String],
+    ) -> Result<Vec<PathBuf>> {
+        let mut openapi_files = Vec::new();
+
+        for pattern in include_patterns {
+            let glob_pattern = repo_path.join(pattern);
+            let entries = glob::glob(&glob_pattern.to_string_lossy())?;
+
+            for entry in entries {
+                match entry {
+                    Ok(path) => {
+                        // Check if file should be excluded
+                        let should_exclude = exclude_patterns.iter().any(|exclude_pattern| {
+                            let exclude_glob = repo_path.join(exclude_pattern);
+                            if let Ok(mut exclude_entries) =
+                                glob::glob(&exclude_glob.to_string_lossy())
+                            {
+                                exclude_entries.any(|exclude_entry| {
+                                    exclude_entry.is_ok_and(|exclude_path| exclude_path == path)
+                                })
+                            } else {
+                                false
+                            }
+                        });
+
+                        if !should_exclude && path.is_file() {
+                            openapi_files.push(path);
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to match pattern {}: {}", pattern, e);
+                    }
+                }
+            }
+        }
+
+        Ok(openapi_files)
+    }
+
+    /// Start streaming the schemas of a single OpenAPI file from the plugin
+    async fn stream_openapi_file_with_plugin(
+        &self,
+        openapi_file: &Path,
+        workspace: &Workspace,
+        metadata: &plugin::SourceMetadata,
+        binding: &plugin::PluginBinding,
+        options: &config::OpenApiOptions,
+    ) -> Result<(plugin::PluginId, plugin::SchemaStream)> {
+        self.inject_fault(
+            chaos::FaultPoint::Parse,
+            &format!("{} ({})", metadata.name, openapi_file.display()),
+        )?;
+
+        // Create plugin context
+        let plugin_config = crate::plugin::PluginConfig {
+            plugin_id: "openapi:builtin".to_string(),
+            config: serde_yaml::to_value(options)?,
+            enabled_capabilities: vec![
+                crate::plugin::PluginCapability::Parse,
+                crate::plugin::PluginCapability::SchemaExtraction,
+                crate::plugin::PluginCapability::Validation,
+            ],
+        };
+
+        let context = self.plugin_context(workspace, plugin_config, metadata);
+
+        // Stream the schemas from the plugin manager
+        self.plugin_manager
+            .process_source_stream_with(binding, openapi_file, &context)
+            .await
+    }
+}
+
+/// Application error types
+#[derive(thiserror::Error, Debug)]
+pub enum JsonnetGenError {
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    #[error("Git operation failed: {0}")]
+    Git(#[from] git2::Error),
+
+    #[error("CRD parsing failed: {0}")]
+    CrdParsing(String),
+
+    #[error("Generation failed: {0}")]
+    Generation(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_yaml::Error),
+
+    #[error("Lockfile error: {0}")]
+    Lockfile(String),
+
+    #[error("Plugin error: {0}")]
+    Plugin(String),
+
+    #[error("Parser setup failed: {0}")]
+    ParserSetup(String),
+
+    #[error("Unknown source: {0}")]
+    UnknownSource(String),
+
+    #[error("Source {0} is not in the lockfile; generate it once without --locked")]
+    NotLocked(String),
+
+    #[error(
+        "Locked commit {commit} of {url} is unreachable; it may have been force-pushed away or deleted upstream"
+    )]
+    UnreachableCommit { url: String, commit: String },
+
+    #[error("Source {source_name} writes to {path:?}, outside output.base_path {base:?}")]
+    OutputOutsideBase {
+        source_name: String,
+        path: PathBuf,
+        base: PathBuf,
+    },
+
+    #[error(
+        "Plugin {plugin_id} of source {source_name} returned {path:?}, outside output.base_path {base:?}"
+    )]
+    PluginPathOutsideBase {
+        plugin_id: String,
+        source_name: String,
+        path: PathBuf,
+        base: PathBuf,
+    },
+
+    #[error(
+        "Sources {} all write to {path:?}; give them distinct output paths or set generation.on_output_collision: namespace",
+        .sources.join(", ")
+    )]
+    OutputCollision { path: PathBuf, sources: Vec<String> },
+
+    #[error(
+        "Generated API breaks the frozen API:\n  {}\nPass --allow-breaking to generate anyway, then run `gensonnet freeze` to accept the new API",
+        .0.join("\n  ")
+    )]
+    BreakingChanges(Vec<String>),
+
+    #[error("Resource limit exceeded: {0}")]
+    ResourceLimit(String),
+
+    #[error(
+        "Run raised warnings in denied categories:\n  {}",
+        .0.join("\n  ")
+    )]
+    DeniedWarnings(Vec<String>),
+}
+
+/// Result type for the main application
+pub type JsonnetGenResult<T> = Result<T, JsonnetGenError>;
+
+/// Generation status information
+#[derive(Debug, Clone)]
+pub struct GenerationStatus {
+    pub last_generation: chrono::DateTime<chrono::Utc>,
+    pub tool_version: String,
+    pub sources_count: usize,
+    pub changed_sources: Vec<String>,
+    pub dependent_sources: Vec<String>,
+    pub can_incremental: bool,
+    pub estimated_time_ms: u64,
+
+    /// Estimated time of each source with recorded timings, from past runs
+    pub source_estimates_ms: BTreeMap<String, u64>,
+    pub statistics: GenerationStatistics,
+}
+
+/// A source whose lockfile pin is behind its upstream
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutdatedSource {
+    pub name: String,
+
+    /// Commit pinned in the lockfile, if the source was generated
+    pub pinned: Option<String>,
+
+    /// Current commit SHA (or artifact digest) of the configured ref
+    pub upstream: String,
+}
+
+/// Detailed view of one source, from [`JsonnetGen::inspect_source`]
+#[derive(Debug, Clone)]
+pub struct SourceInspection {
+    pub name: String,
+    pub source_type: String,
+
+    /// Repository or artifact URL, with credentials redacted
+    pub url: String,
+
+    /// Configured ref, branch or release tag
+    pub git_ref: Option<String>,
+
+    /// Commit pinned in the lockfile, if the source was generated
+    pub pinned: Option<String>,
+
+    /// Commit the configured ref resolves to, or why it could not be resolved
+    pub upstream: std::result::Result<String, String>,
+
+    /// Configured plugin binding, for sources processed by plugins
+    pub plugin_binding: Option<String>,
+
+    /// Plugin that would process the source (CRD sources only)
+    pub plugin: Option<String>,
+
+    /// CRDs found upstream before filtering (CRD sources only)
+    pub crds_found: Option<usize>,
+
+    /// Configured filters and how many CRDs each matches
+    pub filters: Vec<FilterMatches>,
+
+    /// CRDs no filter matches, as `apiVersion kind (file)`
+    pub excluded: Vec<String>,
+
+    pub output_path: PathBuf,
+
+    /// Libraries generation would write, relative to the output path
+    pub layout: Vec<String>,
+}
+
+/// A source filter and the number of CRDs it matches, where counted
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterMatches {
+    pub filter: String,
+    pub matched: Option<usize>,
+}
+
+/// Dry run result for a single source
+#[derive(Debug, Clone)]
+pub struct DryRunSourceResult {
+    pub source_name: String,
+    pub source_type: String,
+    pub files_would_generate: usize,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+    pub output_path: PathBuf,
+}
+
+/// Dry run statistics
+#[derive(Debug, Clone)]
+pub struct DryRunStatistics {
+    pub total_processing_time_ms: u64,
+    pub sources_processed: usize,
+    pub files_would_generate: usize,
+    pub error_count: usize,
+    pub warning_count: usize,
+    pub cache_hit_rate: f64,
+    pub incremental_mode: bool,
+    pub changed_sources_count: usize,
+    pub dependent_sources_count: usize,
+}
+
+/// Dry run result
+#[derive(Debug, Clone)]
+pub struct DryRunResult {
+    pub sources_processed: usize,
+    pub total_sources: usize,
+    pub results: Vec<DryRunSourceResult>,
+    pub statistics: DryRunStatistics,
+}
+
+/// Cleanup dry run result for a single source entry
+#[derive(Debug, Clone)]
+pub struct CleanupSourceEntry {
+    pub source_id: String,
+    pub git_url: String,
+    pub git_ref: String,
+    pub fetched_at: chrono::DateTime<chrono::Utc>,
+    pub age_hours: u64,
+}
+
+/// Cleanup dry run result for a single file entry
+#[derive(Debug, Clone)]
+pub struct CleanupFileEntry {
+    pub file_path: PathBuf,
+    pub size: u64,
+    pub modified_at: chrono::DateTime<chrono::Utc>,
+    pub age_hours: u64,
+}
+
+/// Cleanup dry run result
+#[derive(Debug, Clone)]
+pub struct CleanupDryRunResult {
+    pub max_age_hours: u64,
+    pub stale_sources: Vec<CleanupSourceEntry>,
+    pub stale_files: Vec<CleanupFileEntry>,
+    pub total_sources_removed: usize,
+    pub total_files_removed: usize,
+    pub total_size_freed: u64,
+    pub lockfile_path: PathBuf,
+}
+
+// Add missing methods to Source trait
+impl Source {
+    pub fn source_type(&self) -> &str {
+        match self {
+            Source::Crd(_) => "crd",
+            Source::GoAst(_) => "go_ast",
+            Source::OpenApi(_) => "openapi",
+            Source::Url(_) => "url",
+            Source::Release(_) => "release",
+            Source::Fixture(_) => "fixture",
+            Source::Jsonnet(_) => "jsonnet",
+            Source::Kustomize(_) => "kustomize",
+            Source::Catalog(_) => "catalog",
+            Source::Auto(_) => "auto",
+        }
+    }
+
+    pub fn git_url(&self) -> &str {
+        match self {
+            Source::Crd(crd) => &crd.git.url,
+            Source::GoAst(go_ast) => &go_ast.git.url,
+            Source::OpenApi(openapi) => &openapi.git.url,
+            Source::Url(url) => &url.url,
+            Source::Release(release) => release.repository(),
+            Source::Fixture(fixture) => fixture.fixture.url(),
+            Source::Jsonnet(jsonnet) => jsonnet.url(),
+            Source::Kustomize(kustomize) => &kustomize.git.url,
+            Source::Catalog(catalog) => &catalog.git.url,
+            Source::Auto(auto) => &auto.git.url,
+        }
+    }
+
+    /// Git repository of a source, if it is fetched from one
+    pub fn git_source(&self) -> Option<&config::GitSource> {
+        match self {
+            Source::Crd(crd) => Some(&crd.git),
+            Source::GoAst(go_ast) => Some(&go_ast.git),
+            Source::OpenApi(openapi) => Some(&openapi.git),
+            Source::Url(_) | Source::Release(_) | Source::Fixture(_) | Source::Jsonnet(_) => None,
+            Source::Kustomize(kustomize) => Some(&kustomize.git),
+            Source::Catalog(catalog) => Some(&catalog.git),
+            Source::Auto(auto) => Some(&auto.git),
+        }
+    }
+
+    pub fn git_ref(&self) -> Option<&str> {
+        match self {
+            Source::Crd(crd) => crd.git.ref_name.as_deref(),
+            Source::GoAst(go_ast) => go_ast.git.ref_name.as_deref(),
+            Source::OpenApi(openapi) => openapi.git.ref_name.as_deref(),
+            Source::Url(_) | Source::Fixture(_) | Source::Jsonnet(_) => None,
+            Source::Release(release) => release.tag.as_deref(),
+            Source::Kustomize(kustomize) => kustomize.git.ref_name.as_deref(),
+            Source::Catalog(catalog) => catalog.git.ref_name.as_deref(),
+            Source::Auto(auto) => auto.git.ref_name.as_deref(),
+        }
+    }
+
+    /// Plugin binding of a source processed by plugins
+    pub fn plugin_binding(&self) -> Option<&plugin::PluginBinding> {
+        match self {
+            Source::Crd(crd) => Some(&crd.plugin),
+            Source::GoAst(go_ast) => Some(&go_ast.plugin),
+            Source::OpenApi(openapi) => Some(&openapi.plugin),
+            Source::Release(release) => Some(&release.plugin),
+            Source::Fixture(fixture) => Some(&fixture.plugin),
+            Source::Url(_)
+            | Source::Jsonnet(_)
+            | Source::Kustomize(_)
+            | Source::Catalog(_)
+            | Source::Auto(_) => None,
+        }
+    }
+
+    pub fn filters(&self) -> &[String] {
+        match self {
+            Source::Crd(crd) => &crd.filters,
+            Source::GoAst(go_ast) => &go_ast.include_patterns,
+            Source::OpenApi(openapi) => &openapi.include_patterns,
+            Source::Url(url) => &url.filters,
+            Source::Release(release) => &release.filters,
+            Source::Fixture(fixture) => &fixture.filters,
+            Source::Jsonnet(_) => &[],
+            Source::Kustomize(kustomize) => &kustomize.filters,
+            Source::Catalog(catalog) => &catalog.filters,
+            Source::Auto(auto) => &auto.filters,
+        }
+    }
+
+    pub fn output_path(&self) -> &std::path::Path {
+        match self {
+            Source::Crd(crd) => &crd.output_path,
+            Source::GoAst(go_ast) => &go_ast.output_path,
+            Source::OpenApi(openapi) => &openapi.output_path,
+            Source::Url(url) => &url.output_path,
+            Source::Release(release) => &release.output_path,
+            Source::Fixture(fixture) => &fixture.output_path,
+            Source::Jsonnet(jsonnet) => &jsonnet.output_path,
+            Source::Kustomize(kustomize) => &kustomize.output_path,
+            Source::Catalog(catalog) => &catalog.output_path,
+            Source::Auto(auto) => &auto.output_path,
+        }
+    }
+
+    pub fn output_path_mut(&mut self) -> &mut PathBuf {
+        match self {
+            Source::Crd(crd) => &mut crd.output_path,
+            Source::GoAst(go_ast) => &mut go_ast.output_path,
+            Source::OpenApi(openapi) => &mut openapi.output_path,
+            Source::Url(url) => &mut url.output_path,
+            Source::Release(release) => &mut release.output_path,
+            Source::Fixture(fixture) => &mut fixture.output_path,
+            Source::Jsonnet(jsonnet) => &mut jsonnet.output_path,
+            Source::Kustomize(kustomize) => &mut kustomize.output_path,
+            Source::Catalog(catalog) => &mut catalog.output_path,
+            Source::Auto(auto) => &mut auto.output_path,
+        }
+    }
+}
+
+/// Commit, or content digest, pinned in the lockfile for a source
+///
+/// Catalogs and auto sources are pinned per logical source, all at the same commit.
+fn pinned_commit(lockfile: &jsonnet_lockfile::Lockfile, source: &Source) -> Option<String> {
+    lockfile
+        .sources
+        .iter()
+        .find(|(source_id, _)| owns_source_id(source, source_id))
+        .map(|(_, entry)| entry.revision().to_string())
+}
+
+/// Whether a lockfile source id belongs to a configured source
+///
+/// Catalogs and auto sources own the ids of their logical sources, `<name>/...`.
+pub fn owns_source_id(source: &Source, source_id: &str) -> bool {
+    match source {
+        Source::Catalog(_) | Source::Auto(_) => {
+            source_id == source.name()
+                || source_id
+                    .strip_prefix(source.name())
+                    .is_some_and(|rest| rest.starts_with('/'))
+        }
+        _ => source_id == source.name(),
+    }
+}
+
+/// Type the free-form warnings of a plugin
+fn plugin_warnings(warnings: Vec<String>) -> impl Iterator<Item = Warning> {
+    warnings
+        .into_iter()
+        .map(|warning| Warning::new(WarningCategory::Plugin, "plugin-warning", warning))
+}
+
+/// Whether an error is a run going over its resource limits
+fn is_resource_limit(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<JsonnetGenError>(),
+        Some(JsonnetGenError::ResourceLimit(_))
+    )
+}
+
+/// Whether an error fails the run rather than its source: going over the
+/// run's resource limits, or a library breaking the frozen API
+fn is_fatal(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<JsonnetGenError>(),
+        Some(JsonnetGenError::ResourceLimit(_) | JsonnetGenError::BreakingChanges(_))
+    )
+}
+
+/// File name of the library generated for an extracted schema
+fn schema_file_name(schema: &crate::plugin::ExtractedSchema) -> String {
+    format!("{}.libsonnet", schema.name.to_lowercase())
+}
+
+/// Directory of the schemas that are not generated as resources
+fn schema_group(schema: &crate::plugin::ExtractedSchema) -> Option<&'static str> {
+    match schema.schema_type.as_str() {
+        "openapi_callback" => Some("callbacks"),
+        "openapi_webhook" => Some("webhooks"),
+        "openapi_parameter" => Some("parameters"),
+        "openapi_servers" => Some("servers"),
+        _ => None,
+    }
+}
+
+/// Generate the payload builder of a callback or webhook schema
+///
+/// `new` takes a whole payload and each property with a plain name gets a
+/// setter, named by `naming`, so payloads are composed like resources.
+fn generate_event_builder(
+    schema: &crate::plugin::ExtractedSchema,
+    naming: jsonnet_generator::naming::FieldNaming,
+) -> Result<String> {
+    let event = match schema.schema_type.as_str() {
+        "openapi_webhook" => "webhook",
+        _ => "callback",
+    };
+    let mut code = String::new();
+    code.push_str(&format!(
+        "// Generated from OpenAPI {event}: {}\n",
+        schema.name
+    ));
+    code.push_str(&format!("// Source: {}\n", schema.source_file.display()));
+    for (label, key) in [
+        ("Method", "event_method"),
+        ("Expression", "callback_expression"),
+    ] {
+        if let Some(value) = schema.metadata.get(key).and_then(|value| value.as_str()) {
+            code.push_str(&format!("// {label}: {value}\n"));
+        }
+    }
+    code.push('\n');
+
+    let mut properties: Vec<&str> = schema
+        .content
+        .get("properties")
+        .and_then(|properties| properties.as_mapping())
+        .into_iter()
+        .flat_map(|properties| properties.keys().filter_map(|key| key.as_str()))
+        .filter(|name| {
+            name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+        .collect();
+    properties.sort_unstable();
+    let setters = naming.setter_names(properties.iter().copied())?;
+
+    code.push_str(&format!(
+        "// Build the payload of the {} {event}\n",
+        schema.name
+    ));
+    code.push_str("{\n");
+    code.push_str("  new(payload={}): payload,\n");
+    for property in properties {
+        code.push_str(&format!(
+            "  {}(value): {{ {property}: value }},\n",
+            setters[property]
+        ));
+    }
+    code.push_str("}\n");
+    Ok(code)
+}
+
+/// Generate the builder of a reusable OpenAPI parameter
+///
+/// `new` places a value where the parameter goes in a request, so the
+/// parameters of a request are composed with `+`.
+fn generate_parameter_builder(schema: &crate::plugin::ExtractedSchema) -> String {
+    let metadata = |key: &str| schema.metadata.get(key);
+    let name = metadata("parameter_name")
+        .and_then(|name| name.as_str())
+        .unwrap_or(&schema.name);
+    let location = metadata("parameter_in")
+        .and_then(|location| location.as_str())
+        .unwrap_or("query");
+    let required = metadata("parameter_required")
+        .and_then(|required| required.as_bool())
+        .unwrap_or(false);
+
+    let mut code = String::new();
+    code.push_str(&format!(
+        "// Generated from OpenAPI parameter: {}\n",
+        schema.name
+    ));
+    code.push_str(&format!("// Source: {}\n", schema.source_file.display()));
+    code.push_str(&format!("// In: {location}\n"));
+    code.push_str(&format!("// Required: {required}\n"));
+    if let Some(description) = schema.content.get("description").and_then(|d| d.as_str()) {
+        code.push_str(&format!("// {}\n", description.replace('\n', " ")));
+    }
+    code.push('\n');
+
+    code.push_str("{\n");
+    code.push_str(&format!("  name: {},\n", serde_json::Value::from(name)));
+    code.push_str(&format!("  'in': {},\n", serde_json::Value::from(location)));
+    code.push_str(&format!("  required: {required},\n"));
+    code.push_str("  new(value): { [$['in']]+: { [$.name]: value } },\n");
+    code.push_str("}\n");
+    code
+}
+
+/// Generate the library of the servers of an OpenAPI specification
+///
+/// `baseUrl` is the URL of the first server; every server is listed in
+/// `servers`.
+fn generate_servers_library(schema: &crate::plugin::ExtractedSchema) -> String {
+    let servers = schema.content.as_sequence().cloned().unwrap_or_default();
+    let url = |server: &serde_yaml::Value| serde_json::Value::from(server["url"].as_str());
+
+    let mut code = String::new();
+    code.push_str(&format!(
+        "// Generated from OpenAPI servers: {}\n",
+        schema.name
+    ));
+    code.push_str(&format!("// Source: {}\n\n", schema.source_file.display()));
+    code.push_str("{\n");
+    if let Some(first) = servers.first() {
+        code.push_str(&format!("  baseUrl: {},\n", url(first)));
+    }
+    code.push_str("  servers: [\n");
+    for server in &servers {
+        let mut entry = serde_json::Map::new();
+        entry.insert("url".to_string(), url(server));
+        if let Some(description) = server.get("description").and_then(|d| d.as_str()) {
+            entry.insert("description".to_string(), description.into());
+        }
+        code.push_str(&format!("    {},\n", serde_json::Value::Object(entry)));
+    }
+    code.push_str("  ],\n");
+    code.push_str("}\n");
+    code
+}
+
+/// Import path of `target` relative to the directory `from`
+fn relative_import(from: &Path, target: &Path) -> String {
+    use std::path::Component;
+
+    fn normalize(path: &Path) -> Vec<Component<'_>> {
+        path.components()
+            .filter(|component| !matches!(component, Component::CurDir))
+            .collect()
+    }
+
+    let from = normalize(from);
+    let target = normalize(target);
+    let common = from.iter().zip(&target).take_while(|(a, b)| a == b).count();
+
+    let mut parts: Vec<String> = vec![".".to_string()];
+    parts.extend(std::iter::repeat_n("..".to_string(), from.len() - common));
+    parts.extend(
+        target[common..]
+            .iter()
+            .map(|component| component.as_os_str().to_string_lossy().to_string()),
+    );
+    if parts.len() > 1 && parts[1] == ".." {
+        parts.remove(0);
+    }
+    parts.join("/")
+}
+
+/// Metadata handed to plugins for a git-backed source at a commit
+fn git_source_metadata(
+    name: &str,
+    git: &crate::config::GitSource,
+    filters: &[String],
+    commit_sha: &str,
+) -> plugin::SourceMetadata {
+    plugin::SourceMetadata {
+        name: name.to_string(),
+        repository: Some(jsonnet_lockfile::redact_url(&git.url)),
+        git_ref: git.ref_name.clone(),
+        commit: Some(commit_sha.to_string()),
+        filters: filters.to_vec(),
+        license: None,
+        tag: None,
+    }
+}
+
+/// Plugin ids that processed a source's files, for recording in its result
+fn joined_plugin_ids(plugin_ids: &BTreeSet<plugin::PluginId>) -> Option<String> {
+    if plugin_ids.is_empty() {
+        None
+    } else {
+        Some(plugin_ids.iter().cloned().collect::<Vec<_>>().join(", "))
+    }
+}
+
+/// Convert a parsed CRD into the schema representation plugins work with
+fn to_extracted_schema(schema: &CrdSchema) -> ExtractedSchema {
+    let mut metadata = HashMap::new();
+    metadata.insert(
+        "group".to_string(),
+        serde_yaml::Value::String(schema.group.clone()),
+    );
+    metadata.insert(
+        "version".to_string(),
+        serde_yaml::Value::String(schema.version.clone()),
+    );
+    metadata.insert(
+        "kind".to_string(),
+        serde_yaml::Value::String(schema.kind.clone()),
+    );
+    metadata.insert(
+        "conversion_strategy".to_string(),
+        serde_yaml::Value::String(format!("{:?}", schema.conversion.strategy)),
+    );
+    metadata.insert(
+        "categories".to_string(),
+        serde_yaml::to_value(&schema.categories).unwrap_or_default(),
+    );
+    metadata.insert(
+        "printer_columns".to_string(),
+        serde_yaml::to_value(&schema.printer_columns).unwrap_or_default(),
+    );
+
+    let mut extracted = ExtractedSchema {
+        name: schema.name.clone(),
+        schema_type: "crd".to_string(),
+        content: schema.schema.clone(),
+        source_file: schema.source_path.clone(),
+        metadata,
+    };
+    extracted.set_field_lines(&schema.field_lines);
+    extracted
+}
+
+/// Convert main project's CrdSchema to generator crate's CrdSchema
+fn convert_crd_schema(schema: &CrdSchema) -> jsonnet_generator::crd::CrdSchema {
+    jsonnet_generator::crd::CrdSchema {
+        name: schema.name.clone(),
+        group: schema.group.clone(),
+        version: schema.version.clone(),
+        api_version: schema.api_version.clone(),
+        kind: schema.kind.clone(),
+        categories: schema.categories.clone(),
+        printer_columns: schema
+            .printer_columns
+            .iter()
+            .map(|column| jsonnet_generator::crd::PrinterColumn {
+                name: column.name.clone(),
+                column_type: column.column_type.clone(),
+                json_path: column.json_path.clone(),
+                description: column.description.clone(),
+                priority: column.priority,
+                format: column.format.clone(),
+            })
+            .collect(),
+        schema: schema.schema.clone(),
+        source_path: schema.source_path.clone(),
+        validation_rules: jsonnet_generator::crd::ValidationRules {
+            min_length: schema.validation_rules.min_length,
+            max_length: schema.validation_rules.max_length,
+            pattern: schema.validation_rules.pattern.clone(),
+            minimum: schema.validation_rules.minimum,
+            maximum: schema.validation_rules.maximum,
+            exclusive_minimum: schema.validation_rules.exclusive_minimum,
+            exclusive_maximum: schema.validation_rules.exclusive_maximum,
+            multiple_of: schema.validation_rules.multiple_of,
+            enum_values: schema.validation_rules.enum_values.clone(),
+            format: schema.validation_rules.format.clone(),
+            description: schema.validation_rules.description.clone(),
+            default_value: schema.validation_rules.default_value.clone(),
+            additional_properties: schema.validation_rules.additional_properties.clone(),
+            items: schema.validation_rules.items.clone(),
+            properties: schema.validation_rules.properties.clone(),
+            required: schema.validation_rules.required.clone(),
+        },
+        schema_analysis: jsonnet_generator::crd::SchemaAnalysis {
+            schema_type: schema.schema_analysis.schema_type.clone(),
+            fields: schema
+                .schema_analysis
+                .fields
+                .iter()
+                .map(|(k, v)| {
+                    (
+                        k.clone(),
+                        jsonnet_generator::crd::FieldAnalysis {
+                            pointer: v.pointer.clone(),
+                            field_type: v.field_type.clone(),
+                            validation_rules: jsonnet_generator::crd::ValidationRules {
+                                min_length: v.validation_rules.min_length,
+                                max_length: v.validation_rules.max_length,
+                                pattern: v.validation_rules.pattern.clone(),
+                                minimum: v.validation_rules.minimum,
+                                maximum: v.validation_rules.maximum,
+                                exclusive_minimum: v.validation_rules.exclusive_minimum,
+                                exclusive_maximum: v.validation_rules.exclusive_maximum,
+                                multiple_of: v.validation_rules.multiple_of,
+                                enum_values: v.validation_rules.enum_values.clone(),
+                                format: v.validation_rules.format.clone(),
+                                description: v.validation_rules.description.clone(),
+                                default_value: v.validation_rules.default_value.clone(),
+                                additional_properties: v
+                                    .validation_rules
+                                    .additional_properties
+                                    .clone(),
+                                items: v.validation_rules.items.clone(),
+                                properties: v.validation_rules.properties.clone(),
+                                required: v.validation_rules.required.clone(),
+                            },
+                            nested_properties: v.nested_properties.clone(),
+                            array_items: v.array_items.clone(),
+                        },
+                    )
+                })
+                .collect(),
+            array_item_type: schema.schema_analysis.array_item_type.as_ref().map(|v| {
+                jsonnet_generator::crd::FieldAnalysis {
+                    pointer: v.pointer.clone(),
+                    field_type: v.field_type.clone(),
+                    validation_rules: jsonnet_generator::crd::ValidationRules {
+                        min_length: v.validation_rules.min_length,
+                        max_length: v.validation_rules.max_length,
+                        pattern: v.validation_rules.pattern.clone(),
+                        minimum: v.validation_rules.minimum,
+                        maximum: v.validation_rules.maximum,
+                        exclusive_minimum: v.validation_rules.exclusive_minimum,
+                        exclusive_maximum: v.validation_rules.exclusive_maximum,
+                        multiple_of: v.validation_rules.multiple_of,
+                        enum_values: v.validation_rules.enum_values.clone(),
+                        format: v.validation_rules.format.clone(),
+                        description: v.validation_rules.description.clone(),
+                        default_value: v.validation_rules.default_value.clone(),
+                        additional_properties: v.validation_rules.additional_properties.clone(),
+                        items: v.validation_rules.items.clone(),
+                        properties: v.validation_rules.properties.clone(),
+                        required: v.validation_rules.required.clone(),
+                    },
+                    nested_properties: v.nested_properties.clone(),
+                    array_items: v.array_items.clone(),
+                }
+            }),
+            one_of: schema.schema_analysis.one_of.clone(),
+            any_of: schema.schema_analysis.any_of.clone(),
+            all_of: schema.schema_analysis.all_of.clone(),
+            reference: schema.schema_analysis.reference.clone(),
+        },
+        conversion: jsonnet_generator::crd::ConversionInfo {
+            strategy: match schema.conversion.strategy {
+                jsonnet_crd::ConversionStrategy::None => {
+                    jsonnet_generator::crd::ConversionStrategy::None
+                }
+                jsonnet_crd::ConversionStrategy::Webhook => {
+                    jsonnet_generator::crd::ConversionStrategy::Webhook
+                }
+            },
+            webhook_endpoint: schema.conversion.webhook_endpoint.clone(),
+            conversion_review_versions: schema.conversion.conversion_review_versions.clone(),
+        },
+        field_lines: schema.field_lines.clone(),
+    }
+}
+
+/// Files generated below an output directory
+fn generated_files(output_path: &Path) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(output_path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.path().to_path_buf())
+        .collect()
+}
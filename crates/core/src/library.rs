@@ -0,0 +1,316 @@
+//! Hand-written Jsonnet libraries re-exported as sources
+//!
+//! A `jsonnet` source copies a library to its output path as written, next
+//! to an `index.libsonnet` importing it, so the platform bundle and version
+//! stamps treat it like a generated library. The shape of what the library
+//! exports is introspected with a Jsonnet evaluator and kept in
+//! `_shape.json`: object fields map to the shapes of their values, down to
+//! [`SHAPE_DEPTH`] levels, and any other value to its Jsonnet type.
+
+use crate::config::JsonnetSource;
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// File recording the shape of a re-exported library
+pub const SHAPE_FILE: &str = "_shape.json";
+
+/// Levels of nested objects the shape describes
+pub const SHAPE_DEPTH: usize = 3;
+
+/// Files of a hand-written library
+#[derive(Debug, Clone)]
+pub struct Library {
+    /// Directory holding the library
+    pub dir: PathBuf,
+
+    /// Entry file, relative to `dir`
+    pub entry: PathBuf,
+
+    /// Files of the library, relative to `dir` and sorted
+    pub files: Vec<PathBuf>,
+}
+
+impl Library {
+    /// Collect the files of a source's library
+    ///
+    /// A directory contributes its `.jsonnet`, `.libsonnet` and `.json`
+    /// files, skipping hidden entries and the source's own output path.
+    pub fn open(source: &JsonnetSource) -> Result<Self> {
+        let entry = source.entry_file();
+        if source.path.is_file() {
+            return Ok(Self {
+                dir: match source.path.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                    _ => PathBuf::from("."),
+                },
+                files: vec![entry.clone()],
+                entry,
+            });
+        }
+        if !source.path.is_dir() {
+            return Err(anyhow!(
+                "Jsonnet source {} library {} does not exist",
+                source.name,
+                source.path.display()
+            ));
+        }
+
+        let output_path = source.output_path.canonicalize().ok();
+        let mut files = Vec::new();
+        let walker = WalkDir::new(&source.path).into_iter().filter_entry(|e| {
+            let hidden = e.depth() > 0 && e.file_name().to_string_lossy().starts_with('.');
+            let output = e.file_type().is_dir()
+                && output_path.is_some()
+                && e.path().canonicalize().ok() == output_path;
+            !hidden && !output
+        });
+        for entry in walker {
+            let entry = entry?;
+            let library_file = matches!(
+                entry.path().extension().and_then(|e| e.to_str()),
+                Some("jsonnet" | "libsonnet" | "json")
+            );
+            if entry.file_type().is_file() && library_file {
+                files.push(entry.path().strip_prefix(&source.path)?.to_path_buf());
+            }
+        }
+        files.sort();
+
+        if !files.contains(&entry) {
+            return Err(anyhow!(
+                "Jsonnet source {} has no entry file {} in {}",
+                source.name,
+                entry.display(),
+                source.path.display()
+            ));
+        }
+        Ok(Self {
+            dir: source.path.clone(),
+            entry,
+            files,
+        })
+    }
+
+    /// Path of the entry file
+    pub fn entry_path(&self) -> PathBuf {
+        self.dir.join(&self.entry)
+    }
+
+    /// Digest of the library's file names and contents (`sha256:<hex>`),
+    /// standing in for a commit
+    pub fn digest(&self) -> Result<String> {
+        let mut hasher = Sha256::new();
+        for file in &self.files {
+            hasher.update(file.to_string_lossy().as_bytes());
+            hasher.update([0]);
+            hasher.update(std::fs::read(self.dir.join(file))?);
+            hasher.update([0]);
+        }
+        Ok(format!("sha256:{}", hex::encode(hasher.finalize())))
+    }
+
+    /// Copy the library's files verbatim below `output_path`, returning how
+    /// many were copied
+    pub fn copy_to(&self, output_path: &Path) -> Result<usize> {
+        for file in &self.files {
+            let target = output_path.join(file);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(self.dir.join(file), &target)?;
+        }
+        Ok(self.files.len())
+    }
+}
+
+/// Contents of the `index.libsonnet` re-exporting a library
+///
+/// Object libraries are extended with an empty object, which version
+/// stamping fills with `__version__`.
+pub fn index_file(entry: &Path, shape: Option<&serde_json::Value>) -> String {
+    let import = serde_json::to_string(&format!("./{}", entry.to_string_lossy()))
+        .expect("strings serialize");
+    match shape {
+        Some(serde_json::Value::Object(_)) => {
+            format!("// Re-exported Jsonnet library\n(import {import}) +\n{{\n}}\n")
+        }
+        _ => format!("// Re-exported Jsonnet library\nimport {import}\n"),
+    }
+}
+
+/// Introspects the shape of libraries with a Jsonnet evaluator
+pub struct LibraryIntrospector {
+    evaluator: String,
+    jpaths: Vec<PathBuf>,
+}
+
+impl LibraryIntrospector {
+    /// Create an introspector using the `jsonnet` binary on the PATH
+    pub fn new() -> Self {
+        Self {
+            evaluator: "jsonnet".to_string(),
+            jpaths: Vec::new(),
+        }
+    }
+
+    /// Use a different evaluator binary (e.g. `jrsonnet`)
+    pub fn with_evaluator(mut self, evaluator: impl Into<String>) -> Self {
+        self.evaluator = evaluator.into();
+        self
+    }
+
+    /// Add library search paths, passed to the evaluator as `-J`
+    pub fn with_jpaths(mut self, jpaths: Vec<PathBuf>) -> Self {
+        self.jpaths = jpaths;
+        self
+    }
+
+    /// Shape of what a library file exports
+    pub async fn shape(&self, file: &Path) -> Result<serde_json::Value> {
+        let import = serde_json::to_string(&std::path::absolute(file)?.to_string_lossy())?;
+        let expression = format!(
+            "local shape(value, depth) =\n  \
+             if std.isObject(value) then\n    \
+             if depth == 0 then 'object'\n    \
+             else {{ [field]: shape(value[field], depth - 1) for field in std.objectFields(value) }}\n  \
+             else std.type(value);\n\
+             shape(import {import}, {SHAPE_DEPTH})"
+        );
+
+        let mut command = tokio::process::Command::new(&self.evaluator);
+        for jpath in &self.jpaths {
+            command.arg("-J").arg(jpath);
+        }
+        let output = command
+            .arg("-e")
+            .arg(&expression)
+            .output()
+            .await
+            .map_err(|e| crate::utils::evaluator_error(&self.evaluator, e))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to evaluate {}: {}",
+                file.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        serde_json::from_slice(&output.stdout)
+            .map_err(|e| anyhow!("Unexpected evaluator output for {}: {}", file.display(), e))
+    }
+}
+
+impl Default for LibraryIntrospector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn source(path: &Path, output_path: &Path) -> JsonnetSource {
+        serde_yaml::from_str(&format!(
+            "name: platform\npath: {}\noutput_path: {}\n",
+            path.display(),
+            output_path.display()
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_library() {
+        let temp_dir = TempDir::new().unwrap();
+        let lib = temp_dir.path().join("lib");
+        std::fs::create_dir_all(lib.join("util")).unwrap();
+        std::fs::create_dir_all(lib.join(".git")).unwrap();
+        std::fs::write(
+            lib.join("main.libsonnet"),
+            "{ util: import 'util/fn.libsonnet' }",
+        )
+        .unwrap();
+        std::fs::write(lib.join("util/fn.libsonnet"), "{ id(x): x }").unwrap();
+        std::fs::write(lib.join("README.md"), "# platform").unwrap();
+        std::fs::write(lib.join(".git/config.json"), "{}").unwrap();
+        let output = lib.join("generated");
+        std::fs::create_dir_all(&output).unwrap();
+        std::fs::write(output.join("index.libsonnet"), "{}").unwrap();
+
+        let library = Library::open(&source(&lib, &output)).unwrap();
+        assert_eq!(
+            library.files,
+            [
+                PathBuf::from("main.libsonnet"),
+                PathBuf::from("util/fn.libsonnet")
+            ]
+        );
+        let digest = library.digest().unwrap();
+        assert!(digest.starts_with("sha256:"));
+
+        // The digest follows the contents
+        std::fs::write(lib.join("util/fn.libsonnet"), "{ id(x):: x }").unwrap();
+        assert_ne!(library.digest().unwrap(), digest);
+
+        let target = temp_dir.path().join("out");
+        assert_eq!(library.copy_to(&target).unwrap(), 2);
+        assert_eq!(
+            std::fs::read_to_string(target.join("util/fn.libsonnet")).unwrap(),
+            "{ id(x):: x }"
+        );
+
+        // A single file is its own entry
+        let single = Library::open(&source(&lib.join("util/fn.libsonnet"), &output)).unwrap();
+        assert_eq!(single.entry, PathBuf::from("fn.libsonnet"));
+        assert_eq!(single.entry_path(), lib.join("util/fn.libsonnet"));
+
+        std::fs::remove_file(lib.join("main.libsonnet")).unwrap();
+        assert!(Library::open(&source(&lib, &output)).is_err());
+
+        let object = serde_json::json!({ "util": { "id": "function" } });
+        assert_eq!(
+            index_file(Path::new("main.libsonnet"), Some(&object)),
+            "// Re-exported Jsonnet library\n(import \"./main.libsonnet\") +\n{\n}\n"
+        );
+        assert_eq!(
+            index_file(Path::new("main.libsonnet"), None),
+            "// Re-exported Jsonnet library\nimport \"./main.libsonnet\"\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_introspect_library() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("main.libsonnet");
+        std::fs::write(&file, "{}").unwrap();
+
+        // Prints a shape for whatever it is asked to evaluate
+        let evaluator = temp_dir.path().join("evaluator");
+        std::fs::write(
+            &evaluator,
+            "#!/bin/sh\necho '{\"util\": {\"id\": \"function\"}}'\n",
+        )
+        .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&evaluator, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let shape = LibraryIntrospector::new()
+            .with_evaluator(evaluator.to_string_lossy())
+            .shape(&file)
+            .await
+            .unwrap();
+        assert_eq!(shape["util"]["id"], "function");
+
+        let failing = LibraryIntrospector::new().with_evaluator("false");
+        assert!(failing.shape(&file).await.is_err());
+
+        let missing = LibraryIntrospector::new().with_evaluator("gensonnet-no-such-evaluator");
+        let err = missing.shape(&file).await.unwrap_err();
+        assert!(err.to_string().contains("not found"));
+    }
+}
@@ -0,0 +1,128 @@
+//! License detection for source repositories
+//!
+//! Looks for a license file at the root of a repository and identifies it by
+//! its SPDX identifier, either from an `SPDX-License-Identifier` line or from
+//! characteristic phrases of common licenses.
+
+use std::path::Path;
+
+/// SPDX identifier of a license file that was found but not recognized
+pub const NOASSERTION: &str = "NOASSERTION";
+
+/// Base names of license files, compared case-insensitively
+const LICENSE_FILES: &[&str] = &["LICENSE", "LICENCE", "COPYING"];
+
+/// Detect the SPDX identifier of the license at the root of a repository
+///
+/// Returns `None` if the repository has no license file.
+pub fn detect(repo_path: &Path) -> Option<String> {
+    let mut candidates: Vec<_> = std::fs::read_dir(repo_path)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_license_file(path))
+        .collect();
+    candidates.sort();
+
+    let text = candidates
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())?;
+    Some(identify(&text))
+}
+
+/// Identify a license text, returning `NOASSERTION` if it is not recognized
+pub fn identify(text: &str) -> String {
+    for line in text.lines() {
+        if let Some((_, id)) = line.split_once("SPDX-License-Identifier:") {
+            return id.trim().to_string();
+        }
+    }
+
+    // Compare on lowercase text with collapsed whitespace, as license
+    // files are wrapped differently
+    let text = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    let has = |phrase: &str| text.contains(phrase);
+
+    let id = if has("gnu affero general public license") {
+        "AGPL-3.0"
+    } else if has("gnu lesser general public license") {
+        if has("version 3") {
+            "LGPL-3.0"
+        } else {
+            "LGPL-2.1"
+        }
+    } else if has("gnu general public license") {
+        if has("version 3") {
+            "GPL-3.0"
+        } else {
+            "GPL-2.0"
+        }
+    } else if has("mozilla public license version 2.0")
+        || has("mozilla public license, version 2.0")
+    {
+        "MPL-2.0"
+    } else if has("apache license") && has("version 2.0") {
+        "Apache-2.0"
+    } else if has("permission is hereby granted, free of charge") {
+        "MIT"
+    } else if has("permission to use, copy, modify, and/or distribute") {
+        "ISC"
+    } else if has("redistribution and use in source and binary forms") {
+        if has("neither the name") || has("names of its contributors") {
+            "BSD-3-Clause"
+        } else {
+            "BSD-2-Clause"
+        }
+    } else if has("this is free and unencumbered software released into the public domain") {
+        "Unlicense"
+    } else {
+        NOASSERTION
+    };
+
+    id.to_string()
+}
+
+/// Whether a file is a license file such as `LICENSE`, `LICENSE.md` or `COPYING`
+fn is_license_file(path: &Path) -> bool {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| {
+            LICENSE_FILES
+                .iter()
+                .any(|name| stem.eq_ignore_ascii_case(name))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_license() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(detect(temp_dir.path()), None);
+
+        std::fs::write(
+            temp_dir.path().join("LICENSE.txt"),
+            "\n                                 Apache License\n                           Version 2.0, January 2004\n",
+        )
+        .unwrap();
+        assert_eq!(detect(temp_dir.path()).as_deref(), Some("Apache-2.0"));
+
+        assert_eq!(
+            identify("MIT License\n\nPermission is hereby granted, free of\ncharge, to any person"),
+            "MIT"
+        );
+        assert_eq!(identify("// SPDX-License-Identifier: MPL-2.0\n"), "MPL-2.0");
+        assert_eq!(
+            identify("Redistribution and use in source and binary forms ... Neither the name of"),
+            "BSD-3-Clause"
+        );
+        assert_eq!(identify("All rights reserved."), NOASSERTION);
+    }
+}
@@ -0,0 +1,161 @@
+//! Per-run resource limits
+//!
+//! `generation.resource_limits` caps what a single run may use, so a runaway
+//! configuration fails with an explanation instead of exhausting a shared CI
+//! machine. The repositories a run needs are counted when it is planned,
+//! before anything is fetched. The size of the parsed schemas, an estimate of
+//! the memory they take, and the number of generated files are accounted as
+//! sources are processed; a source going over a limit fails the whole run.
+
+use crate::config::{ResourceLimits, Source};
+use crate::utils::format_bytes;
+use crate::JsonnetGenError;
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+/// Resources used by the current run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// Estimated size in bytes of the schemas parsed so far
+    pub schema_bytes: u64,
+
+    /// Files generated so far
+    pub generated_files: usize,
+}
+
+/// Accounts the resources of a run against its limits
+#[derive(Debug, Default)]
+pub struct ResourceTracker {
+    limits: ResourceLimits,
+    usage: Mutex<ResourceUsage>,
+}
+
+impl ResourceTracker {
+    pub fn new(limits: ResourceLimits) -> Self {
+        Self {
+            limits,
+            usage: Mutex::default(),
+        }
+    }
+
+    /// Start accounting a new run
+    pub fn reset(&self) {
+        *self.usage.lock().unwrap() = ResourceUsage::default();
+    }
+
+    /// Resources used by the run so far
+    pub fn usage(&self) -> ResourceUsage {
+        self.usage.lock().unwrap().clone()
+    }
+
+    /// Refuse to plan a run whose sources use more repositories than allowed
+    pub fn check_plan<'a>(&self, sources: impl IntoIterator<Item = &'a Source>) -> Result<()> {
+        let Some(max) = self.limits.max_open_repositories else {
+            return Ok(());
+        };
+        let repositories: BTreeSet<String> = sources
+            .into_iter()
+            .filter_map(Source::git_source)
+            .map(|git| jsonnet_lockfile::redact_url(&git.url))
+            .collect();
+        if repositories.len() <= max {
+            return Ok(());
+        }
+
+        let listed: Vec<&str> = repositories.iter().take(5).map(String::as_str).collect();
+        let more = repositories.len().saturating_sub(listed.len());
+        Err(JsonnetGenError::ResourceLimit(format!(
+            "the run uses {} repositories, over generation.resource_limits.max_open_repositories ({}): {}{}. Generate fewer sources at once, e.g. with `generate --source`",
+            repositories.len(),
+            max,
+            listed.join(", "),
+            if more > 0 { format!(" and {more} more") } else { String::new() }
+        ))
+        .into())
+    }
+
+    /// Account the estimated size of schemas parsed for a source
+    pub fn add_schema_bytes(&self, source: &str, bytes: u64) -> Result<()> {
+        let mut usage = self.usage.lock().unwrap();
+        usage.schema_bytes += bytes;
+        match self.limits.max_schema_bytes {
+            Some(max) if usage.schema_bytes > max => Err(JsonnetGenError::ResourceLimit(format!(
+                "schemas parsed up to source {} take an estimated {}, over generation.resource_limits.max_schema_bytes ({}). Add filters to narrow the sources",
+                source,
+                format_bytes(usage.schema_bytes),
+                format_bytes(max)
+            ))
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Account the files generated for a source, before they are published
+    pub fn add_generated_files(&self, source: &str, files: usize) -> Result<()> {
+        let mut usage = self.usage.lock().unwrap();
+        usage.generated_files += files;
+        match self.limits.max_generated_files {
+            Some(max) if usage.generated_files > max => {
+                Err(JsonnetGenError::ResourceLimit(format!(
+                    "source {} brings the run to {} generated files, over generation.resource_limits.max_generated_files ({}). Add filters to narrow the sources",
+                    source, usage.generated_files, max
+                ))
+                .into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Estimated size in bytes of a parsed schema, from its YAML serialization
+pub fn schema_size(schema: &serde_yaml::Value) -> u64 {
+    serde_yaml::to_string(schema).map_or(0, |yaml| yaml.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_tracker() {
+        let sources: Vec<Source> = ["a", "b", "a"]
+            .iter()
+            .map(|repo| {
+                serde_yaml::from_str(&format!(
+                    "type: crd\nname: {repo}\ngit: {{url: \"https://example.com/{repo}.git\", ref: main}}\nfilters: []\noutput_path: ./out\n"
+                ))
+                .unwrap()
+            })
+            .collect();
+        let tracker = ResourceTracker::new(ResourceLimits {
+            max_open_repositories: Some(1),
+            max_schema_bytes: Some(100),
+            max_generated_files: Some(2),
+        });
+
+        // Sources sharing a repository count it once
+        assert!(tracker.check_plan(&sources[..1]).is_ok());
+        assert!(tracker.check_plan([&sources[0], &sources[2]]).is_ok());
+        let err = tracker.check_plan(&sources).unwrap_err().to_string();
+        assert!(err.contains("uses 2 repositories"), "{err}");
+
+        assert!(tracker.add_schema_bytes("a", 60).is_ok());
+        assert!(tracker.add_schema_bytes("b", 60).is_err());
+        assert!(tracker.add_generated_files("a", 2).is_ok());
+        assert!(tracker.add_generated_files("b", 1).is_err());
+        assert_eq!(
+            tracker.usage(),
+            ResourceUsage {
+                schema_bytes: 120,
+                generated_files: 3
+            }
+        );
+
+        tracker.reset();
+        assert_eq!(tracker.usage(), ResourceUsage::default());
+        assert!(ResourceTracker::default()
+            .add_generated_files("a", 1000)
+            .is_ok());
+    }
+}
@@ -0,0 +1,325 @@
+//! Prometheus metrics for long-running generation (watch mode)
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use jsonnet_generator::GenerationResult;
+
+/// Metrics collected across generation runs
+#[derive(Debug, Default)]
+pub struct GenerationMetrics {
+    state: Mutex<MetricsState>,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    generations_total: u64,
+    generation_failures_total: u64,
+    duration_seconds_sum: f64,
+    duration_seconds_count: u64,
+    last_duration_seconds: f64,
+    last_sources_processed: usize,
+    last_cache_hit_rate: f64,
+    files_generated: BTreeMap<String, usize>,
+    schemas: BTreeMap<String, usize>,
+    schemas_reused: BTreeMap<String, usize>,
+    errors_total: BTreeMap<String, u64>,
+}
+
+impl GenerationMetrics {
+    /// Create an empty metrics collection
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a completed generation run
+    pub fn record_generation(&self, result: &GenerationResult) {
+        let mut state = self.state.lock().unwrap();
+        let duration_seconds = result.statistics.total_processing_time_ms as f64 / 1000.0;
+
+        state.generations_total += 1;
+        state.duration_seconds_sum += duration_seconds;
+        state.duration_seconds_count += 1;
+        state.last_duration_seconds = duration_seconds;
+        state.last_sources_processed = result.sources_processed;
+        state.last_cache_hit_rate = result.statistics.cache_hit_rate;
+
+        for source_result in &result.results {
+            state.files_generated.insert(
+                source_result.source_name.clone(),
+                source_result.files_generated,
+            );
+            state.schemas.insert(
+                source_result.source_name.clone(),
+                source_result.schema_digests.len(),
+            );
+            state.schemas_reused.insert(
+                source_result.source_name.clone(),
+                source_result.schemas_reused,
+            );
+            *state
+                .errors_total
+                .entry(source_result.source_name.clone())
+                .or_default() += source_result.errors.len() as u64;
+        }
+    }
+
+    /// Record a generation run that failed before producing a result
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.generations_total += 1;
+        state.generation_failures_total += 1;
+    }
+
+    /// Render the metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let state = self.state.lock().unwrap();
+        let mut out = String::new();
+
+        write_metric(
+            &mut out,
+            "gensonnet_generations_total",
+            "counter",
+            "Total number of generation runs",
+            &[(None, state.generations_total as f64)],
+        );
+        write_metric(
+            &mut out,
+            "gensonnet_generation_failures_total",
+            "counter",
+            "Total number of generation runs that failed",
+            &[(None, state.generation_failures_total as f64)],
+        );
+        write_summary(
+            &mut out,
+            "gensonnet_generation_duration_seconds",
+            "Time spent by completed generation runs, in seconds",
+            state.duration_seconds_sum,
+            state.duration_seconds_count,
+        );
+        write_metric(
+            &mut out,
+            "gensonnet_last_generation_duration_seconds",
+            "gauge",
+            "Duration of the most recent generation run, in seconds",
+            &[(None, state.last_duration_seconds)],
+        );
+        write_metric(
+            &mut out,
+            "gensonnet_sources_processed",
+            "gauge",
+            "Number of sources processed by the most recent generation run",
+            &[(None, state.last_sources_processed as f64)],
+        );
+        write_metric(
+            &mut out,
+            "gensonnet_cache_hit_rate",
+            "gauge",
+            "Cache hit rate of the most recent generation run (0.0 to 1.0)",
+            &[(None, state.last_cache_hit_rate)],
+        );
+
+        let files: Vec<_> = state
+            .files_generated
+            .iter()
+            .map(|(source, count)| (Some(source.as_str()), *count as f64))
+            .collect();
+        write_metric(
+            &mut out,
+            "gensonnet_files_generated",
+            "gauge",
+            "Number of files generated per source by the most recent run",
+            &files,
+        );
+
+        let schemas: Vec<_> = state
+            .schemas
+            .iter()
+            .map(|(source, count)| (Some(source.as_str()), *count as f64))
+            .collect();
+        write_metric(
+            &mut out,
+            "gensonnet_schemas",
+            "gauge",
+            "Number of schemas per source in the most recent run",
+            &schemas,
+        );
+
+        let reused: Vec<_> = state
+            .schemas_reused
+            .iter()
+            .map(|(source, count)| (Some(source.as_str()), *count as f64))
+            .collect();
+        write_metric(
+            &mut out,
+            "gensonnet_schemas_reused",
+            "gauge",
+            "Number of schemas per source left as published by the most recent run because they were unchanged",
+            &reused,
+        );
+
+        let errors: Vec<_> = state
+            .errors_total
+            .iter()
+            .map(|(source, count)| (Some(source.as_str()), *count as f64))
+            .collect();
+        write_metric(
+            &mut out,
+            "gensonnet_source_errors_total",
+            "counter",
+            "Total number of errors reported per source",
+            &errors,
+        );
+
+        out
+    }
+}
+
+/// Write a single metric family with optional `source` labels
+fn write_metric(
+    out: &mut String,
+    name: &str,
+    metric_type: &str,
+    help: &str,
+    samples: &[(Option<&str>, f64)],
+) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {metric_type}");
+    for (source, value) in samples {
+        match source {
+            Some(source) => {
+                let source = source.replace('\\', "\\\\").replace('"', "\\\"");
+                let _ = writeln!(out, "{name}{{source=\"{source}\"}} {value}");
+            }
+            None => {
+                let _ = writeln!(out, "{name} {value}");
+            }
+        }
+    }
+}
+
+/// Write a summary without quantiles, as its `_sum` and `_count`
+fn write_summary(out: &mut String, name: &str, help: &str, sum: f64, count: u64) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} summary");
+    let _ = writeln!(out, "{name}_sum {sum}");
+    let _ = writeln!(out, "{name}_count {count}");
+}
+
+/// Serve the metrics on `addr` until the returned task is aborted
+pub async fn serve(metrics: Arc<GenerationMetrics>, addr: SocketAddr) -> Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(addr).await?;
+    info!(
+        "Serving metrics on http://{}/metrics",
+        listener.local_addr()?
+    );
+
+    Ok(tokio::spawn(async move {
+        loop {
+            let (mut stream, peer) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    warn!("Failed to accept metrics connection: {}", e);
+                    continue;
+                }
+            };
+
+            let metrics = Arc::clone(&metrics);
+            tokio::spawn(async move {
+                let mut buffer = [0u8; 1024];
+                let read = match stream.read(&mut buffer).await {
+                    Ok(read) => read,
+                    Err(e) => {
+                        debug!("Failed to read metrics request from {}: {}", peer, e);
+                        return;
+                    }
+                };
+
+                let request = String::from_utf8_lossy(&buffer[..read]);
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+                let response = if path == "/metrics" {
+                    let body = metrics.render();
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                } else {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                };
+
+                if let Err(e) = stream.write_all(response.as_bytes()).await {
+                    debug!("Failed to write metrics response to {}: {}", peer, e);
+                }
+            });
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonnet_generator::result::GenerationStatistics;
+    use jsonnet_generator::SourceResult;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_render_after_generation() {
+        let metrics = GenerationMetrics::new();
+        metrics.record_generation(&GenerationResult {
+            sources_processed: 1,
+            total_sources: 1,
+            results: vec![SourceResult {
+                source_name: "cert-manager".to_string(),
+                source_type: "crd".to_string(),
+                files_generated: 4,
+                errors: vec!["boom".to_string()],
+                output_path: PathBuf::from("./out"),
+                processing_time_ms: 10,
+                warnings: Vec::new(),
+                plugin: None,
+                file_plugins: Default::default(),
+                schema_digests: [
+                    (
+                        "v1/certificate.libsonnet".to_string(),
+                        "sha256:a".to_string(),
+                    ),
+                    ("v1/issuer.libsonnet".to_string(), "sha256:b".to_string()),
+                ]
+                .into_iter()
+                .collect(),
+                schemas_reused: 1,
+                statistics: Default::default(),
+            }],
+            statistics: GenerationStatistics {
+                total_processing_time_ms: 1500,
+                cache_hit_rate: 0.5,
+                ..Default::default()
+            },
+            quarantined: Vec::new(),
+        });
+        metrics.record_failure();
+
+        let output = metrics.render();
+        assert!(output.contains("gensonnet_generations_total 2"));
+        assert!(output.contains("gensonnet_generation_failures_total 1"));
+        assert!(output.contains("gensonnet_last_generation_duration_seconds 1.5"));
+        assert!(output.contains("# TYPE gensonnet_generation_duration_seconds summary"));
+        assert!(output.contains("gensonnet_generation_duration_seconds_sum 1.5"));
+        assert!(output.contains("gensonnet_generation_duration_seconds_count 1"));
+        assert!(output.contains("gensonnet_schemas{source=\"cert-manager\"} 2"));
+        assert!(output.contains("gensonnet_schemas_reused{source=\"cert-manager\"} 1"));
+        assert!(output.contains("gensonnet_cache_hit_rate 0.5"));
+        assert!(output.contains("gensonnet_files_generated{source=\"cert-manager\"} 4"));
+        assert!(output.contains("gensonnet_source_errors_total{source=\"cert-manager\"} 1"));
+    }
+}
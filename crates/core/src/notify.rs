@@ -0,0 +1,387 @@
+//! Notifications of finished generation runs
+//!
+//! Each `generate` run posts a summary to the webhooks in
+//! `notifications.webhooks` that subscribe to its outcome: its statistics,
+//! the sources that failed or were skipped, and how many files the lockfile
+//! gained, changed or lost. A webhook's `payload` template shapes the JSON
+//! posted, e.g. into a Slack message. Notifying never fails the run; a
+//! webhook that cannot be reached is only logged.
+
+use crate::config::{NotificationEvent, NotificationsConfig, WebhookConfig};
+use anyhow::{anyhow, Result};
+use jsonnet_generator::result::GenerationResult;
+use jsonnet_lockfile::Lockfile;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tracing::{info, warn};
+
+/// Summary of a finished run, posted to webhooks
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    /// Command that ran, e.g. `generate`
+    pub command: String,
+
+    /// Outcome of the run
+    pub status: NotificationEvent,
+
+    /// Error that aborted the run
+    pub error: Option<String>,
+
+    /// Number of sources processed
+    pub sources_processed: usize,
+
+    /// Number of configured sources
+    pub total_sources: usize,
+
+    /// Number of files generated
+    pub files_generated: usize,
+
+    /// Number of errors across sources
+    pub error_count: usize,
+
+    /// Number of warnings across sources
+    pub warning_count: usize,
+
+    /// Duration of the run in milliseconds
+    pub duration_ms: u64,
+
+    /// Sources that reported errors
+    pub failed_sources: Vec<String>,
+
+    /// Sources skipped as quarantined
+    pub quarantined: Vec<String>,
+
+    /// Files the lockfile records that it did not before the run
+    pub files_added: usize,
+
+    /// Files whose checksum in the lockfile changed
+    pub files_changed: usize,
+
+    /// Files the lockfile no longer records
+    pub files_removed: usize,
+}
+
+impl RunSummary {
+    /// Summarize a run, given the lockfile before and after it
+    pub fn new(
+        command: &str,
+        result: &Result<GenerationResult>,
+        before: &Lockfile,
+        after: &Lockfile,
+        duration_ms: u64,
+    ) -> Self {
+        let mut summary = Self {
+            command: command.to_string(),
+            status: NotificationEvent::Success,
+            error: None,
+            sources_processed: 0,
+            total_sources: 0,
+            files_generated: 0,
+            error_count: 0,
+            warning_count: 0,
+            duration_ms,
+            failed_sources: Vec::new(),
+            quarantined: Vec::new(),
+            files_added: 0,
+            files_changed: 0,
+            files_removed: 0,
+        };
+
+        for (path, checksum) in &after.files {
+            match before.files.get(path) {
+                None => summary.files_added += 1,
+                Some(previous) if previous.sha256 != checksum.sha256 => summary.files_changed += 1,
+                Some(_) => {}
+            }
+        }
+        summary.files_removed = before
+            .files
+            .keys()
+            .filter(|path| !after.files.contains_key(*path))
+            .count();
+
+        match result {
+            Ok(result) => {
+                summary.sources_processed = result.sources_processed;
+                summary.total_sources = result.total_sources;
+                summary.files_generated = result.statistics.files_generated;
+                summary.error_count = result.statistics.error_count;
+                summary.warning_count = result.statistics.warning_count;
+                summary.failed_sources = result
+                    .results
+                    .iter()
+                    .filter(|source_result| !source_result.errors.is_empty())
+                    .map(|source_result| source_result.source_name.clone())
+                    .collect();
+                summary.quarantined = result.quarantined.clone();
+                if !summary.failed_sources.is_empty() {
+                    summary.status = NotificationEvent::Failure;
+                }
+            }
+            Err(e) => {
+                summary.status = NotificationEvent::Failure;
+                summary.error = Some(e.to_string());
+            }
+        }
+        summary
+    }
+
+    /// Summary of a run that failed one of two sources, to check payload templates against
+    pub fn example() -> Self {
+        Self {
+            command: "generate".to_string(),
+            status: NotificationEvent::Failure,
+            error: None,
+            sources_processed: 2,
+            total_sources: 2,
+            files_generated: 12,
+            error_count: 1,
+            warning_count: 0,
+            duration_ms: 1500,
+            failed_sources: vec!["example".to_string()],
+            quarantined: Vec::new(),
+            files_added: 2,
+            files_changed: 3,
+            files_removed: 0,
+        }
+    }
+}
+
+/// Fill the `{placeholder}`s of a payload template with the fields of a summary
+///
+/// Text is escaped for use inside JSON strings, lists are joined with `, `
+/// and a missing error is empty. Unknown placeholders are left as written.
+/// The result must be valid JSON.
+pub fn render_payload(template: &str, summary: &RunSummary) -> Result<serde_json::Value> {
+    let serde_json::Value::Object(fields) = serde_json::to_value(summary)? else {
+        return Err(anyhow!("Run summary is not an object"));
+    };
+
+    let escape = |text: &str| {
+        let quoted = serde_json::Value::String(text.to_string()).to_string();
+        quoted[1..quoted.len() - 1].to_string()
+    };
+    let values: BTreeMap<String, String> = fields
+        .into_iter()
+        .map(|(name, value)| {
+            let text = match value {
+                serde_json::Value::Null => String::new(),
+                serde_json::Value::String(text) => escape(&text),
+                serde_json::Value::Array(items) => escape(
+                    &items
+                        .iter()
+                        .map(|item| item.as_str().unwrap_or_default())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+                value => value.to_string(),
+            };
+            (name, text)
+        })
+        .collect();
+
+    // One pass over the template, so placeholders in filled-in values stay as they are
+    let mut payload = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        payload.push_str(&rest[..start]);
+        let placeholder = rest[start + 1..].find('}').and_then(|end| {
+            values
+                .get(&rest[start + 1..start + 1 + end])
+                .map(|text| (end, text))
+        });
+        match placeholder {
+            Some((end, text)) => {
+                payload.push_str(text);
+                rest = &rest[start + end + 2..];
+            }
+            None => {
+                payload.push('{');
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    payload.push_str(rest);
+
+    serde_json::from_str(&payload)
+        .map_err(|e| anyhow!("Payload is not valid JSON once filled in: {}", e))
+}
+
+/// Posts run summaries to the configured webhooks
+#[derive(Debug, Clone, Default)]
+pub struct Notifier {
+    config: NotificationsConfig,
+}
+
+impl Notifier {
+    pub fn new(config: NotificationsConfig) -> Self {
+        Self { config }
+    }
+
+    /// Whether any webhook is configured
+    pub fn is_enabled(&self) -> bool {
+        !self.config.webhooks.is_empty()
+    }
+
+    /// Post a summary to every webhook subscribed to its outcome
+    ///
+    /// Webhooks are named by their position, as their URLs may carry tokens.
+    pub async fn notify(&self, summary: &RunSummary) {
+        for (index, webhook) in self.config.webhooks.iter().enumerate() {
+            if !webhook.on.contains(&summary.status) {
+                continue;
+            }
+            match self.send(webhook, summary).await {
+                Ok(()) => info!(
+                    "Notified webhook {} of {} {}",
+                    index + 1,
+                    summary.command,
+                    summary.status.as_str()
+                ),
+                Err(e) => warn!("Failed to notify webhook {}: {}", index + 1, e),
+            }
+        }
+    }
+
+    async fn send(&self, webhook: &WebhookConfig, summary: &RunSummary) -> Result<()> {
+        let payload = match &webhook.payload {
+            Some(template) => render_payload(template, summary)?,
+            None => serde_json::to_value(summary)?,
+        };
+        let mut headers = Vec::new();
+        for (name, value) in &webhook.headers {
+            headers.push((name.clone(), value.resolve()?));
+        }
+        self.post(&webhook.url.resolve()?, &headers, &payload).await
+    }
+
+    #[cfg(feature = "http")]
+    async fn post(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+        payload: &serde_json::Value,
+    ) -> Result<()> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(self.config.timeout_seconds))
+            .build()?;
+        let mut request = client.post(url).json(payload);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        // Errors leave out the URL, which may carry a token
+        request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| anyhow!("{}", e.without_url()))?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "http"))]
+    async fn post(
+        &self,
+        _url: &str,
+        _headers: &[(String, String)],
+        _payload: &serde_json::Value,
+    ) -> Result<()> {
+        Err(anyhow!(
+            "Cannot post notifications: gensonnet was built without the `http` feature"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonnet_lockfile::FileChecksum;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_run_summary() {
+        let checksum = |sha: &str| FileChecksum::new(sha.to_string(), 1, chrono::Utc::now());
+        let mut before = Lockfile::new();
+        before.add_file(PathBuf::from("a.libsonnet"), checksum("1"));
+        before.add_file(PathBuf::from("b.libsonnet"), checksum("1"));
+        before.add_file(PathBuf::from("c.libsonnet"), checksum("1"));
+        let mut after = before.clone();
+        after.add_file(PathBuf::from("b.libsonnet"), checksum("2"));
+        after.files.remove(&PathBuf::from("c.libsonnet"));
+        after.add_file(PathBuf::from("d.libsonnet"), checksum("1"));
+
+        let summary = RunSummary::new(
+            "generate",
+            &Err(anyhow!("hook \"gate\" failed")),
+            &before,
+            &after,
+            10,
+        );
+        assert_eq!(summary.status, NotificationEvent::Failure);
+        assert_eq!(
+            (
+                summary.files_added,
+                summary.files_changed,
+                summary.files_removed
+            ),
+            (1, 1, 1)
+        );
+
+        let payload = render_payload(
+            r#"{"text": "gensonnet {command}: {status} ({error}), {files_changed} changed, failed: {failed_sources}", "count": {error_count}}"#,
+            &summary,
+        )
+        .unwrap();
+        assert_eq!(
+            payload["text"],
+            "gensonnet generate: failure (hook \"gate\" failed), 1 changed, failed: "
+        );
+        assert_eq!(payload["count"], 0);
+
+        // Placeholders in filled-in values, and unknown ones, are left as written
+        let quoting = RunSummary {
+            error: Some("expected {command}".to_string()),
+            ..summary.clone()
+        };
+        let payload =
+            render_payload(r#"{"text": "{error} {unknown} {status}"}"#, &quoting).unwrap();
+        assert_eq!(payload["text"], "expected {command} {unknown} failure");
+
+        let err = render_payload(r#"{"text": {status}}"#, &summary).unwrap_err();
+        assert!(err.to_string().contains("not valid JSON"));
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_notify_posts_payload() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 4096];
+            while !String::from_utf8_lossy(&request).contains("}") {
+                let read = stream.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let config: NotificationsConfig = serde_yaml::from_str(&format!(
+            "webhooks:\n  - url: {url}\n    on: [failure]\n    payload: '{{\"text\": \"{{status}}\"}}'\n    headers: {{X-Token: abc}}\n  - url: {url}\n    on: [success]\n"
+        ))
+        .unwrap();
+        config.validate().unwrap();
+        Notifier::new(config).notify(&RunSummary::example()).await;
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /hook"), "{request}");
+        assert!(request.to_lowercase().contains("x-token: abc"), "{request}");
+        assert!(request.ends_with(r#"{"text":"failure"}"#), "{request}");
+    }
+}
@@ -0,0 +1,9 @@
+//! Go AST plugin factory
+
+use super::plugin::GoAstPlugin;
+
+gensonnet_plugin::plugin_factory!(
+    /// Go AST plugin factory
+    pub GoAstPluginFactory => GoAstPlugin, ["go", "golang"],
+    config_schema: GoAstPlugin::config_schema()
+);
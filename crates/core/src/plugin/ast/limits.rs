@@ -0,0 +1,112 @@
+//! Parse diagnostics for tree-sitter based parsers
+//!
+//! Tree-sitter recovers from syntax errors by wrapping what it could not
+//! parse in ERROR nodes, or inserting MISSING ones. Those regions are
+//! collected so a file that only partly parses yields its valid declarations
+//! and a warning naming the rest, and files over the configured limits are
+//! skipped with a warning instead of stalling the source.
+
+use std::fmt;
+use tree_sitter::Node;
+
+/// Regions listed in a warning before the rest are summarized
+const MAX_LISTED_REGIONS: usize = 10;
+
+/// A span of a source file the parser could not make sense of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnparsedRegion {
+    /// 1-based line and column of the first character
+    pub start: (usize, usize),
+
+    /// 1-based line and column past the last character
+    pub end: (usize, usize),
+}
+
+impl fmt::Display for UnparsedRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}-{}:{}",
+            self.start.0, self.start.1, self.end.0, self.end.1
+        )
+    }
+}
+
+/// What happened while parsing the last file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseDiagnostics {
+    /// Why the file was not parsed at all
+    pub skipped: Option<String>,
+
+    /// Number of ERROR and MISSING nodes in the tree, nested ones included
+    pub error_nodes: usize,
+
+    /// Outermost regions holding errors
+    pub unparsed_regions: Vec<UnparsedRegion>,
+}
+
+impl ParseDiagnostics {
+    /// Collect the error nodes below a tree's root
+    pub fn from_tree(root: Node) -> Self {
+        let mut diagnostics = Self::default();
+        if root.has_error() {
+            diagnostics.collect(root, false);
+        }
+        diagnostics
+    }
+
+    fn collect(&mut self, node: Node, inside_error: bool) {
+        let is_error = node.is_error() || node.is_missing();
+        if is_error {
+            self.error_nodes += 1;
+            if !inside_error {
+                let (start, end) = (node.start_position(), node.end_position());
+                self.unparsed_regions.push(UnparsedRegion {
+                    start: (start.row + 1, start.column + 1),
+                    end: (end.row + 1, end.column + 1),
+                });
+            }
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            if child.has_error() || child.is_missing() {
+                self.collect(child, inside_error || is_error);
+            }
+        }
+    }
+
+    /// Whether the file was parsed completely
+    pub fn is_clean(&self) -> bool {
+        self.skipped.is_none() && self.error_nodes == 0
+    }
+
+    /// Warnings describing the diagnostics of a file
+    pub fn warnings(&self, file: &str) -> Vec<String> {
+        if let Some(reason) = &self.skipped {
+            return vec![format!("Skipped {}: {}", file, reason)];
+        }
+        if self.error_nodes == 0 {
+            return Vec::new();
+        }
+
+        let mut regions: Vec<String> = self
+            .unparsed_regions
+            .iter()
+            .take(MAX_LISTED_REGIONS)
+            .map(ToString::to_string)
+            .collect();
+        if self.unparsed_regions.len() > MAX_LISTED_REGIONS {
+            regions.push(format!(
+                "and {} more",
+                self.unparsed_regions.len() - MAX_LISTED_REGIONS
+            ));
+        }
+        vec![format!(
+            "{} has {} syntax errors; unparsed regions: {}",
+            file,
+            self.error_nodes,
+            regions.join(", ")
+        )]
+    }
+}
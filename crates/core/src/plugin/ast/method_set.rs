@@ -0,0 +1,216 @@
+//! Method set analysis
+//!
+//! Computes the method sets of the types declared in a Go package and which
+//! of them satisfy which interfaces. Satisfaction follows Go's rules: a value
+//! type's method set has the value receiver methods, a pointer type's method
+//! set also has the pointer receiver methods. Methods are compared by name
+//! and signature; interfaces embedding an interface declared outside the
+//! package are left out because their method sets are unknown.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::types::*;
+
+/// Receiver form through which a type satisfies an interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReceiverKind {
+    /// `T` itself implements the interface
+    Value,
+
+    /// Only `*T` implements the interface
+    Pointer,
+}
+
+impl ReceiverKind {
+    /// Name used in schema metadata
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReceiverKind::Value => "value",
+            ReceiverKind::Pointer => "pointer",
+        }
+    }
+}
+
+/// Method sets and interface satisfaction of the types in a package
+#[derive(Debug, Clone, Default)]
+pub struct MethodSets {
+    /// Signatures of methods with a value receiver, by type
+    value_methods: BTreeMap<String, BTreeSet<String>>,
+
+    /// Signatures of methods with a pointer receiver, by type
+    pointer_methods: BTreeMap<String, BTreeSet<String>>,
+
+    /// Complete method sets of interfaces, by interface
+    interfaces: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl MethodSets {
+    /// Analyze the method declarations and interfaces among parsed nodes
+    pub fn from_nodes(nodes: &[GoAstNode]) -> Self {
+        let mut sets = Self::default();
+        let mut declared = BTreeMap::new();
+
+        for node in nodes {
+            match node {
+                GoAstNode::Method(method) => {
+                    let (type_name, methods) = match &method.receiver {
+                        Some(TypeDefinition::Basic(name)) => (name, &mut sets.value_methods),
+                        Some(TypeDefinition::Pointer(inner)) => match inner.as_ref() {
+                            TypeDefinition::Basic(name) => (name, &mut sets.pointer_methods),
+                            _ => continue,
+                        },
+                        _ => continue,
+                    };
+                    methods
+                        .entry(receiver_base_name(type_name))
+                        .or_default()
+                        .insert(method_signature(method));
+                }
+                GoAstNode::TypeDecl(TypeDeclNode {
+                    name,
+                    type_def: TypeDefinition::Interface(interface),
+                    ..
+                }) => {
+                    declared.insert(name.clone(), interface);
+                }
+                _ => {}
+            }
+        }
+
+        for name in declared.keys() {
+            if let Some(methods) = interface_methods(name, &declared, &mut BTreeSet::new()) {
+                sets.interfaces.insert(name.clone(), methods);
+            }
+        }
+
+        sets
+    }
+
+    /// Method set of `*T`, which includes the methods of `T`
+    pub fn methods(&self, type_name: &str) -> BTreeSet<String> {
+        let mut methods = self
+            .value_methods
+            .get(type_name)
+            .cloned()
+            .unwrap_or_default();
+        methods.extend(
+            self.pointer_methods
+                .get(type_name)
+                .into_iter()
+                .flatten()
+                .cloned(),
+        );
+        methods
+    }
+
+    /// Complete method set of an interface, if it could be resolved
+    pub fn interface_methods(&self, interface: &str) -> Option<&BTreeSet<String>> {
+        self.interfaces.get(interface)
+    }
+
+    /// Interfaces a type satisfies, with the receiver form needed
+    pub fn implements(&self, type_name: &str) -> BTreeMap<String, ReceiverKind> {
+        let value_methods = self
+            .value_methods
+            .get(type_name)
+            .cloned()
+            .unwrap_or_default();
+        let pointer_methods = self.methods(type_name);
+
+        self.interfaces
+            .iter()
+            // Every type satisfies an empty interface; that says nothing
+            .filter(|(name, methods)| !methods.is_empty() && name.as_str() != type_name)
+            .filter_map(|(name, methods)| {
+                if methods.is_subset(&value_methods) {
+                    Some((name.clone(), ReceiverKind::Value))
+                } else if methods.is_subset(&pointer_methods) {
+                    Some((name.clone(), ReceiverKind::Pointer))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Types implementing an interface, with the receiver form needed
+    pub fn implementations(&self, interface: &str) -> BTreeMap<String, ReceiverKind> {
+        let types: BTreeSet<&String> = self
+            .value_methods
+            .keys()
+            .chain(self.pointer_methods.keys())
+            .collect();
+
+        types
+            .into_iter()
+            .filter_map(|type_name| {
+                self.implements(type_name)
+                    .remove(interface)
+                    .map(|receiver| (type_name.clone(), receiver))
+            })
+            .collect()
+    }
+}
+
+/// Method set of a declared interface including embedded interfaces, or
+/// `None` if an embedded interface is not declared in the package
+fn interface_methods(
+    name: &str,
+    declared: &BTreeMap<String, &InterfaceTypeNode>,
+    visiting: &mut BTreeSet<String>,
+) -> Option<BTreeSet<String>> {
+    let interface = declared.get(name)?;
+    if !visiting.insert(name.to_string()) {
+        return None;
+    }
+
+    let mut methods: BTreeSet<String> = interface.methods.iter().map(method_signature).collect();
+    for embedded in &interface.embedded {
+        methods.extend(interface_methods(embedded, declared, visiting)?);
+    }
+
+    visiting.remove(name);
+    Some(methods)
+}
+
+/// Signature of a method, e.g. `Get(string) (string, error)`
+pub fn method_signature(method: &MethodNode) -> String {
+    let params = type_list(&method.params).join(", ");
+    let results = type_list(&method.results);
+
+    match results.len() {
+        0 => format!("{}({})", method.name, params),
+        1 => format!("{}({}) {}", method.name, params, results[0]),
+        _ => format!("{}({}) ({})", method.name, params, results.join(", ")),
+    }
+}
+
+/// Types of a parameter list, repeated for grouped names like `a, b string`
+fn type_list(fields: &[FieldNode]) -> Vec<String> {
+    fields
+        .iter()
+        .flat_map(|field| {
+            std::iter::repeat_n(type_name(&field.field_type), field.names.len().max(1))
+        })
+        .collect()
+}
+
+/// Go spelling of a type
+fn type_name(type_def: &TypeDefinition) -> String {
+    match type_def {
+        TypeDefinition::Basic(name) | TypeDefinition::Alias(name) => name.clone(),
+        TypeDefinition::Pointer(inner) => format!("*{}", type_name(inner)),
+        TypeDefinition::Slice(inner) => format!("[]{}", type_name(inner)),
+        TypeDefinition::Array(inner) => format!("[...]{}", type_name(inner)),
+        TypeDefinition::Map(key, value) => {
+            format!("map[{}]{}", type_name(key), type_name(value))
+        }
+        TypeDefinition::Struct(_) => "struct{...}".to_string(),
+        TypeDefinition::Interface(_) => "interface{...}".to_string(),
+    }
+}
+
+/// Name of a receiver type without type parameters, e.g. `List` for `List[T]`
+fn receiver_base_name(type_name: &str) -> String {
+    type_name.split('[').next().unwrap_or(type_name).to_string()
+}
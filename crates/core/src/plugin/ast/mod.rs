@@ -2,6 +2,8 @@
 //! See: https://tree-sitter.github.io/tree-sitter/
 
 pub mod factory;
+pub mod limits;
+pub mod method_set;
 pub mod parser;
 pub mod plugin;
 pub mod types;
@@ -11,6 +13,8 @@ mod tests;
 
 // Re-export main types for convenience
 pub use factory::GoAstPluginFactory;
+pub use limits::{ParseDiagnostics, UnparsedRegion};
+pub use method_set::{MethodSets, ReceiverKind};
 pub use parser::GoAstParser;
 pub use plugin::GoAstPlugin;
 pub use types::*;
@@ -5,8 +5,12 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use tree_sitter::{Language, Node, Parser};
 
+use super::limits::ParseDiagnostics;
+use super::method_set::MethodSets;
 use super::types::*;
+use crate::config::ParseLimits;
 use crate::plugin::*;
+use crate::utils::format_bytes;
 
 /// Go AST parser using tree-sitter
 #[allow(dead_code)]
@@ -25,46 +29,78 @@ pub struct GoAstParser {
 
     /// Package information
     package_info: Option<PackageNode>,
-}
 
-impl Default for GoAstParser {
-    fn default() -> Self {
-        Self::new()
-    }
+    /// Size and time limits for a single file
+    limits: ParseLimits,
+
+    /// Diagnostics of the last parsed file
+    diagnostics: ParseDiagnostics,
 }
 
 impl GoAstParser {
     /// Create a new Go AST parser
-    pub fn new() -> Self {
+    ///
+    /// Fails when the bundled grammar does not match the tree-sitter runtime.
+    pub fn new() -> Result<Self> {
         let mut parser = Parser::new();
         let language = tree_sitter_go::language();
-        parser.set_language(language).unwrap();
+        parser
+            .set_language(language)
+            .map_err(|e| crate::JsonnetGenError::ParserSetup(format!("Go grammar: {e}")))?;
 
-        Self {
+        Ok(Self {
             parser,
             language,
             nodes: Vec::new(),
             type_defs: HashMap::new(),
             package_info: None,
-        }
+            limits: ParseLimits::default(),
+            diagnostics: ParseDiagnostics::default(),
+        })
+    }
+
+    /// Apply size and time limits to parsed files
+    pub fn with_limits(mut self, limits: ParseLimits) -> Self {
+        self.limits = limits;
+        self
     }
 
     /// Parse a Go source file
     pub async fn parse_file(&mut self, file_path: &Path) -> Result<()> {
+        // Check the size first so oversized files are never read
+        let size = tokio::fs::metadata(file_path).await?.len();
+        if size > self.limits.max_file_bytes {
+            self.clear();
+            self.skip_oversized(size);
+            return Ok(());
+        }
+
         let content = tokio::fs::read_to_string(file_path).await?;
         self.parse_content(&content, file_path).await
     }
 
     /// Parse Go source content using tree-sitter
     pub async fn parse_content(&mut self, content: &str, file_path: &Path) -> Result<()> {
-        // Clear previous state
-        self.nodes.clear();
-        self.type_defs.clear();
-        self.package_info = None;
+        self.clear();
 
-        // Parse with tree-sitter
-        let tree = self.parser.parse(content, None).unwrap();
+        if content.len() as u64 > self.limits.max_file_bytes {
+            self.skip_oversized(content.len() as u64);
+            return Ok(());
+        }
+
+        // Parse with tree-sitter; no tree means the timeout ran out
+        self.parser
+            .set_timeout_micros(self.limits.parse_timeout_ms.saturating_mul(1000));
+        let Some(tree) = self.parser.parse(content, None) else {
+            self.parser.reset();
+            self.diagnostics.skipped = Some(format!(
+                "parsing took longer than {}ms",
+                self.limits.parse_timeout_ms
+            ));
+            return Ok(());
+        };
         let root_node = tree.root_node();
+        self.diagnostics = ParseDiagnostics::from_tree(root_node);
 
         // Extract package information
         self.extract_package_info(&root_node, file_path, content)?;
@@ -84,6 +120,22 @@ impl GoAstParser {
         Ok(())
     }
 
+    /// Clear the state of the previous file
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.type_defs.clear();
+        self.package_info = None;
+        self.diagnostics = ParseDiagnostics::default();
+    }
+
+    fn skip_oversized(&mut self, size: u64) {
+        self.diagnostics.skipped = Some(format!(
+            "{} exceeds the {} parse limit",
+            format_bytes(size),
+            format_bytes(self.limits.max_file_bytes)
+        ));
+    }
+
     /// Extract package information from AST
     fn extract_package_info(
         &mut self,
@@ -207,7 +259,7 @@ impl GoAstParser {
             "pointer_type" => self.parse_pointer_type(type_node, content),
             "map_type" => self.parse_map_type(type_node, content),
             "slice_type" => self.parse_slice_type(type_node, content),
-            "type_identifier" => Ok(TypeDefinition::Basic(
+            "type_identifier" | "qualified_type" => Ok(TypeDefinition::Basic(
                 self.get_node_text(*type_node, content),
             )),
             _ => Ok(TypeDefinition::Basic("unknown".to_string())),
@@ -251,18 +303,25 @@ impl GoAstParser {
     /// Parse interface type
     fn parse_interface_type(&self, interface_node: &Node, content: &str) -> Result<TypeDefinition> {
         let mut methods = Vec::new();
-        let embedded = Vec::new();
+        let mut embedded = Vec::new();
         let mut cursor = interface_node.walk();
 
-        // Find method list
         for child in interface_node.children(&mut cursor) {
-            if child.kind() == "method_spec_list" {
-                for method_spec in child.children(&mut child.walk()) {
-                    if method_spec.kind() == "method_spec" {
-                        let method = self.parse_method_spec(&method_spec, content)?;
-                        methods.push(method);
+            match child.kind() {
+                "method_spec" => methods.push(self.parse_method_spec(&child, content)?),
+                // Older grammars wrap the methods in a list
+                "method_spec_list" => {
+                    for method_spec in child.children(&mut child.walk()) {
+                        if method_spec.kind() == "method_spec" {
+                            methods.push(self.parse_method_spec(&method_spec, content)?);
+                        }
                     }
                 }
+                // Embedded interfaces
+                "constraint_elem" | "type_identifier" | "qualified_type" => {
+                    embedded.push(self.get_node_text(child, content).trim().to_string());
+                }
+                _ => {}
             }
         }
 
@@ -389,25 +448,15 @@ impl GoAstParser {
 
     /// Parse method specification
     fn parse_method_spec(&self, method_spec: &Node, content: &str) -> Result<MethodNode> {
-        let mut name = String::new();
-        let mut params = Vec::new();
-        let mut results = Vec::new();
-        let mut cursor = method_spec.walk();
-
-        for child in method_spec.children(&mut cursor) {
-            match child.kind() {
-                "field_identifier" => {
-                    name = self.get_node_text(child, content);
-                }
-                "parameter_list" => {
-                    params = self.parse_parameter_list(&child, content)?;
-                }
-                "result" => {
-                    results = self.parse_result_list(&child, content)?;
-                }
-                _ => {}
-            }
-        }
+        let name = method_spec
+            .child_by_field_name("name")
+            .map(|name| self.get_node_text(name, content))
+            .unwrap_or_default();
+        let params = match method_spec.child_by_field_name("parameters") {
+            Some(params) => self.parse_parameter_list(&params, content)?,
+            None => Vec::new(),
+        };
+        let results = self.parse_result(method_spec, content)?;
 
         Ok(MethodNode {
             name,
@@ -437,21 +486,19 @@ impl GoAstParser {
     /// Parse parameter declaration
     fn parse_parameter_declaration(&self, param_decl: &Node, content: &str) -> Result<FieldNode> {
         let mut names = Vec::new();
-        let mut param_type = TypeDefinition::Basic("unknown".to_string());
         let mut cursor = param_decl.walk();
 
         for child in param_decl.children(&mut cursor) {
-            match child.kind() {
-                "identifier" => {
-                    names.push(self.get_node_text(child, content));
-                }
-                "type" => {
-                    param_type = self.parse_type_definition(&child, content)?;
-                }
-                _ => {}
+            if child.kind() == "identifier" {
+                names.push(self.get_node_text(child, content));
             }
         }
 
+        let param_type = match param_decl.child_by_field_name("type") {
+            Some(type_node) => self.parse_type_definition(&type_node, content)?,
+            None => TypeDefinition::Basic("unknown".to_string()),
+        };
+
         Ok(FieldNode {
             names,
             field_type: param_type,
@@ -461,19 +508,23 @@ impl GoAstParser {
         })
     }
 
-    /// Parse result list
-    fn parse_result_list(&self, result_list: &Node, content: &str) -> Result<Vec<FieldNode>> {
-        let mut results = Vec::new();
-        let mut cursor = result_list.walk();
-
-        for child in result_list.children(&mut cursor) {
-            if child.kind() == "parameter_declaration" {
-                let result = self.parse_parameter_declaration(&child, content)?;
-                results.push(result);
+    /// Parse the results of a function, method or method spec
+    ///
+    /// A single unnamed result is a bare type rather than a parameter list.
+    fn parse_result(&self, func_node: &Node, content: &str) -> Result<Vec<FieldNode>> {
+        match func_node.child_by_field_name("result") {
+            Some(result) if result.kind() == "parameter_list" => {
+                self.parse_parameter_list(&result, content)
             }
+            Some(result) => Ok(vec![FieldNode {
+                names: Vec::new(),
+                field_type: self.parse_type_definition(&result, content)?,
+                tags: None,
+                docs: Vec::new(),
+                position: self.node_to_position(result, &PathBuf::new()),
+            }]),
+            None => Ok(Vec::new()),
         }
-
-        Ok(results)
     }
 
     /// Extract function declarations from AST
@@ -506,7 +557,6 @@ impl GoAstParser {
         let mut name = String::new();
         let mut receiver = None;
         let mut params = Vec::new();
-        let mut results = Vec::new();
         let mut cursor = func_decl_node.walk();
 
         for child in func_decl_node.children(&mut cursor) {
@@ -537,12 +587,10 @@ impl GoAstParser {
                         }
                     }
                 }
-                "result" => {
-                    results = self.parse_result_list(&child, content)?;
-                }
                 _ => {}
             }
         }
+        let results = self.parse_result(func_decl_node, content)?;
 
         // Only create a method node if there's a receiver
         if receiver.is_some() {
@@ -568,46 +616,25 @@ impl GoAstParser {
         file_path: &Path,
         content: &str,
     ) -> Result<()> {
-        let mut name = String::new();
-        let mut receiver = None;
-        let mut params = Vec::new();
-        let mut results = Vec::new();
-        let mut cursor = method_decl_node.walk();
-
-        for child in method_decl_node.children(&mut cursor) {
-            match child.kind() {
-                "field_identifier" => {
-                    name = self.get_node_text(child, content);
-                }
-                "parameter_list" => {
-                    // Parse regular parameters (not receiver)
-                    params = self.parse_parameter_list(&child, content)?;
-                }
-                "result" => {
-                    results = self.parse_result_list(&child, content)?;
-                }
-                _ => {}
-            }
-        }
+        let name = method_decl_node
+            .child_by_field_name("name")
+            .map(|name| self.get_node_text(name, content))
+            .unwrap_or_default();
+        let params = match method_decl_node.child_by_field_name("parameters") {
+            Some(params) => self.parse_parameter_list(&params, content)?,
+            None => Vec::new(),
+        };
+        let results = self.parse_result(method_decl_node, content)?;
 
-        // For method declarations, we need to parse the receiver from the parent context
-        // The receiver is typically the first parameter in the method declaration
-        // Let me check if there's a receiver parameter list
-        let mut receiver_cursor = method_decl_node.walk();
-        for child in method_decl_node.children(&mut receiver_cursor) {
-            if child.kind() == "parameter_list" {
-                // Check if this parameter list contains a receiver
-                let mut param_cursor = child.walk();
-                for param in child.children(&mut param_cursor) {
-                    if param.kind() == "parameter_declaration" {
-                        let receiver_info = self.parse_receiver_parameter(&param, content)?;
-                        if receiver_info.is_some() {
-                            receiver = receiver_info;
-                            break;
-                        }
-                    }
+        // The receiver is the single parameter of the receiver list
+        let mut receiver = None;
+        if let Some(receiver_list) = method_decl_node.child_by_field_name("receiver") {
+            let mut param_cursor = receiver_list.walk();
+            for param in receiver_list.children(&mut param_cursor) {
+                if param.kind() == "parameter_declaration" {
+                    receiver = self.parse_receiver_parameter(&param, content)?;
+                    break;
                 }
-                break;
             }
         }
 
@@ -660,10 +687,8 @@ impl GoAstParser {
         // The receiver is always a single parameter with a type
         // We identify it by checking if it has a valid type (not "unknown")
         match &param_type {
-            TypeDefinition::Basic(type_name) => {
-                if !type_name.is_empty() && type_name != "unknown" {
-                    return Ok(Some(param_type));
-                }
+            TypeDefinition::Basic(type_name) if !type_name.is_empty() && type_name != "unknown" => {
+                return Ok(Some(param_type));
             }
             TypeDefinition::Pointer(inner_type) => {
                 if let TypeDefinition::Basic(type_name) = inner_type.as_ref() {
@@ -846,18 +871,33 @@ impl GoAstParser {
         &self.type_defs
     }
 
+    /// Diagnostics of the last parsed file
+    pub fn diagnostics(&self) -> &ParseDiagnostics {
+        &self.diagnostics
+    }
+
     /// Get package information
     pub fn get_package_info(&self) -> Option<&PackageNode> {
         self.package_info.as_ref()
     }
 
+    /// Method sets and interface satisfaction of the parsed types
+    pub fn method_sets(&self) -> MethodSets {
+        MethodSets::from_nodes(&self.nodes)
+    }
+
     /// Extract schemas from AST
     pub fn extract_schemas(&self) -> Vec<ExtractedSchema> {
+        self.extract_schemas_with(&self.method_sets())
+    }
+
+    /// Extract schemas from AST, with the method sets of the whole package
+    pub fn extract_schemas_with(&self, method_sets: &MethodSets) -> Vec<ExtractedSchema> {
         let mut schemas = Vec::new();
 
         for node in &self.nodes {
             if let GoAstNode::TypeDecl(type_decl) = node {
-                let schema = self.type_decl_to_schema(type_decl);
+                let schema = self.type_decl_to_schema(type_decl, method_sets);
                 schemas.push(schema);
             }
         }
@@ -866,7 +906,11 @@ impl GoAstParser {
     }
 
     /// Convert type declaration to schema
-    fn type_decl_to_schema(&self, type_decl: &TypeDeclNode) -> ExtractedSchema {
+    fn type_decl_to_schema(
+        &self,
+        type_decl: &TypeDeclNode,
+        method_sets: &MethodSets,
+    ) -> ExtractedSchema {
         let mut metadata = HashMap::new();
         metadata.insert(
             "package".to_string(),
@@ -888,19 +932,71 @@ impl GoAstParser {
             ),
         );
 
+        // Interfaces list their implementations, other types the
+        // interfaces they implement
+        let (methods, satisfaction_key, satisfaction) = match &type_decl.type_def {
+            TypeDefinition::Interface(_) => (
+                method_sets
+                    .interface_methods(&type_decl.name)
+                    .cloned()
+                    .unwrap_or_default(),
+                "implementations",
+                method_sets.implementations(&type_decl.name),
+            ),
+            _ => (
+                method_sets.methods(&type_decl.name),
+                "implements",
+                method_sets.implements(&type_decl.name),
+            ),
+        };
+        if !methods.is_empty() {
+            metadata.insert(
+                "methods".to_string(),
+                serde_yaml::Value::Sequence(
+                    methods.into_iter().map(serde_yaml::Value::String).collect(),
+                ),
+            );
+        }
+        if !satisfaction.is_empty() {
+            metadata.insert(
+                satisfaction_key.to_string(),
+                serde_yaml::Value::Mapping(
+                    satisfaction
+                        .into_iter()
+                        .map(|(name, receiver)| (name.into(), receiver.as_str().into()))
+                        .collect(),
+                ),
+            );
+        }
+
         let schema_content = match &type_decl.type_def {
             TypeDefinition::Struct(struct_type) => self.struct_to_schema(struct_type),
             TypeDefinition::Interface(interface_type) => self.interface_to_schema(interface_type),
             _ => serde_yaml::Value::Null,
         };
 
-        ExtractedSchema {
+        let mut schema = ExtractedSchema {
             name: type_decl.name.clone(),
             schema_type: "go_struct".to_string(),
             content: schema_content,
             source_file: type_decl.position.file.clone(),
             metadata,
+        };
+        if let TypeDefinition::Struct(struct_type) = &type_decl.type_def {
+            schema.set_field_lines(
+                &struct_type
+                    .fields
+                    .iter()
+                    .flat_map(|field| {
+                        field
+                            .names
+                            .iter()
+                            .map(|name| (name.clone(), field.position.line))
+                    })
+                    .collect(),
+            );
         }
+        schema
     }
 
     /// Convert struct type to schema
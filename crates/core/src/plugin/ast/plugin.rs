@@ -0,0 +1,280 @@
+//! Go AST plugin implementation
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::method_set::MethodSets;
+use super::parser::GoAstParser;
+use crate::config::ParseLimits;
+use crate::plugin::*;
+
+/// Method sets of the packages seen during a run, by directory and package name
+#[derive(Default)]
+struct PackageMethodSets(HashMap<(PathBuf, String), Arc<MethodSets>>);
+
+/// Go AST plugin
+pub struct GoAstPlugin {
+    /// Plugin configuration
+    config: PluginConfig,
+
+    /// Idle parsers, reused across files; each file is parsed by one parser
+    parsers: Arc<Mutex<Vec<GoAstParser>>>,
+}
+
+impl GoAstPlugin {
+    /// Create a new Go AST plugin
+    pub fn new(config: PluginConfig) -> Self {
+        Self {
+            config,
+            parsers: Arc::default(),
+        }
+    }
+
+    /// Take an idle parser, or create one
+    fn take_parser(&self) -> Result<GoAstParser> {
+        match self.parsers.lock().unwrap().pop() {
+            Some(parser) => Ok(parser),
+            None => GoAstParser::new(),
+        }
+    }
+
+    /// JSON Schema of the plugin configuration, the parse limits
+    pub fn config_schema() -> serde_yaml::Value {
+        serde_yaml::from_str(
+            r#"
+type: object
+additionalProperties: false
+properties:
+  max_file_bytes: {type: integer, minimum: 1}
+  parse_timeout_ms: {type: integer, minimum: 1}
+"#,
+        )
+        .expect("Go AST config schema is valid")
+    }
+}
+
+#[async_trait]
+impl Plugin for GoAstPlugin {
+    fn metadata(&self) -> PluginMetadata {
+        PluginMetadata {
+            id: self.config.plugin_id.clone(),
+            name: "Go AST Plugin".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Plugin for processing Go source code and extracting type information"
+                .to_string(),
+            supported_types: vec!["go".to_string(), "golang".to_string()],
+            capabilities: vec![
+                PluginCapability::Parse,
+                PluginCapability::SchemaExtraction,
+                PluginCapability::AstProcessing,
+            ],
+            commands: Vec::new(),
+        }
+    }
+
+    async fn initialize(&self, _context: &PluginContext) -> Result<()> {
+        // Initialize the parser
+        Ok(())
+    }
+
+    async fn can_handle(&self, source_path: &Path) -> Result<bool> {
+        // Check if it's a Go source file
+        if let Some(extension) = source_path.extension() {
+            Ok(extension == "go")
+        } else {
+            Ok(false)
+        }
+    }
+
+    async fn score(&self, source_path: &Path) -> Result<u32> {
+        if !self.can_handle(source_path).await? {
+            return Ok(0);
+        }
+
+        // Go sources start with a package clause, after comments
+        let has_package = tokio::fs::read_to_string(source_path)
+            .await
+            .ok()
+            .is_some_and(|content| {
+                content
+                    .lines()
+                    .map(str::trim_start)
+                    .any(|line| line.starts_with("package "))
+            });
+        Ok(if has_package { 2 } else { 1 })
+    }
+
+    async fn process_source(
+        &self,
+        source_path: &Path,
+        context: &PluginContext,
+    ) -> Result<PluginResult> {
+        let start_time = std::time::Instant::now();
+        let limits = ParseLimits::from_plugin_config(&context.config.config)?;
+
+        // Parse the Go source file and extract its schemas, then return the
+        // parser to the idle ones
+        let mut parser = self.take_parser()?.with_limits(limits.clone());
+        let parsed = match parser.parse_file(source_path).await {
+            Ok(()) => self
+                .package_method_sets(&parser, source_path, &limits, context)
+                .await
+                .map(|method_sets| {
+                    let diagnostics = parser.diagnostics();
+                    let warnings = diagnostics.warnings(&source_path.display().to_string());
+                    let mut metrics = PluginMetrics::default();
+                    metrics.increment("syntax_error_nodes", diagnostics.error_nodes as u64);
+                    metrics.increment("files_skipped", u64::from(diagnostics.skipped.is_some()));
+                    (warnings, metrics, parser.extract_schemas_with(&method_sets))
+                }),
+            Err(e) => Err(e),
+        };
+        self.parsers.lock().unwrap().push(parser);
+        let (warnings, metrics, schemas) = parsed?;
+
+        let processing_time = start_time.elapsed();
+
+        let schemas_count = schemas.len();
+        Ok(PluginResult {
+            schemas,
+            generated_files: Vec::new(),
+            statistics: PluginStatistics {
+                processing_time_ms: processing_time.as_millis() as u64,
+                files_processed: 1,
+                schemas_extracted: schemas_count,
+                files_generated: 0,
+                metrics,
+            },
+            warnings,
+            errors: Vec::new(),
+        })
+    }
+
+    async fn generate_code(
+        &self,
+        schemas: &[ExtractedSchema],
+        context: &PluginContext,
+    ) -> Result<Vec<PathBuf>> {
+        let mut generated_files = Vec::new();
+
+        for schema in schemas {
+            let output_file = context
+                .output_dir
+                .join(format!("{}.libsonnet", schema.name.to_lowercase()));
+
+            // Generate Jsonnet code from the schema
+            let jsonnet_code = self.generate_jsonnet_code(schema)?;
+            tokio::fs::write(&output_file, jsonnet_code).await?;
+
+            generated_files.push(output_file);
+        }
+
+        Ok(generated_files)
+    }
+
+    async fn cleanup(&self, _context: &PluginContext) -> Result<()> {
+        self.parsers.lock().unwrap().clear();
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn Plugin> {
+        Box::new(GoAstPlugin {
+            config: self.config.clone(),
+            parsers: Arc::clone(&self.parsers),
+        })
+    }
+}
+
+impl GoAstPlugin {
+    /// Method sets of the package a parsed file belongs to
+    ///
+    /// Methods and interfaces may be declared in any file of a Go package, so
+    /// every `.go` file of the directory declaring the same package counts.
+    /// They are parsed once per run; the result is kept in the plugin's
+    /// shared state for the package's other files.
+    async fn package_method_sets(
+        &self,
+        parser: &GoAstParser,
+        source_path: &Path,
+        limits: &ParseLimits,
+        context: &PluginContext,
+    ) -> Result<Arc<MethodSets>> {
+        let (Some(package), Some(dir)) = (parser.get_package_info(), source_path.parent()) else {
+            return Ok(Arc::new(parser.method_sets()));
+        };
+        let key = (dir.to_path_buf(), package.name.clone());
+        let state = context.state::<PackageMethodSets>();
+        if let Some(method_sets) = state.read().await.0.get(&key) {
+            return Ok(Arc::clone(method_sets));
+        }
+
+        let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "go"))
+            .collect();
+        files.sort();
+
+        let mut nodes = Vec::new();
+        let mut sibling = self.take_parser()?.with_limits(limits.clone());
+        for file in files {
+            if file == source_path {
+                nodes.extend(parser.get_nodes().iter().cloned());
+            } else if sibling.parse_file(&file).await.is_ok()
+                && sibling
+                    .get_package_info()
+                    .is_some_and(|sibling_package| sibling_package.name == package.name)
+            {
+                nodes.extend(sibling.get_nodes().iter().cloned());
+            }
+        }
+        self.parsers.lock().unwrap().push(sibling);
+
+        let method_sets = Arc::new(MethodSets::from_nodes(&nodes));
+        state.write().await.0.insert(key, Arc::clone(&method_sets));
+        Ok(method_sets)
+    }
+
+    /// Generate Jsonnet code from schema
+    fn generate_jsonnet_code(&self, schema: &ExtractedSchema) -> Result<String> {
+        let mut code = String::new();
+
+        code.push_str(&format!("// Generated from Go AST: {}\n", schema.name));
+        code.push_str(&format!("// Source: {}\n", schema.source_file.display()));
+        if let Some(implementations) = schema
+            .metadata
+            .get("implementations")
+            .and_then(|i| i.as_mapping())
+        {
+            let names: Vec<String> = implementations
+                .iter()
+                .filter_map(|(name, receiver)| {
+                    Some(format!("{} ({})", name.as_str()?, receiver.as_str()?))
+                })
+                .collect();
+            code.push_str(&format!("// Implementations: {}\n", names.join(", ")));
+        }
+        code.push('\n');
+
+        // Add imports
+        code.push_str("local k = import \"k.libsonnet\";\n");
+        code.push_str("local validate = import \"_validation.libsonnet\";\n\n");
+
+        // Generate the main function
+        code.push_str(&format!("// Create a new {} resource\n", schema.name));
+        code.push_str("function(metadata, spec={}) {\n");
+        code.push_str(&format!(
+            "  apiVersion: \"{}\",\n",
+            schema.name.to_lowercase()
+        ));
+        code.push_str(&format!("  kind: \"{}\",\n", schema.name));
+        code.push_str("  metadata: metadata,\n");
+        code.push_str("  spec: spec,\n");
+        code.push_str("}\n");
+
+        Ok(code)
+    }
+}
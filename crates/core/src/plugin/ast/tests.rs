@@ -2,11 +2,12 @@
 
 use super::*;
 use crate::plugin::{Plugin, PluginCapability, PluginConfig, PluginContext};
+use std::path::Path;
 use tempfile::TempDir;
 
 #[tokio::test]
 async fn test_go_ast_parser_basic() {
-    let mut parser = GoAstParser::new();
+    let mut parser = GoAstParser::new().unwrap();
 
     let test_content = r#"
 package main
@@ -34,7 +35,7 @@ type TestStruct struct {
 
 #[tokio::test]
 async fn test_go_ast_parser_complex() {
-    let mut parser = GoAstParser::new();
+    let mut parser = GoAstParser::new().unwrap();
 
     let test_content = r#"
 package main
@@ -147,7 +148,7 @@ type UserService interface {
 
 #[tokio::test]
 async fn test_go_ast_parser_with_comments() {
-    let mut parser = GoAstParser::new();
+    let mut parser = GoAstParser::new().unwrap();
 
     let test_content = r#"
 package main
@@ -242,4 +243,176 @@ type TestStruct struct {
     assert_eq!(result.schemas[0].name, "TestStruct");
     assert_eq!(result.statistics.files_processed, 1);
     assert_eq!(result.statistics.schemas_extracted, 1);
+    assert_eq!(result.statistics.metrics.counters["syntax_error_nodes"], 0);
+}
+
+#[tokio::test]
+async fn test_go_ast_interface_satisfaction() {
+    let mut parser = GoAstParser::new().unwrap();
+
+    let test_content = r#"
+package store
+
+// Backend stores values by key
+type Backend interface {
+    Get(key string) (string, error)
+    Put(key, value string) error
+}
+
+// ClosableBackend is a backend holding resources
+type ClosableBackend interface {
+    Backend
+    Close() error
+}
+
+type Memory struct {
+    Data map[string]string `json:"data"`
+}
+
+func (m *Memory) Get(key string) (string, error) { return m.Data[key], nil }
+func (m *Memory) Put(key, value string) error { return nil }
+
+type Disk struct {
+    Path string `json:"path"`
+}
+
+func (d Disk) Get(key string) (string, error) { return "", nil }
+func (d Disk) Put(key, value string) error { return nil }
+func (d Disk) Close() error { return nil }
+
+type ReadOnly struct{}
+
+func (r ReadOnly) Get(key string) (string, error) { return "", nil }
+"#;
+
+    parser
+        .parse_content(test_content, Path::new("store.go"))
+        .await
+        .unwrap();
+
+    let method_sets = parser.method_sets();
+    assert_eq!(
+        method_sets
+            .interface_methods("ClosableBackend")
+            .unwrap()
+            .len(),
+        3
+    );
+    assert_eq!(
+        method_sets.implementations("Backend"),
+        [
+            ("Disk".to_string(), ReceiverKind::Value),
+            ("Memory".to_string(), ReceiverKind::Pointer),
+        ]
+        .into()
+    );
+    assert!(method_sets.implements("ReadOnly").is_empty());
+
+    let schemas = parser.extract_schemas();
+    let schema = |name: &str| schemas.iter().find(|s| s.name == name).unwrap();
+
+    let backend = &schema("Backend").metadata;
+    assert_eq!(
+        backend["methods"],
+        serde_yaml::from_str::<serde_yaml::Value>(
+            "['Get(string) (string, error)', 'Put(string, string) error']"
+        )
+        .unwrap()
+    );
+    assert_eq!(backend["implementations"]["Memory"], "pointer");
+
+    let disk = &schema("Disk").metadata;
+    assert_eq!(disk["implements"]["Backend"], "value");
+    assert_eq!(disk["implements"]["ClosableBackend"], "value");
+    assert!(!schema("ReadOnly").metadata.contains_key("implements"));
+}
+
+#[tokio::test]
+async fn test_go_ast_method_sets_span_package_files() {
+    let config = PluginConfig {
+        plugin_id: "test-go-plugin".to_string(),
+        config: serde_yaml::Value::Null,
+        enabled_capabilities: vec![PluginCapability::Parse, PluginCapability::SchemaExtraction],
+    };
+    let plugin = GoAstPlugin::new(config.clone());
+    let temp_dir = TempDir::new().unwrap();
+    for (name, content) in [
+        (
+            "backend.go",
+            "package store\n\ntype Backend interface {\n    Get(key string) (string, error)\n}\n",
+        ),
+        (
+            "memory.go",
+            "package store\n\ntype Memory struct {\n    Size int `json:\"size\"`\n}\n",
+        ),
+        (
+            "memory_methods.go",
+            "package store\n\nfunc (m *Memory) Get(key string) (string, error) { return \"\", nil }\n",
+        ),
+        // Another package in the same directory does not count
+        (
+            "store_test.go",
+            "package store_test\n\nfunc (m Memory) Get(key string) (string, error) { return \"\", nil }\n",
+        ),
+    ] {
+        std::fs::write(temp_dir.path().join(name), content).unwrap();
+    }
+    let context = PluginContext::new(
+        temp_dir.path().to_path_buf(),
+        temp_dir.path().join("output"),
+        config,
+    );
+
+    let memory = plugin
+        .process_source(&temp_dir.path().join("memory.go"), &context)
+        .await
+        .unwrap();
+    assert_eq!(
+        memory.schemas[0].metadata["implements"]["Backend"],
+        "pointer"
+    );
+
+    let backend = plugin
+        .process_source(&temp_dir.path().join("backend.go"), &context)
+        .await
+        .unwrap();
+    assert_eq!(
+        backend.schemas[0].metadata["implementations"]["Memory"],
+        "pointer"
+    );
+}
+
+#[tokio::test]
+async fn test_go_ast_parse_limits() {
+    let test_content = r#"
+package main
+
+type Valid struct {
+    Name string `json:"name"`
+}
+
+func broken( {
+"#;
+    let temp_dir = TempDir::new().unwrap();
+    let test_file = temp_dir.path().join("broken.go");
+    tokio::fs::write(&test_file, test_content).await.unwrap();
+
+    let mut parser = GoAstParser::new().unwrap();
+    parser.parse_file(&test_file).await.unwrap();
+    assert_eq!(parser.extract_schemas().len(), 1);
+    let diagnostics = parser.diagnostics();
+    assert!(diagnostics.error_nodes > 0);
+    assert!(!diagnostics.unparsed_regions.is_empty());
+    let warnings = diagnostics.warnings("broken.go");
+    assert!(warnings[0].contains("unparsed regions: 8:1-"));
+
+    let mut parser = GoAstParser::new()
+        .unwrap()
+        .with_limits(crate::config::ParseLimits {
+            max_file_bytes: 16,
+            ..Default::default()
+        });
+    parser.parse_file(&test_file).await.unwrap();
+    assert!(parser.extract_schemas().is_empty());
+    assert!(parser.diagnostics().warnings("broken.go")[0].starts_with("Skipped broken.go"));
 }
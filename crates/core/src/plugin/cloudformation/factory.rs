@@ -0,0 +1,8 @@
+//! CloudFormation plugin factory
+
+use super::plugin::CloudFormationPlugin;
+
+gensonnet_plugin::plugin_factory!(
+    /// CloudFormation plugin factory
+    pub CloudFormationPluginFactory => CloudFormationPlugin, ["cloudformation", "json"]
+);
@@ -0,0 +1,12 @@
+//! CloudFormation resource specification processing
+
+pub mod factory;
+pub mod parser;
+pub mod plugin;
+
+#[cfg(test)]
+mod tests;
+
+// Re-export main types for convenience
+pub use factory::CloudFormationPluginFactory;
+pub use plugin::CloudFormationPlugin;
@@ -0,0 +1,257 @@
+//! CloudFormation resource specification parsing
+//!
+//! Two formats describe CloudFormation resource types. The resource
+//! specification (`CloudFormationResourceSpecification.json`, or one file per
+//! resource type) lists `ResourceTypes` and `PropertyTypes` with their
+//! properties. The registry schemas (`aws-s3-bucket.json`, as returned by
+//! `DescribeType`) give one resource type each as a JSON schema with a
+//! `typeName`. Both are read into the same [`ResourceType`].
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// A CloudFormation resource type and the property types it uses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceType {
+    /// Type name, e.g. `AWS::S3::Bucket`
+    pub name: String,
+
+    /// Documentation URL or description
+    pub documentation: Option<String>,
+
+    /// Properties set in the resource's `Properties`, by name
+    pub properties: BTreeMap<String, Property>,
+
+    /// Attributes available with `Fn::GetAtt`
+    pub attributes: Vec<String>,
+
+    /// Property types of the resource by short name, e.g. `VersioningConfiguration`
+    pub property_types: BTreeMap<String, BTreeMap<String, Property>>,
+}
+
+/// A property of a resource or property type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    /// Type of the property, e.g. `String`, `List<Tag>` or `VersioningConfiguration`
+    pub type_name: String,
+
+    /// Whether the property must be set
+    pub required: bool,
+
+    /// Documentation URL or description
+    pub documentation: Option<String>,
+}
+
+/// Whether a document is a resource specification or registry schema
+pub fn is_specification(doc: &Value) -> bool {
+    ["ResourceTypes", "ResourceType", "typeName"]
+        .iter()
+        .any(|key| doc.get(key).is_some())
+}
+
+/// Resource types described by a resource specification or registry schema
+pub fn parse(doc: &Value) -> Result<Vec<ResourceType>> {
+    if let Some(type_name) = doc.get("typeName").and_then(Value::as_str) {
+        return Ok(vec![parse_registry_schema(type_name, doc)]);
+    }
+
+    // Single-resource specification files use the singular key
+    let resource_types = doc
+        .get("ResourceTypes")
+        .or_else(|| doc.get("ResourceType"))
+        .and_then(Value::as_object)
+        .ok_or_else(|| anyhow!("Not a CloudFormation resource specification"))?;
+    let property_types = doc.get("PropertyTypes").and_then(Value::as_object);
+
+    Ok(resource_types
+        .iter()
+        .map(|(name, resource)| {
+            let properties = spec_properties(resource);
+
+            // Property types are named `<resource type>.<name>`; shared ones, like
+            // `Tag`, have no prefix
+            let prefix = format!("{name}.");
+            let property_types = property_types
+                .into_iter()
+                .flatten()
+                .filter_map(|(type_name, property_type)| {
+                    let short_name = match type_name.strip_prefix(&prefix) {
+                        Some(short_name) => short_name,
+                        None if !type_name.contains('.') => type_name.as_str(),
+                        None => return None,
+                    };
+                    Some((short_name.to_string(), spec_properties(property_type)))
+                })
+                .collect::<BTreeMap<_, _>>();
+            let mut resource_type = ResourceType {
+                name: name.clone(),
+                documentation: documentation(resource, "Documentation"),
+                properties,
+                attributes: resource
+                    .get("Attributes")
+                    .and_then(Value::as_object)
+                    .map(|attributes| attributes.keys().cloned().collect())
+                    .unwrap_or_default(),
+                property_types,
+            };
+            retain_used_property_types(&mut resource_type);
+            resource_type
+        })
+        .collect())
+}
+
+/// Resource type of a registry schema
+fn parse_registry_schema(type_name: &str, doc: &Value) -> ResourceType {
+    // Read-only properties are attributes, given as `/properties/<name>`
+    let attributes: Vec<String> = doc
+        .get("readOnlyProperties")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .filter_map(|pointer| pointer.strip_prefix("/properties/"))
+        .map(|name| name.replace('/', "."))
+        .collect();
+
+    let mut properties = schema_properties(doc);
+    properties.retain(|name, _| !attributes.contains(name));
+    let property_types = doc
+        .get("definitions")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .map(|(name, definition)| (name.clone(), schema_properties(definition)))
+        .collect();
+
+    ResourceType {
+        name: type_name.to_string(),
+        documentation: documentation(doc, "description"),
+        properties,
+        attributes,
+        property_types,
+    }
+}
+
+/// Properties of a resource or property type of a resource specification
+fn spec_properties(doc: &Value) -> BTreeMap<String, Property> {
+    doc.get("Properties")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .map(|(name, property)| {
+            let field = |key: &str| property.get(key).and_then(Value::as_str);
+            let item = field("PrimitiveItemType").or_else(|| field("ItemType"));
+            let type_name = match (field("PrimitiveType"), field("Type"), item) {
+                (Some(primitive), _, _) => primitive.to_string(),
+                (None, Some(container), Some(item)) => format!("{container}<{item}>"),
+                (None, Some(type_name), None) => type_name.to_string(),
+                (None, None, _) => "Json".to_string(),
+            };
+            let property = Property {
+                type_name,
+                required: property
+                    .get("Required")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+                documentation: documentation(property, "Documentation"),
+            };
+            (name.clone(), property)
+        })
+        .collect()
+}
+
+/// Properties of an object of a registry schema
+fn schema_properties(schema: &Value) -> BTreeMap<String, Property> {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .map(|(name, property)| {
+            let property = Property {
+                type_name: schema_type_name(property),
+                required: required.contains(&name.as_str()),
+                documentation: documentation(property, "description"),
+            };
+            (name.clone(), property)
+        })
+        .collect()
+}
+
+/// Type of a property of a registry schema, in the notation of the resource specification
+fn schema_type_name(schema: &Value) -> String {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return reference
+            .rsplit('/')
+            .next()
+            .unwrap_or(reference)
+            .to_string();
+    }
+    let type_name = match schema.get("type") {
+        Some(Value::String(type_name)) => type_name.as_str(),
+        // Several allowed types, e.g. `[string, object]`, take any JSON
+        _ => return "Json".to_string(),
+    };
+    match type_name {
+        "string" => "String".to_string(),
+        "integer" => "Integer".to_string(),
+        "number" => "Double".to_string(),
+        "boolean" => "Boolean".to_string(),
+        "array" => format!(
+            "List<{}>",
+            schema
+                .get("items")
+                .map(schema_type_name)
+                .unwrap_or_else(|| "Json".to_string())
+        ),
+        "object" => match schema.get("additionalProperties") {
+            Some(values @ Value::Object(_)) => format!("Map<{}>", schema_type_name(values)),
+            _ => "Json".to_string(),
+        },
+        _ => "Json".to_string(),
+    }
+}
+
+/// Keep the property types a resource refers to, directly or through other property types
+fn retain_used_property_types(resource_type: &mut ResourceType) {
+    let mut used = std::collections::BTreeSet::new();
+    let mut pending: Vec<&Property> = resource_type.properties.values().collect();
+    while let Some(property) = pending.pop() {
+        for name in referenced_types(&property.type_name) {
+            if let Some(properties) = resource_type.property_types.get(name) {
+                if used.insert(name.to_string()) {
+                    pending.extend(properties.values());
+                }
+            }
+        }
+    }
+    resource_type
+        .property_types
+        .retain(|name, _| used.contains(name));
+}
+
+/// Type names a property type notation refers to, e.g. `Tag` for `List<Tag>`
+fn referenced_types(type_name: &str) -> impl Iterator<Item = &str> {
+    type_name.split(['<', '>']).filter(|name| !name.is_empty())
+}
+
+/// Documentation of a document, if it has any
+fn documentation(doc: &Value, key: &str) -> Option<String> {
+    doc.get(key)
+        .and_then(Value::as_str)
+        .map(|documentation| {
+            documentation
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|documentation| !documentation.is_empty())
+}
@@ -0,0 +1,380 @@
+//! CloudFormation plugin implementation
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+use walkdir::WalkDir;
+
+use super::parser::{self, Property, ResourceType};
+use crate::plugin::*;
+use crate::FieldNaming;
+
+/// Fields of a template resource besides its `Type` and `Properties`
+const RESOURCE_FIELDS: &[&str] = &["Condition", "DeletionPolicy", "DependsOn", "Metadata"];
+
+/// CloudFormation plugin, generating builders for template resources
+pub struct CloudFormationPlugin {
+    /// Plugin configuration
+    config: PluginConfig,
+}
+
+impl CloudFormationPlugin {
+    /// Create a new CloudFormation plugin
+    pub fn new(config: PluginConfig) -> Self {
+        Self { config }
+    }
+
+    /// Get plugin metadata
+    fn get_metadata() -> PluginMetadata {
+        PluginMetadata {
+            id: "cloudformation:builtin".to_string(),
+            name: "CloudFormation Plugin".to_string(),
+            version: "1.0.0".to_string(),
+            description:
+                "Plugin for generating builders from CloudFormation resource specifications"
+                    .to_string(),
+            supported_types: vec!["cloudformation".to_string(), "json".to_string()],
+            capabilities: vec![PluginCapability::Parse, PluginCapability::SchemaExtraction],
+            commands: Vec::new(),
+        }
+    }
+
+    /// Resource types of the specifications in a file or directory, by type name
+    ///
+    /// Files that are not resource specifications are skipped; `filters` are
+    /// glob patterns of the types to keep, e.g. `AWS::S3::*`.
+    fn read_resource_types(
+        source_path: &Path,
+        filters: &[String],
+    ) -> Result<BTreeMap<String, (ResourceType, PathBuf)>> {
+        let patterns = filters
+            .iter()
+            .map(|filter| glob::Pattern::new(filter))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut resource_types = BTreeMap::new();
+        let files = WalkDir::new(source_path)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| {
+                entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.')
+            })
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"));
+
+        for entry in files {
+            let content = std::fs::read_to_string(entry.path())?;
+            let doc: serde_json::Value = match serde_json::from_str(&content) {
+                Ok(doc) => doc,
+                Err(e) => {
+                    debug!("Skipping {}: {}", entry.path().display(), e);
+                    continue;
+                }
+            };
+            if !parser::is_specification(&doc) {
+                continue;
+            }
+            for resource_type in parser::parse(&doc)? {
+                if patterns.is_empty()
+                    || patterns
+                        .iter()
+                        .any(|pattern| pattern.matches(&resource_type.name))
+                {
+                    let relative = entry
+                        .path()
+                        .strip_prefix(source_path)
+                        .unwrap_or(entry.path())
+                        .to_path_buf();
+                    resource_types.insert(resource_type.name.clone(), (resource_type, relative));
+                }
+            }
+        }
+        Ok(resource_types)
+    }
+
+    /// Extracted schema of a resource type, for policies and accounting
+    fn extracted_schema(resource_type: &ResourceType, source_file: &Path) -> ExtractedSchema {
+        let mut properties = serde_yaml::Mapping::new();
+        for (name, property) in &resource_type.properties {
+            let mut schema = serde_yaml::Mapping::new();
+            schema.insert("type".into(), property.type_name.clone().into());
+            if let Some(documentation) = &property.documentation {
+                schema.insert("description".into(), documentation.clone().into());
+            }
+            properties.insert(name.clone().into(), serde_yaml::Value::Mapping(schema));
+        }
+        let required: Vec<serde_yaml::Value> = resource_type
+            .properties
+            .iter()
+            .filter(|(_, property)| property.required)
+            .map(|(name, _)| name.clone().into())
+            .collect();
+
+        let mut content = serde_yaml::Mapping::new();
+        content.insert("type".into(), "object".into());
+        content.insert("properties".into(), serde_yaml::Value::Mapping(properties));
+        content.insert("required".into(), serde_yaml::Value::Sequence(required));
+        ExtractedSchema {
+            name: resource_type.name.clone(),
+            schema_type: "cloudformation_resource".to_string(),
+            content: serde_yaml::Value::Mapping(content),
+            source_file: source_file.to_path_buf(),
+            metadata: HashMap::from([(
+                "attributes".to_string(),
+                resource_type.attributes.clone().into(),
+            )]),
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for CloudFormationPlugin {
+    fn metadata(&self) -> PluginMetadata {
+        Self::get_metadata()
+    }
+
+    async fn initialize(&self, _context: &PluginContext) -> Result<()> {
+        info!("Initializing CloudFormation plugin");
+        Ok(())
+    }
+
+    async fn can_handle(&self, source_path: &Path) -> Result<bool> {
+        if source_path.extension().is_none_or(|ext| ext != "json") {
+            return Ok(false);
+        }
+        Ok(tokio::fs::read_to_string(source_path)
+            .await
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .is_some_and(|doc| parser::is_specification(&doc)))
+    }
+
+    async fn score(&self, source_path: &Path) -> Result<u32> {
+        // can_handle only accepts files that are resource specifications
+        Ok(if self.can_handle(source_path).await? {
+            2
+        } else {
+            0
+        })
+    }
+
+    async fn process_source(
+        &self,
+        source_path: &Path,
+        context: &PluginContext,
+    ) -> Result<PluginResult> {
+        info!("Processing CloudFormation source: {:?}", source_path);
+        let start_time = std::time::Instant::now();
+
+        let resource_types = Self::read_resource_types(source_path, &context.source.filters)?;
+        let schemas: Vec<ExtractedSchema> = resource_types
+            .values()
+            .map(|(resource_type, source_file)| Self::extracted_schema(resource_type, source_file))
+            .collect();
+
+        tokio::fs::create_dir_all(&context.output_dir).await?;
+        let mut generated_files = Vec::new();
+        for (resource_type, _) in resource_types.values() {
+            let output_file = context.output_dir.join(file_name(&resource_type.name));
+            tokio::fs::write(
+                &output_file,
+                generate_resource_builder(resource_type, &context.source),
+            )
+            .await?;
+            generated_files.push(output_file);
+        }
+        let index_file = context.output_dir.join("_index.libsonnet");
+        tokio::fs::write(&index_file, generate_index(resource_types.keys())).await?;
+        generated_files.push(index_file);
+
+        let warnings = if resource_types.is_empty() {
+            vec![format!(
+                "No CloudFormation resource specification found in {}",
+                source_path.display()
+            )]
+        } else {
+            Vec::new()
+        };
+        Ok(PluginResult {
+            statistics: PluginStatistics {
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                files_processed: 1,
+                schemas_extracted: schemas.len(),
+                files_generated: generated_files.len(),
+                metrics: Default::default(),
+            },
+            schemas,
+            generated_files,
+            errors: Vec::new(),
+            warnings,
+        })
+    }
+
+    async fn generate_code(
+        &self,
+        _schemas: &[ExtractedSchema],
+        _context: &PluginContext,
+    ) -> Result<Vec<PathBuf>> {
+        // Builders are generated from the resource types while processing
+        Ok(Vec::new())
+    }
+
+    async fn cleanup(&self, _context: &PluginContext) -> Result<()> {
+        info!("Cleaning up CloudFormation plugin");
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn Plugin> {
+        Box::new(Self::new(self.config.clone()))
+    }
+}
+
+/// File name of the builder of a resource type, e.g. `aws_s3_bucket.libsonnet`
+pub fn file_name(type_name: &str) -> String {
+    format!("{}.libsonnet", type_name.to_lowercase().replace("::", "_"))
+}
+
+/// Generate the builder of a resource type
+///
+/// `new` starts a template resource of the type, each property with a plain
+/// name gets a setter merging into `Properties`, and each property type gets
+/// setters below `types`. Attributes are read with `getAtt`.
+pub fn generate_resource_builder(resource_type: &ResourceType, source: &SourceMetadata) -> String {
+    let type_name = serde_json::Value::from(resource_type.name.as_str());
+    let mut code = String::new();
+    code.push_str(&format!(
+        "// Generated from CloudFormation resource type: {}\n",
+        resource_type.name
+    ));
+    if !source.name.is_empty() {
+        code.push_str(&format!("// Source: {}\n", source.provenance()));
+    }
+    if let Some(documentation) = &resource_type.documentation {
+        code.push_str(&format!("// {documentation}\n"));
+    }
+    code.push('\n');
+
+    code.push_str("{\n");
+    code.push_str(&format!("  type:: {type_name},\n"));
+    let required: Vec<&str> = resource_type
+        .properties
+        .iter()
+        .filter(|(_, property)| property.required)
+        .map(|(name, _)| name.as_str())
+        .collect();
+    if !required.is_empty() {
+        code.push_str(&format!(
+            "  // Required properties: {}\n",
+            required.join(", ")
+        ));
+    }
+    code.push_str(&format!(
+        "  new(properties={{}}): {{ Type: {type_name}, Properties: properties }},\n"
+    ));
+    push_setters(&mut code, "  ", &resource_type.properties, |name| {
+        format!("{{ Properties+: {{ {name}: value }} }}")
+    });
+    for field in RESOURCE_FIELDS {
+        code.push_str(&format!(
+            "  {}(value): {{ {field}: value }},\n",
+            FieldNaming::Preserve.setter_name(field)
+        ));
+    }
+
+    let attributes: Vec<String> = resource_type
+        .attributes
+        .iter()
+        .map(|attribute| serde_json::Value::from(attribute.as_str()).to_string())
+        .collect();
+    code.push_str(&format!("  attributes:: [{}],\n", attributes.join(", ")));
+    code.push_str("  getAtt(logicalId, attribute): { 'Fn::GetAtt': [logicalId, attribute] },\n");
+    code.push_str("  ref(logicalId): { Ref: logicalId },\n");
+
+    if !resource_type.property_types.is_empty() {
+        code.push_str("  types:: {\n");
+        for (name, properties) in &resource_type.property_types {
+            if !is_identifier(name) {
+                continue;
+            }
+            code.push_str(&format!("    {name}: {{\n"));
+            push_setters(&mut code, "      ", properties, |name| {
+                format!("{{ {name}: value }}")
+            });
+            code.push_str("    },\n");
+        }
+        code.push_str("  },\n");
+    }
+    code.push_str("}\n");
+    code
+}
+
+/// Append a setter, preceded by its type, for each property with a plain name
+fn push_setters(
+    code: &mut String,
+    indent: &str,
+    properties: &BTreeMap<String, Property>,
+    body: impl Fn(&str) -> String,
+) {
+    for (name, property) in properties {
+        if !is_identifier(name) {
+            continue;
+        }
+        let required = if property.required { ", required" } else { "" };
+        code.push_str(&format!("{indent}// {}{required}\n", property.type_name));
+        code.push_str(&format!(
+            "{indent}{}(value): {},\n",
+            FieldNaming::Preserve.setter_name(name),
+            body(name)
+        ));
+    }
+}
+
+/// Generate the index of a library, nesting the builders by the parts of their type name
+pub fn generate_index<'a>(type_names: impl Iterator<Item = &'a String>) -> String {
+    #[derive(Default)]
+    struct Node {
+        import: Option<String>,
+        children: BTreeMap<String, Node>,
+    }
+    fn render(node: &Node, indent: usize, code: &mut String) {
+        for (name, child) in &node.children {
+            let key = serde_json::Value::from(name.as_str());
+            match &child.import {
+                Some(file) => code.push_str(&format!(
+                    "{:indent$}{key}: import {},\n",
+                    "",
+                    serde_json::Value::from(file.as_str())
+                )),
+                None => {
+                    code.push_str(&format!("{:indent$}{key}: {{\n", ""));
+                    render(child, indent + 2, code);
+                    code.push_str(&format!("{:indent$}}},\n", ""));
+                }
+            }
+        }
+    }
+
+    let mut root = Node::default();
+    for type_name in type_names {
+        let node = type_name.split("::").fold(&mut root, |node, part| {
+            node.children.entry(part.to_string()).or_default()
+        });
+        node.import = Some(file_name(type_name));
+    }
+
+    let mut code = String::new();
+    code.push_str("// CloudFormation Library Index\n");
+    code.push_str("// Builders by resource type, e.g. AWS.S3.Bucket for AWS::S3::Bucket\n\n");
+    code.push_str("{\n");
+    render(&root, 2, &mut code);
+    code.push_str("}\n");
+    code
+}
+
+/// Whether a name can be used as a Jsonnet field and setter without quoting
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
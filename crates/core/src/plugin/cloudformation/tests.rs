@@ -0,0 +1,137 @@
+//! CloudFormation plugin tests
+
+use super::*;
+use crate::plugin::{Plugin, PluginCapability, PluginConfig, PluginContext};
+use tempfile::TempDir;
+
+const SPECIFICATION: &str = r#"{
+  "ResourceSpecificationVersion": "1.0.0",
+  "PropertyTypes": {
+    "AWS::S3::Bucket.VersioningConfiguration": {
+      "Properties": {
+        "Status": {"PrimitiveType": "String", "Required": true}
+      }
+    },
+    "AWS::SQS::Queue.RedrivePolicy": {
+      "Properties": {
+        "maxReceiveCount": {"PrimitiveType": "Integer"}
+      }
+    },
+    "Tag": {
+      "Properties": {
+        "Key": {"PrimitiveType": "String", "Required": true},
+        "Value": {"PrimitiveType": "String", "Required": true}
+      }
+    }
+  },
+  "ResourceTypes": {
+    "AWS::S3::Bucket": {
+      "Documentation": "http://docs.aws.amazon.com/AWSCloudFormation/latest/UserGuide/aws-resource-s3-bucket.html",
+      "Attributes": {"Arn": {"PrimitiveType": "String"}},
+      "Properties": {
+        "BucketName": {"PrimitiveType": "String", "Required": false},
+        "Tags": {"Type": "List", "ItemType": "Tag"},
+        "VersioningConfiguration": {"Type": "VersioningConfiguration"}
+      }
+    },
+    "AWS::SQS::Queue": {
+      "Properties": {
+        "QueueName": {"PrimitiveType": "String", "Required": true}
+      }
+    }
+  }
+}"#;
+
+const REGISTRY_SCHEMA: &str = r##"{
+  "typeName": "AWS::Logs::LogGroup",
+  "description": "Creates a log group.",
+  "definitions": {
+    "Tag": {
+      "type": "object",
+      "properties": {"Key": {"type": "string"}, "Value": {"type": "string"}},
+      "required": ["Key", "Value"]
+    }
+  },
+  "properties": {
+    "Arn": {"type": "string"},
+    "LogGroupName": {"type": "string"},
+    "RetentionInDays": {"type": "integer"},
+    "Tags": {"type": "array", "items": {"$ref": "#/definitions/Tag"}}
+  },
+  "required": ["LogGroupName"],
+  "readOnlyProperties": ["/properties/Arn"]
+}"##;
+
+#[test]
+fn test_parse_specifications() {
+    let spec: serde_json::Value = serde_json::from_str(SPECIFICATION).unwrap();
+    assert!(parser::is_specification(&spec));
+    let resource_types = parser::parse(&spec).unwrap();
+    let bucket = &resource_types[0];
+    assert_eq!(bucket.name, "AWS::S3::Bucket");
+    assert_eq!(bucket.properties["Tags"].type_name, "List<Tag>");
+    assert_eq!(bucket.attributes, ["Arn"]);
+    // Only the property types the resource uses, with shared ones unprefixed
+    assert_eq!(
+        bucket.property_types.keys().collect::<Vec<_>>(),
+        ["Tag", "VersioningConfiguration"]
+    );
+    assert!(resource_types[1].property_types.is_empty());
+
+    let schema: serde_json::Value = serde_json::from_str(REGISTRY_SCHEMA).unwrap();
+    let log_group = &parser::parse(&schema).unwrap()[0];
+    assert_eq!(log_group.attributes, ["Arn"]);
+    assert!(!log_group.properties.contains_key("Arn"));
+    assert!(log_group.properties["LogGroupName"].required);
+    assert_eq!(log_group.properties["RetentionInDays"].type_name, "Integer");
+    assert_eq!(log_group.properties["Tags"].type_name, "List<Tag>");
+    assert!(log_group.property_types["Tag"]["Key"].required);
+}
+
+#[tokio::test]
+async fn test_cloudformation_plugin() {
+    let temp_dir = TempDir::new().unwrap();
+    let specs = temp_dir.path().join("specs");
+    std::fs::create_dir_all(&specs).unwrap();
+    std::fs::write(specs.join("spec.json"), SPECIFICATION).unwrap();
+    std::fs::write(specs.join("aws-logs-loggroup.json"), REGISTRY_SCHEMA).unwrap();
+    std::fs::write(specs.join("package.json"), r#"{"name": "specs"}"#).unwrap();
+
+    let config = PluginConfig {
+        plugin_id: "cloudformation:builtin".to_string(),
+        config: serde_yaml::Value::Null,
+        enabled_capabilities: vec![PluginCapability::Parse],
+    };
+    let plugin = CloudFormationPlugin::new(config.clone());
+    assert!(plugin.can_handle(&specs.join("spec.json")).await.unwrap());
+    assert!(!plugin
+        .can_handle(&specs.join("package.json"))
+        .await
+        .unwrap());
+
+    let mut context = PluginContext::new(
+        temp_dir.path().to_path_buf(),
+        temp_dir.path().join("output"),
+        config,
+    );
+    context.source.filters = vec!["AWS::S3::*".to_string(), "AWS::Logs::*".to_string()];
+    let result = plugin.process_source(&specs, &context).await.unwrap();
+    let names: Vec<&str> = result.schemas.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["AWS::Logs::LogGroup", "AWS::S3::Bucket"]);
+    assert_eq!(result.generated_files.len(), 3);
+
+    let bucket =
+        std::fs::read_to_string(temp_dir.path().join("output/aws_s3_bucket.libsonnet")).unwrap();
+    assert!(bucket.contains(
+        "  new(properties={}): { Type: \"AWS::S3::Bucket\", Properties: properties },\n"
+    ));
+    assert!(
+        bucket.contains("  // List<Tag>\n  withTags(value): { Properties+: { Tags: value } },\n")
+    );
+    assert!(bucket.contains("  withDependsOn(value): { DependsOn: value },\n"));
+    assert!(bucket.contains("  attributes:: [\"Arn\"],\n"));
+    assert!(bucket.contains("    VersioningConfiguration: {\n      // String, required\n      withStatus(value): { Status: value },\n"));
+
+    let index = std::fs::read_to_string(temp_dir.path().join("output/_index.libsonnet")).unwrap();
+    assert!(index.contains("  \"AWS\": {\n    \"Logs\": {\n      \"LogGroup\": import \"aws_logs_loggroup.libsonnet\",\n"));
+}
@@ -0,0 +1,8 @@
+//! CRD plugin factory
+
+use super::plugin::CrdPlugin;
+
+gensonnet_plugin::plugin_factory!(
+    /// CRD plugin factory
+    pub CrdPluginFactory => CrdPlugin, ["crd", "yaml", "yml"]
+);
@@ -34,12 +34,13 @@ impl CrdPlugin {
             name: "CRD Plugin".to_string(),
             version: "1.0.0".to_string(),
             description: "Plugin for processing Kubernetes CustomResourceDefinitions".to_string(),
-            supported_types: vec!["yaml".to_string(), "yml".to_string()],
+            supported_types: vec!["crd".to_string(), "yaml".to_string(), "yml".to_string()],
             capabilities: vec![
                 PluginCapability::Parse,
                 PluginCapability::SchemaExtraction,
                 PluginCapability::Validation,
             ],
+            commands: Vec::new(),
         }
     }
 
@@ -102,7 +103,10 @@ impl Plugin for CrdPlugin {
             if ext_str == "yaml" || ext_str == "yml" {
                 // Try to read the file and check if it contains CRD content
                 if let Ok(content) = tokio::fs::read_to_string(source_path).await {
-                    if content.contains("kind:") && content.contains("CustomResourceDefinition") {
+                    if content.contains("kind:")
+                        && (content.contains("CustomResourceDefinition")
+                            || content.contains(jsonnet_crd::crossplane::XRD_KIND))
+                    {
                         return Ok(true);
                     }
                 }
@@ -111,6 +115,15 @@ impl Plugin for CrdPlugin {
         Ok(false)
     }
 
+    async fn score(&self, source_path: &Path) -> Result<u32> {
+        // can_handle only accepts files that declare a CRD
+        Ok(if self.can_handle(source_path).await? {
+            2
+        } else {
+            0
+        })
+    }
+
     async fn process_source(
         &self,
         source_path: &Path,
@@ -120,7 +133,7 @@ impl Plugin for CrdPlugin {
 
         let start_time = std::time::Instant::now();
 
-        // Parse CRDs from the source
+        // Parse CRDs from the source, defaulting to the source's own filters
         let filters = self
             .config
             .config
@@ -132,9 +145,14 @@ impl Plugin for CrdPlugin {
                     .map(|s| s.to_string())
                     .collect::<Vec<String>>()
             })
-            .unwrap_or_default();
+            .unwrap_or_else(|| context.source.filters.clone());
 
-        let crd_schemas = self.parser.parse_from_directory(source_path, &filters)?;
+        let crd_schemas = self
+            .parser
+            .clone()
+            .with_strict_schemas(context.strict_schemas)
+            .with_strict_crds(context.strict_crds)
+            .parse_from_directory(source_path, &filters)?;
 
         // Extract schemas
         let extracted_schemas = self.extract_schemas_from_crd(crd_schemas).await;
@@ -156,6 +174,7 @@ impl Plugin for CrdPlugin {
                 files_processed: 1,
                 schemas_extracted: schemas_count,
                 files_generated: files_count,
+                metrics: Default::default(),
             },
         })
     }
@@ -180,7 +199,7 @@ impl Plugin for CrdPlugin {
                 .join(format!("{}.libsonnet", schema_name.to_lowercase()));
 
             // Generate Jsonnet content
-            let jsonnet_content = self.generate_jsonnet_content(schema)?;
+            let jsonnet_content = self.generate_jsonnet_content(schema, &context.source)?;
 
             // Write to file
             tokio::fs::write(&output_file, jsonnet_content).await?;
@@ -209,7 +228,11 @@ impl Plugin for CrdPlugin {
 
 impl CrdPlugin {
     /// Generate Jsonnet content for a schema
-    fn generate_jsonnet_content(&self, schema: &ExtractedSchema) -> Result<String> {
+    fn generate_jsonnet_content(
+        &self,
+        schema: &ExtractedSchema,
+        source: &SourceMetadata,
+    ) -> Result<String> {
         let schema_name = &schema.name;
         let group = schema
             .metadata
@@ -233,7 +256,11 @@ impl CrdPlugin {
         content.push_str(&format!("// Generated from CRD: {schema_name}\n"));
         content.push_str(&format!("// API Group: {group}\n"));
         content.push_str(&format!("// API Version: {version}\n"));
-        content.push_str(&format!("// Kind: {kind}\n\n"));
+        content.push_str(&format!("// Kind: {kind}\n"));
+        if !source.name.is_empty() {
+            content.push_str(&format!("// Source: {}\n", source.provenance()));
+        }
+        content.push('\n');
 
         // Add schema definition
         content.push_str("{\n");
@@ -1,7 +1,11 @@
 //! CRD plugin tests
 
 use super::*;
-use crate::plugin::{Plugin, PluginCapability, PluginConfig, PluginFactory};
+use crate::plugin::{
+    ExtractedSchema, Plugin, PluginCapability, PluginConfig, PluginContext, PluginFactory,
+    SourceMetadata,
+};
+use std::collections::HashMap;
 use tempfile::TempDir;
 
 #[tokio::test]
@@ -77,3 +81,41 @@ spec:
     tokio::fs::write(&crd_file, crd_content).await.unwrap();
     assert!(plugin.can_handle(&crd_file).await.unwrap());
 }
+
+#[tokio::test]
+async fn test_crd_plugin_embeds_source_provenance() {
+    let config = PluginConfig {
+        plugin_id: "crd:test".to_string(),
+        config: serde_yaml::Value::Null,
+        enabled_capabilities: vec![PluginCapability::CodeGeneration],
+    };
+
+    let plugin = CrdPlugin::new(config.clone());
+    let temp_dir = TempDir::new().unwrap();
+    let context = PluginContext::new(
+        temp_dir.path().to_path_buf(),
+        temp_dir.path().join("output"),
+        config,
+    )
+    .with_source(SourceMetadata {
+        name: "examples".to_string(),
+        repository: Some("https://github.com/example/crds".to_string()),
+        git_ref: Some("main".to_string()),
+        commit: Some("0123abcd".to_string()),
+        filters: Vec::new(),
+        license: None,
+        tag: None,
+    });
+
+    let schema = ExtractedSchema {
+        name: "Example".to_string(),
+        schema_type: "crd".to_string(),
+        content: serde_yaml::Value::Null,
+        source_file: temp_dir.path().join("example.yaml"),
+        metadata: HashMap::new(),
+    };
+    let files = plugin.generate_code(&[schema], &context).await.unwrap();
+
+    let content = std::fs::read_to_string(&files[0]).unwrap();
+    assert!(content.contains("// Source: examples (https://github.com/example/crds @ 0123abcd)"));
+}
@@ -4,8 +4,12 @@
 //! The core plugin infrastructure is now in the `gensonnet-plugin` crate.
 
 // Temporary plugin implementations (will be moved to dynamic loading)
+#[cfg(feature = "go-ast")]
 pub mod ast;
+#[cfg(feature = "cloudformation")]
+pub mod cloudformation;
 pub mod crd;
+#[cfg(feature = "openapi")]
 pub mod openapi;
 
 pub mod registry;
@@ -0,0 +1,8 @@
+//! OpenAPI plugin factory
+
+use super::plugin::OpenApiPlugin;
+
+gensonnet_plugin::plugin_factory!(
+    /// OpenAPI plugin factory
+    pub OpenApiPluginFactory => OpenApiPlugin, ["openapi", "swagger", "yaml", "json"]
+);
@@ -0,0 +1,124 @@
+//! Lint rules for OpenAPI specifications, run by `x openapi lint`
+
+use std::collections::HashMap;
+
+use super::types::Schema;
+use crate::plugin::{CliArgSpec, CliCommandSpec};
+
+/// Reference prefixes of schemas declared in a specification
+const REF_PREFIXES: &[&str] = &["#/definitions/", "#/components/schemas/"];
+
+/// Spec of the `lint` subcommand
+pub fn lint_command() -> CliCommandSpec {
+    CliCommandSpec {
+        name: "lint".to_string(),
+        about: "Check an OpenAPI specification for common schema problems".to_string(),
+        args: vec![
+            CliArgSpec {
+                name: "file".to_string(),
+                help: "Specification file to lint".to_string(),
+                required: true,
+                ..Default::default()
+            },
+            CliArgSpec {
+                name: "strict".to_string(),
+                help: "Fail on warnings too".to_string(),
+                flag: true,
+                ..Default::default()
+            },
+        ],
+    }
+}
+
+/// How serious a lint finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem found in a schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub severity: Severity,
+
+    /// Dotted path of the schema or property, e.g. `User.address`
+    pub path: String,
+
+    pub message: String,
+}
+
+impl std::fmt::Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {}: {}", severity, self.path, self.message)
+    }
+}
+
+/// Lint the named schemas of a specification, sorted by path
+pub fn lint(schemas: &HashMap<String, Schema>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for (name, schema) in schemas {
+        if schema.description.is_none() {
+            findings.push(Finding {
+                severity: Severity::Warning,
+                path: name.clone(),
+                message: "schema has no description".to_string(),
+            });
+        }
+        lint_schema(name, schema, schemas, &mut findings);
+    }
+
+    findings.sort_by(|a, b| a.path.cmp(&b.path).then(b.severity.cmp(&a.severity)));
+    findings
+}
+
+fn lint_schema(
+    path: &str,
+    schema: &Schema,
+    schemas: &HashMap<String, Schema>,
+    findings: &mut Vec<Finding>,
+) {
+    let mut error = |message: String| {
+        findings.push(Finding {
+            severity: Severity::Error,
+            path: path.to_string(),
+            message,
+        })
+    };
+
+    if let Some(reference) = &schema.r#ref {
+        let target = REF_PREFIXES
+            .iter()
+            .find_map(|prefix| reference.strip_prefix(prefix));
+        if target.is_some_and(|target| !schemas.contains_key(target)) {
+            error(format!("reference {reference} does not resolve"));
+        }
+    }
+
+    if schema.r#type.as_deref() == Some("array") && schema.items.is_none() {
+        error("array has no items".to_string());
+    }
+
+    for required in schema.required.iter().flatten() {
+        if !schema
+            .properties
+            .as_ref()
+            .is_some_and(|properties| properties.contains_key(required))
+        {
+            error(format!("required property {required} is not declared"));
+        }
+    }
+
+    let mut properties: Vec<_> = schema.properties.iter().flatten().collect();
+    properties.sort_by_key(|(name, _)| name.as_str());
+    for (name, property) in properties {
+        lint_schema(&format!("{path}.{name}"), property, schemas, findings);
+    }
+    if let Some(items) = &schema.items {
+        lint_schema(&format!("{path}[]"), items, schemas, findings);
+    }
+}
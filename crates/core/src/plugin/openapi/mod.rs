@@ -1,6 +1,7 @@
 //! OpenAPI (Swagger) specification processing
 
 pub mod factory;
+pub mod lint;
 pub mod parser;
 pub mod plugin;
 pub mod types;
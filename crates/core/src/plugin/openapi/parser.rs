@@ -0,0 +1,536 @@
+//! OpenAPI parser implementation
+
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use super::types::*;
+use crate::plugin::*;
+
+/// OpenAPI parser
+pub struct OpenApiParser {
+    /// Parsed OpenAPI specifications
+    specs: Vec<OpenApiSpec>,
+
+    /// Extracted schemas
+    schemas: HashMap<String, Schema>,
+
+    /// Lines the properties of each spec's schemas are declared on, by schema name
+    field_lines: Vec<HashMap<String, BTreeMap<String, usize>>>,
+
+    /// Whether unsupported schema constructs fail parsing
+    strict_schemas: bool,
+
+    /// Whether the payloads of callbacks and webhooks are extracted too
+    include_callbacks: bool,
+}
+
+impl Default for OpenApiParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OpenApiParser {
+    /// Create a new OpenAPI parser
+    pub fn new() -> Self {
+        Self {
+            specs: Vec::new(),
+            schemas: HashMap::new(),
+            field_lines: Vec::new(),
+            strict_schemas: false,
+            include_callbacks: false,
+        }
+    }
+
+    /// Fail on schema constructs the generator cannot represent
+    pub fn with_strict_schemas(mut self, strict_schemas: bool) -> Self {
+        self.strict_schemas = strict_schemas;
+        self
+    }
+
+    /// Also extract the request payloads of callbacks and webhooks
+    pub fn with_callbacks(mut self, include_callbacks: bool) -> Self {
+        self.include_callbacks = include_callbacks;
+        self
+    }
+
+    /// Parse an OpenAPI specification file
+    pub async fn parse_file(&mut self, file_path: &Path) -> Result<()> {
+        let content = tokio::fs::read_to_string(file_path).await?;
+        self.parse_content(&content, file_path).await
+    }
+
+    /// Parse OpenAPI specification content
+    pub async fn parse_content(&mut self, content: &str, file_path: &Path) -> Result<()> {
+        if self.strict_schemas {
+            check_strict(content, file_path)?;
+        }
+
+        // Try to parse as JSON first
+        if let Ok(spec) = serde_json::from_str::<OpenApiSpec>(content) {
+            self.process_spec(spec, content)?;
+            return Ok(());
+        }
+
+        // Try to parse as YAML
+        if let Ok(spec) = serde_yaml::from_str::<OpenApiSpec>(content) {
+            self.process_spec(spec, content)?;
+            return Ok(());
+        }
+
+        // If both fail, try to get more specific error information
+        let json_error = serde_json::from_str::<OpenApiSpec>(content).unwrap_err();
+        let yaml_error = serde_yaml::from_str::<OpenApiSpec>(content).unwrap_err();
+
+        Err(anyhow::anyhow!(
+            "Failed to parse OpenAPI specification. JSON error: {}, YAML error: {}",
+            json_error,
+            yaml_error
+        ))
+    }
+
+    /// Process an OpenAPI specification
+    fn process_spec(&mut self, spec: OpenApiSpec, content: &str) -> Result<()> {
+        self.specs.push(spec.clone());
+
+        let mut field_lines = HashMap::new();
+        for (parent, names) in [
+            (&["definitions"][..], spec.definitions.as_ref()),
+            (
+                &["components", "schemas"][..],
+                spec.components
+                    .as_ref()
+                    .and_then(|components| components.schemas.as_ref()),
+            ),
+        ] {
+            for name in names.into_iter().flat_map(|schemas| schemas.keys()) {
+                let mut path = parent.to_vec();
+                path.extend([name.as_str(), "properties"]);
+                field_lines.insert(
+                    name.clone(),
+                    jsonnet_crd::provenance::property_lines(content, &path),
+                );
+            }
+        }
+        self.field_lines.push(field_lines);
+
+        // Extract schemas from definitions (v2)
+        if let Some(definitions) = &spec.definitions {
+            for (name, schema) in definitions {
+                self.schemas.insert(name.clone(), schema.clone());
+            }
+        }
+
+        // Extract schemas from components (v3)
+        if let Some(components) = &spec.components {
+            if let Some(schemas) = &components.schemas {
+                for (name, schema) in schemas {
+                    self.schemas.insert(name.clone(), schema.clone());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get all parsed specifications
+    pub fn get_specs(&self) -> &[OpenApiSpec] {
+        &self.specs
+    }
+
+    /// Get extracted schemas
+    pub fn get_schemas(&self) -> &HashMap<String, Schema> {
+        &self.schemas
+    }
+
+    /// Extract schemas from OpenAPI specifications
+    pub fn extract_schemas(&self) -> Vec<ExtractedSchema> {
+        self.schemas().collect()
+    }
+
+    /// Schemas of the OpenAPI specifications, converted one at a time
+    pub fn schemas(&self) -> impl Iterator<Item = ExtractedSchema> + '_ {
+        self.specs
+            .iter()
+            .enumerate()
+            .flat_map(move |(index, spec)| {
+                // Definitions (v2), then components (v3)
+                let definitions = spec.definitions.iter().flatten();
+                let components = spec
+                    .components
+                    .iter()
+                    .filter_map(|components| components.schemas.as_ref())
+                    .flatten();
+                let events = if self.include_callbacks {
+                    self.event_schemas(spec)
+                } else {
+                    Vec::new()
+                };
+                definitions
+                    .chain(components)
+                    .map(move |(name, schema)| {
+                        let mut extracted =
+                            self.schema_to_extracted_schema(name, schema, &spec.info);
+                        if let Some(lines) = self
+                            .field_lines
+                            .get(index)
+                            .and_then(|lines| lines.get(name))
+                        {
+                            extracted.set_field_lines(lines);
+                        }
+                        extracted
+                    })
+                    .chain(events)
+                    .chain(self.parameter_schemas(spec))
+                    .chain(self.servers_schema(spec))
+            })
+    }
+
+    /// Reusable parameters of a specification (`openapi_parameter`), ordered by name
+    ///
+    /// Parameters come from `components.parameters` (v3) or the top-level
+    /// `parameters` (v2); the schema of a v2 parameter is its type and format.
+    fn parameter_schemas(&self, spec: &OpenApiSpec) -> Vec<ExtractedSchema> {
+        let mut parameters: Vec<(&String, &Parameter)> = spec
+            .components
+            .iter()
+            .filter_map(|components| components.parameters.as_ref())
+            .chain(spec.parameters.as_ref())
+            .flatten()
+            .collect();
+        parameters.sort_by_key(|(name, _)| *name);
+
+        parameters
+            .into_iter()
+            .map(|(name, parameter)| {
+                let schema = parameter.schema.clone().unwrap_or_else(|| Schema {
+                    r#type: parameter.r#type.clone(),
+                    format: parameter.format.clone(),
+                    ..Schema::default()
+                });
+                let schema = resolve_reference(spec, &schema).unwrap_or(&schema);
+                let mut extracted = self.schema_to_extracted_schema(name, schema, &spec.info);
+                extracted.schema_type = "openapi_parameter".to_string();
+                if let Some(description) = &parameter.description {
+                    extracted.content["description"] = description.clone().into();
+                }
+                for (key, value) in [
+                    ("parameter_name", parameter.name.clone().into()),
+                    ("parameter_in", parameter.r#in.clone().into()),
+                    (
+                        "parameter_required",
+                        parameter
+                            .required
+                            .unwrap_or(parameter.r#in == "path")
+                            .into(),
+                    ),
+                ] {
+                    extracted.metadata.insert(key.to_string(), value);
+                }
+                extracted
+            })
+            .collect()
+    }
+
+    /// Servers of a specification (`openapi_servers`), named after the API
+    ///
+    /// The content lists each server's URL, with URL variables set to their
+    /// defaults; v2 specifications give one server per scheme of their host
+    /// and base path.
+    fn servers_schema(&self, spec: &OpenApiSpec) -> Option<ExtractedSchema> {
+        let servers: Vec<(String, Option<String>)> = match (&spec.servers, &spec.host) {
+            (Some(servers), _) => servers
+                .iter()
+                .map(|server| (server.default_url(), server.description.clone()))
+                .collect(),
+            (None, Some(host)) => {
+                let base_path = spec.base_path.as_deref().unwrap_or_default();
+                spec.schemes
+                    .clone()
+                    .unwrap_or_else(|| vec!["https".to_string()])
+                    .iter()
+                    .map(|scheme| (format!("{scheme}://{host}{base_path}"), None))
+                    .collect()
+            }
+            (None, None) => vec![(spec.base_path.clone()?, None)],
+        };
+        if servers.is_empty() {
+            return None;
+        }
+
+        let name: String = spec
+            .info
+            .title
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let mut extracted =
+            self.schema_to_extracted_schema(name.trim_matches('_'), &Schema::default(), &spec.info);
+        extracted.schema_type = "openapi_servers".to_string();
+        extracted.content = serde_yaml::Value::Sequence(
+            servers
+                .into_iter()
+                .map(|(url, description)| {
+                    let mut server = serde_yaml::Mapping::new();
+                    server.insert("url".into(), url.into());
+                    if let Some(description) = description {
+                        server.insert("description".into(), description.into());
+                    }
+                    serde_yaml::Value::Mapping(server)
+                })
+                .collect(),
+        );
+        Some(extracted)
+    }
+
+    /// Request payloads of a specification's callbacks (`openapi_callback`)
+    /// and webhooks (`openapi_webhook`), ordered by name
+    ///
+    /// Each operation with a request body gives a schema named after its
+    /// callback or webhook, suffixed with `_<method>` when there are several.
+    fn event_schemas(&self, spec: &OpenApiSpec) -> Vec<ExtractedSchema> {
+        let callbacks = spec
+            .components
+            .iter()
+            .filter_map(|components| components.callbacks.as_ref())
+            .flatten()
+            .map(|(name, expressions)| {
+                let items = expressions
+                    .iter()
+                    .map(|(expression, item)| (Some(expression.as_str()), item))
+                    .collect::<Vec<_>>();
+                ("openapi_callback", name, items)
+            });
+        let webhooks = spec
+            .webhooks
+            .iter()
+            .flatten()
+            .map(|(name, item)| ("openapi_webhook", name, vec![(None, item)]));
+
+        let mut events = Vec::new();
+        for (schema_type, name, items) in callbacks.chain(webhooks) {
+            let payloads: Vec<_> = items
+                .into_iter()
+                .flat_map(|(expression, item)| {
+                    item.operations().filter_map(move |(method, operation)| {
+                        Some((expression, method, payload_schema(operation)?))
+                    })
+                })
+                .collect();
+            for (expression, method, payload) in &payloads {
+                let event_name = if payloads.len() == 1 {
+                    name.clone()
+                } else {
+                    format!("{name}_{method}")
+                };
+                let payload = resolve_reference(spec, payload).unwrap_or(payload);
+                let mut extracted =
+                    self.schema_to_extracted_schema(&event_name, payload, &spec.info);
+                extracted.schema_type = schema_type.to_string();
+                extracted.metadata.insert(
+                    "event_method".to_string(),
+                    serde_yaml::Value::String(method.to_uppercase()),
+                );
+                if let Some(expression) = expression {
+                    extracted.metadata.insert(
+                        "callback_expression".to_string(),
+                        serde_yaml::Value::String(expression.to_string()),
+                    );
+                }
+                events.push(extracted);
+            }
+        }
+        events.sort_by(|a, b| (&a.schema_type, &a.name).cmp(&(&b.schema_type, &b.name)));
+        events
+    }
+
+    /// Convert OpenAPI schema to extracted schema
+    fn schema_to_extracted_schema(
+        &self,
+        name: &str,
+        schema: &Schema,
+        info: &ApiInfo,
+    ) -> ExtractedSchema {
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "api_title".to_string(),
+            serde_yaml::Value::String(info.title.clone()),
+        );
+        metadata.insert(
+            "api_version".to_string(),
+            serde_yaml::Value::String(info.version.clone()),
+        );
+        if let Some(description) = &info.description {
+            metadata.insert(
+                "api_description".to_string(),
+                serde_yaml::Value::String(description.clone()),
+            );
+        }
+
+        let schema_content = self.schema_to_yaml(schema);
+
+        ExtractedSchema {
+            name: name.to_string(),
+            schema_type: "openapi_schema".to_string(),
+            content: schema_content,
+            source_file: PathBuf::new(), // Will be set by caller
+            metadata,
+        }
+    }
+
+    /// Convert OpenAPI schema to YAML
+    #[allow(clippy::only_used_in_recursion)]
+    fn schema_to_yaml(&self, schema: &Schema) -> serde_yaml::Value {
+        let mut yaml = serde_yaml::Mapping::new();
+
+        if let Some(schema_type) = &schema.r#type {
+            yaml.insert(
+                serde_yaml::Value::String("type".to_string()),
+                serde_yaml::Value::String(schema_type.clone()),
+            );
+        }
+
+        if let Some(format) = &schema.format {
+            yaml.insert(
+                serde_yaml::Value::String("format".to_string()),
+                serde_yaml::Value::String(format.clone()),
+            );
+        }
+
+        if let Some(description) = &schema.description {
+            yaml.insert(
+                serde_yaml::Value::String("description".to_string()),
+                serde_yaml::Value::String(description.clone()),
+            );
+        }
+
+        if let Some(properties) = &schema.properties {
+            let mut props_yaml = serde_yaml::Mapping::new();
+            for (prop_name, prop_schema) in properties {
+                props_yaml.insert(
+                    serde_yaml::Value::String(prop_name.clone()),
+                    self.schema_to_yaml(prop_schema),
+                );
+            }
+            yaml.insert(
+                serde_yaml::Value::String("properties".to_string()),
+                serde_yaml::Value::Mapping(props_yaml),
+            );
+        }
+
+        if let Some(required) = &schema.required {
+            yaml.insert(
+                serde_yaml::Value::String("required".to_string()),
+                serde_yaml::Value::Sequence(
+                    required
+                        .iter()
+                        .map(|r| serde_yaml::Value::String(r.clone()))
+                        .collect(),
+                ),
+            );
+        }
+
+        if let Some(items) = &schema.items {
+            yaml.insert(
+                serde_yaml::Value::String("items".to_string()),
+                self.schema_to_yaml(items),
+            );
+        }
+
+        if let Some(enum_values) = &schema.r#enum {
+            let enum_yaml: Vec<serde_yaml::Value> = enum_values
+                .iter()
+                .map(|v| serde_yaml::to_value(v).unwrap_or(serde_yaml::Value::Null))
+                .collect();
+            yaml.insert(
+                serde_yaml::Value::String("enum".to_string()),
+                serde_yaml::Value::Sequence(enum_yaml),
+            );
+        }
+
+        if let Some(example) = &schema.example {
+            yaml.insert(
+                serde_yaml::Value::String("example".to_string()),
+                serde_yaml::to_value(example).unwrap_or(serde_yaml::Value::Null),
+            );
+        }
+
+        if let Some(default) = &schema.default {
+            yaml.insert(
+                serde_yaml::Value::String("default".to_string()),
+                serde_yaml::to_value(default).unwrap_or(serde_yaml::Value::Null),
+            );
+        }
+
+        serde_yaml::Value::Mapping(yaml)
+    }
+}
+/// Schema of an operation's request body, preferring its JSON representation
+fn payload_schema(operation: &Operation) -> Option<&Schema> {
+    let content = &operation.request_body.as_ref()?.content;
+    content
+        .get("application/json")
+        .or_else(|| {
+            content
+                .iter()
+                .min_by_key(|(media_type, _)| media_type.as_str())
+                .map(|(_, media_type)| media_type)
+        })?
+        .schema
+        .as_ref()
+}
+
+/// The component schema or definition a schema refers to, if it is a local reference
+fn resolve_reference<'a>(spec: &'a OpenApiSpec, schema: &Schema) -> Option<&'a Schema> {
+    let reference = schema.r#ref.as_deref()?;
+    if let Some(name) = reference.strip_prefix("#/components/schemas/") {
+        return spec.components.as_ref()?.schemas.as_ref()?.get(name);
+    }
+    spec.definitions
+        .as_ref()?
+        .get(reference.strip_prefix("#/definitions/")?)
+}
+
+/// Fail if any schema of a specification uses a construct the generator cannot represent
+///
+/// Runs on the raw document, as typed parsing drops some of these constructs.
+fn check_strict(content: &str, file_path: &Path) -> Result<()> {
+    let document: serde_yaml::Value = serde_yaml::from_str(content)?;
+    let definitions = document.get("definitions").map(|d| ("/definitions", d));
+    let components = document
+        .get("components")
+        .and_then(|c| c.get("schemas"))
+        .map(|s| ("/components/schemas", s));
+
+    let mut unsupported = Vec::new();
+    for (prefix, schemas) in definitions.into_iter().chain(components) {
+        let Some(schemas) = schemas.as_mapping() else {
+            continue;
+        };
+        for (name, schema) in schemas {
+            let name = name.as_str().unwrap_or_default();
+            unsupported.extend(
+                jsonnet_crd::strict::find_unsupported(schema)
+                    .into_iter()
+                    .map(|found| format!("{prefix}/{name}{}: {}", found.path, found.construct)),
+            );
+        }
+    }
+
+    if unsupported.is_empty() {
+        return Ok(());
+    }
+    Err(anyhow::anyhow!(
+        "Strict schema mode found {} unsupported constructs in {}:\n  {}",
+        unsupported.len(),
+        file_path.display(),
+        unsupported.join("\n  ")
+    ))
+}
@@ -2,9 +2,13 @@
 
 use anyhow::Result;
 use async_trait::async_trait;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
 
+use super::lint::{self, Severity};
 use super::parser::OpenApiParser;
+use crate::config::OpenApiOptions;
 use crate::plugin::*;
 
 /// OpenAPI plugin
@@ -41,7 +45,9 @@ impl Plugin for OpenApiPlugin {
                 PluginCapability::Parse,
                 PluginCapability::SchemaExtraction,
                 PluginCapability::Validation,
+                PluginCapability::Cli,
             ],
+            commands: vec![lint::lint_command()],
         }
     }
 
@@ -60,15 +66,34 @@ impl Plugin for OpenApiPlugin {
         }
     }
 
+    async fn score(&self, source_path: &Path) -> Result<u32> {
+        if !self.can_handle(source_path).await? {
+            return Ok(0);
+        }
+
+        // Specifications declare their version at the top level
+        let declares_version = tokio::fs::read_to_string(source_path)
+            .await
+            .ok()
+            .and_then(|content| serde_yaml::from_str::<serde_yaml::Value>(&content).ok())
+            .is_some_and(|document| {
+                document.get("openapi").is_some() || document.get("swagger").is_some()
+            });
+        Ok(if declares_version { 2 } else { 1 })
+    }
+
     async fn process_source(
         &self,
         source_path: &Path,
-        _context: &PluginContext,
+        context: &PluginContext,
     ) -> Result<PluginResult> {
         let start_time = std::time::Instant::now();
 
         // Parse the OpenAPI specification file
-        let mut parser = OpenApiParser::new();
+        let options = OpenApiOptions::from_plugin_config(&context.config.config)?;
+        let mut parser = OpenApiParser::new()
+            .with_strict_schemas(context.strict_schemas)
+            .with_callbacks(options.include_callbacks);
         parser.parse_file(source_path).await?;
 
         // Extract schemas
@@ -85,12 +110,44 @@ impl Plugin for OpenApiPlugin {
                 files_processed: 1,
                 schemas_extracted: schemas_count,
                 files_generated: 0,
+                metrics: Default::default(),
             },
             warnings: Vec::new(),
             errors: Vec::new(),
         })
     }
 
+    async fn process_source_stream(
+        &self,
+        source_path: &Path,
+        context: &PluginContext,
+        sender: mpsc::Sender<ExtractedSchema>,
+    ) -> Result<PluginStatistics> {
+        let start_time = std::time::Instant::now();
+        let options = OpenApiOptions::from_plugin_config(&context.config.config)?;
+        let mut parser = OpenApiParser::new()
+            .with_strict_schemas(context.strict_schemas)
+            .with_callbacks(options.include_callbacks);
+        parser.parse_file(source_path).await?;
+
+        // Specs with thousands of components are converted as the consumer
+        // keeps up, instead of all at once
+        let mut schemas_extracted = 0;
+        for schema in parser.schemas() {
+            if sender.send(schema).await.is_err() {
+                break;
+            }
+            schemas_extracted += 1;
+        }
+        Ok(PluginStatistics {
+            processing_time_ms: start_time.elapsed().as_millis() as u64,
+            files_processed: 1,
+            schemas_extracted,
+            files_generated: 0,
+            metrics: Default::default(),
+        })
+    }
+
     async fn generate_code(
         &self,
         schemas: &[ExtractedSchema],
@@ -113,6 +170,44 @@ impl Plugin for OpenApiPlugin {
         Ok(generated_files)
     }
 
+    async fn run_command(
+        &self,
+        command: &str,
+        args: &BTreeMap<String, String>,
+        context: &PluginContext,
+    ) -> Result<CommandOutput> {
+        if command != "lint" {
+            return Err(anyhow::anyhow!("Unknown OpenAPI command: {}", command));
+        }
+
+        let file = context.working_dir.join(&args["file"]);
+        let mut parser = OpenApiParser::new();
+        parser.parse_file(&file).await?;
+
+        let findings = lint::lint(parser.get_schemas());
+        let fails_on = if args.contains_key("strict") {
+            Severity::Warning
+        } else {
+            Severity::Error
+        };
+
+        let mut output: String = findings
+            .iter()
+            .map(|finding| format!("{finding}\n"))
+            .collect();
+        output.push_str(&format!(
+            "{}: {} schemas, {} problems\n",
+            file.display(),
+            parser.get_schemas().len(),
+            findings.len()
+        ));
+
+        Ok(CommandOutput {
+            output,
+            success: findings.iter().all(|finding| finding.severity < fails_on),
+        })
+    }
+
     async fn cleanup(&self, _context: &PluginContext) -> Result<()> {
         // Clean up any resources
         Ok(())
@@ -0,0 +1,328 @@
+//! OpenAPI plugin tests
+
+use super::*;
+use crate::plugin::{Plugin, PluginCapability, PluginConfig, PluginContext};
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_openapi_parser_basic() {
+    let mut parser = OpenApiParser::new();
+
+    let test_content = r#"
+openapi: 3.0.0
+info:
+  title: Test API
+  version: 1.0.0
+paths:
+  /users:
+    get:
+      summary: Get users
+      responses:
+        '200':
+          description: Success
+          content:
+            application/json:
+              schema:
+                type: array
+                items:
+                  $ref: '#/components/schemas/User'
+components:
+  schemas:
+    User:
+      type: object
+      properties:
+        id:
+          type: integer
+        name:
+          type: string
+      required:
+        - id
+        - name
+"#;
+
+    let temp_dir = TempDir::new().unwrap();
+    let test_file = temp_dir.path().join("test.yaml");
+    tokio::fs::write(&test_file, test_content).await.unwrap();
+
+    parser
+        .parse_content(test_content, &test_file)
+        .await
+        .unwrap();
+
+    let schemas = parser.extract_schemas();
+    assert_eq!(schemas.len(), 1);
+    assert_eq!(schemas[0].name, "User");
+}
+
+#[tokio::test]
+async fn test_openapi_parser_strict_schemas() {
+    let content = r#"
+openapi: 3.0.0
+info:
+  title: Test API
+  version: 1.0.0
+paths: {}
+components:
+  schemas:
+    Labels:
+      type: object
+      patternProperties:
+        "^[a-z]+$": {type: string}
+    User:
+      type: object
+      properties:
+        id:
+          type: string
+          format: snowflake
+"#;
+    let path = std::path::Path::new("api.yaml");
+
+    assert!(OpenApiParser::new()
+        .parse_content(content, path)
+        .await
+        .is_ok());
+
+    let err = OpenApiParser::new()
+        .with_strict_schemas(true)
+        .parse_content(content, path)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("/components/schemas/Labels: `patternProperties`"));
+    assert!(err.contains("/components/schemas/User/properties/id: unknown format `snowflake`"));
+}
+
+#[tokio::test]
+async fn test_openapi_parser_swagger_parameters_and_servers() {
+    let content = r#"
+swagger: "2.0"
+info:
+  title: Pet Store
+  version: 1.0.0
+host: pets.example.com
+basePath: /v2
+schemes: [http, https]
+paths: {}
+parameters:
+  limit:
+    name: limit
+    in: query
+    type: integer
+    format: int32
+"#;
+    let mut parser = OpenApiParser::new();
+    parser
+        .parse_content(content, std::path::Path::new("api.yaml"))
+        .await
+        .unwrap();
+    let schemas = parser.extract_schemas();
+
+    let limit = schemas.iter().find(|s| s.name == "limit").unwrap();
+    assert_eq!(limit.schema_type, "openapi_parameter");
+    assert_eq!(limit.content["type"], "integer");
+    assert_eq!(limit.metadata["parameter_in"], "query");
+    assert_eq!(limit.metadata["parameter_required"], false);
+
+    let servers = schemas.iter().find(|s| s.name == "pet_store").unwrap();
+    assert_eq!(servers.schema_type, "openapi_servers");
+    assert_eq!(servers.content[0]["url"], "http://pets.example.com/v2");
+    assert_eq!(servers.content[1]["url"], "https://pets.example.com/v2");
+}
+
+#[tokio::test]
+async fn test_openapi_plugin() {
+    let config = PluginConfig {
+        plugin_id: "test-openapi-plugin".to_string(),
+        config: serde_yaml::Value::Null,
+        enabled_capabilities: vec![PluginCapability::Parse, PluginCapability::SchemaExtraction],
+    };
+
+    let plugin = OpenApiPlugin::new(config);
+    let metadata = plugin.metadata();
+
+    assert_eq!(metadata.name, "OpenAPI Plugin");
+    assert!(metadata.supported_types.contains(&"openapi".to_string()));
+}
+
+#[tokio::test]
+async fn test_openapi_plugin_processing() {
+    let config = PluginConfig {
+        plugin_id: "test-openapi-plugin".to_string(),
+        config: serde_yaml::Value::Null,
+        enabled_capabilities: vec![PluginCapability::Parse, PluginCapability::SchemaExtraction],
+    };
+
+    let plugin = OpenApiPlugin::new(config.clone());
+    let temp_dir = TempDir::new().unwrap();
+    let test_file = temp_dir.path().join("test.yaml");
+
+    let test_content = r#"
+openapi: 3.0.0
+info:
+  title: Test API
+  version: 1.0.0
+paths:
+  /test:
+    get:
+      summary: Test endpoint
+      responses:
+        '200':
+          description: Success
+components:
+  schemas:
+    User:
+      type: object
+      properties:
+        id:
+          type: integer
+        name:
+          type: string
+"#;
+
+    tokio::fs::write(&test_file, &test_content).await.unwrap();
+
+    let context = PluginContext::new(
+        temp_dir.path().to_path_buf(),
+        temp_dir.path().join("output"),
+        config,
+    );
+
+    let result = plugin.process_source(&test_file, &context).await.unwrap();
+
+    assert_eq!(result.schemas.len(), 1);
+    assert_eq!(result.schemas[0].name, "User");
+    assert_eq!(result.statistics.files_processed, 1);
+    assert_eq!(result.statistics.schemas_extracted, 1);
+}
+
+#[tokio::test]
+async fn test_openapi_lint_command() {
+    let manager = crate::plugin::PluginManager::new();
+    manager
+        .register_factory("openapi".to_string(), Box::new(OpenApiPluginFactory))
+        .await;
+    let config = PluginConfig {
+        plugin_id: "openapi:builtin".to_string(),
+        config: serde_yaml::Value::Null,
+        enabled_capabilities: vec![PluginCapability::Cli],
+    };
+    manager
+        .create_plugin("openapi", config.clone())
+        .await
+        .unwrap();
+
+    let temp_dir = TempDir::new().unwrap();
+    let spec = r#"
+openapi: 3.0.0
+info:
+  title: Test API
+  version: 1.0.0
+paths: {}
+components:
+  schemas:
+    User:
+      description: A user
+      type: object
+      required: [id, email]
+      properties:
+        id:
+          type: integer
+        group:
+          $ref: '#/components/schemas/Group'
+"#;
+    tokio::fs::write(temp_dir.path().join("api.yaml"), spec)
+        .await
+        .unwrap();
+    let context = PluginContext::new(
+        temp_dir.path().to_path_buf(),
+        temp_dir.path().join("output"),
+        config,
+    );
+    let args = |args: &[(&str, &str)]| {
+        args.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    };
+
+    let output = manager
+        .run_command("openapi", "lint", args(&[("file", "api.yaml")]), &context)
+        .await
+        .unwrap();
+    assert!(!output.success);
+    assert!(output
+        .output
+        .contains("error: User: required property email is not declared\n"));
+    assert!(output
+        .output
+        .contains("error: User.group: reference #/components/schemas/Group does not resolve\n"));
+
+    // Arguments are checked against the declared schema
+    assert!(manager
+        .run_command("openapi", "lint", args(&[]), &context)
+        .await
+        .is_err());
+    assert!(manager
+        .run_command(
+            "openapi",
+            "lint",
+            args(&[("file", "api.yaml"), ("fix", "true")]),
+            &context
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_openapi_schema_stream() {
+    let manager = crate::plugin::PluginManager::new();
+    manager
+        .register_factory("openapi".to_string(), Box::new(OpenApiPluginFactory))
+        .await;
+    let config = PluginConfig {
+        plugin_id: "openapi:builtin".to_string(),
+        config: serde_yaml::Value::Null,
+        enabled_capabilities: vec![PluginCapability::Parse],
+    };
+    manager
+        .create_plugin("openapi", config.clone())
+        .await
+        .unwrap();
+
+    // More schemas than the channel holds, so the plugin waits on the consumer
+    let count = crate::plugin::SchemaStream::CAPACITY * 3;
+    let mut spec = String::from(
+        "openapi: 3.0.0\ninfo:\n  title: Test API\n  version: 1.0.0\npaths: {}\ncomponents:\n  schemas:\n",
+    );
+    for i in 0..count {
+        spec.push_str(&format!("    Schema{i}:\n      type: object\n"));
+    }
+    let temp_dir = TempDir::new().unwrap();
+    let spec_file = temp_dir.path().join("api.yaml");
+    tokio::fs::write(&spec_file, spec).await.unwrap();
+    let context = PluginContext::new(
+        temp_dir.path().to_path_buf(),
+        temp_dir.path().join("output"),
+        config,
+    );
+    let binding = crate::plugin::PluginBinding::Plugin("openapi:builtin".to_string());
+
+    let (plugin_id, mut stream) = manager
+        .process_source_stream_with(&binding, &spec_file, &context)
+        .await
+        .unwrap();
+    assert_eq!(plugin_id, "openapi:builtin");
+    let mut received = 0;
+    while let Some(schema) = stream.next().await {
+        assert!(schema.name.starts_with("Schema"));
+        received += 1;
+    }
+    assert_eq!(received, count);
+    assert_eq!(stream.finish().await.unwrap().schemas_extracted, count);
+
+    let (_, mut stream) = manager
+        .process_source_stream_with(&binding, &temp_dir.path().join("missing.yaml"), &context)
+        .await
+        .unwrap();
+    assert!(stream.next().await.is_none());
+    assert!(stream.finish().await.is_err());
+}
@@ -28,16 +28,29 @@ pub struct OpenApiSpec {
     /// API information
     pub info: ApiInfo,
 
+    /// Host serving the API (v2)
+    pub host: Option<String>,
+
     /// Base path (v2)
     #[serde(rename = "basePath")]
     pub base_path: Option<String>,
 
+    /// Transfer protocols of the API (v2)
+    pub schemes: Option<Vec<String>>,
+
+    /// Parameters shared by operations (v2)
+    pub parameters: Option<HashMap<String, Parameter>>,
+
     /// Servers (v3)
     pub servers: Option<Vec<Server>>,
 
     /// Paths/endpoints
+    #[serde(default)]
     pub paths: HashMap<String, PathItem>,
 
+    /// Webhooks (v3.1)
+    pub webhooks: Option<HashMap<String, PathItem>>,
+
     /// Definitions/schemas (v2)
     pub definitions: Option<HashMap<String, Schema>>,
 
@@ -95,6 +108,33 @@ pub struct Server {
 
     /// Server description
     pub description: Option<String>,
+
+    /// Variables substituted into the URL template
+    pub variables: Option<HashMap<String, ServerVariable>>,
+}
+
+impl Server {
+    /// URL of the server with every variable set to its default
+    pub fn default_url(&self) -> String {
+        let mut url = self.url.clone();
+        for (name, variable) in self.variables.iter().flatten() {
+            url = url.replace(&format!("{{{name}}}"), &variable.default);
+        }
+        url
+    }
+}
+
+/// Variable of a server URL template (v3)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerVariable {
+    /// Value used when none is given
+    pub default: String,
+
+    /// Values the variable may take
+    pub r#enum: Option<Vec<String>>,
+
+    /// Variable description
+    pub description: Option<String>,
 }
 
 /// Path item (endpoint)
@@ -111,6 +151,21 @@ pub struct PathItem {
     pub parameters: Option<Vec<Parameter>>,
 }
 
+impl PathItem {
+    /// The item's operations, as (method, operation)
+    pub fn operations(&self) -> impl Iterator<Item = (&'static str, &Operation)> {
+        [
+            ("get", &self.get),
+            ("post", &self.post),
+            ("put", &self.put),
+            ("delete", &self.delete),
+            ("patch", &self.patch),
+        ]
+        .into_iter()
+        .filter_map(|(method, operation)| operation.as_ref().map(|operation| (method, operation)))
+    }
+}
+
 /// API operation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Operation {
@@ -127,12 +182,15 @@ pub struct Operation {
     pub parameters: Option<Vec<Parameter>>,
 
     /// Request body
+    #[serde(rename = "requestBody")]
     pub request_body: Option<RequestBody>,
 
     /// Responses
+    #[serde(default)]
     pub responses: HashMap<String, Response>,
 
     /// Operation ID
+    #[serde(rename = "operationId")]
     pub operation_id: Option<String>,
 }
 
@@ -234,10 +292,13 @@ pub struct Components {
 
     /// Component examples
     pub examples: Option<HashMap<String, Example>>,
+
+    /// Component callbacks, by name, then by URL expression
+    pub callbacks: Option<HashMap<String, HashMap<String, PathItem>>>,
 }
 
 /// Schema
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Schema {
     /// Schema type
     pub r#type: Option<String>,
@@ -258,6 +319,7 @@ pub struct Schema {
     pub items: Option<Box<Schema>>,
 
     /// Schema reference
+    #[serde(rename = "$ref")]
     pub r#ref: Option<String>,
 
     /// Schema allOf
@@ -21,9 +21,17 @@ pub struct RegistryEntry {
     /// Plugin configuration
     pub config: PluginConfig,
 
+    /// JSON Schema the plugin configuration is validated against
+    #[serde(default)]
+    pub config_schema: Option<serde_yaml::Value>,
+
     /// Plugin file path
     pub plugin_path: PathBuf,
 
+    /// Dynamic library of a native plugin
+    #[serde(default)]
+    pub library: Option<PathBuf>,
+
     /// Plugin status
     pub status: RegistryPluginStatus,
 
@@ -121,11 +129,15 @@ impl PluginRegistry {
     async fn load_plugin_manifest(&self, manifest_path: &Path) -> Result<()> {
         let content = tokio::fs::read_to_string(manifest_path).await?;
         let manifest: PluginManifest = serde_yaml::from_str(&content)?;
+        manifest.check_compatibility()?;
 
+        let manifest_dir = manifest_path.parent().unwrap_or(Path::new(""));
         let entry = RegistryEntry {
             metadata: manifest.metadata,
             config: manifest.config,
+            config_schema: manifest.config_schema,
             plugin_path: manifest_path.to_path_buf(),
+            library: manifest.library.map(|library| manifest_dir.join(library)),
             status: RegistryPluginStatus::Available,
             last_loaded: None,
         };
@@ -157,12 +169,30 @@ impl PluginRegistry {
 
     /// Create a plugin from registry entry
     async fn create_plugin_from_entry(&self, entry: &RegistryEntry) -> Result<()> {
-        // For now, we'll create built-in plugins based on the plugin type
-        // In the future, this could load dynamic libraries or WASM modules
+        // Manifests naming a library load a native plugin; the others
+        // configure a built-in plugin of their type
 
         let plugin_type = entry.metadata.id.split(':').next().unwrap_or("unknown");
 
+        if let Some(schema) = &entry.config_schema {
+            self.plugin_manager
+                .declare_config_schema(&entry.config.plugin_id, schema.clone())
+                .await;
+        }
+
         match plugin_type {
+            // Native plugins register their factory under their own id, so
+            // they never replace a built-in one
+            _ if entry.library.is_some() => {
+                let factory = load_native_factory(entry)?;
+                self.plugin_manager
+                    .register_factory(entry.metadata.id.clone(), factory)
+                    .await;
+                self.plugin_manager
+                    .create_plugin(&entry.metadata.id, entry.config.clone())
+                    .await?;
+            }
+            #[cfg(feature = "go-ast")]
             "go-ast" => {
                 // Note: In a real implementation, this would dynamically load the plugin
                 // For now, we'll keep the built-in registration
@@ -185,6 +215,7 @@ impl PluginRegistry {
                     .create_plugin("crd", entry.config.clone())
                     .await?;
             }
+            #[cfg(feature = "openapi")]
             "openapi" => {
                 // Note: In a real implementation, this would dynamically load the plugin
                 // For now, we'll keep the built-in registration
@@ -201,6 +232,13 @@ impl PluginRegistry {
             }
         }
 
+        // Subcommands declared in the manifest are mounted under `x <plugin>`
+        if entry.metadata.capabilities.contains(&PluginCapability::Cli) {
+            self.plugin_manager
+                .declare_commands(&entry.config.plugin_id, entry.metadata.commands.clone())
+                .await;
+        }
+
         Ok(())
     }
 
@@ -269,33 +307,19 @@ impl PluginRegistry {
     }
 }
 
-/// Plugin manifest file structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PluginManifest {
-    /// Plugin metadata
-    pub metadata: PluginMetadata,
-
-    /// Plugin configuration
-    pub config: PluginConfig,
-
-    /// Plugin dependencies
-    pub dependencies: Option<Vec<String>>,
-
-    /// Plugin requirements
-    pub requirements: Option<PluginRequirements>,
+/// Load the factory of a native plugin from its library
+#[cfg(feature = "native-plugins")]
+fn load_native_factory(entry: &RegistryEntry) -> Result<Box<dyn PluginFactory>> {
+    let library = entry.library.as_deref().unwrap_or(Path::new(""));
+    gensonnet_plugin::native::load_factory(library)
 }
 
-/// Plugin requirements
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PluginRequirements {
-    /// Minimum tool version
-    pub min_tool_version: Option<String>,
-
-    /// Required capabilities
-    pub required_capabilities: Option<Vec<PluginCapability>>,
-
-    /// Required dependencies
-    pub required_dependencies: Option<Vec<String>>,
+#[cfg(not(feature = "native-plugins"))]
+fn load_native_factory(entry: &RegistryEntry) -> Result<Box<dyn PluginFactory>> {
+    Err(anyhow::anyhow!(
+        "Plugin {} is a native library, but this gensonnet was built without the native-plugins feature",
+        entry.metadata.id
+    ))
 }
 
 /// Built-in plugin loader
@@ -305,9 +329,12 @@ impl BuiltinPluginLoader {
     /// Load built-in plugins
     pub async fn load_builtin_plugins(plugin_manager: &Arc<PluginManager>) -> Result<()> {
         // Register Go AST plugin factory
-        let go_ast_factory = Box::new(crate::plugin::ast::GoAstPluginFactory);
+        #[cfg(feature = "go-ast")]
         plugin_manager
-            .register_factory("go-ast".to_string(), go_ast_factory)
+            .register_factory(
+                "go-ast".to_string(),
+                Box::new(crate::plugin::ast::GoAstPluginFactory),
+            )
             .await;
 
         // Register CRD plugin factory
@@ -317,9 +344,21 @@ impl BuiltinPluginLoader {
             .await;
 
         // Register OpenAPI plugin factory
-        let openapi_factory = Box::new(crate::plugin::openapi::OpenApiPluginFactory);
+        #[cfg(feature = "openapi")]
+        plugin_manager
+            .register_factory(
+                "openapi".to_string(),
+                Box::new(crate::plugin::openapi::OpenApiPluginFactory),
+            )
+            .await;
+
+        // Register CloudFormation plugin factory
+        #[cfg(feature = "cloudformation")]
         plugin_manager
-            .register_factory("openapi".to_string(), openapi_factory)
+            .register_factory(
+                "cloudformation".to_string(),
+                Box::new(crate::plugin::cloudformation::CloudFormationPluginFactory),
+            )
             .await;
 
         // - Additional plugin factories can be added here as needed
@@ -329,7 +368,7 @@ impl BuiltinPluginLoader {
 
     /// Create built-in plugin configurations
     pub fn get_builtin_plugin_configs() -> Vec<PluginConfig> {
-        vec![
+        let mut configs = vec![
             PluginConfig {
                 plugin_id: "go-ast:builtin".to_string(),
                 config: serde_yaml::Value::Null,
@@ -357,10 +396,31 @@ impl BuiltinPluginLoader {
                     PluginCapability::Validation,
                 ],
             },
-        ]
+            PluginConfig {
+                plugin_id: "cloudformation:builtin".to_string(),
+                config: serde_yaml::Value::Null,
+                enabled_capabilities: vec![
+                    PluginCapability::Parse,
+                    PluginCapability::SchemaExtraction,
+                ],
+            },
+        ];
+        configs.retain(|config| builtin_plugin_available(&config.plugin_id));
+        configs
     }
 }
 
+/// Whether a built-in plugin was compiled into this build
+///
+/// The Go AST, OpenAPI and CloudFormation plugins sit behind the `go-ast`,
+/// `openapi` and `cloudformation` features.
+pub fn builtin_plugin_available(plugin_id: &str) -> bool {
+    let plugin_type = plugin_id.split(':').next().unwrap_or_default();
+    (plugin_type != "go-ast" || cfg!(feature = "go-ast"))
+        && (plugin_type != "openapi" || cfg!(feature = "openapi"))
+        && (plugin_type != "cloudformation" || cfg!(feature = "cloudformation"))
+}
+
 /// Plugin discovery service
 pub struct PluginDiscoveryService {
     /// Registry instance
@@ -465,6 +525,28 @@ mod tests {
         assert!(!configs.is_empty());
     }
 
+    #[test]
+    fn test_builtin_plugin_availability_follows_features() {
+        let ids: Vec<_> = BuiltinPluginLoader::get_builtin_plugin_configs()
+            .into_iter()
+            .map(|config| config.plugin_id)
+            .collect();
+
+        assert!(ids.contains(&"crd:builtin".to_string()));
+        assert_eq!(
+            ids.contains(&"go-ast:builtin".to_string()),
+            cfg!(feature = "go-ast")
+        );
+        assert_eq!(
+            ids.contains(&"openapi:builtin".to_string()),
+            cfg!(feature = "openapi")
+        );
+        assert_eq!(
+            ids.contains(&"cloudformation:builtin".to_string()),
+            cfg!(feature = "cloudformation")
+        );
+    }
+
     #[tokio::test]
     async fn test_plugin_discovery_service() {
         let _temp_dir = TempDir::new().unwrap();
@@ -475,4 +557,95 @@ mod tests {
         // Test with a non-existent directory (should not fail)
         discovery_service.discover_and_load().await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_manifest_config_schema() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("plugin.yaml"),
+            r#"
+metadata:
+  id: "crd:naming"
+  name: Naming
+  version: "1.0.0"
+  description: Prefixes kinds
+  supported_types: [crd]
+  capabilities: [Parse]
+config:
+  plugin_id: "crd:naming"
+  config:
+    prefix: 42
+  enabled_capabilities: [Parse]
+config_schema:
+  type: object
+  properties:
+    prefix: {type: string}
+dependencies: null
+requirements: null
+"#,
+        )
+        .unwrap();
+
+        let registry = PluginRegistry::new(Arc::new(PluginManager::new()));
+        registry
+            .add_plugin_directory(temp_dir.path().to_path_buf())
+            .await;
+        registry.discover_plugins().await.unwrap();
+        registry.load_plugin("crd:naming").await.unwrap();
+
+        let entry = registry.get_plugin("crd:naming").await.unwrap();
+        match entry.status {
+            RegistryPluginStatus::Error(message) => {
+                assert!(message.contains("config.prefix: expected string, got integer"))
+            }
+            status => panic!("unexpected status {status:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_native_plugin_manifest() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("plugin.yaml"),
+            r#"
+metadata:
+  id: "crd:fast"
+  name: Fast
+  version: "1.0.0"
+  description: Native CRD processing
+  supported_types: [crd]
+  capabilities: [Parse]
+config:
+  plugin_id: "crd:fast"
+  config: null
+  enabled_capabilities: [Parse]
+library: lib/libfast.so
+dependencies: null
+requirements: null
+"#,
+        )
+        .unwrap();
+
+        let registry = PluginRegistry::new(Arc::new(PluginManager::new()));
+        registry
+            .add_plugin_directory(temp_dir.path().to_path_buf())
+            .await;
+        registry.discover_plugins().await.unwrap();
+        registry.load_plugin("crd:fast").await.unwrap();
+
+        let entry = registry.get_plugin("crd:fast").await.unwrap();
+        assert_eq!(entry.library, Some(temp_dir.path().join("lib/libfast.so")));
+        // Never falls back to the built-in plugin of its type
+        let expected = if cfg!(feature = "native-plugins") {
+            "Failed to load native plugin"
+        } else {
+            "built without the native-plugins feature"
+        };
+        match entry.status {
+            RegistryPluginStatus::Error(message) => {
+                assert!(message.contains(expected), "{message}")
+            }
+            status => panic!("unexpected status {status:?}"),
+        }
+    }
 }
@@ -1113,21 +1113,18 @@ impl PluginTestRunner {
 
     /// Test shared state integration
     async fn test_shared_state_integration(&self, context: &PluginContext) -> Result<()> {
-        // Test setting and getting shared state values
-        let test_key = "integration_test_key";
-        let test_value = serde_yaml::Value::String("integration_test_value".to_string());
-
-        // Set a value
+        // Write the plugin's state, then read it back as another plugin
         context
-            .set_shared_value(test_key.to_string(), test_value.clone())
-            .await;
+            .state::<Vec<String>>()
+            .write()
+            .await
+            .push("integration_test_value".to_string());
 
-        // Get the value back
-        let retrieved_value = context.get_shared_value(test_key).await;
+        let retrieved_value = context.state_of::<Vec<String>>(&context.config.plugin_id);
 
         match retrieved_value {
             Some(value) => {
-                if value == test_value {
+                if value.read().await.last().map(String::as_str) == Some("integration_test_value") {
                     Ok(())
                 } else {
                     Err(anyhow::anyhow!(
@@ -0,0 +1,313 @@
+//! Plugin output verification
+//!
+//! Files a plugin reports as generated are checked before the source's
+//! staging directory is published: they must lie inside the staging output
+//! directory, be non-empty and, when `plugins.output_verification.syntax_check`
+//! names a command, parse as Jsonnet. Rejected files in the staging directory
+//! are moved to `<output.base_path>/.rejected/<source>/` next to a
+//! `diagnostics.json` explaining why each was rejected; files outside of it
+//! are reported but never moved, as they may belong to another source.
+
+use crate::config::PluginOutputConfig;
+use crate::utils;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+/// Directory of quarantined plugin output, relative to the output base path
+pub const REJECTED_DIR: &str = ".rejected";
+
+/// Diagnostics written next to a source's quarantined files
+pub const DIAGNOSTICS_FILE: &str = "diagnostics.json";
+
+/// A plugin-produced file that failed verification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rejection {
+    /// File as reported by the plugin
+    pub file: PathBuf,
+    /// Plugin that produced the file
+    pub plugin: String,
+    /// Why the file was rejected
+    pub reason: String,
+    /// Where the file was moved to, if it existed
+    pub quarantined_at: Option<PathBuf>,
+}
+
+/// Outcome of verifying the output of a source's plugins
+#[derive(Debug, Clone, Default)]
+pub struct Verification {
+    /// Files that passed verification, with the plugin that produced each
+    pub kept: Vec<(String, PathBuf)>,
+    /// Files that were rejected
+    pub rejected: Vec<Rejection>,
+}
+
+impl Verification {
+    /// Plugin that produced each kept file, by the file's path
+    pub fn file_plugins(&self) -> BTreeMap<PathBuf, String> {
+        self.kept
+            .iter()
+            .map(|(plugin_id, file)| (file.clone(), plugin_id.clone()))
+            .collect()
+    }
+
+    /// Describe each rejection as a source error
+    pub fn errors(&self) -> Vec<String> {
+        self.rejected
+            .iter()
+            .map(|rejection| match &rejection.quarantined_at {
+                Some(path) => format!(
+                    "Rejected {} from plugin {}: {} (moved to {})",
+                    rejection.file.display(),
+                    rejection.plugin,
+                    rejection.reason,
+                    path.display()
+                ),
+                None => format!(
+                    "Rejected {} from plugin {}: {}",
+                    rejection.file.display(),
+                    rejection.plugin,
+                    rejection.reason
+                ),
+            })
+            .collect()
+    }
+}
+
+/// Verifies plugin-produced files and quarantines the invalid ones
+#[derive(Debug, Clone)]
+pub struct OutputVerifier {
+    config: PluginOutputConfig,
+    base_path: PathBuf,
+}
+
+impl OutputVerifier {
+    pub fn new(config: PluginOutputConfig, base_path: impl Into<PathBuf>) -> Self {
+        Self {
+            config,
+            base_path: base_path.into(),
+        }
+    }
+
+    /// Directory a source's rejected files are moved to
+    pub fn rejected_dir(&self, source_name: &str) -> PathBuf {
+        self.base_path.join(REJECTED_DIR).join(source_name)
+    }
+
+    /// Verify the files each plugin reported, relative to `staging`
+    ///
+    /// Quarantine left by an earlier run of the source is cleared first, so
+    /// `.rejected/<source>/` always reflects the latest run.
+    pub async fn verify(
+        &self,
+        source_name: &str,
+        staging: &Path,
+        files: &[(String, PathBuf)],
+    ) -> Result<Verification> {
+        let mut verification = Verification::default();
+        if !self.config.enabled {
+            verification.kept = files.to_vec();
+            return Ok(verification);
+        }
+
+        let rejected_dir = self.rejected_dir(source_name);
+        if rejected_dir.exists() {
+            std::fs::remove_dir_all(&rejected_dir)?;
+        }
+
+        for (plugin_id, file) in files {
+            let path = staging.join(file);
+            let Some(reason) = self.check(&path, staging).await? else {
+                verification.kept.push((plugin_id.clone(), file.clone()));
+                continue;
+            };
+
+            let quarantined_at = match path.strip_prefix(staging) {
+                Ok(relative) if utils::is_within_base(&path, staging) && path.is_file() => {
+                    let target = rejected_dir.join(relative);
+                    if let Some(parent) = target.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    std::fs::rename(&path, &target)
+                        .or_else(|_| std::fs::copy(&path, &target).map(|_| ()))?;
+                    let _ = std::fs::remove_file(&path);
+                    Some(target)
+                }
+                _ => None,
+            };
+            tracing::warn!(
+                "Rejected {} from plugin {}: {}",
+                file.display(),
+                plugin_id,
+                reason
+            );
+            verification.rejected.push(Rejection {
+                file: file.clone(),
+                plugin: plugin_id.clone(),
+                reason,
+                quarantined_at,
+            });
+        }
+
+        if !verification.rejected.is_empty() {
+            std::fs::create_dir_all(&rejected_dir)?;
+            std::fs::write(
+                rejected_dir.join(DIAGNOSTICS_FILE),
+                serde_json::to_string_pretty(&verification.rejected)?,
+            )?;
+        }
+        Ok(verification)
+    }
+
+    /// Why a file fails verification, if it does
+    async fn check(&self, path: &Path, staging: &Path) -> Result<Option<String>> {
+        if !utils::is_within_base(path, staging) {
+            return Ok(Some("outside the plugin output directory".to_string()));
+        }
+        let metadata = match std::fs::metadata(path) {
+            Ok(metadata) if metadata.is_file() => metadata,
+            Ok(_) => return Ok(Some("not a regular file".to_string())),
+            Err(_) => return Ok(Some("does not exist".to_string())),
+        };
+        if metadata.len() == 0 {
+            return Ok(Some("empty".to_string()));
+        }
+
+        let is_jsonnet = matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("jsonnet" | "libsonnet")
+        );
+        match &self.config.syntax_check {
+            Some(command) if is_jsonnet => self.syntax_check(command, path).await,
+            _ => Ok(None),
+        }
+    }
+
+    /// Run the syntax check command with the file as its last argument
+    async fn syntax_check(&self, command: &str, path: &Path) -> Result<Option<String>> {
+        let output = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(format!("{command} \"$1\""))
+            .arg("sh")
+            .arg(path)
+            .stdin(Stdio::null())
+            .output()
+            .await
+            .map_err(|e| anyhow!("Failed to run syntax check `{}`: {}", command, e))?;
+        if output.status.success() {
+            return Ok(None);
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = stderr.trim();
+        Ok(Some(if detail.is_empty() {
+            format!("does not parse as Jsonnet ({})", output.status)
+        } else {
+            format!("does not parse as Jsonnet: {detail}")
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_verify_quarantines_rejected_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().join("generated");
+        let staging = temp_dir.path().join("staging");
+        std::fs::create_dir_all(staging.join("v1")).unwrap();
+        std::fs::write(staging.join("v1/good.libsonnet"), "{ ok: true }\n").unwrap();
+        std::fs::write(staging.join("v1/empty.libsonnet"), "").unwrap();
+        std::fs::write(staging.join("v1/broken.libsonnet"), "{ ok: \n").unwrap();
+
+        let verifier = OutputVerifier::new(
+            PluginOutputConfig {
+                enabled: true,
+                syntax_check: Some("! grep -q ': $'".to_string()),
+            },
+            &base,
+        );
+        let files: Vec<(String, PathBuf)> = [
+            "v1/good.libsonnet",
+            "v1/empty.libsonnet",
+            "v1/broken.libsonnet",
+            "v1/missing.libsonnet",
+        ]
+        .into_iter()
+        .map(|file| ("crd:builtin".to_string(), PathBuf::from(file)))
+        .collect();
+        let verification = verifier.verify("widgets", &staging, &files).await.unwrap();
+
+        assert_eq!(
+            verification.kept,
+            [(
+                "crd:builtin".to_string(),
+                PathBuf::from("v1/good.libsonnet")
+            )]
+        );
+        let reasons: Vec<&str> = verification
+            .rejected
+            .iter()
+            .map(|rejection| rejection.reason.as_str())
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                "empty",
+                "does not parse as Jsonnet (exit status: 1)",
+                "does not exist"
+            ]
+        );
+
+        let rejected_dir = base.join(REJECTED_DIR).join("widgets");
+        assert!(rejected_dir.join("v1/broken.libsonnet").exists());
+        assert!(!staging.join("v1/broken.libsonnet").exists());
+        let diagnostics: Vec<Rejection> = serde_json::from_str(
+            &std::fs::read_to_string(rejected_dir.join(DIAGNOSTICS_FILE)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(diagnostics, verification.rejected);
+
+        // A clean run clears the quarantine of the previous one
+        let verification = verifier
+            .verify("widgets", &staging, &files[..1])
+            .await
+            .unwrap();
+        assert!(verification.rejected.is_empty());
+        assert!(!rejected_dir.exists());
+    }
+
+    #[tokio::test]
+    async fn test_verify_leaves_files_outside_staging_alone() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().join("generated");
+        let staging = temp_dir.path().join("staging");
+        std::fs::create_dir_all(base.join("other")).unwrap();
+        std::fs::create_dir_all(&staging).unwrap();
+        std::fs::write(base.join("other/index.libsonnet"), "{}\n").unwrap();
+
+        let verifier = OutputVerifier::new(
+            PluginOutputConfig {
+                enabled: true,
+                syntax_check: None,
+            },
+            &base,
+        );
+        let files = [(
+            "crd:builtin".to_string(),
+            PathBuf::from("../generated/other/index.libsonnet"),
+        )];
+        let verification = verifier.verify("widgets", &staging, &files).await.unwrap();
+
+        assert_eq!(
+            verification.rejected[0].reason,
+            "outside the plugin output directory"
+        );
+        assert_eq!(verification.rejected[0].quarantined_at, None);
+        assert!(base.join("other/index.libsonnet").exists());
+    }
+}
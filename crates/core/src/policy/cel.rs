@@ -0,0 +1,500 @@
+//! A small subset of the Common Expression Language (CEL)
+//!
+//! Supported: literals (null, bool, int, double, string, list), field selection
+//! (`a.b`, `a["b"]`, `a[0]`), `!`, `-`, `==`, `!=`, `<`, `<=`, `>`, `>=`, `in`,
+//! `&&`, `||`, the `has()` macro, `size()` and the string methods `startsWith`,
+//! `endsWith` and `contains`. Values are represented as YAML values.
+
+use anyhow::{anyhow, Result};
+use serde_yaml::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// A parsed CEL expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    List(Vec<Expr>),
+    Ident(String),
+    Select(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Call(Option<Box<Expr>>, String, Vec<Expr>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+/// Binary operators
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Int(i64),
+    Double(f64),
+    Str(String),
+    Ident(String),
+    Op(&'static str),
+}
+
+/// Parse a CEL expression
+pub fn parse(source: &str) -> Result<Expr> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.parse_or()?;
+    if parser.pos != parser.tokens.len() {
+        return Err(anyhow!(
+            "Unexpected token {:?} in expression '{}'",
+            parser.tokens[parser.pos],
+            source
+        ));
+    }
+    Ok(expr)
+}
+
+/// Evaluate an expression against a set of variables
+pub fn evaluate(expr: &Expr, vars: &HashMap<&str, Value>) -> Result<Value> {
+    match expr {
+        Expr::Literal(value) => Ok(value.clone()),
+        Expr::List(items) => Ok(Value::Sequence(
+            items
+                .iter()
+                .map(|item| evaluate(item, vars))
+                .collect::<Result<_>>()?,
+        )),
+        Expr::Ident(name) => vars
+            .get(name.as_str())
+            .cloned()
+            .ok_or_else(|| anyhow!("undeclared reference to '{}'", name)),
+        Expr::Select(target, field) => {
+            let target = evaluate(target, vars)?;
+            target
+                .get(field.as_str())
+                .cloned()
+                .ok_or_else(|| anyhow!("no such key: {}", field))
+        }
+        Expr::Index(target, index) => {
+            let target = evaluate(target, vars)?;
+            match evaluate(index, vars)? {
+                Value::String(key) => target
+                    .get(key.as_str())
+                    .cloned()
+                    .ok_or_else(|| anyhow!("no such key: {}", key)),
+                Value::Number(n) => n
+                    .as_u64()
+                    .and_then(|i| target.get(i as usize))
+                    .cloned()
+                    .ok_or_else(|| anyhow!("index out of range: {}", n)),
+                other => Err(anyhow!("invalid index {:?}", other)),
+            }
+        }
+        Expr::Call(target, function, args) => call(target.as_deref(), function, args, vars),
+        Expr::Not(inner) => match evaluate(inner, vars)? {
+            Value::Bool(b) => Ok(Value::Bool(!b)),
+            other => Err(anyhow!("'!' expects a bool, got {:?}", other)),
+        },
+        Expr::Neg(inner) => match evaluate(inner, vars)? {
+            Value::Number(n) if n.is_i64() => n
+                .as_i64()
+                .and_then(i64::checked_neg)
+                .map(Value::from)
+                .ok_or_else(|| anyhow!("integer overflow negating {}", n)),
+            Value::Number(n) => Ok(Value::from(-n.as_f64().unwrap_or_default())),
+            other => Err(anyhow!("'-' expects a number, got {:?}", other)),
+        },
+        Expr::Binary(BinaryOp::And, left, right) => logical(left, right, false, vars),
+        Expr::Binary(BinaryOp::Or, left, right) => logical(left, right, true, vars),
+        Expr::Binary(op, left, right) => {
+            let left = evaluate(left, vars)?;
+            let right = evaluate(right, vars)?;
+            let result = match op {
+                BinaryOp::Eq => values_equal(&left, &right),
+                BinaryOp::Ne => !values_equal(&left, &right),
+                BinaryOp::In => match &right {
+                    Value::Sequence(items) => items.iter().any(|item| values_equal(item, &left)),
+                    Value::Mapping(map) => map.contains_key(&left),
+                    other => return Err(anyhow!("'in' expects a list or map, got {:?}", other)),
+                },
+                BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
+                    let ordering = compare(&left, &right)?;
+                    match op {
+                        BinaryOp::Lt => ordering == Ordering::Less,
+                        BinaryOp::Le => ordering != Ordering::Greater,
+                        BinaryOp::Gt => ordering == Ordering::Greater,
+                        _ => ordering != Ordering::Less,
+                    }
+                }
+                BinaryOp::And | BinaryOp::Or => unreachable!(),
+            };
+            Ok(Value::Bool(result))
+        }
+    }
+}
+
+/// Evaluate `&&`/`||` with CEL's commutative error handling
+fn logical(
+    left: &Expr,
+    right: &Expr,
+    short_circuit_on: bool,
+    vars: &HashMap<&str, Value>,
+) -> Result<Value> {
+    let as_bool = |value: Result<Value>| -> Result<bool> {
+        match value? {
+            Value::Bool(b) => Ok(b),
+            other => Err(anyhow!("logical operator expects a bool, got {:?}", other)),
+        }
+    };
+
+    let left = as_bool(evaluate(left, vars));
+    if matches!(left, Ok(b) if b == short_circuit_on) {
+        return Ok(Value::Bool(short_circuit_on));
+    }
+
+    let right = as_bool(evaluate(right, vars));
+    match (left, right) {
+        (_, Ok(b)) if b == short_circuit_on => Ok(Value::Bool(short_circuit_on)),
+        (Ok(_), Ok(b)) => Ok(Value::Bool(b)),
+        (Err(e), _) | (_, Err(e)) => Err(e),
+    }
+}
+
+/// Evaluate a function, method or macro call
+fn call(
+    target: Option<&Expr>,
+    function: &str,
+    args: &[Expr],
+    vars: &HashMap<&str, Value>,
+) -> Result<Value> {
+    match (target, function, args) {
+        (None, "has", [Expr::Select(inner, field)]) => {
+            let inner = evaluate(inner, vars)?;
+            Ok(Value::Bool(inner.get(field.as_str()).is_some()))
+        }
+        (None, "has", _) => Err(anyhow!("has() expects a field selection")),
+        (None, "size", [arg]) => size(&evaluate(arg, vars)?),
+        (Some(target), "size", []) => size(&evaluate(target, vars)?),
+        (Some(target), "startsWith" | "endsWith" | "contains", [arg]) => {
+            let target = evaluate(target, vars)?;
+            let arg = evaluate(arg, vars)?;
+            match (target.as_str(), arg.as_str()) {
+                (Some(s), Some(a)) => Ok(Value::Bool(match function {
+                    "startsWith" => s.starts_with(a),
+                    "endsWith" => s.ends_with(a),
+                    _ => s.contains(a),
+                })),
+                _ => Err(anyhow!("{}() expects string arguments", function)),
+            }
+        }
+        _ => Err(anyhow!("unsupported function '{}'", function)),
+    }
+}
+
+fn size(value: &Value) -> Result<Value> {
+    let len = match value {
+        Value::String(s) => s.chars().count(),
+        Value::Sequence(items) => items.len(),
+        Value::Mapping(map) => map.len(),
+        other => return Err(anyhow!("size() not supported for {:?}", other)),
+    };
+    Ok(Value::from(len as i64))
+}
+
+fn values_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        _ => left == right,
+    }
+}
+
+fn compare(left: &Value, right: &Value) -> Result<Ordering> {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .zip(b.as_f64())
+            .and_then(|(a, b)| a.partial_cmp(&b))
+            .ok_or_else(|| anyhow!("cannot compare {} and {}", a, b)),
+        (Value::String(a), Value::String(b)) => Ok(a.cmp(b)),
+        _ => Err(anyhow!("cannot compare {:?} and {:?}", left, right)),
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    const OPERATORS: &[&str] = &[
+        "==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", "-", "(", ")", "[", "]", ".", ",",
+    ];
+
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            tokens.push(if text.contains('.') {
+                Token::Double(text.parse()?)
+            } else {
+                Token::Int(text.parse()?)
+            });
+        } else if c == '"' || c == '\'' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err(anyhow!("Unterminated string in '{}'", source)),
+                    Some(&ch) if ch == c => break,
+                    Some('\\') => {
+                        i += 1;
+                        match chars.get(i) {
+                            Some('n') => text.push('\n'),
+                            Some(&escaped) => text.push(escaped),
+                            None => return Err(anyhow!("Unterminated string in '{}'", source)),
+                        }
+                    }
+                    Some(&ch) => text.push(ch),
+                }
+                i += 1;
+            }
+            i += 1;
+            tokens.push(Token::Str(text));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(**op))
+                .ok_or_else(|| anyhow!("Unexpected character '{}' in '{}'", c, source))?;
+            tokens.push(Token::Op(op));
+            i += op.len();
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_op(&self, op: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(Token::Op(o)) if *o == op)
+    }
+
+    fn eat_op(&mut self, op: &str) -> bool {
+        if self.peek_op(op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_op(&mut self, op: &str) -> Result<()> {
+        if self.eat_op(op) {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Expected '{}', found {:?}",
+                op,
+                self.tokens.get(self.pos)
+            ))
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut left = self.parse_and()?;
+        while self.eat_op("||") {
+            let right = self.parse_and()?;
+            left = Expr::Binary(BinaryOp::Or, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut left = self.parse_relation()?;
+        while self.eat_op("&&") {
+            let right = self.parse_relation()?;
+            left = Expr::Binary(BinaryOp::And, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_relation(&mut self) -> Result<Expr> {
+        let left = self.parse_unary()?;
+        let op = match self.tokens.get(self.pos) {
+            Some(Token::Op("==")) => BinaryOp::Eq,
+            Some(Token::Op("!=")) => BinaryOp::Ne,
+            Some(Token::Op("<")) => BinaryOp::Lt,
+            Some(Token::Op("<=")) => BinaryOp::Le,
+            Some(Token::Op(">")) => BinaryOp::Gt,
+            Some(Token::Op(">=")) => BinaryOp::Ge,
+            Some(Token::Ident(word)) if word == "in" => BinaryOp::In,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        let right = self.parse_unary()?;
+        Ok(Expr::Binary(op, Box::new(left), Box::new(right)))
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        if self.eat_op("!") {
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.eat_op("-") {
+            return Ok(Expr::Neg(Box::new(self.parse_unary()?)));
+        }
+        self.parse_postfix()
+    }
+
+    fn parse_postfix(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_primary()?;
+        loop {
+            if self.eat_op(".") {
+                let field = match self.tokens.get(self.pos) {
+                    Some(Token::Ident(name)) => name.clone(),
+                    other => return Err(anyhow!("Expected field name, found {:?}", other)),
+                };
+                self.pos += 1;
+                if self.eat_op("(") {
+                    let args = self.parse_args()?;
+                    expr = Expr::Call(Some(Box::new(expr)), field, args);
+                } else {
+                    expr = Expr::Select(Box::new(expr), field);
+                }
+            } else if self.eat_op("[") {
+                let index = self.parse_or()?;
+                self.expect_op("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn parse_args(&mut self) -> Result<Vec<Expr>> {
+        let mut args = Vec::new();
+        if self.eat_op(")") {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.parse_or()?);
+            if self.eat_op(")") {
+                return Ok(args);
+            }
+            self.expect_op(",")?;
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| anyhow!("Unexpected end of expression"))?;
+        self.pos += 1;
+
+        match token {
+            Token::Int(n) => Ok(Expr::Literal(Value::from(n))),
+            Token::Double(n) => Ok(Expr::Literal(Value::from(n))),
+            Token::Str(s) => Ok(Expr::Literal(Value::String(s))),
+            Token::Ident(name) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                _ if self.eat_op("(") => Ok(Expr::Call(None, name, self.parse_args()?)),
+                _ => Ok(Expr::Ident(name)),
+            },
+            Token::Op("(") => {
+                let expr = self.parse_or()?;
+                self.expect_op(")")?;
+                Ok(expr)
+            }
+            Token::Op("[") => {
+                let mut items = Vec::new();
+                if !self.eat_op("]") {
+                    loop {
+                        items.push(self.parse_or()?);
+                        if self.eat_op("]") {
+                            break;
+                        }
+                        self.expect_op(",")?;
+                    }
+                }
+                Ok(Expr::List(items))
+            }
+            other => Err(anyhow!("Unexpected token {:?}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(source: &str, this: &str) -> Result<Value> {
+        let vars = HashMap::from([("self", serde_yaml::from_str(this).unwrap())]);
+        evaluate(&parse(source)?, &vars)
+    }
+
+    #[test]
+    fn test_evaluate_expressions() {
+        let field = "{type: string, maxLength: 64, enum: [a, b]}";
+        assert_eq!(
+            eval("self.type != 'string' || has(self.maxLength)", field).unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            eval("self.maxLength <= 63", field).unwrap(),
+            Value::Bool(false)
+        );
+        assert_eq!(
+            eval("'a' in self.enum && size(self.enum) == 2", field).unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            eval("self.type.startsWith(\"str\")", field).unwrap(),
+            Value::Bool(true)
+        );
+    }
+
+    #[test]
+    fn test_errors_are_absorbed_by_logical_operators() {
+        // `self.type` is missing, but the right-hand side decides the result
+        assert_eq!(
+            eval("self.type == 'string' || true", "{}").unwrap(),
+            Value::Bool(true)
+        );
+        assert!(eval("self.type == 'string'", "{}").is_err());
+        assert!(parse("self.type ==").is_err());
+    }
+
+    #[test]
+    fn test_negation_overflow_is_an_error() {
+        assert_eq!(eval("-self", "5").unwrap(), Value::from(-5));
+        let err = eval("-self", "-9223372036854775808").unwrap_err();
+        assert!(err.to_string().contains("overflow"), "{err}");
+    }
+}
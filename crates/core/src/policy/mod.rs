@@ -0,0 +1,266 @@
+//! Policy rules evaluated over extracted schemas
+
+pub mod cel;
+
+use crate::config::{PolicyAction, PolicyConfig, PolicyRule, PolicyScope};
+use anyhow::{anyhow, Result};
+use serde_yaml::{Mapping, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A violated policy rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyViolation {
+    /// Name of the violated rule
+    pub rule: String,
+
+    /// Name of the schema that violated the rule
+    pub schema: String,
+
+    /// Field path for field-scoped rules
+    pub path: Option<String>,
+
+    /// Description of the violation
+    pub message: String,
+
+    /// Configured action for the rule
+    pub action: PolicyAction,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(
+                f,
+                "Policy '{}' violated by {}.{}: {}",
+                self.rule, self.schema, path, self.message
+            ),
+            None => write!(
+                f,
+                "Policy '{}' violated by {}: {}",
+                self.rule, self.schema, self.message
+            ),
+        }
+    }
+}
+
+/// Evaluates configured policy rules against schemas
+pub struct PolicyEngine {
+    rules: Vec<(PolicyRule, cel::Expr)>,
+}
+
+impl PolicyEngine {
+    /// Compile the rules of a policy configuration
+    pub fn new(config: &PolicyConfig) -> Result<Self> {
+        let rules = config
+            .rules
+            .iter()
+            .map(|rule| {
+                cel::parse(&rule.expression)
+                    .map(|expr| (rule.clone(), expr))
+                    .map_err(|e| anyhow!("Invalid expression for policy rule {}: {}", rule.name, e))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { rules })
+    }
+
+    /// Whether there are no rules to evaluate
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Evaluate every rule against a schema
+    ///
+    /// `subject` is bound to `self` for schema-scoped rules; field-scoped rules
+    /// walk the properties of `subject.schema`.
+    pub fn evaluate(&self, schema_name: &str, subject: &Value) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+        let mut fields = Vec::new();
+        if let Some(properties) = subject
+            .get("schema")
+            .and_then(|s| s.get("properties"))
+            .and_then(|p| p.as_mapping())
+        {
+            collect_fields("", properties, &mut fields);
+        }
+
+        for (rule, expr) in &self.rules {
+            match rule.scope {
+                PolicyScope::Schema => {
+                    let vars = HashMap::from([("self", subject.clone())]);
+                    if let Some(message) = check(rule, expr, &vars) {
+                        violations.push(violation(rule, schema_name, None, message));
+                    }
+                }
+                PolicyScope::Field => {
+                    for (path, field) in &fields {
+                        let vars = HashMap::from([
+                            ("self", (*field).clone()),
+                            ("path", Value::String(path.clone())),
+                        ]);
+                        if let Some(message) = check(rule, expr, &vars) {
+                            violations.push(violation(
+                                rule,
+                                schema_name,
+                                Some(path.clone()),
+                                message,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        violations
+    }
+}
+
+/// Evaluate a rule, returning a message when it is violated
+fn check(rule: &PolicyRule, expr: &cel::Expr, vars: &HashMap<&str, Value>) -> Option<String> {
+    match cel::evaluate(expr, vars) {
+        Ok(Value::Bool(true)) => None,
+        Ok(Value::Bool(false)) => Some(
+            rule.message
+                .clone()
+                .unwrap_or_else(|| format!("expression '{}' is false", rule.expression)),
+        ),
+        Ok(other) => Some(format!("expression did not return a bool: {other:?}")),
+        Err(e) => Some(format!("evaluation error: {e}")),
+    }
+}
+
+fn violation(
+    rule: &PolicyRule,
+    schema: &str,
+    path: Option<String>,
+    message: String,
+) -> PolicyViolation {
+    PolicyViolation {
+        rule: rule.name.clone(),
+        schema: schema.to_string(),
+        path,
+        message,
+        action: rule.action,
+    }
+}
+
+/// Collect every field below a properties mapping with its dotted path
+fn collect_fields<'a>(
+    prefix: &str,
+    properties: &'a Mapping,
+    fields: &mut Vec<(String, &'a Value)>,
+) {
+    for (name, field) in properties {
+        let Some(name) = name.as_str() else {
+            continue;
+        };
+        let path = if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{prefix}.{name}")
+        };
+
+        fields.push((path.clone(), field));
+
+        if let Some(nested) = field.get("properties").and_then(|p| p.as_mapping()) {
+            collect_fields(&path, nested, fields);
+        }
+        if let Some(nested) = field
+            .get("items")
+            .and_then(|items| items.get("properties"))
+            .and_then(|p| p.as_mapping())
+        {
+            collect_fields(&format!("{path}[]"), nested, fields);
+        }
+    }
+}
+
+/// Build the policy subject for a parsed CRD
+pub fn crd_subject(schema: &crate::CrdSchema) -> Value {
+    let mut subject = Mapping::new();
+    subject.insert("name".into(), schema.name.clone().into());
+    subject.insert("group".into(), schema.group.clone().into());
+    subject.insert("version".into(), schema.version.clone().into());
+    subject.insert("kind".into(), schema.kind.clone().into());
+    subject.insert(
+        "categories".into(),
+        Value::Sequence(schema.categories.iter().cloned().map(Value::from).collect()),
+    );
+    subject.insert("schema".into(), schema.schema.clone());
+    Value::Mapping(subject)
+}
+
+/// Build the policy subject for a schema extracted by a plugin
+pub fn extracted_subject(schema: &crate::ExtractedSchema) -> Value {
+    let mut subject = Mapping::new();
+    for (key, value) in &schema.metadata {
+        subject.insert(key.clone().into(), value.clone());
+    }
+    subject.insert("name".into(), schema.name.clone().into());
+    subject.insert("type".into(), schema.schema_type.clone().into());
+    subject.insert("schema".into(), schema.content.clone());
+    Value::Mapping(subject)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str, scope: PolicyScope, expression: &str, action: PolicyAction) -> PolicyRule {
+        PolicyRule {
+            name: name.to_string(),
+            scope,
+            expression: expression.to_string(),
+            message: None,
+            action,
+        }
+    }
+
+    #[test]
+    fn test_schema_and_field_rules() {
+        let engine = PolicyEngine::new(&PolicyConfig {
+            rules: vec![
+                rule(
+                    "has-categories",
+                    PolicyScope::Schema,
+                    "size(self.categories) > 0",
+                    PolicyAction::Fail,
+                ),
+                rule(
+                    "string-max-length",
+                    PolicyScope::Field,
+                    "!has(self.type) || self.type != 'string' || has(self.maxLength)",
+                    PolicyAction::Warn,
+                ),
+            ],
+        })
+        .unwrap();
+
+        let subject: Value = serde_yaml::from_str(
+            r#"
+name: widgets.example.com
+categories: []
+schema:
+  type: object
+  properties:
+    spec:
+      type: object
+      properties:
+        name: {type: string, maxLength: 63}
+        owner: {type: string}
+"#,
+        )
+        .unwrap();
+
+        let violations = engine.evaluate("widgets.example.com", &subject);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].rule, "has-categories");
+        assert_eq!(violations[0].action, PolicyAction::Fail);
+        assert_eq!(violations[1].path.as_deref(), Some("spec.owner"));
+        assert_eq!(
+            violations[1].to_string(),
+            "Policy 'string-max-length' violated by widgets.example.com.spec.owner: expression '!has(self.type) || self.type != 'string' || has(self.maxLength)' is false"
+        );
+    }
+}
@@ -0,0 +1,343 @@
+//! Profiling the evaluation of generated libraries
+//!
+//! Every resource of a library's `_samples.libsonnet` is evaluated on its own
+//! with a Jsonnet evaluator, and timed. Fields of Jsonnet objects are lazy, so
+//! evaluating only a resource's `apiVersion` and `kind` skips the validation
+//! its `metadata` and `spec` go through; the difference to the full evaluation
+//! is the time validation takes. The time the evaluator needs to start is
+//! measured once and left out. The evaluator is an external binary, `jsonnet`
+//! on the PATH unless another is configured.
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Share of a resource's evaluation time above which validation is a hotspot
+pub const HOTSPOT_SHARE: f64 = 0.5;
+
+/// Evaluation profile of one sample resource
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceProfile {
+    /// Name of the resource's schema
+    pub resource: String,
+
+    /// Milliseconds evaluating the resource takes
+    pub eval_ms: f64,
+
+    /// Milliseconds of that spent validating the resource
+    pub validation_ms: f64,
+
+    /// Peak memory of the evaluator in KiB, where the platform reports it
+    pub peak_memory_kib: Option<u64>,
+}
+
+impl ResourceProfile {
+    /// Share of the evaluation time spent validating
+    pub fn validation_share(&self) -> f64 {
+        if self.eval_ms > 0.0 {
+            (self.validation_ms / self.eval_ms).min(1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Whether validation dominates the resource's evaluation
+    pub fn is_validation_hotspot(&self) -> bool {
+        self.validation_share() >= HOTSPOT_SHARE
+    }
+}
+
+/// Evaluation profile of one library
+#[derive(Debug, Clone, Serialize)]
+pub struct LibraryProfile {
+    /// The profiled `_samples.libsonnet`
+    pub file: PathBuf,
+
+    /// Milliseconds the evaluator takes to start, left out of every resource
+    pub baseline_ms: f64,
+
+    /// Profiles of the library's resources, slowest first
+    pub resources: Vec<ResourceProfile>,
+}
+
+/// Time and peak memory of one evaluation
+struct Measurement {
+    elapsed: Duration,
+    peak_memory_kib: Option<u64>,
+}
+
+/// Profiles sample files with a Jsonnet evaluator
+pub struct LibraryProfiler {
+    evaluator: String,
+    jpaths: Vec<PathBuf>,
+    iterations: usize,
+}
+
+impl LibraryProfiler {
+    /// Create a profiler using the `jsonnet` binary on the PATH
+    pub fn new() -> Self {
+        Self {
+            evaluator: "jsonnet".to_string(),
+            jpaths: Vec::new(),
+            iterations: 3,
+        }
+    }
+
+    /// Use a different evaluator binary (e.g. `jrsonnet`)
+    pub fn with_evaluator(mut self, evaluator: impl Into<String>) -> Self {
+        self.evaluator = evaluator.into();
+        self
+    }
+
+    /// Add library search paths, passed to the evaluator as `-J`
+    pub fn with_jpaths(mut self, jpaths: Vec<PathBuf>) -> Self {
+        self.jpaths = jpaths;
+        self
+    }
+
+    /// Evaluate everything this many times, keeping the fastest run
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations.max(1);
+        self
+    }
+
+    /// Profile the resources of a sample file
+    pub async fn profile(&self, file: &Path) -> Result<LibraryProfile> {
+        let samples = format!(
+            "(import {})",
+            serde_json::to_string(&std::path::absolute(file)?.to_string_lossy())?
+        );
+        let baseline = self.fastest("null").await?.elapsed;
+
+        let mut resources = Vec::new();
+        for resource in self.resource_names(&samples).await? {
+            let instance = format!("{samples}[{}]", serde_json::to_string(&resource)?);
+            let full = self.fastest(&instance).await?;
+            let skeleton = self
+                .fastest(&format!(
+                    "local r = {instance}; {{ apiVersion: r.apiVersion, kind: r.kind }}"
+                ))
+                .await?;
+            resources.push(ResourceProfile {
+                resource,
+                eval_ms: millis(full.elapsed.saturating_sub(baseline)),
+                validation_ms: millis(full.elapsed.saturating_sub(skeleton.elapsed)),
+                peak_memory_kib: full.peak_memory_kib,
+            });
+        }
+        resources.sort_by(|a, b| b.eval_ms.total_cmp(&a.eval_ms));
+
+        Ok(LibraryProfile {
+            file: file.to_path_buf(),
+            baseline_ms: millis(baseline),
+            resources,
+        })
+    }
+
+    /// Names of the resources a sample file holds
+    async fn resource_names(&self, samples: &str) -> Result<Vec<String>> {
+        let output = self
+            .command()
+            .arg("-e")
+            .arg(format!("std.objectFields({samples})"))
+            .output()
+            .await
+            .map_err(|e| self.spawn_error(e))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to evaluate {}: {}",
+                samples,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        serde_json::from_slice(&output.stdout)
+            .map_err(|e| anyhow!("{} did not evaluate to an object: {}", samples, e))
+    }
+
+    /// The fastest of `iterations` evaluations of an expression, with the
+    /// highest peak memory among them
+    async fn fastest(&self, expression: &str) -> Result<Measurement> {
+        let mut fastest = self.measure(expression).await?;
+        for _ in 1..self.iterations {
+            let measurement = self.measure(expression).await?;
+            fastest.elapsed = fastest.elapsed.min(measurement.elapsed);
+            fastest.peak_memory_kib = fastest.peak_memory_kib.max(measurement.peak_memory_kib);
+        }
+        Ok(fastest)
+    }
+
+    /// Evaluate an expression once, reaping the evaluator with `wait4` to
+    /// learn its peak memory
+    #[cfg(unix)]
+    async fn measure(&self, expression: &str) -> Result<Measurement> {
+        use std::io::{Read, Seek};
+        use std::process::Stdio;
+
+        let mut stderr = tempfile::tempfile()?;
+        let mut command = std::process::Command::new(&self.evaluator);
+        for jpath in &self.jpaths {
+            command.arg("-J").arg(jpath);
+        }
+        command
+            .arg("-e")
+            .arg(expression)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(stderr.try_clone()?);
+        let child = command.spawn().map_err(|e| self.spawn_error(e))?;
+        let start = std::time::Instant::now();
+
+        let (status, usage, elapsed) = tokio::task::spawn_blocking(move || {
+            let mut status = 0;
+            // SAFETY: rusage is plain data that wait4 fills in
+            let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+            loop {
+                // SAFETY: the child is ours and has not been reaped; std
+                // never waits for it once this has
+                let pid =
+                    unsafe { libc::wait4(child.id() as libc::pid_t, &mut status, 0, &mut usage) };
+                if pid >= 0 {
+                    return Ok((status, usage, start.elapsed()));
+                }
+                let error = std::io::Error::last_os_error();
+                if error.kind() != std::io::ErrorKind::Interrupted {
+                    return Err(error);
+                }
+            }
+        })
+        .await??;
+
+        if !(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0) {
+            let mut message = String::new();
+            stderr.rewind()?;
+            stderr.read_to_string(&mut message)?;
+            return Err(anyhow!(
+                "Failed to evaluate {}: {}",
+                expression,
+                message.trim()
+            ));
+        }
+
+        // ru_maxrss is in bytes on macOS and KiB elsewhere
+        let max_rss = usage.ru_maxrss.max(0) as u64;
+        let peak_memory_kib = if cfg!(target_os = "macos") {
+            max_rss / 1024
+        } else {
+            max_rss
+        };
+        Ok(Measurement {
+            elapsed,
+            peak_memory_kib: Some(peak_memory_kib),
+        })
+    }
+
+    /// Evaluate an expression once
+    #[cfg(not(unix))]
+    async fn measure(&self, expression: &str) -> Result<Measurement> {
+        let start = std::time::Instant::now();
+        let output = self
+            .command()
+            .arg("-e")
+            .arg(expression)
+            .output()
+            .await
+            .map_err(|e| self.spawn_error(e))?;
+        let elapsed = start.elapsed();
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to evaluate {}: {}",
+                expression,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(Measurement {
+            elapsed,
+            peak_memory_kib: None,
+        })
+    }
+
+    fn command(&self) -> tokio::process::Command {
+        let mut command = tokio::process::Command::new(&self.evaluator);
+        for jpath in &self.jpaths {
+            command.arg("-J").arg(jpath);
+        }
+        command
+    }
+
+    fn spawn_error(&self, error: std::io::Error) -> anyhow::Error {
+        crate::utils::evaluator_error(&self.evaluator, error)
+    }
+}
+
+impl Default for LibraryProfiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_profile_library() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join(jsonnet_generator::SAMPLES_FILE);
+        std::fs::write(&file, "{}").unwrap();
+
+        // Lists two resources and evaluates everything else successfully
+        let evaluator = temp_dir.path().join("evaluator");
+        std::fs::write(
+            &evaluator,
+            "#!/bin/sh\ncase \"$2\" in\n  std.objectFields*) echo '[\"Widget\", \"Gadget\"]' ;;\nesac\n",
+        )
+        .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&evaluator, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let profile = LibraryProfiler::new()
+            .with_evaluator(evaluator.to_string_lossy())
+            .with_iterations(1)
+            .profile(&file)
+            .await
+            .unwrap();
+        let mut resources: Vec<_> = profile
+            .resources
+            .iter()
+            .map(|resource| resource.resource.as_str())
+            .collect();
+        resources.sort();
+        assert_eq!(resources, ["Gadget", "Widget"]);
+        #[cfg(unix)]
+        assert!(profile.resources[0].peak_memory_kib.unwrap() > 0);
+
+        let failing = LibraryProfiler::new().with_evaluator("false");
+        assert!(failing.profile(&file).await.is_err());
+
+        let missing = LibraryProfiler::new().with_evaluator("gensonnet-no-such-evaluator");
+        let err = missing.profile(&file).await.unwrap_err();
+        assert!(err.to_string().contains("not found"));
+
+        let hotspot = ResourceProfile {
+            resource: "Widget".to_string(),
+            eval_ms: 10.0,
+            validation_ms: 8.0,
+            peak_memory_kib: None,
+        };
+        assert!(hotspot.is_validation_hotspot());
+        assert!(!ResourceProfile {
+            validation_ms: 2.0,
+            ..hotspot
+        }
+        .is_validation_hotspot());
+    }
+}
@@ -0,0 +1,504 @@
+//! Resolution of release assets published on GitHub or GitLab
+//!
+//! A release source names a repository, an asset pattern and optionally a tag
+//! pattern. The newest release whose tag matches is looked up through the
+//! provider's REST API, and the download URL of its matching asset is
+//! returned. Downloading and digest verification are left to the
+//! [`UrlFetcher`](crate::UrlFetcher), authenticated with the source's token so
+//! assets of private repositories can be fetched too.
+
+use crate::config::{ReleaseSource, RetryPolicy};
+use crate::fetch::Credentials;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Environment variable holding a GitHub token for API requests
+pub const GITHUB_TOKEN_ENV: &str = "GITHUB_TOKEN";
+
+/// Environment variable holding a GitLab token for API requests
+pub const GITLAB_TOKEN_ENV: &str = "GITLAB_TOKEN";
+
+/// A published release
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    /// Tag the release was published from
+    pub tag: String,
+
+    /// Whether the release is marked as a pre-release
+    pub prerelease: bool,
+
+    /// Files attached to the release
+    pub assets: Vec<ReleaseAsset>,
+}
+
+/// A file attached to a release
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReleaseAsset {
+    /// File name of the asset
+    pub name: String,
+
+    /// Download URL of the asset
+    pub url: String,
+
+    /// API URL serving the asset's contents to authenticated requests (GitHub only)
+    pub api_url: Option<String>,
+}
+
+/// The asset a release source resolved to
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedAsset {
+    /// Tag of the selected release
+    pub tag: String,
+
+    /// File name of the asset
+    pub name: String,
+
+    /// Download URL of the asset
+    pub url: String,
+
+    /// API URL serving the asset's contents to authenticated requests (GitHub only)
+    pub api_url: Option<String>,
+}
+
+impl ResolvedAsset {
+    /// URLs the asset may be downloaded from
+    pub fn urls(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.url.as_str()).chain(self.api_url.as_deref())
+    }
+}
+
+/// Pick the asset of the newest release matching a source's tag pattern
+///
+/// Drafts are never listed by the providers; pre-releases are skipped unless
+/// the source allows them. Fails if the selected release has no matching asset
+/// rather than falling back to an older release.
+pub fn select(releases: &[Release], source: &ReleaseSource) -> Result<ResolvedAsset> {
+    let tag_pattern = source.tag.as_deref().map(glob::Pattern::new).transpose()?;
+    let asset_pattern = glob::Pattern::new(&source.asset)?;
+
+    let mut candidates: Vec<&Release> = releases
+        .iter()
+        .filter(|release| source.prereleases || !release.prerelease)
+        .filter(|release| {
+            tag_pattern
+                .as_ref()
+                .is_none_or(|pattern| pattern.matches(&release.tag))
+        })
+        .collect();
+    // Stable sort, so releases with equal versions keep the API's newest-first order
+    candidates.sort_by_key(|release| std::cmp::Reverse(version_key(&release.tag)));
+
+    let release = candidates.first().ok_or_else(|| {
+        anyhow!(
+            "No release of {} matches tag {}",
+            source.repository(),
+            source.tag.as_deref().unwrap_or("*")
+        )
+    })?;
+    let asset = release
+        .assets
+        .iter()
+        .find(|asset| asset_pattern.matches(&asset.name))
+        .ok_or_else(|| {
+            anyhow!(
+                "Release {} of {} has no asset matching {}",
+                release.tag,
+                source.repository(),
+                source.asset
+            )
+        })?;
+
+    Ok(ResolvedAsset {
+        tag: release.tag.clone(),
+        name: asset.name.clone(),
+        url: asset.url.clone(),
+        api_url: asset.api_url.clone(),
+    })
+}
+
+/// Sort key of a tag: its version numbers, then whether it is a final release
+///
+/// `v1.14.0` sorts after `v1.14.0-rc.1`, which sorts after `v1.13.2`.
+fn version_key(tag: &str) -> (Vec<u64>, bool, Vec<u64>) {
+    fn numbers(text: &str) -> Vec<u64> {
+        text.split(|c: char| !c.is_ascii_digit())
+            .filter_map(|part| part.parse().ok())
+            .collect()
+    }
+
+    match tag.split_once('-') {
+        Some((version, suffix)) => (numbers(version), false, numbers(suffix)),
+        None => (numbers(tag), true, Vec::new()),
+    }
+}
+
+/// Resolves release sources to asset download URLs
+pub struct ReleaseResolver {
+    #[cfg_attr(not(feature = "http"), allow(dead_code))]
+    retry_policy: RetryPolicy,
+    retry_counts: Mutex<HashMap<String, u32>>,
+    resolved: Mutex<HashMap<String, ResolvedAsset>>,
+}
+
+impl ReleaseResolver {
+    /// Create a new ReleaseResolver instance
+    pub fn new() -> Self {
+        Self {
+            retry_policy: RetryPolicy::default(),
+            retry_counts: Mutex::new(HashMap::new()),
+            resolved: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Use the given retry policy for API requests
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// The asset a source was resolved to, if it has been
+    pub fn resolved(&self, source: &ReleaseSource) -> Option<ResolvedAsset> {
+        self.resolved.lock().unwrap().get(&source.name).cloned()
+    }
+
+    /// Take the number of retries performed listing a source's releases since the last call
+    pub fn take_retry_count(&self, source: &ReleaseSource) -> u32 {
+        self.retry_counts
+            .lock()
+            .unwrap()
+            .remove(&releases_url(source))
+            .unwrap_or(0)
+    }
+
+    /// URL to download a resolved asset from, with the credentials to send
+    ///
+    /// With a token, GitHub assets are downloaded through the API, which
+    /// serves assets of private repositories, and GitLab links are sent the
+    /// token. Without one the public download URL is used.
+    pub fn download_request(
+        &self,
+        source: &ReleaseSource,
+        asset: &ResolvedAsset,
+    ) -> Result<(String, Option<Credentials>)> {
+        if source.gitlab.is_some() {
+            let credentials = token(source, GITLAB_TOKEN_ENV)?.map(|token| Credentials {
+                header: "PRIVATE-TOKEN",
+                token,
+                accept: None,
+            });
+            return Ok((asset.url.clone(), credentials));
+        }
+
+        match (token(source, GITHUB_TOKEN_ENV)?, &asset.api_url) {
+            (Some(token), Some(api_url)) => Ok((
+                api_url.clone(),
+                Some(Credentials {
+                    header: "Authorization",
+                    token,
+                    accept: Some("application/octet-stream"),
+                }),
+            )),
+            _ => Ok((asset.url.clone(), None)),
+        }
+    }
+
+    /// Resolve a release source to the asset it currently points at
+    ///
+    /// Sources are resolved at most once per resolver, so every step of a run
+    /// sees the same release even if a new one is published meanwhile.
+    pub async fn resolve(&self, source: &ReleaseSource) -> Result<ResolvedAsset> {
+        let cached = self.resolved.lock().unwrap().get(&source.name).cloned();
+        if let Some(asset) = cached {
+            return Ok(asset);
+        }
+
+        let releases = self.list_releases(source).await?;
+        let asset = select(&releases, source)?;
+        tracing::info!(
+            "Resolved {} to {} of {} release {}",
+            source.name,
+            asset.name,
+            source.repository(),
+            asset.tag
+        );
+        self.resolved
+            .lock()
+            .unwrap()
+            .insert(source.name.clone(), asset.clone());
+
+        Ok(asset)
+    }
+
+    /// List the most recent releases of a source's repository
+    #[cfg(feature = "http")]
+    async fn list_releases(&self, source: &ReleaseSource) -> Result<Vec<Release>> {
+        let url = releases_url(source);
+        if source.gitlab.is_some() {
+            let releases: Vec<api::GitLabRelease> = self
+                .get_json(&url, "PRIVATE-TOKEN", token(source, GITLAB_TOKEN_ENV)?)
+                .await?;
+            Ok(releases
+                .into_iter()
+                .map(api::GitLabRelease::into_release)
+                .collect())
+        } else {
+            let releases: Vec<api::GitHubRelease> = self
+                .get_json(&url, "Authorization", token(source, GITHUB_TOKEN_ENV)?)
+                .await?;
+            Ok(releases
+                .into_iter()
+                .filter_map(api::GitHubRelease::into_release)
+                .collect())
+        }
+    }
+
+    #[cfg(not(feature = "http"))]
+    async fn list_releases(&self, source: &ReleaseSource) -> Result<Vec<Release>> {
+        Err(anyhow!(
+            "Cannot list releases of {}: gensonnet was built without the `http` feature",
+            source.repository()
+        ))
+    }
+
+    /// GET a JSON document, authenticating with `token` if set
+    #[cfg(feature = "http")]
+    async fn get_json<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        token_header: &str,
+        token: Option<String>,
+    ) -> Result<T> {
+        let client = reqwest::Client::new();
+        let mut retries = 0;
+
+        loop {
+            let mut request = client.get(url).header("User-Agent", "gensonnet");
+            if let Some(token) = &token {
+                request = match token_header {
+                    "Authorization" => request.bearer_auth(token),
+                    header => request.header(header, token),
+                };
+            }
+            let attempt = async { request.send().await?.error_for_status()?.json().await };
+
+            match attempt.await {
+                Ok(document) => return Ok(document),
+                Err(e)
+                    if retries < self.retry_policy.max_retries
+                        && crate::fetch::is_transient(&e) =>
+                {
+                    retries += 1;
+                    *self
+                        .retry_counts
+                        .lock()
+                        .unwrap()
+                        .entry(url.to_string())
+                        .or_default() += 1;
+
+                    let delay = self.retry_policy.backoff(retries);
+                    tracing::warn!(
+                        "Request to {} failed ({}), retry {}/{} in {:?}",
+                        url,
+                        e,
+                        retries,
+                        self.retry_policy.max_retries,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => return Err(anyhow!("Failed to list releases from {}: {}", url, e)),
+            }
+        }
+    }
+}
+
+impl Default for ReleaseResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// API URL listing the most recent releases of a source's repository
+fn releases_url(source: &ReleaseSource) -> String {
+    if source.gitlab.is_some() {
+        format!(
+            "{}/projects/{}/releases?per_page=100",
+            source.api_base(),
+            source.repository().replace('/', "%2F")
+        )
+    } else {
+        format!(
+            "{}/repos/{}/releases?per_page=100",
+            source.api_base(),
+            source.repository()
+        )
+    }
+}
+
+/// API token of a source: its own, or the one in `token_env` if set
+fn token(source: &ReleaseSource, token_env: &str) -> Result<Option<String>> {
+    match &source.token {
+        Some(token) => Ok(Some(token.resolve()?)),
+        None => Ok(std::env::var(token_env).ok()),
+    }
+}
+
+/// Release listings as returned by the provider APIs
+#[cfg(feature = "http")]
+mod api {
+    use super::{Release, ReleaseAsset};
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    pub struct GitHubRelease {
+        tag_name: String,
+        #[serde(default)]
+        draft: bool,
+        #[serde(default)]
+        prerelease: bool,
+        #[serde(default)]
+        assets: Vec<GitHubAsset>,
+    }
+
+    #[derive(Deserialize)]
+    struct GitHubAsset {
+        name: String,
+        url: String,
+        browser_download_url: String,
+    }
+
+    impl GitHubRelease {
+        /// The release, unless it is a draft
+        pub fn into_release(self) -> Option<Release> {
+            (!self.draft).then(|| Release {
+                tag: self.tag_name,
+                prerelease: self.prerelease,
+                assets: self
+                    .assets
+                    .into_iter()
+                    .map(|asset| ReleaseAsset {
+                        name: asset.name,
+                        url: asset.browser_download_url,
+                        api_url: Some(asset.url),
+                    })
+                    .collect(),
+            })
+        }
+    }
+
+    #[derive(Deserialize)]
+    pub struct GitLabRelease {
+        tag_name: String,
+        #[serde(default)]
+        upcoming_release: bool,
+        #[serde(default)]
+        assets: GitLabAssets,
+    }
+
+    #[derive(Default, Deserialize)]
+    struct GitLabAssets {
+        #[serde(default)]
+        links: Vec<GitLabLink>,
+    }
+
+    #[derive(Deserialize)]
+    struct GitLabLink {
+        name: String,
+        url: String,
+        #[serde(default)]
+        direct_asset_url: Option<String>,
+    }
+
+    impl GitLabRelease {
+        /// The release; upcoming releases count as pre-releases
+        pub fn into_release(self) -> Release {
+            Release {
+                tag: self.tag_name,
+                prerelease: self.upcoming_release,
+                assets: self
+                    .assets
+                    .links
+                    .into_iter()
+                    .map(|link| ReleaseAsset {
+                        name: link.name,
+                        url: link.direct_asset_url.unwrap_or(link.url),
+                        api_url: None,
+                    })
+                    .collect(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str, prerelease: bool, assets: &[&str]) -> Release {
+        Release {
+            tag: tag.to_string(),
+            prerelease,
+            assets: assets
+                .iter()
+                .map(|name| ReleaseAsset {
+                    name: name.to_string(),
+                    url: format!("https://example.com/{tag}/{name}"),
+                    api_url: Some(format!("https://api.example.com/{tag}/{name}")),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_select_newest_matching_release() {
+        let releases = vec![
+            release("v1.15.0-rc.1", true, &["crds.yaml"]),
+            release("v1.14.10", false, &["crds.yaml", "notes.txt"]),
+            release("v1.14.9", false, &["crds.yaml"]),
+            release("v1.13.4", false, &["crds.yaml"]),
+        ];
+        let mut source: ReleaseSource = serde_yaml::from_str(
+            "name: test\ngithub: org/repo\nasset: \"*.yaml\"\ntag: \"v1.14.*\"\noutput_path: ./out\n",
+        )
+        .unwrap();
+
+        let asset = select(&releases, &source).unwrap();
+        assert_eq!(asset.tag, "v1.14.10");
+        assert_eq!(asset.url, "https://example.com/v1.14.10/crds.yaml");
+
+        source.tag = None;
+        assert_eq!(select(&releases, &source).unwrap().tag, "v1.14.10");
+        source.prereleases = true;
+        assert_eq!(select(&releases, &source).unwrap().tag, "v1.15.0-rc.1");
+
+        source.asset = "bundle.yaml".to_string();
+        assert!(select(&releases, &source).is_err());
+        assert!(version_key("v1.14.0") > version_key("v1.14.0-rc.1"));
+    }
+
+    #[test]
+    fn test_download_request_authenticates_private_assets() {
+        let mut source: ReleaseSource = serde_yaml::from_str(
+            "name: test\ngithub: org/repo\nasset: crds.yaml\ntoken: secret\noutput_path: ./out\n",
+        )
+        .unwrap();
+        let asset = select(&[release("v1.0.0", false, &["crds.yaml"])], &source).unwrap();
+        let resolver = ReleaseResolver::new();
+
+        // GitHub assets are downloaded through the API with the token
+        let (url, credentials) = resolver.download_request(&source, &asset).unwrap();
+        assert_eq!(url, "https://api.example.com/v1.0.0/crds.yaml");
+        let credentials = credentials.unwrap();
+        assert_eq!(credentials.header, "Authorization");
+        assert_eq!(credentials.token, "secret");
+        assert_eq!(credentials.accept, Some("application/octet-stream"));
+
+        // GitLab links are sent the token as is
+        source.github = None;
+        source.gitlab = Some("group/project".to_string());
+        let (url, credentials) = resolver.download_request(&source, &asset).unwrap();
+        assert_eq!(url, asset.url);
+        assert_eq!(credentials.unwrap().header, "PRIVATE-TOKEN");
+    }
+}
@@ -0,0 +1,317 @@
+//! Sample resources ingested from CRD repositories
+//!
+//! Operator repositories often keep sample resources next to their CRDs, as
+//! in `config/samples/*.yaml`. The files matching a CRD source's `samples`
+//! globs are read, each resource is matched to the schema of its apiVersion
+//! and kind, and its spec is validated against the schema. Valid samples
+//! become the examples and presets of the generated library; the others are
+//! reported as warnings and left out.
+
+use crate::kustomize::{parse_documents, KUSTOMIZATION_FILES};
+use anyhow::{anyhow, Result};
+use jsonnet_crd::CrdSchema;
+use jsonnet_generator::presets::Preset;
+use jsonnet_generator::{Warning, WarningCategory};
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Metadata fields of a sample carried into its example besides its name
+const METADATA_FIELDS: &[&str] = &["namespace", "labels", "annotations"];
+
+/// A sample resource read from a repository
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    /// File the sample was read from, relative to the repository
+    pub file: PathBuf,
+
+    /// The resource as written
+    pub resource: Value,
+}
+
+impl Sample {
+    fn field(&self, name: &str) -> Option<&str> {
+        self.resource.get(name).and_then(Value::as_str)
+    }
+
+    /// Name of the sample: its `metadata.name`, else the name of its file
+    pub fn name(&self) -> String {
+        self.resource
+            .get("metadata")
+            .and_then(|metadata| metadata.get("name"))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| {
+                self.file
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            })
+    }
+}
+
+/// Samples read from a repository, with the problems reading them
+#[derive(Debug, Default)]
+pub struct Samples {
+    pub samples: Vec<Sample>,
+    pub warnings: Vec<Warning>,
+}
+
+impl Samples {
+    /// Read the samples in the files below `root` matching `patterns`
+    ///
+    /// Kustomization files are skipped and files that are not valid YAML
+    /// are reported as warnings; a pattern that is not a valid glob fails.
+    pub fn load(root: &Path, patterns: &[String]) -> Result<Self> {
+        let mut loaded = Self::default();
+        let mut seen = BTreeSet::new();
+        for pattern in patterns {
+            let full = root.join(pattern);
+            let mut files: Vec<PathBuf> = glob::glob(&full.to_string_lossy())
+                .map_err(|e| anyhow!("Invalid samples pattern {}: {}", pattern, e))?
+                .filter_map(|entry| entry.ok())
+                .filter(|path| path.is_file())
+                .collect();
+            files.sort();
+
+            for path in files {
+                let kustomization = path
+                    .file_name()
+                    .is_some_and(|name| KUSTOMIZATION_FILES.iter().any(|k| name == *k));
+                if kustomization || !seen.insert(path.clone()) {
+                    continue;
+                }
+                let file = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+                let documents = std::fs::read_to_string(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|content| parse_documents(&content));
+                match documents {
+                    Ok(documents) => {
+                        loaded
+                            .samples
+                            .extend(documents.into_iter().map(|resource| Sample {
+                                file: file.clone(),
+                                resource,
+                            }))
+                    }
+                    Err(e) => loaded.warnings.push(
+                        Warning::new(
+                            WarningCategory::Source,
+                            "unreadable-sample",
+                            format!("Sample file could not be read: {e}"),
+                        )
+                        .with_path(file.display().to_string()),
+                    ),
+                }
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Turn the samples of the given schemas into presets
+    ///
+    /// Returns the presets in the order the samples were read, with warnings
+    /// for samples matching no schema, failing validation or repeating the
+    /// name of an earlier sample of the same resource.
+    pub fn ingest(self, schemas: &[CrdSchema]) -> (Vec<Preset>, Vec<Warning>) {
+        let mut presets: Vec<Preset> = Vec::new();
+        let mut warnings = self.warnings;
+        for sample in self.samples {
+            let name = sample.name();
+            let path = format!("{}:{}", sample.file.display(), name);
+            let warning = |code: &str, message: String| {
+                Warning::new(WarningCategory::Source, code, message).with_path(path.clone())
+            };
+
+            let (api_version, kind) = (
+                sample.field("apiVersion").unwrap_or_default(),
+                sample.field("kind").unwrap_or_default(),
+            );
+            let Some(schema) = schemas
+                .iter()
+                .find(|schema| schema.api_version == api_version && schema.kind == kind)
+            else {
+                warnings.push(warning(
+                    "unmatched-sample",
+                    format!("Sample {kind} ({api_version}) matches no generated resource"),
+                ));
+                continue;
+            };
+
+            let spec = sample
+                .resource
+                .get("spec")
+                .cloned()
+                .unwrap_or_else(|| Value::Mapping(Mapping::new()));
+            let errors = gensonnet_plugin::validate_value(spec_schema(schema), &spec, "spec");
+            if !errors.is_empty() {
+                let details: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                warnings.push(warning(
+                    "invalid-sample",
+                    format!(
+                        "Sample does not match the schema of {}: {}",
+                        schema.name,
+                        details.join("; ")
+                    ),
+                ));
+                continue;
+            }
+
+            if presets
+                .iter()
+                .any(|preset| preset.schema == schema.name && preset.name == name)
+            {
+                warnings.push(warning(
+                    "duplicate-sample",
+                    format!("Another sample of {} is named {}", schema.name, name),
+                ));
+                continue;
+            }
+
+            let mut metadata = Mapping::new();
+            metadata.insert("name".into(), name.clone().into());
+            if let Some(Value::Mapping(original)) = sample.resource.get("metadata") {
+                for field in METADATA_FIELDS {
+                    if let Some(value) = original.get(*field) {
+                        metadata.insert((*field).into(), value.clone());
+                    }
+                }
+            }
+            presets.push(Preset {
+                schema: schema.name.clone(),
+                name,
+                file: sample.file.display().to_string(),
+                metadata: Value::Mapping(metadata),
+                spec,
+            });
+        }
+        (presets, warnings)
+    }
+}
+
+/// Schema of a resource's spec
+///
+/// Schemas describing the whole resource hold it under `properties.spec`;
+/// the others describe the spec itself.
+fn spec_schema(schema: &CrdSchema) -> &Value {
+    schema
+        .schema
+        .get("properties")
+        .and_then(|properties| properties.get("spec"))
+        .unwrap_or(&schema.schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_ingest_samples() {
+        let temp_dir = TempDir::new().unwrap();
+        let samples_dir = temp_dir.path().join("config/samples");
+        std::fs::create_dir_all(&samples_dir).unwrap();
+        std::fs::write(
+            samples_dir.join("demo_v1_widget.yaml"),
+            r#"
+apiVersion: demo.example.com/v1
+kind: Widget
+metadata:
+  name: widget-sample
+  labels: {app: demo}
+  uid: ignored
+spec:
+  size: small
+---
+apiVersion: demo.example.com/v1
+kind: Widget
+metadata:
+  name: widget-huge
+spec:
+  size: huge
+---
+apiVersion: demo.example.com/v1
+kind: Widget
+metadata:
+  name: widget-sample
+spec:
+  size: large
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            samples_dir.join("demo_v1_gadget.yaml"),
+            "apiVersion: demo.example.com/v1\nkind: Gadget\nmetadata: {name: gadget}\n",
+        )
+        .unwrap();
+        std::fs::write(samples_dir.join("broken.yaml"), "spec: [").unwrap();
+        std::fs::write(
+            samples_dir.join("kustomization.yaml"),
+            "resources: [demo_v1_widget.yaml]\n",
+        )
+        .unwrap();
+
+        let loaded =
+            Samples::load(temp_dir.path(), &["config/samples/*.yaml".to_string()]).unwrap();
+        assert_eq!(loaded.samples.len(), 4);
+        assert_eq!(loaded.warnings.len(), 1);
+        assert_eq!(loaded.warnings[0].code, "unreadable-sample");
+
+        let crds_dir = temp_dir.path().join("config/crd");
+        std::fs::create_dir_all(&crds_dir).unwrap();
+        std::fs::write(
+            crds_dir.join("widgets.yaml"),
+            r#"
+apiVersion: apiextensions.k8s.io/v1
+kind: CustomResourceDefinition
+metadata:
+  name: widgets.demo.example.com
+spec:
+  group: demo.example.com
+  names: {kind: Widget, plural: widgets}
+  scope: Namespaced
+  versions:
+  - name: v1
+    served: true
+    storage: true
+    schema:
+      openAPIV3Schema:
+        type: object
+        properties:
+          spec:
+            type: object
+            required: [size]
+            properties:
+              size: {type: string, enum: [small, large]}
+"#,
+        )
+        .unwrap();
+        let schemas = jsonnet_crd::CrdParser::new()
+            .parse_from_directory(&crds_dir, &[])
+            .unwrap();
+        let (presets, warnings) = loaded.ingest(&schemas);
+        assert_eq!(presets.len(), 1);
+        assert_eq!(presets[0].name, "widget-sample");
+        assert_eq!(presets[0].file, "config/samples/demo_v1_widget.yaml");
+        assert_eq!(
+            presets[0].metadata,
+            serde_yaml::from_str::<Value>("name: widget-sample\nlabels: {app: demo}").unwrap()
+        );
+
+        let codes: Vec<&str> = warnings.iter().map(|w| w.code.as_str()).collect();
+        assert_eq!(
+            codes,
+            [
+                "unreadable-sample",
+                "unmatched-sample",
+                "invalid-sample",
+                "duplicate-sample"
+            ]
+        );
+        assert!(
+            warnings[2].message.contains("spec.size"),
+            "{:?}",
+            warnings[2]
+        );
+    }
+}
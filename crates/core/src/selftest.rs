@@ -0,0 +1,144 @@
+//! Running the self-tests emitted next to generated libraries
+//!
+//! gensonnet does not embed a Jsonnet evaluator: tests are run with an
+//! external one, `jsonnet` on the PATH unless configured otherwise.
+
+use crate::utils::evaluator_error;
+use anyhow::Result;
+use jsonnet_generator::examples::EXAMPLES_DIR;
+use jsonnet_generator::SELFTEST_FILE;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Outcome of evaluating one self-test file
+#[derive(Debug, Clone)]
+pub struct SelftestOutcome {
+    /// The evaluated `_tests.libsonnet`
+    pub file: PathBuf,
+
+    /// Whether evaluation succeeded
+    pub passed: bool,
+
+    /// Evaluator error output for failed tests
+    pub message: String,
+}
+
+/// Evaluates self-test files with a Jsonnet evaluator
+pub struct SelftestRunner {
+    evaluator: String,
+    jpaths: Vec<PathBuf>,
+}
+
+impl SelftestRunner {
+    /// Create a runner using the `jsonnet` binary on the PATH
+    pub fn new() -> Self {
+        Self {
+            evaluator: "jsonnet".to_string(),
+            jpaths: Vec::new(),
+        }
+    }
+
+    /// Use a different evaluator binary (e.g. `jrsonnet`)
+    pub fn with_evaluator(mut self, evaluator: impl Into<String>) -> Self {
+        self.evaluator = evaluator.into();
+        self
+    }
+
+    /// Add library search paths, passed to the evaluator as `-J`
+    pub fn with_jpaths(mut self, jpaths: Vec<PathBuf>) -> Self {
+        self.jpaths = jpaths;
+        self
+    }
+
+    /// Evaluate a self-test file
+    pub async fn run(&self, file: &Path) -> Result<SelftestOutcome> {
+        let mut command = tokio::process::Command::new(&self.evaluator);
+        for jpath in &self.jpaths {
+            command.arg("-J").arg(jpath);
+        }
+
+        let output = command
+            .arg(file)
+            .output()
+            .await
+            .map_err(|e| evaluator_error(&self.evaluator, e))?;
+
+        Ok(SelftestOutcome {
+            file: file.to_path_buf(),
+            passed: output.status.success(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        })
+    }
+}
+
+impl Default for SelftestRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Find the self-test files below a directory
+pub fn find_selftests(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.file_name() == SELFTEST_FILE)
+        .map(|e| e.into_path())
+        .collect();
+    files.sort();
+    files
+}
+
+/// Find the usage examples of the library at `dir`
+pub fn find_examples(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = WalkDir::new(dir.join(EXAMPLES_DIR))
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.file_type().is_file() && e.path().extension().is_some_and(|ext| ext == "jsonnet")
+        })
+        .map(|e| e.into_path())
+        .collect();
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_find_and_run_selftests() {
+        let temp_dir = TempDir::new().unwrap();
+        for library in ["alpha", "beta"] {
+            let dir = temp_dir.path().join(library);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join(SELFTEST_FILE), "{}").unwrap();
+        }
+        std::fs::write(temp_dir.path().join("index.libsonnet"), "{}").unwrap();
+
+        let files = find_selftests(temp_dir.path());
+        assert_eq!(files.len(), 2);
+        assert!(files[0].ends_with("alpha/_tests.libsonnet"));
+
+        let passing = SelftestRunner::new().with_evaluator("true");
+        assert!(passing.run(&files[0]).await.unwrap().passed);
+
+        let failing = SelftestRunner::new().with_evaluator("false");
+        assert!(!failing.run(&files[0]).await.unwrap().passed);
+
+        let missing = SelftestRunner::new().with_evaluator("gensonnet-no-such-evaluator");
+        let err = missing.run(&files[0]).await.unwrap_err();
+        assert!(err.to_string().contains("not found"));
+
+        let examples = temp_dir.path().join("alpha/examples/example.com_v1");
+        std::fs::create_dir_all(&examples).unwrap();
+        std::fs::write(examples.join("widget.jsonnet"), "{}").unwrap();
+        std::fs::write(examples.join("notes.md"), "").unwrap();
+        assert_eq!(
+            find_examples(&temp_dir.path().join("alpha")),
+            vec![examples.join("widget.jsonnet")]
+        );
+    }
+}
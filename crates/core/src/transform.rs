@@ -0,0 +1,531 @@
+//! Schema transforms applied to a source's schemas before generation
+//!
+//! A source lists transforms under `transforms:`. They run in declared order
+//! on every schema the source yields, after parsing and before policies and
+//! generation. Built-in transforms rewrite the schemas in place; `plugin`
+//! transforms hand them to a plugin with the Transform capability.
+
+use crate::plugin::ExtractedSchema;
+use crate::CrdSchema;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+
+/// A schema transform
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Transform {
+    /// Merge `allOf` subschemas into the schema holding them
+    FlattenAllOf,
+
+    /// Remove every description, shrinking the generated docs
+    StripDescriptions,
+
+    /// Move CRDs from one API group to another
+    RenameGroup { from: String, to: String },
+
+    /// Keep this many levels of nested properties; deeper objects become free-form
+    LimitDepth(usize),
+
+    /// Keep or drop fields named by JSON pointers
+    Fields(FieldFilter),
+
+    /// A transform provided by a plugin
+    Plugin(PluginTransform),
+}
+
+/// Fields to keep or drop, as JSON pointers into resources like `/spec/legacyOptions`
+///
+/// Array items are addressed without an index: `/spec/containers/image` names
+/// the `image` of every container.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldFilter {
+    /// Fields to keep; their siblings below the top level are dropped
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+
+    /// Fields to drop, after `include` is applied
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+/// A transform provided by a plugin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginTransform {
+    /// Plugin id, or its type (the part of the id before `:`)
+    pub id: String,
+
+    /// Name of the transform within the plugin
+    pub name: String,
+
+    /// Configuration handed to the transform
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub config: Value,
+}
+
+impl Transform {
+    /// Validate the transform configuration
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Transform::RenameGroup { from, to } if from.is_empty() || to.is_empty() => {
+                Err(anyhow!("renameGroup needs both `from` and `to`"))
+            }
+            Transform::LimitDepth(0) => Err(anyhow!("limitDepth must be at least 1")),
+            Transform::Fields(filter) => {
+                if filter.include.is_empty() && filter.exclude.is_empty() {
+                    return Err(anyhow!("fields needs `include` or `exclude` pointers"));
+                }
+                match filter
+                    .include
+                    .iter()
+                    .chain(&filter.exclude)
+                    .find(|pointer| jsonnet_crd::pointer::segments(pointer).is_none())
+                {
+                    Some(pointer) => Err(anyhow!(
+                        "Field pointer '{}' must start with / and name a field",
+                        pointer
+                    )),
+                    None => Ok(()),
+                }
+            }
+            Transform::Plugin(plugin) if plugin.id.is_empty() || plugin.name.is_empty() => {
+                Err(anyhow!("Plugin transforms need both `id` and `name`"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A schema transforms can be applied to
+pub trait TransformTarget {
+    /// The OpenAPI schema
+    fn schema_mut(&mut self) -> &mut Value;
+
+    /// Move the schema to another API group if it belongs to `from`
+    fn rename_group(&mut self, from: &str, to: &str);
+
+    /// The schema as handed to plugin transforms
+    fn to_extracted(&self) -> ExtractedSchema;
+
+    /// Take over the result of a plugin transform
+    fn update_from(&mut self, schema: ExtractedSchema);
+}
+
+impl TransformTarget for CrdSchema {
+    fn schema_mut(&mut self) -> &mut Value {
+        &mut self.schema
+    }
+
+    fn rename_group(&mut self, from: &str, to: &str) {
+        if self.group != from {
+            return;
+        }
+        if let Some(plural) = self.name.strip_suffix(&format!(".{from}")) {
+            self.name = format!("{plural}.{to}");
+        }
+        self.group = to.to_string();
+        self.api_version = format!("{}/{}", to, self.version);
+    }
+
+    fn to_extracted(&self) -> ExtractedSchema {
+        crate::to_extracted_schema(self)
+    }
+
+    fn update_from(&mut self, schema: ExtractedSchema) {
+        self.schema = schema.content;
+    }
+}
+
+impl TransformTarget for ExtractedSchema {
+    fn schema_mut(&mut self) -> &mut Value {
+        &mut self.content
+    }
+
+    fn rename_group(&mut self, from: &str, to: &str) {
+        if self.metadata.get("group").and_then(Value::as_str) == Some(from) {
+            self.metadata
+                .insert("group".to_string(), Value::String(to.to_string()));
+        }
+    }
+
+    fn to_extracted(&self) -> ExtractedSchema {
+        self.clone()
+    }
+
+    fn update_from(&mut self, schema: ExtractedSchema) {
+        *self = schema;
+    }
+}
+
+/// Apply a built-in transform; plugin transforms are left to the caller
+pub fn apply_builtin(transform: &Transform, target: &mut impl TransformTarget) {
+    match transform {
+        Transform::FlattenAllOf => flatten_all_of(target.schema_mut()),
+        Transform::StripDescriptions => strip_descriptions(target.schema_mut()),
+        Transform::RenameGroup { from, to } => target.rename_group(from, to),
+        Transform::LimitDepth(depth) => limit_depth(target.schema_mut(), *depth),
+        Transform::Fields(filter) => filter_fields(target.schema_mut(), filter),
+        Transform::Plugin(_) => {}
+    }
+}
+
+/// Call `f` with the keyword holding each direct subschema and the subschema
+fn for_each_subschema(schema: &mut Value, f: &mut impl FnMut(&str, &mut Value)) {
+    let Some(mapping) = schema.as_mapping_mut() else {
+        return;
+    };
+    for (key, value) in mapping.iter_mut() {
+        let Some(keyword) = key.as_str() else {
+            continue;
+        };
+        match (keyword, value) {
+            (
+                "properties" | "patternProperties" | "definitions" | "$defs",
+                Value::Mapping(schemas),
+            ) => {
+                for schema in schemas.values_mut() {
+                    f(keyword, schema);
+                }
+            }
+            (
+                "items" | "additionalProperties" | "allOf" | "anyOf" | "oneOf",
+                Value::Sequence(schemas),
+            ) => {
+                for schema in schemas {
+                    f(keyword, schema);
+                }
+            }
+            ("items" | "additionalProperties" | "not", schema @ Value::Mapping(_)) => {
+                f(keyword, schema)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn flatten_all_of(schema: &mut Value) {
+    for_each_subschema(schema, &mut |_, subschema| flatten_all_of(subschema));
+
+    let Some(mapping) = schema.as_mapping_mut() else {
+        return;
+    };
+    if let Some(Value::Sequence(members)) = mapping.remove("allOf") {
+        for member in members {
+            if let Value::Mapping(member) = member {
+                merge_schema(mapping, member);
+            }
+        }
+    }
+}
+
+/// Merge a subschema into a schema, whose own keywords take precedence
+fn merge_schema(schema: &mut Mapping, subschema: Mapping) {
+    for (key, value) in subschema {
+        match (key.as_str(), schema.get_mut(&key), value) {
+            (Some("required"), Some(Value::Sequence(required)), Value::Sequence(more)) => {
+                for name in more {
+                    if !required.contains(&name) {
+                        required.push(name);
+                    }
+                }
+            }
+            (Some("properties"), Some(Value::Mapping(properties)), Value::Mapping(more)) => {
+                for (name, property) in more {
+                    match (properties.get_mut(&name), property) {
+                        (Some(Value::Mapping(existing)), Value::Mapping(property)) => {
+                            merge_schema(existing, property)
+                        }
+                        (Some(_), _) => {}
+                        (None, property) => {
+                            properties.insert(name, property);
+                        }
+                    }
+                }
+            }
+            (_, Some(_), _) => {}
+            (_, None, value) => {
+                schema.insert(key, value);
+            }
+        }
+    }
+}
+
+fn strip_descriptions(schema: &mut Value) {
+    if let Some(mapping) = schema.as_mapping_mut() {
+        mapping.remove("description");
+    }
+    for_each_subschema(schema, &mut |_, subschema| strip_descriptions(subschema));
+}
+
+fn limit_depth(schema: &mut Value, depth: usize) {
+    let Some(mapping) = schema.as_mapping_mut() else {
+        return;
+    };
+    if depth == 0 && mapping.remove("properties").is_some() {
+        mapping.remove("required");
+        mapping.insert("x-kubernetes-preserve-unknown-fields".into(), true.into());
+    }
+
+    for_each_subschema(schema, &mut |keyword, subschema| match keyword {
+        "properties" => limit_depth(subschema, depth - 1),
+        _ => limit_depth(subschema, depth),
+    });
+}
+
+fn filter_fields(schema: &mut Value, filter: &FieldFilter) {
+    let include: Vec<Vec<String>> = filter
+        .include
+        .iter()
+        .filter_map(|pointer| jsonnet_crd::pointer::segments(pointer))
+        .collect();
+    if !include.is_empty() {
+        // Top-level fields such as apiVersion, kind and metadata are kept
+        if let Some(Value::Mapping(properties)) = fields_schema_mut(schema).get_mut("properties") {
+            for (name, property) in properties.iter_mut() {
+                let paths = paths_below(&include, name);
+                if !paths.is_empty() {
+                    keep_fields(property, &paths);
+                }
+            }
+        }
+    }
+
+    for segments in filter
+        .exclude
+        .iter()
+        .filter_map(|pointer| jsonnet_crd::pointer::segments(pointer))
+    {
+        remove_field(schema, &segments);
+    }
+}
+
+/// The remainders of the paths going through the field `name`
+fn paths_below<'a>(paths: &'a [impl AsRef<[String]>], name: &Value) -> Vec<&'a [String]> {
+    paths
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|path| path.first().map(String::as_str) == name.as_str())
+        .map(|path| &path[1..])
+        .collect()
+}
+
+/// Drop the fields of a schema no path goes through
+fn keep_fields(schema: &mut Value, paths: &[&[String]]) {
+    // A path ending here keeps the whole field
+    if paths.iter().any(|path| path.is_empty()) {
+        return;
+    }
+    let Some(mapping) = fields_schema_mut(schema).as_mapping_mut() else {
+        return;
+    };
+    let kept = |name: &Value| paths.iter().any(|path| name.as_str() == Some(&path[0]));
+    if let Some(Value::Sequence(required)) = mapping.get_mut("required") {
+        required.retain(|name| kept(name));
+    }
+    if let Some(Value::Mapping(properties)) = mapping.get_mut("properties") {
+        properties.retain(|name, _| kept(name));
+        for (name, property) in properties.iter_mut() {
+            keep_fields(property, &paths_below(paths, name));
+        }
+    }
+}
+
+/// Drop the field at a path, if the schema has it
+fn remove_field(schema: &mut Value, segments: &[String]) {
+    let Some((name, parents)) = segments.split_last() else {
+        return;
+    };
+    let mut schema = schema;
+    for parent in parents {
+        match fields_schema_mut(schema)
+            .get_mut("properties")
+            .and_then(|properties| properties.get_mut(parent.as_str()))
+        {
+            Some(property) => schema = property,
+            None => return,
+        }
+    }
+    let Some(mapping) = fields_schema_mut(schema).as_mapping_mut() else {
+        return;
+    };
+    if let Some(Value::Mapping(properties)) = mapping.get_mut("properties") {
+        properties.remove(name.as_str());
+    }
+    if let Some(Value::Sequence(required)) = mapping.get_mut("required") {
+        required.retain(|required| required.as_str() != Some(name));
+    }
+}
+
+/// The schema holding an object's fields, stepping through arrays to their items
+fn fields_schema_mut(schema: &mut Value) -> &mut Value {
+    if schema.get("properties").is_none() && schema.get("items").is_some_and(Value::is_mapping) {
+        return fields_schema_mut(&mut schema["items"]);
+    }
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_transforms() {
+        let source: crate::config::Source = serde_yaml::from_str(
+            r#"
+type: url
+name: widgets
+url: file:///crds/widgets.yaml
+output_path: ./out
+transforms:
+  - flattenAllOf
+  - stripDescriptions
+  - renameGroup: {from: example.com, to: acme.io}
+  - limitDepth: 2
+  - plugin: {id: naming, name: prefixKinds}
+"#,
+        )
+        .unwrap();
+        assert!(source.validate().is_ok());
+        let transforms = source.transforms();
+        assert_eq!(transforms.len(), 5);
+        assert!(Transform::LimitDepth(0).validate().is_err());
+
+        let mut schema = ExtractedSchema {
+            name: "Widget".to_string(),
+            schema_type: "crd".to_string(),
+            content: serde_yaml::from_str(
+                r#"
+type: object
+description: A widget
+properties:
+  spec:
+    description: Desired state
+    allOf:
+      - properties:
+          size: {type: integer}
+        required: [size]
+      - properties:
+          template:
+            type: object
+            properties:
+              name: {type: string, description: Template name}
+        required: [template]
+    required: [size]
+"#,
+            )
+            .unwrap(),
+            source_file: Default::default(),
+            metadata: [("group".to_string(), Value::from("example.com"))]
+                .into_iter()
+                .collect(),
+        };
+        for transform in transforms {
+            apply_builtin(transform, &mut schema);
+        }
+
+        let expected: Value = serde_yaml::from_str(
+            r#"
+type: object
+properties:
+  spec:
+    required: [size, template]
+    properties:
+      size: {type: integer}
+      template:
+        type: object
+        x-kubernetes-preserve-unknown-fields: true
+"#,
+        )
+        .unwrap();
+        assert_eq!(schema.content, expected);
+        assert_eq!(schema.metadata["group"], Value::from("acme.io"));
+    }
+
+    #[test]
+    fn test_field_filter() {
+        let transform: Transform = serde_yaml::with::singleton_map_recursive::deserialize(
+            serde_yaml::Deserializer::from_str(
+                r#"
+fields:
+  include: [/spec/replicas, /spec/endpoints/port, /spec/endpoints/relabelings]
+  exclude: [/spec/endpoints/relabelings/action, /status, /spec/missing/field]
+"#,
+            ),
+        )
+        .unwrap();
+        assert!(transform.validate().is_ok());
+        assert!(Transform::Fields(FieldFilter::default())
+            .validate()
+            .is_err());
+        assert!(Transform::Fields(FieldFilter {
+            include: vec!["spec//replicas".to_string()],
+            exclude: Vec::new(),
+        })
+        .validate()
+        .is_err());
+
+        let mut schema: Value = serde_yaml::from_str(
+            r#"
+type: object
+properties:
+  apiVersion: {type: string}
+  spec:
+    type: object
+    required: [replicas, legacyOptions]
+    properties:
+      replicas: {type: integer}
+      legacyOptions: {type: object}
+      endpoints:
+        type: array
+        items:
+          type: object
+          required: [port, path]
+          properties:
+            port: {type: string}
+            path: {type: string}
+            relabelings:
+              type: array
+              items:
+                type: object
+                properties:
+                  action: {type: string}
+                  regex: {type: string}
+  status: {type: object}
+"#,
+        )
+        .unwrap();
+        let Transform::Fields(filter) = &transform else {
+            panic!("not a field filter: {transform:?}");
+        };
+        filter_fields(&mut schema, filter);
+
+        let expected: Value = serde_yaml::from_str(
+            r#"
+type: object
+properties:
+  apiVersion: {type: string}
+  spec:
+    type: object
+    required: [replicas]
+    properties:
+      replicas: {type: integer}
+      endpoints:
+        type: array
+        items:
+          type: object
+          required: [port]
+          properties:
+            port: {type: string}
+            relabelings:
+              type: array
+              items:
+                type: object
+                properties:
+                  regex: {type: string}
+"#,
+        )
+        .unwrap();
+        assert_eq!(schema, expected);
+    }
+}
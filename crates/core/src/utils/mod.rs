@@ -2,7 +2,7 @@
 
 use anyhow::{anyhow, Result};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use walkdir::WalkDir;
 
 /// Ensure a directory exists, creating it if necessary
@@ -99,10 +99,42 @@ pub fn dirname_to_api_version(dirname: &str) -> String {
 }
 
 /// Check if a path is within a base directory
+///
+/// Neither path has to exist yet: symlinks are resolved for the parts that
+/// exist and `..` is applied to the rest, so paths about to be written can be
+/// checked before writing them.
 pub fn is_within_base(path: &Path, base: &Path) -> bool {
-    path.canonicalize()
-        .and_then(|p| base.canonicalize().map(|b| p.starts_with(b)))
-        .unwrap_or(false)
+    match (resolve_path(path), resolve_path(base)) {
+        (Ok(path), Ok(base)) => path.starts_with(base),
+        _ => false,
+    }
+}
+
+/// Absolute form of a path that may not exist, following the symlinks of
+/// its existing ancestors
+pub fn resolve_path(path: &Path) -> Result<PathBuf> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+
+    let mut resolved = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            component => {
+                resolved.push(component);
+                if let Ok(canonical) = resolved.canonicalize() {
+                    resolved = canonical;
+                }
+            }
+        }
+    }
+    Ok(resolved)
 }
 
 /// Get relative path from base
@@ -139,6 +171,29 @@ pub fn copy_directory(src: &Path, dst: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Replace the directory `dst` with a copy of `src`
+///
+/// The copy is built next to `dst` and renamed into place, so `dst` is never
+/// left half-written and keeps no files `src` does not have.
+pub fn replace_directory(src: &Path, dst: &Path) -> Result<()> {
+    let parent = dst
+        .parent()
+        .ok_or_else(|| anyhow!("No parent directory for {:?}", dst))?;
+    ensure_directory(parent)?;
+
+    // Removed with whatever `dst` held before
+    let staging = tempfile::Builder::new()
+        .prefix(".staging-")
+        .tempdir_in(parent)?;
+    let copy = staging.path().join("new");
+    copy_directory(src, &copy)?;
+    if dst.exists() {
+        fs::rename(dst, staging.path().join("old"))?;
+    }
+    fs::rename(&copy, dst)?;
+    Ok(())
+}
+
 /// Remove directory recursively
 pub fn remove_directory(path: &Path) -> Result<()> {
     if path.exists() {
@@ -173,6 +228,22 @@ pub fn format_duration(duration: std::time::Duration) -> String {
     }
 }
 
+/// Error for a Jsonnet evaluator that could not be run
+///
+/// gensonnet does not embed an evaluator, so a missing binary gets a hint at
+/// installing one.
+pub fn evaluator_error(evaluator: &str, error: std::io::Error) -> anyhow::Error {
+    if error.kind() == std::io::ErrorKind::NotFound {
+        anyhow!(
+            "Jsonnet evaluator '{}' not found. gensonnet runs an external evaluator: \
+             install `jsonnet` (go-jsonnet) or `jrsonnet` on the PATH, or configure the path of one",
+            evaluator
+        )
+    } else {
+        anyhow!("Failed to run Jsonnet evaluator '{}': {}", evaluator, error)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,6 +269,27 @@ mod tests {
         assert_eq!(format_bytes(1024 * 1024), "1.0 MB");
     }
 
+    #[test]
+    fn test_is_within_base() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = temp_dir.path().join("generated");
+        std::fs::create_dir_all(&base).unwrap();
+
+        assert!(is_within_base(&base.join("widgets/v1.libsonnet"), &base));
+        assert!(is_within_base(&base.join("new/../widgets"), &base));
+        assert!(!is_within_base(&base.join("../outside"), &base));
+        assert!(!is_within_base(
+            &temp_dir.path().join("generated-other"),
+            &base
+        ));
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(temp_dir.path(), base.join("link")).unwrap();
+            assert!(!is_within_base(&base.join("link/escaped"), &base));
+        }
+    }
+
     #[test]
     fn test_copy_directory() {
         let temp_dir = TempDir::new().unwrap();
@@ -212,4 +304,27 @@ mod tests {
         assert!(dst_dir.exists());
         assert!(dst_dir.join("test.txt").exists());
     }
+
+    #[test]
+    fn test_replace_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let src_dir = temp_dir.path().join("src");
+        let dst_dir = temp_dir.path().join("dst");
+
+        fs::create_dir(&src_dir).unwrap();
+        fs::write(src_dir.join("test.txt"), "new content").unwrap();
+        fs::create_dir(&dst_dir).unwrap();
+        fs::write(dst_dir.join("test.txt"), "old content").unwrap();
+        fs::write(dst_dir.join("stale.txt"), "").unwrap();
+
+        replace_directory(&src_dir, &dst_dir).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dst_dir.join("test.txt")).unwrap(),
+            "new content"
+        );
+        assert!(!dst_dir.join("stale.txt").exists());
+        // Nothing is left next to the directory
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+    }
 }
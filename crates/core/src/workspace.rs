@@ -0,0 +1,368 @@
+//! Per-source workspaces for intermediate artifacts
+//!
+//! Each source is processed in `$CACHE/gensonnet/workspaces/<source-id>/<commit>`.
+//! Generated files are staged there and only copied to the configured output
+//! path once the source has been processed, so parallel sources never share
+//! scratch space and the workspace of a failed run is left behind for inspection.
+//!
+//! Publishing records the files it wrote in `.published.json` at the output
+//! path, and the next publish removes those it no longer stages, so files of
+//! schemas dropped upstream do not linger in the library.
+
+use anyhow::{anyhow, Result};
+use jsonnet_generator::config::Newline;
+use jsonnet_generator::header::takes_header;
+use jsonnet_generator::style::CodeStyle;
+use jsonnet_generator::write::write_generated;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+use tracing::debug;
+use walkdir::WalkDir;
+
+/// File at an output path listing the files published there, relative to it
+pub const PUBLISHED_FILE: &str = ".published.json";
+
+/// Creates workspaces below a root directory
+#[derive(Debug, Clone)]
+pub struct WorkspaceManager {
+    root: PathBuf,
+}
+
+/// The workspace of one source at one commit
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    path: PathBuf,
+}
+
+impl WorkspaceManager {
+    /// Create a manager rooted in the user cache directory
+    pub fn new() -> Result<Self> {
+        let root = dirs::cache_dir()
+            .ok_or_else(|| anyhow!("Could not determine cache directory"))?
+            .join("gensonnet")
+            .join("workspaces");
+
+        Ok(Self::with_root(root))
+    }
+
+    /// Create a manager rooted in the given directory
+    pub fn with_root(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Root directory of all workspaces
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of the workspace for a source at a commit
+    pub fn path(&self, source_id: &str, commit: &str) -> PathBuf {
+        let mut path = self.root.clone();
+        for segment in source_id.split('/') {
+            path.push(sanitize(segment));
+        }
+        path.join(sanitize(commit))
+    }
+
+    /// Create an empty workspace, discarding what a previous run left behind
+    pub fn prepare(&self, source_id: &str, commit: &str) -> Result<Workspace> {
+        let path = self.path(source_id, commit);
+        if path.exists() {
+            std::fs::remove_dir_all(&path)?;
+        }
+
+        let workspace = Workspace { path };
+        std::fs::create_dir_all(workspace.scratch_dir())?;
+        std::fs::create_dir_all(workspace.output_dir())?;
+        debug!("Prepared workspace {:?}", workspace.path);
+
+        Ok(workspace)
+    }
+}
+
+impl Workspace {
+    /// Root of the workspace
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Scratch directory handed to plugins as their working directory
+    pub fn scratch_dir(&self) -> PathBuf {
+        self.path.join("scratch")
+    }
+
+    /// Directory generated files are staged in
+    pub fn output_dir(&self) -> PathBuf {
+        self.path.join("output")
+    }
+
+    /// Size of every file the output path will hold once the staged files are
+    /// published with `kept`, by path relative to the output path
+    ///
+    /// Files already at the output path count too, unless publishing removes them.
+    pub fn published_sizes(
+        &self,
+        output_path: &Path,
+        kept: &BTreeSet<PathBuf>,
+    ) -> Result<BTreeMap<PathBuf, u64>> {
+        let previous = published_files(output_path)?;
+        let mut sizes = BTreeMap::new();
+        for dir in [output_path.to_path_buf(), self.output_dir()] {
+            for entry in WalkDir::new(&dir).into_iter().filter_map(|e| e.ok()) {
+                if entry.file_type().is_file() {
+                    let relative = entry.path().strip_prefix(&dir)?.to_path_buf();
+                    if dir == output_path
+                        && (relative == Path::new(PUBLISHED_FILE)
+                            || previous.contains(&relative) && !kept.contains(&relative))
+                    {
+                        continue;
+                    }
+                    sizes.insert(relative, entry.metadata()?.len());
+                }
+            }
+        }
+        Ok(sizes)
+    }
+
+    /// Number of files staged for publishing
+    pub fn staged_files(&self) -> usize {
+        WalkDir::new(self.output_dir())
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .count()
+    }
+
+    /// Size on disk of the staged files at the output path, once published
+    pub fn published_bytes(&self, output_path: &Path) -> Result<u64> {
+        let staged = self.output_dir();
+        let mut bytes = 0;
+        for entry in WalkDir::new(&staged).into_iter().filter_map(|e| e.ok()) {
+            if entry.file_type().is_file() {
+                let target = output_path.join(entry.path().strip_prefix(&staged)?);
+                bytes += std::fs::metadata(target)?.len();
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Copy the staged files to the output path, returning how many were copied
+    ///
+    /// Text files are written with the given line endings, whichever plugin
+    /// staged them, and Jsonnet files are restyled to `style` and start with
+    /// `header` if given. Files the previous publish wrote are removed unless
+    /// staged again or listed in `kept`, paths relative to the output path of
+    /// files left as published.
+    pub fn publish(
+        &self,
+        output_path: &Path,
+        kept: &BTreeSet<PathBuf>,
+        newline: Newline,
+        style: &CodeStyle,
+        header: Option<&str>,
+    ) -> Result<usize> {
+        let staged = self.output_dir();
+        let previous = published_files(output_path)?;
+        let mut published = BTreeSet::new();
+        let mut copied = 0;
+
+        for entry in WalkDir::new(&staged).into_iter().filter_map(|e| e.ok()) {
+            let relative = entry.path().strip_prefix(&staged)?;
+            let target = output_path.join(relative);
+
+            if entry.file_type().is_dir() {
+                std::fs::create_dir_all(&target)?;
+            } else if entry.file_type().is_file() {
+                match std::fs::read_to_string(entry.path()) {
+                    Ok(content) if takes_header(&target) => write_generated(
+                        &target,
+                        &format!("{}{}", header.unwrap_or_default(), style.apply(&content)),
+                        newline,
+                    )?,
+                    Ok(content) => write_generated(&target, &content, newline)?,
+                    // Not UTF-8 text; copy as is
+                    Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+                        std::fs::copy(entry.path(), &target)?;
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+                published.insert(relative.to_path_buf());
+                copied += 1;
+            }
+        }
+
+        published.extend(
+            kept.iter()
+                .filter(|relative| output_path.join(relative).is_file())
+                .cloned(),
+        );
+        for relative in previous.difference(&published) {
+            let path = output_path.join(relative);
+            if path.is_file() {
+                std::fs::remove_file(&path)?;
+                debug!("Removed {:?}, no longer generated", path);
+                remove_empty_parents(&path, output_path);
+            }
+        }
+        if !published.is_empty() || !previous.is_empty() {
+            std::fs::create_dir_all(output_path)?;
+            std::fs::write(
+                output_path.join(PUBLISHED_FILE),
+                serde_json::to_string_pretty(&published)?,
+            )?;
+        }
+
+        Ok(copied)
+    }
+}
+
+/// Files the last publish wrote to an output path, ignoring any entry that
+/// would lead outside of it
+fn published_files(output_path: &Path) -> Result<BTreeSet<PathBuf>> {
+    let path = output_path.join(PUBLISHED_FILE);
+    if !path.exists() {
+        return Ok(BTreeSet::new());
+    }
+    let files: BTreeSet<PathBuf> = serde_json::from_str(&std::fs::read_to_string(&path)?)
+        .map_err(|e| anyhow!("Invalid published file list {:?}: {}", path, e))?;
+    Ok(files
+        .into_iter()
+        .filter(|file| {
+            file.components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        })
+        .collect())
+}
+
+/// Remove the directories between a removed file and the output path that are left empty
+fn remove_empty_parents(file: &Path, output_path: &Path) {
+    let mut dir = file.parent();
+    while let Some(current) = dir.filter(|dir| *dir != output_path) {
+        if std::fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+/// Make a source id segment or commit safe to use as a directory name
+fn sanitize(segment: &str) -> String {
+    let sanitized: String = segment
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    match sanitized.as_str() {
+        "" | "." | ".." => "_".to_string(),
+        _ => sanitized,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_workspace_layout_and_publish() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = WorkspaceManager::with_root(temp_dir.path().join("workspaces"));
+
+        assert_eq!(
+            manager.path("fleet/alpha", "sha256:abc"),
+            temp_dir.path().join("workspaces/fleet/alpha/sha256_abc")
+        );
+        assert_eq!(
+            manager.path("../escape", "HEAD"),
+            temp_dir.path().join("workspaces/_/escape/HEAD")
+        );
+
+        let workspace = manager.prepare("crds", "0123abcd").unwrap();
+        std::fs::create_dir_all(workspace.output_dir().join("v1")).unwrap();
+        std::fs::write(
+            workspace.output_dir().join("v1/widget.libsonnet"),
+            "{\n  a: \"b\",\n}",
+        )
+        .unwrap();
+        std::fs::write(workspace.scratch_dir().join("partial.json"), "{}").unwrap();
+
+        let output = temp_dir.path().join("out");
+        assert_eq!(
+            workspace
+                .publish(
+                    &output,
+                    &BTreeSet::new(),
+                    Newline::Crlf,
+                    &CodeStyle {
+                        indent: 4,
+                        quotes: jsonnet_generator::style::QuoteStyle::Single,
+                        ..Default::default()
+                    },
+                    Some("// License\n")
+                )
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            std::fs::read_to_string(output.join("v1/widget.libsonnet")).unwrap(),
+            "// License\r\n{\r\n    a: 'b',\r\n}\r\n"
+        );
+        assert!(!output.join("partial.json").exists());
+
+        let again = manager.prepare("crds", "0123abcd").unwrap();
+        assert!(!again.scratch_dir().join("partial.json").exists());
+    }
+
+    #[test]
+    fn test_publish_removes_files_no_longer_staged() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = WorkspaceManager::with_root(temp_dir.path().join("workspaces"));
+        let output = temp_dir.path().join("out");
+        std::fs::create_dir_all(&output).unwrap();
+        std::fs::write(output.join("README.md"), "hand-written").unwrap();
+
+        let stage = |commit: &str, files: &[&str]| {
+            let workspace = manager.prepare("crds", commit).unwrap();
+            for file in files {
+                let path = workspace.output_dir().join(file);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(path, "{}").unwrap();
+            }
+            workspace
+        };
+        let publish = |workspace: &Workspace, kept: &[&str]| {
+            let kept = kept.iter().map(PathBuf::from).collect();
+            workspace
+                .publish(&output, &kept, Newline::Lf, &CodeStyle::default(), None)
+                .unwrap()
+        };
+
+        let first = stage(
+            "1",
+            &[
+                "v1/widget.libsonnet",
+                "v1/gadget.libsonnet",
+                "v2/old.libsonnet",
+            ],
+        );
+        assert_eq!(publish(&first, &[]), 3);
+
+        // Unchanged schema files are kept without being staged
+        let second = stage("2", &["v1/widget.libsonnet"]);
+        assert_eq!(publish(&second, &["v1/gadget.libsonnet"]), 1);
+        assert!(output.join("v1/gadget.libsonnet").exists());
+        assert!(!output.join("v2").exists());
+        assert!(output.join("README.md").exists());
+
+        let third = stage("3", &["v1/widget.libsonnet"]);
+        publish(&third, &[]);
+        assert!(!output.join("v1/gadget.libsonnet").exists());
+        assert!(output.join("v1/widget.libsonnet").exists());
+    }
+}
@@ -0,0 +1,138 @@
+//! Crossplane composite resource definitions
+//!
+//! An XRD (`apiextensions.crossplane.io` `CompositeResourceDefinition`) has
+//! the layout of a v1 CRD: a group, names and versions with an OpenAPI
+//! schema. Crossplane serves a CRD for the composite resource it defines and,
+//! when it declares `claimNames`, one for its claim. The parser expands each
+//! XRD into those CRDs, so composite resources and claims get the same
+//! builders as any other custom resource.
+
+use serde_yaml::{Mapping, Value};
+
+/// Kind of Crossplane composite resource definitions
+pub const XRD_KIND: &str = "CompositeResourceDefinition";
+
+/// API group of Crossplane composite resource definitions
+pub const XRD_GROUP: &str = "apiextensions.crossplane.io";
+
+/// Whether a document is a Crossplane composite resource definition
+pub fn is_xrd(doc: &Value) -> bool {
+    doc.get("kind").and_then(Value::as_str) == Some(XRD_KIND)
+        && doc
+            .get("apiVersion")
+            .and_then(Value::as_str)
+            .is_some_and(|api_version| api_version.starts_with(&format!("{XRD_GROUP}/")))
+}
+
+/// CRDs Crossplane serves for an XRD: the composite resource, then the claim
+///
+/// Versions keep their name, served and storage flags and schema; fields
+/// specific to XRDs, such as `referenceable`, are dropped.
+pub fn to_crds(xrd: &Value) -> Vec<Value> {
+    let Some(spec) = xrd.get("spec") else {
+        return Vec::new();
+    };
+    let versions: Vec<Value> = spec
+        .get("versions")
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+        .map(|version| {
+            let mut crd_version = Mapping::new();
+            for field in [
+                "name",
+                "served",
+                "storage",
+                "schema",
+                "additionalPrinterColumns",
+            ] {
+                if let Some(value) = version.get(field) {
+                    crd_version.insert(field.into(), value.clone());
+                }
+            }
+            Value::Mapping(crd_version)
+        })
+        .collect();
+
+    ["names", "claimNames"]
+        .iter()
+        .filter_map(|field| spec.get(*field))
+        .map(|names| {
+            let plural = names.get("plural").and_then(Value::as_str);
+            let group = spec.get("group").and_then(Value::as_str);
+            let name = match (plural, group) {
+                (Some(plural), Some(group)) => format!("{plural}.{group}"),
+                _ => xrd
+                    .get("metadata")
+                    .and_then(|metadata| metadata.get("name"))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+            };
+
+            let mut crd_spec = Mapping::new();
+            if let Some(group) = spec.get("group") {
+                crd_spec.insert("group".into(), group.clone());
+            }
+            crd_spec.insert("names".into(), names.clone());
+            crd_spec.insert("versions".into(), Value::Sequence(versions.clone()));
+
+            let mut metadata = Mapping::new();
+            metadata.insert("name".into(), name.into());
+            let mut crd = Mapping::new();
+            crd.insert("apiVersion".into(), "apiextensions.k8s.io/v1".into());
+            crd.insert("kind".into(), "CustomResourceDefinition".into());
+            crd.insert("metadata".into(), Value::Mapping(metadata));
+            crd.insert("spec".into(), Value::Mapping(crd_spec));
+            Value::Mapping(crd)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_crds() {
+        let xrd: Value = serde_yaml::from_str(
+            r#"
+            apiVersion: apiextensions.crossplane.io/v1
+            kind: CompositeResourceDefinition
+            metadata: {name: xpostgresqlinstances.database.example.org}
+            spec:
+              group: database.example.org
+              names: {kind: XPostgreSQLInstance, plural: xpostgresqlinstances}
+              claimNames: {kind: PostgreSQLInstance, plural: postgresqlinstances}
+              versions:
+                - name: v1alpha1
+                  served: true
+                  referenceable: true
+                  schema:
+                    openAPIV3Schema:
+                      type: object
+        "#,
+        )
+        .unwrap();
+        assert!(is_xrd(&xrd));
+
+        let crds = to_crds(&xrd);
+        assert_eq!(crds.len(), 2);
+        assert_eq!(
+            crds[0]["metadata"]["name"],
+            "xpostgresqlinstances.database.example.org"
+        );
+        assert_eq!(crds[1]["spec"]["names"]["kind"], "PostgreSQLInstance");
+        assert_eq!(
+            crds[1]["metadata"]["name"],
+            "postgresqlinstances.database.example.org"
+        );
+        assert!(crds[0]["spec"]["versions"][0]
+            .get("referenceable")
+            .is_none());
+        assert_eq!(
+            crds[0]["spec"]["versions"][0]["schema"]["openAPIV3Schema"]["type"],
+            "object"
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! Generation hints CRDs carry as annotations
+//!
+//! Maintainers of an upstream or a fork can steer generation from the CRD
+//! manifest itself, without touching the configuration of every consumer:
+//!
+//! - `gensonnet.dev/skip: "true"` leaves the CRD out of generation
+//! - `gensonnet.dev/rename: Foo` generates the CRD's library under another
+//!   name; the `kind` of the resources it creates is unchanged
+
+use serde_yaml::Value;
+
+/// Prefix of the annotations holding generation hints
+pub const PREFIX: &str = "gensonnet.dev/";
+
+/// Annotation leaving a CRD out of generation
+pub const SKIP: &str = "gensonnet.dev/skip";
+
+/// Annotation naming the library generated for a CRD
+pub const RENAME: &str = "gensonnet.dev/rename";
+
+/// Hints read from a CRD's annotations
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GenerationHints {
+    /// Leave the CRD out of generation
+    pub skip: bool,
+
+    /// Name to generate the CRD's library under instead of the CRD's name
+    pub rename: Option<String>,
+}
+
+impl GenerationHints {
+    /// Read the hints from a CRD's `metadata`
+    ///
+    /// Returns them with the problems of hint annotations that were ignored:
+    /// unknown `gensonnet.dev/` annotations and invalid values.
+    pub fn from_metadata(metadata: &Value) -> (Self, Vec<String>) {
+        let mut hints = Self::default();
+        let mut problems = Vec::new();
+        let Some(annotations) = metadata.get("annotations").and_then(Value::as_mapping) else {
+            return (hints, problems);
+        };
+
+        for (key, value) in annotations {
+            let Some(key) = key.as_str().filter(|key| key.starts_with(PREFIX)) else {
+                continue;
+            };
+            match key {
+                SKIP => match value {
+                    Value::Bool(skip) => hints.skip = *skip,
+                    Value::String(skip) if skip == "true" || skip == "false" => {
+                        hints.skip = skip == "true"
+                    }
+                    _ => problems.push(format!(
+                        "has annotation {SKIP} that is neither \"true\" nor \"false\""
+                    )),
+                },
+                RENAME => match value.as_str().map(str::trim) {
+                    Some(name)
+                        if !name.is_empty()
+                            && !name.contains(['/', '\\'])
+                            && !name.contains(char::is_whitespace) =>
+                    {
+                        hints.rename = Some(name.to_string())
+                    }
+                    _ => problems.push(format!(
+                        "has annotation {RENAME} that is not a name without slashes or spaces"
+                    )),
+                },
+                _ => problems.push(format!("has unknown annotation {key}")),
+            }
+        }
+        (hints, problems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generation_hints() {
+        let metadata: Value = serde_yaml::from_str(
+            r#"
+name: widgets.example.com
+annotations:
+  gensonnet.dev/skip: "false"
+  gensonnet.dev/rename: Gizmo
+  controller-gen.kubebuilder.io/version: v0.14.0
+"#,
+        )
+        .unwrap();
+        let (hints, problems) = GenerationHints::from_metadata(&metadata);
+        assert_eq!(
+            hints,
+            GenerationHints {
+                skip: false,
+                rename: Some("Gizmo".to_string()),
+            }
+        );
+        assert!(problems.is_empty());
+
+        let metadata: Value =
+            serde_yaml::from_str("annotations: {gensonnet.dev/skip: true}").unwrap();
+        assert!(GenerationHints::from_metadata(&metadata).0.skip);
+
+        // Invalid and unknown hints are reported and ignored
+        let metadata: Value = serde_yaml::from_str(
+            r#"
+annotations:
+  gensonnet.dev/skip: "yes"
+  gensonnet.dev/rename: "a/b"
+  gensonnet.dev/skpi: "true"
+"#,
+        )
+        .unwrap();
+        let (hints, problems) = GenerationHints::from_metadata(&metadata);
+        assert_eq!(hints, GenerationHints::default());
+        assert_eq!(problems.len(), 3);
+        assert!(problems[2].contains("gensonnet.dev/skpi"), "{problems:?}");
+    }
+}
@@ -1,9 +1,21 @@
 //! CRD (CustomResourceDefinition) parsing and schema extraction
 
+pub mod crossplane;
+pub mod hints;
+pub mod normalize;
 pub mod parser;
+pub mod pointer;
+pub mod provenance;
 pub mod schema;
+pub mod strict;
 pub mod types;
+pub mod v1beta1;
 
+pub use hints::GenerationHints;
 pub use parser::CrdParser;
 pub use schema::CrdSchema;
-pub use types::{FieldAnalysis, SchemaAnalysis, ValidationRules};
+pub use strict::UnsupportedConstruct;
+pub use types::{
+    ConversionInfo, ConversionStrategy, FieldAnalysis, PrinterColumn, SchemaAnalysis,
+    ValidationRules, VersionSelection,
+};
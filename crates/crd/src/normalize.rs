@@ -0,0 +1,90 @@
+//! Normalization of parsed YAML documents
+//!
+//! Aliases are resolved by the YAML parser itself, but merge keys (`<<`)
+//! and custom tags such as `!Ref` survive into the parsed value. The parser
+//! normalizes each document so schema analysis and generation only ever see
+//! plain mappings, sequences and scalars.
+
+use anyhow::{anyhow, Result};
+use serde_yaml::{Mapping, Value};
+
+/// Normalize a parsed document in place
+///
+/// Tags are unwrapped to the value they annotate, then `<<` merge keys are
+/// applied, keys already present in a mapping taking precedence. With
+/// `unwrap_tags` false tags are kept, so strict mode can report them.
+pub fn normalize(value: &mut Value, unwrap_tags: bool) -> Result<()> {
+    if unwrap_tags {
+        untag(value);
+    }
+    value
+        .apply_merge()
+        .map_err(|e| anyhow!("Invalid YAML merge key: {}", e))
+}
+
+/// Replace every tagged value, including mapping keys, by its inner value
+fn untag(value: &mut Value) {
+    match value {
+        Value::Tagged(tagged) => {
+            let mut inner = std::mem::take(&mut tagged.value);
+            untag(&mut inner);
+            *value = inner;
+        }
+        Value::Sequence(sequence) => sequence.iter_mut().for_each(untag),
+        Value::Mapping(mapping) => {
+            let mut untagged = Mapping::with_capacity(mapping.len());
+            for (mut key, mut item) in std::mem::take(mapping) {
+                untag(&mut key);
+                untag(&mut item);
+                untagged.insert(key, item);
+            }
+            *mapping = untagged;
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_merges_and_untags() {
+        let mut doc: Value = serde_yaml::from_str(
+            r#"
+shared: &shared
+  type: string
+  maxLength: 63
+properties:
+  name:
+    <<: *shared
+    description: Name
+  zone:
+    <<: [*shared, {pattern: "^[a-z]+$"}]
+    maxLength: 16
+  size: !Ref
+    type: !custom integer
+    default: !Ref 3
+"#,
+        )
+        .unwrap();
+
+        let mut strict = doc.clone();
+        normalize(&mut strict, false).unwrap();
+        assert!(matches!(strict["properties"]["size"], Value::Tagged(_)));
+
+        normalize(&mut doc, true).unwrap();
+        let name = &doc["properties"]["name"];
+        assert_eq!(name["type"], "string");
+        assert_eq!(name["maxLength"], 63);
+        assert!(name.get("<<").is_none());
+
+        let zone = &doc["properties"]["zone"];
+        assert_eq!(zone["maxLength"], 16);
+        assert_eq!(zone["pattern"], "^[a-z]+$");
+
+        let size = &doc["properties"]["size"];
+        assert_eq!(size["type"], "integer");
+        assert_eq!(size["default"], 3);
+    }
+}
@@ -1,14 +1,24 @@
 //! CRD parser implementation
 
 use crate::schema::CrdSchema;
-use crate::types::{FieldAnalysis, SchemaAnalysis, ValidationRules};
+use crate::types::{
+    ConversionInfo, ConversionStrategy, FieldAnalysis, SchemaAnalysis, ValidationRules,
+    VersionSelection,
+};
 use anyhow::{anyhow, Result};
 use glob::Pattern;
-use std::path::Path; 
-use tracing::{debug, info};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::{debug, info, warn};
 use walkdir::WalkDir;
 
-pub struct CrdParser;
+#[derive(Debug, Clone)]
+pub struct CrdParser {
+    strict_schemas: bool,
+    strict_crds: bool,
+    relative_paths: bool,
+    versions: VersionSelection,
+}
 
 impl Default for CrdParser {
     fn default() -> Self {
@@ -18,7 +28,39 @@ impl Default for CrdParser {
 
 impl CrdParser {
     pub fn new() -> Self {
-        Self
+        Self {
+            strict_schemas: false,
+            strict_crds: false,
+            relative_paths: false,
+            versions: VersionSelection::All,
+        }
+    }
+
+    /// Fail on schema constructs the generator cannot represent instead of
+    /// falling back to plain objects
+    pub fn with_strict_schemas(mut self, strict_schemas: bool) -> Self {
+        self.strict_schemas = strict_schemas;
+        self
+    }
+
+    /// Fail on invalid CRD manifests instead of recovering what they declare
+    /// and skipping what cannot be recovered
+    pub fn with_strict_crds(mut self, strict_crds: bool) -> Self {
+        self.strict_crds = strict_crds;
+        self
+    }
+
+    /// Record the source paths of schemas relative to the directory parsed
+    pub fn with_relative_paths(mut self, relative_paths: bool) -> Self {
+        self.relative_paths = relative_paths;
+        self
+    }
+
+    /// Only generate the versions `versions` selects; CRDs without such a
+    /// version yield no schema
+    pub fn with_versions(mut self, versions: VersionSelection) -> Self {
+        self.versions = versions;
+        self
     }
 
     /// Parse CRDs from a directory, applying filters
@@ -30,8 +72,12 @@ impl CrdParser {
         info!("Parsing CRDs from directory: {:?}", dir_path);
 
         let mut schemas = Vec::new();
+        let mut unsupported = Vec::new();
+        let mut invalid = Vec::new();
 
+        // Files are read in name order, whatever order the file system lists them in
         for entry in WalkDir::new(dir_path)
+            .sort_by_file_name()
             .into_iter()
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
@@ -52,30 +98,89 @@ impl CrdParser {
                 Ok(mut crd_schemas) => {
                     // Apply filters
                     crd_schemas.retain(|schema| self.matches_filters(schema, filters));
+                    if self.relative_paths {
+                        for schema in &mut crd_schemas {
+                            if let Ok(relative) = path.strip_prefix(dir_path) {
+                                schema.source_path = relative.to_path_buf();
+                            }
+                        }
+                    }
+                    if self.strict_schemas {
+                        for schema in &crd_schemas {
+                            unsupported.extend(
+                                crate::strict::find_unsupported(&schema.schema)
+                                    .into_iter()
+                                    .map(|construct| {
+                                        format!(
+                                            "{} {} ({}) {}",
+                                            schema.api_version,
+                                            schema.kind,
+                                            path.display(),
+                                            construct
+                                        )
+                                    }),
+                            );
+                        }
+                    }
                     schemas.extend(crd_schemas);
                 }
-                Err(e) => {
-                    debug!("Failed to parse {} as CRD: {}", path.display(), e);
-                    // Continue with other files
-                }
+                Err(e) if self.strict_crds => invalid.push(format!("{}: {}", path.display(), e)),
+                Err(e) => warn!("Skipping invalid CRD {}: {}", path.display(), e),
             }
         }
 
+        if !invalid.is_empty() {
+            return Err(anyhow!(
+                "Strict CRD mode found {} invalid CRDs:\n  {}",
+                invalid.len(),
+                invalid.join("\n  ")
+            ));
+        }
+
+        if !unsupported.is_empty() {
+            return Err(anyhow!(
+                "Strict schema mode found {} unsupported constructs:\n  {}",
+                unsupported.len(),
+                unsupported.join("\n  ")
+            ));
+        }
+
         info!("Found {} CRD schemas after filtering", schemas.len());
         Ok(schemas)
     }
 
     /// Parse a single CRD file
+    ///
+    /// Files that are not YAML are not CRDs and yield no schemas.
     fn parse_crd_file(&self, path: &Path) -> Result<Vec<CrdSchema>> {
-        let content = std::fs::read_to_string(path)?;
+        self.parse_crd_content(&std::fs::read_to_string(path)?, path)
+    }
 
+    /// Parse the content of a CRD file
+    fn parse_crd_content(&self, content: &str, path: &Path) -> Result<Vec<CrdSchema>> {
         // Try to parse as a single document first
-        let doc: serde_yaml::Value = serde_yaml::from_str(&content)?;
+        let mut doc: serde_yaml::Value = match serde_yaml::from_str(content) {
+            Ok(doc) => doc,
+            Err(e) => {
+                debug!("Failed to parse {} as CRD: {}", path.display(), e);
+                return Ok(Vec::new());
+            }
+        };
+        crate::normalize::normalize(&mut doc, !self.strict_schemas)?;
 
-        let mut schemas = Vec::new();
+        // Crossplane XRDs stand for the CRDs of their composite resource and claim
+        let documents = if crate::crossplane::is_xrd(&doc) {
+            crate::crossplane::to_crds(&doc)
+        } else {
+            vec![doc.clone()]
+        };
 
-        if let Some(crd) = self.extract_crd_from_document(&doc, path)? {
-            schemas.push(crd);
+        let mut schemas = Vec::new();
+        for document in &documents {
+            if let Some(mut crd) = self.extract_crd_from_document(document, path)? {
+                crd.field_lines = field_lines(content, &doc, &crd.version);
+                schemas.push(crd);
+            }
         }
 
         Ok(schemas)
@@ -95,6 +200,8 @@ impl CrdParser {
         } else {
             return Ok(None);
         }
+        let mut doc = doc.clone();
+        crate::v1beta1::upgrade(&mut doc);
 
         // Extract metadata
         let metadata = doc
@@ -114,47 +221,238 @@ impl CrdParser {
             .and_then(|g| g.as_str())
             .ok_or_else(|| anyhow!("CRD missing group"))?;
 
+        // Problems recovered from, or errors in strict mode
+        let mut recovered = Vec::new();
+        let mut recover = |problem: String, recovery: &str| {
+            if self.strict_crds {
+                return Err(anyhow!("CRD {} {}", name, problem));
+            }
+            recovered.push(format!("CRD {name} {problem}; {recovery}"));
+            Ok(())
+        };
+
+        // Hints maintainers left in the CRD's annotations
+        let (hints, problems) = crate::hints::GenerationHints::from_metadata(metadata);
+        for problem in problems {
+            recover(problem, "ignoring it")?;
+        }
+        if hints.skip {
+            info!("Skipping CRD {}: annotated {}", name, crate::hints::SKIP);
+            return Ok(None);
+        }
+
         // Extract the kind from spec.names.kind
-        let kind = spec
-            .get("names")
-            .and_then(|n| n.get("kind"))
-            .and_then(|k| k.as_str())
-            .unwrap_or(name); // Fallback to CRD name if kind is not specified
+        let names = spec.get("names");
+        let kind = match names.and_then(|n| n.get("kind")).and_then(|k| k.as_str()) {
+            Some(kind) => kind.to_string(),
+            None => {
+                let kind = kind_from_names(names, name);
+                recover(
+                    "is missing spec.names.kind".to_string(),
+                    &format!("using {kind}"),
+                )?;
+                kind
+            }
+        };
+
+        let categories: Vec<String> = names
+            .and_then(|n| n.get("categories"))
+            .and_then(|c| c.as_sequence())
+            .map(|seq| {
+                seq.iter()
+                    .filter_map(|c| c.as_str())
+                    .map(|c| c.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let conversion = self.extract_conversion(spec, &mut recover)?;
 
         let versions = spec
             .get("versions")
             .and_then(|v| v.as_sequence())
             .ok_or_else(|| anyhow!("CRD missing versions"))?;
+        if versions.is_empty() {
+            return Err(anyhow!("CRD {} declares no versions", name));
+        }
+        let flag = |version_doc: &serde_yaml::Value, name: &str| {
+            version_doc
+                .get(name)
+                .and_then(|f| f.as_bool())
+                .unwrap_or(false)
+        };
+        let versions: Vec<&serde_yaml::Value> = versions
+            .iter()
+            .filter(|v| self.versions.allows(flag(v, "served"), flag(v, "storage")))
+            .collect();
+        if versions.is_empty() {
+            info!(
+                "Skipping CRD {}: no version is {}",
+                name,
+                match self.versions {
+                    VersionSelection::Storage => "served and stored",
+                    _ => "served",
+                }
+            );
+            return Ok(None);
+        }
+        let version_schema = |version_doc: &serde_yaml::Value| {
+            version_doc
+                .get("schema")
+                .and_then(|s| s.get("openAPIV3Schema"))
+                .cloned()
+        };
 
-        let mut crd_schemas = Vec::new();
-
-        for version_doc in versions {
-            let version_name = version_doc
+        // The first version with a schema is generated
+        let version_name = |version_doc: &serde_yaml::Value| {
+            version_doc
                 .get("name")
                 .and_then(|n| n.as_str())
-                .ok_or_else(|| anyhow!("CRD version missing name"))?;
+                .map(str::to_string)
+                .ok_or_else(|| anyhow!("CRD version missing name"))
+        };
+        let version_doc = match versions
+            .iter()
+            .copied()
+            .find(|v| version_schema(v).is_some())
+        {
+            Some(version_doc) => {
+                for skipped in versions.iter().take_while(|v| version_schema(v).is_none()) {
+                    recover(
+                        format!("version {} has no schema", version_name(skipped)?),
+                        &format!("generating version {}", version_name(version_doc)?),
+                    )?;
+                }
+                version_doc
+            }
+            None => {
+                // Legacy CRDs keep unknown fields and need not declare a schema
+                let legacy = spec
+                    .get("preserveUnknownFields")
+                    .and_then(|p| p.as_bool())
+                    .unwrap_or(false);
+                recover(
+                    if legacy {
+                        "preserves unknown fields without a schema".to_string()
+                    } else {
+                        "declares no schema".to_string()
+                    },
+                    "generating it without validation",
+                )?;
+                versions[0]
+            }
+        };
+        let version_name = version_name(version_doc)?;
+        let schema = version_schema(version_doc).unwrap_or_else(|| {
+            serde_yaml::from_str("{type: object, x-kubernetes-preserve-unknown-fields: true}")
+                .unwrap_or_default()
+        });
+
+        let printer_columns = match version_doc.get("additionalPrinterColumns") {
+            Some(columns) => serde_yaml::from_value(columns.clone())
+                .map_err(|e| anyhow!("Invalid additionalPrinterColumns in {}: {}", name, e))?,
+            None => Vec::new(),
+        };
 
-            let schema = version_doc
-                .get("schema")
-                .and_then(|s| s.get("openAPIV3Schema"))
-                .ok_or_else(|| anyhow!("CRD version missing openAPIV3Schema"))?;
+        for problem in &recovered {
+            warn!("{}", problem);
+        }
 
-            let crd_schema = CrdSchema {
-                name: name.to_string(),
-                group: group.to_string(),
-                version: version_name.to_string(),
-                api_version: format!("{group}/{version_name}"),
-                kind: kind.to_string(),
-                schema: schema.clone(),
-                source_path: source_path.to_path_buf(),
-                validation_rules: self.extract_validation_rules(schema)?,
-                schema_analysis: self.analyze_schema(schema)?,
-            };
+        Ok(Some(CrdSchema {
+            name: hints.rename.unwrap_or_else(|| name.to_string()),
+            group: group.to_string(),
+            version: version_name.clone(),
+            api_version: format!("{group}/{version_name}"),
+            served: flag(version_doc, "served"),
+            storage: flag(version_doc, "storage"),
+            kind,
+            categories,
+            printer_columns,
+            validation_rules: self.extract_validation_rules(&schema)?,
+            schema_analysis: self.analyze_schema(&schema)?,
+            schema,
+            source_path: source_path.to_path_buf(),
+            conversion,
+            field_lines: Default::default(),
+            recovered,
+        }))
+    }
 
-            crd_schemas.push(crd_schema);
-        }
+    /// Recompute the validation rules and analysis of a schema whose
+    /// OpenAPI schema was rewritten after parsing
+    pub fn refresh_analysis(&self, schema: &mut CrdSchema) -> Result<()> {
+        schema.validation_rules = self.extract_validation_rules(&schema.schema)?;
+        schema.schema_analysis = self.analyze_schema(&schema.schema)?;
+        Ok(())
+    }
+
+    /// Extract the conversion strategy and webhook from spec.conversion
+    ///
+    /// An unknown strategy goes through `recover` and is treated as a webhook,
+    /// so generated code leaves converting to the API server.
+    fn extract_conversion(
+        &self,
+        spec: &serde_yaml::Value,
+        recover: &mut impl FnMut(String, &str) -> Result<()>,
+    ) -> Result<ConversionInfo> {
+        let Some(conversion) = spec.get("conversion") else {
+            return Ok(ConversionInfo::default());
+        };
 
-        Ok(Some(crd_schemas.into_iter().next().unwrap()))
+        let strategy = match conversion.get("strategy").and_then(|s| s.as_str()) {
+            None | Some("None") => ConversionStrategy::None,
+            Some("Webhook") => ConversionStrategy::Webhook,
+            Some(other) => {
+                recover(
+                    format!("has unknown conversion strategy {other}"),
+                    "converting through the API server",
+                )?;
+                ConversionStrategy::Webhook
+            }
+        };
+
+        // apiextensions/v1 nests the webhook settings under `webhook`,
+        // v1beta1 keeps them directly on `conversion`
+        let webhook = conversion.get("webhook").unwrap_or(conversion);
+        let client_config = webhook
+            .get("clientConfig")
+            .or_else(|| conversion.get("webhookClientConfig"));
+
+        let webhook_endpoint = client_config.and_then(|config| {
+            if let Some(url) = config.get("url").and_then(|u| u.as_str()) {
+                return Some(url.to_string());
+            }
+            let service = config.get("service")?;
+            let mut endpoint = format!(
+                "service {}/{}",
+                service.get("namespace")?.as_str()?,
+                service.get("name")?.as_str()?
+            );
+            if let Some(port) = service.get("port").and_then(|p| p.as_u64()) {
+                endpoint.push_str(&format!(":{port}"));
+            }
+            if let Some(path) = service.get("path").and_then(|p| p.as_str()) {
+                endpoint.push_str(path);
+            }
+            Some(endpoint)
+        });
+
+        let conversion_review_versions = webhook
+            .get("conversionReviewVersions")
+            .and_then(|v| v.as_sequence())
+            .map(|seq| {
+                seq.iter()
+                    .filter_map(|v| v.as_str())
+                    .map(|v| v.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(ConversionInfo {
+            strategy,
+            webhook_endpoint,
+            conversion_review_versions,
+        })
     }
 
     /// Extract validation rules from OpenAPI schema
@@ -250,25 +548,18 @@ impl CrdParser {
             .and_then(|t| t.as_str())
             .unwrap_or("object")
             .to_string();
-        
+
         let mut analysis = SchemaAnalysis {
             schema_type,
             ..Default::default()
         };
 
-        // Analyze object properties
-        if let Some(properties) = schema.get("properties").and_then(|p| p.as_mapping()) {
-            for (key, value) in properties {
-                if let Some(key_str) = key.as_str() {
-                    let field_analysis = self.analyze_field_schema(value)?;
-                    analysis.fields.insert(key_str.to_string(), field_analysis);
-                }
-            }
-        }
+        // Analyze object properties, nested ones included
+        self.analyze_fields(schema, "", &mut analysis.fields)?;
 
         // Analyze array items
         if let Some(items) = schema.get("items") {
-            analysis.array_item_type = Some(self.analyze_field_schema(items)?);
+            analysis.array_item_type = Some(self.analyze_field_schema(items, "")?);
         }
 
         // Check for oneOf, anyOf, allOf
@@ -292,8 +583,40 @@ impl CrdParser {
         Ok(analysis)
     }
 
+    /// Analyze the fields below the field at `pointer`, stepping through arrays to their items
+    fn analyze_fields(
+        &self,
+        schema: &serde_yaml::Value,
+        pointer: &str,
+        fields: &mut BTreeMap<String, FieldAnalysis>,
+    ) -> Result<()> {
+        let mut schema = schema;
+        while schema.get("properties").is_none() {
+            match schema.get("items") {
+                Some(items) if items.is_mapping() => schema = items,
+                _ => return Ok(()),
+            }
+        }
+
+        if let Some(properties) = schema.get("properties").and_then(|p| p.as_mapping()) {
+            for (key, value) in properties {
+                if let Some(key_str) = key.as_str() {
+                    let field_pointer = crate::pointer::child(pointer, key_str);
+                    self.analyze_fields(value, &field_pointer, fields)?;
+                    let field_analysis = self.analyze_field_schema(value, &field_pointer)?;
+                    fields.insert(field_pointer, field_analysis);
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Analyze a field schema
-    fn analyze_field_schema(&self, schema: &serde_yaml::Value) -> Result<FieldAnalysis> {
+    fn analyze_field_schema(
+        &self,
+        schema: &serde_yaml::Value,
+        pointer: &str,
+    ) -> Result<FieldAnalysis> {
         let field_type = schema
             .get("type")
             .and_then(|t| t.as_str())
@@ -303,6 +626,7 @@ impl CrdParser {
         let validation_rules = self.extract_validation_rules(schema)?;
 
         let mut analysis = FieldAnalysis {
+            pointer: pointer.to_string(),
             field_type,
             validation_rules,
             ..Default::default()
@@ -337,7 +661,7 @@ impl CrdParser {
     }
 
     /// Check if a CRD schema matches a specific filter pattern
-    fn matches_filter(&self, schema: &CrdSchema, filter: &str) -> bool {
+    pub fn matches_filter(&self, schema: &CrdSchema, filter: &str) -> bool {
         // Convert filter to glob pattern
         let pattern = match Pattern::new(filter) {
             Ok(p) => p,
@@ -349,6 +673,63 @@ impl CrdParser {
     }
 }
 
+/// Lines of the top-level properties of a version's schema in a CRD or XRD file
+fn field_lines(content: &str, doc: &serde_yaml::Value, version: &str) -> BTreeMap<String, usize> {
+    let index = doc
+        .get("spec")
+        .and_then(|spec| spec.get("versions"))
+        .and_then(|versions| versions.as_sequence())
+        .and_then(|versions| {
+            versions
+                .iter()
+                .position(|v| v.get("name").and_then(|n| n.as_str()) == Some(version))
+        });
+    let lines = match index {
+        Some(index) => crate::provenance::property_lines(
+            content,
+            &[
+                "spec",
+                "versions",
+                &index.to_string(),
+                "schema",
+                "openAPIV3Schema",
+                "properties",
+            ],
+        ),
+        None => BTreeMap::new(),
+    };
+    if !lines.is_empty() {
+        return lines;
+    }
+    // v1beta1 CRDs may declare one schema for all versions
+    crate::provenance::property_lines(
+        content,
+        &["spec", "validation", "openAPIV3Schema", "properties"],
+    )
+}
+
+/// Kind of a CRD without `spec.names.kind`, from its singular or plural name
+///
+/// `widget` and `widgets.example.com` both give `Widget`.
+fn kind_from_names(names: Option<&serde_yaml::Value>, crd_name: &str) -> String {
+    let singular = names
+        .and_then(|n| n.get("singular"))
+        .and_then(|s| s.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            let plural = names
+                .and_then(|n| n.get("plural"))
+                .and_then(|p| p.as_str())
+                .unwrap_or_else(|| crd_name.split('.').next().unwrap_or(crd_name));
+            plural.strip_suffix('s').unwrap_or(plural).to_string()
+        });
+    let mut chars = singular.chars();
+    match chars.next() {
+        Some(first) => format!("{}{}", first.to_uppercase(), chars.as_str()),
+        None => crd_name.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -361,11 +742,18 @@ mod tests {
             group: "test.example.com".to_string(),
             version: "v1".to_string(),
             api_version: "test.example.com/v1".to_string(),
+            served: true,
+            storage: true,
             kind: "TestResource".to_string(),
+            categories: Vec::new(),
+            printer_columns: Vec::new(),
             schema: serde_yaml::Value::Null,
             source_path: std::path::PathBuf::from("test.yaml"),
             validation_rules: crate::types::ValidationRules::default(),
             schema_analysis: crate::types::SchemaAnalysis::default(),
+            conversion: crate::types::ConversionInfo::default(),
+            field_lines: Default::default(),
+            recovered: Vec::new(),
         };
 
         // Test exact match
@@ -386,11 +774,18 @@ mod tests {
             group: "test.example.com".to_string(),
             version: "v1".to_string(),
             api_version: "test.example.com/v1".to_string(),
+            served: true,
+            storage: true,
             kind: "TestResource".to_string(),
+            categories: Vec::new(),
+            printer_columns: Vec::new(),
             schema: serde_yaml::Value::Null,
             source_path: std::path::PathBuf::from("test.yaml"),
             validation_rules: crate::types::ValidationRules::default(),
             schema_analysis: crate::types::SchemaAnalysis::default(),
+            conversion: crate::types::ConversionInfo::default(),
+            field_lines: Default::default(),
+            recovered: Vec::new(),
         };
 
         assert!(parser.matches_filters(&schema, &[]));
@@ -419,4 +814,427 @@ mod tests {
         assert_eq!(rules.description, Some("A test field".to_string()));
         assert_eq!(rules.enum_values, vec!["value1", "value2", "value3"]);
     }
+
+    #[test]
+    fn test_schema_analysis_pointers() {
+        let parser = CrdParser::new();
+        let schema = serde_yaml::from_str(
+            r#"
+            type: object
+            properties:
+              spec:
+                type: object
+                properties:
+                  replicas: {type: integer, minimum: 1}
+                  containers:
+                    type: array
+                    items:
+                      type: object
+                      properties:
+                        name: {type: string}
+              metadata:
+                type: object
+                properties:
+                  annotations/key: {type: string}
+        "#,
+        )
+        .unwrap();
+
+        let analysis = parser.analyze_schema(&schema).unwrap();
+        assert_eq!(
+            analysis.fields.keys().collect::<Vec<_>>(),
+            [
+                "/metadata",
+                "/metadata/annotations~1key",
+                "/spec",
+                "/spec/containers",
+                "/spec/containers/name",
+                "/spec/replicas"
+            ]
+        );
+        let replicas = analysis.field("/spec/replicas").unwrap();
+        assert_eq!(replicas.pointer, "/spec/replicas");
+        assert_eq!(replicas.validation_rules.minimum, Some(1.0));
+        assert_eq!(
+            analysis
+                .children("/spec")
+                .map(|field| field.pointer.as_str())
+                .collect::<Vec<_>>(),
+            ["/spec/containers", "/spec/replicas"]
+        );
+        assert_eq!(analysis.children("").count(), 2);
+    }
+
+    #[test]
+    fn test_conversion_extraction() {
+        let parser = CrdParser::new();
+        let mut recovered = Vec::new();
+        let mut lenient = |problem: String, recovery: &str| -> Result<()> {
+            recovered.push(format!("{problem}; {recovery}"));
+            Ok(())
+        };
+
+        let v1: serde_yaml::Value = serde_yaml::from_str(
+            r#"
+            conversion:
+              strategy: Webhook
+              webhook:
+                conversionReviewVersions: ["v1"]
+                clientConfig:
+                  service:
+                    namespace: system
+                    name: webhook-service
+                    port: 443
+                    path: /convert
+        "#,
+        )
+        .unwrap();
+        let conversion = parser.extract_conversion(&v1, &mut lenient).unwrap();
+        assert!(conversion.is_webhook());
+        assert_eq!(
+            conversion.webhook_endpoint.as_deref(),
+            Some("service system/webhook-service:443/convert")
+        );
+        assert_eq!(conversion.conversion_review_versions, vec!["v1"]);
+
+        let v1beta1: serde_yaml::Value = serde_yaml::from_str(
+            r#"
+            conversion:
+              strategy: Webhook
+              conversionReviewVersions: ["v1beta1"]
+              webhookClientConfig:
+                url: https://convert.example.com/
+        "#,
+        )
+        .unwrap();
+        let conversion = parser.extract_conversion(&v1beta1, &mut lenient).unwrap();
+        assert_eq!(
+            conversion.webhook_endpoint.as_deref(),
+            Some("https://convert.example.com/")
+        );
+        assert_eq!(conversion.conversion_review_versions, vec!["v1beta1"]);
+
+        let none = serde_yaml::from_str("group: example.com").unwrap();
+        assert_eq!(
+            parser.extract_conversion(&none, &mut lenient).unwrap(),
+            ConversionInfo::default()
+        );
+
+        let unknown = serde_yaml::from_str("conversion: {strategy: Magic}").unwrap();
+        assert!(parser
+            .extract_conversion(&unknown, &mut lenient)
+            .unwrap()
+            .is_webhook());
+        assert_eq!(
+            recovered,
+            ["has unknown conversion strategy Magic; converting through the API server"]
+        );
+        let mut strict = |problem: String, _: &str| -> Result<()> { Err(anyhow!(problem)) };
+        assert!(parser.extract_conversion(&unknown, &mut strict).is_err());
+    }
+
+    #[test]
+    fn test_printer_columns_and_categories() {
+        let parser = CrdParser::new();
+
+        let doc: serde_yaml::Value = serde_yaml::from_str(
+            r#"
+            apiVersion: apiextensions.k8s.io/v1
+            kind: CustomResourceDefinition
+            metadata:
+              name: certificates.cert-manager.io
+            spec:
+              group: cert-manager.io
+              names:
+                kind: Certificate
+                categories: [cert-manager]
+              versions:
+                - name: v1
+                  schema:
+                    openAPIV3Schema:
+                      type: object
+                  additionalPrinterColumns:
+                    - name: Ready
+                      type: string
+                      jsonPath: .status.conditions[?(@.type=="Ready")].status
+                    - name: Age
+                      type: date
+                      jsonPath: .metadata.creationTimestamp
+                      priority: 1
+        "#,
+        )
+        .unwrap();
+        let schema = parser
+            .extract_crd_from_document(&doc, Path::new("certificates.yaml"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(schema.categories, vec!["cert-manager"]);
+        assert_eq!(schema.printer_columns.len(), 2);
+        assert_eq!(schema.printer_columns[1].column_type, "date");
+        assert_eq!(schema.printer_columns[1].priority, 1);
+
+        // v1beta1 declares the columns once, with `JSONPath`
+        let mut v1beta1 = doc.clone();
+        let columns = v1beta1["spec"]["versions"][0]
+            .as_mapping_mut()
+            .unwrap()
+            .remove("additionalPrinterColumns")
+            .unwrap();
+        let mut columns: Vec<serde_yaml::Mapping> = serde_yaml::from_value(columns).unwrap();
+        for column in &mut columns {
+            let json_path = column.remove("jsonPath").unwrap();
+            column.insert("JSONPath".into(), json_path);
+        }
+        v1beta1["spec"]["additionalPrinterColumns"] = serde_yaml::to_value(columns).unwrap();
+        let schema = parser
+            .extract_crd_from_document(&v1beta1, Path::new("certificates.yaml"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            schema.printer_columns[1].json_path,
+            ".metadata.creationTimestamp"
+        );
+    }
+
+    #[test]
+    fn test_crd_without_versions() {
+        let parser = CrdParser::new();
+        let doc = serde_yaml::from_str(
+            r#"
+            kind: CustomResourceDefinition
+            metadata: {name: widgets.example.com}
+            spec:
+              group: example.com
+              names: {kind: Widget}
+              versions: []
+        "#,
+        )
+        .unwrap();
+
+        let err = parser
+            .extract_crd_from_document(&doc, Path::new("widgets.yaml"))
+            .unwrap_err();
+        assert!(err.to_string().contains("declares no versions"));
+    }
+
+    #[test]
+    fn test_version_selection() {
+        let doc: serde_yaml::Value = serde_yaml::from_str(
+            r#"
+            kind: CustomResourceDefinition
+            metadata: {name: widgets.example.com}
+            spec:
+              group: example.com
+              names: {kind: Widget}
+              versions:
+                - name: v1alpha1
+                  served: false
+                  storage: false
+                  schema: {openAPIV3Schema: {type: object}}
+                - name: v1beta1
+                  served: true
+                  storage: false
+                  schema: {openAPIV3Schema: {type: object}}
+                - name: v1
+                  served: true
+                  storage: true
+                  schema: {openAPIV3Schema: {type: object}}
+        "#,
+        )
+        .unwrap();
+        let version = |versions| {
+            CrdParser::new()
+                .with_versions(versions)
+                .extract_crd_from_document(&doc, Path::new("widgets.yaml"))
+                .unwrap()
+                .map(|schema| (schema.version, schema.served, schema.storage))
+        };
+
+        assert_eq!(
+            version(VersionSelection::All),
+            Some(("v1alpha1".to_string(), false, false))
+        );
+        assert_eq!(
+            version(VersionSelection::Served),
+            Some(("v1beta1".to_string(), true, false))
+        );
+        assert_eq!(
+            version(VersionSelection::Storage),
+            Some(("v1".to_string(), true, true))
+        );
+
+        let unserved: serde_yaml::Value = serde_yaml::from_str(
+            r#"
+            kind: CustomResourceDefinition
+            metadata: {name: gadgets.example.com}
+            spec:
+              group: example.com
+              names: {kind: Gadget}
+              versions:
+                - {name: v1, served: false, storage: true}
+        "#,
+        )
+        .unwrap();
+        assert!(CrdParser::new()
+            .with_versions(VersionSelection::Served)
+            .extract_crd_from_document(&unserved, Path::new("gadgets.yaml"))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_field_lines_of_selected_version() {
+        let content = r#"kind: CustomResourceDefinition
+metadata:
+  name: widgets.example.com
+spec:
+  group: example.com
+  names:
+    kind: Widget
+  versions:
+  - name: v1alpha1
+    served: false
+    schema:
+      openAPIV3Schema:
+        properties:
+          size:
+            type: string
+  - name: v1
+    served: true
+    schema:
+      openAPIV3Schema:
+        properties:
+          replicas:
+            type: integer
+          size:
+            type: string
+"#;
+
+        let schemas = CrdParser::new()
+            .with_versions(VersionSelection::Served)
+            .parse_crd_content(content, Path::new("widgets.yaml"))
+            .unwrap();
+        assert_eq!(schemas[0].version, "v1");
+        assert_eq!(
+            schemas[0].field_lines,
+            BTreeMap::from([("replicas".to_string(), 21), ("size".to_string(), 23)])
+        );
+    }
+
+    #[test]
+    fn test_crd_recovery() {
+        let parser = CrdParser::new();
+        let doc: serde_yaml::Value = serde_yaml::from_str(
+            r#"
+            kind: CustomResourceDefinition
+            metadata: {name: widgets.example.com}
+            spec:
+              group: example.com
+              names: {plural: widgets}
+              versions:
+                - name: v1alpha1
+                - name: v1
+                  schema:
+                    openAPIV3Schema:
+                      type: object
+        "#,
+        )
+        .unwrap();
+
+        let schema = parser
+            .extract_crd_from_document(&doc, Path::new("widgets.yaml"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(schema.kind, "Widget");
+        assert_eq!(schema.version, "v1");
+        assert_eq!(
+            schema.recovered,
+            [
+                "CRD widgets.example.com is missing spec.names.kind; using Widget",
+                "CRD widgets.example.com version v1alpha1 has no schema; generating version v1",
+            ]
+        );
+
+        let err = CrdParser::new()
+            .with_strict_crds(true)
+            .extract_crd_from_document(&doc, Path::new("widgets.yaml"))
+            .unwrap_err();
+        assert!(err.to_string().contains("is missing spec.names.kind"));
+
+        // Legacy v1beta1 CRDs may keep unknown fields without any schema
+        let legacy: serde_yaml::Value = serde_yaml::from_str(
+            r#"
+            kind: CustomResourceDefinition
+            metadata: {name: gadgets.example.com}
+            spec:
+              group: example.com
+              names: {kind: Gadget}
+              version: v1beta1
+              preserveUnknownFields: true
+        "#,
+        )
+        .unwrap();
+        let schema = parser
+            .extract_crd_from_document(&legacy, Path::new("gadgets.yaml"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(schema.api_version, "example.com/v1beta1");
+        assert_eq!(
+            schema.schema["x-kubernetes-preserve-unknown-fields"],
+            serde_yaml::Value::Bool(true)
+        );
+        assert_eq!(schema.recovered.len(), 1);
+    }
+
+    #[test]
+    fn test_annotation_hints() {
+        let parser = CrdParser::new();
+        let crd = |annotations: &str| -> serde_yaml::Value {
+            serde_yaml::from_str(&format!(
+                r#"
+            kind: CustomResourceDefinition
+            metadata:
+              name: widgets.example.com
+              annotations: {annotations}
+            spec:
+              group: example.com
+              names: {{kind: Widget}}
+              versions:
+                - name: v1
+                  schema:
+                    openAPIV3Schema:
+                      type: object
+        "#
+            ))
+            .unwrap()
+        };
+
+        let skipped = crd(r#"{gensonnet.dev/skip: "true"}"#);
+        assert!(parser
+            .extract_crd_from_document(&skipped, Path::new("widgets.yaml"))
+            .unwrap()
+            .is_none());
+
+        // Renamed libraries keep the kind of the resources they create
+        let renamed = crd("{gensonnet.dev/rename: Gizmo}");
+        let schema = parser
+            .extract_crd_from_document(&renamed, Path::new("widgets.yaml"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(schema.name, "Gizmo");
+        assert_eq!(schema.kind, "Widget");
+
+        // Invalid hints are ignored, or refused in strict mode
+        let invalid = crd(r#"{gensonnet.dev/skip: "yes"}"#);
+        let schema = parser
+            .extract_crd_from_document(&invalid, Path::new("widgets.yaml"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(schema.name, "widgets.example.com");
+        assert_eq!(schema.recovered.len(), 1);
+        assert!(CrdParser::new()
+            .with_strict_crds(true)
+            .extract_crd_from_document(&invalid, Path::new("widgets.yaml"))
+            .is_err());
+    }
 }
@@ -0,0 +1,53 @@
+//! JSON pointers addressing the fields of a resource
+//!
+//! Fields are named by RFC 6901 pointers into the resources a schema
+//! describes, such as `/spec/template/replicas`. Arrays are stepped through to
+//! their items, so `/spec/containers/name` is the name of every container.
+//! Schema analysis, field transforms and the field index all use this scheme.
+
+/// Escape a key for use in a JSON pointer
+pub fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Pointer to the field `key` of the field at `parent`; `""` is the resource itself
+pub fn child(parent: &str, key: &str) -> String {
+    format!("{parent}/{}", escape(key))
+}
+
+/// Pointer to a field given by the keys leading to it
+pub fn from_keys<'a>(keys: impl IntoIterator<Item = &'a str>) -> String {
+    keys.into_iter()
+        .fold(String::new(), |pointer, key| child(&pointer, key))
+}
+
+/// Keys of a pointer, unescaped; `None` unless it names a field
+pub fn segments(pointer: &str) -> Option<Vec<String>> {
+    let segments: Vec<String> = pointer
+        .strip_prefix('/')?
+        .split('/')
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+        .collect();
+    segments
+        .iter()
+        .all(|segment| !segment.is_empty())
+        .then_some(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pointers() {
+        let pointer = from_keys(["metadata", "annotations", "example.com/a~b"]);
+        assert_eq!(pointer, "/metadata/annotations/example.com~1a~0b");
+        assert_eq!(
+            segments(&pointer).unwrap(),
+            ["metadata", "annotations", "example.com/a~b"]
+        );
+        assert_eq!(child("", "spec"), "/spec");
+        assert!(segments("spec").is_none());
+        assert!(segments("/spec//size").is_none());
+    }
+}
@@ -0,0 +1,234 @@
+//! Source locations of schema properties
+//!
+//! Parsed YAML and JSON values carry no positions, so the lines properties
+//! are declared on are recovered from the document text: keys are followed
+//! by indentation, which covers block-style YAML and pretty-printed JSON.
+//! Properties declared in flow style (`{a: 1}`) get no location.
+
+use std::collections::BTreeMap;
+
+/// Lines (1-based) of the properties declared under a mapping
+///
+/// `parent` is the key path of the mapping holding the properties, matched
+/// as a suffix of each key's path, e.g. `["openAPIV3Schema", "properties"]`.
+/// Items of a sequence are in paths by their index, e.g. `["versions", "1"]`
+/// for the second version; only the first mapping matching `parent` is read.
+pub fn property_lines(content: &str, parent: &[&str]) -> BTreeMap<String, usize> {
+    let mut lines = BTreeMap::new();
+    if parent.is_empty() {
+        return lines;
+    }
+
+    let mut stack: Vec<Open> = Vec::new();
+    let mut root_items = 0;
+    let mut block_scalar: Option<usize> = None;
+    let mut parent_line = None;
+
+    for (index, line) in content.lines().enumerate() {
+        let mut indent = line.len() - line.trim_start().len();
+        let mut rest = line.trim_start();
+        if let Some(block_indent) = block_scalar {
+            if rest.is_empty() || indent > block_indent {
+                continue;
+            }
+            block_scalar = None;
+        }
+
+        // Sequence items start with `- ` in YAML and with a line opening an
+        // object in pretty-printed JSON
+        loop {
+            let (item, marker) = match rest.strip_prefix("- ") {
+                Some(item) => (item, 2),
+                None if rest == "{" => ("", 1),
+                None => break,
+            };
+            // A YAML sequence may be indented as far as its key
+            while stack
+                .last()
+                .is_some_and(|open| open.indent > indent || (open.item && open.indent == indent))
+            {
+                stack.pop();
+            }
+            let items = match stack.last_mut() {
+                Some(open) => &mut open.items,
+                None => &mut root_items,
+            };
+            let key = items.to_string();
+            *items += 1;
+            stack.push(Open {
+                indent,
+                key,
+                line: index + 1,
+                item: true,
+                items: 0,
+            });
+            indent += marker + (item.len() - item.trim_start().len());
+            rest = item.trim_start();
+        }
+
+        if rest.is_empty() || rest.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = split_key(rest) else {
+            continue;
+        };
+        if value.starts_with('|') || value.starts_with('>') {
+            block_scalar = Some(indent);
+        }
+
+        while stack.last().is_some_and(|open| open.indent >= indent) {
+            stack.pop();
+        }
+        let in_parent = stack.len() >= parent.len()
+            && stack[stack.len() - parent.len()..]
+                .iter()
+                .map(|open| open.key.as_str())
+                .eq(parent.iter().copied());
+        if in_parent {
+            let line = stack[stack.len() - 1].line;
+            if *parent_line.get_or_insert(line) == line {
+                lines.entry(key.clone()).or_insert(index + 1);
+            }
+        }
+        stack.push(Open {
+            indent,
+            key,
+            line: index + 1,
+            item: false,
+            items: 0,
+        });
+    }
+    lines
+}
+
+/// A key or sequence item whose value is still being read
+struct Open {
+    /// Indentation of the key or item marker
+    indent: usize,
+
+    /// The key, or the index of the item
+    key: String,
+
+    /// Line of the key or item
+    line: usize,
+
+    /// Whether this is a sequence item
+    item: bool,
+
+    /// Number of items of the sequence it holds so far
+    items: usize,
+}
+
+/// Split `key: value` into its unquoted key and the value's text
+fn split_key(text: &str) -> Option<(String, &str)> {
+    if let Some(quote @ ('"' | '\'')) = text.chars().next() {
+        let end = text[1..].find(quote)? + 1;
+        let value = text[end + 1..].trim_start().strip_prefix(':')?;
+        return Some((text[1..end].to_string(), value.trim()));
+    }
+    let colon = text
+        .match_indices(':')
+        .map(|(i, _)| i)
+        .find(|&i| text[i + 1..].is_empty() || text[i + 1..].starts_with(' '))?;
+    let key = text[..colon].trim();
+    if key.is_empty() || key.starts_with(['{', '[', '}', ']']) {
+        return None;
+    }
+    Some((key.to_string(), text[colon + 1..].trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_property_lines() {
+        let crd = r#"spec:
+  versions:
+  - name: v1
+    schema:
+      openAPIV3Schema:
+        type: object
+        description: |
+          replicas: not a property
+        properties:
+          replicas:
+            type: integer
+          "size": {type: string}
+  - name: v2
+    schema:
+      openAPIV3Schema:
+        properties:
+          replicas:
+            type: integer
+          paused:
+            type: boolean
+"#;
+        let lines = property_lines(crd, &["openAPIV3Schema", "properties"]);
+        assert_eq!(
+            lines,
+            BTreeMap::from([("replicas".to_string(), 10), ("size".to_string(), 12)])
+        );
+        let lines = property_lines(
+            crd,
+            &["versions", "1", "schema", "openAPIV3Schema", "properties"],
+        );
+        assert_eq!(
+            lines,
+            BTreeMap::from([("paused".to_string(), 19), ("replicas".to_string(), 17)])
+        );
+
+        let spec = r#"{
+  "components": {
+    "schemas": {
+      "Widget": {
+        "properties": {
+          "name": {
+            "type": "string"
+          },
+          "tags": {"type": "array"}
+        }
+      }
+    }
+  }
+}"#;
+        let lines = property_lines(spec, &["schemas", "Widget", "properties"]);
+        assert_eq!(
+            lines,
+            BTreeMap::from([("name".to_string(), 6), ("tags".to_string(), 9)])
+        );
+
+        let crd = r#"{
+  "spec": {
+    "versions": [
+      {
+        "name": "v1",
+        "schema": {"openAPIV3Schema": {"type": "object"}}
+      },
+      {
+        "name": "v2",
+        "schema": {
+          "openAPIV3Schema": {
+            "properties": {
+              "paused": {"type": "boolean"}
+            }
+          }
+        }
+      }
+    ]
+  }
+}"#;
+        let lines = property_lines(
+            crd,
+            &[
+                "spec",
+                "versions",
+                "1",
+                "schema",
+                "openAPIV3Schema",
+                "properties",
+            ],
+        );
+        assert_eq!(lines, BTreeMap::from([("paused".to_string(), 13)]));
+    }
+}
@@ -1,7 +1,8 @@
 //! CRD schema definition and implementation
 
-use crate::types::{FieldAnalysis, SchemaAnalysis, ValidationRules};
+use crate::types::{ConversionInfo, FieldAnalysis, PrinterColumn, SchemaAnalysis, ValidationRules};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// Represents a parsed CRD schema with advanced features
@@ -19,9 +20,25 @@ pub struct CrdSchema {
     /// Full API version (group/version)
     pub api_version: String,
 
+    /// Whether the API server serves the version
+    #[serde(default)]
+    pub served: bool,
+
+    /// Whether the version is the one objects are stored as
+    #[serde(default)]
+    pub storage: bool,
+
     /// Resource kind (from spec.names.kind)
     pub kind: String,
 
+    /// Categories the resource belongs to (from spec.names.categories)
+    #[serde(default)]
+    pub categories: Vec<String>,
+
+    /// Columns shown by `kubectl get` (from additionalPrinterColumns)
+    #[serde(default)]
+    pub printer_columns: Vec<PrinterColumn>,
+
     /// OpenAPI v3 schema
     pub schema: serde_yaml::Value,
 
@@ -33,6 +50,18 @@ pub struct CrdSchema {
 
     /// Schema analysis
     pub schema_analysis: SchemaAnalysis,
+
+    /// Conversion between versions (from spec.conversion)
+    #[serde(default)]
+    pub conversion: ConversionInfo,
+
+    /// Line each property is declared on in `source_path`
+    #[serde(default)]
+    pub field_lines: BTreeMap<String, usize>,
+
+    /// Problems of the upstream manifest the parser recovered from
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recovered: Vec<String>,
 }
 
 impl CrdSchema {
@@ -70,9 +99,7 @@ impl CrdSchema {
 
     /// Get validation rules for a specific field
     pub fn get_field_validation(&self, field_name: &str) -> Option<&ValidationRules> {
-        self.schema_analysis
-            .fields
-            .get(field_name)
+        self.get_field_type(field_name)
             .map(|field| &field.validation_rules)
     }
 
@@ -81,9 +108,14 @@ impl CrdSchema {
         self.required_fields().contains(&field_name.to_string())
     }
 
-    /// Get field type information
+    /// Get field type information, by top-level field name or JSON pointer
     pub fn get_field_type(&self, field_name: &str) -> Option<&FieldAnalysis> {
-        self.schema_analysis.fields.get(field_name)
+        if field_name.starts_with('/') {
+            self.schema_analysis.field(field_name)
+        } else {
+            self.schema_analysis
+                .field(&crate::pointer::child("", field_name))
+        }
     }
 }
 
@@ -98,11 +130,18 @@ mod tests {
             group: "test.example.com".to_string(),
             version: "v1".to_string(),
             api_version: "test.example.com/v1".to_string(),
+            served: true,
+            storage: true,
             kind: "TestResource".to_string(),
+            categories: Vec::new(),
+            printer_columns: Vec::new(),
             schema: serde_yaml::Value::Null,
             source_path: PathBuf::from("test.yaml"),
             validation_rules: crate::types::ValidationRules::default(),
             schema_analysis: crate::types::SchemaAnalysis::default(),
+            conversion: crate::types::ConversionInfo::default(),
+            field_lines: Default::default(),
+            recovered: Vec::new(),
         };
 
         assert_eq!(schema.kind(), "TestResource");
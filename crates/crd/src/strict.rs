@@ -0,0 +1,215 @@
+//! Detection of schema constructs the generator cannot represent
+//!
+//! By default such constructs are ignored and the affected schema falls back
+//! to a plain object. In strict mode the analyzers use [`find_unsupported`] to
+//! fail instead, naming every affected location as a JSON pointer into the
+//! schema.
+
+use crate::pointer::escape;
+use serde_yaml::Value;
+use std::fmt;
+
+/// Formats the generator knows: those of OpenAPI and of Kubernetes' apiextensions
+pub const KNOWN_FORMATS: &[&str] = &[
+    "int32",
+    "int64",
+    "float",
+    "double",
+    "byte",
+    "binary",
+    "date",
+    "date-time",
+    "datetime",
+    "time",
+    "duration",
+    "password",
+    "email",
+    "hostname",
+    "ipv4",
+    "ipv6",
+    "cidr",
+    "mac",
+    "uri",
+    "uri-reference",
+    "uuid",
+    "uuid3",
+    "uuid4",
+    "uuid5",
+    "bsonobjectid",
+    "isbn",
+    "isbn10",
+    "isbn13",
+    "creditcard",
+    "ssn",
+    "hexcolor",
+    "rgbcolor",
+    "int-or-string",
+];
+
+/// An unsupported construct and where in the schema it was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedConstruct {
+    /// JSON pointer to the schema holding the construct
+    pub path: String,
+
+    /// Description of the construct
+    pub construct: String,
+}
+
+impl fmt::Display for UnsupportedConstruct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        write!(f, "{}: {}", path, self.construct)
+    }
+}
+
+/// Find `not`, `patternProperties`, YAML tags and unknown formats in a schema
+pub fn find_unsupported(schema: &Value) -> Vec<UnsupportedConstruct> {
+    let mut found = Vec::new();
+    visit(schema, String::new(), &mut found);
+    found
+}
+
+fn visit(schema: &Value, path: String, found: &mut Vec<UnsupportedConstruct>) {
+    let mut report = |path: &str, construct: String| {
+        found.push(UnsupportedConstruct {
+            path: path.to_string(),
+            construct,
+        })
+    };
+
+    let schema = match schema {
+        Value::Tagged(tagged) => {
+            report(&path, format!("YAML tag `{}`", tagged.tag));
+            &tagged.value
+        }
+        schema => schema,
+    };
+    let Some(mapping) = schema.as_mapping() else {
+        return;
+    };
+
+    for (key, value) in mapping {
+        let Some(key) = key.as_str() else {
+            continue;
+        };
+        match key {
+            "not" | "patternProperties" => report(&path, format!("`{key}`")),
+            "format" => match value.as_str() {
+                Some(format) if !KNOWN_FORMATS.contains(&format) => {
+                    report(&path, format!("unknown format `{format}`"))
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        // Tags on nested schemas are reported when visiting them
+        if let Value::Tagged(tagged) = value {
+            if !is_subschema(key) {
+                report(
+                    &format!("{path}/{}", escape(key)),
+                    format!("YAML tag `{}`", tagged.tag),
+                );
+            }
+        }
+    }
+
+    for keyword in ["properties", "definitions", "$defs"] {
+        if let Some(properties) = mapping.get(keyword).and_then(Value::as_mapping) {
+            for (name, property) in properties {
+                if let Some(name) = name.as_str() {
+                    visit(
+                        property,
+                        format!("{path}/{keyword}/{}", escape(name)),
+                        found,
+                    );
+                }
+            }
+        }
+    }
+    for keyword in ["items", "additionalProperties"] {
+        match mapping.get(keyword) {
+            Some(Value::Sequence(items)) => {
+                for (index, item) in items.iter().enumerate() {
+                    visit(item, format!("{path}/{keyword}/{index}"), found);
+                }
+            }
+            Some(Value::Bool(_)) | None => {}
+            Some(item) => visit(item, format!("{path}/{keyword}"), found),
+        }
+    }
+    for keyword in ["allOf", "anyOf", "oneOf"] {
+        if let Some(schemas) = mapping.get(keyword).and_then(Value::as_sequence) {
+            for (index, item) in schemas.iter().enumerate() {
+                visit(item, format!("{path}/{keyword}/{index}"), found);
+            }
+        }
+    }
+}
+
+/// Whether a keyword holds nested schemas, which are visited on their own
+fn is_subschema(keyword: &str) -> bool {
+    matches!(
+        keyword,
+        "properties"
+            | "definitions"
+            | "$defs"
+            | "items"
+            | "additionalProperties"
+            | "allOf"
+            | "anyOf"
+            | "oneOf"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_unsupported() {
+        let schema: Value = serde_yaml::from_str(
+            r#"
+type: object
+properties:
+  not:
+    type: string
+    format: email
+  spec:
+    type: object
+    patternProperties:
+      "^x-": {type: string}
+    properties:
+      selector:
+        not: {required: [name]}
+      size:
+        type: string
+        format: quantity
+        default: !Ref size
+  items:
+    type: array
+    items: !custom {type: string}
+"#,
+        )
+        .unwrap();
+
+        let found: Vec<String> = find_unsupported(&schema)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                "/properties/spec: `patternProperties`",
+                "/properties/spec/properties/selector: `not`",
+                "/properties/spec/properties/size: unknown format `quantity`",
+                "/properties/spec/properties/size/default: YAML tag `!Ref`",
+                "/properties/items/items: YAML tag `!custom`",
+            ]
+        );
+    }
+}
@@ -1,7 +1,7 @@
 //! CRD types and data structures
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 /// Validation rules extracted from OpenAPI schema
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -61,8 +61,9 @@ pub struct SchemaAnalysis {
     /// Schema type (object, array, string, etc.)
     pub schema_type: String,
 
-    /// Field analysis for object types
-    pub fields: HashMap<String, FieldAnalysis>,
+    /// Analysis of every field, nested ones included, by JSON pointer
+    /// (see [`crate::pointer`])
+    pub fields: BTreeMap<String, FieldAnalysis>,
 
     /// Array item type analysis
     pub array_item_type: Option<FieldAnalysis>,
@@ -80,9 +81,31 @@ pub struct SchemaAnalysis {
     pub reference: Option<String>,
 }
 
+impl SchemaAnalysis {
+    /// Analysis of the field at a JSON pointer, e.g. `/spec/replicas`
+    pub fn field(&self, pointer: &str) -> Option<&FieldAnalysis> {
+        self.fields.get(pointer)
+    }
+
+    /// Fields directly below the field at a pointer; `""` gives the top-level fields
+    pub fn children<'a>(&'a self, pointer: &'a str) -> impl Iterator<Item = &'a FieldAnalysis> {
+        self.fields.values().filter(move |field| {
+            field
+                .pointer
+                .strip_prefix(pointer)
+                .and_then(|rest| rest.strip_prefix('/'))
+                .is_some_and(|rest| !rest.contains('/'))
+        })
+    }
+}
+
 /// Analysis of a field schema
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct FieldAnalysis {
+    /// JSON pointer to the field, e.g. `/spec/replicas`
+    #[serde(default)]
+    pub pointer: String,
+
     /// Field type
     pub field_type: String,
 
@@ -95,3 +118,92 @@ pub struct FieldAnalysis {
     /// Array items schema
     pub array_items: Option<serde_yaml::Value>,
 }
+
+/// Which versions of a CRD may be generated
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionSelection {
+    /// Any version, served or not
+    #[default]
+    All,
+
+    /// Versions marked `served: true`
+    Served,
+
+    /// The version marked `storage: true`, when it is also served
+    Storage,
+}
+
+impl VersionSelection {
+    pub fn is_all(&self) -> bool {
+        *self == VersionSelection::All
+    }
+
+    /// Whether a version with these flags may be generated
+    pub fn allows(&self, served: bool, storage: bool) -> bool {
+        match self {
+            VersionSelection::All => true,
+            VersionSelection::Served => served,
+            VersionSelection::Storage => served && storage,
+        }
+    }
+}
+
+/// How the API server converts objects between versions of a CRD
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum ConversionStrategy {
+    /// Only `apiVersion` changes; versions must be structurally identical
+    #[default]
+    None,
+
+    /// A conversion webhook transforms objects between versions
+    Webhook,
+}
+
+/// Conversion settings of a CRD (from spec.conversion)
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct ConversionInfo {
+    /// Conversion strategy
+    pub strategy: ConversionStrategy,
+
+    /// Webhook endpoint, as a URL or `service namespace/name[:port][/path]`
+    pub webhook_endpoint: Option<String>,
+
+    /// ConversionReview versions the webhook accepts
+    pub conversion_review_versions: Vec<String>,
+}
+
+impl ConversionInfo {
+    /// Whether conversion between versions needs a webhook
+    pub fn is_webhook(&self) -> bool {
+        self.strategy == ConversionStrategy::Webhook
+    }
+}
+
+/// A column `kubectl get` shows for a resource (from additionalPrinterColumns)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrinterColumn {
+    /// Column header
+    pub name: String,
+
+    /// Column type, e.g. `string`, `integer` or `date`
+    #[serde(rename = "type")]
+    pub column_type: String,
+
+    /// JSON path of the value shown in the column
+    #[serde(alias = "JSONPath")]
+    pub json_path: String,
+
+    /// Human readable description of the column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Priority; columns above 0 are only shown in wide output
+    #[serde(default)]
+    pub priority: i32,
+
+    /// Format of the value, e.g. `date-time`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+}
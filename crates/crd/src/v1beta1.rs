@@ -0,0 +1,186 @@
+//! Conversion of `apiextensions.k8s.io/v1beta1` CRDs
+//!
+//! v1beta1 CRDs declare their schema, printer columns and subresources once
+//! for all versions, may name a single `version`, and keep the conversion
+//! webhook settings directly on `conversion`. The parser upgrades them to the
+//! v1 layout first, so everything after it only handles v1 CRDs.
+
+use serde_yaml::{Mapping, Value};
+
+/// API version of v1 CRDs
+pub const V1_API_VERSION: &str = "apiextensions.k8s.io/v1";
+
+/// API version of v1beta1 CRDs
+pub const V1BETA1_API_VERSION: &str = "apiextensions.k8s.io/v1beta1";
+
+/// Fields v1beta1 declares for all versions and v1 per version
+const PER_VERSION_FIELDS: &[(&str, &str)] = &[
+    ("validation", "schema"),
+    ("additionalPrinterColumns", "additionalPrinterColumns"),
+    ("subresources", "subresources"),
+];
+
+/// Upgrade a CRD document to the v1 layout in place
+///
+/// Fields a version declares itself take precedence over the ones shared by
+/// all versions. Upgrading goes by the layout rather than the `apiVersion`, as
+/// vendored bundles often mislabel it, and leaves v1 documents unchanged.
+pub fn upgrade(doc: &mut Value) {
+    let Some(spec) = doc.get_mut("spec").and_then(Value::as_mapping_mut) else {
+        return;
+    };
+
+    // A single `version` becomes the only, served and stored, version
+    let version = spec.remove("version");
+    if !spec.contains_key("versions") {
+        if let Some(version) = version {
+            let mut version_doc = Mapping::new();
+            version_doc.insert("name".into(), version);
+            version_doc.insert("served".into(), true.into());
+            version_doc.insert("storage".into(), true.into());
+            spec.insert("versions".into(), vec![Value::Mapping(version_doc)].into());
+        }
+    }
+
+    let shared: Vec<(&str, Value)> = PER_VERSION_FIELDS
+        .iter()
+        .filter_map(|(from, to)| Some((*to, spec.remove(*from)?)))
+        .collect();
+    let preserve_unknown_fields = spec
+        .get("preserveUnknownFields")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if let Some(versions) = spec.get_mut("versions").and_then(Value::as_sequence_mut) {
+        for version_doc in versions.iter_mut().filter_map(Value::as_mapping_mut) {
+            for (field, value) in &shared {
+                if !version_doc.contains_key(*field) {
+                    version_doc.insert((*field).into(), value.clone());
+                }
+            }
+            if let Some(columns) = version_doc
+                .get_mut("additionalPrinterColumns")
+                .and_then(Value::as_sequence_mut)
+            {
+                columns
+                    .iter_mut()
+                    .filter_map(Value::as_mapping_mut)
+                    .for_each(|column| rename(column, "JSONPath", "jsonPath"));
+            }
+            // v1 marks the root schema instead of the whole CRD
+            if preserve_unknown_fields {
+                if let Some(schema) = version_doc
+                    .get_mut("schema")
+                    .and_then(|s| s.get_mut("openAPIV3Schema"))
+                    .and_then(Value::as_mapping_mut)
+                {
+                    schema.insert("x-kubernetes-preserve-unknown-fields".into(), true.into());
+                }
+            }
+        }
+    }
+
+    // The webhook settings move below `conversion.webhook`
+    if let Some(conversion) = spec.get_mut("conversion").and_then(Value::as_mapping_mut) {
+        let mut webhook = Mapping::new();
+        if let Some(client_config) = conversion.remove("webhookClientConfig") {
+            webhook.insert("clientConfig".into(), client_config);
+        }
+        if let Some(review_versions) = conversion.remove("conversionReviewVersions") {
+            webhook.insert("conversionReviewVersions".into(), review_versions);
+        }
+        if !webhook.is_empty() && !conversion.contains_key("webhook") {
+            conversion.insert("webhook".into(), Value::Mapping(webhook));
+        }
+    }
+
+    if let Some(api_version) = doc.get_mut("apiVersion") {
+        if api_version.as_str() == Some(V1BETA1_API_VERSION) {
+            *api_version = V1_API_VERSION.into();
+        }
+    }
+}
+
+/// Rename a key of a mapping, unless the new key is already present
+fn rename(mapping: &mut Mapping, from: &str, to: &str) {
+    if mapping.contains_key(to) {
+        return;
+    }
+    if let Some(value) = mapping.remove(from) {
+        mapping.insert(to.into(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade() {
+        let mut doc: Value = serde_yaml::from_str(
+            r#"
+            apiVersion: apiextensions.k8s.io/v1beta1
+            kind: CustomResourceDefinition
+            metadata: {name: widgets.example.com}
+            spec:
+              group: example.com
+              names: {kind: Widget}
+              version: v1alpha1
+              preserveUnknownFields: true
+              validation:
+                openAPIV3Schema:
+                  type: object
+              additionalPrinterColumns:
+                - name: Size
+                  type: string
+                  JSONPath: .spec.size
+              subresources:
+                status: {}
+              conversion:
+                strategy: Webhook
+                webhookClientConfig:
+                  url: https://widgets.example.com/convert
+                conversionReviewVersions: [v1beta1]
+        "#,
+        )
+        .unwrap();
+        upgrade(&mut doc);
+
+        let expected: Value = serde_yaml::from_str(
+            r#"
+            apiVersion: apiextensions.k8s.io/v1
+            kind: CustomResourceDefinition
+            metadata: {name: widgets.example.com}
+            spec:
+              group: example.com
+              names: {kind: Widget}
+              preserveUnknownFields: true
+              conversion:
+                strategy: Webhook
+                webhook:
+                  clientConfig:
+                    url: https://widgets.example.com/convert
+                  conversionReviewVersions: [v1beta1]
+              versions:
+                - name: v1alpha1
+                  served: true
+                  storage: true
+                  schema:
+                    openAPIV3Schema:
+                      type: object
+                      x-kubernetes-preserve-unknown-fields: true
+                  additionalPrinterColumns:
+                    - name: Size
+                      type: string
+                      jsonPath: .spec.size
+                  subresources:
+                    status: {}
+        "#,
+        )
+        .unwrap();
+        assert_eq!(doc, expected);
+
+        // v1 documents are left alone
+        upgrade(&mut doc);
+        assert_eq!(doc, expected);
+    }
+}
@@ -0,0 +1,282 @@
+//! Function argument schemas
+//!
+//! `_arguments.json` holds a standalone JSON Schema (draft-07) for every
+//! generated builder function, describing the object of its named
+//! arguments. Forms and CI validators can check parameter objects with any
+//! JSON Schema validator before the Jsonnet is evaluated.
+
+use crate::crd::CrdSchema;
+use crate::list_maps;
+use crate::naming::FieldNaming;
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+
+/// Name of the argument schemas emitted at the root of each generated library
+pub const ARGUMENTS_FILE: &str = "_arguments.json";
+
+/// Dialect the argument schemas declare
+const SCHEMA_DIALECT: &str = "http://json-schema.org/draft-07/schema#";
+
+/// Generate the argument schemas of a set of schemas
+///
+/// `library_path` gives the path of a schema's library relative to the
+/// output root. Entries are ordered by kind, then function, the resource
+/// function first.
+pub fn generate_arguments(
+    schemas: &[&CrdSchema],
+    naming: FieldNaming,
+    library_path: impl Fn(&CrdSchema) -> String,
+) -> Result<String> {
+    let mut schemas = schemas.to_vec();
+    schemas.sort_by(|a, b| (a.kind(), &a.api_version).cmp(&(b.kind(), &b.api_version)));
+
+    let mut entries = Vec::new();
+    for schema in schemas {
+        let file = library_path(schema);
+        let properties = schema.properties().filter(|_| schema.is_object());
+
+        let mut arguments = Map::new();
+        arguments.insert("metadata".to_string(), metadata_schema());
+        if properties.is_some() {
+            arguments.insert("spec".to_string(), to_json_schema(&schema.schema)?);
+        }
+        entries.push(json!({
+            "function": schema.kind(),
+            "kind": schema.kind(),
+            "apiVersion": schema.api_version,
+            "file": file,
+            "parameters": arguments.keys().collect::<Vec<_>>(),
+            "schema": arguments_schema(
+                &format!("Arguments of {}", schema.kind()),
+                arguments.clone(),
+                &["metadata"],
+            ),
+        }));
+
+        let Some(properties) = properties else {
+            continue;
+        };
+        let mut fields: Vec<(&str, &serde_yaml::Value)> = properties
+            .iter()
+            .filter_map(|(name, field)| name.as_str().map(|name| (name, field)))
+            .collect();
+        fields.sort_by_key(|(name, _)| *name);
+
+        let setters = naming.setter_names(fields.iter().map(|(name, _)| *name))?;
+        let mut functions = Vec::new();
+        for (field_name, field_schema) in fields {
+            let mut arguments = Map::new();
+            arguments.insert(field_name.to_string(), to_json_schema(field_schema)?);
+            functions.push((
+                setters[field_name].clone(),
+                arguments,
+                vec![field_name.to_string()],
+            ));
+        }
+
+        // Upsert helpers take the entry's keys and the entry itself
+        for list_map in list_maps::find_list_map_fields(properties, naming) {
+            let items = properties
+                .get(list_map.field.as_str())
+                .and_then(|field| field.get("items"));
+            let mut arguments = Map::new();
+            for key in &list_map.keys {
+                let key_schema = match items.and_then(|items| items.get("properties")?.get(key)) {
+                    Some(key_schema) => to_json_schema(key_schema)?,
+                    None => json!({}),
+                };
+                arguments.insert(key.clone(), key_schema);
+            }
+            let obj_schema = match items {
+                Some(items) => to_json_schema(items)?,
+                None => json!({"type": "object"}),
+            };
+            arguments.insert("obj".to_string(), obj_schema);
+            let mut parameters = list_map.keys.clone();
+            parameters.push("obj".to_string());
+            functions.push((list_map.helper_name(), arguments, parameters));
+        }
+        functions.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (function, arguments, parameters) in functions {
+            let required: Vec<&str> = parameters.iter().map(String::as_str).collect();
+            entries.push(json!({
+                "function": function,
+                "kind": schema.kind(),
+                "apiVersion": schema.api_version,
+                "file": file,
+                "parameters": parameters,
+                "schema": arguments_schema(
+                    &format!("Arguments of {}.{}", schema.kind(), function),
+                    arguments,
+                    &required,
+                ),
+            }));
+        }
+    }
+
+    Ok(serde_json::to_string_pretty(&entries)?)
+}
+
+/// Schema of an object of named arguments
+fn arguments_schema(title: &str, properties: Map<String, Value>, required: &[&str]) -> Value {
+    json!({
+        "$schema": SCHEMA_DIALECT,
+        "title": title,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// Schema of the `metadata` argument, matching the generated validation
+fn metadata_schema() -> Value {
+    let string_map = json!({"type": "object", "additionalProperties": {"type": "string"}});
+    json!({
+        "type": "object",
+        "properties": {
+            "name": {"type": "string"},
+            "namespace": {"type": "string"},
+            "labels": string_map,
+            "annotations": string_map,
+        },
+        "required": ["name"],
+    })
+}
+
+/// Convert an OpenAPI v3 schema to JSON Schema
+///
+/// `nullable` becomes a `null` type and `x-kubernetes-int-or-string` an
+/// integer-or-string union; other Kubernetes extensions are dropped.
+fn to_json_schema(schema: &serde_yaml::Value) -> Result<Value> {
+    Ok(convert(serde_json::to_value(schema)?))
+}
+
+fn convert(value: Value) -> Value {
+    let Value::Object(object) = value else {
+        return value;
+    };
+
+    let nullable = object.get("nullable").and_then(Value::as_bool) == Some(true);
+    let int_or_string = object
+        .get("x-kubernetes-int-or-string")
+        .and_then(Value::as_bool)
+        == Some(true);
+
+    let mut converted = Map::new();
+    for (keyword, value) in object {
+        if keyword == "nullable" || keyword.starts_with("x-kubernetes-") {
+            continue;
+        }
+        let value = match keyword.as_str() {
+            "properties" | "patternProperties" | "definitions" => match value {
+                Value::Object(children) => Value::Object(
+                    children
+                        .into_iter()
+                        .map(|(name, child)| (name, convert(child)))
+                        .collect(),
+                ),
+                other => other,
+            },
+            "items" | "additionalProperties" | "not" => convert(value),
+            "allOf" | "anyOf" | "oneOf" => match value {
+                Value::Array(children) => Value::Array(children.into_iter().map(convert).collect()),
+                other => other,
+            },
+            _ => value,
+        };
+        converted.insert(keyword, value);
+    }
+
+    if int_or_string {
+        converted.insert("type".to_string(), json!(["integer", "string"]));
+    }
+    if nullable {
+        let types = match converted.remove("type") {
+            Some(Value::String(t)) => json!([t, "null"]),
+            Some(Value::Array(mut types)) => {
+                types.push(json!("null"));
+                Value::Array(types)
+            }
+            _ => json!(null),
+        };
+        if !types.is_null() {
+            converted.insert("type".to_string(), types);
+        }
+    }
+    Value::Object(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_generate_arguments() {
+        let widget = CrdSchema {
+            name: "widgets.example.com".to_string(),
+            group: "example.com".to_string(),
+            version: "v1".to_string(),
+            api_version: "example.com/v1".to_string(),
+            kind: "Widget".to_string(),
+            schema: serde_yaml::from_str(
+                r#"
+type: object
+required: [size]
+properties:
+  size:
+    type: string
+    enum: [small, large]
+  port:
+    x-kubernetes-int-or-string: true
+  owner:
+    type: string
+    nullable: true
+"#,
+            )
+            .unwrap(),
+            source_path: PathBuf::from("widget.yaml"),
+            validation_rules: Default::default(),
+            schema_analysis: Default::default(),
+            categories: Vec::new(),
+            printer_columns: Vec::new(),
+            conversion: Default::default(),
+            field_lines: Default::default(),
+        };
+
+        let content = generate_arguments(&[&widget], FieldNaming::Preserve, |_| {
+            "example.com_v1/widgets.example.com.libsonnet".to_string()
+        })
+        .unwrap();
+        let entries: Value = serde_json::from_str(&content).unwrap();
+        let functions: Vec<&str> = entries
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["function"].as_str().unwrap())
+            .collect();
+        assert_eq!(functions, ["Widget", "withOwner", "withPort", "withSize"]);
+
+        let resource = &entries[0]["schema"];
+        assert_eq!(resource["$schema"], SCHEMA_DIALECT);
+        assert_eq!(resource["required"], json!(["metadata"]));
+        assert_eq!(
+            resource["properties"]["metadata"]["required"],
+            json!(["name"])
+        );
+        assert_eq!(resource["properties"]["spec"]["required"], json!(["size"]));
+
+        assert_eq!(
+            entries[1]["schema"]["properties"]["owner"],
+            json!({"type": ["string", "null"]})
+        );
+        assert_eq!(
+            entries[2]["schema"]["properties"]["port"],
+            json!({"type": ["integer", "string"]})
+        );
+        assert_eq!(entries[3]["parameters"], json!(["size"]));
+        assert_eq!(entries[3]["schema"]["required"], json!(["size"]));
+    }
+}
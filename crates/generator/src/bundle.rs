@@ -0,0 +1,165 @@
+//! Platform bundle aggregating the libraries of every source
+
+/// Default name of the bundle file
+pub const BUNDLE_FILE: &str = "platform.libsonnet";
+
+/// A source library included in the bundle
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleMember {
+    /// Field the library is exposed under
+    pub key: String,
+
+    /// Name of the source the library was generated from
+    pub source: String,
+
+    /// Import path of the library's index, relative to the bundle file
+    pub import_path: String,
+
+    /// Repository or artifact URL of the source
+    pub url: String,
+
+    /// Git reference of the source, empty for URL sources
+    pub ref_name: String,
+
+    /// Commit SHA the library was generated from, empty for sources pinned by digest
+    pub commit: String,
+
+    /// Digest of the content the library was generated from, empty for Git sources
+    pub digest: String,
+
+    /// Version stamped into the library, empty if it has none
+    pub version: String,
+}
+
+/// Generate the bundle file importing every member under its key
+///
+/// The hidden `_meta` field records the version of every source, and
+/// `generated_at` if given.
+pub fn generate_bundle_file(
+    members: &[BundleMember],
+    generated_at: Option<chrono::DateTime<chrono::Utc>>,
+) -> String {
+    let mut content = String::new();
+    content.push_str("// Generated platform bundle\n");
+    content.push_str("{\n");
+
+    content.push_str("  _meta:: {\n");
+    if let Some(generated_at) = generated_at {
+        content.push_str(&format!(
+            "    generated_at: \"{}\",\n",
+            generated_at.to_rfc3339()
+        ));
+    }
+    content.push_str("    sources: {\n");
+    for member in members {
+        content.push_str(&format!(
+            "      {}: {{ source: \"{}\", url: \"{}\", ref: \"{}\", commit: \"{}\", digest: \"{}\", version: \"{}\" }},\n",
+            field_name(&member.key),
+            member.source,
+            member.url,
+            member.ref_name,
+            member.commit,
+            member.digest,
+            member.version
+        ));
+    }
+    content.push_str("    },\n");
+    content.push_str("  },\n");
+
+    for member in members {
+        content.push_str(&format!(
+            "  {}: import \"{}\",\n",
+            field_name(&member.key),
+            member.import_path
+        ));
+    }
+
+    content.push_str("}\n");
+    content
+}
+
+/// Default bundle key of a source: its name without separators
+pub fn default_key(source_name: &str) -> String {
+    source_name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// A Jsonnet field name for a key, quoted unless it is a plain identifier
+fn field_name(key: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "assert",
+        "else",
+        "error",
+        "false",
+        "for",
+        "function",
+        "if",
+        "import",
+        "importstr",
+        "importbin",
+        "in",
+        "local",
+        "null",
+        "self",
+        "super",
+        "tailstrict",
+        "then",
+        "true",
+    ];
+
+    let is_identifier = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if is_identifier && !KEYWORDS.contains(&key) {
+        key.to_string()
+    } else {
+        format!("\"{key}\"")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_bundle_file() {
+        let members = vec![
+            BundleMember {
+                key: default_key("cert-manager"),
+                source: "cert-manager".to_string(),
+                import_path: "./cert-manager/index.libsonnet".to_string(),
+                url: "https://github.com/cert-manager/cert-manager.git".to_string(),
+                ref_name: "v1.14.0".to_string(),
+                commit: "0123abcd".to_string(),
+                digest: String::new(),
+                version: "v1.14.0".to_string(),
+            },
+            BundleMember {
+                key: "3rd-party".to_string(),
+                source: "vendor".to_string(),
+                import_path: "../vendor/index.libsonnet".to_string(),
+                url: "https://example.com/crds.tar.gz".to_string(),
+                ref_name: String::new(),
+                commit: String::new(),
+                digest: "sha256:ff".to_string(),
+                version: "ff".to_string(),
+            },
+        ];
+
+        let content = generate_bundle_file(&members, None);
+        assert!(content.contains("  certmanager: import \"./cert-manager/index.libsonnet\",\n"));
+        assert!(content.contains("  \"3rd-party\": import \"../vendor/index.libsonnet\",\n"));
+        assert!(content.contains(
+            "      certmanager: { source: \"cert-manager\", url: \"https://github.com/cert-manager/cert-manager.git\", ref: \"v1.14.0\", commit: \"0123abcd\", digest: \"\", version: \"v1.14.0\" },\n"
+        ));
+        assert!(content.contains("commit: \"\", digest: \"sha256:ff\", version: \"ff\""));
+        assert!(!content.contains("generated_at"));
+        assert_eq!(field_name("local"), "\"local\"");
+    }
+}
@@ -0,0 +1,34 @@
+//! Time source of generation
+//!
+//! Timestamps written to generated files and the dates deciding which
+//! renames are shimmed come from a [`Clock`], so embedders and tests can fix
+//! the time instead of reading the system clock.
+
+use chrono::{DateTime, Utc};
+use std::fmt::Debug;
+
+/// Source of the current time
+pub trait Clock: Debug + Send + Sync {
+    /// The current time
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock stopped at a given time
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
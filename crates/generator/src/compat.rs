@@ -0,0 +1,153 @@
+//! Deprecation shims for renamed kinds and fields
+
+use crate::crd::CrdSchema;
+use crate::naming::FieldNaming;
+use std::collections::BTreeMap;
+
+/// Name of the shim file emitted at the root of a generated library
+pub const COMPAT_FILE: &str = "_compat.libsonnet";
+
+/// An upstream rename to keep the old name working for
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rename {
+    /// A kind renamed from `from` to `to`
+    Kind { from: String, to: String },
+
+    /// A spec field of `kind` renamed from `from` to `to`
+    Field {
+        kind: String,
+        from: String,
+        to: String,
+    },
+}
+
+/// Generate the shim file for the renames that apply to a set of schemas
+///
+/// `import_path` gives the path of a schema's library relative to the output
+/// root. Returns `None` when no rename targets a generated kind.
+pub fn generate_compat_file(
+    renames: &[Rename],
+    schemas: &[&CrdSchema],
+    naming: FieldNaming,
+    import_path: impl Fn(&CrdSchema) -> String,
+) -> Option<String> {
+    let find = |kind: &str| schemas.iter().find(|schema| schema.kind() == kind);
+
+    let mut kinds = Vec::new();
+    let mut fields: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+
+    for rename in renames {
+        match rename {
+            Rename::Kind { from, to } => {
+                let Some(schema) = find(to) else {
+                    continue;
+                };
+                let params = if schema.is_object() && schema.properties().is_some() {
+                    "metadata, spec={}"
+                } else {
+                    "metadata"
+                };
+                let args = params.replace("={}", "");
+                kinds.push(format!(
+                    "  // Deprecated: {from} was renamed to {to}\n  {from}: function({params}) std.trace(\"{from} is deprecated, use {to}\", (import \"{}\")({args})),\n",
+                    import_path(schema)
+                ));
+            }
+            Rename::Field { kind, from, to } => {
+                if find(kind).is_none() {
+                    continue;
+                }
+                fields.entry(kind.as_str()).or_default().push(format!(
+                    "    // Deprecated: {from} was renamed to {to}\n    {}: function(value) std.trace(\"{kind}.{from} is deprecated, use {to}\", {{ spec+: {{ {to}: value }} }}),\n",
+                    naming.setter_name(from)
+                ));
+            }
+        }
+    }
+
+    if kinds.is_empty() && fields.is_empty() {
+        return None;
+    }
+
+    let mut content = String::new();
+    content.push_str("// Generated compatibility shims for renamed resources\n");
+    content.push_str("{\n");
+    for shim in kinds {
+        content.push_str(&shim);
+    }
+    for (kind, setters) in fields {
+        content.push_str(&format!("  {kind}: {{\n"));
+        for setter in setters {
+            content.push_str(&setter);
+        }
+        content.push_str("  },\n");
+    }
+    content.push_str("}\n");
+
+    Some(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn schema(kind: &str) -> CrdSchema {
+        CrdSchema {
+            name: format!("{}s.example.com", kind.to_lowercase()),
+            group: "example.com".to_string(),
+            version: "v1".to_string(),
+            api_version: "example.com/v1".to_string(),
+            kind: kind.to_string(),
+            schema: serde_yaml::from_str("type: object\nproperties:\n  replicas: {type: integer}")
+                .unwrap(),
+            source_path: PathBuf::from("widget.yaml"),
+            validation_rules: crate::crd::ValidationRules::default(),
+            schema_analysis: crate::crd::SchemaAnalysis::default(),
+            categories: Vec::new(),
+            printer_columns: Vec::new(),
+            conversion: crate::crd::ConversionInfo::default(),
+            field_lines: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_generate_compat_file() {
+        let widget = schema("Widget");
+        let renames = vec![
+            Rename::Kind {
+                from: "Gadget".to_string(),
+                to: "Widget".to_string(),
+            },
+            Rename::Field {
+                kind: "Widget".to_string(),
+                from: "size".to_string(),
+                to: "replicas".to_string(),
+            },
+            Rename::Kind {
+                from: "Old".to_string(),
+                to: "Missing".to_string(),
+            },
+        ];
+
+        let content = generate_compat_file(&renames, &[&widget], FieldNaming::Preserve, |_| {
+            "./example.com_v1/widget.libsonnet".to_string()
+        })
+        .unwrap();
+
+        assert!(content.contains(
+            "Gadget: function(metadata, spec={}) std.trace(\"Gadget is deprecated, use Widget\", (import \"./example.com_v1/widget.libsonnet\")(metadata, spec)),"
+        ));
+        assert!(content.contains(
+            "withSize: function(value) std.trace(\"Widget.size is deprecated, use replicas\", { spec+: { replicas: value } }),"
+        ));
+        assert!(!content.contains("Old"));
+
+        assert!(
+            generate_compat_file(&renames[2..], &[&widget], FieldNaming::Preserve, |_| {
+                String::new()
+            })
+            .is_none()
+        );
+    }
+}
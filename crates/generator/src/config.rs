@@ -1,7 +1,10 @@
 //! Configuration types for the generator
 
+use crate::naming::FieldNaming;
+use crate::style::CodeStyle;
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// Output configuration
@@ -12,6 +15,51 @@ pub struct OutputConfig {
 
     /// Organization strategy for output files
     pub organization: OrganizationStrategy,
+
+    /// Line endings of generated files
+    #[serde(default)]
+    pub newline: Newline,
+
+    /// Comment header prepended to every generated Jsonnet file
+    #[serde(default)]
+    pub header_template: Option<String>,
+
+    /// Preambles, localized headers and descriptions of generated files
+    #[serde(default)]
+    pub header: HeaderConfig,
+
+    /// Locale of generated comments, e.g. `de`
+    #[serde(default)]
+    pub locale: Option<String>,
+
+    /// Record generation timestamps; disable to keep regenerated files identical
+    #[serde(default = "default_timestamps")]
+    pub timestamps: bool,
+
+    /// Also emit `bundle.libsonnet`, the whole library inlined into one file
+    #[serde(default)]
+    pub bundle_single_file: bool,
+
+    /// Further roots every library is copied to, at its path relative to `base_path`
+    #[serde(default)]
+    pub mirrors: Vec<PathBuf>,
+
+    /// How setter names are derived from field names
+    #[serde(default)]
+    pub field_naming: FieldNaming,
+
+    /// Split the setters of schemas with more fields than this into files of
+    /// at most this many, below a directory named after the schema
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_functions_per_file: Option<usize>,
+
+    /// Code style of generated Jsonnet
+    #[serde(default, skip_serializing_if = "CodeStyle::is_default")]
+    pub style: CodeStyle,
+}
+
+fn default_timestamps() -> bool {
+    true
 }
 
 impl OutputConfig {
@@ -19,6 +67,19 @@ impl OutputConfig {
         if self.base_path.to_string_lossy().is_empty() {
             return Err(anyhow!("Base path cannot be empty"));
         }
+        if self.max_functions_per_file == Some(0) {
+            return Err(anyhow!("max_functions_per_file must be greater than 0"));
+        }
+        self.style.validate()?;
+        for mirror in &self.mirrors {
+            if mirror.starts_with(&self.base_path) {
+                return Err(anyhow!(
+                    "Mirror {:?} cannot be inside the base path {:?}",
+                    mirror,
+                    self.base_path
+                ));
+            }
+        }
         Ok(())
     }
 }
@@ -28,10 +89,41 @@ impl Default for OutputConfig {
         Self {
             base_path: PathBuf::from("./generated"),
             organization: OrganizationStrategy::ApiVersion,
+            newline: Newline::default(),
+            header_template: None,
+            header: HeaderConfig::default(),
+            locale: None,
+            timestamps: default_timestamps(),
+            bundle_single_file: false,
+            mirrors: Vec::new(),
+            field_naming: FieldNaming::default(),
+            max_functions_per_file: None,
+            style: CodeStyle::default(),
         }
     }
 }
 
+/// Preambles, localized headers and descriptions of generated files
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeaderConfig {
+    /// Preamble of every source's files, before the header template
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preamble: Option<String>,
+
+    /// Preambles by source name, replacing `preamble` for that source
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub sources: BTreeMap<String, String>,
+
+    /// Header templates by locale; the one of `output.locale` replaces
+    /// `header_template`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub locales: BTreeMap<String, String>,
+
+    /// Comment resources and setters with the descriptions of their schemas
+    pub descriptions: bool,
+}
+
 /// Organization strategy for output files
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -45,3 +137,25 @@ pub enum OrganizationStrategy {
     /// Hierarchical organization (nested directories)
     Hierarchical,
 }
+
+/// Line endings of generated files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Newline {
+    /// Unix line endings (`\n`)
+    #[default]
+    Lf,
+
+    /// Windows line endings (`\r\n`)
+    Crlf,
+}
+
+impl Newline {
+    /// The line ending itself
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Newline::Lf => "\n",
+            Newline::Crlf => "\r\n",
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! Builtin Kubernetes core types
+//!
+//! CRDs inline the schemas of the core types they use, such as a
+//! `LabelSelector`, a list of `Condition`s or a whole `PodTemplateSpec`. The
+//! definitions of the most common ones are bundled, so fields holding them are
+//! recognized by `$ref`, by `x-kubernetes-embedded-resource` or by their
+//! structure, and get the setters of the shared `_k8s.libsonnet` instead of
+//! being treated as opaque objects.
+
+use crate::naming::FieldNaming;
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// Name of the core type helpers emitted at the root of a generated library
+pub const CORE_TYPES_FILE: &str = "_k8s.libsonnet";
+
+/// A bundled core type definition
+#[derive(Debug, Deserialize)]
+pub struct CoreType {
+    /// Name of the upstream OpenAPI definition
+    pub definition: String,
+
+    /// Properties a field must declare to be taken for this type; dotted
+    /// paths name properties of nested objects
+    pub required: Vec<String>,
+
+    /// All properties of the type
+    pub properties: BTreeMap<String, PropertyKind>,
+}
+
+/// Shape of a core type property, which decides the setters it gets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PropertyKind {
+    String,
+    Integer,
+    Boolean,
+    Map,
+    Array,
+    Object,
+}
+
+/// A schema field holding a core type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreTypeField {
+    /// Dotted path of the field within the schema, `[]` marking array items
+    pub field_path: String,
+
+    /// Name of the core type, e.g. `LabelSelector`
+    pub type_name: &'static str,
+}
+
+/// The bundled core types by name
+pub fn core_types() -> &'static BTreeMap<String, CoreType> {
+    static CORE_TYPES: OnceLock<BTreeMap<String, CoreType>> = OnceLock::new();
+    CORE_TYPES.get_or_init(|| {
+        serde_json::from_str(include_str!("core_types.json")).expect("bundled core types are valid")
+    })
+}
+
+/// Core type a `$ref` points at, e.g. `#/definitions/io.k8s.api.core.v1.Container`
+pub fn resolve_reference(reference: &str) -> Option<&'static str> {
+    let definition = reference.rsplit('/').next()?;
+    core_types()
+        .iter()
+        .find(|(_, core_type)| core_type.definition == definition)
+        .map(|(name, _)| name.as_str())
+}
+
+/// Core type a field schema has the structure of
+///
+/// The field's properties must all belong to the type and include its
+/// required ones. When several types fit, the one with the fewest properties
+/// is the closest match.
+pub fn match_structure(field_schema: &Value) -> Option<&'static str> {
+    let properties = field_schema.get("properties")?.as_mapping()?;
+    if properties.is_empty() {
+        return None;
+    }
+
+    core_types()
+        .iter()
+        .filter(|(_, core_type)| {
+            properties.keys().all(|key| {
+                key.as_str()
+                    .is_some_and(|key| core_type.properties.contains_key(key))
+            }) && core_type
+                .required
+                .iter()
+                .all(|path| has_property(field_schema, path))
+        })
+        .min_by_key(|(_, core_type)| core_type.properties.len())
+        .map(|(name, _)| name.as_str())
+}
+
+/// Whether a schema declares the property at a dotted path
+fn has_property(schema: &Value, path: &str) -> bool {
+    path.split('.')
+        .try_fold(schema, |schema, name| schema.get("properties")?.get(name))
+        .is_some()
+}
+
+/// Find the fields below a properties mapping that hold core types
+pub fn find_core_type_fields(properties: &Mapping) -> Vec<CoreTypeField> {
+    let mut fields = Vec::new();
+    for (name, field_schema) in properties {
+        if let Some(name) = name.as_str() {
+            visit(name.to_string(), field_schema, &mut fields);
+        }
+    }
+    fields
+}
+
+fn visit(field_path: String, field_schema: &Value, fields: &mut Vec<CoreTypeField>) {
+    let core_type = field_schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(resolve_reference)
+        .or_else(|| match_structure(field_schema));
+    if let Some(type_name) = core_type {
+        fields.push(CoreTypeField {
+            field_path,
+            type_name,
+        });
+        return;
+    }
+
+    // An embedded resource carries its own metadata, even when the schema
+    // leaves it unspecified
+    let embedded = field_schema
+        .get("x-kubernetes-embedded-resource")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if embedded {
+        fields.push(CoreTypeField {
+            field_path: format!("{field_path}.metadata"),
+            type_name: "ObjectMeta",
+        });
+    }
+
+    if let Some(properties) = field_schema.get("properties").and_then(Value::as_mapping) {
+        for (name, nested) in properties {
+            match name.as_str() {
+                Some("metadata") if embedded => {}
+                Some(name) => visit(format!("{field_path}.{name}"), nested, fields),
+                None => {}
+            }
+        }
+    }
+    if let Some(items) = field_schema.get("items") {
+        visit(format!("{field_path}[]"), items, fields);
+    }
+}
+
+/// Generate `_k8s.libsonnet`, holding setters for every bundled core type
+pub fn generate_core_types_file() -> String {
+    let mut content = String::new();
+    content.push_str("// Generated helpers for builtin Kubernetes core types\n");
+    content.push_str("{\n");
+
+    for (name, core_type) in core_types() {
+        content.push_str(&format!("  // {}\n", core_type.definition));
+        content.push_str(&format!("  {name}: {{\n"));
+        for (property, kind) in &core_type.properties {
+            let setter = FieldNaming::Preserve.setter_name(property);
+            match kind {
+                PropertyKind::Array => {
+                    let value =
+                        format!("if std.isArray({property}) then {property} else [{property}]");
+                    content.push_str(&format!(
+                        "    {setter}({property}): {{ {property}: {value} }},\n"
+                    ));
+                    content.push_str(&format!(
+                        "    {setter}Mixin({property}): {{ {property}+: {value} }},\n"
+                    ));
+                }
+                PropertyKind::Map | PropertyKind::Object => {
+                    content.push_str(&format!(
+                        "    {setter}({property}): {{ {property}: {property} }},\n"
+                    ));
+                    content.push_str(&format!(
+                        "    {setter}Mixin({property}): {{ {property}+: {property} }},\n"
+                    ));
+                }
+                PropertyKind::String | PropertyKind::Integer | PropertyKind::Boolean => {
+                    content.push_str(&format!(
+                        "    {setter}({property}): {{ {property}: {property} }},\n"
+                    ));
+                }
+            }
+        }
+        content.push_str("  },\n");
+    }
+
+    content.push_str("}\n");
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_core_type_fields() {
+        let properties: Mapping = serde_yaml::from_str(
+            r##"
+spec:
+  type: object
+  properties:
+    selector:
+      type: object
+      properties:
+        matchLabels: {type: object, additionalProperties: {type: string}}
+    template:
+      type: object
+      properties:
+        metadata: {type: object}
+        spec:
+          type: object
+          properties:
+            containers: {type: array}
+    owner:
+      $ref: "#/definitions/io.k8s.api.core.v1.LocalObjectReference"
+    resource:
+      type: object
+      x-kubernetes-embedded-resource: true
+      properties:
+        metadata: {type: object}
+        data: {type: object}
+    replicas:
+      type: integer
+status:
+  type: object
+  properties:
+    conditions:
+      type: array
+      items:
+        type: object
+        properties:
+          type: {type: string}
+          status: {type: string}
+          lastTransitionTime: {type: string}
+          message: {type: string}
+"##,
+        )
+        .unwrap();
+
+        let fields: Vec<(String, &str)> = find_core_type_fields(&properties)
+            .into_iter()
+            .map(|field| (field.field_path, field.type_name))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("spec.selector".to_string(), "LabelSelector"),
+                ("spec.template".to_string(), "PodTemplateSpec"),
+                ("spec.owner".to_string(), "LocalObjectReference"),
+                ("spec.resource.metadata".to_string(), "ObjectMeta"),
+                ("status.conditions[]".to_string(), "Condition"),
+            ]
+        );
+
+        let content = generate_core_types_file();
+        assert!(content
+            .contains("    withMatchLabelsMixin(matchLabels): { matchLabels+: matchLabels },\n"));
+        assert!(content.contains("  // io.k8s.api.core.v1.Container\n  Container: {\n"));
+    }
+}
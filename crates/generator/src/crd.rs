@@ -1,7 +1,7 @@
 //! CRD types for the generator
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// Represents a parsed CRD schema with advanced features
@@ -22,6 +22,14 @@ pub struct CrdSchema {
     /// Resource kind (from spec.names.kind)
     pub kind: String,
 
+    /// Categories the resource belongs to
+    #[serde(default)]
+    pub categories: Vec<String>,
+
+    /// Columns shown by `kubectl get`
+    #[serde(default)]
+    pub printer_columns: Vec<PrinterColumn>,
+
     /// OpenAPI v3 schema
     pub schema: serde_yaml::Value,
 
@@ -33,6 +41,14 @@ pub struct CrdSchema {
 
     /// Schema analysis
     pub schema_analysis: SchemaAnalysis,
+
+    /// Conversion between versions
+    #[serde(default)]
+    pub conversion: ConversionInfo,
+
+    /// Line each property is declared on in `source_path`
+    #[serde(default)]
+    pub field_lines: BTreeMap<String, usize>,
 }
 
 impl CrdSchema {
@@ -70,9 +86,7 @@ impl CrdSchema {
 
     /// Get validation rules for a specific field
     pub fn get_field_validation(&self, field_name: &str) -> Option<&ValidationRules> {
-        self.schema_analysis
-            .fields
-            .get(field_name)
+        self.get_field_type(field_name)
             .map(|field| &field.validation_rules)
     }
 
@@ -81,9 +95,13 @@ impl CrdSchema {
         self.required_fields().contains(&field_name.to_string())
     }
 
-    /// Get field type information
+    /// Get field type information, by top-level field name or JSON pointer
     pub fn get_field_type(&self, field_name: &str) -> Option<&FieldAnalysis> {
-        self.schema_analysis.fields.get(field_name)
+        if field_name.starts_with('/') {
+            self.schema_analysis.fields.get(field_name)
+        } else {
+            self.schema_analysis.fields.get(&field_pointer(field_name))
+        }
     }
 }
 
@@ -145,8 +163,8 @@ pub struct SchemaAnalysis {
     /// Schema type (object, array, string, etc.)
     pub schema_type: String,
 
-    /// Field analysis for object types
-    pub fields: HashMap<String, FieldAnalysis>,
+    /// Analysis of every field, nested ones included, by JSON pointer
+    pub fields: BTreeMap<String, FieldAnalysis>,
 
     /// Array item type analysis
     pub array_item_type: Option<FieldAnalysis>,
@@ -167,6 +185,10 @@ pub struct SchemaAnalysis {
 /// Analysis of a field schema
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct FieldAnalysis {
+    /// JSON pointer to the field, e.g. `/spec/replicas`
+    #[serde(default)]
+    pub pointer: String,
+
     /// Field type
     pub field_type: String,
 
@@ -179,3 +201,67 @@ pub struct FieldAnalysis {
     /// Array items schema
     pub array_items: Option<serde_yaml::Value>,
 }
+
+/// JSON pointer to a top-level field, e.g. `/replicas` for `replicas`
+pub fn field_pointer(field_name: &str) -> String {
+    format!("/{}", field_name.replace('~', "~0").replace('/', "~1"))
+}
+
+/// How the API server converts objects between versions of a CRD
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub enum ConversionStrategy {
+    /// Only `apiVersion` changes; versions must be structurally identical
+    #[default]
+    None,
+
+    /// A conversion webhook transforms objects between versions
+    Webhook,
+}
+
+/// Conversion settings of a CRD
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct ConversionInfo {
+    /// Conversion strategy
+    pub strategy: ConversionStrategy,
+
+    /// Webhook endpoint, as a URL or service reference
+    pub webhook_endpoint: Option<String>,
+
+    /// ConversionReview versions the webhook accepts
+    pub conversion_review_versions: Vec<String>,
+}
+
+impl ConversionInfo {
+    /// Whether conversion between versions needs a webhook
+    pub fn is_webhook(&self) -> bool {
+        self.strategy == ConversionStrategy::Webhook
+    }
+}
+
+/// A column `kubectl get` shows for a resource
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrinterColumn {
+    /// Column header
+    pub name: String,
+
+    /// Column type, e.g. `string`, `integer` or `date`
+    #[serde(rename = "type")]
+    pub column_type: String,
+
+    /// JSON path of the value shown in the column
+    #[serde(alias = "JSONPath")]
+    pub json_path: String,
+
+    /// Human readable description of the column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Priority; columns above 0 are only shown in wide output
+    #[serde(default)]
+    pub priority: i32,
+
+    /// Format of the value, e.g. `date-time`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+}
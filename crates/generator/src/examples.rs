@@ -0,0 +1,158 @@
+//! Usage examples
+//!
+//! `examples/` holds one runnable Jsonnet file per generated resource,
+//! instantiating it with its required fields and the fields its schema
+//! gives defaults for. The examples document how each resource is built and
+//! double as smoke tests of the library.
+
+use crate::crd::CrdSchema;
+use anyhow::Result;
+
+/// Directory of the examples, relative to the library root
+pub const EXAMPLES_DIR: &str = "examples";
+
+/// Path of a schema's example relative to the library root,
+/// e.g. `examples/example.com_v1/widget.jsonnet`
+pub fn example_path(schema: &CrdSchema) -> String {
+    format!(
+        "{}/{}/{}.jsonnet",
+        EXAMPLES_DIR,
+        schema.api_version.replace('/', "_"),
+        schema.kind().to_lowercase()
+    )
+}
+
+/// Generate the example of a schema
+///
+/// `library_path` is the schema's library relative to the library root;
+/// `value` renders a field's example value from its schema.
+pub fn generate_example(
+    schema: &CrdSchema,
+    library_path: &str,
+    value: impl Fn(&serde_yaml::Value) -> Result<String>,
+) -> Result<String> {
+    let kind = schema.kind();
+    let mut content = String::new();
+    content.push_str(&format!("// Example {} ({})\n", kind, schema.api_version));
+    content.push_str("// Built from the resource's required fields and schema defaults.\n");
+    content.push_str("// Evaluate with the library root and k.libsonnet on the search path.\n");
+    content.push_str(&format!(
+        "local resource = import \"../../{}\";\n\n",
+        library_path.trim_start_matches("./")
+    ));
+    content.push_str("resource(\n");
+    content.push_str(&format!(
+        "  {{ name: \"example-{}\" }},\n",
+        kind.to_lowercase()
+    ));
+
+    if let (true, Some(properties)) = (schema.is_object(), schema.properties()) {
+        let required: Vec<&str> = schema
+            .schema
+            .get("required")
+            .and_then(|required| required.as_sequence())
+            .map(|required| required.iter().filter_map(|r| r.as_str()).collect())
+            .unwrap_or_default();
+        let mut defaulted: Vec<&str> = properties
+            .iter()
+            .filter(|(_, field)| field.get("default").is_some())
+            .filter_map(|(name, _)| name.as_str())
+            .filter(|name| !required.contains(name))
+            .collect();
+        defaulted.sort_unstable();
+
+        content.push_str("  {\n");
+        for (group, fields) in [("Required", &required), ("Defaults", &defaulted)] {
+            let fields: Vec<(&str, &serde_yaml::Value)> = fields
+                .iter()
+                .filter_map(|name| properties.get(*name).map(|field| (*name, field)))
+                .collect();
+            if fields.is_empty() {
+                continue;
+            }
+            content.push_str(&format!("    // {group}\n"));
+            for (name, field) in fields {
+                content.push_str(&format!("    {}: {},\n", name, value(field)?));
+            }
+        }
+        content.push_str("  },\n");
+    }
+
+    content.push_str(")\n");
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_generate_example() {
+        let widget = CrdSchema {
+            name: "widgets.example.com".to_string(),
+            group: "example.com".to_string(),
+            version: "v1".to_string(),
+            api_version: "example.com/v1".to_string(),
+            kind: "Widget".to_string(),
+            schema: serde_yaml::from_str(
+                r#"
+type: object
+required: [size]
+properties:
+  size:
+    type: string
+    enum: [small, large]
+  replicas:
+    type: integer
+    default: 1
+  paused:
+    type: boolean
+"#,
+            )
+            .unwrap(),
+            source_path: PathBuf::from("widget.yaml"),
+            validation_rules: Default::default(),
+            schema_analysis: Default::default(),
+            categories: Vec::new(),
+            printer_columns: Vec::new(),
+            conversion: Default::default(),
+            field_lines: Default::default(),
+        };
+
+        assert_eq!(
+            example_path(&widget),
+            "examples/example.com_v1/widget.jsonnet"
+        );
+        let content = generate_example(
+            &widget,
+            "./example.com_v1/widgets.example.com.libsonnet",
+            |field| {
+                let value = field
+                    .get("default")
+                    .or_else(|| field.get("enum").and_then(|e| e.get(0)))
+                    .unwrap();
+                Ok(serde_json::to_string(value)?)
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            content,
+            r#"// Example Widget (example.com/v1)
+// Built from the resource's required fields and schema defaults.
+// Evaluate with the library root and k.libsonnet on the search path.
+local resource = import "../../example.com_v1/widgets.example.com.libsonnet";
+
+resource(
+  { name: "example-widget" },
+  {
+    // Required
+    size: "small",
+    // Defaults
+    replicas: 1,
+  },
+)
+"#
+        );
+    }
+}
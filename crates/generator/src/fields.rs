@@ -0,0 +1,164 @@
+//! Field reference index
+//!
+//! `_fields.json` lists every generated setter with the schema path it sets,
+//! the JSON pointer naming the field in schema analyses, the field's type,
+//! validation rules, description and the line of the upstream file declaring
+//! it, so documentation sites and editors can offer searchable reference docs
+//! without parsing the schemas themselves.
+
+use crate::crd::CrdSchema;
+use crate::naming::FieldNaming;
+use anyhow::Result;
+
+/// Name of the field index emitted at the root of each generated library
+pub const FIELDS_INDEX_FILE: &str = "_fields.json";
+
+/// Schema keywords recorded as a field's validation rules
+const VALIDATION_KEYWORDS: &[&str] = &[
+    "enum",
+    "format",
+    "pattern",
+    "minLength",
+    "maxLength",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "x-kubernetes-validations",
+];
+
+/// Generate the field index of a set of schemas
+///
+/// `library_path` gives the path of a schema's library relative to the
+/// output root. Entries are ordered by kind, then field.
+pub fn generate_fields_index(
+    schemas: &[&CrdSchema],
+    naming: FieldNaming,
+    library_path: impl Fn(&CrdSchema) -> String,
+) -> Result<String> {
+    let mut schemas = schemas.to_vec();
+    schemas.sort_by(|a, b| (a.kind(), &a.api_version).cmp(&(b.kind(), &b.api_version)));
+
+    let mut entries = Vec::new();
+    for schema in schemas {
+        let Some(properties) = schema.properties() else {
+            continue;
+        };
+        let mut fields: Vec<(&str, &serde_yaml::Value)> = properties
+            .iter()
+            .filter_map(|(name, field)| name.as_str().map(|name| (name, field)))
+            .collect();
+        fields.sort_by_key(|(name, _)| *name);
+        let setters = naming.setter_names(fields.iter().map(|(name, _)| *name))?;
+
+        for (field_name, field_schema) in fields {
+            let mut validation = serde_json::Map::new();
+            for keyword in VALIDATION_KEYWORDS {
+                if let Some(value) = field_schema.get(*keyword) {
+                    validation.insert(keyword.to_string(), serde_json::to_value(value)?);
+                }
+            }
+
+            entries.push(serde_json::json!({
+                "setter": setters[field_name],
+                "kind": schema.kind(),
+                "apiVersion": schema.api_version,
+                "file": library_path(schema),
+                "path": format!("spec.{field_name}"),
+                "pointer": crate::crd::field_pointer(field_name),
+                "type": field_schema.get("type").and_then(|t| t.as_str()).unwrap_or("object"),
+                "required": schema.is_field_required(field_name),
+                "validation": validation,
+                "description": field_schema.get("description").and_then(|d| d.as_str()),
+                "source": schema.field_lines.get(field_name).map(|line| serde_json::json!({
+                    "file": schema.source_path,
+                    "line": line,
+                })),
+            }));
+        }
+    }
+
+    Ok(serde_json::to_string_pretty(&entries)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_generate_fields_index() {
+        let widget = CrdSchema {
+            name: "widgets.example.com".to_string(),
+            group: "example.com".to_string(),
+            version: "v1".to_string(),
+            api_version: "example.com/v1".to_string(),
+            kind: "Widget".to_string(),
+            schema: serde_yaml::from_str(
+                r#"
+type: object
+properties:
+  size:
+    type: string
+    description: Size of the widget
+    enum: [small, large]
+  replicas:
+    type: integer
+    minimum: 1
+"#,
+            )
+            .unwrap(),
+            source_path: PathBuf::from("widget.yaml"),
+            validation_rules: crate::crd::ValidationRules {
+                required: vec!["size".to_string()],
+                ..Default::default()
+            },
+            schema_analysis: crate::crd::SchemaAnalysis::default(),
+            categories: Vec::new(),
+            printer_columns: Vec::new(),
+            conversion: crate::crd::ConversionInfo::default(),
+            field_lines: [("size".to_string(), 14)].into(),
+        };
+
+        let content = generate_fields_index(&[&widget], FieldNaming::Preserve, |schema| {
+            format!("example.com_v1/{}.libsonnet", schema.kind().to_lowercase())
+        })
+        .unwrap();
+        let entries: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(
+            entries,
+            serde_json::json!([
+                {
+                    "setter": "withReplicas",
+                    "kind": "Widget",
+                    "apiVersion": "example.com/v1",
+                    "file": "example.com_v1/widget.libsonnet",
+                    "path": "spec.replicas",
+                    "pointer": "/replicas",
+                    "type": "integer",
+                    "required": false,
+                    "validation": {"minimum": 1},
+                    "description": null,
+                    "source": null,
+                },
+                {
+                    "setter": "withSize",
+                    "kind": "Widget",
+                    "apiVersion": "example.com/v1",
+                    "file": "example.com_v1/widget.libsonnet",
+                    "path": "spec.size",
+                    "pointer": "/size",
+                    "type": "string",
+                    "required": true,
+                    "validation": {"enum": ["small", "large"]},
+                    "description": "Size of the widget",
+                    "source": {"file": "widget.yaml", "line": 14},
+                },
+            ])
+        );
+    }
+}
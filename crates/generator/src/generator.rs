@@ -1,50 +1,195 @@
 //! Main Jsonnet generator implementation
 
-use crate::config::OutputConfig;
+use crate::arguments;
+use crate::clock::{Clock, SystemClock};
+use crate::compat::{self, Rename};
+use crate::config::{Newline, OutputConfig};
+use crate::core_types::{self, CoreTypeField};
 use crate::crd::CrdSchema;
-use crate::result::SourceResult;
-use crate::validation::ValidationGenerator;
-use anyhow::Result;
-use std::collections::HashMap;
+use crate::examples;
+use crate::fields;
+use crate::header;
+use crate::list_maps;
+use crate::naming::FieldNaming;
+use crate::presets::{self, Preset};
+use crate::result::{SourceResult, UnresolvedType};
+use crate::style::FieldOrder;
+use crate::validation::{CustomValidator, ValidationGenerator};
+use crate::write::write_generated;
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tracing::{info, warn};
 
 pub struct JsonnetGenerator {
     output_config: OutputConfig,
     validation_generator: ValidationGenerator,
+    fail_on_unresolved: bool,
+    renames: Vec<Rename>,
+    clock: Arc<dyn Clock>,
 }
 
+/// Schema digests of a previously published library, used to skip unchanged schemas
+#[derive(Debug, Clone, Default)]
+pub struct SchemaCache {
+    /// Schema digests by file path relative to the library root
+    pub digests: BTreeMap<String, String>,
+
+    /// Where the library was published; reused files must still exist there
+    pub published_path: PathBuf,
+
+    /// Header the published files start with, part of every digest
+    pub header: String,
+
+    /// SPDX license of the source, recorded in `_meta.libsonnet`
+    pub license: Option<String>,
+}
+
+impl SchemaCache {
+    /// Whether the file at `relative_path` was generated from a schema with this digest
+    fn is_fresh(&self, relative_path: &str, digest: &str) -> bool {
+        self.digests.get(relative_path).map(String::as_str) == Some(digest)
+            && self.published_path.join(relative_path).is_file()
+    }
+}
+
+/// Name of the self-test file emitted at the root of each generated library
+pub const SELFTEST_FILE: &str = "_tests.libsonnet";
+
+/// Name of the sample resources emitted at the root of each generated library
+pub const SAMPLES_FILE: &str = "_samples.libsonnet";
+
+/// Name of the JSON export of all schemas emitted at the root of each library
+pub const SCHEMA_EXPORT_FILE: &str = "_schemas.json";
+
+/// Name of the index merging the setter files of a split schema library
+const SETTERS_INDEX_FILE: &str = "_index.libsonnet";
+
+/// A spec field with its setter name and schema
+type Setter<'a> = (&'a str, String, &'a serde_yaml::Value);
+
+/// Schema types the generator knows how to emit
+const KNOWN_TYPES: &[&str] = &["string", "integer", "number", "boolean", "array", "object"];
+
 impl JsonnetGenerator {
     pub fn new(output_config: OutputConfig) -> Self {
         Self {
+            validation_generator: ValidationGenerator::new().with_newline(output_config.newline),
             output_config,
-            validation_generator: ValidationGenerator::new(),
+            fail_on_unresolved: false,
+            renames: Vec::new(),
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Read generation timestamps from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Record generation timestamps, overriding `output.timestamps`
+    pub fn with_timestamps(mut self, timestamps: bool) -> Self {
+        self.output_config.timestamps = timestamps;
+        self
+    }
+
+    /// Fail generation instead of emitting `__unresolved` stubs for unknown types
+    pub fn with_fail_on_unresolved(mut self, fail_on_unresolved: bool) -> Self {
+        self.fail_on_unresolved = fail_on_unresolved;
+        self
+    }
+
+    /// Target the gensonnet-natives `std.native` functions in generated validation
+    pub fn with_native_functions(mut self, native_functions: bool) -> Self {
+        self.validation_generator = self
+            .validation_generator
+            .with_native_functions(native_functions);
+        self
+    }
+
+    /// Emit deprecation shims in `_compat.libsonnet` for the given renames
+    pub fn with_renames(mut self, renames: Vec<Rename>) -> Self {
+        self.renames = renames;
+        self
+    }
+
     /// Generate Jsonnet library from CRD schemas
     pub async fn generate_crd_library(
         &self,
         schemas: &[CrdSchema],
         output_path: &Path,
+    ) -> Result<SourceResult> {
+        self.generate_crd_library_cached(schemas, output_path, &SchemaCache::default())
+            .await
+    }
+
+    /// Generate Jsonnet library from CRD schemas, skipping schema files whose
+    /// schema is unchanged since the library was published
+    ///
+    /// Skipped files are not written to `output_path`; they are left in place
+    /// at `cache.published_path`.
+    pub async fn generate_crd_library_cached(
+        &self,
+        schemas: &[CrdSchema],
+        output_path: &Path,
+        cache: &SchemaCache,
     ) -> Result<SourceResult> {
         info!(
             "Generating Jsonnet library for {} CRD schemas",
             schemas.len()
         );
 
+        let unresolved: Vec<UnresolvedType> =
+            schemas.iter().flat_map(find_unresolved_types).collect();
+        if self.fail_on_unresolved && !unresolved.is_empty() {
+            let details: Vec<String> = unresolved.iter().map(|u| u.to_string()).collect();
+            return Err(anyhow!(
+                "{} unresolved types:\n  {}",
+                unresolved.len(),
+                details.join("\n  ")
+            ));
+        }
+
+        // Fields are generated in the configured order
+        let ordered: Vec<CrdSchema>;
+        let schemas = if self.output_config.style.field_order == FieldOrder::Alphabetical {
+            ordered = schemas
+                .iter()
+                .map(|schema| {
+                    let mut schema = schema.clone();
+                    self.output_config.style.order_fields(&mut schema.schema);
+                    schema
+                })
+                .collect();
+            &ordered
+        } else {
+            schemas
+        };
+
         // Create output directory
         std::fs::create_dir_all(output_path)?;
 
         let mut generated_files = Vec::new();
         let mut errors = Vec::new();
+        let mut schema_digests = BTreeMap::new();
+        let mut schemas_reused = 0;
 
         // Group schemas by API version
         let grouped_schemas = self.group_schemas_by_version(schemas);
 
         for (api_version, version_schemas) in &grouped_schemas {
             match self
-                .generate_version_library(api_version, version_schemas, output_path)
+                .generate_version_library(
+                    api_version,
+                    version_schemas,
+                    output_path,
+                    cache,
+                    &mut schema_digests,
+                    &mut schemas_reused,
+                )
                 .await
             {
                 Ok(files) => generated_files.extend(files),
@@ -63,30 +208,184 @@ impl JsonnetGenerator {
             errors.push(format!("Failed to generate index file: {e}"));
         }
 
+        // Generate self-tests
+        if let Err(e) = self.generate_selftest_file(&grouped_schemas, output_path) {
+            errors.push(format!("Failed to generate self-tests: {e}"));
+        }
+
+        // Generate sample resources
+        if let Err(e) = self.generate_samples_file(&grouped_schemas, output_path) {
+            errors.push(format!("Failed to generate samples: {e}"));
+        }
+
+        // Generate usage examples
+        if let Err(e) = self.generate_examples(&grouped_schemas, output_path) {
+            errors.push(format!("Failed to generate examples: {e}"));
+        }
+
+        // Generate compatibility shims
+        let all_schemas: Vec<&CrdSchema> = schemas.iter().collect();
+        if let Some(content) = compat::generate_compat_file(
+            &self.renames,
+            &all_schemas,
+            self.output_config.field_naming,
+            |schema| {
+                format!(
+                    "./{}/{}.libsonnet",
+                    self.version_dir(&schema.api_version),
+                    schema.name.to_lowercase()
+                )
+            },
+        ) {
+            if let Err(e) = write_generated(
+                &output_path.join(compat::COMPAT_FILE),
+                &content,
+                self.output_config.newline,
+            ) {
+                errors.push(format!("Failed to generate compatibility shims: {e}"));
+            }
+        }
+
         // Generate metadata file
-        if let Err(e) = self.generate_metadata_file(schemas, output_path).await {
+        if let Err(e) = self
+            .generate_metadata_file(schemas, cache.license.as_deref(), output_path)
+            .await
+        {
             errors.push(format!("Failed to generate metadata file: {e}"));
         }
 
+        // Export the schemas as JSON for tools that do not read Jsonnet
+        if let Err(e) = self.generate_schema_export(schemas, output_path) {
+            errors.push(format!("Failed to export schemas: {e}"));
+        }
+
+        let library_path = |schema: &CrdSchema| self.library_path(schema);
+
+        // Index every setter for documentation tools
+        if let Err(e) = fields::generate_fields_index(
+            &all_schemas,
+            self.output_config.field_naming,
+            library_path,
+        )
+        .and_then(|content| {
+            write_generated(
+                &output_path.join(fields::FIELDS_INDEX_FILE),
+                &content,
+                self.output_config.newline,
+            )
+        }) {
+            errors.push(format!("Failed to generate field index: {e}"));
+        }
+
+        // Describe the arguments of every builder function as JSON Schema
+        if let Err(e) = arguments::generate_arguments(
+            &all_schemas,
+            self.output_config.field_naming,
+            library_path,
+        )
+        .and_then(|content| {
+            write_generated(
+                &output_path.join(arguments::ARGUMENTS_FILE),
+                &content,
+                self.output_config.newline,
+            )
+        }) {
+            errors.push(format!("Failed to generate argument schemas: {e}"));
+        }
+
+        // Generate helpers for the core types schemas embed
+        if schemas
+            .iter()
+            .any(|schema| !find_core_type_fields(schema).is_empty())
+        {
+            if let Err(e) = write_generated(
+                &output_path.join(core_types::CORE_TYPES_FILE),
+                &core_types::generate_core_types_file(),
+                self.output_config.newline,
+            ) {
+                errors.push(format!("Failed to generate core type helpers: {e}"));
+            }
+        }
+
         // Generate validation utilities
         if let Err(e) = self
             .validation_generator
-            .generate_validation_utilities(output_path)
+            .generate_validation_utilities(output_path, &all_schemas)
             .await
         {
             errors.push(format!("Failed to generate validation utilities: {e}"));
         }
 
         Ok(SourceResult {
+            source_name: String::new(), // Will be set by the caller
             source_type: "crd".to_string(),
             files_generated: generated_files.len(),
             errors,
             output_path: output_path.to_path_buf(),
             processing_time_ms: 0, // Will be set by the caller
-            warnings: Vec::new(),
+            warnings: unresolved.iter().map(UnresolvedType::to_warning).collect(),
+            plugin: None,
+            file_plugins: BTreeMap::new(),
+            schema_digests,
+            schemas_reused,
+            statistics: Default::default(),
         })
     }
 
+    /// Append plugin-contributed validators to the generated `_validation.libsonnet`
+    pub fn append_custom_validators(
+        &self,
+        output_path: &Path,
+        validators: &[CustomValidator],
+    ) -> Result<()> {
+        self.validation_generator
+            .append_custom_validators(output_path, validators)
+    }
+
+    /// Write the examples and `_presets.libsonnet` of ingested samples and
+    /// link the presets from the generated `index.libsonnet`
+    ///
+    /// Presets of schemas not among `schemas` are ignored.
+    pub fn generate_presets(
+        &self,
+        schemas: &[CrdSchema],
+        samples: &[Preset],
+        output_path: &Path,
+    ) -> Result<()> {
+        let schemas: Vec<&CrdSchema> = schemas
+            .iter()
+            .filter(|schema| samples.iter().any(|preset| preset.schema == schema.name))
+            .collect();
+        if schemas.is_empty() {
+            return Ok(());
+        }
+
+        for preset in samples {
+            let Some(schema) = schemas.iter().find(|schema| schema.name == preset.schema) else {
+                continue;
+            };
+            let content = presets::generate_example(schema, &self.library_path(schema), preset)?;
+            let path = output_path.join(presets::example_path(schema, preset));
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            write_generated(&path, &content, self.output_config.newline)?;
+        }
+
+        write_generated(
+            &output_path.join(presets::PRESETS_FILE),
+            &presets::generate_presets_file(&schemas, samples)?,
+            self.output_config.newline,
+        )?;
+        let index_path = output_path.join("index.libsonnet");
+        let index = std::fs::read_to_string(&index_path)?;
+        write_generated(
+            &index_path,
+            &presets::link_index(&index),
+            self.output_config.newline,
+        )
+    }
+
     /// Group schemas by API version
     fn group_schemas_by_version<'a>(
         &self,
@@ -106,11 +405,17 @@ impl JsonnetGenerator {
     }
 
     /// Generate library for a specific API version
+    ///
+    /// Records the digest of every schema and skips schema files the cache
+    /// holds a fresh copy of.
     async fn generate_version_library(
         &self,
         api_version: &str,
         schemas: &[&CrdSchema],
         output_path: &Path,
+        cache: &SchemaCache,
+        schema_digests: &mut BTreeMap<String, String>,
+        schemas_reused: &mut usize,
     ) -> Result<Vec<PathBuf>> {
         let version_path = match self.output_config.organization {
             crate::config::OrganizationStrategy::ApiVersion => {
@@ -134,9 +439,24 @@ impl JsonnetGenerator {
 
         for schema in schemas {
             let file_path = version_path.join(format!("{}.libsonnet", schema.name.to_lowercase()));
+            let relative_path = file_path
+                .strip_prefix(output_path)?
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let digest = schema_digest(schema, &cache.header, &self.content_settings())?;
+
+            if cache.is_fresh(&relative_path, &digest) {
+                info!("Schema unchanged, keeping {}", relative_path);
+                schema_digests.insert(relative_path, digest);
+                *schemas_reused += 1;
+                continue;
+            }
+            schema_digests.insert(relative_path, digest);
 
             match self.generate_schema_file(schema, &file_path).await {
-                Ok(_) => generated_files.push(file_path),
+                Ok(files) => generated_files.extend(files),
                 Err(e) => {
                     warn!("Failed to generate schema file for {}: {}", schema.name, e);
                 }
@@ -155,11 +475,49 @@ impl JsonnetGenerator {
     }
 
     /// Generate Jsonnet file for a single schema
-    async fn generate_schema_file(&self, schema: &CrdSchema, file_path: &Path) -> Result<()> {
+    ///
+    /// Returns the files written: the schema file, and the setter files and
+    /// their index when the schema's setters are split.
+    async fn generate_schema_file(
+        &self,
+        schema: &CrdSchema,
+        file_path: &Path,
+    ) -> Result<Vec<PathBuf>> {
         let content = self.generate_schema_content(schema)?;
-        std::fs::write(file_path, content)?;
+        write_generated(file_path, &content, self.output_config.newline)?;
         info!("Generated schema file: {:?}", file_path);
-        Ok(())
+
+        let mut files = vec![file_path.to_path_buf()];
+        if let Some(chunks) = self.field_chunks(schema)? {
+            let dir = file_path.with_file_name(schema.name.to_lowercase());
+            std::fs::create_dir_all(&dir)?;
+            let mut index = format!(
+                "// Setters of {}, split into {} files\n",
+                schema.name,
+                chunks.len()
+            );
+            for (part, chunk) in chunks.iter().enumerate() {
+                let name = format!("fields_{}.libsonnet", part + 1);
+                let path = dir.join(&name);
+                write_generated(
+                    &path,
+                    &self.generate_field_chunk(schema, part, chunks.len(), chunk),
+                    self.output_config.newline,
+                )?;
+                files.push(path);
+                let separator = if part == 0 { "" } else { "+ " };
+                index.push_str(&format!("{separator}(import \"{name}\")\n"));
+            }
+            let index_path = dir.join(SETTERS_INDEX_FILE);
+            write_generated(&index_path, &index, self.output_config.newline)?;
+            files.push(index_path);
+            info!(
+                "Split the setters of {} into {} files",
+                schema.name,
+                chunks.len()
+            );
+        }
+        Ok(files)
     }
 
     /// Generate Jsonnet content for a schema
@@ -169,11 +527,39 @@ impl JsonnetGenerator {
         // Add header comment
         content.push_str(&format!("// Generated from CRD: {}\n", schema.name));
         content.push_str(&format!("// API Version: {}\n", schema.api_version));
-        content.push_str(&format!("// Source: {}\n\n", schema.source_path.display()));
+        content.push_str(&format!("// Source: {}\n", schema.source_path.display()));
+        if schema.conversion.is_webhook() {
+            // Escaped so a line break in the endpoint cannot end the comment
+            content.push_str(&format!(
+                "// Conversion: Webhook ({})\n",
+                schema
+                    .conversion
+                    .webhook_endpoint
+                    .as_deref()
+                    .unwrap_or("endpoint not declared")
+                    .escape_debug()
+            ));
+        }
+        content.push('\n');
 
         // Add imports
         content.push_str("local k = import \"k.libsonnet\";\n");
-        content.push_str("local validate = import \"_validation.libsonnet\";\n\n");
+        content.push_str("local validate = import \"_validation.libsonnet\";\n");
+        if !find_core_type_fields(schema).is_empty() {
+            content.push_str(&format!(
+                "local k8s = import \"{}\";\n",
+                core_types::CORE_TYPES_FILE
+            ));
+        }
+        let chunked = self.field_chunks(schema)?.is_some();
+        if chunked {
+            content.push_str(&format!(
+                "local fields = import \"{}/{}\";\n",
+                schema.name.to_lowercase(),
+                SETTERS_INDEX_FILE
+            ));
+        }
+        content.push('\n');
 
         // Generate the main resource function
         content.push_str(&self.generate_resource_function(schema)?);
@@ -187,12 +573,21 @@ impl JsonnetGenerator {
         );
         content.push_str("\n\n");
 
-        // Generate field-specific functions
-        content.push_str(&self.generate_field_functions(schema)?);
-        content.push_str("\n\n");
+        // Generate field-specific functions, unless split into files of their own
+        if !chunked {
+            content.push_str(&self.generate_field_functions(schema)?);
+            content.push_str("\n\n");
+        }
+
+        // List the fields with unresolved types, for the helper object
+        let unresolved = find_unresolved_types(schema);
+        if !unresolved.is_empty() {
+            content.push_str(&self.generate_unresolved_helper(&unresolved)?);
+            content.push('\n');
+        }
 
         // Generate helper functions
-        content.push_str(&self.generate_helper_functions(schema)?);
+        content.push_str(&self.generate_helper_functions(schema, chunked)?);
 
         Ok(content)
     }
@@ -205,6 +600,7 @@ impl JsonnetGenerator {
         let kind = schema.kind();
 
         content.push_str(&format!("// Create a new {kind} resource\n"));
+        content.push_str(&self.description_comment(&schema.schema, ""));
         content.push_str(&format!(
             "function({}) {{\n",
             self.generate_function_params(schema)
@@ -333,9 +729,14 @@ impl JsonnetGenerator {
         let mut content = String::new();
 
         if let Some(properties) = schema.properties() {
+            let setters = self.setter_names(properties)?;
             for (field_name, field_schema) in properties {
                 if let Some(field_name_str) = field_name.as_str() {
-                    content.push_str(&self.generate_field_function(field_name_str, field_schema)?);
+                    content.push_str(&self.generate_field_function(
+                        field_name_str,
+                        &setters[field_name_str],
+                        field_schema,
+                    )?);
                     content.push_str("\n\n");
                 }
             }
@@ -345,25 +746,18 @@ impl JsonnetGenerator {
     }
 
     /// Generate a field-specific function
+    ///
+    /// The setter is named `function_name`; the field keeps its wire name.
     fn generate_field_function(
         &self,
         field_name: &str,
-        _field_schema: &serde_yaml::Value,
+        function_name: &str,
+        field_schema: &serde_yaml::Value,
     ) -> Result<String> {
         let mut content = String::new();
 
-        let function_name = format!(
-            "with{}",
-            field_name
-                .chars()
-                .next()
-                .unwrap()
-                .to_uppercase()
-                .chain(field_name.chars().skip(1))
-                .collect::<String>()
-        );
-
         content.push_str(&format!("// Set the {field_name} field\n"));
+        content.push_str(&self.description_comment(field_schema, ""));
         content.push_str(&format!("function({function_name}) {{\n"));
         content.push_str("  spec +: {\n");
         content.push_str(&format!("    {field_name}: {field_name},\n"));
@@ -373,32 +767,175 @@ impl JsonnetGenerator {
         Ok(content)
     }
 
+    /// The spec fields of a schema with their setter names, in chunks of at
+    /// most `output.max_functions_per_file`
+    ///
+    /// `None` unless the schema has more fields than fit into one file.
+    fn field_chunks<'a>(&self, schema: &'a CrdSchema) -> Result<Option<Vec<Vec<Setter<'a>>>>> {
+        let (Some(max), Some(properties)) = (
+            self.output_config.max_functions_per_file,
+            schema.properties(),
+        ) else {
+            return Ok(None);
+        };
+        if properties.len() <= max {
+            return Ok(None);
+        }
+
+        let mut setters = self.setter_names(properties)?;
+        let fields: Vec<_> = properties
+            .iter()
+            .filter_map(|(name, field_schema)| {
+                let name = name.as_str()?;
+                Some((name, setters.remove(name)?, field_schema))
+            })
+            .collect();
+        Ok(Some(fields.chunks(max).map(<[_]>::to_vec).collect()))
+    }
+
+    /// Generate one file of a schema's split setters, an object of setters
+    fn generate_field_chunk(
+        &self,
+        schema: &CrdSchema,
+        part: usize,
+        parts: usize,
+        fields: &[Setter<'_>],
+    ) -> String {
+        let mut content = format!(
+            "// Setters of {}, part {} of {}\n{{\n",
+            schema.name,
+            part + 1,
+            parts
+        );
+        for (field_name, function_name, field_schema) in fields {
+            content.push_str(&format!("  // Set the {field_name} field\n"));
+            content.push_str(&self.description_comment(field_schema, "  "));
+            content.push_str(&format!(
+                "  {function_name}: function({function_name}) {{\n"
+            ));
+            content.push_str("    spec +: {\n");
+            content.push_str(&format!("      {field_name}: {field_name},\n"));
+            content.push_str("    },\n");
+            content.push_str("  },\n");
+        }
+        content.push_str("}\n");
+        content
+    }
+
+    /// Settings deciding the comments, setter names, field checks and line
+    /// endings of schema files, part of their digests
+    fn content_settings(&self) -> String {
+        let mut settings = String::new();
+        if self.output_config.newline != Newline::default() {
+            settings.push_str(&format!("newline:{:?}", self.output_config.newline));
+        }
+        if self.output_config.header.descriptions {
+            settings.push_str(&format!(
+                "descriptions:{}",
+                self.output_config.locale.as_deref().unwrap_or_default()
+            ));
+        }
+        if self.output_config.field_naming != FieldNaming::Preserve {
+            settings.push_str(&format!("naming:{:?}", self.output_config.field_naming));
+        }
+        if let Some(max) = self.output_config.max_functions_per_file {
+            settings.push_str(&format!("chunks:{max}"));
+        }
+        if !self.output_config.style.is_default() {
+            settings.push_str(&format!("style:{:?}", self.output_config.style));
+        }
+        // Native checks have different code, and so different hashed names
+        if self.validation_generator.native_functions() {
+            settings.push_str("natives");
+        }
+        settings
+    }
+
+    /// Setter names of a schema's spec fields
+    fn setter_names<'a>(
+        &self,
+        properties: &'a serde_yaml::Mapping,
+    ) -> Result<BTreeMap<&'a str, String>> {
+        self.output_config
+            .field_naming
+            .setter_names(properties.keys().filter_map(|name| name.as_str()))
+    }
+
+    /// Description of a schema as comment lines, when `header.descriptions` is set
+    fn description_comment(&self, schema: &serde_yaml::Value, indent: &str) -> String {
+        if !self.output_config.header.descriptions {
+            return String::new();
+        }
+        header::description(schema, self.output_config.locale.as_deref())
+            .map(|description| header::comment_lines(description, indent))
+            .unwrap_or_default()
+    }
+
     /// Generate helper functions
-    fn generate_helper_functions(&self, schema: &CrdSchema) -> Result<String> {
+    ///
+    /// Split setters come from the `fields` import rather than being listed.
+    fn generate_helper_functions(&self, schema: &CrdSchema, chunked: bool) -> Result<String> {
         let mut content = String::new();
 
         // Generate factory functions for common patterns
         content.push_str("// Helper functions\n");
-        content.push_str(&format!("local {} = {{\n", schema.name.to_lowercase()));
+        let merged = if chunked { "fields + " } else { "" };
+        content.push_str(&format!(
+            "local {} = {merged}{{\n",
+            schema.name.to_lowercase()
+        ));
         content.push_str(&format!("  new: {},\n", schema.name.to_lowercase()));
 
         // Add common field setters
         if let Some(properties) = schema.properties() {
-            for (field_name, _field_schema) in properties {
-                if let Some(field_name_str) = field_name.as_str() {
-                    let setter_name = format!(
-                        "with{}",
-                        field_name_str
-                            .chars()
-                            .next()
-                            .unwrap()
-                            .to_uppercase()
-                            .chain(field_name_str.chars().skip(1))
-                            .collect::<String>()
-                    );
+            if !chunked {
+                let setters = self.setter_names(properties)?;
+                for field_name in properties.keys().filter_map(|name| name.as_str()) {
+                    let setter_name = &setters[field_name];
                     content.push_str(&format!("  {setter_name}: {setter_name},\n"));
                 }
             }
+
+            // Upserts of single entries of list-map fields
+            for list_map in
+                list_maps::find_list_map_fields(properties, self.output_config.field_naming)
+            {
+                content.push_str(&list_maps::generate_upsert_helper(&list_map));
+            }
+        }
+
+        // Helpers for fields holding builtin core types, by field path
+        let core_type_fields = find_core_type_fields(schema);
+        if !core_type_fields.is_empty() {
+            content.push_str("  coreTypes: {\n");
+            for field in core_type_fields {
+                content.push_str(&format!(
+                    "    \"{}\": k8s.{},\n",
+                    field.field_path, field.type_name
+                ));
+            }
+            content.push_str("  },\n");
+        }
+
+        // Fields whose values are passed through unchecked, with the reason
+        if !find_unresolved_types(schema).is_empty() {
+            content.push_str("  unresolved: __unresolved,\n");
+        }
+
+        // Converting between versions is only a matter of apiVersion when the
+        // API server does not use a conversion webhook
+        if schema.conversion.is_webhook() {
+            content.push_str(&format!(
+                "  convertTo: function(resource, apiVersion) error {},\n",
+                serde_json::to_string(&format!(
+                    "{} uses webhook conversion; convert through the API server",
+                    schema.kind()
+                ))?
+            ));
+        } else {
+            content.push_str(
+                "  convertTo: function(resource, apiVersion) resource + { apiVersion: apiVersion },\n",
+            );
         }
 
         content.push_str("};\n");
@@ -406,6 +943,26 @@ impl JsonnetGenerator {
         Ok(content)
     }
 
+    /// Generate the `__unresolved` helper listing fields whose types could not be resolved
+    fn generate_unresolved_helper(&self, unresolved: &[UnresolvedType]) -> Result<String> {
+        let mut content = String::new();
+
+        content.push_str(
+            "// Fields whose schema type could not be resolved; values are passed through unchecked\n",
+        );
+        content.push_str("local __unresolved = {\n");
+        for entry in unresolved {
+            content.push_str(&format!(
+                "  {}: {},\n",
+                serde_json::to_string(&entry.field_path)?,
+                serde_json::to_string(&entry.reason)?
+            ));
+        }
+        content.push_str("};\n");
+
+        Ok(content)
+    }
+
     /// Generate version index file
     async fn generate_version_index(
         &self,
@@ -426,7 +983,7 @@ impl JsonnetGenerator {
 
         content.push_str("}\n");
 
-        std::fs::write(index_path, content)?;
+        write_generated(index_path, &content, self.output_config.newline)?;
         Ok(())
     }
 
@@ -442,19 +999,11 @@ impl JsonnetGenerator {
         content.push_str("// Main index file\n");
         content.push_str("{\n");
 
-        for api_version in grouped_schemas.keys() {
-            let version_path = match self.output_config.organization {
-                crate::config::OrganizationStrategy::ApiVersion => api_version.replace('/', "_"),
-                crate::config::OrganizationStrategy::Flat => ".".to_string(),
-                crate::config::OrganizationStrategy::Hierarchical => {
-                    let parts: Vec<&str> = api_version.split('/').collect();
-                    if parts.len() == 2 {
-                        format!("{}/{}", parts[0], parts[1])
-                    } else {
-                        api_version.clone()
-                    }
-                }
-            };
+        // Sorted, so regenerating an unchanged source reproduces the file
+        let mut api_versions: Vec<&String> = grouped_schemas.keys().collect();
+        api_versions.sort();
+        for api_version in api_versions {
+            let version_path = self.version_dir(api_version);
 
             content.push_str(&format!(
                 "  {}: import \"./{}/_index.libsonnet\",\n",
@@ -465,7 +1014,191 @@ impl JsonnetGenerator {
 
         content.push_str("}\n");
 
-        std::fs::write(index_path, content)?;
+        write_generated(&index_path, &content, self.output_config.newline)?;
+        Ok(())
+    }
+
+    /// Path of a schema's library relative to the output path,
+    /// e.g. `example.com_v1/widgets.example.com.libsonnet`
+    pub fn library_path(&self, schema: &CrdSchema) -> String {
+        format!(
+            "{}/{}.libsonnet",
+            self.version_dir(&schema.api_version),
+            schema.name.to_lowercase()
+        )
+        .trim_start_matches("./")
+        .to_string()
+    }
+
+    /// Directory of an API version's library, relative to the output path
+    fn version_dir(&self, api_version: &str) -> String {
+        match self.output_config.organization {
+            crate::config::OrganizationStrategy::ApiVersion => api_version.replace('/', "_"),
+            crate::config::OrganizationStrategy::Flat => ".".to_string(),
+            crate::config::OrganizationStrategy::Hierarchical => {
+                let parts: Vec<&str> = api_version.split('/').collect();
+                if parts.len() == 2 {
+                    format!("{}/{}", parts[0], parts[1])
+                } else {
+                    api_version.to_string()
+                }
+            }
+        }
+    }
+
+    /// Generate the self-test file
+    ///
+    /// Every resource is instantiated with its minimal required fields and the
+    /// evaluation asserts the result carries the expected apiVersion and kind.
+    fn generate_selftest_file(
+        &self,
+        grouped_schemas: &HashMap<String, Vec<&CrdSchema>>,
+        output_path: &Path,
+    ) -> Result<()> {
+        let schemas = sorted_by_name(grouped_schemas);
+
+        let mut content = String::new();
+        content.push_str("// Generated self-tests\n");
+        content.push_str("// Instantiates every resource with its minimal required fields.\n");
+        content.push_str("{\n");
+
+        for (api_version, schema) in schemas {
+            content.push_str(&format!("  \"{}\": (\n", schema.name));
+            content.push_str(&format!(
+                "    local resource = {};\n",
+                self.minimal_instance(api_version, schema, "selftest")?
+            ));
+            content.push_str(&format!(
+                "    assert resource.apiVersion == \"{}\" : \"{}: unexpected apiVersion \" + resource.apiVersion;\n",
+                schema.api_version, schema.name
+            ));
+            content.push_str(&format!(
+                "    assert resource.kind == \"{}\" : \"{}: unexpected kind \" + resource.kind;\n",
+                schema.kind(),
+                schema.name
+            ));
+            content.push_str("    true\n");
+            content.push_str("  ),\n");
+        }
+
+        content.push_str("}\n");
+
+        write_generated(
+            &output_path.join(SELFTEST_FILE),
+            &content,
+            self.output_config.newline,
+        )?;
+        Ok(())
+    }
+
+    /// Generate `_samples.libsonnet`, instantiating every resource for server-side checks
+    fn generate_samples_file(
+        &self,
+        grouped_schemas: &HashMap<String, Vec<&CrdSchema>>,
+        output_path: &Path,
+    ) -> Result<()> {
+        let mut content = String::new();
+        content.push_str("// Generated sample resources\n");
+        content.push_str("// Every resource with its minimal required fields, as submitted by\n");
+        content.push_str("// `gensonnet test --cluster-dry-run`.\n");
+        content.push_str("{\n");
+        for (api_version, schema) in sorted_by_name(grouped_schemas) {
+            content.push_str(&format!(
+                "  \"{}\": {},\n",
+                schema.name,
+                self.minimal_instance(api_version, schema, "gensonnet-sample")?
+            ));
+        }
+        content.push_str("}\n");
+
+        write_generated(
+            &output_path.join(SAMPLES_FILE),
+            &content,
+            self.output_config.newline,
+        )?;
+        Ok(())
+    }
+
+    /// Expression instantiating a resource with its minimal required fields
+    fn minimal_instance(
+        &self,
+        api_version: &str,
+        schema: &CrdSchema,
+        name: &str,
+    ) -> Result<String> {
+        let import_path = format!(
+            "./{}/{}.libsonnet",
+            self.version_dir(api_version),
+            schema.name.to_lowercase()
+        );
+        let mut args = vec![format!("{{ name: \"{name}\" }}")];
+        if schema.is_object() && schema.properties().is_some() {
+            args.push(self.minimal_spec(schema)?);
+        }
+
+        Ok(format!("(import \"{}\")({})", import_path, args.join(", ")))
+    }
+
+    /// Spec object holding only the required fields of a schema
+    fn minimal_spec(&self, schema: &CrdSchema) -> Result<String> {
+        let required: Vec<&str> = schema
+            .schema
+            .get("required")
+            .and_then(|required| required.as_sequence())
+            .map(|required| required.iter().filter_map(|r| r.as_str()).collect())
+            .unwrap_or_default();
+
+        let mut fields = Vec::new();
+        for field_name in required {
+            let Some(field_schema) = schema.properties().and_then(|p| p.get(field_name)) else {
+                continue;
+            };
+            fields.push(format!(
+                "{field_name}: {}",
+                self.example_value(field_schema)?
+            ));
+        }
+
+        Ok(format!("{{ {} }}", fields.join(", ")))
+    }
+
+    /// Value given to a field in samples and examples: its default, else the
+    /// first allowed value, else an empty value of its type
+    fn example_value(&self, field_schema: &serde_yaml::Value) -> Result<String> {
+        match field_schema
+            .get("enum")
+            .and_then(|values| values.as_sequence())
+            .and_then(|values| values.first())
+        {
+            Some(first) if field_schema.get("default").is_none() => {
+                self.serialize_yaml_to_jsonnet(first)
+            }
+            _ => self.get_field_default_value(field_schema),
+        }
+    }
+
+    /// Generate one runnable example per resource under `examples/`
+    fn generate_examples(
+        &self,
+        grouped_schemas: &HashMap<String, Vec<&CrdSchema>>,
+        output_path: &Path,
+    ) -> Result<()> {
+        for (api_version, schema) in sorted_by_name(grouped_schemas) {
+            let library_path = format!(
+                "{}/{}.libsonnet",
+                self.version_dir(api_version),
+                schema.name.to_lowercase()
+            );
+            let content = examples::generate_example(schema, &library_path, |field| {
+                self.example_value(field)
+            })?;
+
+            let path = output_path.join(examples::example_path(schema));
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            write_generated(&path, &content, self.output_config.newline)?;
+        }
         Ok(())
     }
 
@@ -473,6 +1206,7 @@ impl JsonnetGenerator {
     async fn generate_metadata_file(
         &self,
         schemas: &[CrdSchema],
+        license: Option<&str>,
         output_path: &Path,
     ) -> Result<()> {
         let metadata_path = output_path.join("_meta.libsonnet");
@@ -480,14 +1214,19 @@ impl JsonnetGenerator {
 
         content.push_str("// Generation metadata\n");
         content.push_str("{\n");
-        content.push_str(&format!(
-            "  generated_at: \"{}\",\n",
-            chrono::Utc::now().to_rfc3339()
-        ));
+        if self.output_config.timestamps {
+            content.push_str(&format!(
+                "  generated_at: \"{}\",\n",
+                self.clock.now().to_rfc3339()
+            ));
+        }
         content.push_str(&format!(
             "  tool_version: \"{}\",\n",
             env!("CARGO_PKG_VERSION")
         ));
+        if let Some(license) = license {
+            content.push_str(&format!("  license: \"{license}\",\n"));
+        }
         content.push_str("  schemas: [\n");
 
         for schema in schemas {
@@ -498,20 +1237,174 @@ impl JsonnetGenerator {
                 "      source: \"{}\",\n",
                 schema.source_path.display()
             ));
+            content.push_str(&format!(
+                "      conversion: {{ strategy: \"{:?}\", convertible: {}",
+                schema.conversion.strategy,
+                !schema.conversion.is_webhook()
+            ));
+            if let Some(endpoint) = &schema.conversion.webhook_endpoint {
+                content.push_str(&format!(", webhook: {}", serde_json::to_string(endpoint)?));
+            }
+            content.push_str(" },\n");
+            content.push_str(&format!(
+                "      categories: {},\n",
+                serde_json::to_string(&schema.categories)?
+            ));
+            content.push_str(&format!(
+                "      printerColumns: {},\n",
+                serde_json::to_string(&schema.printer_columns)?
+            ));
             content.push_str("    },\n");
         }
 
         content.push_str("  ],\n");
         content.push_str("}\n");
 
-        std::fs::write(metadata_path, content)?;
+        write_generated(&metadata_path, &content, self.output_config.newline)?;
+        Ok(())
+    }
+
+    /// Write every schema with its kubectl metadata to `_schemas.json`
+    fn generate_schema_export(&self, schemas: &[CrdSchema], output_path: &Path) -> Result<()> {
+        let exported: Vec<serde_json::Value> = schemas
+            .iter()
+            .map(|schema| {
+                Ok(serde_json::json!({
+                    "name": schema.name,
+                    "apiVersion": schema.api_version,
+                    "kind": schema.kind,
+                    "categories": schema.categories,
+                    "printerColumns": schema.printer_columns,
+                    "schema": serde_json::to_value(&schema.schema)?,
+                }))
+            })
+            .collect::<Result<_>>()?;
+
+        write_generated(
+            &output_path.join(SCHEMA_EXPORT_FILE),
+            &serde_json::to_string_pretty(&exported)?,
+            self.output_config.newline,
+        )?;
         Ok(())
     }
 }
 
+/// Schemas of all versions with their API version, ordered by name
+fn sorted_by_name<'a>(
+    grouped_schemas: &'a HashMap<String, Vec<&'a CrdSchema>>,
+) -> Vec<(&'a String, &'a CrdSchema)> {
+    let mut schemas: Vec<(&String, &CrdSchema)> = grouped_schemas
+        .iter()
+        .flat_map(|(api_version, schemas)| schemas.iter().map(move |schema| (api_version, *schema)))
+        .collect();
+    schemas.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+    schemas
+}
+
+/// Digest of everything a schema file is generated from
+fn schema_digest(schema: &CrdSchema, header: &str, comments: &str) -> Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION"));
+    hasher.update(header);
+    hasher.update(comments);
+    hasher.update(serde_yaml::to_string(schema)?);
+    Ok(format!("sha256:{:x}", hasher.finalize()))
+}
+
+/// Find the fields of a schema holding builtin core types
+fn find_core_type_fields(schema: &CrdSchema) -> Vec<CoreTypeField> {
+    schema
+        .properties()
+        .map(core_types::find_core_type_fields)
+        .unwrap_or_default()
+}
+
+/// Find the fields of a schema whose types cannot be resolved
+fn find_unresolved_types(schema: &CrdSchema) -> Vec<UnresolvedType> {
+    let mut unresolved = Vec::new();
+    if let Some(properties) = schema.properties() {
+        collect_unresolved(schema.kind(), "", properties, &mut unresolved);
+    }
+    unresolved
+}
+
+/// Recursively collect unresolved fields below a properties mapping
+fn collect_unresolved(
+    kind: &str,
+    prefix: &str,
+    properties: &serde_yaml::Mapping,
+    unresolved: &mut Vec<UnresolvedType>,
+) {
+    for (field_name, field_schema) in properties {
+        let Some(field_name) = field_name.as_str() else {
+            continue;
+        };
+        let field_path = if prefix.is_empty() {
+            field_name.to_string()
+        } else {
+            format!("{prefix}.{field_name}")
+        };
+
+        if let Some(reason) = unresolved_reason(field_schema) {
+            unresolved.push(UnresolvedType {
+                kind: kind.to_string(),
+                field_path,
+                reason,
+            });
+            continue;
+        }
+
+        if let Some(nested) = field_schema.get("properties").and_then(|p| p.as_mapping()) {
+            collect_unresolved(kind, &field_path, nested, unresolved);
+        }
+        if let Some(nested) = field_schema
+            .get("items")
+            .and_then(|items| items.get("properties"))
+            .and_then(|p| p.as_mapping())
+        {
+            collect_unresolved(kind, &format!("{field_path}[]"), nested, unresolved);
+        }
+    }
+}
+
+/// Why a field schema's type cannot be resolved, if it cannot
+fn unresolved_reason(field_schema: &serde_yaml::Value) -> Option<String> {
+    if let Some(reference) = field_schema.get("$ref").and_then(|r| r.as_str()) {
+        if core_types::resolve_reference(reference).is_some() {
+            return None;
+        }
+        return Some(format!("unresolved reference {reference}"));
+    }
+
+    match field_schema.get("type").and_then(|t| t.as_str()) {
+        Some(field_type) if KNOWN_TYPES.contains(&field_type) => None,
+        Some(field_type) => Some(format!("unknown type '{field_type}'")),
+        None => {
+            let structural = [
+                "properties",
+                "items",
+                "additionalProperties",
+                "anyOf",
+                "oneOf",
+                "allOf",
+                "enum",
+                "x-kubernetes-preserve-unknown-fields",
+                "x-kubernetes-int-or-string",
+                "x-kubernetes-embedded-resource",
+            ];
+            if structural.iter().any(|key| field_schema.get(key).is_some()) {
+                None
+            } else {
+                Some("schema has no type information".to_string())
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::result::schema_cache_hit_rate;
 
     #[test]
     fn test_group_schemas_by_version() {
@@ -528,6 +1421,10 @@ mod tests {
                 source_path: PathBuf::from("test1.yaml"),
                 validation_rules: crate::crd::ValidationRules::default(),
                 schema_analysis: crate::crd::SchemaAnalysis::default(),
+                categories: Vec::new(),
+                printer_columns: Vec::new(),
+                conversion: crate::crd::ConversionInfo::default(),
+                field_lines: Default::default(),
             },
             CrdSchema {
                 name: "Test2".to_string(),
@@ -539,6 +1436,10 @@ mod tests {
                 source_path: PathBuf::from("test2.yaml"),
                 validation_rules: crate::crd::ValidationRules::default(),
                 schema_analysis: crate::crd::SchemaAnalysis::default(),
+                categories: Vec::new(),
+                printer_columns: Vec::new(),
+                conversion: crate::crd::ConversionInfo::default(),
+                field_lines: Default::default(),
             },
         ];
 
@@ -561,6 +1462,10 @@ mod tests {
             source_path: PathBuf::from("test.yaml"),
             validation_rules: crate::crd::ValidationRules::default(),
             schema_analysis: crate::crd::SchemaAnalysis::default(),
+            categories: Vec::new(),
+            printer_columns: Vec::new(),
+            conversion: crate::crd::ConversionInfo::default(),
+            field_lines: Default::default(),
         };
 
         let params = generator.generate_function_params(&schema);
@@ -592,4 +1497,434 @@ mod tests {
             "true"
         );
     }
+
+    #[tokio::test]
+    async fn test_selftest_file() {
+        let schema = CrdSchema {
+            name: "Widget".to_string(),
+            group: "test.example.com".to_string(),
+            version: "v1".to_string(),
+            api_version: "test.example.com/v1".to_string(),
+            kind: "Widget".to_string(),
+            schema: serde_yaml::from_str(
+                r#"
+type: object
+required: [size, mode]
+properties:
+  size:
+    type: integer
+  mode:
+    type: string
+    enum: [fast, slow]
+  label:
+    type: string
+"#,
+            )
+            .unwrap(),
+            source_path: PathBuf::from("widget.yaml"),
+            validation_rules: crate::crd::ValidationRules::default(),
+            schema_analysis: crate::crd::SchemaAnalysis::default(),
+            categories: Vec::new(),
+            printer_columns: Vec::new(),
+            conversion: crate::crd::ConversionInfo::default(),
+            field_lines: Default::default(),
+        };
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        JsonnetGenerator::new(OutputConfig::default())
+            .generate_crd_library(std::slice::from_ref(&schema), temp_dir.path())
+            .await
+            .unwrap();
+
+        let content = std::fs::read_to_string(temp_dir.path().join(SELFTEST_FILE)).unwrap();
+        assert!(content.contains(
+            "(import \"./test.example.com_v1/widget.libsonnet\")({ name: \"selftest\" }, { size: 0, mode: \"fast\" })"
+        ));
+        assert!(content.contains("assert resource.kind == \"Widget\""));
+
+        let samples = std::fs::read_to_string(temp_dir.path().join(SAMPLES_FILE)).unwrap();
+        assert!(samples.contains(
+            "  \"Widget\": (import \"./test.example.com_v1/widget.libsonnet\")({ name: \"gensonnet-sample\" }, { size: 0, mode: \"fast\" }),"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_types() {
+        let schema = CrdSchema {
+            name: "Widget".to_string(),
+            group: "test.example.com".to_string(),
+            version: "v1".to_string(),
+            api_version: "test.example.com/v1".to_string(),
+            kind: "Widget".to_string(),
+            schema: serde_yaml::from_str(
+                r##"
+type: object
+properties:
+  name:
+    type: string
+  opaque: {}
+  config:
+    type: object
+    properties:
+      mode:
+        type: quantum
+      ref:
+        $ref: "#/definitions/Ref"
+"##,
+            )
+            .unwrap(),
+            source_path: PathBuf::from("widget.yaml"),
+            validation_rules: crate::crd::ValidationRules::default(),
+            schema_analysis: crate::crd::SchemaAnalysis::default(),
+            categories: Vec::new(),
+            printer_columns: Vec::new(),
+            conversion: crate::crd::ConversionInfo::default(),
+            field_lines: Default::default(),
+        };
+
+        let unresolved = find_unresolved_types(&schema);
+        let paths: Vec<_> = unresolved.iter().map(|u| u.field_path.as_str()).collect();
+        assert_eq!(paths, vec!["opaque", "config.mode", "config.ref"]);
+
+        let content = JsonnetGenerator::new(OutputConfig::default())
+            .generate_schema_content(&schema)
+            .unwrap();
+        assert!(content.contains("local __unresolved = {"));
+        assert!(content.contains("\"config.mode\": \"unknown type 'quantum'\""));
+        assert!(content.contains("  unresolved: __unresolved,\n"));
+        assert!(
+            content.find("local __unresolved").unwrap() < content.find("local widget").unwrap()
+        );
+
+        // Field names are escaped as Jsonnet strings
+        let mut quoted = schema.clone();
+        quoted.schema = serde_yaml::from_str("type: object\nproperties:\n  'a\"b': {}\n").unwrap();
+        let content = JsonnetGenerator::new(OutputConfig::default())
+            .generate_schema_content(&quoted)
+            .unwrap();
+        assert!(
+            content.contains("  \"a\\\"b\": \"schema has no type information\",\n"),
+            "{content}"
+        );
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let result = JsonnetGenerator::new(OutputConfig::default())
+            .with_fail_on_unresolved(true)
+            .generate_crd_library(std::slice::from_ref(&schema), temp_dir.path())
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_webhook_conversion() {
+        let mut schema = CrdSchema {
+            name: "Widget".to_string(),
+            group: "test.example.com".to_string(),
+            version: "v2".to_string(),
+            api_version: "test.example.com/v2".to_string(),
+            kind: "Widget".to_string(),
+            schema: serde_yaml::from_str("type: object").unwrap(),
+            source_path: PathBuf::from("widget.yaml"),
+            validation_rules: crate::crd::ValidationRules::default(),
+            schema_analysis: crate::crd::SchemaAnalysis::default(),
+            categories: Vec::new(),
+            printer_columns: Vec::new(),
+            conversion: crate::crd::ConversionInfo::default(),
+            field_lines: Default::default(),
+        };
+        let generator = JsonnetGenerator::new(OutputConfig::default());
+
+        let content = generator.generate_schema_content(&schema).unwrap();
+        assert!(content.contains("convertTo: function(resource, apiVersion) resource + {"));
+
+        schema.conversion = crate::crd::ConversionInfo {
+            strategy: crate::crd::ConversionStrategy::Webhook,
+            webhook_endpoint: Some("service system/webhook:443/convert".to_string()),
+            conversion_review_versions: vec!["v1".to_string()],
+        };
+        let content = generator.generate_schema_content(&schema).unwrap();
+        assert!(content.contains("// Conversion: Webhook (service system/webhook:443/convert)"));
+        assert!(content.contains(
+            "convertTo: function(resource, apiVersion) error \"Widget uses webhook conversion; convert through the API server\""
+        ));
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        generator
+            .generate_crd_library(std::slice::from_ref(&schema), temp_dir.path())
+            .await
+            .unwrap();
+        let meta = std::fs::read_to_string(temp_dir.path().join("_meta.libsonnet")).unwrap();
+        assert!(meta.contains(
+            "conversion: { strategy: \"Webhook\", convertible: false, webhook: \"service system/webhook:443/convert\" },"
+        ));
+
+        // Endpoints are escaped wherever they end up
+        schema.conversion.webhook_endpoint = Some("https://x/\"\nlocal y = 1;".to_string());
+        let content = generator.generate_schema_content(&schema).unwrap();
+        assert!(content.contains("// Conversion: Webhook (https://x/\\\"\\nlocal y = 1;)\n"));
+        generator
+            .generate_crd_library(std::slice::from_ref(&schema), temp_dir.path())
+            .await
+            .unwrap();
+        let meta = std::fs::read_to_string(temp_dir.path().join("_meta.libsonnet")).unwrap();
+        assert!(meta.contains("webhook: \"https://x/\\\"\\nlocal y = 1;\" },"));
+    }
+
+    #[tokio::test]
+    async fn test_printer_columns_in_metadata() {
+        let schema = CrdSchema {
+            name: "Widget".to_string(),
+            group: "test.example.com".to_string(),
+            version: "v1".to_string(),
+            api_version: "test.example.com/v1".to_string(),
+            kind: "Widget".to_string(),
+            schema: serde_yaml::from_str("type: object").unwrap(),
+            source_path: PathBuf::from("widget.yaml"),
+            validation_rules: crate::crd::ValidationRules::default(),
+            schema_analysis: crate::crd::SchemaAnalysis::default(),
+            categories: vec!["gadgets".to_string()],
+            printer_columns: vec![crate::crd::PrinterColumn {
+                name: "Ready".to_string(),
+                column_type: "string".to_string(),
+                json_path: ".status.ready".to_string(),
+                description: None,
+                priority: 0,
+                format: None,
+            }],
+            conversion: crate::crd::ConversionInfo::default(),
+            field_lines: Default::default(),
+        };
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        JsonnetGenerator::new(OutputConfig::default())
+            .generate_crd_library(std::slice::from_ref(&schema), temp_dir.path())
+            .await
+            .unwrap();
+
+        let meta = std::fs::read_to_string(temp_dir.path().join("_meta.libsonnet")).unwrap();
+        assert!(meta.contains("      categories: [\"gadgets\"],\n"));
+        assert!(meta.contains(
+            "      printerColumns: [{\"name\":\"Ready\",\"type\":\"string\",\"jsonPath\":\".status.ready\",\"priority\":0}],\n"
+        ));
+
+        let export: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(temp_dir.path().join(SCHEMA_EXPORT_FILE)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(export[0]["kind"], "Widget");
+        assert_eq!(export[0]["printerColumns"][0]["jsonPath"], ".status.ready");
+        assert_eq!(export[0]["schema"]["type"], "object");
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_schemas_are_reused() {
+        let widget = CrdSchema {
+            name: "Widget".to_string(),
+            group: "test.example.com".to_string(),
+            version: "v1".to_string(),
+            api_version: "test.example.com/v1".to_string(),
+            kind: "Widget".to_string(),
+            schema: serde_yaml::from_str("type: object").unwrap(),
+            source_path: PathBuf::from("widget.yaml"),
+            validation_rules: crate::crd::ValidationRules::default(),
+            schema_analysis: crate::crd::SchemaAnalysis::default(),
+            categories: Vec::new(),
+            printer_columns: Vec::new(),
+            conversion: crate::crd::ConversionInfo::default(),
+            field_lines: Default::default(),
+        };
+        let mut gadget = widget.clone();
+        gadget.name = "Gadget".to_string();
+        gadget.kind = "Gadget".to_string();
+
+        let generator = JsonnetGenerator::new(OutputConfig::default());
+        let published = tempfile::TempDir::new().unwrap();
+        let first = generator
+            .generate_crd_library(&[widget.clone(), gadget.clone()], published.path())
+            .await
+            .unwrap();
+        assert_eq!(first.schemas_reused, 0);
+        assert_eq!(first.schema_digests.len(), 2);
+
+        // Only the changed schema is regenerated
+        gadget.schema = serde_yaml::from_str("type: object\nrequired: [size]").unwrap();
+        let cache = SchemaCache {
+            digests: first.schema_digests.clone(),
+            published_path: published.path().to_path_buf(),
+            header: String::new(),
+            license: None,
+        };
+        let staging = tempfile::TempDir::new().unwrap();
+        let second = generator
+            .generate_crd_library_cached(&[widget.clone(), gadget.clone()], staging.path(), &cache)
+            .await
+            .unwrap();
+        assert_eq!(second.schemas_reused, 1);
+        assert!(!staging
+            .path()
+            .join("test.example.com_v1/widget.libsonnet")
+            .exists());
+        assert!(staging
+            .path()
+            .join("test.example.com_v1/gadget.libsonnet")
+            .exists());
+        assert_eq!(schema_cache_hit_rate(&[second]), 0.5);
+
+        // A new header changes every published file
+        let cache = SchemaCache {
+            header: "// Copyright\n".to_string(),
+            ..cache
+        };
+        let staging = tempfile::TempDir::new().unwrap();
+        let third = generator
+            .generate_crd_library_cached(&[widget.clone(), gadget.clone()], staging.path(), &cache)
+            .await
+            .unwrap();
+        assert_eq!(third.schemas_reused, 0);
+
+        // Native functions rename the field checks schema files reference
+        let cache = SchemaCache {
+            header: String::new(),
+            ..cache
+        };
+        let staging = tempfile::TempDir::new().unwrap();
+        let native = generator
+            .with_native_functions(true)
+            .generate_crd_library_cached(&[widget.clone(), gadget.clone()], staging.path(), &cache)
+            .await
+            .unwrap();
+        assert_eq!(native.schemas_reused, 0);
+
+        // Other line endings change every schema file
+        let crlf = JsonnetGenerator::new(OutputConfig {
+            newline: Newline::Crlf,
+            ..OutputConfig::default()
+        });
+        let staging = tempfile::TempDir::new().unwrap();
+        let crlf = crlf
+            .generate_crd_library_cached(&[widget, gadget], staging.path(), &cache)
+            .await
+            .unwrap();
+        assert_eq!(crlf.schemas_reused, 0);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_without_timestamps() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        JsonnetGenerator::new(OutputConfig::default())
+            .generate_crd_library(&[], temp_dir.path())
+            .await
+            .unwrap();
+        let meta = std::fs::read_to_string(temp_dir.path().join("_meta.libsonnet")).unwrap();
+        assert!(meta.contains("generated_at"));
+
+        JsonnetGenerator::new(OutputConfig {
+            timestamps: false,
+            ..Default::default()
+        })
+        .generate_crd_library(&[], temp_dir.path())
+        .await
+        .unwrap();
+        let meta = std::fs::read_to_string(temp_dir.path().join("_meta.libsonnet")).unwrap();
+        assert!(!meta.contains("generated_at"));
+    }
+
+    #[test]
+    fn test_description_comments() {
+        let schema = CrdSchema {
+            name: "Widget".to_string(),
+            group: "test.example.com".to_string(),
+            version: "v1".to_string(),
+            api_version: "test.example.com/v1".to_string(),
+            kind: "Widget".to_string(),
+            schema: serde_yaml::from_str(
+                "type: object\ndescription: A widget\nproperties:\n  size:\n    type: string\n    description: Size of the widget\n    x-descriptions:\n      de: Größe des Widgets\n",
+            )
+            .unwrap(),
+            source_path: PathBuf::from("widget.yaml"),
+            validation_rules: crate::crd::ValidationRules::default(),
+            schema_analysis: crate::crd::SchemaAnalysis::default(),
+            categories: Vec::new(),
+            printer_columns: Vec::new(),
+            conversion: crate::crd::ConversionInfo::default(),
+            field_lines: Default::default(),
+        };
+        let content = JsonnetGenerator::new(OutputConfig::default())
+            .generate_schema_content(&schema)
+            .unwrap();
+        assert!(!content.contains("Size of the widget"));
+
+        let mut output_config = OutputConfig::default();
+        output_config.header.descriptions = true;
+        let content = JsonnetGenerator::new(output_config.clone())
+            .generate_schema_content(&schema)
+            .unwrap();
+        assert!(content.contains("// Create a new Widget resource\n// A widget\n"));
+        assert!(content.contains("// Set the size field\n// Size of the widget\n"));
+
+        output_config.locale = Some("de".to_string());
+        let content = JsonnetGenerator::new(output_config)
+            .generate_schema_content(&schema)
+            .unwrap();
+        assert!(content.contains("// Set the size field\n// Größe des Widgets\n"));
+    }
+
+    #[tokio::test]
+    async fn test_split_setters() {
+        let schema = CrdSchema {
+            name: "Widget".to_string(),
+            group: "test.example.com".to_string(),
+            version: "v1".to_string(),
+            api_version: "test.example.com/v1".to_string(),
+            kind: "Widget".to_string(),
+            schema: serde_yaml::from_str(
+                "type: object\nproperties:\n  a: {type: string}\n  b: {type: string}\n  c: {type: string}\n  d: {type: string}\n  e: {type: string}\n",
+            )
+            .unwrap(),
+            source_path: PathBuf::from("widget.yaml"),
+            validation_rules: crate::crd::ValidationRules::default(),
+            schema_analysis: crate::crd::SchemaAnalysis::default(),
+            categories: Vec::new(),
+            printer_columns: Vec::new(),
+            conversion: crate::crd::ConversionInfo::default(),
+            field_lines: Default::default(),
+        };
+        let generator = JsonnetGenerator::new(OutputConfig {
+            max_functions_per_file: Some(2),
+            ..Default::default()
+        });
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let result = generator
+            .generate_crd_library(std::slice::from_ref(&schema), temp_dir.path())
+            .await
+            .unwrap();
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+        let version_dir = temp_dir.path().join("test.example.com_v1");
+        let content = std::fs::read_to_string(version_dir.join("widget.libsonnet")).unwrap();
+        assert!(content.contains("local fields = import \"widget/_index.libsonnet\";\n"));
+        assert!(content.contains("local widget = fields + {\n  new: widget,\n  convertTo:"));
+        assert!(!content.contains("// Set the a field"));
+
+        let dir = version_dir.join("widget");
+        assert_eq!(
+            std::fs::read_to_string(dir.join(SETTERS_INDEX_FILE)).unwrap(),
+            "// Setters of Widget, split into 3 files\n(import \"fields_1.libsonnet\")\n+ (import \"fields_2.libsonnet\")\n+ (import \"fields_3.libsonnet\")\n"
+        );
+        let part = std::fs::read_to_string(dir.join("fields_3.libsonnet")).unwrap();
+        assert_eq!(
+            part,
+            "// Setters of Widget, part 3 of 3\n{\n  // Set the e field\n  withE: function(withE) {\n    spec +: {\n      e: e,\n    },\n  },\n}\n"
+        );
+
+        // Schemas with few enough fields stay in one file
+        let content = JsonnetGenerator::new(OutputConfig {
+            max_functions_per_file: Some(5),
+            ..Default::default()
+        })
+        .generate_schema_content(&schema)
+        .unwrap();
+        assert!(content.contains("// Set the e field\n"));
+        assert!(!content.contains("local fields"));
+    }
 }
@@ -0,0 +1,202 @@
+//! Configurable headers of generated files
+
+use crate::config::OutputConfig;
+use chrono::{DateTime, Utc};
+use std::path::Path;
+
+/// Schema extension holding descriptions by locale
+pub const DESCRIPTIONS_EXTENSION: &str = "x-descriptions";
+
+/// Values available to header templates
+#[derive(Debug, Clone, Default)]
+pub struct HeaderValues {
+    /// Name of the source
+    pub source: String,
+
+    /// Repository or artifact URL of the source
+    pub source_url: String,
+
+    /// Git reference of the source
+    pub git_ref: String,
+
+    /// Commit SHA or artifact digest the files were generated from
+    pub commit: String,
+}
+
+/// Render a header template as Jsonnet comment lines
+///
+/// Supports `{source}`, `{source_url}`, `{ref}`, `{commit}`, `{date}` and
+/// `{tool_version}`. Lines using `{date}` are left out without a generation
+/// time, so regenerating unchanged sources produces identical files.
+pub fn render_header(
+    template: &str,
+    values: &HeaderValues,
+    generated_at: Option<DateTime<Utc>>,
+) -> String {
+    let date = generated_at.map(|generated_at| generated_at.format("%Y-%m-%d").to_string());
+    let mut header = String::new();
+
+    for line in template.lines() {
+        if line.contains("{date}") && date.is_none() {
+            continue;
+        }
+
+        let line = line
+            .replace("{source}", &values.source)
+            .replace("{source_url}", &values.source_url)
+            .replace("{ref}", &values.git_ref)
+            .replace("{commit}", &values.commit)
+            .replace("{date}", date.as_deref().unwrap_or_default())
+            .replace("{tool_version}", env!("CARGO_PKG_VERSION"));
+        let line = line.trim_end();
+
+        if line.starts_with("//") {
+            header.push_str(line);
+        } else if line.is_empty() {
+            header.push_str("//");
+        } else {
+            header.push_str("// ");
+            header.push_str(line);
+        }
+        header.push('\n');
+    }
+
+    header
+}
+
+/// Header template of a source's files, if any
+///
+/// The source's preamble, or else `header.preamble`, comes first, followed by
+/// the header template of `output.locale`, or else `header_template`.
+/// Logical sources named `<source>/<entry>` fall back to the preamble of
+/// their source.
+pub fn source_template(config: &OutputConfig, source: &str) -> Option<String> {
+    let preambles = &config.header.sources;
+    let preamble = preambles
+        .get(source)
+        .or_else(|| preambles.get(source.split('/').next()?))
+        .or(config.header.preamble.as_ref());
+    let template = config
+        .locale
+        .as_deref()
+        .and_then(|locale| localized(locale, |key| config.header.locales.get(key)))
+        .or(config.header_template.as_ref());
+
+    match (preamble, template) {
+        (None, None) => None,
+        (Some(text), None) | (None, Some(text)) => Some(text.clone()),
+        (Some(preamble), Some(template)) => {
+            Some(format!("{}\n{}", preamble.trim_end_matches('\n'), template))
+        }
+    }
+}
+
+/// Description of a schema in `locale`, from its `x-descriptions`, or else
+/// its `description`
+pub fn description<'a>(schema: &'a serde_yaml::Value, locale: Option<&str>) -> Option<&'a str> {
+    let translated = locale.and_then(|locale| {
+        let translations = schema.get(DESCRIPTIONS_EXTENSION)?.as_mapping()?;
+        localized(locale, |key| translations.get(key)?.as_str())
+    });
+    translated
+        .or_else(|| schema.get("description")?.as_str())
+        .filter(|text| !text.trim().is_empty())
+}
+
+/// Text as Jsonnet comment lines, each starting with `indent`
+pub fn comment_lines(text: &str, indent: &str) -> String {
+    text.trim()
+        .lines()
+        .map(|line| match line.trim_end() {
+            "" => format!("{indent}//\n"),
+            line => format!("{indent}// {line}\n"),
+        })
+        .collect()
+}
+
+/// Entry of a locale, falling back from a region such as `de-AT` to its language
+fn localized<'a, T: ?Sized>(locale: &str, entry: impl Fn(&str) -> Option<&'a T>) -> Option<&'a T> {
+    entry(locale).or_else(|| entry(locale.split(['-', '_']).next()?))
+}
+
+/// Whether a generated file is Jsonnet and can carry a comment header
+pub fn takes_header(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("libsonnet" | "jsonnet")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_header() {
+        let values = HeaderValues {
+            source: "cert-manager".to_string(),
+            source_url: "https://github.com/cert-manager/cert-manager.git".to_string(),
+            git_ref: "v1.14.0".to_string(),
+            commit: "0123abcd".to_string(),
+        };
+        let template = "SPDX-License-Identifier: Apache-2.0\n\nGenerated from {source_url}@{commit}\nGenerated on {date}\n";
+
+        assert_eq!(
+            render_header(template, &values, None),
+            "// SPDX-License-Identifier: Apache-2.0\n//\n// Generated from https://github.com/cert-manager/cert-manager.git@0123abcd\n"
+        );
+        let generated_at = DateTime::parse_from_rfc3339("2026-03-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert!(render_header(template, &values, Some(generated_at))
+            .contains("// Generated on 2026-03-01\n"));
+        assert!(takes_header(Path::new("v1/certificate.libsonnet")));
+        assert!(!takes_header(Path::new("_schemas.json")));
+    }
+
+    #[test]
+    fn test_localized_headers_and_descriptions() {
+        let mut config: OutputConfig = serde_yaml::from_str(
+            r#"
+base_path: ./generated
+organization: api_version
+header_template: "Generated from {source_url}"
+header:
+  preamble: "Internal use only"
+  sources:
+    catalog: "Owned by the platform team"
+  locales:
+    de: "Erzeugt aus {source_url}"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            source_template(&config, "widgets").as_deref(),
+            Some("Internal use only\nGenerated from {source_url}")
+        );
+        assert_eq!(
+            source_template(&config, "catalog/cert-manager").as_deref(),
+            Some("Owned by the platform team\nGenerated from {source_url}")
+        );
+        config.locale = Some("de-AT".to_string());
+        assert_eq!(
+            source_template(&config, "widgets").as_deref(),
+            Some("Internal use only\nErzeugt aus {source_url}")
+        );
+        config.header = Default::default();
+        config.header_template = None;
+        assert_eq!(source_template(&config, "widgets"), None);
+
+        let schema: serde_yaml::Value = serde_yaml::from_str(
+            "description: Size of the widget\nx-descriptions:\n  de: Größe des Widgets\n",
+        )
+        .unwrap();
+        assert_eq!(description(&schema, None), Some("Size of the widget"));
+        assert_eq!(description(&schema, Some("de")), Some("Größe des Widgets"));
+        assert_eq!(description(&schema, Some("fr")), Some("Size of the widget"));
+        assert_eq!(
+            comment_lines("Size of the widget.\n\nIn pixels.\n", "  "),
+            "  // Size of the widget.\n  //\n  // In pixels.\n"
+        );
+    }
+}
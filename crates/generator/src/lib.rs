@@ -1,10 +1,32 @@
 //! Jsonnet code generation from schema sources
 
+pub mod arguments;
+pub mod bundle;
+pub mod clock;
+pub mod compat;
 pub mod config;
+pub mod core_types;
 pub mod crd;
+pub mod examples;
+pub mod fields;
 pub mod generator;
+pub mod header;
+pub mod list_maps;
+pub mod naming;
+pub mod presets;
 pub mod result;
+pub mod single_file;
+pub mod style;
 pub mod validation;
+pub mod version;
+pub mod warning;
+pub mod write;
 
-pub use generator::JsonnetGenerator;
-pub use result::{GenerationResult, SourceResult};
+pub use generator::{
+    JsonnetGenerator, SchemaCache, SAMPLES_FILE, SCHEMA_EXPORT_FILE, SELFTEST_FILE,
+};
+pub use result::{
+    GenerationResult, GenerationStatistics, PluginMetrics, SourceResult, SourceStatistics,
+    UnresolvedType,
+};
+pub use warning::{Warning, WarningCategory};
@@ -0,0 +1,220 @@
+//! Keyed helpers for list-map fields
+//!
+//! Arrays declared with `x-kubernetes-list-type: map` are merged by the API
+//! server per entry, matching entries on their `x-kubernetes-list-map-keys`.
+//! Besides the setter replacing the whole array, such fields get an upsert
+//! helper, e.g. `withContainer(name, obj)`, which replaces the entry with the
+//! same keys or appends one, so mixins compose the way server-side apply
+//! merges them.
+
+use crate::naming::FieldNaming;
+use serde_yaml::Mapping;
+
+/// A spec field holding a list-map
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListMapField {
+    /// Name of the field, e.g. `containers`
+    pub field: String,
+
+    /// Keys identifying an entry, e.g. `["name"]`
+    pub keys: Vec<String>,
+
+    /// How the helper is named
+    pub naming: FieldNaming,
+}
+
+impl ListMapField {
+    /// Name of the upsert helper, the setter of the field's singular
+    ///
+    /// `containers` gets `withContainer` and `policies` `withPolicy`; fields
+    /// that do not end in `s` get `with<Field>Entry`.
+    pub fn helper_name(&self) -> String {
+        let singular = if let Some(stem) = self.field.strip_suffix("ies") {
+            format!("{stem}y")
+        } else {
+            match self.field.strip_suffix('s') {
+                Some(stem) if !stem.is_empty() && !stem.ends_with('s') => stem.to_string(),
+                _ => format!("{}Entry", self.field),
+            }
+        };
+        self.naming.setter_name(&singular)
+    }
+}
+
+/// The list-map fields among a schema's spec properties, in property order
+///
+/// Fields whose keys are not valid Jsonnet parameter names, or whose helper
+/// would shadow the setter of another property, are skipped.
+pub fn find_list_map_fields(properties: &Mapping, naming: FieldNaming) -> Vec<ListMapField> {
+    let setters: Vec<String> = properties
+        .keys()
+        .filter_map(|name| name.as_str().map(|name| naming.setter_name(name)))
+        .collect();
+    properties
+        .iter()
+        .filter_map(|(name, field_schema)| {
+            let field = name.as_str()?;
+            if field_schema.get("x-kubernetes-list-type")?.as_str()? != "map" {
+                return None;
+            }
+            let keys: Vec<String> = field_schema
+                .get("x-kubernetes-list-map-keys")?
+                .as_sequence()?
+                .iter()
+                .map(|key| key.as_str().map(str::to_string))
+                .collect::<Option<_>>()?;
+            if keys.is_empty() || !keys.iter().all(|key| is_parameter_name(key)) {
+                return None;
+            }
+            Some(ListMapField {
+                field: field.to_string(),
+                keys,
+                naming,
+            })
+        })
+        .filter(|list_map| !setters.contains(&list_map.helper_name()))
+        .collect()
+}
+
+/// Generate the upsert helper of a list-map field, as a member of the helper object
+///
+/// The keys are set on `obj`, so callers need not repeat them.
+pub fn generate_upsert_helper(list_map: &ListMapField) -> String {
+    let field = &list_map.field;
+    let params = list_map.keys.join(", ");
+    let matches = list_map
+        .keys
+        .iter()
+        .map(|key| format!("std.objectHas(entry, \"{key}\") && entry.{key} == {key}"))
+        .collect::<Vec<_>>()
+        .join(" && ");
+    let keyed = list_map
+        .keys
+        .iter()
+        .map(|key| format!("{key}: {key}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut content = String::new();
+    content.push_str(&format!(
+        "  // Replace the {field} entry with the same {} or append it\n",
+        list_map.keys.join(" and ")
+    ));
+    content.push_str(&format!(
+        "  {}({params}, obj): {{\n",
+        list_map.helper_name()
+    ));
+    content.push_str("    spec+: {\n");
+    content.push_str(&format!(
+        "      local existing = if \"{field}\" in super then super.{field} else [],\n"
+    ));
+    content.push_str(&format!("      local matches(entry) = {matches},\n"));
+    content.push_str(&format!("      local upserted = obj {{ {keyed} }},\n"));
+    content.push_str(&format!(
+        "      {field}: if std.length(std.filter(matches, existing)) > 0\n"
+    ));
+    content.push_str("        then [if matches(e) then upserted else e for e in existing]\n");
+    content.push_str("        else existing + [upserted],\n");
+    content.push_str("    },\n");
+    content.push_str("  },\n");
+    content
+}
+
+/// Whether a key can be used as a parameter name as is
+fn is_parameter_name(key: &str) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "assert",
+        "else",
+        "error",
+        "false",
+        "for",
+        "function",
+        "if",
+        "import",
+        "importstr",
+        "importbin",
+        "in",
+        "local",
+        "null",
+        "self",
+        "super",
+        "tailstrict",
+        "then",
+        "true",
+        "obj",
+        "e",
+        "entry",
+        "existing",
+        "matches",
+        "upserted",
+    ];
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !KEYWORDS.contains(&key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_map_helpers() {
+        let properties: Mapping = serde_yaml::from_str(
+            r#"
+containers:
+  type: array
+  x-kubernetes-list-type: map
+  x-kubernetes-list-map-keys: [name]
+  items: {type: object}
+ports:
+  type: array
+  x-kubernetes-list-type: map
+  x-kubernetes-list-map-keys: [containerPort, protocol]
+policies:
+  type: array
+  x-kubernetes-list-type: map
+  x-kubernetes-list-map-keys: [name]
+tags:
+  type: array
+  x-kubernetes-list-type: set
+odd:
+  type: array
+  x-kubernetes-list-type: map
+  x-kubernetes-list-map-keys: [if]
+rules:
+  type: array
+  x-kubernetes-list-type: map
+  x-kubernetes-list-map-keys: [name]
+rule:
+  type: string
+"#,
+        )
+        .unwrap();
+
+        let fields = find_list_map_fields(&properties, FieldNaming::Preserve);
+        let names: Vec<String> = fields.iter().map(ListMapField::helper_name).collect();
+        assert_eq!(names, ["withContainer", "withPort", "withPolicy"]);
+        assert_eq!(fields[1].keys, ["containerPort", "protocol"]);
+
+        let helper = generate_upsert_helper(&fields[1]);
+        assert!(helper.contains("  withPort(containerPort, protocol, obj): {\n"));
+        assert!(helper.contains(
+            "local matches(entry) = std.objectHas(entry, \"containerPort\") && entry.containerPort == containerPort && std.objectHas(entry, \"protocol\") && entry.protocol == protocol,"
+        ));
+        assert!(helper.contains(
+            "local upserted = obj { containerPort: containerPort, protocol: protocol },"
+        ));
+
+        let mut data = ListMapField {
+            field: "data".to_string(),
+            keys: vec!["key".to_string()],
+            naming: FieldNaming::Preserve,
+        };
+        assert_eq!(data.helper_name(), "withDataEntry");
+        data.naming = FieldNaming::SnakeCase;
+        assert_eq!(data.helper_name(), "with_data_entry");
+    }
+}
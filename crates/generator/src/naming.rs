@@ -0,0 +1,182 @@
+//! Setter names of fields
+//!
+//! Upstream schemas, OpenAPI and Go ones in particular, sometimes mix naming
+//! conventions, so one library would offer `withMaxRetries` next to
+//! `withRetry_policy`. `output.field_naming` normalizes the field names setters
+//! are named after. Only the setter names change: setters still write the
+//! field under its original wire name. Fields whose setters would end up with
+//! the same name fail generation instead of shadowing each other.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How setter names are derived from field names
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldNaming {
+    /// `with` followed by the field name, capitalized: `retry_policy` gets `withRetry_policy`
+    #[default]
+    Preserve,
+
+    /// Words of the field name in camel case: `retry_policy` gets `withRetryPolicy`
+    CamelCase,
+
+    /// Words of the field name in snake case: `retryPolicy` gets `with_retry_policy`
+    SnakeCase,
+}
+
+impl FieldNaming {
+    /// Name of the setter of a field
+    pub fn setter_name(self, field_name: &str) -> String {
+        match self {
+            FieldNaming::Preserve => {
+                let mut chars = field_name.chars();
+                match chars.next() {
+                    Some(first) => format!("with{}{}", first.to_uppercase(), chars.as_str()),
+                    None => "with".to_string(),
+                }
+            }
+            FieldNaming::CamelCase => {
+                let mut name = String::from("with");
+                for word in words(field_name) {
+                    let mut chars = word.chars();
+                    if let Some(first) = chars.next() {
+                        name.extend(first.to_uppercase());
+                        name.push_str(chars.as_str());
+                    }
+                }
+                name
+            }
+            FieldNaming::SnakeCase => {
+                let mut name = String::from("with");
+                for word in words(field_name) {
+                    name.push('_');
+                    name.push_str(&word.to_lowercase());
+                }
+                name
+            }
+        }
+    }
+
+    /// Setter names of a set of fields, failing when two fields get the same one
+    pub fn setter_names<'a>(
+        self,
+        field_names: impl IntoIterator<Item = &'a str>,
+    ) -> Result<BTreeMap<&'a str, String>> {
+        let mut fields_by_setter: BTreeMap<String, &str> = BTreeMap::new();
+        let mut setters = BTreeMap::new();
+        for field_name in field_names {
+            let setter = self.setter_name(field_name);
+            if let Some(other) = fields_by_setter.insert(setter.clone(), field_name) {
+                if other != field_name {
+                    return Err(anyhow!(
+                        "Fields '{}' and '{}' would both get the setter {}; set output.field_naming to preserve or drop one of them with a fields transform",
+                        other,
+                        field_name,
+                        setter
+                    ));
+                }
+            }
+            setters.insert(field_name, setter);
+        }
+        Ok(setters)
+    }
+}
+
+/// Words of a field name, split at `_`, `-`, `.` and spaces and where the case changes
+///
+/// Runs of capitals stay together as acronyms: `podCIDRs` gives `pod`, `CIDRs`
+/// and `HTTPServer` gives `HTTP`, `Server`.
+fn words(field_name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in field_name.split(['_', '-', '.', ' ']) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut word = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            let boundary = i > 0
+                && c.is_uppercase()
+                && (chars[i - 1].is_lowercase()
+                    || chars[i - 1].is_ascii_digit()
+                    || chars.get(i + 1).is_some_and(|next| next.is_lowercase())
+                        && chars[i - 1].is_uppercase()
+                        // A trailing plural `s` stays with its acronym
+                        && !(chars.get(i + 1) == Some(&'s') && i + 2 == chars.len()));
+            if boundary && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.push(c);
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_setter_names() {
+        let names = |naming: FieldNaming| {
+            [
+                "retry_policy",
+                "maxRetries",
+                "podCIDRs",
+                "HTTPServer",
+                "tls-config",
+            ]
+            .map(|field| naming.setter_name(field))
+        };
+        assert_eq!(
+            names(FieldNaming::Preserve),
+            [
+                "withRetry_policy",
+                "withMaxRetries",
+                "withPodCIDRs",
+                "withHTTPServer",
+                "withTls-config"
+            ]
+        );
+        assert_eq!(
+            names(FieldNaming::CamelCase),
+            [
+                "withRetryPolicy",
+                "withMaxRetries",
+                "withPodCIDRs",
+                "withHTTPServer",
+                "withTlsConfig"
+            ]
+        );
+        assert_eq!(
+            names(FieldNaming::SnakeCase),
+            [
+                "with_retry_policy",
+                "with_max_retries",
+                "with_pod_cidrs",
+                "with_http_server",
+                "with_tls_config"
+            ]
+        );
+
+        let setters = FieldNaming::CamelCase
+            .setter_names(["retry_policy", "size"])
+            .unwrap();
+        assert_eq!(setters["retry_policy"], "withRetryPolicy");
+        let err = FieldNaming::CamelCase
+            .setter_names(["retryPolicy", "retry_policy"])
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains(
+                "'retryPolicy' and 'retry_policy' would both get the setter withRetryPolicy"
+            ),
+            "{err}"
+        );
+        assert!(FieldNaming::Preserve
+            .setter_names(["retryPolicy", "retry_policy"])
+            .is_ok());
+    }
+}
@@ -0,0 +1,269 @@
+//! Presets and examples from upstream samples
+//!
+//! Operator repositories often ship sample resources, such as
+//! `config/samples/*.yaml`. Ingested samples become runnable examples next to
+//! the generated ones, and presets in `_presets.libsonnet`: the spec of every
+//! sample by resource, the first sample of each resource doubling as
+//! `standard`. The index exposes the presets as its hidden `presets` field:
+//!
+//! ```jsonnet
+//! local lib = import "operator/index.libsonnet";
+//! widget({ name: "web" }, lib.presets["widgets.example.com"].standard + { replicas: 3 })
+//! ```
+
+use crate::crd::CrdSchema;
+use crate::examples::EXAMPLES_DIR;
+use anyhow::Result;
+
+/// File holding the presets, relative to the library root
+pub const PRESETS_FILE: &str = "_presets.libsonnet";
+
+/// Preset every resource with samples has
+pub const STANDARD: &str = "standard";
+
+/// Hidden field of the index importing the presets
+pub const INDEX_FIELD: &str = "presets";
+
+/// A sample resource ingested from the upstream
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preset {
+    /// Name of the schema the sample is a resource of
+    pub schema: String,
+
+    /// Name of the sample, from its metadata
+    pub name: String,
+
+    /// File the sample was read from, relative to the repository
+    pub file: String,
+
+    /// Metadata the example gives the resource
+    pub metadata: serde_yaml::Value,
+
+    /// Spec of the sample
+    pub spec: serde_yaml::Value,
+}
+
+/// Path of the example made from a sample relative to the library root,
+/// e.g. `examples/example.com_v1/widget-widget-sample.jsonnet`
+pub fn example_path(schema: &CrdSchema, preset: &Preset) -> String {
+    let name: String = preset
+        .name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!(
+        "{}/{}/{}-{}.jsonnet",
+        EXAMPLES_DIR,
+        schema.api_version.replace('/', "_"),
+        schema.kind().to_lowercase(),
+        name
+    )
+}
+
+/// Generate the example made from a sample
+///
+/// `library_path` is the schema's library relative to the library root.
+pub fn generate_example(schema: &CrdSchema, library_path: &str, preset: &Preset) -> Result<String> {
+    let mut content = String::new();
+    content.push_str(&format!(
+        "// Example {} ({}) from sample {}\n",
+        schema.kind(),
+        schema.api_version,
+        preset.name
+    ));
+    content.push_str(&format!("// Converted from {}.\n", preset.file));
+    content.push_str("// Evaluate with the library root and k.libsonnet on the search path.\n");
+    content.push_str(&format!(
+        "local resource = import \"../../{}\";\n\n",
+        library_path.trim_start_matches("./")
+    ));
+    content.push_str("resource(\n");
+    content.push_str(&format!("  {},\n", to_jsonnet(&preset.metadata, "  ")?));
+    content.push_str(&format!("  {},\n", to_jsonnet(&preset.spec, "  ")?));
+    content.push_str(")\n");
+    Ok(content)
+}
+
+/// Generate `_presets.libsonnet` from the presets of the given schemas
+///
+/// Resources are sorted by schema name and keep the order of their samples.
+pub fn generate_presets_file(schemas: &[&CrdSchema], presets: &[Preset]) -> Result<String> {
+    let mut names: Vec<&str> = schemas.iter().map(|schema| schema.name.as_str()).collect();
+    names.sort_unstable();
+
+    let mut content = String::new();
+    content.push_str("// Presets from upstream samples\n");
+    content.push_str(
+        "// The spec of every sample by resource; `standard` is the first sample of each.\n",
+    );
+    content.push_str("{\n");
+    for name in names {
+        let samples: Vec<&Preset> = presets.iter().filter(|p| p.schema == name).collect();
+        let Some(first) = samples.first() else {
+            continue;
+        };
+        content.push_str(&format!("  {}: {{\n", serde_json::to_string(name)?));
+        if !samples.iter().any(|p| p.name == STANDARD) {
+            content.push_str(&format!(
+                "    {STANDARD}: self[{}],\n",
+                serde_json::to_string(&first.name)?
+            ));
+        }
+        for preset in samples {
+            content.push_str(&format!(
+                "    {}: {},\n",
+                serde_json::to_string(&preset.name)?,
+                to_jsonnet(&preset.spec, "    ")?
+            ));
+        }
+        content.push_str("  },\n");
+    }
+    content.push_str("}\n");
+    Ok(content)
+}
+
+/// Add the presets field to the content of an `index.libsonnet`
+///
+/// The field goes right after the opening brace of the index object;
+/// content already linking the presets is returned unchanged.
+pub fn link_index(content: &str) -> String {
+    if content
+        .lines()
+        .any(|line| line.trim_start().starts_with(&format!("{INDEX_FIELD}::")))
+    {
+        return content.to_string();
+    }
+
+    let mut linked = String::with_capacity(content.len());
+    let mut done = false;
+    for line in content.split_inclusive('\n') {
+        linked.push_str(line);
+        if !done && line.trim_end() == "{" {
+            linked.push_str(&format!("  {INDEX_FIELD}:: import \"./{PRESETS_FILE}\",\n"));
+            done = true;
+        }
+    }
+    linked
+}
+
+/// Render a value as Jsonnet, indenting continuation lines by `indent`
+fn to_jsonnet(value: &serde_yaml::Value, indent: &str) -> Result<String> {
+    let json = serde_json::to_string_pretty(value)?;
+    Ok(json.replace('\n', &format!("\n{indent}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn widget() -> CrdSchema {
+        CrdSchema {
+            name: "widgets.example.com".to_string(),
+            group: "example.com".to_string(),
+            version: "v1".to_string(),
+            api_version: "example.com/v1".to_string(),
+            kind: "Widget".to_string(),
+            schema: serde_yaml::from_str("type: object").unwrap(),
+            source_path: PathBuf::from("widget.yaml"),
+            validation_rules: Default::default(),
+            schema_analysis: Default::default(),
+            categories: Vec::new(),
+            printer_columns: Vec::new(),
+            conversion: Default::default(),
+            field_lines: Default::default(),
+        }
+    }
+
+    fn preset(name: &str, spec: &str) -> Preset {
+        Preset {
+            schema: "widgets.example.com".to_string(),
+            name: name.to_string(),
+            file: "config/samples/widgets.yaml".to_string(),
+            metadata: serde_yaml::from_str(&format!("name: {name}")).unwrap(),
+            spec: serde_yaml::from_str(spec).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_generate_example() {
+        let widget = widget();
+        let small = preset("widget_small", "size: small\nlabels: {tier: web}");
+        assert_eq!(
+            example_path(&widget, &small),
+            "examples/example.com_v1/widget-widget-small.jsonnet"
+        );
+        assert_eq!(
+            generate_example(
+                &widget,
+                "./example.com_v1/widgets.example.com.libsonnet",
+                &small
+            )
+            .unwrap(),
+            r#"// Example Widget (example.com/v1) from sample widget_small
+// Converted from config/samples/widgets.yaml.
+// Evaluate with the library root and k.libsonnet on the search path.
+local resource = import "../../example.com_v1/widgets.example.com.libsonnet";
+
+resource(
+  {
+    "name": "widget_small"
+  },
+  {
+    "size": "small",
+    "labels": {
+      "tier": "web"
+    }
+  },
+)
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_presets_file() {
+        let widget = widget();
+        let content = generate_presets_file(
+            &[&widget],
+            &[
+                preset("widget-small", "size: small"),
+                preset("widget-large", "{}"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            content,
+            r#"// Presets from upstream samples
+// The spec of every sample by resource; `standard` is the first sample of each.
+{
+  "widgets.example.com": {
+    standard: self["widget-small"],
+    "widget-small": {
+      "size": "small"
+    },
+    "widget-large": {},
+  },
+}
+"#
+        );
+
+        // A sample named standard is the standard preset
+        let content =
+            generate_presets_file(&[&widget], &[preset("standard", "size: small")]).unwrap();
+        assert!(!content.contains("self["), "{content}");
+
+        let index = "// Main index file\n{\n  example.com_v1: import \"./example.com_v1/_index.libsonnet\",\n}\n";
+        let linked = link_index(index);
+        assert_eq!(
+            linked,
+            "// Main index file\n{\n  presets:: import \"./_presets.libsonnet\",\n  example.com_v1: import \"./example.com_v1/_index.libsonnet\",\n}\n"
+        );
+        assert_eq!(link_index(&linked), linked);
+    }
+}
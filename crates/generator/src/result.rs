@@ -1,16 +1,68 @@
 //! Result types for generation operations
 
+use crate::warning::{Warning, WarningCategory};
+pub use jsonnet_lockfile::stats::{GenerationStatistics, PluginMetrics, SourceStatistics};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// Result of processing a source
 #[derive(Debug, Clone)]
 pub struct SourceResult {
+    pub source_name: String,
     pub source_type: String,
     pub files_generated: usize,
     pub errors: Vec<String>,
     pub output_path: PathBuf,
     pub processing_time_ms: u64,
-    pub warnings: Vec<String>,
+    pub warnings: Vec<Warning>,
+    /// Plugin that processed the source, when one was used
+    pub plugin: Option<String>,
+    /// Plugins that produced files of the output, by path relative to it
+    pub file_plugins: BTreeMap<PathBuf, String>,
+    /// Digests of the schemas behind the generated schema files, by relative path
+    pub schema_digests: BTreeMap<String, String>,
+    /// Schema files left as published because their schema was unchanged
+    pub schemas_reused: usize,
+    /// Time spent per phase and bytes written, filled in once the source is processed
+    pub statistics: SourceStatistics,
+}
+
+impl SourceResult {
+    /// Whether processing failed without generating anything
+    pub fn is_failure(&self) -> bool {
+        self.files_generated == 0 && !self.errors.is_empty()
+    }
+}
+
+/// A schema field whose type could not be resolved during generation
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnresolvedType {
+    /// Kind of the schema containing the field
+    pub kind: String,
+
+    /// Dotted path of the field within the schema
+    pub field_path: String,
+
+    /// Why the type could not be resolved
+    pub reason: String,
+}
+
+impl std::fmt::Display for UnresolvedType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Unresolved type for {}.{}: {}",
+            self.kind, self.field_path, self.reason
+        )
+    }
+}
+
+impl UnresolvedType {
+    /// The warning reporting the field
+    pub fn to_warning(&self) -> Warning {
+        Warning::new(WarningCategory::Schema, "unresolved-type", self.to_string())
+            .with_path(format!("{}.{}", self.kind, self.field_path))
+    }
 }
 
 /// Overall generation result
@@ -20,39 +72,110 @@ pub struct GenerationResult {
     pub total_sources: usize,
     pub results: Vec<SourceResult>,
     pub statistics: GenerationStatistics,
+    /// Sources skipped as quarantined, sorted
+    pub quarantined: Vec<String>,
 }
 
-/// Generation statistics
-#[derive(Debug, Clone)]
-pub struct GenerationStatistics {
-    /// Total processing time in milliseconds
-    pub total_processing_time_ms: u64,
+/// Statistics of a run that processed the given sources
+pub fn generation_statistics(
+    results: &[SourceResult],
+    total_processing_time_ms: u64,
+) -> GenerationStatistics {
+    GenerationStatistics {
+        total_processing_time_ms,
+        sources_processed: results.len(),
+        files_generated: results.iter().map(|r| r.files_generated).sum(),
+        error_count: results.iter().map(|r| r.errors.len()).sum(),
+        warning_count: results.iter().map(|r| r.warnings.len()).sum(),
+        cache_hit_rate: 0.0,
+        schema_cache_hit_rate: schema_cache_hit_rate(results),
+        bytes_written: results.iter().map(|r| r.statistics.bytes_written).sum(),
+        sources: results
+            .iter()
+            .map(|r| (r.source_name.clone(), r.statistics.clone()))
+            .collect(),
+        plugin_metrics: plugin_metrics(results),
+    }
+}
 
-    /// Number of sources processed
-    pub sources_processed: usize,
+/// Custom metrics of the given source results, merged by plugin
+pub fn plugin_metrics(results: &[SourceResult]) -> BTreeMap<String, PluginMetrics> {
+    let mut metrics: BTreeMap<String, PluginMetrics> = BTreeMap::new();
+    for result in results {
+        if let Some(plugin) = &result.plugin {
+            if !result.statistics.plugin_metrics.is_empty() {
+                metrics
+                    .entry(plugin.clone())
+                    .or_default()
+                    .merge(&result.statistics.plugin_metrics);
+            }
+        }
+    }
+    metrics
+}
 
-    /// Number of files generated
-    pub files_generated: usize,
+/// Share of schema files reused across the given source results (0.0 to 1.0)
+pub fn schema_cache_hit_rate(results: &[SourceResult]) -> f64 {
+    let considered: usize = results.iter().map(|r| r.schema_digests.len()).sum();
+    let reused: usize = results.iter().map(|r| r.schemas_reused).sum();
+    if considered == 0 {
+        0.0
+    } else {
+        reused as f64 / considered as f64
+    }
+}
 
-    /// Number of errors encountered
-    pub error_count: usize,
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    /// Number of warnings encountered
-    pub warning_count: usize,
+    fn source_result(name: &str, plugin: &str, metrics: PluginMetrics) -> SourceResult {
+        SourceResult {
+            source_name: name.to_string(),
+            source_type: "go_ast".to_string(),
+            files_generated: 1,
+            errors: Vec::new(),
+            output_path: PathBuf::from("./out"),
+            processing_time_ms: 0,
+            warnings: Vec::new(),
+            plugin: Some(plugin.to_string()),
+            file_plugins: BTreeMap::new(),
+            schema_digests: BTreeMap::new(),
+            schemas_reused: 0,
+            statistics: SourceStatistics {
+                plugin_metrics: metrics,
+                ..Default::default()
+            },
+        }
+    }
 
-    /// Cache hit rate (0.0 to 1.0)
-    pub cache_hit_rate: f64,
-}
+    #[test]
+    fn test_plugin_metrics() {
+        let mut first = PluginMetrics::default();
+        first.increment("generic_types_skipped", 2);
+        first.set_gauge("max_depth", 3.0);
+        let mut second = PluginMetrics::default();
+        second.increment("generic_types_skipped", 1);
+        second.set_gauge("max_depth", 5.0);
 
-impl Default for GenerationStatistics {
-    fn default() -> Self {
-        Self {
-            total_processing_time_ms: 0,
-            sources_processed: 0,
-            files_generated: 0,
-            error_count: 0,
-            warning_count: 0,
-            cache_hit_rate: 0.0,
-        }
+        let statistics = generation_statistics(
+            &[
+                source_result("a", "go-ast:builtin", first),
+                source_result("b", "go-ast:builtin", second),
+                source_result("c", "crd:builtin", PluginMetrics::default()),
+            ],
+            0,
+        );
+        let metrics = &statistics.plugin_metrics["go-ast:builtin"];
+        assert_eq!(metrics.counters["generic_types_skipped"], 3);
+        assert_eq!(metrics.gauges["max_depth"], 5.0);
+        assert!(!statistics.plugin_metrics.contains_key("crd:builtin"));
+
+        let json = serde_json::to_value(&statistics).unwrap();
+        assert_eq!(
+            json["sources"]["a"]["plugin_metrics"]["counters"]["generic_types_skipped"],
+            2
+        );
+        assert!(json["sources"]["c"].get("plugin_metrics").is_none());
     }
 }
@@ -0,0 +1,260 @@
+//! Single-file library
+//!
+//! Some tools accept one Jsonnet file and cannot vendor a directory tree.
+//! `bundle.libsonnet` inlines every file of a library reachable from its
+//! index, each bound to a local, so it evaluates to the same object as
+//! `index.libsonnet` without importing anything from the library. Imports
+//! of files outside the library, such as `k.libsonnet`, are kept.
+
+use crate::style::CodeStyle;
+use anyhow::Result;
+use std::collections::{BTreeMap, VecDeque};
+use std::path::{Component, Path, PathBuf};
+
+/// Name of the single-file library emitted at the root of a library
+pub const SINGLE_FILE: &str = "bundle.libsonnet";
+
+/// A library file inlined into the single file
+struct Inlined {
+    /// Order in which the file was first imported
+    index: usize,
+    content: String,
+}
+
+/// Generate the single-file library of the library at `library`
+///
+/// Libraries without an `index.libsonnet` are exposed as an object holding
+/// each top-level file by name. The library's files are inlined as they
+/// are, already in `style`; the lines binding them are indented to match.
+pub fn generate_single_file(library: &Path, style: &CodeStyle) -> Result<String> {
+    let index = library.join("index.libsonnet");
+    let entry = if index.is_file() {
+        std::fs::read_to_string(&index)?
+    } else {
+        style.apply(&synthesize_index(library)?).into_owned()
+    };
+
+    let mut files: BTreeMap<PathBuf, Inlined> = BTreeMap::new();
+    let mut queue = VecDeque::new();
+    let entry = rewrite_imports(&entry, library, library, &mut files, &mut queue);
+
+    while let Some(path) = queue.pop_front() {
+        let content = std::fs::read_to_string(&path)?;
+        let dir = path.parent().unwrap_or(library).to_path_buf();
+        let content = rewrite_imports(&content, &dir, library, &mut files, &mut queue);
+        if let Some(inlined) = files.get_mut(&path) {
+            inlined.content = content;
+        }
+    }
+
+    let mut output = String::new();
+    output.push_str("// Generated single-file library\n");
+    if !files.is_empty() {
+        let mut inlined: Vec<(&PathBuf, &Inlined)> = files.iter().collect();
+        inlined.sort_by_key(|(_, file)| file.index);
+
+        let indent = " ".repeat(style.indent);
+        let binds: Vec<String> = inlined
+            .into_iter()
+            .map(|(path, file)| {
+                format!(
+                    "{indent}// {}\n{indent}{} = (\n{}\n{indent})",
+                    path.strip_prefix(library).unwrap_or(path).display(),
+                    local_name(file.index),
+                    file.content.trim_end()
+                )
+            })
+            .collect();
+        output.push_str(&format!("local\n{};\n", binds.join(",\n")));
+    }
+    output.push_str(entry.trim_end());
+    output.push('\n');
+    Ok(output)
+}
+
+/// Local bound to an inlined file
+fn local_name(index: usize) -> String {
+    format!("__gensonnet_file_{index}")
+}
+
+/// An index exposing every top-level file of a library by name
+fn synthesize_index(library: &Path) -> Result<String> {
+    let mut names = Vec::new();
+    for entry in std::fs::read_dir(library)? {
+        let file_name = entry?.file_name().to_string_lossy().to_string();
+        if let Some(name) = file_name.strip_suffix(".libsonnet") {
+            if !name.starts_with('_') && file_name != SINGLE_FILE && file_name != "index.libsonnet"
+            {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names.sort();
+
+    let mut content = String::from("{\n");
+    for name in names {
+        content.push_str(&format!("  \"{name}\": import \"./{name}.libsonnet\",\n"));
+    }
+    content.push_str("}\n");
+    Ok(content)
+}
+
+/// Replace the imports of library files in `content` with their locals,
+/// queueing files seen for the first time
+fn rewrite_imports(
+    content: &str,
+    dir: &Path,
+    library: &Path,
+    files: &mut BTreeMap<PathBuf, Inlined>,
+    queue: &mut VecDeque<PathBuf>,
+) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(position) = rest.find("import") {
+        let (before, from_keyword) = rest.split_at(position);
+        output.push_str(before);
+
+        let keyword_start = before
+            .chars()
+            .next_back()
+            .is_none_or(|c| !(c.is_ascii_alphanumeric() || c == '_'));
+        let after_keyword = &from_keyword["import".len()..];
+        let path_start = after_keyword.trim_start();
+        let quote = path_start
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'');
+
+        let import = match quote {
+            Some(quote) if keyword_start => path_start[1..]
+                .find(quote)
+                .map(|end| (&path_start[1..1 + end], 2 + end)),
+            _ => None,
+        };
+        let Some((import_path, quoted_len)) = import else {
+            output.push_str("import");
+            rest = after_keyword;
+            continue;
+        };
+
+        let consumed = "import".len() + (after_keyword.len() - path_start.len()) + quoted_len;
+        match resolve(import_path, dir, library) {
+            Some(path) => {
+                let next = files.len();
+                let inlined = files.entry(path.clone()).or_insert_with(|| {
+                    queue.push_back(path);
+                    Inlined {
+                        index: next,
+                        content: String::new(),
+                    }
+                });
+                output.push_str(&local_name(inlined.index));
+            }
+            None => output.push_str(&from_keyword[..consumed]),
+        }
+        rest = &from_keyword[consumed..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Resolve an import like Jsonnet does with the library on the search path:
+/// relative to the importing file, then to the library root
+fn resolve(import_path: &str, dir: &Path, library: &Path) -> Option<PathBuf> {
+    [dir, library]
+        .into_iter()
+        .map(|base| normalize(&base.join(import_path)))
+        .find(|path| path.starts_with(library) && path.is_file())
+}
+
+/// Resolve `.` and `..` components without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_generate_single_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let library = temp_dir.path();
+        std::fs::create_dir_all(library.join("example.com_v1")).unwrap();
+        std::fs::write(
+            library.join("index.libsonnet"),
+            "{\n  v1: import \"./example.com_v1/_index.libsonnet\",\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            library.join("example.com_v1/_index.libsonnet"),
+            "{\n  widget: import \"./widget.libsonnet\",\n  gadget: import './widget.libsonnet',\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            library.join("example.com_v1/widget.libsonnet"),
+            "local k = import \"k.libsonnet\";\nlocal validate = import \"_validation.libsonnet\";\nfunction(metadata) { metadata: metadata }\n",
+        )
+        .unwrap();
+        std::fs::write(library.join("_validation.libsonnet"), "{}\n").unwrap();
+
+        let content = generate_single_file(library, &CodeStyle::default()).unwrap();
+        assert_eq!(
+            content,
+            r#"// Generated single-file library
+local
+  // example.com_v1/_index.libsonnet
+  __gensonnet_file_0 = (
+{
+  widget: __gensonnet_file_1,
+  gadget: __gensonnet_file_1,
+}
+  ),
+  // example.com_v1/widget.libsonnet
+  __gensonnet_file_1 = (
+local k = import "k.libsonnet";
+local validate = __gensonnet_file_2;
+function(metadata) { metadata: metadata }
+  ),
+  // _validation.libsonnet
+  __gensonnet_file_2 = (
+{}
+  );
+{
+  v1: __gensonnet_file_0,
+}
+"#
+        );
+
+        // Without an index, top-level files are exposed by name
+        std::fs::remove_file(library.join("index.libsonnet")).unwrap();
+        std::fs::write(library.join("widget.libsonnet"), "{}\n").unwrap();
+        let content = generate_single_file(library, &CodeStyle::default()).unwrap();
+        assert!(content.ends_with("{\n  \"widget\": __gensonnet_file_0,\n}\n"));
+
+        let style = CodeStyle {
+            indent: 4,
+            quotes: crate::style::QuoteStyle::Single,
+            ..Default::default()
+        };
+        let content = generate_single_file(library, &style).unwrap();
+        assert!(
+            content.contains("\n    __gensonnet_file_0 = (\n"),
+            "{content}"
+        );
+        assert!(content.ends_with("{\n    'widget': __gensonnet_file_0,\n}\n"));
+    }
+}
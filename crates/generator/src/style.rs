@@ -0,0 +1,502 @@
+//! Code style of generated Jsonnet
+//!
+//! Generators emit Jsonnet with two-space indentation, double quotes, fields
+//! in schema order and trailing commas. `output.style` restyles every
+//! generated Jsonnet file as it is published, so libraries follow an
+//! organization's style guide whichever generator or plugin emitted them:
+//! indentation is rescaled to the configured width, strings are requoted
+//! where that needs no extra escaping, and the last entries of multi-line
+//! objects and arrays gain or lose their commas. Comments and text blocks are
+//! kept as they are. Field order is applied to schemas before generation.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// Style of generated Jsonnet code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CodeStyle {
+    /// Spaces per indentation level
+    pub indent: usize,
+
+    /// Quotes of string literals
+    pub quotes: QuoteStyle,
+
+    /// Order of a schema's fields in setters, validation and samples
+    pub field_order: FieldOrder,
+
+    /// End the last entry of multi-line objects and arrays with a comma
+    pub trailing_commas: bool,
+}
+
+impl Default for CodeStyle {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            quotes: QuoteStyle::Double,
+            field_order: FieldOrder::Schema,
+            trailing_commas: true,
+        }
+    }
+}
+
+/// Quotes of string literals
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteStyle {
+    /// `"value"`
+    #[default]
+    Double,
+
+    /// `'value'`
+    Single,
+}
+
+/// Order of a schema's fields in generated code
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldOrder {
+    /// The order the schema declares them in
+    #[default]
+    Schema,
+
+    /// Sorted by name
+    Alphabetical,
+}
+
+impl CodeStyle {
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=8).contains(&self.indent) {
+            return Err(anyhow!(
+                "style.indent must be between 1 and 8, got {}",
+                self.indent
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether files need restyling, i.e. the style differs from the one
+    /// generators emit in other than field order
+    fn restyles(&self) -> bool {
+        let default = Self::default();
+        self.indent != default.indent
+            || self.quotes != default.quotes
+            || self.trailing_commas != default.trailing_commas
+    }
+
+    /// Restyle generated Jsonnet
+    pub fn apply<'a>(&self, content: &'a str) -> Cow<'a, str> {
+        if !self.restyles() {
+            return Cow::Borrowed(content);
+        }
+        Cow::Owned(render(
+            with_trailing_commas(tokenize(content), self.trailing_commas),
+            self,
+        ))
+    }
+
+    /// Order the fields of a schema and of the objects nested in it
+    pub fn order_fields(&self, schema: &mut serde_yaml::Value) {
+        if self.field_order == FieldOrder::Alphabetical {
+            sort_properties(schema);
+        }
+    }
+}
+
+/// Sort every `properties` mapping of a schema by field name
+fn sort_properties(schema: &mut serde_yaml::Value) {
+    match schema {
+        serde_yaml::Value::Mapping(mapping) => {
+            if let Some(serde_yaml::Value::Mapping(properties)) = mapping.get_mut("properties") {
+                let mut fields: Vec<_> = std::mem::take(properties).into_iter().collect();
+                fields.sort_by(|(a, _), (b, _)| a.as_str().cmp(&b.as_str()));
+                properties.extend(fields);
+            }
+            for (_, value) in mapping.iter_mut() {
+                sort_properties(value);
+            }
+        }
+        serde_yaml::Value::Sequence(items) => items.iter_mut().for_each(sort_properties),
+        _ => {}
+    }
+}
+
+/// A token of Jsonnet source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    /// Spaces and line breaks
+    Space(&'a str),
+
+    /// A comment
+    Comment(&'a str),
+
+    /// A quoted string, its escapes unresolved
+    Str {
+        quote: char,
+        body: &'a str,
+    },
+
+    Open(char),
+    Close(char),
+    Comma,
+
+    /// Identifiers, numbers, operators, verbatim strings and text blocks
+    Other(&'a str),
+}
+
+fn tokenize(content: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = content;
+
+    while let Some(c) = rest.chars().next() {
+        let len = if c.is_whitespace() {
+            let len = rest
+                .find(|c: char| !c.is_whitespace())
+                .unwrap_or(rest.len());
+            tokens.push(Token::Space(&rest[..len]));
+            len
+        } else if c == '#' || rest.starts_with("//") {
+            let len = rest.find('\n').unwrap_or(rest.len());
+            tokens.push(Token::Comment(&rest[..len]));
+            len
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            let len = comment.find("*/").map_or(rest.len(), |end| end + 4);
+            tokens.push(Token::Comment(&rest[..len]));
+            len
+        } else if rest.starts_with("|||") {
+            let len = text_block_len(rest);
+            tokens.push(Token::Other(&rest[..len]));
+            len
+        } else if c == '"' || c == '\'' {
+            match quoted_len(rest, c) {
+                Some(len) => {
+                    tokens.push(Token::Str {
+                        quote: c,
+                        body: &rest[1..len - 1],
+                    });
+                    len
+                }
+                // Unterminated, so kept as is
+                None => {
+                    tokens.push(Token::Other(rest));
+                    rest.len()
+                }
+            }
+        } else if c == '@' && (rest[1..].starts_with('"') || rest[1..].starts_with('\'')) {
+            let len = 1 + verbatim_len(&rest[1..]);
+            tokens.push(Token::Other(&rest[..len]));
+            len
+        } else if matches!(c, '{' | '[' | '(') {
+            tokens.push(Token::Open(c));
+            1
+        } else if matches!(c, '}' | ']' | ')') {
+            tokens.push(Token::Close(c));
+            1
+        } else if c == ',' {
+            tokens.push(Token::Comma);
+            1
+        } else if c.is_alphanumeric() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Other(&rest[..len]));
+            len
+        } else {
+            tokens.push(Token::Other(&rest[..c.len_utf8()]));
+            c.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+    tokens
+}
+
+/// Length of the string starting `rest`, quotes included
+fn quoted_len(rest: &str, quote: char) -> Option<usize> {
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == quote {
+            return Some(index + 1);
+        }
+    }
+    None
+}
+
+/// Length of the verbatim string starting `rest`, where doubled quotes escape
+fn verbatim_len(rest: &str) -> usize {
+    let quote = rest.as_bytes()[0];
+    let bytes = rest.as_bytes();
+    let mut index = 1;
+    while index < bytes.len() {
+        if bytes[index] == quote {
+            if bytes.get(index + 1) == Some(&quote) {
+                index += 2;
+                continue;
+            }
+            return index + 1;
+        }
+        index += 1;
+    }
+    rest.len()
+}
+
+/// Length of the text block starting `rest`, through its closing `|||`
+fn text_block_len(rest: &str) -> usize {
+    let Some(first_line) = rest.find('\n') else {
+        return rest.len();
+    };
+    let mut offset = first_line + 1;
+    for line in rest[offset..].split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("|||") {
+            return offset + (line.len() - trimmed.len()) + 3;
+        }
+        offset += line.len();
+    }
+    rest.len()
+}
+
+/// Add or remove the commas ending multi-line objects and arrays
+///
+/// Comprehensions are left alone, as they cannot take a trailing comma, and
+/// so are brackets holding `;`, which are blocks rather than objects.
+fn with_trailing_commas(tokens: Vec<Token<'_>>, trailing_commas: bool) -> Vec<Token<'_>> {
+    // Open brackets, and whether they hold a comprehension or block
+    let mut brackets: Vec<(char, bool)> = Vec::new();
+    let mut output: Vec<Token<'_>> = Vec::with_capacity(tokens.len());
+
+    for token in tokens {
+        match token {
+            Token::Open(c) => brackets.push((c, false)),
+            Token::Other("for" | ";") => {
+                if let Some((_, skipped)) = brackets.last_mut() {
+                    *skipped = true;
+                }
+            }
+            Token::Close(_) => {
+                if let Some((open @ ('{' | '['), false)) = brackets.pop() {
+                    let last = output
+                        .iter()
+                        .rposition(|t| !matches!(t, Token::Space(_) | Token::Comment(_)));
+                    if let Some(last) = last {
+                        let multi_line = output[last..]
+                            .iter()
+                            .any(|t| matches!(t, Token::Space(s) if s.contains('\n')));
+                        match output[last] {
+                            Token::Comma if !trailing_commas => {
+                                output.remove(last);
+                            }
+                            Token::Comma => {}
+                            Token::Open(c) if c == open => {}
+                            _ if trailing_commas && multi_line => {
+                                output.insert(last + 1, Token::Comma)
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        output.push(token);
+    }
+    output
+}
+
+/// Render tokens, rescaling the indentation of every line
+///
+/// The indentation unit of the content is its smallest indentation, so
+/// hanging indents are kept and restyled content restyles to itself.
+fn render(tokens: Vec<Token<'_>>, style: &CodeStyle) -> String {
+    let line_indent = |space: &str| space.rsplit('\n').next().unwrap_or_default().len();
+    let unit = tokens
+        .iter()
+        .filter_map(|token| match token {
+            Token::Space(space) if space.contains('\n') => Some(line_indent(space)),
+            _ => None,
+        })
+        .filter(|indent| *indent > 0)
+        .min()
+        .unwrap_or(style.indent);
+
+    let mut output = String::new();
+    for (index, token) in tokens.iter().enumerate() {
+        match *token {
+            Token::Space(space) if space.contains('\n') => {
+                // Trailing spaces are dropped and the next line reindented
+                output.extend(std::iter::repeat_n('\n', space.matches('\n').count()));
+                if index + 1 < tokens.len() {
+                    let indent = line_indent(space);
+                    let width = indent / unit * style.indent + indent % unit;
+                    output.extend(std::iter::repeat_n(' ', width));
+                }
+            }
+            Token::Open(c) | Token::Close(c) => output.push(c),
+            Token::Comma => output.push(','),
+            Token::Str { quote, body } => output.push_str(&requote(quote, body, style.quotes)),
+            Token::Space(text) | Token::Comment(text) | Token::Other(text) => output.push_str(text),
+        }
+    }
+    output
+}
+
+/// A string in the configured quotes, unless its content holds them
+fn requote(quote: char, body: &str, quotes: QuoteStyle) -> String {
+    let target = match quotes {
+        QuoteStyle::Double => '"',
+        QuoteStyle::Single => '\'',
+    };
+    if quote == target {
+        return format!("{quote}{body}{quote}");
+    }
+
+    let mut requoted = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                // The old quote no longer needs escaping
+                Some(escaped) if escaped == quote => requoted.push(escaped),
+                Some(escaped) => {
+                    requoted.push('\\');
+                    requoted.push(escaped);
+                }
+                None => requoted.push('\\'),
+            },
+            c if c == target => return format!("{quote}{body}{quote}"),
+            c => requoted.push(c),
+        }
+    }
+    format!("{target}{requoted}{target}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENERATED: &str = r#"// Set the "replicas" field
+local k = import "k.libsonnet";
+
+function(metadata, spec={}) {
+  apiVersion: "example.com/v1",
+  kind: "Widget",
+  metadata: metadata {
+    labels: ["it's", "a"],
+  },
+  names: [name for name in ["a", "b"]],
+  check: function(value) {
+    assert value != null : "required";
+    value
+  },
+  ports: [
+    80,
+    443
+  ],
+  empty: {},
+}
+"#;
+
+    #[test]
+    fn test_restyle() {
+        let style = CodeStyle {
+            indent: 4,
+            quotes: QuoteStyle::Single,
+            trailing_commas: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            style.apply(GENERATED),
+            r#"// Set the "replicas" field
+local k = import 'k.libsonnet';
+
+function(metadata, spec={}) {
+    apiVersion: 'example.com/v1',
+    kind: 'Widget',
+    metadata: metadata {
+        labels: ["it's", 'a']
+    },
+    names: [name for name in ['a', 'b']],
+    check: function(value) {
+        assert value != null : 'required';
+        value
+    },
+    ports: [
+        80,
+        443
+    ],
+    empty: {}
+}
+"#
+        );
+
+        // Restyling twice changes nothing more
+        assert_eq!(style.apply(&style.apply(GENERATED)), style.apply(GENERATED));
+
+        let trailing = CodeStyle {
+            indent: 3,
+            ..Default::default()
+        };
+        let restyled = trailing.apply(GENERATED);
+        assert!(restyled.contains("\n      443,\n   ],"), "{restyled}");
+        assert!(restyled.contains("kind: \"Widget\""), "{restyled}");
+
+        // The default style leaves generated code as it is
+        assert!(matches!(
+            CodeStyle::default().apply(GENERATED),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_restyle_keeps_text_blocks_and_comments() {
+        let content = "{\n  doc: |||\n    He said \"hi\",\n  |||,\n  /* a,\n  b */\n  x: @\"a\"\"b\", # \"c\"\n}\n";
+        let style = CodeStyle {
+            indent: 4,
+            quotes: QuoteStyle::Single,
+            ..Default::default()
+        };
+        assert_eq!(
+            style.apply(content),
+            "{\n    doc: |||\n    He said \"hi\",\n  |||,\n    /* a,\n  b */\n    x: @\"a\"\"b\", # \"c\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_order_fields() {
+        let mut schema: serde_yaml::Value = serde_yaml::from_str(
+            "properties:\n  b: {type: string}\n  a:\n    properties: {d: {}, c: {}}\n",
+        )
+        .unwrap();
+        let style = CodeStyle {
+            field_order: FieldOrder::Alphabetical,
+            ..Default::default()
+        };
+        let names = |value: &serde_yaml::Value| -> Vec<String> {
+            value["properties"]
+                .as_mapping()
+                .unwrap()
+                .keys()
+                .map(|key| key.as_str().unwrap().to_string())
+                .collect()
+        };
+        CodeStyle::default().order_fields(&mut schema);
+        assert_eq!(names(&schema), ["b", "a"]);
+
+        style.order_fields(&mut schema);
+        assert_eq!(names(&schema), ["a", "b"]);
+        assert_eq!(names(&schema["properties"]["a"]), ["c", "d"]);
+
+        assert!(CodeStyle {
+            indent: 0,
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+    }
+}
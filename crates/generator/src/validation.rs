@@ -1,14 +1,136 @@
 //! Validation code generation for Jsonnet
 
+use crate::config::Newline;
 use crate::crd::CrdSchema;
+use crate::write::write_generated;
 use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
+use tracing::warn;
 
-pub struct ValidationGenerator;
+/// Common assertions opening `_validation.libsonnet`, up to `assertPattern`
+const COMMON_ASSERTIONS: &str = r#"// Validation utilities
+{
+  // Common validation functions
+  assertRequired: function(field, value, fieldName) {
+    assert value != null : fieldName + " is required";
+    value
+  },
+  
+  assertString: function(value, fieldName) {
+    assert std.type(value) == "string" : fieldName + " must be a string";
+    value
+  },
+  
+  assertNumber: function(value, fieldName) {
+    assert std.type(value) == "number" : fieldName + " must be a number";
+    value
+  },
+  
+  assertBoolean: function(value, fieldName) {
+    assert std.type(value) == "boolean" : fieldName + " must be a boolean";
+    value
+  },
+  
+  assertArray: function(value, fieldName) {
+    assert std.type(value) == "array" : fieldName + " must be an array";
+    value
+  },
+  
+  assertObject: function(value, fieldName) {
+    assert std.type(value) == "object" : fieldName + " must be an object";
+    value
+  },
+  
+  assertEnum: function(value, allowedValues, fieldName) {
+    assert std.member(value, allowedValues) : fieldName + " must be one of " + std.join(", ", allowedValues);
+    value
+  },
+  
+"#;
+
+/// Bound assertions of `_validation.libsonnet`, following `assertPattern`
+const BOUND_ASSERTIONS: &str = r#"
+  assertMinLength: function(value, minLength, fieldName) {
+    assert std.length(value) >= minLength : fieldName + " must be at least " + minLength + " characters";
+    value
+  },
+  
+  assertMaxLength: function(value, maxLength, fieldName) {
+    assert std.length(value) <= maxLength : fieldName + " must be at most " + maxLength + " characters";
+    value
+  },
+  
+  assertMinValue: function(value, minValue, fieldName) {
+    assert value >= minValue : fieldName + " must be at least " + minValue;
+    value
+  },
+  
+  assertMaxValue: function(value, maxValue, fieldName) {
+    assert value <= maxValue : fieldName + " must be at most " + maxValue;
+    value
+  },
+"#;
+
+/// Assertions added to `_validation.libsonnet` with native functions
+const NATIVE_ASSERTIONS: &str = r#"
+  // Native functions of the gensonnet-natives pack
+  semverCompare: function(a, b) std.native("semverCompare")(a, b),
+
+  assertSemverAtLeast: function(value, minimum, fieldName) {
+    assert std.native("semverCompare")(value, minimum) >= 0 : fieldName + " must be at least version " + minimum;
+    value
+  },
+
+  assertCidr: function(value, fieldName) {
+    assert std.native("isCidr")(value) : fieldName + " must be a CIDR";
+    value
+  },
+"#;
+
+pub struct ValidationGenerator {
+    newline: Newline,
+    native_functions: bool,
+}
+
+/// A validator contributed to `_validation.libsonnet` from outside the generator
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomValidator {
+    /// Name of the schema the validator applies to
+    pub schema: String,
+
+    /// Field name of the validator; validators whose name is not an identifier are skipped
+    pub name: String,
+
+    /// Jsonnet expression implementing the validator
+    pub code: String,
+}
 
 impl ValidationGenerator {
     pub fn new() -> Self {
-        Self
+        Self {
+            newline: Newline::default(),
+            native_functions: false,
+        }
+    }
+
+    /// Write validation utilities with the given line endings
+    pub fn with_newline(mut self, newline: Newline) -> Self {
+        self.newline = newline;
+        self
+    }
+
+    /// Check patterns, CIDRs and versions with the `std.native` functions of
+    /// the gensonnet-natives pack
+    pub fn with_native_functions(mut self, native_functions: bool) -> Self {
+        self.native_functions = native_functions;
+        self
+    }
+
+    /// Whether checks use the `std.native` functions of the gensonnet-natives pack
+    pub fn native_functions(&self) -> bool {
+        self.native_functions
     }
 }
 
@@ -20,11 +142,12 @@ impl Default for ValidationGenerator {
 
 impl ValidationGenerator {
     /// Generate validation functions for a schema
+    ///
+    /// Field checks are not inlined: each field calls the shared helper of
+    /// its rule set in `_validation.libsonnet` (see [`field_check`]).
     pub fn generate_validation_functions(&self, schema: &CrdSchema) -> Result<String> {
         let mut content = String::new();
 
-        let _resource_name = schema.resource_name();
-
         content.push_str(&format!("// Validation function for {}\n", schema.name));
         content.push_str(&format!(
             "function validate{}(metadata, spec) {{\n",
@@ -43,8 +166,11 @@ impl ValidationGenerator {
 
             for (field_name, field_schema) in schema.properties().unwrap() {
                 if let Some(field_name_str) = field_name.as_str() {
-                    content
-                        .push_str(&self.generate_field_validation(field_name_str, field_schema)?);
+                    content.push_str(&self.generate_field_validation(
+                        field_name_str,
+                        &field_label(schema, field_name_str),
+                        field_schema,
+                    ));
                 }
             }
 
@@ -66,11 +192,14 @@ impl ValidationGenerator {
     }
 
     /// Generate field validation
+    ///
+    /// `label` names the field in error messages.
     fn generate_field_validation(
         &self,
         field_name: &str,
+        label: &str,
         field_schema: &serde_yaml::Value,
-    ) -> Result<String> {
+    ) -> String {
         let mut content = String::new();
 
         // Check if field is required
@@ -78,230 +207,407 @@ impl ValidationGenerator {
             if required {
                 content.push_str(&format!("    // {field_name} is required\n"));
                 content.push_str(&format!(
-                    "    assert spec.{field_name} != null : \"{field_name} is required\";\n"
+                    "    assert spec.{field_name} != null : {};\n",
+                    jsonnet_string(&format!("{label} is required"))
                 ));
             }
         }
 
-        // Add type-specific validation
-        if let Some(field_type) = field_schema.get("type").and_then(|t| t.as_str()) {
-            match field_type {
-                "string" => {
-                    content.push_str(&self.generate_string_validation(field_name, field_schema)?);
-                }
-                "integer" | "number" => {
-                    content.push_str(&self.generate_number_validation(field_name, field_schema)?);
-                }
-                "array" => {
-                    content.push_str(&self.generate_array_validation(field_name, field_schema)?);
-                }
-                "object" => {
-                    content.push_str(&self.generate_object_validation(field_name, field_schema)?);
-                }
-                _ => {}
-            }
-        }
-
-        // Add enum validation
-        if let Some(enum_values) = field_schema.get("enum").and_then(|e| e.as_sequence()) {
-            content.push_str(&self.generate_enum_validation(field_name, enum_values)?);
+        if let Some(check) = field_check(field_schema, self.native_functions) {
+            content.push_str(&format!(
+                "    [if std.objectHas(spec, \"{field_name}\") then \"{field_name}\"]: validate.checks.{}(spec.{field_name}, {}),\n",
+                check.name,
+                jsonnet_string(label)
+            ));
         }
 
-        Ok(content)
+        content
     }
 
-    /// Generate string validation
-    fn generate_string_validation(
+    /// Generate validation utilities
+    ///
+    /// Besides the common assertions, the file holds the shared check of every
+    /// distinct field rule set among `schemas`. Checks are derived from all
+    /// schemas rather than the ones regenerated, so libraries reused from an
+    /// earlier run keep resolving theirs.
+    pub async fn generate_validation_utilities(
         &self,
-        field_name: &str,
-        field_schema: &serde_yaml::Value,
-    ) -> Result<String> {
-        let mut content = String::new();
-
-        if let Some(min_length) = field_schema.get("minLength").and_then(|v| v.as_u64()) {
-            content.push_str(&format!("    if spec.{field_name} != null then\n"));
-            content.push_str(&format!(
-                "      assert std.length(spec.{field_name}) >= {min_length} : \"{field_name} must be at least {min_length} characters\";\n"
-            ));
-        }
-
-        if let Some(max_length) = field_schema.get("maxLength").and_then(|v| v.as_u64()) {
-            content.push_str(&format!("    if spec.{field_name} != null then\n"));
-            content.push_str(&format!(
-                "      assert std.length(spec.{field_name}) <= {max_length} : \"{field_name} must be at most {max_length} characters\";\n"
-            ));
+        output_path: &Path,
+        schemas: &[&CrdSchema],
+    ) -> Result<()> {
+        let validation_path = output_path.join("_validation.libsonnet");
+        let mut content = COMMON_ASSERTIONS.to_string();
+        content.push_str(&format!(
+            r#"  assertPattern: function(value, pattern, fieldName) {{
+    assert {}(pattern, value) : fieldName + " must match pattern " + pattern;
+    value
+  }},
+  "#,
+            regex_match(self.native_functions)
+        ));
+        content.push_str(BOUND_ASSERTIONS);
+        if self.native_functions {
+            content.push_str(NATIVE_ASSERTIONS);
         }
 
-        if let Some(pattern) = field_schema.get("pattern").and_then(|v| v.as_str()) {
-            content.push_str(&format!("    if spec.{field_name} != null then\n"));
-            content.push_str(&format!(
-                "      assert std.regexMatch(\"{pattern}\", spec.{field_name}) : \"{field_name} must match pattern {pattern}\";\n"
-            ));
+        let checks: BTreeMap<String, String> = schemas
+            .iter()
+            .filter(|schema| schema.is_object())
+            .filter_map(|schema| schema.properties())
+            .flat_map(|properties| properties.values())
+            .filter_map(|field_schema| field_check(field_schema, self.native_functions))
+            .map(|check| (check.name, check.code))
+            .collect();
+        if !checks.is_empty() {
+            content.push_str("\n  // Field checks, shared by every field with the same rules\n");
+            content.push_str("  checks: {\n");
+            for (name, code) in checks {
+                content.push_str(&format!("    {name}: {code},\n"));
+            }
+            content.push_str("  },\n");
         }
+        content.push_str("}\n");
 
-        Ok(content)
+        write_generated(&validation_path, &content, self.newline)?;
+        Ok(())
     }
 
-    /// Generate number validation
-    fn generate_number_validation(
+    /// Append custom validators to `_validation.libsonnet` under `custom.<schema>.<name>`
+    pub fn append_custom_validators(
         &self,
-        field_name: &str,
-        field_schema: &serde_yaml::Value,
-    ) -> Result<String> {
-        let mut content = String::new();
+        output_path: &Path,
+        validators: &[CustomValidator],
+    ) -> Result<()> {
+        if validators.is_empty() {
+            return Ok(());
+        }
 
-        if let Some(minimum) = field_schema.get("minimum").and_then(|v| v.as_f64()) {
-            content.push_str(&format!("    if spec.{field_name} != null then\n"));
-            content.push_str(&format!(
-                "      assert spec.{field_name} >= {minimum} : \"{field_name} must be at least {minimum}\";\n"
-            ));
+        let mut by_schema: BTreeMap<&str, Vec<&CustomValidator>> = BTreeMap::new();
+        let mut seen = HashSet::new();
+        for validator in validators {
+            if !is_identifier(&validator.name) {
+                warn!(
+                    "Skipping validator {:?} for {}: its name is not an identifier",
+                    validator.name, validator.schema
+                );
+                continue;
+            }
+            if !seen.insert((&validator.schema, &validator.name)) {
+                warn!(
+                    "Skipping duplicate validator {} for {}",
+                    validator.name, validator.schema
+                );
+                continue;
+            }
+            by_schema
+                .entry(validator.schema.as_str())
+                .or_default()
+                .push(validator);
         }
 
-        if let Some(maximum) = field_schema.get("maximum").and_then(|v| v.as_f64()) {
-            content.push_str(&format!("    if spec.{field_name} != null then\n"));
-            content.push_str(&format!(
-                "      assert spec.{field_name} <= {maximum} : \"{field_name} must be at most {maximum}\";\n"
-            ));
+        let mut content = String::new();
+        content.push_str("+ {\n");
+        content.push_str("  // Validators contributed by plugins\n");
+        content.push_str("  custom: {\n");
+        for (schema, schema_validators) in by_schema {
+            content.push_str(&format!("    {}: {{\n", jsonnet_string(schema)));
+            for validator in schema_validators {
+                content.push_str(&format!(
+                    "      {}: {},\n",
+                    jsonnet_string(&validator.name),
+                    validator.code
+                ));
+            }
+            content.push_str("    },\n");
         }
+        content.push_str("  },\n");
+        content.push_str("}\n");
 
-        Ok(content)
+        let validation_path = output_path.join("_validation.libsonnet");
+        let mut existing = std::fs::read_to_string(&validation_path)?;
+        existing.push_str(&content);
+        write_generated(&validation_path, &existing, self.newline)?;
+        Ok(())
     }
+}
 
-    /// Generate array validation
-    fn generate_array_validation(
-        &self,
-        field_name: &str,
-        field_schema: &serde_yaml::Value,
-    ) -> Result<String> {
-        let mut content = String::new();
-
-        if let Some(min_items) = field_schema.get("minItems").and_then(|v| v.as_u64()) {
-            content.push_str(&format!("    if spec.{field_name} != null then\n"));
-            content.push_str(&format!(
-                "      assert std.length(spec.{field_name}) >= {min_items} : \"{field_name} must have at least {min_items} items\";\n"
-            ));
-        }
+/// Shared check of a field's rule set
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldCheck {
+    /// Name of the check under `checks` in `_validation.libsonnet`
+    pub name: String,
 
-        if let Some(max_items) = field_schema.get("maxItems").and_then(|v| v.as_u64()) {
-            content.push_str(&format!("    if spec.{field_name} != null then\n"));
-            content.push_str(&format!(
-                "      assert std.length(spec.{field_name}) <= {max_items} : \"{field_name} must have at most {max_items} items\";\n"
-            ));
-        }
+    /// Jsonnet function asserting the rules on `(value, fieldName)`
+    pub code: String,
+}
 
-        Ok(content)
+/// Check of a field's schema, or `None` when the schema imposes no rules
+///
+/// The name is derived from the check's code, so fields with identical
+/// rules share one check across resources and runs. With `native_functions`,
+/// patterns and CIDRs are checked with `std.native` functions.
+pub fn field_check(field_schema: &serde_yaml::Value, native_functions: bool) -> Option<FieldCheck> {
+    let assertions = field_assertions(field_schema, native_functions);
+    if assertions.is_empty() {
+        return None;
     }
 
-    /// Generate object validation
-    fn generate_object_validation(
-        &self,
-        field_name: &str,
-        _field_schema: &serde_yaml::Value,
-    ) -> Result<String> {
-        let mut content = String::new();
+    let mut code = String::from("function(value, fieldName)\n");
+    for assertion in assertions {
+        code.push_str(&format!("      {assertion};\n"));
+    }
+    code.push_str("      value");
 
-        content.push_str(&format!("    if spec.{field_name} != null then\n"));
-        content.push_str(&format!(
-            "      assert std.type(spec.{field_name}) == \"object\" : \"{field_name} must be an object\";\n"
-        ));
+    let digest = format!("{:x}", Sha256::digest(code.as_bytes()));
+    Some(FieldCheck {
+        name: format!("check_{}", &digest[..12]),
+        code,
+    })
+}
 
-        Ok(content)
+/// Function matching a value against a regular expression
+fn regex_match(native_functions: bool) -> &'static str {
+    if native_functions {
+        "std.native(\"regexMatch\")"
+    } else {
+        "std.regexMatch"
     }
+}
 
-    /// Generate enum validation
-    fn generate_enum_validation(
-        &self,
-        field_name: &str,
-        enum_values: &serde_yaml::Sequence,
-    ) -> Result<String> {
-        let mut content = String::new();
+/// Assertions a field's schema imposes on its value
+fn field_assertions(field_schema: &serde_yaml::Value, native_functions: bool) -> Vec<String> {
+    let mut assertions = Vec::new();
+    let bound = |keyword: &str| field_schema.get(keyword).and_then(|v| v.as_u64());
+    let limit = |keyword: &str| field_schema.get(keyword).and_then(|v| v.as_f64());
+
+    match field_schema.get("type").and_then(|t| t.as_str()) {
+        Some("string") => {
+            if let Some(min_length) = bound("minLength") {
+                assertions.push(format!(
+                    "assert std.length(value) >= {min_length} : fieldName + \" must be at least {min_length} characters\""
+                ));
+            }
+            if let Some(max_length) = bound("maxLength") {
+                assertions.push(format!(
+                    "assert std.length(value) <= {max_length} : fieldName + \" must be at most {max_length} characters\""
+                ));
+            }
+            if let Some(pattern) = field_schema.get("pattern").and_then(|v| v.as_str()) {
+                let pattern = jsonnet_string(pattern);
+                let regex_match = regex_match(native_functions);
+                assertions.push(format!(
+                    "assert {regex_match}({pattern}, value) : fieldName + \" must match pattern \" + {pattern}"
+                ));
+            }
+            // The standard library cannot parse CIDRs
+            if native_functions
+                && field_schema.get("format").and_then(|v| v.as_str()) == Some("cidr")
+            {
+                assertions.push(
+                    "assert std.native(\"isCidr\")(value) : fieldName + \" must be a CIDR\""
+                        .to_string(),
+                );
+            }
+        }
+        Some("integer" | "number") => {
+            if let Some(minimum) = limit("minimum") {
+                assertions.push(format!(
+                    "assert value >= {minimum} : fieldName + \" must be at least {minimum}\""
+                ));
+            }
+            if let Some(maximum) = limit("maximum") {
+                assertions.push(format!(
+                    "assert value <= {maximum} : fieldName + \" must be at most {maximum}\""
+                ));
+            }
+        }
+        Some("array") => {
+            if let Some(min_items) = bound("minItems") {
+                assertions.push(format!(
+                    "assert std.length(value) >= {min_items} : fieldName + \" must have at least {min_items} items\""
+                ));
+            }
+            if let Some(max_items) = bound("maxItems") {
+                assertions.push(format!(
+                    "assert std.length(value) <= {max_items} : fieldName + \" must have at most {max_items} items\""
+                ));
+            }
+        }
+        Some("object") => {
+            assertions.push(
+                "assert std.type(value) == \"object\" : fieldName + \" must be an object\""
+                    .to_string(),
+            );
+        }
+        _ => {}
+    }
 
-        let enum_strings: Vec<String> = enum_values
+    if let Some(enum_values) = field_schema.get("enum").and_then(|e| e.as_sequence()) {
+        let allowed: Vec<String> = enum_values
             .iter()
-            .filter_map(|v| v.as_str().map(|s| format!("\"{s}\"")))
+            .filter_map(|v| v.as_str().map(jsonnet_string))
             .collect();
-
-        content.push_str(&format!("    if spec.{field_name} != null then\n"));
-        content.push_str(&format!(
-            "      assert std.member(spec.{}, [{}]) : \"{} must be one of [{}]\";\n",
-            field_name,
-            enum_strings.join(", "),
-            field_name,
-            enum_strings.join(", ")
+        let allowed = allowed.join(", ");
+        assertions.push(format!(
+            "assert std.member([{allowed}], value) : fieldName + \" must be one of [{}]\"",
+            allowed.replace('\\', "\\\\").replace('"', "\\\"")
         ));
+    }
 
-        Ok(content)
+    assertions
+}
+
+/// Name of a field in error messages
+///
+/// Fields whose declaration was located are named with their source
+/// location, e.g. `replicas (crds/widget.yaml:42)`.
+fn field_label(schema: &CrdSchema, field_name: &str) -> String {
+    match schema.field_lines.get(field_name) {
+        Some(line) => format!("{} ({}:{})", field_name, schema.source_path.display(), line),
+        None => field_name.to_string(),
     }
+}
 
-    /// Generate validation utilities
-    pub async fn generate_validation_utilities(&self, output_path: &Path) -> Result<()> {
-        let validation_path = output_path.join("_validation.libsonnet");
-        let content = r#"// Validation utilities
-{
-  // Common validation functions
-  assertRequired: function(field, value, fieldName) {
-    assert value != null : fieldName + " is required";
-    value
-  },
-  
-  assertString: function(value, fieldName) {
-    assert std.type(value) == "string" : fieldName + " must be a string";
-    value
-  },
-  
-  assertNumber: function(value, fieldName) {
-    assert std.type(value) == "number" : fieldName + " must be a number";
-    value
-  },
-  
-  assertBoolean: function(value, fieldName) {
-    assert std.type(value) == "boolean" : fieldName + " must be a boolean";
-    value
-  },
-  
-  assertArray: function(value, fieldName) {
-    assert std.type(value) == "array" : fieldName + " must be an array";
-    value
-  },
-  
-  assertObject: function(value, fieldName) {
-    assert std.type(value) == "object" : fieldName + " must be an object";
-    value
-  },
-  
-  assertEnum: function(value, allowedValues, fieldName) {
-    assert std.member(value, allowedValues) : fieldName + " must be one of " + std.join(", ", allowedValues);
-    value
-  },
-  
-  assertPattern: function(value, pattern, fieldName) {
-    assert std.regexMatch(pattern, value) : fieldName + " must match pattern " + pattern;
-    value
-  },
-  
-  assertMinLength: function(value, minLength, fieldName) {
-    assert std.length(value) >= minLength : fieldName + " must be at least " + minLength + " characters";
-    value
-  },
-  
-  assertMaxLength: function(value, maxLength, fieldName) {
-    assert std.length(value) <= maxLength : fieldName + " must be at most " + maxLength + " characters";
-    value
-  },
-  
-  assertMinValue: function(value, minValue, fieldName) {
-    assert value >= minValue : fieldName + " must be at least " + minValue;
-    value
-  },
-  
-  assertMaxValue: function(value, maxValue, fieldName) {
-    assert value <= maxValue : fieldName + " must be at most " + maxValue;
-    value
-  },
+/// Whether a name can be used as a Jsonnet field without quoting
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
-"#;
 
-        std::fs::write(validation_path, content)?;
-        Ok(())
+/// Jsonnet string literal of a value
+fn jsonnet_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| format!("\"{value}\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_append_custom_validators() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let generator = ValidationGenerator::new();
+        generator
+            .generate_validation_utilities(temp_dir.path(), &[])
+            .await
+            .unwrap();
+
+        let validator = CustomValidator {
+            schema: "Widget".to_string(),
+            name: "requireTeamLabel".to_string(),
+            code: "function(metadata) std.objectHas(metadata.labels, 'team')".to_string(),
+        };
+        let invalid = CustomValidator {
+            schema: "Gadget\"s".to_string(),
+            name: "team\": 1, x".to_string(),
+            ..validator.clone()
+        };
+        let quoted = CustomValidator {
+            schema: "Gadget\"s".to_string(),
+            ..validator.clone()
+        };
+        generator
+            .append_custom_validators(
+                temp_dir.path(),
+                &[validator.clone(), validator, invalid, quoted],
+            )
+            .unwrap();
+
+        let content =
+            std::fs::read_to_string(temp_dir.path().join("_validation.libsonnet")).unwrap();
+        assert!(content.contains("}\n+ {\n"));
+        assert!(content.contains("    \"Widget\": {\n"));
+        assert!(content.contains("    \"Gadget\\\"s\": {\n"));
+        assert_eq!(content.matches("\"requireTeamLabel\"").count(), 2);
+        assert!(!content.contains("team\\\": 1"));
+    }
+
+    #[tokio::test]
+    async fn test_field_checks_are_shared() {
+        let schema = |kind: &str, yaml: &str| CrdSchema {
+            name: format!("{}s.example.com", kind.to_lowercase()),
+            group: "example.com".to_string(),
+            version: "v1".to_string(),
+            api_version: "example.com/v1".to_string(),
+            kind: kind.to_string(),
+            schema: serde_yaml::from_str(yaml).unwrap(),
+            source_path: std::path::PathBuf::from("crds.yaml"),
+            validation_rules: Default::default(),
+            schema_analysis: Default::default(),
+            categories: Vec::new(),
+            printer_columns: Vec::new(),
+            conversion: Default::default(),
+            field_lines: Default::default(),
+        };
+        let mut widget = schema(
+            "Widget",
+            "type: object\nproperties:\n  name: {type: string, maxLength: 63}\n  owner: {type: string, maxLength: 63}\n  replicas: {type: integer, minimum: 1}\n",
+        );
+        widget.field_lines.insert("name".to_string(), 3);
+        let gadget = schema(
+            "Gadget",
+            "type: object\nproperties:\n  label: {type: string, maxLength: 63}\n  paused: {type: boolean}\n",
+        );
+
+        let check = field_check(&widget.schema["properties"]["name"], false).unwrap();
+        assert_eq!(
+            field_check(&gadget.schema["properties"]["label"], false),
+            Some(check.clone())
+        );
+        assert_eq!(
+            field_check(&gadget.schema["properties"]["paused"], false),
+            None
+        );
+
+        let generator = ValidationGenerator::new();
+        let functions = generator.generate_validation_functions(&widget).unwrap();
+        assert!(functions.contains(&format!(
+            "validate.checks.{}(spec.owner, \"owner\")",
+            check.name
+        )));
+        assert!(functions.contains(&format!(
+            "validate.checks.{}(spec.name, \"name (crds.yaml:3)\")",
+            check.name
+        )));
+        assert!(!functions.contains("maxLength"));
+        assert!(!functions.contains("must be at most"));
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        generator
+            .generate_validation_utilities(temp_dir.path(), &[&widget, &gadget])
+            .await
+            .unwrap();
+        let content =
+            std::fs::read_to_string(temp_dir.path().join("_validation.libsonnet")).unwrap();
+        assert_eq!(content.matches("must be at most 63 characters").count(), 1);
+        assert_eq!(content.matches("    check_").count(), 2);
+        assert!(content.contains(&format!("    {}: function(value, fieldName)\n", check.name)));
+        assert!(content.ends_with("  },\n}\n"));
+
+        let native = |yaml: &str| {
+            field_check(&serde_yaml::from_str(yaml).unwrap(), true)
+                .unwrap()
+                .code
+        };
+        assert!(native("{type: string, pattern: '^[a-z]+$'}")
+            .contains("assert std.native(\"regexMatch\")(\"^[a-z]+$\", value)"));
+        assert!(native("{type: string, format: cidr}").contains("std.native(\"isCidr\")(value)"));
+        assert_eq!(
+            field_check(
+                &serde_yaml::from_str("{type: string, format: cidr}").unwrap(),
+                false
+            ),
+            None
+        );
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        ValidationGenerator::new()
+            .with_native_functions(true)
+            .generate_validation_utilities(temp_dir.path(), &[])
+            .await
+            .unwrap();
+        let content =
+            std::fs::read_to_string(temp_dir.path().join("_validation.libsonnet")).unwrap();
+        assert!(!content.contains("std.regexMatch("));
+        assert!(content.contains("assertSemverAtLeast: function(value, minimum, fieldName)"));
     }
 }
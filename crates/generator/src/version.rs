@@ -0,0 +1,98 @@
+//! Version stamps of generated libraries
+//!
+//! Every library's `index.libsonnet` carries the version of the schemas it
+//! was generated from as the hidden field `__version__`, so configurations can
+//! report which version they were built against.
+
+/// Name of the field holding a library's version
+pub const VERSION_FIELD: &str = "__version__";
+
+/// Version of a library: the source's tag, or else its short commit SHA
+///
+/// Artifact digests such as `sha256:<hex>` are shortened like commits.
+pub fn library_version(tag: Option<&str>, commit: Option<&str>) -> Option<String> {
+    if let Some(tag) = tag.filter(|tag| !tag.is_empty()) {
+        return Some(tag.to_string());
+    }
+    let commit = commit?;
+    let hex = commit.split_once(':').map_or(commit, |(_, hex)| hex);
+    (!hex.is_empty()).then(|| hex.chars().take(7).collect())
+}
+
+/// Add the version field to the content of an `index.libsonnet`
+///
+/// The field goes right after the opening brace of the index object,
+/// replacing an earlier stamp; content without one is returned unchanged.
+pub fn stamp_index(content: &str, version: &str) -> String {
+    let mut stamped = String::with_capacity(content.len());
+    let mut done = false;
+    for line in content.split_inclusive('\n') {
+        if line.trim_start().starts_with(VERSION_FIELD) {
+            continue;
+        }
+        stamped.push_str(line);
+        if !done && line.trim_end() == "{" {
+            stamped.push_str(&format!(
+                "  {VERSION_FIELD}:: {},\n",
+                serde_json::to_string(version).unwrap_or_default()
+            ));
+            done = true;
+        }
+    }
+    stamped
+}
+
+/// Version stamped into the content of an `index.libsonnet`, if any
+pub fn read_version(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let value = line
+            .trim()
+            .strip_prefix(VERSION_FIELD)?
+            .strip_prefix("::")?
+            .trim()
+            .strip_suffix(',')?;
+        serde_json::from_str(value).ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_stamp() {
+        assert_eq!(
+            library_version(Some("v1.2.3"), Some("0123456789abcdef")),
+            Some("v1.2.3".to_string())
+        );
+        assert_eq!(
+            library_version(None, Some("0123456789abcdef")),
+            Some("0123456".to_string())
+        );
+        assert_eq!(
+            library_version(Some(""), Some("sha256:fedcba9876543210")),
+            Some("fedcba9".to_string())
+        );
+        assert_eq!(library_version(None, None), None);
+
+        let index =
+            "// Main index file\n{\n  v1: import \"./example.com_v1/_index.libsonnet\",\n}\n";
+        let stamped = stamp_index(index, "v1.2.3");
+        assert_eq!(
+            stamped,
+            "// Main index file\n{\n  __version__:: \"v1.2.3\",\n  v1: import \"./example.com_v1/_index.libsonnet\",\n}\n"
+        );
+        assert_eq!(read_version(&stamped), Some("v1.2.3".to_string()));
+        assert_eq!(
+            read_version(&stamp_index(&stamped, "v1.3.0")),
+            Some("v1.3.0".to_string())
+        );
+        assert_eq!(
+            stamp_index(&stamped, "v1.3.0")
+                .matches(VERSION_FIELD)
+                .count(),
+            1
+        );
+        assert_eq!(read_version(index), None);
+    }
+}
@@ -0,0 +1,139 @@
+//! Typed warnings of generation runs
+//!
+//! Every warning a source raises carries a category, a stable code and, where
+//! there is one, the schema path it concerns, so configurations can suppress
+//! known-benign warnings by code or path and fail runs on whole categories.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Kind of problem a warning reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningCategory {
+    /// Schemas that could only be generated in part or after recovery
+    Schema,
+
+    /// Violations of policy rules configured to warn
+    Policy,
+
+    /// Libraries over the output size budgets
+    Budget,
+
+    /// Retried fetches of upstreams
+    Fetch,
+
+    /// Warnings reported by plugins
+    Plugin,
+
+    /// Sources that yielded less than configured
+    Source,
+}
+
+impl WarningCategory {
+    /// Every category, in declaration order
+    pub const ALL: [WarningCategory; 6] = [
+        WarningCategory::Schema,
+        WarningCategory::Policy,
+        WarningCategory::Budget,
+        WarningCategory::Fetch,
+        WarningCategory::Plugin,
+        WarningCategory::Source,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarningCategory::Schema => "schema",
+            WarningCategory::Policy => "policy",
+            WarningCategory::Budget => "budget",
+            WarningCategory::Fetch => "fetch",
+            WarningCategory::Plugin => "plugin",
+            WarningCategory::Source => "source",
+        }
+    }
+
+    /// Parse a category by name
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.as_str() == name)
+    }
+}
+
+impl fmt::Display for WarningCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A warning raised while processing a source
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Warning {
+    /// Kind of problem
+    pub category: WarningCategory,
+
+    /// Stable identifier of the warning, e.g. `unresolved-type`
+    pub code: String,
+
+    /// Source that raised the warning, filled in once the source is processed
+    pub source: String,
+
+    /// Schema, or field of a schema as `Kind.spec.field`, the warning concerns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// Description of the problem
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new(category: WarningCategory, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            category,
+            code: code.to_string(),
+            source: String::new(),
+            path: None,
+            message: message.into(),
+        }
+    }
+
+    /// The warning concerning a schema path
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]", self.message, self.code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warning() {
+        let warning = Warning::new(
+            WarningCategory::Schema,
+            "unresolved-type",
+            "Unresolved type for Widget.spec.size: no type",
+        )
+        .with_path("Widget.spec.size");
+        assert_eq!(
+            warning.to_string(),
+            "Unresolved type for Widget.spec.size: no type [unresolved-type]"
+        );
+        assert_eq!(
+            serde_json::to_value(&warning).unwrap()["category"],
+            "schema"
+        );
+        assert_eq!(
+            WarningCategory::parse("policy"),
+            Some(WarningCategory::Policy)
+        );
+        assert_eq!(WarningCategory::parse("lint"), None);
+    }
+}
@@ -0,0 +1,50 @@
+//! Writing generated files
+//!
+//! Every generated file is written through [`write_generated`], so output is
+//! UTF-8 without a byte order mark, ends with a newline and uses the
+//! configured line endings on every platform.
+
+use anyhow::Result;
+use std::path::Path;
+
+use crate::config::Newline;
+
+/// Normalize generated content to the given line endings with a final newline
+pub fn normalize(content: &str, newline: Newline) -> String {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    if content.is_empty() {
+        return String::new();
+    }
+
+    let mut normalized = content.replace("\r\n", "\n");
+    if !normalized.ends_with('\n') {
+        normalized.push('\n');
+    }
+
+    match newline {
+        Newline::Lf => normalized,
+        Newline::Crlf => normalized.replace('\n', "\r\n"),
+    }
+}
+
+/// Write a generated file with normalized line endings
+pub fn write_generated(path: &Path, content: &str, newline: Newline) -> Result<()> {
+    std::fs::write(path, normalize(content, newline))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("{\r\n}", Newline::Lf), "{\n}\n");
+        assert_eq!(normalize("\u{feff}{\n}\n", Newline::Lf), "{\n}\n");
+        assert_eq!(
+            normalize("{\r\n  a: 1,\n}", Newline::Crlf),
+            "{\r\n  a: 1,\r\n}\r\n"
+        );
+        assert_eq!(normalize("", Newline::Crlf), "");
+    }
+}
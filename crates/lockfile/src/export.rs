@@ -0,0 +1,127 @@
+//! Stable export of the lockfile for external tooling
+//!
+//! The lockfile's own layout follows what generation needs and may change
+//! between releases. The export flattens it into sorted lists with a format
+//! version of its own, so dashboards and policy bots can rely on its shape.
+
+use crate::lockfile::Lockfile;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Version of the export format, bumped on incompatible changes
+pub const EXPORT_FORMAT_VERSION: u32 = 2;
+
+/// Generation state recorded in a lockfile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockfileExport {
+    /// Version of the export format
+    pub format_version: u32,
+
+    /// When the lockfile was last written
+    pub generated_at: DateTime<Utc>,
+
+    /// Version of the tool that wrote the lockfile
+    pub tool_version: String,
+
+    /// Locked sources, by name
+    pub sources: Vec<SourceExport>,
+
+    /// Generated files, by path
+    pub files: Vec<FileExport>,
+}
+
+/// A locked source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceExport {
+    /// Name of the source
+    pub name: String,
+
+    /// Upstream URL, without credentials
+    pub url: String,
+
+    /// Reference the source was locked at
+    #[serde(rename = "ref")]
+    pub ref_name: String,
+
+    /// Commit SHA the source was locked at, empty for sources locked by digest
+    pub commit_sha: String,
+
+    /// Digest of the content the source was locked at, for URL, release,
+    /// fixture and library sources
+    pub digest: Option<String>,
+
+    /// When the source was last fetched
+    pub fetched_at: DateTime<Utc>,
+
+    /// Filters applied to the source
+    pub filters: Vec<String>,
+
+    /// SPDX identifier of the source's license, if detected
+    pub license: Option<String>,
+
+    /// Digests of the schemas behind each generated schema file, by relative path
+    pub schema_digests: BTreeMap<String, String>,
+}
+
+/// A generated file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileExport {
+    /// Path of the file
+    pub path: PathBuf,
+
+    /// SHA256 checksum of the file
+    pub sha256: String,
+
+    /// Size of the file in bytes
+    pub size: u64,
+
+    /// Source that generated the file
+    pub source: Option<String>,
+
+    /// Plugin that contributed the file
+    pub plugin: Option<String>,
+}
+
+impl From<&Lockfile> for LockfileExport {
+    fn from(lockfile: &Lockfile) -> Self {
+        let mut sources: Vec<SourceExport> = lockfile
+            .sources
+            .iter()
+            .map(|(name, entry)| SourceExport {
+                name: name.clone(),
+                url: entry.url.clone(),
+                ref_name: entry.ref_name.clone(),
+                commit_sha: entry.commit_sha.clone(),
+                digest: entry.digest.clone(),
+                fetched_at: entry.fetched_at,
+                filters: entry.filters.clone(),
+                license: entry.license.clone(),
+                schema_digests: entry.schema_digests.clone(),
+            })
+            .collect();
+        sources.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut files: Vec<FileExport> = lockfile
+            .files
+            .iter()
+            .map(|(path, checksum)| FileExport {
+                path: path.clone(),
+                sha256: checksum.sha256.clone(),
+                size: checksum.size,
+                source: checksum.metadata.source_id.clone(),
+                plugin: checksum.metadata.plugin.clone(),
+            })
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Self {
+            format_version: EXPORT_FORMAT_VERSION,
+            generated_at: lockfile.generated_at,
+            tool_version: lockfile.tool_version.clone(),
+            sources,
+            files,
+        }
+    }
+}
@@ -1,12 +1,16 @@
 //! Lockfile management for reproducible builds
 
+pub mod export;
 pub mod lockfile;
 pub mod manager;
+pub mod stats;
 pub mod types;
 
+pub use export::{FileExport, LockfileExport, SourceExport, EXPORT_FORMAT_VERSION};
 pub use lockfile::Lockfile;
 pub use manager::LockfileManager;
+pub use stats::{GenerationStatistics, PluginMetrics, SourceStatistics};
 pub use types::{
-    FileChecksum, FileMetadata, GenerationStatistics, IncrementalPlan, LockfileEntry,
-    SourceMetadata,
+    redact_url, FileChecksum, FileMetadata, IncrementalPlan, LockfileEntry, LockfileOrphans,
+    SourceHealth, SourceMetadata, SourceTimings,
 };
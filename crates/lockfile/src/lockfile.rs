@@ -1,10 +1,12 @@
 //! Main lockfile implementation
 
-use crate::types::{FileChecksum, GenerationStatistics, LockfileEntry};
+use crate::stats::GenerationStatistics;
+use crate::types::{FileChecksum, LockfileEntry, LockfileOrphans, SourceHealth, SourceTimings};
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
 /// Lockfile structure for tracking generation state
@@ -20,16 +22,27 @@ pub struct Lockfile {
     pub tool_version: String,
 
     /// Source entries
+    #[serde(serialize_with = "sorted")]
     pub sources: HashMap<String, LockfileEntry>,
 
     /// Generated files checksums
+    #[serde(serialize_with = "sorted")]
     pub files: HashMap<PathBuf, FileChecksum>,
 
     /// Dependencies between sources
+    #[serde(serialize_with = "sorted")]
     pub dependencies: HashMap<String, Vec<String>>,
 
     /// Generation statistics
     pub statistics: GenerationStatistics,
+
+    /// Average processing times by source name, across runs
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub timings: BTreeMap<String, SourceTimings>,
+
+    /// Consecutive failures of sources that failed their latest run, by source name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub health: BTreeMap<String, SourceHealth>,
 }
 
 impl Default for Lockfile {
@@ -49,6 +62,8 @@ impl Lockfile {
             files: HashMap::new(),
             dependencies: HashMap::new(),
             statistics: GenerationStatistics::default(),
+            timings: BTreeMap::new(),
+            health: BTreeMap::new(),
         }
     }
 
@@ -80,6 +95,30 @@ impl Lockfile {
         self.files.insert(file_path, checksum);
     }
 
+    /// Add file checksums in one batch
+    pub fn add_files(&mut self, checksums: impl IntoIterator<Item = (PathBuf, FileChecksum)>) {
+        self.files.extend(checksums);
+    }
+
+    /// Checksums of files, hashed in parallel
+    ///
+    /// Files whose size and modification time match the checksum recorded for
+    /// them, such as schema files kept unchanged from an earlier run, reuse it
+    /// without being read. Files that cannot be read are left out.
+    pub fn checksum_files(&self, paths: Vec<PathBuf>) -> Vec<(PathBuf, FileChecksum)> {
+        paths
+            .into_par_iter()
+            .filter_map(|path| {
+                let metadata = std::fs::metadata(&path).ok()?;
+                let checksum = match self.files.get(&path) {
+                    Some(recorded) if recorded.matches(&metadata) => recorded.clone(),
+                    _ => FileChecksum::from_file(&path).ok()?,
+                };
+                Some((path, checksum))
+            })
+            .collect()
+    }
+
     /// Add a dependency relationship
     pub fn add_dependency(&mut self, source_id: String, depends_on: String) {
         self.dependencies
@@ -88,10 +127,10 @@ impl Lockfile {
             .push(depends_on);
     }
 
-    /// Check if a source has changed
+    /// Check if a source has changed since the commit or content digest it is pinned at
     pub fn source_changed(&self, source_id: &str, current_commit: &str) -> bool {
         if let Some(entry) = self.sources.get(source_id) {
-            entry.commit_sha != current_commit
+            entry.revision() != current_commit
         } else {
             true // New source
         }
@@ -162,6 +201,118 @@ impl Lockfile {
         self.files = files;
     }
 
+    /// Drop everything the machine or time of a run decides
+    ///
+    /// Timestamps are set to the Unix epoch, and processing times, statistics
+    /// and timing averages are cleared, so runs generating the same files
+    /// write the same lockfile.
+    pub fn make_hermetic(&mut self) {
+        self.generated_at = DateTime::UNIX_EPOCH;
+        for entry in self.sources.values_mut() {
+            entry.fetched_at = DateTime::UNIX_EPOCH;
+            entry.metadata.processing_time_ms = 0;
+        }
+        for checksum in self.files.values_mut() {
+            checksum.modified_at = DateTime::UNIX_EPOCH;
+            checksum.metadata.generated_at = None;
+        }
+        self.statistics = GenerationStatistics::default();
+        self.timings.clear();
+    }
+
+    /// Fold the times of the sources a run processed into their averages
+    ///
+    /// Sources that failed are left out, as they may have stopped early.
+    pub fn record_timings(&mut self, statistics: &GenerationStatistics) {
+        for (source, source_statistics) in &statistics.sources {
+            if source_statistics.error_count == 0 {
+                self.timings.entry(source.clone()).or_default().record(
+                    source_statistics.clone_ms,
+                    source_statistics.parse_ms + source_statistics.generate_ms,
+                );
+            }
+        }
+    }
+
+    /// Record whether a source failed in a run, with the error if it did
+    ///
+    /// A success clears the source's failures. A failure is counted, and
+    /// quarantines the source once it has failed `quarantine_after` runs in a
+    /// row; returns whether this failure quarantined it.
+    pub fn record_health(
+        &mut self,
+        source: &str,
+        error: Option<&str>,
+        quarantine_after: Option<u32>,
+    ) -> bool {
+        let Some(error) = error else {
+            self.health.remove(source);
+            return false;
+        };
+        let health = self.health.entry(source.to_string()).or_default();
+        health.consecutive_failures += 1;
+        health.last_error = error.to_string();
+        let quarantine = !health.quarantined
+            && quarantine_after.is_some_and(|after| health.consecutive_failures >= after);
+        health.quarantined |= quarantine;
+        quarantine
+    }
+
+    /// Names of the quarantined sources, sorted
+    pub fn quarantined_sources(&self) -> Vec<String> {
+        self.health
+            .iter()
+            .filter(|(_, health)| health.quarantined)
+            .map(|(source, _)| source.clone())
+            .collect()
+    }
+
+    /// Entries of the sources `is_configured` rejects, and the files they generated
+    ///
+    /// Files recorded without a source are never orphans.
+    pub fn orphans(&self, is_configured: impl Fn(&str) -> bool) -> LockfileOrphans {
+        let mut sources: Vec<String> = self
+            .sources
+            .keys()
+            .filter(|source_id| !is_configured(source_id))
+            .cloned()
+            .collect();
+        sources.sort();
+
+        let mut files: Vec<PathBuf> = self
+            .files
+            .iter()
+            .filter(|(_, checksum)| {
+                checksum
+                    .metadata
+                    .source_id
+                    .as_deref()
+                    .is_some_and(|source_id| !is_configured(source_id))
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        files.sort();
+
+        LockfileOrphans { sources, files }
+    }
+
+    /// Remove orphaned entries, along with the dependencies of and on their sources
+    pub fn prune(&mut self, orphans: &LockfileOrphans) {
+        for source_id in &orphans.sources {
+            self.sources.remove(source_id);
+            self.dependencies.remove(source_id);
+            self.health.remove(source_id);
+        }
+        for dependencies in self.dependencies.values_mut() {
+            dependencies.retain(|dependency| !orphans.sources.contains(dependency));
+        }
+        self.dependencies
+            .retain(|_, dependencies| !dependencies.is_empty());
+        for file in &orphans.files {
+            self.files.remove(file);
+        }
+    }
+
     /// Get generation order based on dependencies
     pub fn get_generation_order(&self) -> Result<Vec<String>> {
         let mut order = Vec::new();
@@ -244,6 +395,16 @@ impl Lockfile {
     }
 }
 
+/// Serialize a map in key order, so saving a lockfile is deterministic
+fn sorted<S, K, V>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    K: Ord + Serialize,
+    V: Serialize,
+{
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -265,12 +426,22 @@ mod tests {
             vec!["test.com/v1".to_string()],
         );
         lockfile.add_source("test".to_string(), entry);
+        lockfile.statistics.sources.insert(
+            "test".to_string(),
+            crate::stats::SourceStatistics {
+                clone_ms: 120,
+                bytes_written: 2048,
+                schemas_skipped: 3,
+                ..Default::default()
+            },
+        );
 
         let yaml = serde_yaml::to_string(&lockfile).unwrap();
         let parsed: Lockfile = serde_yaml::from_str(&yaml).unwrap();
 
         assert_eq!(parsed.sources.len(), 1);
         assert!(parsed.sources.contains_key("test"));
+        assert_eq!(parsed.statistics, lockfile.statistics);
     }
 
     #[test]
@@ -292,6 +463,18 @@ mod tests {
 
         // New source
         assert!(lockfile.source_changed("new", "abc123"));
+
+        // Sources pinned by content are compared by digest
+        let entry = crate::types::LockfileEntry::with_digest(
+            "https://example.com/crds.yaml".to_string(),
+            String::new(),
+            "sha256:abc".to_string(),
+            vec![],
+        );
+        assert!(entry.commit_sha.is_empty());
+        lockfile.add_source("url".to_string(), entry);
+        assert!(!lockfile.source_changed("url", "sha256:abc"));
+        assert!(lockfile.source_changed("url", "sha256:def"));
     }
 
     #[test]
@@ -309,6 +492,99 @@ mod tests {
         assert!(dependents.contains(&"source3".to_string()));
     }
 
+    #[test]
+    fn test_hermetic() {
+        let lockfile = |order: &[&str], clone_ms: u64| {
+            let mut lockfile = Lockfile::new();
+            for source_id in order {
+                lockfile.add_source(
+                    source_id.to_string(),
+                    crate::types::LockfileEntry::new(
+                        format!("https://github.com/test/{source_id}.git"),
+                        "main".to_string(),
+                        "abc123".to_string(),
+                        vec![],
+                    ),
+                );
+                lockfile.add_file(
+                    PathBuf::from(format!("{source_id}.libsonnet")),
+                    FileChecksum::new("sha".to_string(), 1, Utc::now()),
+                );
+                lockfile.statistics.sources.insert(
+                    source_id.to_string(),
+                    crate::stats::SourceStatistics {
+                        clone_ms,
+                        ..Default::default()
+                    },
+                );
+            }
+            lockfile.record_timings(&lockfile.statistics.clone());
+            lockfile.make_hermetic();
+            serde_yaml::to_string(&lockfile).unwrap()
+        };
+
+        let yaml = lockfile(&["a", "b", "c", "d"], 120);
+        assert_eq!(yaml, lockfile(&["d", "c", "b", "a"], 450));
+        assert!(yaml.contains("generated_at: 1970-01-01T00:00:00Z"));
+        assert!(!yaml.contains("timings"));
+    }
+
+    #[test]
+    fn test_source_health() {
+        let mut lockfile = Lockfile::new();
+        assert!(!lockfile.record_health("flaky", Some("timed out"), Some(2)));
+        assert!(lockfile.quarantined_sources().is_empty());
+        assert!(lockfile.record_health("flaky", Some("not found"), Some(2)));
+        assert!(!lockfile.record_health("flaky", Some("not found"), Some(2)));
+        assert!(!lockfile.record_health("other", Some("failed"), None));
+        assert_eq!(lockfile.quarantined_sources(), ["flaky"]);
+        assert_eq!(lockfile.health["flaky"].consecutive_failures, 3);
+        assert_eq!(lockfile.health["flaky"].last_error, "not found");
+
+        let yaml = serde_yaml::to_string(&lockfile).unwrap();
+        let parsed: Lockfile = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed.health, lockfile.health);
+
+        // A success clears the failures and lifts the quarantine
+        lockfile.record_health("flaky", None, Some(2));
+        assert!(lockfile.quarantined_sources().is_empty());
+        assert_eq!(lockfile.health.keys().collect::<Vec<_>>(), ["other"]);
+    }
+
+    #[test]
+    fn test_orphans() {
+        let mut lockfile = Lockfile::new();
+        for source_id in ["kept", "removed"] {
+            lockfile.add_source(
+                source_id.to_string(),
+                crate::types::LockfileEntry::new(
+                    format!("https://github.com/test/{source_id}.git"),
+                    "main".to_string(),
+                    "abc123".to_string(),
+                    vec![],
+                ),
+            );
+            let mut checksum = FileChecksum::new("sha".to_string(), 1, Utc::now());
+            checksum.metadata.source_id = Some(source_id.to_string());
+            lockfile.add_file(PathBuf::from(format!("{source_id}.libsonnet")), checksum);
+        }
+        lockfile.add_file(
+            PathBuf::from("untracked.libsonnet"),
+            FileChecksum::new("sha".to_string(), 1, Utc::now()),
+        );
+        lockfile.add_dependency("kept".to_string(), "removed".to_string());
+
+        let orphans = lockfile.orphans(|source_id| source_id == "kept");
+        assert_eq!(orphans.sources, ["removed"]);
+        assert_eq!(orphans.files, [PathBuf::from("removed.libsonnet")]);
+
+        lockfile.prune(&orphans);
+        assert!(lockfile.orphans(|source_id| source_id == "kept").is_empty());
+        assert_eq!(lockfile.sources.len(), 1);
+        assert_eq!(lockfile.files.len(), 2);
+        assert!(lockfile.dependencies.is_empty());
+    }
+
     #[test]
     fn test_generation_order() {
         let mut lockfile = Lockfile::new();
@@ -340,4 +616,37 @@ mod tests {
         let source2_index = order.iter().position(|s| s == "source2").unwrap();
         assert!(source1_index < source2_index);
     }
+
+    #[test]
+    fn test_checksum_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let kept = temp_dir.path().join("kept.libsonnet");
+        let changed = temp_dir.path().join("changed.libsonnet");
+        std::fs::write(&kept, "{}").unwrap();
+        std::fs::write(&changed, "{}").unwrap();
+
+        let mut lockfile = Lockfile::new();
+        let checksums = lockfile.checksum_files(vec![
+            kept.clone(),
+            changed.clone(),
+            temp_dir.path().join("gone"),
+        ]);
+        assert_eq!(checksums.len(), 2);
+        lockfile.add_files(checksums);
+
+        // A recorded checksum is trusted while size and modification time match
+        lockfile.files.get_mut(&kept).unwrap().sha256 = "recorded".to_string();
+        lockfile.files.get_mut(&changed).unwrap().sha256 = "recorded".to_string();
+        std::fs::write(&changed, "{ a: 1 }").unwrap();
+
+        let checksums: HashMap<_, _> = lockfile
+            .checksum_files(vec![kept.clone(), changed.clone()])
+            .into_iter()
+            .collect();
+        assert_eq!(checksums[&kept].sha256, "recorded");
+        assert_eq!(
+            checksums[&changed].sha256,
+            FileChecksum::from_file(&changed).unwrap().sha256
+        );
+    }
 }
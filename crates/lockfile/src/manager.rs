@@ -1,10 +1,11 @@
 //! Lockfile manager for handling lockfile operations
 
+use crate::export::LockfileExport;
 use crate::lockfile::Lockfile;
 use crate::types::{FileChecksum, IncrementalPlan, LockfileEntry};
 use anyhow::Result;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
 /// Lockfile manager for handling lockfile operations
 pub struct LockfileManager {
@@ -72,26 +73,51 @@ impl LockfileManager {
         let dependent_sources = lockfile.get_dependent_sources(changed_sources);
         let files_to_regenerate = lockfile.get_files_to_regenerate(changed_sources);
         let can_incremental = lockfile.can_incremental_generate(changed_sources);
+        let source_estimates_ms: BTreeMap<String, u64> = changed_sources
+            .iter()
+            .chain(&dependent_sources)
+            .filter_map(|source| {
+                let timings = lockfile.timings.get(source)?;
+                Some((source.clone(), timings.estimate_ms()))
+            })
+            .collect();
+        let estimated_time_ms =
+            self.estimate_regeneration_time(&lockfile, &source_estimates_ms, &files_to_regenerate);
 
         Ok(IncrementalPlan {
             changed_sources: changed_sources.to_vec(),
             dependent_sources,
-            files_to_regenerate: files_to_regenerate.clone(),
+            files_to_regenerate,
             can_incremental,
-            estimated_time_ms: self.estimate_regeneration_time(&lockfile, &files_to_regenerate),
+            estimated_time_ms,
+            source_estimates_ms,
         })
     }
 
-    /// Estimate regeneration time based on file sizes and previous statistics
-    fn estimate_regeneration_time(&self, lockfile: &Lockfile, files: &[PathBuf]) -> u64 {
-        let total_size: u64 = files
+    /// Estimate regeneration time from the recorded timings of the planned sources
+    ///
+    /// Files of sources without recorded timings fall back to a size heuristic.
+    fn estimate_regeneration_time(
+        &self,
+        lockfile: &Lockfile,
+        source_estimates_ms: &BTreeMap<String, u64>,
+        files: &[PathBuf],
+    ) -> u64 {
+        let untimed_size: u64 = files
             .iter()
             .filter_map(|path| lockfile.files.get(path))
+            .filter(|checksum| {
+                checksum
+                    .metadata
+                    .source_id
+                    .as_ref()
+                    .is_none_or(|source| !source_estimates_ms.contains_key(source))
+            })
             .map(|checksum| checksum.size)
             .sum();
 
-        // Rough estimate: 1ms per KB
-        total_size / 1024
+        // Rough estimate for untimed sources: 1ms per KB
+        source_estimates_ms.values().sum::<u64>() + untimed_size / 1024
     }
 
     /// Clean up stale entries
@@ -126,6 +152,53 @@ impl LockfileManager {
         Ok(())
     }
 
+    /// Checksums of the files a source generated, by path
+    pub fn files_by_source(&self, source_id: &str) -> Result<Vec<(PathBuf, FileChecksum)>> {
+        let lockfile = self.load_or_create()?;
+        let mut files: Vec<_> = lockfile
+            .files
+            .into_iter()
+            .filter(|(_, checksum)| checksum.metadata.source_id.as_deref() == Some(source_id))
+            .collect();
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(files)
+    }
+
+    /// Locked sources, least recently fetched first
+    pub fn sources_by_age(&self) -> Result<Vec<(String, LockfileEntry)>> {
+        let lockfile = self.load_or_create()?;
+        let mut sources: Vec<_> = lockfile.sources.into_iter().collect();
+        sources.sort_by(|a, b| a.1.fetched_at.cmp(&b.1.fetched_at).then(a.0.cmp(&b.0)));
+        Ok(sources)
+    }
+
+    /// SHA256 checksum recorded for a generated file
+    pub fn file_digest(&self, path: &Path) -> Result<Option<String>> {
+        let lockfile = self.load_or_create()?;
+        Ok(lockfile
+            .files
+            .get(path)
+            .map(|checksum| checksum.sha256.clone()))
+    }
+
+    /// Generated files whose recorded SHA256 checksum is `sha256`
+    pub fn files_with_digest(&self, sha256: &str) -> Result<Vec<PathBuf>> {
+        let lockfile = self.load_or_create()?;
+        let mut files: Vec<_> = lockfile
+            .files
+            .into_iter()
+            .filter(|(_, checksum)| checksum.sha256 == sha256)
+            .map(|(path, _)| path)
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    /// Stable export of the lockfile for external tooling
+    pub fn export(&self) -> Result<LockfileExport> {
+        Ok(LockfileExport::from(&self.load_or_create()?))
+    }
+
     /// Get the default lockfile path
     pub fn default_path() -> PathBuf {
         PathBuf::from("gensonnet.lock")
@@ -146,4 +219,104 @@ mod tests {
         assert_eq!(plan.changed_sources, vec!["source1"]);
         assert_eq!(plan.total_sources(), 1);
     }
+
+    #[test]
+    fn test_incremental_plan_estimates_from_timings() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manager = LockfileManager::new(temp_dir.path().join("gensonnet.lock"));
+        let mut lockfile = Lockfile::new();
+        let mut statistics = crate::stats::GenerationStatistics::default();
+        statistics.sources.insert(
+            "timed".to_string(),
+            crate::stats::SourceStatistics {
+                clone_ms: 200,
+                parse_ms: 300,
+                generate_ms: 500,
+                ..Default::default()
+            },
+        );
+        lockfile.record_timings(&statistics);
+        manager.save(&lockfile).unwrap();
+
+        let plan = manager
+            .get_incremental_plan(&["timed".to_string(), "untimed".to_string()])
+            .unwrap();
+        assert_eq!(
+            plan.source_estimates_ms,
+            BTreeMap::from([("timed".to_string(), 1000)])
+        );
+        assert_eq!(plan.estimated_time_ms, 1000);
+    }
+
+    #[test]
+    fn test_queries_and_export() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manager = LockfileManager::new(temp_dir.path().join("gensonnet.lock"));
+        let mut lockfile = Lockfile::new();
+
+        let mut older = LockfileEntry::new(
+            "https://example.com/older.git".to_string(),
+            "main".to_string(),
+            "abc".to_string(),
+            vec![],
+        );
+        older.fetched_at -= chrono::Duration::hours(2);
+        lockfile.add_source("older".to_string(), older);
+        lockfile.add_source(
+            "newer".to_string(),
+            LockfileEntry::new(
+                "https://example.com/newer.git".to_string(),
+                "main".to_string(),
+                "def".to_string(),
+                vec![],
+            ),
+        );
+        for (path, sha256, source) in [
+            ("b.libsonnet", "1111", "older"),
+            ("a.libsonnet", "2222", "older"),
+            ("c.libsonnet", "1111", "newer"),
+        ] {
+            let mut checksum = FileChecksum::new(sha256.to_string(), 4, chrono::Utc::now());
+            checksum.metadata.source_id = Some(source.to_string());
+            lockfile.add_file(PathBuf::from(path), checksum);
+        }
+        manager.save(&lockfile).unwrap();
+
+        let files: Vec<_> = manager
+            .files_by_source("older")
+            .unwrap()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(
+            files,
+            [PathBuf::from("a.libsonnet"), PathBuf::from("b.libsonnet")]
+        );
+        let sources: Vec<_> = manager
+            .sources_by_age()
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(sources, ["older", "newer"]);
+        assert_eq!(
+            manager
+                .file_digest(Path::new("a.libsonnet"))
+                .unwrap()
+                .as_deref(),
+            Some("2222")
+        );
+        assert_eq!(manager.file_digest(Path::new("d.libsonnet")).unwrap(), None);
+        assert_eq!(
+            manager.files_with_digest("1111").unwrap(),
+            [PathBuf::from("b.libsonnet"), PathBuf::from("c.libsonnet")]
+        );
+
+        let export = serde_json::to_value(manager.export().unwrap()).unwrap();
+        assert_eq!(export["format_version"], crate::EXPORT_FORMAT_VERSION);
+        assert_eq!(export["sources"][0]["name"], "newer");
+        assert_eq!(export["sources"][0]["ref"], "main");
+        assert_eq!(export["files"][0]["path"], "a.libsonnet");
+        assert_eq!(export["files"][0]["source"], "older");
+    }
 }
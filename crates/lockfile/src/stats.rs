@@ -0,0 +1,116 @@
+//! Statistics of generation runs, shared by the CLI output, the lockfile and plugins
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Statistics of a generation run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationStatistics {
+    /// Total processing time in milliseconds
+    pub total_processing_time_ms: u64,
+
+    /// Number of sources processed
+    pub sources_processed: usize,
+
+    /// Number of files generated
+    pub files_generated: usize,
+
+    /// Number of errors encountered
+    pub error_count: usize,
+
+    /// Number of warnings encountered
+    pub warning_count: usize,
+
+    /// Cache hit rate (0.0 to 1.0)
+    pub cache_hit_rate: f64,
+
+    /// Share of schema files reused from the previous run (0.0 to 1.0)
+    #[serde(default)]
+    pub schema_cache_hit_rate: f64,
+
+    /// Bytes written to the output paths
+    #[serde(default)]
+    pub bytes_written: u64,
+
+    /// Breakdown by source name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sources: BTreeMap<String, SourceStatistics>,
+
+    /// Custom metrics reported by plugins, by plugin
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub plugin_metrics: BTreeMap<String, PluginMetrics>,
+}
+
+/// Statistics of one source
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourceStatistics {
+    /// Time spent cloning or fetching the source in milliseconds
+    pub clone_ms: u64,
+
+    /// Time spent parsing and extracting schemas in milliseconds
+    pub parse_ms: u64,
+
+    /// Time spent generating and publishing the library in milliseconds
+    pub generate_ms: u64,
+
+    /// Number of files generated
+    pub files_generated: usize,
+
+    /// Bytes written to the output path
+    pub bytes_written: u64,
+
+    /// Schema files left as published because their schema was unchanged
+    pub schemas_skipped: usize,
+
+    /// Number of errors encountered
+    pub error_count: usize,
+
+    /// Number of warnings encountered
+    pub warning_count: usize,
+
+    /// Custom metrics reported by the plugins that processed the source
+    #[serde(skip_serializing_if = "PluginMetrics::is_empty")]
+    pub plugin_metrics: PluginMetrics,
+}
+
+/// Custom counters and gauges a plugin reports, e.g. the number of types it
+/// skipped because they are generic
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginMetrics {
+    /// Counters by name; merged by adding them up
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub counters: BTreeMap<String, u64>,
+
+    /// Gauges by name; merged by keeping the highest value
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub gauges: BTreeMap<String, f64>,
+}
+
+impl PluginMetrics {
+    pub fn is_empty(&self) -> bool {
+        self.counters.is_empty() && self.gauges.is_empty()
+    }
+
+    /// Add to a counter
+    pub fn increment(&mut self, name: &str, by: u64) {
+        *self.counters.entry(name.to_string()).or_default() += by;
+    }
+
+    /// Set a gauge
+    pub fn set_gauge(&mut self, name: &str, value: f64) {
+        self.gauges.insert(name.to_string(), value);
+    }
+
+    /// Merge the metrics of another run or source into these
+    pub fn merge(&mut self, other: &PluginMetrics) {
+        for (name, value) in &other.counters {
+            self.increment(name, *value);
+        }
+        for (name, value) in &other.gauges {
+            let gauge = self.gauges.entry(name.clone()).or_insert(*value);
+            *gauge = gauge.max(*value);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+// Package natives registers the gensonnet native functions with go-jsonnet.
+//
+// It links the C ABI of the gensonnet-natives crate; build the crate with
+// `cargo build --release -p gensonnet-natives` and point cgo at the static
+// library, e.g. CGO_LDFLAGS="-L/path/to/target/release".
+package natives
+
+/*
+#cgo LDFLAGS: -lgensonnet_natives -ldl -lm -lpthread
+#include <stdlib.h>
+
+int gensonnet_regex_match(const char *pattern, const char *value);
+int gensonnet_semver_compare(const char *a, const char *b);
+int gensonnet_is_cidr(const char *value);
+*/
+import "C"
+
+import (
+	"fmt"
+	"unsafe"
+
+	"github.com/google/go-jsonnet"
+	"github.com/google/go-jsonnet/ast"
+)
+
+// Register adds every native function to vm.
+func Register(vm *jsonnet.VM) {
+	vm.NativeFunction(&jsonnet.NativeFunction{
+		Name:   "regexMatch",
+		Params: ast.Identifiers{"pattern", "value"},
+		Func: func(args []interface{}) (interface{}, error) {
+			return call2("regexMatch", args, func(pattern, value *C.char) (interface{}, error) {
+				switch C.gensonnet_regex_match(pattern, value) {
+				case 1:
+					return true, nil
+				case 0:
+					return false, nil
+				}
+				return nil, fmt.Errorf("regexMatch: invalid pattern %q", args[0])
+			})
+		},
+	})
+	vm.NativeFunction(&jsonnet.NativeFunction{
+		Name:   "semverCompare",
+		Params: ast.Identifiers{"a", "b"},
+		Func: func(args []interface{}) (interface{}, error) {
+			return call2("semverCompare", args, func(a, b *C.char) (interface{}, error) {
+				result := C.gensonnet_semver_compare(a, b)
+				if result == -2 {
+					return nil, fmt.Errorf("semverCompare: invalid version in %q, %q", args[0], args[1])
+				}
+				return float64(result), nil
+			})
+		},
+	})
+	vm.NativeFunction(&jsonnet.NativeFunction{
+		Name:   "isCidr",
+		Params: ast.Identifiers{"value"},
+		Func: func(args []interface{}) (interface{}, error) {
+			value, ok := args[0].(string)
+			if !ok {
+				return nil, fmt.Errorf("isCidr: value must be a string")
+			}
+			cValue := C.CString(value)
+			defer C.free(unsafe.Pointer(cValue))
+			return C.gensonnet_is_cidr(cValue) == 1, nil
+		},
+	})
+}
+
+// call2 converts two string arguments to C strings for f.
+func call2(name string, args []interface{}, f func(a, b *C.char) (interface{}, error)) (interface{}, error) {
+	a, okA := args[0].(string)
+	b, okB := args[1].(string)
+	if !okA || !okB {
+		return nil, fmt.Errorf("%s: arguments must be strings", name)
+	}
+	cA, cB := C.CString(a), C.CString(b)
+	defer C.free(unsafe.Pointer(cA))
+	defer C.free(unsafe.Pointer(cB))
+	return f(cA, cB)
+}
@@ -0,0 +1,64 @@
+//! C ABI of the native functions, for embedders outside Rust
+//!
+//! Strings are NUL-terminated UTF-8. Functions returning a boolean return 1
+//! or 0, and -1 when an argument is invalid; `gensonnet_semver_compare`
+//! returns -2 on invalid versions.
+
+use std::ffi::{c_char, c_int, CStr};
+
+/// Read a C string argument, `None` if it is null or not UTF-8
+///
+/// # Safety
+///
+/// `value` must be null or point to a NUL-terminated string.
+unsafe fn argument<'a>(value: *const c_char) -> Option<&'a str> {
+    if value.is_null() {
+        return None;
+    }
+    CStr::from_ptr(value).to_str().ok()
+}
+
+/// See [`crate::regex_match`]
+///
+/// # Safety
+///
+/// Both arguments must be null or point to NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn gensonnet_regex_match(
+    pattern: *const c_char,
+    value: *const c_char,
+) -> c_int {
+    match (argument(pattern), argument(value)) {
+        (Some(pattern), Some(value)) => match crate::regex_match(pattern, value) {
+            Ok(matched) => matched as c_int,
+            Err(_) => -1,
+        },
+        _ => -1,
+    }
+}
+
+/// See [`crate::semver_compare`]
+///
+/// # Safety
+///
+/// Both arguments must be null or point to NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn gensonnet_semver_compare(a: *const c_char, b: *const c_char) -> c_int {
+    match (argument(a), argument(b)) {
+        (Some(a), Some(b)) => crate::semver_compare(a, b).unwrap_or(-2),
+        _ => -2,
+    }
+}
+
+/// See [`crate::is_cidr`]
+///
+/// # Safety
+///
+/// `value` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn gensonnet_is_cidr(value: *const c_char) -> c_int {
+    match argument(value) {
+        Some(value) => crate::is_cidr(value) as c_int,
+        None => -1,
+    }
+}
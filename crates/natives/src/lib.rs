@@ -0,0 +1,101 @@
+//! Native functions for gensonnet libraries
+//!
+//! Libraries generated with `validation.native_functions` check patterns,
+//! CIDRs and versions with `std.native` functions instead of the standard
+//! library, which lacks regular expressions in go-jsonnet and is slow at
+//! parsing in every evaluator. Embedders register [`NATIVE_FUNCTIONS`] with
+//! their evaluator: Rust ones such as jrsonnet call the functions below, and
+//! go-jsonnet embedders link the C ABI in [`ffi`] through the bindings in
+//! `go/`.
+
+use regex::Regex;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+pub mod ffi;
+
+/// A native function as registered with an evaluator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NativeFunction {
+    /// Name passed to `std.native`
+    pub name: &'static str,
+
+    /// Parameter names, in order
+    pub params: &'static [&'static str],
+}
+
+/// Every native function generated libraries may call
+pub const NATIVE_FUNCTIONS: &[NativeFunction] = &[
+    NativeFunction {
+        name: "regexMatch",
+        params: &["pattern", "value"],
+    },
+    NativeFunction {
+        name: "semverCompare",
+        params: &["a", "b"],
+    },
+    NativeFunction {
+        name: "isCidr",
+        params: &["value"],
+    },
+];
+
+/// Whether `value` contains a match of `pattern`, like OpenAPI's `pattern`
+///
+/// Compiled patterns are cached, as generated checks use the same few
+/// patterns over and over.
+pub fn regex_match(pattern: &str, value: &str) -> Result<bool, String> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Regex>>> = OnceLock::new();
+    let mut cache = CACHE.get_or_init(Default::default).lock().unwrap();
+    if let Some(regex) = cache.get(pattern) {
+        return Ok(regex.is_match(value));
+    }
+    let regex = Regex::new(pattern).map_err(|e| format!("invalid pattern {pattern:?}: {e}"))?;
+    let matched = regex.is_match(value);
+    cache.insert(pattern.to_string(), regex);
+    Ok(matched)
+}
+
+/// Compare two semantic versions, returning -1, 0 or 1
+///
+/// A leading `v`, as in Kubernetes and git tags, is ignored.
+pub fn semver_compare(a: &str, b: &str) -> Result<i32, String> {
+    let parse = |version: &str| {
+        semver::Version::parse(version.strip_prefix('v').unwrap_or(version))
+            .map_err(|e| format!("invalid version {version:?}: {e}"))
+    };
+    Ok(match parse(a)?.cmp(&parse(b)?) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    })
+}
+
+/// Whether `value` is an IPv4 or IPv6 CIDR such as `10.0.0.0/8`
+pub fn is_cidr(value: &str) -> bool {
+    value.parse::<ipnet::IpNet>().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_native_functions() {
+        assert_eq!(regex_match("^[a-z]+$", "widget"), Ok(true));
+        assert_eq!(regex_match("^[a-z]+$", "Widget"), Ok(false));
+        assert_eq!(regex_match("[0-9]", "v1"), Ok(true));
+        assert!(regex_match("(", "x").is_err());
+
+        assert_eq!(semver_compare("v1.2.3", "1.10.0"), Ok(-1));
+        assert_eq!(semver_compare("1.2.3", "v1.2.3"), Ok(0));
+        assert_eq!(semver_compare("2.0.0", "2.0.0-rc.1"), Ok(1));
+        assert!(semver_compare("1.2", "1.2.0").is_err());
+
+        assert!(is_cidr("10.0.0.0/8"));
+        assert!(is_cidr("fd00::/64"));
+        assert!(!is_cidr("10.0.0.1"));
+        assert!(!is_cidr("10.0.0.0/33"));
+    }
+}
@@ -213,6 +213,7 @@ impl AbstractAstPlugin {
                 PluginCapability::SchemaExtraction,
                 PluginCapability::AstProcessing,
             ],
+            commands: Vec::new(),
         }
     }
 }
@@ -266,6 +267,7 @@ impl Plugin for AbstractAstPlugin {
                 files_processed: 1,
                 schemas_extracted: schemas_count,
                 files_generated: 0,
+                metrics: Default::default(),
             },
             warnings: parse_result.warnings,
             errors: parse_result.errors,
@@ -318,7 +320,7 @@ impl AbstractAstPlugin {
         // Convert schema content to Jsonnet
         match &schema.content {
             serde_yaml::Value::Mapping(map) => {
-                jsonnet.push_str(&format!("{{\n"));
+                jsonnet.push_str("{\n");
                 for (key, value) in map {
                     if let Some(key_str) = key.as_str() {
                         jsonnet.push_str(&format!(
@@ -328,7 +330,7 @@ impl AbstractAstPlugin {
                         ));
                     }
                 }
-                jsonnet.push_str(&format!("}}\n"));
+                jsonnet.push_str("}\n");
             }
             _ => {
                 jsonnet.push_str(&format!("{}\n", self.value_to_jsonnet(&schema.content)));
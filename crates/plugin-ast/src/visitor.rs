@@ -53,7 +53,7 @@ impl DefaultAstVisitor {
         let source_file = self
             .current_file
             .as_ref()
-            .map(|f| std::path::PathBuf::from(f))
+            .map(std::path::PathBuf::from)
             .unwrap_or_else(|| std::path::PathBuf::from("unknown"));
 
         match node.node_type {
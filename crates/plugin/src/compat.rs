@@ -0,0 +1,171 @@
+//! Plugin API versioning
+//!
+//! The traits and types of this crate form the plugin API, versioned as
+//! `major.minor`. Within a major version the API only grows: trait methods
+//! are added with default implementations and manifest fields with serde
+//! defaults, so a plugin built against an older minor version keeps working.
+//! Anything else bumps the major version.
+//!
+//! Plugins declare the version they were built against, through their
+//! factory or the `requirements.api_version` of their manifest, and are
+//! checked against this build when they are loaded.
+
+use anyhow::{anyhow, Result};
+
+/// Version of the plugin API defined by this crate
+pub const PLUGIN_API_VERSION: &str = "1.0";
+
+/// Plugin API versions and the gensonnet release that introduced each
+pub const API_COMPATIBILITY: &[(&str, &str)] = &[("1.0", "0.1.0")];
+
+/// Parse a `major.minor` API version
+fn parse_api_version(version: &str) -> Option<(u32, u32)> {
+    let (major, minor) = version.split_once('.').unwrap_or((version, "0"));
+    Some((major.trim().parse().ok()?, minor.trim().parse().ok()?))
+}
+
+/// Whether a plugin built against `version` runs on this build
+pub fn is_compatible(version: &str) -> bool {
+    let host = parse_api_version(PLUGIN_API_VERSION).expect("valid plugin API version");
+    parse_api_version(version).is_some_and(|(major, minor)| major == host.0 && minor <= host.1)
+}
+
+/// Check that a plugin built against `version` runs on this build
+pub fn check_api_version(plugin: &str, version: &str) -> Result<()> {
+    if parse_api_version(version).is_none() {
+        return Err(anyhow!(
+            "Plugin {} declares an invalid plugin API version '{}'",
+            plugin,
+            version
+        ));
+    }
+    if is_compatible(version) {
+        return Ok(());
+    }
+
+    let supported: Vec<String> = API_COMPATIBILITY
+        .iter()
+        .filter(|(api_version, _)| is_compatible(api_version))
+        .map(|(api_version, release)| format!("{api_version} (since {release})"))
+        .collect();
+    Err(anyhow!(
+        "Plugin {} was built against plugin API {}, but this gensonnet supports {}",
+        plugin,
+        version,
+        supported.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Plugin, PluginCapability, PluginConfig, PluginContext, PluginResult};
+    use std::path::{Path, PathBuf};
+
+    #[derive(Clone)]
+    struct NoopPlugin {
+        config: PluginConfig,
+    }
+
+    impl NoopPlugin {
+        fn new(config: PluginConfig) -> Self {
+            Self { config }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Plugin for NoopPlugin {
+        fn metadata(&self) -> crate::PluginMetadata {
+            crate::plugin_manifest! {
+                id: self.config.plugin_id.clone(),
+                name: "Noop",
+                version: "1.0.0",
+                description: "Extracts nothing",
+                types: ["noop"],
+                capabilities: [Parse],
+            }
+            .metadata
+        }
+
+        async fn initialize(&self, _context: &PluginContext) -> Result<()> {
+            Ok(())
+        }
+
+        async fn can_handle(&self, source_path: &Path) -> Result<bool> {
+            Ok(source_path.extension().is_some_and(|e| e == "noop"))
+        }
+
+        async fn process_source(
+            &self,
+            _source_path: &Path,
+            _context: &PluginContext,
+        ) -> Result<PluginResult> {
+            Ok(crate::testing::empty_result())
+        }
+
+        async fn generate_code(
+            &self,
+            _schemas: &[crate::ExtractedSchema],
+            _context: &PluginContext,
+        ) -> Result<Vec<PathBuf>> {
+            Ok(Vec::new())
+        }
+
+        async fn cleanup(&self, _context: &PluginContext) -> Result<()> {
+            Ok(())
+        }
+
+        fn clone_box(&self) -> Box<dyn Plugin> {
+            Box::new(self.clone())
+        }
+    }
+
+    crate::plugin_factory!(NoopPluginFactory => NoopPlugin, ["noop"]);
+
+    #[tokio::test]
+    async fn test_api_compatibility() {
+        assert!(check_api_version("noop", PLUGIN_API_VERSION).is_ok());
+        assert!(check_api_version("noop", "1").is_ok());
+        let err = check_api_version("noop", "2.0").unwrap_err();
+        assert!(err.to_string().contains("supports 1.0 (since 0.1.0)"));
+        assert!(check_api_version("noop", "1.9").is_err());
+        assert!(check_api_version("noop", "one").is_err());
+
+        let manifest = crate::plugin_manifest! {
+            id: "noop:1.0",
+            name: "Noop",
+            version: "1.0.0",
+            description: "Extracts nothing",
+            types: ["noop"],
+            capabilities: [Parse, SchemaExtraction],
+        };
+        assert!(manifest.check_compatibility().is_ok());
+        assert_eq!(
+            manifest.config.enabled_capabilities,
+            vec![PluginCapability::Parse, PluginCapability::SchemaExtraction]
+        );
+        let mut manifest: crate::PluginManifest =
+            serde_yaml::from_str(&serde_yaml::to_string(&manifest).unwrap()).unwrap();
+        manifest.requirements.as_mut().unwrap().api_version = Some("2.0".to_string());
+        assert!(manifest.check_compatibility().is_err());
+
+        let manager = crate::PluginManager::new();
+        manager
+            .register_factory("noop".to_string(), Box::new(NoopPluginFactory))
+            .await;
+        manager
+            .create_plugin("noop", manifest.config.clone())
+            .await
+            .unwrap();
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let source = temp_dir.path().join("widgets.noop");
+        std::fs::write(&source, "").unwrap();
+        let plugin = manager.get_plugin("noop:1.0").await.unwrap();
+        let context = crate::testing::test_context(temp_dir.path(), "noop:1.0");
+        let result = crate::testing::process_once(plugin.as_ref(), &source, &context)
+            .await
+            .unwrap();
+        assert!(result.schemas.is_empty());
+    }
+}
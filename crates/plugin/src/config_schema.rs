@@ -0,0 +1,245 @@
+//! Validation of plugin configuration
+//!
+//! Plugins publish a JSON Schema for their `config:` block, in their
+//! manifest or through their factory, and the configuration is checked
+//! against it before the plugin is created. The common structural keywords
+//! are supported: `type`, `enum`, `properties`, `required`,
+//! `additionalProperties`, `items` and the numeric, length and size bounds.
+//! Other keywords are ignored. The same checks validate CRD samples against
+//! the schemas of their resources.
+
+use anyhow::{anyhow, Result};
+use serde_yaml::Value;
+use std::fmt;
+
+/// A configuration value that does not match the plugin's schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// Path of the value, e.g. `config.rules[0].name`
+    pub path: String,
+
+    /// What is wrong with the value
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Validate a plugin configuration against its schema
+///
+/// A missing configuration is validated as an empty object.
+pub fn validate_config(schema: &Value, config: &Value) -> Vec<ConfigError> {
+    let empty = Value::Mapping(Default::default());
+    let config = if config.is_null() { &empty } else { config };
+
+    validate_value(schema, config, "config")
+}
+
+/// Validate any value against a schema, reporting paths below `path`
+pub fn validate_value(schema: &Value, value: &Value, path: &str) -> Vec<ConfigError> {
+    let mut errors = Vec::new();
+    validate_at(schema, value, path, &mut errors);
+    errors
+}
+
+/// Check a plugin configuration against its schema, listing every mismatch
+pub fn check_config(plugin_id: &str, schema: &Value, config: &Value) -> Result<()> {
+    let errors = validate_config(schema, config);
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    let details: Vec<String> = errors.iter().map(|e| format!("  {e}")).collect();
+    Err(anyhow!(
+        "Invalid configuration for plugin {}:\n{}",
+        plugin_id,
+        details.join("\n")
+    ))
+}
+
+/// JSON Schema type name of a value
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Sequence(_) => "array",
+        Value::Mapping(_) => "object",
+        Value::Tagged(tagged) => type_name(&tagged.value),
+    }
+}
+
+fn matches_type(expected: &str, actual: &str) -> bool {
+    expected == actual || (expected == "number" && actual == "integer")
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str, errors: &mut Vec<ConfigError>) {
+    let error = |errors: &mut Vec<ConfigError>, message: String| {
+        errors.push(ConfigError {
+            path: path.to_string(),
+            message,
+        })
+    };
+
+    let actual = type_name(value);
+    let expected: Vec<&str> = match schema.get("type") {
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Sequence(names)) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !expected.is_empty() && !expected.iter().any(|name| matches_type(name, actual)) {
+        error(
+            errors,
+            format!("expected {}, got {}", expected.join(" or "), actual),
+        );
+        return;
+    }
+
+    if let Some(Value::Sequence(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed
+                .iter()
+                .map(|v| {
+                    serde_yaml::to_string(v)
+                        .unwrap_or_default()
+                        .trim()
+                        .to_string()
+                })
+                .collect();
+            error(errors, format!("must be one of {}", allowed.join(", ")));
+        }
+    }
+
+    let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+    let count = |keyword: &str| schema.get(keyword).and_then(Value::as_u64);
+    match value {
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or_default();
+            if let Some(minimum) = bound("minimum").filter(|minimum| n < *minimum) {
+                error(errors, format!("must be at least {minimum}"));
+            }
+            if let Some(maximum) = bound("maximum").filter(|maximum| n > *maximum) {
+                error(errors, format!("must be at most {maximum}"));
+            }
+        }
+        Value::String(s) => {
+            let length = s.chars().count() as u64;
+            if let Some(min) = count("minLength").filter(|min| length < *min) {
+                error(errors, format!("must be at least {min} characters long"));
+            }
+            if let Some(max) = count("maxLength").filter(|max| length > *max) {
+                error(errors, format!("must be at most {max} characters long"));
+            }
+        }
+        Value::Sequence(items) => {
+            let length = items.len() as u64;
+            if let Some(min) = count("minItems").filter(|min| length < *min) {
+                error(errors, format!("must have at least {min} items"));
+            }
+            if let Some(max) = count("maxItems").filter(|max| length > *max) {
+                error(errors, format!("must have at most {max} items"));
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_at(item_schema, item, &format!("{path}[{index}]"), errors);
+                }
+            }
+        }
+        Value::Mapping(mapping) => {
+            if let Some(Value::Sequence(required)) = schema.get("required") {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !mapping.contains_key(name) {
+                        error(errors, format!("missing required field `{name}`"));
+                    }
+                }
+            }
+
+            let properties = schema.get("properties").and_then(Value::as_mapping);
+            for (key, field) in mapping {
+                let Some(name) = key.as_str() else {
+                    continue;
+                };
+                let field_path = format!("{path}.{name}");
+                match (
+                    properties.and_then(|p| p.get(name)),
+                    schema.get("additionalProperties"),
+                ) {
+                    (Some(field_schema), _) => {
+                        validate_at(field_schema, field, &field_path, errors)
+                    }
+                    (None, Some(Value::Bool(false))) => errors.push(ConfigError {
+                        path: field_path,
+                        message: "unknown field".to_string(),
+                    }),
+                    (None, Some(extra @ Value::Mapping(_))) => {
+                        validate_at(extra, field, &field_path, errors)
+                    }
+                    (None, _) => {}
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_config() {
+        let schema: Value = serde_yaml::from_str(
+            r#"
+type: object
+required: [prefix]
+additionalProperties: false
+properties:
+  prefix: {type: string, minLength: 1}
+  mode: {enum: [strict, lenient]}
+  rules:
+    type: array
+    items:
+      type: object
+      required: [name]
+      properties:
+        name: {type: string}
+        weight: {type: number, minimum: 0}
+"#,
+        )
+        .unwrap();
+
+        let valid: Value = serde_yaml::from_str(
+            "prefix: Acme\nmode: strict\nrules: [{name: kinds, weight: 1}, {name: fields, weight: 0.5}]",
+        )
+        .unwrap();
+        assert!(validate_config(&schema, &valid).is_empty());
+
+        let invalid: Value =
+            serde_yaml::from_str("prefix: 42\nmode: loose\nrules: [{weight: -1}]\nextra: true")
+                .unwrap();
+        let errors: Vec<String> = validate_config(&schema, &invalid)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "config.prefix: expected string, got integer",
+                "config.mode: must be one of strict, lenient",
+                "config.rules[0]: missing required field `name`",
+                "config.rules[0].weight: must be at least 0",
+                "config.extra: unknown field",
+            ]
+        );
+
+        let err = check_config("naming", &schema, &Value::Null).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("config: missing required field `prefix`"));
+    }
+}
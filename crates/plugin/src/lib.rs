@@ -1,23 +1,91 @@
 //! Common plugin infrastructure for gensonnet
 //!
 //! This crate provides the core plugin architecture, traits, and types
-//! that all gensonnet plugins must implement.
+//! that all gensonnet plugins must implement. It is also the SDK for third
+//! party plugins: the API is versioned (see [`compat`]), and the
+//! [`plugin_factory!`] and [`plugin_manifest!`] macros and the [`testing`]
+//! helpers cover the boilerplate of a plugin crate. Plugins can also ship
+//! as dynamic libraries, see [`native`].
 
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-
+use tokio::sync::{mpsc, RwLock};
+
+pub mod compat;
+pub mod config_schema;
+mod macros;
+pub mod manifest;
+pub mod native;
+pub mod pool;
+pub mod state;
+pub mod store;
+pub mod testing;
 pub mod traits;
 
+pub use compat::{check_api_version, PLUGIN_API_VERSION};
+pub use config_schema::{check_config, validate_config, validate_value, ConfigError};
+pub use jsonnet_lockfile::PluginMetrics;
+pub use manifest::{PluginManifest, PluginRequirements};
+pub use pool::{PooledPlugin, DEFAULT_CONCURRENCY};
+pub use state::{PluginState, SharedState, StateView};
+pub use store::PluginStore;
 pub use traits::*;
 
+#[doc(hidden)]
+pub mod __private {
+    pub use anyhow::Result;
+    pub use async_trait::async_trait;
+    pub use serde_yaml::Value;
+}
+
 /// Plugin identifier
 pub type PluginId = String;
 
+/// Which plugin processes a source
+///
+/// Serialized as `auto` or the plugin id.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum PluginBinding {
+    /// Pick the registered plugin with the highest priority and score
+    #[default]
+    Auto,
+
+    /// Always use the plugin with this id
+    Plugin(PluginId),
+}
+
+impl PluginBinding {
+    /// Whether the plugin is picked automatically
+    pub fn is_auto(&self) -> bool {
+        matches!(self, PluginBinding::Auto)
+    }
+}
+
+impl From<String> for PluginBinding {
+    fn from(value: String) -> Self {
+        if value == "auto" {
+            PluginBinding::Auto
+        } else {
+            PluginBinding::Plugin(value)
+        }
+    }
+}
+
+impl From<PluginBinding> for String {
+    fn from(binding: PluginBinding) -> Self {
+        match binding {
+            PluginBinding::Auto => "auto".to_string(),
+            PluginBinding::Plugin(id) => id,
+        }
+    }
+}
+
 /// Plugin metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginMetadata {
@@ -38,6 +106,10 @@ pub struct PluginMetadata {
 
     /// Plugin capabilities
     pub capabilities: Vec<PluginCapability>,
+
+    /// CLI subcommands the plugin contributes (used with the Cli capability)
+    #[serde(default)]
+    pub commands: Vec<CliCommandSpec>,
 }
 
 /// Plugin capabilities
@@ -60,6 +132,143 @@ pub enum PluginCapability {
 
     /// Can handle dependencies
     DependencyResolution,
+
+    /// Contributes CLI subcommands
+    Cli,
+
+    /// Provides schema transforms sources can list under `transforms:`
+    Transform,
+}
+
+/// A CLI subcommand contributed by a plugin, mounted under `x <plugin> <name>`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CliCommandSpec {
+    /// Subcommand name
+    pub name: String,
+
+    /// One-line description shown in help
+    #[serde(default)]
+    pub about: String,
+
+    /// Arguments accepted by the subcommand
+    #[serde(default)]
+    pub args: Vec<CliArgSpec>,
+}
+
+/// An argument of a plugin subcommand
+///
+/// Arguments are passed as `--<name> <value>`, or `--<name>` for flags.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CliArgSpec {
+    /// Argument name
+    pub name: String,
+
+    /// Description shown in help
+    #[serde(default)]
+    pub help: String,
+
+    /// Whether the argument must be given
+    #[serde(default)]
+    pub required: bool,
+
+    /// Whether the argument is a flag without a value
+    #[serde(default)]
+    pub flag: bool,
+
+    /// Value used when the argument is not given
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+impl CliCommandSpec {
+    /// Argument names taken by the command line itself
+    pub const RESERVED_ARGS: &'static [&'static str] = &["help", "version"];
+
+    /// Check that the command and its arguments can be mounted on the command line
+    ///
+    /// Names are made of ASCII letters, digits, `-` and `_`, and start with a
+    /// letter or digit. The command cannot be named `help`, and its arguments
+    /// need distinct names other than [`RESERVED_ARGS`](Self::RESERVED_ARGS).
+    pub fn validate(&self) -> Result<()> {
+        let is_name = |name: &str| {
+            name.starts_with(|c: char| c.is_ascii_alphanumeric())
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        };
+        if !is_name(&self.name) || self.name == "help" {
+            return Err(anyhow::anyhow!("Invalid command name {:?}", self.name));
+        }
+
+        let mut names = std::collections::HashSet::new();
+        for arg in &self.args {
+            if !is_name(&arg.name) || Self::RESERVED_ARGS.contains(&arg.name.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "Invalid argument name {:?} for command {}",
+                    arg.name,
+                    self.name
+                ));
+            }
+            if !names.insert(arg.name.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "Duplicate argument --{} for command {}",
+                    arg.name,
+                    self.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check arguments against the spec and fill in defaults
+    ///
+    /// Flags that are given have the value `true`.
+    pub fn resolve_args(
+        &self,
+        mut args: BTreeMap<String, String>,
+    ) -> Result<BTreeMap<String, String>> {
+        if let Some(unknown) = args
+            .keys()
+            .find(|name| !self.args.iter().any(|arg| &arg.name == *name))
+        {
+            return Err(anyhow::anyhow!(
+                "Unknown argument --{} for command {}",
+                unknown,
+                self.name
+            ));
+        }
+
+        for arg in &self.args {
+            if args.contains_key(&arg.name) {
+                continue;
+            }
+            match &arg.default {
+                Some(default) => {
+                    args.insert(arg.name.clone(), default.clone());
+                }
+                None if arg.required => {
+                    return Err(anyhow::anyhow!(
+                        "Missing required argument --{} for command {}",
+                        arg.name,
+                        self.name
+                    ));
+                }
+                None => {}
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+/// Output of a plugin subcommand
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandOutput {
+    /// Text printed to the terminal
+    pub output: String,
+
+    /// Whether the command succeeded; failed commands exit with a non-zero status
+    pub success: bool,
 }
 
 /// Plugin configuration
@@ -75,6 +284,61 @@ pub struct PluginConfig {
     pub enabled_capabilities: Vec<PluginCapability>,
 }
 
+/// Provenance of the source a plugin is processing
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SourceMetadata {
+    /// Source name from the configuration
+    pub name: String,
+
+    /// Git repository or artifact URL
+    pub repository: Option<String>,
+
+    /// Configured git reference
+    pub git_ref: Option<String>,
+
+    /// Resolved commit SHA (or artifact digest for URL sources)
+    pub commit: Option<String>,
+
+    /// File filters or include patterns of the source
+    pub filters: Vec<String>,
+
+    /// SPDX identifier of the source's license, if detected
+    #[serde(default)]
+    pub license: Option<String>,
+
+    /// Tag of the resolved commit, or the release tag of release sources
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+impl SourceMetadata {
+    /// Key identifying the source at its resolved commit with its filters,
+    /// for plugin caches, e.g. `widgets@0123abcd[crds/]`
+    pub fn cache_key(&self) -> String {
+        let mut key = self.name.clone();
+        if let Some(commit) = &self.commit {
+            key.push_str(&format!("@{commit}"));
+        }
+        if !self.filters.is_empty() {
+            key.push_str(&format!("[{}]", self.filters.join(",")));
+        }
+        key
+    }
+
+    /// One-line description of where generated code came from
+    pub fn provenance(&self) -> String {
+        let mut provenance = self.name.clone();
+        if let Some(repository) = &self.repository {
+            provenance.push_str(&format!(" ({repository}"));
+            if let Some(commit) = &self.commit {
+                provenance.push_str(&format!(" @ {commit}"));
+            }
+            provenance.push(')');
+        }
+        provenance
+    }
+}
+
 /// Plugin context for processing
 #[derive(Debug, Clone)]
 pub struct PluginContext {
@@ -87,8 +351,20 @@ pub struct PluginContext {
     /// Plugin configuration
     pub config: PluginConfig,
 
-    /// Shared state between plugins
-    pub shared_state: Arc<RwLock<HashMap<String, serde_yaml::Value>>>,
+    /// Source being processed
+    pub source: SourceMetadata,
+
+    /// Root of the persistent plugin state, if enabled
+    pub state_dir: Option<PathBuf>,
+
+    /// Whether schema constructs that cannot be represented should fail the source
+    pub strict_schemas: bool,
+
+    /// Whether invalid CRD manifests should fail the source instead of being recovered
+    pub strict_crds: bool,
+
+    /// State plugins share during a run
+    pub shared_state: SharedState,
 }
 
 impl PluginContext {
@@ -98,18 +374,117 @@ impl PluginContext {
             working_dir,
             output_dir,
             config,
-            shared_state: Arc::new(RwLock::new(HashMap::new())),
+            source: SourceMetadata::default(),
+            state_dir: None,
+            strict_schemas: false,
+            strict_crds: false,
+            shared_state: SharedState::new(),
         }
     }
 
+    /// Attach metadata of the source being processed
+    pub fn with_source(mut self, source: SourceMetadata) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Keep persistent plugin state below the given directory
+    pub fn with_state_dir(mut self, state_dir: PathBuf) -> Self {
+        self.state_dir = Some(state_dir);
+        self
+    }
+
+    /// Fail on unsupported schema constructs instead of falling back to plain objects
+    pub fn with_strict_schemas(mut self, strict_schemas: bool) -> Self {
+        self.strict_schemas = strict_schemas;
+        self
+    }
+
+    /// Fail on invalid CRD manifests instead of recovering what they declare
+    pub fn with_strict_crds(mut self, strict_crds: bool) -> Self {
+        self.strict_crds = strict_crds;
+        self
+    }
+
+    /// Persistent store of a plugin, if persistent state is enabled
+    ///
+    /// Unlike shared state, values in the store survive across runs.
+    pub fn store(&self, plugin_id: &str) -> Option<PluginStore> {
+        self.state_dir
+            .as_deref()
+            .map(|state_dir| PluginStore::open(state_dir, plugin_id))
+    }
+
+    /// Share the state of a run with other contexts
+    pub fn with_shared_state(mut self, shared_state: SharedState) -> Self {
+        self.shared_state = shared_state;
+        self
+    }
+
     /// Get a value from shared state
+    #[deprecated(note = "use the typed `state` and `state_of` instead")]
     pub async fn get_shared_value(&self, key: &str) -> Option<serde_yaml::Value> {
-        self.shared_state.read().await.get(key).cloned()
+        self.shared_state.values().read().await.get(key).cloned()
     }
 
     /// Set a value in shared state
+    #[deprecated(note = "use the typed `state` and `state_of` instead")]
     pub async fn set_shared_value(&self, key: String, value: serde_yaml::Value) {
-        self.shared_state.write().await.insert(key, value);
+        self.shared_state.values().write().await.insert(key, value);
+    }
+
+    /// The context as seen by a plugin, whose state is namespaced by its id
+    pub fn for_plugin(&self, plugin_id: &str) -> Self {
+        let mut context = self.clone();
+        context.config.plugin_id = plugin_id.to_string();
+        context
+    }
+
+    /// State of type `T` of the plugin the context is for, created empty on
+    /// first access
+    pub fn state<T>(&self) -> PluginState<T>
+    where
+        T: Default + Send + Sync + 'static,
+    {
+        self.shared_state.owned(&self.config.plugin_id)
+    }
+
+    /// State of type `T` another plugin shares, if it has created one
+    pub fn state_of<T>(&self, plugin_id: &str) -> Option<StateView<T>>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.shared_state.view(plugin_id)
+    }
+}
+
+/// Schemas a plugin streams while processing a source
+///
+/// The plugin runs on its own task and is held back once the channel is
+/// full, so only a bounded number of schemas is in memory at a time.
+pub struct SchemaStream {
+    receiver: mpsc::Receiver<ExtractedSchema>,
+    task: tokio::task::JoinHandle<Result<PluginStatistics>>,
+}
+
+impl SchemaStream {
+    /// Schemas a plugin may send ahead of the consumer
+    pub const CAPACITY: usize = 16;
+
+    /// Next schema, or `None` once the plugin has finished
+    pub async fn next(&mut self) -> Option<ExtractedSchema> {
+        self.receiver.recv().await
+    }
+
+    /// Wait for the plugin to finish, returning its statistics, or its error
+    /// if it failed
+    ///
+    /// Schemas not yet received are discarded.
+    pub async fn finish(self) -> Result<PluginStatistics> {
+        drop(self.receiver);
+        self.task
+            .await
+            .map_err(|e| anyhow::anyhow!("Plugin task failed: {}", e))?
     }
 }
 
@@ -151,8 +526,40 @@ pub struct ExtractedSchema {
     pub metadata: HashMap<String, serde_yaml::Value>,
 }
 
+impl ExtractedSchema {
+    /// Metadata key mapping each property to the line declaring it in `source_file`
+    pub const FIELD_LINES: &'static str = "field_lines";
+
+    /// Line each property is declared on in the source file, where known
+    pub fn field_lines(&self) -> BTreeMap<String, usize> {
+        self.metadata
+            .get(Self::FIELD_LINES)
+            .and_then(|lines| serde_yaml::from_value(lines.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Record the lines the schema's properties are declared on
+    pub fn set_field_lines(&mut self, lines: &BTreeMap<String, usize>) {
+        if lines.is_empty() {
+            self.metadata.remove(Self::FIELD_LINES);
+        } else if let Ok(lines) = serde_yaml::to_value(lines) {
+            self.metadata.insert(Self::FIELD_LINES.to_string(), lines);
+        }
+    }
+}
+
+/// Jsonnet validation snippet contributed by a plugin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorSnippet {
+    /// Field name of the validator in `_validation.libsonnet`
+    pub name: String,
+
+    /// Jsonnet expression implementing the validator (usually a function)
+    pub code: String,
+}
+
 /// Plugin processing statistics
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PluginStatistics {
     /// Processing time in milliseconds
     pub processing_time_ms: u64,
@@ -165,6 +572,11 @@ pub struct PluginStatistics {
 
     /// Number of files generated
     pub files_generated: usize,
+
+    /// Custom counters and gauges, aggregated into the generation statistics
+    /// instead of being lost in logs
+    #[serde(default, skip_serializing_if = "PluginMetrics::is_empty")]
+    pub metrics: PluginMetrics,
 }
 
 /// Plugin trait that all plugins must implement
@@ -179,6 +591,16 @@ pub trait Plugin: Send + Sync {
     /// Check if the plugin can handle the given source
     async fn can_handle(&self, source_path: &Path) -> Result<bool>;
 
+    /// How specifically the plugin handles a source; 0 means it cannot handle it
+    ///
+    /// When several plugins claim the same file type, the highest score wins
+    /// among plugins of equal priority. By convention 1 means the plugin
+    /// handles the file type and 2 or more that it recognised the content;
+    /// repository auto-detection only counts recognised files by default.
+    async fn score(&self, source_path: &Path) -> Result<u32> {
+        Ok(u32::from(self.can_handle(source_path).await?))
+    }
+
     /// Process a source and extract schemas
     async fn process_source(
         &self,
@@ -186,6 +608,27 @@ pub trait Plugin: Send + Sync {
         context: &PluginContext,
     ) -> Result<PluginResult>;
 
+    /// Process a source, sending schemas to `sender` as they are extracted
+    ///
+    /// Lets the generator write files as schemas arrive instead of holding all
+    /// of them. The default sends the schemas of `process_source` once it
+    /// completes; plugins for large sources override it.
+    async fn process_source_stream(
+        &self,
+        source_path: &Path,
+        context: &PluginContext,
+        sender: mpsc::Sender<ExtractedSchema>,
+    ) -> Result<PluginStatistics> {
+        let result = self.process_source(source_path, context).await?;
+        for schema in result.schemas {
+            // The consumer stopped listening
+            if sender.send(schema).await.is_err() {
+                break;
+            }
+        }
+        Ok(result.statistics)
+    }
+
     /// Generate code from extracted schemas
     async fn generate_code(
         &self,
@@ -193,6 +636,48 @@ pub trait Plugin: Send + Sync {
         context: &PluginContext,
     ) -> Result<Vec<PathBuf>>;
 
+    /// Contribute extra validators for a schema (used by plugins with the Validation capability)
+    async fn generate_validators(
+        &self,
+        _schema: &ExtractedSchema,
+        _context: &PluginContext,
+    ) -> Result<Vec<ValidatorSnippet>> {
+        Ok(Vec::new())
+    }
+
+    /// Apply one of the plugin's schema transforms (used by plugins with the Transform capability)
+    ///
+    /// The transform's configuration is in `context.config.config`. One schema
+    /// must be returned for each schema given, in the same order.
+    async fn transform_schemas(
+        &self,
+        transform: &str,
+        _schemas: Vec<ExtractedSchema>,
+        _context: &PluginContext,
+    ) -> Result<Vec<ExtractedSchema>> {
+        Err(anyhow::anyhow!(
+            "Plugin {} does not implement transform {}",
+            self.metadata().id,
+            transform
+        ))
+    }
+
+    /// Run one of the CLI subcommands declared in the plugin's metadata
+    ///
+    /// `args` have been checked against the command's spec, with defaults filled in.
+    async fn run_command(
+        &self,
+        command: &str,
+        _args: &BTreeMap<String, String>,
+        _context: &PluginContext,
+    ) -> Result<CommandOutput> {
+        Err(anyhow::anyhow!(
+            "Plugin {} does not implement command {}",
+            self.metadata().id,
+            command
+        ))
+    }
+
     /// Clean up plugin resources
     async fn cleanup(&self, context: &PluginContext) -> Result<()>;
 
@@ -209,17 +694,41 @@ pub trait PluginFactory: Send + Sync {
     /// Get supported plugin types
     fn supported_types(&self) -> Vec<String>;
 
+    /// Plugin API version the factory was built against
+    fn api_version(&self) -> &'static str {
+        PLUGIN_API_VERSION
+    }
+
+    /// JSON Schema of the `config:` block the plugins accept, if published
+    fn config_schema(&self) -> Option<serde_yaml::Value> {
+        None
+    }
+
     /// Clone the factory as a boxed trait object
     fn clone_box(&self) -> Box<dyn PluginFactory>;
 }
 
 /// Plugin manager for coordinating multiple plugins
+///
+/// Plugins run from a pool of instances, see [`pool`].
 pub struct PluginManager {
-    /// Registered plugins
-    plugins: Arc<RwLock<HashMap<PluginId, Box<dyn Plugin>>>>,
+    /// Registered plugin instances
+    plugins: Arc<RwLock<HashMap<PluginId, Arc<pool::PluginSlot>>>>,
+
+    /// Checkouts allowed at once per plugin, default [`DEFAULT_CONCURRENCY`]
+    concurrency: Arc<RwLock<HashMap<PluginId, usize>>>,
 
     /// Plugin factories
     factories: Arc<RwLock<HashMap<String, Box<dyn PluginFactory>>>>,
+
+    /// Priorities used to choose between plugins claiming the same source
+    priorities: Arc<RwLock<HashMap<PluginId, i32>>>,
+
+    /// CLI subcommands declared in plugin manifests, overriding plugin metadata
+    commands: Arc<RwLock<HashMap<PluginId, Vec<CliCommandSpec>>>>,
+
+    /// Config schemas declared in plugin manifests, overriding the factory's
+    config_schemas: Arc<RwLock<HashMap<PluginId, serde_yaml::Value>>>,
 }
 
 impl PluginManager {
@@ -227,7 +736,11 @@ impl PluginManager {
     pub fn new() -> Self {
         Self {
             plugins: Arc::new(RwLock::new(HashMap::new())),
+            concurrency: Arc::new(RwLock::new(HashMap::new())),
             factories: Arc::new(RwLock::new(HashMap::new())),
+            priorities: Arc::new(RwLock::new(HashMap::new())),
+            commands: Arc::new(RwLock::new(HashMap::new())),
+            config_schemas: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -236,13 +749,44 @@ impl PluginManager {
         self.factories.write().await.insert(factory_type, factory);
     }
 
+    /// Declare the schema a plugin's configuration is validated against
+    pub async fn declare_config_schema(&self, plugin_id: &str, schema: serde_yaml::Value) {
+        self.config_schemas
+            .write()
+            .await
+            .insert(plugin_id.to_string(), schema);
+    }
+
     /// Create and register a plugin
+    ///
+    /// The configuration is validated against the schema declared for the
+    /// plugin, or else the one its factory publishes.
     pub async fn create_plugin(&self, plugin_type: &str, config: PluginConfig) -> Result<()> {
         let factory = self.get_factory(plugin_type).await?;
+        check_api_version(plugin_type, factory.api_version())?;
+        let declared = self
+            .config_schemas
+            .read()
+            .await
+            .get(&config.plugin_id)
+            .cloned();
+        if let Some(schema) = declared.or_else(|| factory.config_schema()) {
+            check_config(&config.plugin_id, &schema, &config.config)?;
+        }
         let plugin = factory.create_plugin(config.clone()).await?;
         let plugin_id = config.plugin_id.clone();
-
-        self.plugins.write().await.insert(plugin_id, plugin);
+        let concurrency = self
+            .concurrency
+            .read()
+            .await
+            .get(&plugin_id)
+            .copied()
+            .unwrap_or(DEFAULT_CONCURRENCY);
+
+        self.plugins.write().await.insert(
+            plugin_id,
+            Arc::new(pool::PluginSlot::new(plugin, concurrency)),
+        );
         Ok(())
     }
 
@@ -255,10 +799,93 @@ impl PluginManager {
         Ok(factory.clone_box())
     }
 
-    /// Get a plugin by ID
+    /// Get a copy of a plugin by ID, e.g. to read its metadata
     pub async fn get_plugin(&self, plugin_id: &str) -> Option<Box<dyn Plugin>> {
         let plugins = self.plugins.read().await;
-        plugins.get(plugin_id).map(|p| p.as_ref().clone_box())
+        plugins.get(plugin_id).map(|slot| slot.plugin().clone_box())
+    }
+
+    /// Limit the sources a plugin processes at once (default [`DEFAULT_CONCURRENCY`])
+    pub async fn set_concurrency(&self, plugin_id: &str, concurrency: usize) {
+        self.concurrency
+            .write()
+            .await
+            .insert(plugin_id.to_string(), concurrency);
+        if let Some(slot) = self.plugins.read().await.get(plugin_id) {
+            slot.set_concurrency(concurrency);
+        }
+    }
+
+    /// Check out a plugin's instance, waiting while it is at its concurrency limit
+    ///
+    /// The instance is initialized with `context` on its first checkout.
+    pub async fn checkout(&self, plugin_id: &str, context: &PluginContext) -> Result<PooledPlugin> {
+        let slot = self
+            .plugins
+            .read()
+            .await
+            .get(plugin_id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Plugin {} is not registered", plugin_id))?;
+        slot.checkout(context).await
+    }
+
+    /// Set the priority of a plugin (default 0); higher priorities win
+    pub async fn set_priority(&self, plugin_id: &str, priority: i32) {
+        self.priorities
+            .write()
+            .await
+            .insert(plugin_id.to_string(), priority);
+    }
+
+    /// Declare the CLI subcommands of a plugin, e.g. from its manifest
+    pub async fn declare_commands(&self, plugin_id: &str, commands: Vec<CliCommandSpec>) {
+        self.commands
+            .write()
+            .await
+            .insert(plugin_id.to_string(), commands);
+    }
+
+    /// Pick the plugin for a source
+    ///
+    /// Candidates are the plugins with a non-zero score, ordered by priority,
+    /// then score, then plugin id.
+    pub async fn resolve_plugin(&self, source_path: &Path) -> Result<PluginId> {
+        self.best_plugin(source_path)
+            .await?
+            .map(|(plugin_id, _)| plugin_id)
+            .ok_or_else(|| {
+                anyhow::anyhow!("No plugin found that can handle source: {:?}", source_path)
+            })
+    }
+
+    /// The plugin [`resolve_plugin`](Self::resolve_plugin) picks for a source,
+    /// with its score, or `None` when no plugin handles the source
+    pub async fn best_plugin(&self, source_path: &Path) -> Result<Option<(PluginId, u32)>> {
+        let plugins = self.plugins.read().await;
+        let priorities = self.priorities.read().await;
+
+        let mut best: Option<(i32, u32, &PluginId)> = None;
+        for (plugin_id, slot) in plugins.iter() {
+            let score = slot.plugin().score(source_path).await?;
+            if score == 0 {
+                continue;
+            }
+
+            let priority = priorities.get(plugin_id).copied().unwrap_or_default();
+            let better = match best {
+                None => true,
+                Some((best_priority, best_score, best_id)) => {
+                    (priority, score, Reverse(plugin_id))
+                        > (best_priority, best_score, Reverse(best_id))
+                }
+            };
+            if better {
+                best = Some((priority, score, plugin_id));
+            }
+        }
+
+        Ok(best.map(|(_, score, plugin_id)| (plugin_id.clone(), score)))
     }
 
     /// Process a source with the appropriate plugin
@@ -267,18 +894,53 @@ impl PluginManager {
         source_path: &Path,
         context: &PluginContext,
     ) -> Result<PluginResult> {
-        let plugins = self.plugins.read().await;
+        self.process_source_with(&PluginBinding::Auto, source_path, context)
+            .await
+            .map(|(_, result)| result)
+    }
 
-        for plugin in plugins.values() {
-            if plugin.can_handle(source_path).await? {
-                return plugin.process_source(source_path, context).await;
-            }
-        }
+    /// Process a source with the plugin chosen by a binding, returning the plugin id
+    pub async fn process_source_with(
+        &self,
+        binding: &PluginBinding,
+        source_path: &Path,
+        context: &PluginContext,
+    ) -> Result<(PluginId, PluginResult)> {
+        let plugin_id = match binding {
+            PluginBinding::Auto => self.resolve_plugin(source_path).await?,
+            PluginBinding::Plugin(plugin_id) => plugin_id.clone(),
+        };
 
-        Err(anyhow::anyhow!(
-            "No plugin found that can handle source: {:?}",
-            source_path
-        ))
+        let context = context.for_plugin(&plugin_id);
+        let plugin = self.checkout(&plugin_id, &context).await?;
+        let result = plugin.process_source(source_path, &context).await?;
+
+        Ok((plugin_id, result))
+    }
+
+    /// Process a source with the plugin chosen by a binding, streaming its schemas
+    pub async fn process_source_stream_with(
+        &self,
+        binding: &PluginBinding,
+        source_path: &Path,
+        context: &PluginContext,
+    ) -> Result<(PluginId, SchemaStream)> {
+        let plugin_id = match binding {
+            PluginBinding::Auto => self.resolve_plugin(source_path).await?,
+            PluginBinding::Plugin(plugin_id) => plugin_id.clone(),
+        };
+
+        let context = context.for_plugin(&plugin_id);
+        let plugin = self.checkout(&plugin_id, &context).await?;
+        let (sender, receiver) = mpsc::channel(SchemaStream::CAPACITY);
+        let source_path = source_path.to_path_buf();
+        let task = tokio::spawn(async move {
+            plugin
+                .process_source_stream(&source_path, &context, sender)
+                .await
+        });
+
+        Ok((plugin_id, SchemaStream { receiver, task }))
     }
 
     /// Generate code for all plugins
@@ -288,9 +950,10 @@ impl PluginManager {
         context: &PluginContext,
     ) -> Result<Vec<PathBuf>> {
         let mut all_generated_files = Vec::new();
-        let plugins = self.plugins.read().await;
+        let plugin_ids: Vec<PluginId> = self.plugins.read().await.keys().cloned().collect();
 
-        for plugin in plugins.values() {
+        for plugin_id in plugin_ids {
+            let plugin = self.checkout(&plugin_id, context).await?;
             let files = plugin.generate_code(schemas, context).await?;
             all_generated_files.extend(files);
         }
@@ -298,12 +961,160 @@ impl PluginManager {
         Ok(all_generated_files)
     }
 
-    /// Clean up all plugins
+    /// Collect validators from plugins with the Validation capability, keyed by schema name
+    pub async fn generate_validators(
+        &self,
+        schemas: &[ExtractedSchema],
+        context: &PluginContext,
+    ) -> Result<BTreeMap<String, Vec<ValidatorSnippet>>> {
+        let mut validators: BTreeMap<String, Vec<ValidatorSnippet>> = BTreeMap::new();
+
+        // Visit plugins in a stable order so generated output is reproducible
+        let mut plugin_ids: Vec<PluginId> = self
+            .plugins
+            .read()
+            .await
+            .iter()
+            .filter(|(_, slot)| {
+                slot.plugin()
+                    .metadata()
+                    .capabilities
+                    .contains(&PluginCapability::Validation)
+            })
+            .map(|(plugin_id, _)| plugin_id.clone())
+            .collect();
+        plugin_ids.sort();
+
+        for plugin_id in plugin_ids {
+            let plugin = self.checkout(&plugin_id, context).await?;
+            for schema in schemas {
+                let snippets = plugin.generate_validators(schema, context).await?;
+                if !snippets.is_empty() {
+                    validators
+                        .entry(schema.name.clone())
+                        .or_default()
+                        .extend(snippets);
+                }
+            }
+        }
+
+        Ok(validators)
+    }
+
+    /// Apply a schema transform of a plugin with the Transform capability
+    ///
+    /// The plugin is given by its id or by its type, the part of the id before `:`.
+    pub async fn transform_schemas(
+        &self,
+        plugin: &str,
+        transform: &str,
+        schemas: Vec<ExtractedSchema>,
+        context: &PluginContext,
+    ) -> Result<Vec<ExtractedSchema>> {
+        let plugin_id = {
+            let plugins = self.plugins.read().await;
+            plugins
+                .keys()
+                .find(|plugin_id| plugin_id.as_str() == plugin)
+                .or_else(|| {
+                    plugins
+                        .keys()
+                        .find(|plugin_id| plugin_id.split(':').next() == Some(plugin))
+                })
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("No plugin {} is registered", plugin))?
+        };
+        let plugin = self.checkout(&plugin_id, context).await?;
+        if !plugin
+            .metadata()
+            .capabilities
+            .contains(&PluginCapability::Transform)
+        {
+            return Err(anyhow::anyhow!(
+                "Plugin {} does not provide schema transforms",
+                plugin_id
+            ));
+        }
+
+        let count = schemas.len();
+        let transformed = plugin
+            .transform_schemas(transform, schemas, context)
+            .await?;
+        if transformed.len() != count {
+            return Err(anyhow::anyhow!(
+                "Transform {} of plugin {} returned {} schemas for {}",
+                transform,
+                plugin_id,
+                transformed.len(),
+                count
+            ));
+        }
+
+        Ok(transformed)
+    }
+
+    /// CLI subcommands of plugins with the Cli capability, by plugin id
+    pub async fn cli_commands(&self) -> BTreeMap<PluginId, Vec<CliCommandSpec>> {
+        let declared = self.commands.read().await;
+        self.plugins
+            .read()
+            .await
+            .iter()
+            .filter_map(|(plugin_id, slot)| {
+                let metadata = slot.plugin().metadata();
+                let commands = match declared.get(plugin_id) {
+                    Some(commands) => commands.clone(),
+                    None if metadata.capabilities.contains(&PluginCapability::Cli) => {
+                        metadata.commands
+                    }
+                    None => return None,
+                };
+                (!commands.is_empty()).then(|| (plugin_id.clone(), commands))
+            })
+            .collect()
+    }
+
+    /// Run a CLI subcommand of a plugin
+    ///
+    /// The plugin is given by its id or by its type, the part of the id before `:`.
+    pub async fn run_command(
+        &self,
+        plugin: &str,
+        command: &str,
+        args: BTreeMap<String, String>,
+        context: &PluginContext,
+    ) -> Result<CommandOutput> {
+        let commands = self.cli_commands().await;
+        let (plugin_id, specs) = commands
+            .iter()
+            .find(|(plugin_id, _)| plugin_id.as_str() == plugin)
+            .or_else(|| {
+                commands
+                    .iter()
+                    .find(|(plugin_id, _)| plugin_id.split(':').next() == Some(plugin))
+            })
+            .ok_or_else(|| anyhow::anyhow!("No plugin {} provides CLI commands", plugin))?;
+        let spec = specs
+            .iter()
+            .find(|spec| spec.name == command)
+            .ok_or_else(|| anyhow::anyhow!("Plugin {} has no command {}", plugin_id, command))?;
+        let args = spec.resolve_args(args)?;
+
+        self.checkout(plugin_id, context)
+            .await?
+            .run_command(command, &args, context)
+            .await
+    }
+
+    /// Clean up the plugins initialized so far, once their running work finishes
+    ///
+    /// Plugins checked out again afterwards are initialized again.
     pub async fn cleanup(&self, context: &PluginContext) -> Result<()> {
-        let plugins = self.plugins.read().await;
+        let slots: Vec<Arc<pool::PluginSlot>> =
+            self.plugins.read().await.values().cloned().collect();
 
-        for plugin in plugins.values() {
-            plugin.cleanup(context).await?;
+        for slot in slots {
+            slot.shutdown(context).await?;
         }
 
         Ok(())
@@ -321,6 +1132,10 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    fn slot(plugin: Box<dyn Plugin>) -> Arc<pool::PluginSlot> {
+        Arc::new(pool::PluginSlot::new(plugin, DEFAULT_CONCURRENCY))
+    }
+
     #[tokio::test]
     async fn test_plugin_manager_creation() {
         let manager = PluginManager::new();
@@ -337,12 +1152,254 @@ mod tests {
             description: "A test plugin".to_string(),
             supported_types: vec!["test".to_string()],
             capabilities: vec![PluginCapability::Parse],
+            commands: Vec::new(),
         };
 
         assert_eq!(metadata.id, "test-plugin");
         assert_eq!(metadata.name, "Test Plugin");
     }
 
+    #[test]
+    fn test_cli_command_spec_validation() {
+        let arg = |name: &str| CliArgSpec {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let spec = |name: &str, args: Vec<CliArgSpec>| CliCommandSpec {
+            name: name.to_string(),
+            about: String::new(),
+            args,
+        };
+
+        assert!(spec("lint", vec![arg("strict"), arg("max_depth")])
+            .validate()
+            .is_ok());
+        assert!(spec("help", Vec::new()).validate().is_err());
+        assert!(spec("two words", Vec::new()).validate().is_err());
+        assert!(spec("lint", vec![arg("help")]).validate().is_err());
+        assert!(spec("lint", vec![arg("version")]).validate().is_err());
+        assert!(spec("lint", vec![arg("-x")]).validate().is_err());
+        assert!(spec("lint", vec![arg("strict"), arg("strict")])
+            .validate()
+            .is_err());
+    }
+
+    #[derive(Clone)]
+    struct LabelPolicyPlugin {
+        capabilities: Vec<PluginCapability>,
+    }
+
+    #[async_trait]
+    impl Plugin for LabelPolicyPlugin {
+        fn metadata(&self) -> PluginMetadata {
+            PluginMetadata {
+                id: "label-policy".to_string(),
+                name: "Label Policy".to_string(),
+                version: "1.0.0".to_string(),
+                description: "Requires a team label".to_string(),
+                supported_types: vec![],
+                capabilities: self.capabilities.clone(),
+                commands: Vec::new(),
+            }
+        }
+
+        async fn initialize(&self, _context: &PluginContext) -> Result<()> {
+            Ok(())
+        }
+
+        async fn can_handle(&self, _source_path: &Path) -> Result<bool> {
+            Ok(false)
+        }
+
+        async fn process_source(
+            &self,
+            _source_path: &Path,
+            _context: &PluginContext,
+        ) -> Result<PluginResult> {
+            Err(anyhow::anyhow!("not a source plugin"))
+        }
+
+        async fn generate_code(
+            &self,
+            _schemas: &[ExtractedSchema],
+            _context: &PluginContext,
+        ) -> Result<Vec<PathBuf>> {
+            Ok(Vec::new())
+        }
+
+        async fn generate_validators(
+            &self,
+            _schema: &ExtractedSchema,
+            _context: &PluginContext,
+        ) -> Result<Vec<ValidatorSnippet>> {
+            Ok(vec![ValidatorSnippet {
+                name: "requireTeamLabel".to_string(),
+                code: "function(metadata) std.objectHas(metadata.labels, 'team')".to_string(),
+            }])
+        }
+
+        async fn cleanup(&self, _context: &PluginContext) -> Result<()> {
+            Ok(())
+        }
+
+        fn clone_box(&self) -> Box<dyn Plugin> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_validators_requires_validation_capability() {
+        let manager = PluginManager::new();
+        manager.plugins.write().await.insert(
+            "with-validation".to_string(),
+            slot(Box::new(LabelPolicyPlugin {
+                capabilities: vec![PluginCapability::Validation],
+            })),
+        );
+        manager.plugins.write().await.insert(
+            "without-validation".to_string(),
+            slot(Box::new(LabelPolicyPlugin {
+                capabilities: vec![PluginCapability::Parse],
+            })),
+        );
+
+        let schema = ExtractedSchema {
+            name: "Widget".to_string(),
+            schema_type: "crd".to_string(),
+            content: serde_yaml::Value::Null,
+            source_file: PathBuf::from("widget.yaml"),
+            metadata: HashMap::new(),
+        };
+        let context = PluginContext::new(
+            PathBuf::from("."),
+            PathBuf::from("./out"),
+            PluginConfig {
+                plugin_id: "test".to_string(),
+                config: serde_yaml::Value::Null,
+                enabled_capabilities: vec![],
+            },
+        );
+
+        let validators = manager
+            .generate_validators(&[schema], &context)
+            .await
+            .unwrap();
+        assert_eq!(validators.len(), 1);
+        assert_eq!(validators["Widget"].len(), 1);
+        assert_eq!(validators["Widget"][0].name, "requireTeamLabel");
+    }
+
+    #[derive(Clone)]
+    struct ClaimingPlugin {
+        score: u32,
+    }
+
+    #[async_trait]
+    impl Plugin for ClaimingPlugin {
+        fn metadata(&self) -> PluginMetadata {
+            PluginMetadata {
+                id: "claiming".to_string(),
+                name: "Claiming".to_string(),
+                version: "1.0.0".to_string(),
+                description: "Claims every source with a fixed score".to_string(),
+                supported_types: vec!["yaml".to_string()],
+                capabilities: vec![PluginCapability::Parse],
+                commands: Vec::new(),
+            }
+        }
+
+        async fn initialize(&self, _context: &PluginContext) -> Result<()> {
+            Ok(())
+        }
+
+        async fn can_handle(&self, _source_path: &Path) -> Result<bool> {
+            Ok(self.score > 0)
+        }
+
+        async fn score(&self, _source_path: &Path) -> Result<u32> {
+            Ok(self.score)
+        }
+
+        async fn process_source(
+            &self,
+            _source_path: &Path,
+            _context: &PluginContext,
+        ) -> Result<PluginResult> {
+            Ok(PluginResult {
+                schemas: Vec::new(),
+                generated_files: Vec::new(),
+                statistics: PluginStatistics {
+                    processing_time_ms: 0,
+                    files_processed: 1,
+                    schemas_extracted: 0,
+                    files_generated: 0,
+                    metrics: Default::default(),
+                },
+                warnings: Vec::new(),
+                errors: Vec::new(),
+            })
+        }
+
+        async fn generate_code(
+            &self,
+            _schemas: &[ExtractedSchema],
+            _context: &PluginContext,
+        ) -> Result<Vec<PathBuf>> {
+            Ok(Vec::new())
+        }
+
+        async fn cleanup(&self, _context: &PluginContext) -> Result<()> {
+            Ok(())
+        }
+
+        fn clone_box(&self) -> Box<dyn Plugin> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plugin_resolution_and_binding() {
+        let manager = PluginManager::new();
+        for (id, score) in [("b", 2), ("a", 2), ("low", 1), ("none", 0)] {
+            manager
+                .plugins
+                .write()
+                .await
+                .insert(id.to_string(), slot(Box::new(ClaimingPlugin { score })));
+        }
+        let source = Path::new("widget.yaml");
+
+        // Equal scores fall back to the plugin id
+        assert_eq!(manager.resolve_plugin(source).await.unwrap(), "a");
+
+        // Priority outranks score
+        manager.set_priority("low", 10).await;
+        assert_eq!(manager.resolve_plugin(source).await.unwrap(), "low");
+
+        let context = PluginContext::new(
+            PathBuf::from("."),
+            PathBuf::from("./out"),
+            PluginConfig {
+                plugin_id: "test".to_string(),
+                config: serde_yaml::Value::Null,
+                enabled_capabilities: vec![],
+            },
+        );
+        let binding = PluginBinding::from("b".to_string());
+        let (plugin_id, _) = manager
+            .process_source_with(&binding, source, &context)
+            .await
+            .unwrap();
+        assert_eq!(plugin_id, "b");
+
+        let missing = PluginBinding::Plugin("missing".to_string());
+        assert!(manager
+            .process_source_with(&missing, source, &context)
+            .await
+            .is_err());
+        assert_eq!(String::from(PluginBinding::Auto), "auto");
+    }
+
     #[tokio::test]
     async fn test_plugin_context() {
         let temp_dir = TempDir::new().unwrap();
@@ -360,15 +1417,66 @@ mod tests {
 
         // Test shared state
         context
-            .set_shared_value(
-                "test_key".to_string(),
-                serde_yaml::Value::String("test_value".to_string()),
-            )
-            .await;
-        let value = context.get_shared_value("test_key").await;
+            .state::<Vec<String>>()
+            .write()
+            .await
+            .push("test_value".to_string());
+        let other = PluginContext::new(
+            temp_dir.path().to_path_buf(),
+            temp_dir.path().join("output"),
+            PluginConfig {
+                plugin_id: "other".to_string(),
+                config: serde_yaml::Value::Null,
+                enabled_capabilities: vec![],
+            },
+        )
+        .with_shared_state(context.shared_state.clone());
+        let value = other.state_of::<Vec<String>>("test").unwrap();
+        assert_eq!(*value.read().await, ["test_value"]);
+        assert!(other.state::<Vec<String>>().read().await.is_empty());
+
+        // Untyped values set through the deprecated API are shared too
+        #[allow(deprecated)]
+        {
+            context
+                .set_shared_value(
+                    "test_key".to_string(),
+                    serde_yaml::Value::String("test_value".to_string()),
+                )
+                .await;
+            assert_eq!(
+                other.get_shared_value("test_key").await,
+                Some(serde_yaml::Value::String("test_value".to_string()))
+            );
+        }
+
+        // Test source metadata
+        assert_eq!(context.source.cache_key(), "");
+        let context = context.with_source(SourceMetadata {
+            name: "widgets".to_string(),
+            repository: Some("https://github.com/example/widgets".to_string()),
+            git_ref: Some("main".to_string()),
+            commit: Some("0123abcd".to_string()),
+            filters: vec!["crds/".to_string()],
+            license: None,
+            tag: None,
+        });
+        assert_eq!(context.source.cache_key(), "widgets@0123abcd[crds/]");
+        let unfiltered = SourceMetadata {
+            filters: Vec::new(),
+            ..context.source.clone()
+        };
+        assert_eq!(unfiltered.cache_key(), "widgets@0123abcd");
+        assert!(context.store("test").is_none());
+        let context = context.with_state_dir(temp_dir.path().join("state"));
+        assert!(context
+            .store("test")
+            .unwrap()
+            .dir()
+            .starts_with(temp_dir.path().join("state")));
         assert_eq!(
-            value,
-            Some(serde_yaml::Value::String("test_value".to_string()))
+            context.source.provenance(),
+            "widgets (https://github.com/example/widgets @ 0123abcd)"
         );
     }
 }
@@ -0,0 +1,108 @@
+//! Macros for declaring plugin factories and manifests, and exporting
+//! native plugins
+
+/// Declare a factory creating a plugin with `Plugin::new(config)`
+///
+/// An optional `config_schema:` publishes the JSON Schema of the plugin's
+/// configuration.
+///
+/// ```ignore
+/// gensonnet_plugin::plugin_factory!(
+///     /// Factory of the naming plugin
+///     pub NamingPluginFactory => NamingPlugin, ["go", "golang"],
+///     config_schema: NamingPlugin::config_schema()
+/// );
+/// ```
+#[macro_export]
+macro_rules! plugin_factory {
+    ($(#[$attr:meta])* $vis:vis $factory:ident => $plugin:ty, [$($source_type:expr),* $(,)?]
+        $(, config_schema: $config_schema:expr)? $(,)?) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, Default)]
+        $vis struct $factory;
+
+        #[$crate::__private::async_trait]
+        impl $crate::PluginFactory for $factory {
+            async fn create_plugin(
+                &self,
+                config: $crate::PluginConfig,
+            ) -> $crate::__private::Result<::std::boxed::Box<dyn $crate::Plugin>> {
+                Ok(::std::boxed::Box::new(<$plugin>::new(config)))
+            }
+
+            fn supported_types(&self) -> ::std::vec::Vec<::std::string::String> {
+                ::std::vec![$(::std::string::String::from($source_type)),*]
+            }
+
+            fn api_version(&self) -> &'static str {
+                $crate::PLUGIN_API_VERSION
+            }
+
+            $(
+                fn config_schema(&self) -> ::std::option::Option<$crate::__private::Value> {
+                    ::std::option::Option::Some($config_schema)
+                }
+            )?
+
+            fn clone_box(&self) -> ::std::boxed::Box<dyn $crate::PluginFactory> {
+                ::std::boxed::Box::new(*self)
+            }
+        }
+    };
+}
+
+/// Build a [`PluginManifest`](crate::PluginManifest) targeting the current plugin API
+///
+/// ```ignore
+/// let manifest = gensonnet_plugin::plugin_manifest! {
+///     id: "naming:1.0",
+///     name: "Naming",
+///     version: "1.0.0",
+///     description: "Prefixes kinds",
+///     types: ["go"],
+///     capabilities: [Parse, Transform],
+/// };
+/// ```
+#[macro_export]
+macro_rules! plugin_manifest {
+    (
+        id: $id:expr,
+        name: $name:expr,
+        version: $version:expr,
+        description: $description:expr,
+        types: [$($source_type:expr),* $(,)?],
+        capabilities: [$($capability:ident),* $(,)?] $(,)?
+    ) => {
+        $crate::PluginManifest::new($crate::PluginMetadata {
+            id: ::std::string::String::from($id),
+            name: ::std::string::String::from($name),
+            version: ::std::string::String::from($version),
+            description: ::std::string::String::from($description),
+            supported_types: ::std::vec![$(::std::string::String::from($source_type)),*],
+            capabilities: ::std::vec![$($crate::PluginCapability::$capability),*],
+            commands: ::std::vec::Vec::new(),
+        })
+    };
+}
+
+/// Export a plugin factory from a `cdylib` as a native plugin
+///
+/// Declares the [`PluginDeclaration`](crate::native::PluginDeclaration) the
+/// host looks up when it loads the library. Once per library:
+///
+/// ```ignore
+/// gensonnet_plugin::export_plugin!(NamingPluginFactory);
+/// ```
+#[macro_export]
+macro_rules! export_plugin {
+    ($factory:expr) => {
+        #[export_name = "gensonnet_plugin_declaration"]
+        pub static GENSONNET_PLUGIN_DECLARATION: $crate::native::PluginDeclaration =
+            $crate::native::PluginDeclaration {
+                abi_version: $crate::native::NATIVE_ABI_VERSION,
+                rustc_version: $crate::native::RUSTC_VERSION,
+                api_version: $crate::PLUGIN_API_VERSION,
+                create_factory: || ::std::boxed::Box::new($factory),
+            };
+    };
+}
@@ -0,0 +1,90 @@
+//! Plugin manifests
+//!
+//! A `plugin.yaml` next to a plugin describes it to the registry: its
+//! metadata, default configuration and what it needs from the host. A
+//! manifest naming a `library` describes a native plugin (see
+//! [`native`](crate::native)).
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::compat::{check_api_version, PLUGIN_API_VERSION};
+use crate::{PluginCapability, PluginConfig, PluginMetadata};
+
+/// Plugin manifest file structure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    /// Plugin metadata
+    pub metadata: PluginMetadata,
+
+    /// Plugin configuration
+    pub config: PluginConfig,
+
+    /// JSON Schema the `config` block is validated against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_schema: Option<serde_yaml::Value>,
+
+    /// Dynamic library implementing the plugin, relative to the manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub library: Option<PathBuf>,
+
+    /// Plugin dependencies
+    pub dependencies: Option<Vec<String>>,
+
+    /// Plugin requirements
+    pub requirements: Option<PluginRequirements>,
+}
+
+/// Plugin requirements
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginRequirements {
+    /// Minimum tool version
+    pub min_tool_version: Option<String>,
+
+    /// Plugin API version the plugin was built against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_version: Option<String>,
+
+    /// Required capabilities
+    pub required_capabilities: Option<Vec<PluginCapability>>,
+
+    /// Required dependencies
+    pub required_dependencies: Option<Vec<String>>,
+}
+
+impl PluginManifest {
+    /// Create a manifest enabling every capability of the plugin, built
+    /// against the current plugin API
+    pub fn new(metadata: PluginMetadata) -> Self {
+        Self {
+            config: PluginConfig {
+                plugin_id: metadata.id.clone(),
+                config: serde_yaml::Value::Null,
+                enabled_capabilities: metadata.capabilities.clone(),
+            },
+            metadata,
+            config_schema: None,
+            library: None,
+            dependencies: None,
+            requirements: Some(PluginRequirements {
+                api_version: Some(PLUGIN_API_VERSION.to_string()),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Check that the plugin runs on this build
+    ///
+    /// Manifests predating API versioning target the first version.
+    pub fn check_compatibility(&self) -> Result<()> {
+        match self
+            .requirements
+            .as_ref()
+            .and_then(|requirements| requirements.api_version.as_deref())
+        {
+            Some(api_version) => check_api_version(&self.metadata.id, api_version),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Native plugins loaded from dynamic libraries
+//!
+//! A plugin whose performance matters most can be compiled into a `cdylib`
+//! that exports its factory with [`export_plugin!`](crate::export_plugin).
+//! Hosts built with the `native` feature load it with [`load_factory`].
+//!
+//! Rust has no stable ABI, so a native plugin only runs on a host built by
+//! the same compiler against the same plugin API version. Unlike manifests,
+//! native plugins get no leeway across minor API versions: the traits'
+//! layouts must match exactly. The [`PluginDeclaration`] a library exports
+//! records what it was built with and is checked before anything else in
+//! the library is called. Loaded libraries stay loaded until the process
+//! exits, so the factories and plugins they create remain valid.
+
+use anyhow::{anyhow, Result};
+
+use crate::compat::PLUGIN_API_VERSION;
+use crate::PluginFactory;
+
+/// Version of the layout of [`PluginDeclaration`]
+pub const NATIVE_ABI_VERSION: u32 = 1;
+
+/// Symbol under which a library exports its [`PluginDeclaration`]
+pub const DECLARATION_SYMBOL: &str = "gensonnet_plugin_declaration";
+
+/// Compiler this crate was built with, e.g. `rustc 1.80.0 (051478957 2024-07-21)`
+pub const RUSTC_VERSION: &str = env!("GENSONNET_RUSTC_VERSION");
+
+/// What a native plugin library exports to the host
+///
+/// `abi_version` comes first so any host can read it, whatever the layout
+/// of the rest.
+#[repr(C)]
+pub struct PluginDeclaration {
+    /// Layout version of this declaration, [`NATIVE_ABI_VERSION`]
+    pub abi_version: u32,
+
+    /// Compiler the library was built with
+    pub rustc_version: &'static str,
+
+    /// Plugin API version the library was built against
+    pub api_version: &'static str,
+
+    /// Create the factory of the library's plugins
+    pub create_factory: fn() -> Box<dyn PluginFactory>,
+}
+
+impl PluginDeclaration {
+    /// Check that the library declaring this was built for this host
+    pub fn check(&self, library: &str) -> Result<()> {
+        if self.rustc_version != RUSTC_VERSION {
+            return Err(anyhow!(
+                "Native plugin {} was built with {}, but this gensonnet was built with {}",
+                library,
+                self.rustc_version,
+                RUSTC_VERSION
+            ));
+        }
+        if self.api_version != PLUGIN_API_VERSION {
+            return Err(anyhow!(
+                "Native plugin {} was built against plugin API {}, but native plugins must match this gensonnet's plugin API {}",
+                library,
+                self.api_version,
+                PLUGIN_API_VERSION
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Check the ABI version a library declares, before the rest of its
+/// declaration is read
+pub fn check_abi_version(library: &str, abi_version: u32) -> Result<()> {
+    if abi_version != NATIVE_ABI_VERSION {
+        return Err(anyhow!(
+            "Native plugin {} declares ABI version {}, but this gensonnet loads version {}",
+            library,
+            abi_version,
+            NATIVE_ABI_VERSION
+        ));
+    }
+    Ok(())
+}
+
+/// Load the plugin factory a dynamic library exports
+///
+/// The library is checked against this host before its factory is created
+/// and is never unloaded.
+#[cfg(feature = "native")]
+pub fn load_factory(path: &std::path::Path) -> Result<Box<dyn PluginFactory>> {
+    let name = path.display().to_string();
+    // SAFETY: loading runs the library's initializers; native plugins are
+    // trusted code, like the host itself.
+    let library = unsafe { libloading::Library::new(path) }
+        .map_err(|e| anyhow!("Failed to load native plugin {}: {}", name, e))?;
+
+    // SAFETY: the symbol is the declaration `export_plugin!` defines. Its
+    // ABI version is read alone first, as it sits first in every layout.
+    let declaration = unsafe {
+        let symbol = library
+            .get::<*const PluginDeclaration>(DECLARATION_SYMBOL.as_bytes())
+            .map_err(|e| {
+                anyhow!(
+                    "{} is not a gensonnet native plugin (no {} symbol): {}",
+                    name,
+                    DECLARATION_SYMBOL,
+                    e
+                )
+            })?;
+        let declaration = *symbol;
+        check_abi_version(&name, (*declaration).abi_version)?;
+        &*declaration
+    };
+    declaration.check(&name)?;
+
+    let factory = (declaration.create_factory)();
+    std::mem::forget(library);
+    Ok(factory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy)]
+    struct NoopPluginFactory;
+
+    #[async_trait::async_trait]
+    impl PluginFactory for NoopPluginFactory {
+        async fn create_plugin(
+            &self,
+            _config: crate::PluginConfig,
+        ) -> Result<Box<dyn crate::Plugin>> {
+            Err(anyhow!("noop creates no plugins"))
+        }
+
+        fn supported_types(&self) -> Vec<String> {
+            vec!["noop".to_string()]
+        }
+
+        fn clone_box(&self) -> Box<dyn PluginFactory> {
+            Box::new(*self)
+        }
+    }
+
+    crate::export_plugin!(NoopPluginFactory);
+
+    #[test]
+    fn test_plugin_declaration() {
+        let declaration = &GENSONNET_PLUGIN_DECLARATION;
+        assert_eq!(declaration.abi_version, NATIVE_ABI_VERSION);
+        declaration.check("libnoop.so").unwrap();
+        assert_eq!((declaration.create_factory)().supported_types(), ["noop"]);
+
+        let other_compiler = PluginDeclaration {
+            rustc_version: "rustc 0.1.0",
+            ..*declaration
+        };
+        let error = other_compiler.check("libnoop.so").unwrap_err();
+        assert!(error.to_string().contains("rustc 0.1.0"), "{error}");
+
+        let older_api = PluginDeclaration {
+            api_version: "0.9",
+            ..*declaration
+        };
+        assert!(older_api.check("libnoop.so").is_err());
+
+        assert!(check_abi_version("libnoop.so", NATIVE_ABI_VERSION).is_ok());
+        assert!(check_abi_version("libnoop.so", NATIVE_ABI_VERSION + 1).is_err());
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn test_load_factory() {
+        let error = load_factory(std::path::Path::new("/nonexistent/libnoop.so"))
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("Failed to load native plugin"));
+    }
+}
@@ -0,0 +1,203 @@
+//! Pooled plugin instances
+//!
+//! The manager keeps one instance of each registered plugin. An instance is
+//! initialized the first time it is checked out and cleaned up when the
+//! manager shuts down, and each checkout holds a permit of the plugin's
+//! concurrency limit, so no plugin processes more sources at once than it is
+//! allowed to. Plugins with per-file state keep it behind their own locks.
+
+use crate::{Plugin, PluginContext};
+use anyhow::Result;
+use std::ops::Deref;
+use std::sync::{Arc, RwLock};
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+
+/// Checkouts of a plugin allowed at once when no limit is set
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// A registered plugin instance with its lifecycle and concurrency limit
+pub(crate) struct PluginSlot {
+    plugin: Box<dyn Plugin>,
+    limit: RwLock<(usize, Arc<Semaphore>)>,
+    initialized: Mutex<bool>,
+}
+
+impl PluginSlot {
+    pub(crate) fn new(plugin: Box<dyn Plugin>, concurrency: usize) -> Self {
+        let concurrency = concurrency.max(1);
+        Self {
+            plugin,
+            limit: RwLock::new((concurrency, Arc::new(Semaphore::new(concurrency)))),
+            initialized: Mutex::new(false),
+        }
+    }
+
+    /// The plugin, without initializing it or taking a permit
+    pub(crate) fn plugin(&self) -> &dyn Plugin {
+        self.plugin.as_ref()
+    }
+
+    /// Limit the checkouts running at once; running checkouts are not affected
+    pub(crate) fn set_concurrency(&self, concurrency: usize) {
+        let concurrency = concurrency.max(1);
+        *self.limit.write().unwrap() = (concurrency, Arc::new(Semaphore::new(concurrency)));
+    }
+
+    /// Wait for a permit, initializing the plugin if it is not yet
+    pub(crate) async fn checkout(
+        self: &Arc<Self>,
+        context: &PluginContext,
+    ) -> Result<PooledPlugin> {
+        let semaphore = Arc::clone(&self.limit.read().unwrap().1);
+        let permit = semaphore.acquire_owned().await?;
+
+        let mut initialized = self.initialized.lock().await;
+        if !*initialized {
+            self.plugin.initialize(context).await?;
+            *initialized = true;
+        }
+        drop(initialized);
+
+        Ok(PooledPlugin {
+            slot: Arc::clone(self),
+            _permit: permit,
+        })
+    }
+
+    /// Clean up the plugin once its running checkouts finish, if it was initialized
+    ///
+    /// A later checkout initializes it again.
+    pub(crate) async fn shutdown(&self, context: &PluginContext) -> Result<()> {
+        let (concurrency, semaphore) = self.limit.read().unwrap().clone();
+        let _all = semaphore.acquire_many(concurrency as u32).await?;
+
+        let mut initialized = self.initialized.lock().await;
+        if *initialized {
+            self.plugin.cleanup(context).await?;
+            *initialized = false;
+        }
+        Ok(())
+    }
+}
+
+/// A plugin checked out of the manager's pool
+///
+/// Dereferences to the plugin; its concurrency permit is released on drop.
+pub struct PooledPlugin {
+    slot: Arc<PluginSlot>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Deref for PooledPlugin {
+    type Target = dyn Plugin;
+
+    fn deref(&self) -> &Self::Target {
+        self.slot.plugin.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExtractedSchema, PluginConfig, PluginMetadata, PluginResult};
+    use async_trait::async_trait;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts its initializations and cleanups
+    #[derive(Clone, Default)]
+    struct CountingPlugin {
+        initialized: Arc<AtomicUsize>,
+        cleaned_up: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Plugin for CountingPlugin {
+        fn metadata(&self) -> PluginMetadata {
+            PluginMetadata {
+                id: "counting".to_string(),
+                name: "Counting".to_string(),
+                version: "1.0.0".to_string(),
+                description: "Counts its lifecycle calls".to_string(),
+                supported_types: Vec::new(),
+                capabilities: Vec::new(),
+                commands: Vec::new(),
+            }
+        }
+
+        async fn initialize(&self, _context: &PluginContext) -> Result<()> {
+            self.initialized.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn can_handle(&self, _source_path: &Path) -> Result<bool> {
+            Ok(false)
+        }
+
+        async fn process_source(
+            &self,
+            _source_path: &Path,
+            _context: &PluginContext,
+        ) -> Result<PluginResult> {
+            Err(anyhow::anyhow!("not a source plugin"))
+        }
+
+        async fn generate_code(
+            &self,
+            _schemas: &[ExtractedSchema],
+            _context: &PluginContext,
+        ) -> Result<Vec<PathBuf>> {
+            Ok(Vec::new())
+        }
+
+        async fn cleanup(&self, _context: &PluginContext) -> Result<()> {
+            self.cleaned_up.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn clone_box(&self) -> Box<dyn Plugin> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slot_lifecycle_and_limit() {
+        let plugin = CountingPlugin::default();
+        let slot = Arc::new(PluginSlot::new(Box::new(plugin.clone()), 2));
+        let context = PluginContext::new(
+            PathBuf::from("."),
+            PathBuf::from("./out"),
+            PluginConfig {
+                plugin_id: "counting".to_string(),
+                config: serde_yaml::Value::Null,
+                enabled_capabilities: Vec::new(),
+            },
+        );
+
+        // Shutting down a plugin never checked out does not clean it up
+        slot.shutdown(&context).await.unwrap();
+        assert_eq!(plugin.cleaned_up.load(Ordering::SeqCst), 0);
+
+        // The plugin is initialized once across checkouts
+        let first = slot.checkout(&context).await.unwrap();
+        let second = slot.checkout(&context).await.unwrap();
+        assert_eq!(plugin.initialized.load(Ordering::SeqCst), 1);
+        assert_eq!(second.metadata().id, "counting");
+
+        // A third checkout waits for one of the two permits
+        tokio::select! {
+            biased;
+            _ = slot.checkout(&context) => panic!("checkout exceeded the limit"),
+            _ = std::future::ready(()) => {}
+        }
+        drop(first);
+        let third = slot.checkout(&context).await.unwrap();
+        drop((second, third));
+
+        // Shutdown cleans up, and the next checkout initializes again
+        slot.shutdown(&context).await.unwrap();
+        assert_eq!(plugin.cleaned_up.load(Ordering::SeqCst), 1);
+        let _again = slot.checkout(&context).await.unwrap();
+        assert_eq!(plugin.initialized.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,233 @@
+//! Typed state plugins share during a run
+//!
+//! Each plugin owns a namespace, keyed by its plugin id, holding at most one
+//! value per Rust type. Only the owning plugin gets write access, through
+//! [`PluginContext::state`](crate::PluginContext::state); other plugins read
+//! it with [`PluginContext::state_of`](crate::PluginContext::state_of).
+//!
+//! # Concurrency
+//!
+//! Every value sits behind its own async read-write lock, so instances of a
+//! plugin processing files concurrently, or a streaming plugin running
+//! alongside the generator, see a value either before or after a write and
+//! never half of it. Guards are held across awaits without blocking other
+//! values, but a plugin should drop a write guard before sending schemas, as
+//! the consumer may be another plugin waiting to read. Sources are processed
+//! in configuration order, so a plugin sees the state another plugin built
+//! for sources configured before its own. State is dropped when a run starts.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+type Slot = Arc<dyn Any + Send + Sync>;
+
+/// Owner of a slot, kept apart from plugin ids so no plugin can reach the
+/// untyped values by choosing its id
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Namespace {
+    Plugin(String),
+    Values,
+}
+
+/// Run-scoped store of the states of all plugins
+#[derive(Clone, Default)]
+pub struct SharedState {
+    slots: Arc<Mutex<HashMap<(Namespace, TypeId), Slot>>>,
+}
+
+impl SharedState {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// State of type `T` owned by a plugin, created empty on first access
+    pub fn owned<T>(&self, plugin_id: &str) -> PluginState<T>
+    where
+        T: Default + Send + Sync + 'static,
+    {
+        PluginState {
+            value: self.slot(Namespace::Plugin(plugin_id.to_string())),
+        }
+    }
+
+    /// Untyped values shared by key, as read and written by the deprecated
+    /// [`PluginContext::get_shared_value`](crate::PluginContext::get_shared_value)
+    /// and [`PluginContext::set_shared_value`](crate::PluginContext::set_shared_value)
+    pub fn values(&self) -> Arc<RwLock<HashMap<String, serde_yaml::Value>>> {
+        self.slot(Namespace::Values)
+    }
+
+    fn slot<T>(&self, namespace: Namespace) -> Arc<RwLock<T>>
+    where
+        T: Default + Send + Sync + 'static,
+    {
+        let slot = self
+            .slots
+            .lock()
+            .unwrap()
+            .entry((namespace, TypeId::of::<T>()))
+            .or_insert_with(|| Arc::new(RwLock::new(T::default())))
+            .clone();
+        downcast(slot)
+    }
+
+    /// State of type `T` owned by a plugin, if it has created one
+    pub fn view<T>(&self, plugin_id: &str) -> Option<StateView<T>>
+    where
+        T: Send + Sync + 'static,
+    {
+        let slot = self
+            .slots
+            .lock()
+            .unwrap()
+            .get(&(Namespace::Plugin(plugin_id.to_string()), TypeId::of::<T>()))?
+            .clone();
+        Some(StateView {
+            value: downcast(slot),
+        })
+    }
+
+    /// Drop the states of all plugins
+    pub fn clear(&self) {
+        self.slots.lock().unwrap().clear();
+    }
+
+    /// Number of states held
+    pub fn len(&self) -> usize {
+        self.slots.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Debug for SharedState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut namespaces: Vec<String> = self
+            .slots
+            .lock()
+            .unwrap()
+            .keys()
+            .filter_map(|(namespace, _)| match namespace {
+                Namespace::Plugin(plugin_id) => Some(plugin_id.clone()),
+                Namespace::Values => None,
+            })
+            .collect();
+        namespaces.sort();
+        namespaces.dedup();
+        f.debug_struct("SharedState")
+            .field("namespaces", &namespaces)
+            .finish()
+    }
+}
+
+/// Slots are only ever created holding an `RwLock<T>` for their `TypeId`
+fn downcast<T: Send + Sync + 'static>(slot: Slot) -> Arc<RwLock<T>> {
+    slot.downcast::<RwLock<T>>()
+        .unwrap_or_else(|_| unreachable!("shared state slot holds a different type"))
+}
+
+/// Read and write access to a state of the plugin owning it
+pub struct PluginState<T> {
+    value: Arc<RwLock<T>>,
+}
+
+impl<T> PluginState<T> {
+    /// Wait for readers and writers to finish, then read the state
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.value.read().await
+    }
+
+    /// Wait for readers and writers to finish, then modify the state
+    pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.value.write().await
+    }
+}
+
+impl<T> Clone for PluginState<T> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+        }
+    }
+}
+
+/// Read-only access to a state of another plugin
+pub struct StateView<T> {
+    value: Arc<RwLock<T>>,
+}
+
+impl<T> StateView<T> {
+    /// Wait for the owner to finish writing, then read the state
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.value.read().await
+    }
+}
+
+impl<T> Clone for StateView<T> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq)]
+    struct ModuleGraph {
+        modules: Vec<String>,
+    }
+
+    #[tokio::test]
+    async fn test_shared_state() {
+        let state = SharedState::new();
+        assert!(state.view::<ModuleGraph>("go-ast:builtin").is_none());
+
+        state
+            .owned::<ModuleGraph>("go-ast:builtin")
+            .write()
+            .await
+            .modules
+            .push("example.com/api".to_string());
+
+        let view = state.view::<ModuleGraph>("go-ast:builtin").unwrap();
+        assert_eq!(view.read().await.modules, ["example.com/api"]);
+
+        // Namespaces and types are kept apart
+        assert!(state.view::<ModuleGraph>("crd:builtin").is_none());
+        assert!(state.view::<Vec<String>>("go-ast:builtin").is_none());
+        assert!(state
+            .owned::<ModuleGraph>("crd:builtin")
+            .read()
+            .await
+            .modules
+            .is_empty());
+
+        // Untyped values live apart from every plugin, even one without an id
+        state
+            .values()
+            .write()
+            .await
+            .insert("key".to_string(), "value".into());
+        assert_eq!(state.values().read().await["key"], "value");
+        assert!(state
+            .owned::<HashMap<String, serde_yaml::Value>>("")
+            .read()
+            .await
+            .is_empty());
+
+        // Clones share the store, and clearing it starts over
+        let clone = state.clone();
+        assert_eq!(clone.len(), 4);
+        clone.clear();
+        assert!(state.is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+//! Persistent plugin state
+//!
+//! Each plugin gets its own directory below the state root. Values are
+//! stored as one JSON file per key, so plugins can cache expensive
+//! intermediate results across invocations.
+
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Key-value store of one plugin, kept across runs
+#[derive(Debug, Clone)]
+pub struct PluginStore {
+    dir: PathBuf,
+}
+
+impl PluginStore {
+    /// Open the store of a plugin below a state root
+    pub fn open(root: &Path, plugin_id: &str) -> Self {
+        Self {
+            dir: root.join(encode(plugin_id)),
+        }
+    }
+
+    /// Directory the plugin's values are kept in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Read a value, returning `None` if it was never stored
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let path = self.path(key);
+        let content = match tokio::fs::read(&path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        serde_json::from_slice(&content)
+            .map(Some)
+            .map_err(|e| anyhow!("Corrupt plugin state {:?}: {}", path, e))
+    }
+
+    /// Store a value, replacing any previous one
+    pub async fn set<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+
+        // Write to a temporary file of its own first so readers never see a
+        // partial value, and concurrent writers never share one
+        let content = serde_json::to_vec(value)?;
+        let dir = self.dir.clone();
+        let path = self.path(key);
+        tokio::task::spawn_blocking(move || -> Result<()> {
+            let mut temp = tempfile::NamedTempFile::new_in(&dir)?;
+            temp.write_all(&content)?;
+            temp.persist(&path)?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Remove a value if it exists
+    pub async fn remove(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path(key)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Remove every value of the plugin
+    pub async fn clear(&self) -> Result<()> {
+        match tokio::fs::remove_dir_all(&self.dir).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", encode(key)))
+    }
+}
+
+/// Encode a plugin id or key as a file name, escaping anything but `[A-Za-z0-9_-]`
+fn encode(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_plugin_store_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = PluginStore::open(temp_dir.path(), "go-ast:builtin");
+        assert!(store.dir().ends_with("go-ast%3Abuiltin"));
+
+        let key = "modules/github.com/example@abc";
+        assert_eq!(store.get::<Vec<String>>(key).await.unwrap(), None);
+
+        let modules = vec!["github.com/example/a".to_string()];
+        store.set(key, &modules).await.unwrap();
+
+        // A store opened later sees the value; other plugins do not
+        let reopened = PluginStore::open(temp_dir.path(), "go-ast:builtin");
+        assert_eq!(reopened.get(key).await.unwrap(), Some(modules));
+        let other = PluginStore::open(temp_dir.path(), "openapi:builtin");
+        assert_eq!(other.get::<Vec<String>>(key).await.unwrap(), None);
+
+        reopened.remove(key).await.unwrap();
+        reopened.remove(key).await.unwrap();
+        assert_eq!(store.get::<Vec<String>>(key).await.unwrap(), None);
+
+        store.set("other", &1).await.unwrap();
+        store.clear().await.unwrap();
+        assert!(!store.dir().exists());
+    }
+
+    #[tokio::test]
+    async fn test_plugin_store_concurrent_writes() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = PluginStore::open(temp_dir.path(), "go-ast:builtin");
+
+        let writes = (0..16).map(|i| {
+            let store = store.clone();
+            tokio::spawn(async move { store.set("modules", &vec![i; 1024]).await })
+        });
+        for write in writes {
+            write.await.unwrap().unwrap();
+        }
+
+        let modules: Vec<u32> = store.get("modules").await.unwrap().unwrap();
+        assert!(modules.iter().all(|&m| m == modules[0]));
+        assert_eq!(std::fs::read_dir(store.dir()).unwrap().count(), 1);
+    }
+}
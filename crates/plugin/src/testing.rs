@@ -0,0 +1,56 @@
+//! Utilities for testing plugins outside of a generator run
+
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+use crate::{Plugin, PluginConfig, PluginContext, PluginResult, PluginStatistics};
+
+/// Context for running a plugin in a scratch directory, writing to `output/`
+pub fn test_context(dir: &Path, plugin_id: &str) -> PluginContext {
+    PluginContext::new(
+        dir.to_path_buf(),
+        dir.join("output"),
+        PluginConfig {
+            plugin_id: plugin_id.to_string(),
+            config: serde_yaml::Value::Null,
+            enabled_capabilities: Vec::new(),
+        },
+    )
+}
+
+/// Run a plugin over one source the way the generator does: initialize,
+/// check it claims the source, process and clean up
+pub async fn process_once(
+    plugin: &dyn Plugin,
+    source_path: &Path,
+    context: &PluginContext,
+) -> Result<PluginResult> {
+    plugin.initialize(context).await?;
+    if !plugin.can_handle(source_path).await? {
+        return Err(anyhow!(
+            "Plugin {} does not handle {}",
+            plugin.metadata().id,
+            source_path.display()
+        ));
+    }
+    let result = plugin.process_source(source_path, context).await;
+    plugin.cleanup(context).await?;
+    result
+}
+
+/// A result without schemas, files or diagnostics
+pub fn empty_result() -> PluginResult {
+    PluginResult {
+        schemas: Vec::new(),
+        generated_files: Vec::new(),
+        statistics: PluginStatistics {
+            processing_time_ms: 0,
+            files_processed: 0,
+            schemas_extracted: 0,
+            files_generated: 0,
+            metrics: Default::default(),
+        },
+        warnings: Vec::new(),
+        errors: Vec::new(),
+    }
+}
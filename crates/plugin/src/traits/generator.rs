@@ -8,6 +8,7 @@ use std::path::PathBuf;
 
 use super::super::ExtractedSchema;
 use super::processor::OutputFormat;
+pub use jsonnet_lockfile::GenerationStatistics;
 
 /// Schema generator trait for generating code from schemas
 #[async_trait]
@@ -93,22 +94,6 @@ pub struct GeneratedFile {
     pub metadata: HashMap<String, serde_yaml::Value>,
 }
 
-/// Generation statistics
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GenerationStatistics {
-    /// Generation time in milliseconds
-    pub generation_time_ms: u64,
-
-    /// Number of files generated
-    pub files_generated: usize,
-
-    /// Total lines of code generated
-    pub lines_generated: usize,
-
-    /// Memory usage in bytes
-    pub memory_usage_bytes: usize,
-}
-
 /// Generator capability
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum GeneratorCapability {
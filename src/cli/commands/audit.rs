@@ -0,0 +1,41 @@
+//! Audit command implementation
+
+use crate::audit::AuditLog;
+use crate::cli::utils;
+use anyhow::{anyhow, Result};
+use clap::{ArgMatches, Command};
+
+pub fn command() -> Command {
+    Command::new("audit")
+        .about("Inspect the audit manifest of generation runs")
+        .subcommand_required(true)
+        .arg(
+            clap::Arg::new("config")
+                .short('c')
+                .long("config")
+                .help("Configuration file path")
+                .value_name("FILE")
+                .global(true),
+        )
+        .subcommand(
+            Command::new("verify")
+                .about("Check that no record of the audit manifest was altered or removed"),
+        )
+}
+
+pub async fn run(matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        Some(("verify", _)) => {
+            let config = utils::load_config(matches)?;
+            let log = AuditLog::from_config(&config.audit)?;
+            let records = log.verify()?;
+            println!(
+                "Audit manifest {} verified: {} records",
+                log.path().display(),
+                records
+            );
+            Ok(())
+        }
+        _ => Err(anyhow!("Missing audit command")),
+    }
+}
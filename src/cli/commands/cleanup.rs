@@ -49,7 +49,7 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
         println!("Would clean up entries older than {max_age} hours");
 
         let config = utils::load_config(matches)?;
-        let app = utils::create_app(config)?;
+        let app = utils::create_app(matches, config)?;
 
         let result = app.cleanup_dry_run(max_age)?;
 
@@ -100,7 +100,7 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
     info!("Cleaning up entries older than {} hours", max_age);
 
     let config = utils::load_config(matches)?;
-    let app = utils::create_app(config)?;
+    let app = utils::create_app(matches, config)?;
 
     app.cleanup(max_age)?;
 
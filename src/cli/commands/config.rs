@@ -0,0 +1,180 @@
+//! Config command implementation
+//!
+//! `config migrate` upgrades a configuration file written for an older format,
+//! along with its fragments in `.gensonnet.d/`. The original of each migrated
+//! file is kept as `<file>.bak`, which must not exist yet, and the changes are
+//! shown as a diff.
+//! Files with a name from before the project was renamed are migrated to
+//! `.gensonnet.yaml` next to them.
+
+use crate::config::migrate::{self, LEGACY_FILE_NAMES};
+use anyhow::{anyhow, Result};
+use clap::{ArgMatches, Command};
+use std::path::{Path, PathBuf};
+
+pub fn command() -> Command {
+    Command::new("config")
+        .about("Manage the configuration file")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("migrate")
+                .about("Upgrade a configuration file to the current format")
+                .arg(
+                    clap::Arg::new("config")
+                        .short('c')
+                        .long("config")
+                        .help("Configuration file path")
+                        .value_name("FILE"),
+                )
+                .arg(
+                    clap::Arg::new("dry-run")
+                        .long("dry-run")
+                        .help("Show the changes without writing them")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+}
+
+pub async fn run(matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        Some(("migrate", sub_matches)) => run_migrate(sub_matches),
+        _ => Err(anyhow!("Missing config command")),
+    }
+}
+
+/// A configuration file or fragment to rewrite in the current format
+struct PendingMigration {
+    path: PathBuf,
+    target: PathBuf,
+    content: String,
+    migration: migrate::ConfigMigration,
+}
+
+fn run_migrate(matches: &ArgMatches) -> Result<()> {
+    let path = match matches.get_one::<String>("config") {
+        Some(path) => PathBuf::from(path),
+        None => crate::cli::utils::get_config_path(matches)
+            .or_else(|e| legacy_config_path().ok_or(e))?,
+    };
+    let content = read(&path)?;
+
+    let legacy = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| LEGACY_FILE_NAMES.contains(&name));
+    let target = if legacy {
+        path.with_file_name(crate::Config::FILE_NAMES[0])
+    } else {
+        path.clone()
+    };
+
+    let migration = migrate::migrate(&content)?;
+    let mut pending = Vec::new();
+    if !migration.is_empty() || legacy {
+        pending.push(PendingMigration {
+            path: path.clone(),
+            target,
+            content,
+            migration,
+        });
+    }
+
+    let fragments_dir = path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(crate::Config::FRAGMENTS_DIR);
+    if fragments_dir.is_dir() {
+        for fragment in crate::Config::fragment_paths(&fragments_dir)? {
+            let content = read(&fragment)?;
+            let migration = migrate::migrate_fragment(&content)
+                .map_err(|e| anyhow!("Failed to migrate {}: {}", fragment.display(), e))?;
+            if !migration.is_empty() {
+                pending.push(PendingMigration {
+                    path: fragment.clone(),
+                    target: fragment,
+                    content,
+                    migration,
+                });
+            }
+        }
+    }
+
+    if pending.is_empty() {
+        println!("{} already has the current format", path.display());
+        return Ok(());
+    }
+
+    for file in &pending {
+        println!(
+            "Migrating {} to {}:",
+            file.path.display(),
+            file.target.display()
+        );
+        for change in &file.migration.changes {
+            println!("  {change}");
+        }
+        if file.target != file.path {
+            println!("  Renamed the file to {}", file.target.display());
+        }
+        println!();
+        println!("--- {}", file.path.display());
+        println!("+++ {}", file.target.display());
+        print!("{}", migrate::diff(&file.content, &file.migration.content));
+        println!();
+    }
+
+    if matches.get_flag("dry-run") {
+        println!("Dry run, nothing written");
+        return Ok(());
+    }
+
+    // Check every file before writing any, so a migration is never left half done
+    for file in &pending {
+        if file.target != file.path && file.target.exists() {
+            return Err(anyhow!(
+                "{} already exists; remove it or migrate it instead",
+                file.target.display()
+            ));
+        }
+        let backup = backup_path(&file.path);
+        if backup.exists() {
+            return Err(anyhow!(
+                "Backup {} already exists; move it away before migrating {}",
+                backup.display(),
+                file.path.display()
+            ));
+        }
+    }
+
+    for file in &pending {
+        let backup = backup_path(&file.path);
+        std::fs::rename(&file.path, &backup)?;
+        std::fs::write(&file.target, &file.migration.content)?;
+        println!(
+            "Wrote {}, keeping the original as {}",
+            file.target.display(),
+            backup.display()
+        );
+    }
+    Ok(())
+}
+
+/// Content of a file to migrate
+fn read(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))
+}
+
+/// Configuration file with a legacy name in the current directory, if any
+fn legacy_config_path() -> Option<PathBuf> {
+    LEGACY_FILE_NAMES
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.exists())
+}
+
+/// Path the original of a migrated file is kept at
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    PathBuf::from(backup)
+}
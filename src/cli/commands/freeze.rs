@@ -0,0 +1,40 @@
+//! Freeze command implementation
+
+use crate::cli::utils;
+use anyhow::Result;
+use clap::{ArgMatches, Command};
+use tracing::info;
+
+pub fn command() -> Command {
+    Command::new("freeze")
+        .about("Freeze the generated API; later generations fail on breaking changes to it")
+        .arg(
+            clap::Arg::new("config")
+                .short('c')
+                .long("config")
+                .help("Configuration file path")
+                .value_name("FILE"),
+        )
+}
+
+pub async fn run(matches: &ArgMatches) -> Result<()> {
+    info!("Freezing the generated API");
+
+    let config = utils::load_config(matches)?;
+    let app = utils::create_app(matches, config)?;
+
+    let snapshot = app.current_api()?;
+    let path = app.freeze_api()?;
+
+    println!("Froze the generated API in {}", path.display());
+    for (source, api) in &snapshot.sources {
+        println!(
+            "  {}: {} functions, {} fields",
+            source,
+            api.functions.len(),
+            api.fields.len()
+        );
+    }
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 //! Generate command implementation
 
 use crate::cli::utils;
+use crate::WarningCategory;
 use anyhow::Result;
 use clap::{ArgMatches, Command};
 use std::path::PathBuf;
@@ -36,18 +37,73 @@ pub fn command() -> Command {
                 .help("Don't write files")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            clap::Arg::new("check")
+                .long("check")
+                .help("Fail if the generated output is out of date, without writing anything")
+                .conflicts_with_all(["dry-run", "audit"])
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             clap::Arg::new("fail-fast")
                 .long("fail-fast")
                 .help("Stop on first error")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            clap::Arg::new("locked")
+                .long("locked")
+                .help("Generate from the commits recorded in the lockfile, never newer ones")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("allow-breaking")
+                .long("allow-breaking")
+                .help("Generate even if the API frozen in api-freeze.json breaks")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("retry-quarantined")
+                .long("retry-quarantined")
+                .help("Generate quarantined sources again instead of skipping them")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("hermetic")
+                .long("hermetic")
+                .help("Keep timestamps, absolute paths and the environment out of outputs and the lockfile")
+                .conflicts_with("audit")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("audit")
+                .long("audit")
+                .help("Record the files written by this run in the audit manifest")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("deny")
+                .long("deny")
+                .help("Fail the run if it raises warnings of a category, or of `all`")
+                .value_name("warnings=CATEGORY")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(utils::tags_arg())
+        .arg(utils::prune_lock_arg())
+        .arg(
+            clap::Arg::new("format")
+                .long("format")
+                .help("Output format for the generation summary")
+                .value_parser(["text", "json"])
+                .default_value("text"),
+        )
 }
 
 pub async fn run(matches: &ArgMatches) -> Result<()> {
     info!("Starting Jsonnet library generation");
 
     let mut config = utils::load_config(matches)?;
+    utils::select_tags(matches, &mut config)?;
 
     // Override output path if specified
     if let Some(output_path) = matches.get_one::<String>("output") {
@@ -59,14 +115,70 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
         config.generation.fail_fast = true;
     }
 
-    let app = utils::create_app(config)?;
+    // Regenerate every schema file when forced
+    if matches.get_flag("force") {
+        config.generation.reuse_unchanged_schemas = false;
+    }
+
+    // Record this run in the audit manifest
+    if matches.get_flag("audit") {
+        config.audit.enabled = true;
+    }
+
+    for deny in matches.get_many::<String>("deny").unwrap_or_default() {
+        for category in denied_categories(deny)? {
+            if !config.warnings.deny.contains(&category) {
+                config.warnings.deny.push(category);
+            }
+        }
+    }
+
+    let app = utils::create_app(matches, config)?
+        .with_locked(matches.get_flag("locked"))
+        .with_allow_breaking(matches.get_flag("allow-breaking"))
+        .with_hermetic(matches.get_flag("hermetic"))
+        .with_retry_quarantined(matches.get_flag("retry-quarantined"));
     app.initialize().await?;
 
+    if matches.get_flag("check") {
+        let result = app.check().await?;
+        app.shutdown_plugins().await?;
+        print_quarantined(&result.generation.quarantined);
+
+        for source_result in &result.generation.results {
+            for error in &source_result.errors {
+                eprintln!("  {}: Error: {error}", source_result.source_name);
+            }
+        }
+        if result.is_up_to_date() {
+            println!(
+                "Generated output is up to date ({} files checked)",
+                result.files_checked
+            );
+            return Ok(());
+        }
+
+        const SHOWN: usize = 20;
+        for mismatch in result.mismatches.iter().take(SHOWN) {
+            println!("  {mismatch}");
+        }
+        if result.mismatches.len() > SHOWN {
+            println!("  ... and {} more", result.mismatches.len() - SHOWN);
+        }
+        return Err(anyhow::anyhow!(
+            "{} of {} generated files are out of date, with {} generation errors; run `gensonnet generate` and commit the result",
+            result.mismatches.len(),
+            result.files_checked,
+            result.generation.statistics.error_count
+        ));
+    }
+
     if matches.get_flag("dry-run") {
         info!("Dry run mode - no files will be written");
         println!("Dry run mode - no files will be written");
 
         let result = app.dry_run().await?;
+        app.shutdown_plugins().await?;
 
         println!("Dry run completed successfully!");
         println!(
@@ -134,7 +246,15 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
         return Ok(());
     }
 
+    utils::reconcile_lockfile(matches, &app)?;
     let result = app.generate().await?;
+    app.shutdown_plugins().await?;
+    print_quarantined(&result.quarantined);
+
+    if matches.get_one::<String>("format").map(String::as_str) == Some("json") {
+        println!("{}", serde_json::to_string_pretty(&result.statistics)?);
+        return Ok(());
+    }
 
     println!("Generation completed successfully!");
     println!(
@@ -152,6 +272,23 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
             "  {}: {} files generated",
             source_result.source_type, source_result.files_generated
         );
+        if let Some(plugin) = &source_result.plugin {
+            println!("    Plugin: {plugin}");
+        }
+        let metrics = &source_result.statistics.plugin_metrics;
+        for (name, value) in &metrics.counters {
+            println!("    {name}: {value}");
+        }
+        for (name, value) in &metrics.gauges {
+            println!("    {name}: {value}");
+        }
+        if source_result.schemas_reused > 0 {
+            println!(
+                "    Unchanged schemas: {}/{}",
+                source_result.schemas_reused,
+                source_result.schema_digests.len()
+            );
+        }
         if !source_result.errors.is_empty() {
             for error in source_result.errors {
                 eprintln!("    Error: {error}");
@@ -161,3 +298,34 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
 
     Ok(())
 }
+
+/// Warn about the sources a run skipped as quarantined
+fn print_quarantined(quarantined: &[String]) {
+    for source in quarantined {
+        eprintln!(
+            "WARNING: source {source} is quarantined and was skipped; its library is left as last generated. Pass --retry-quarantined to retry it"
+        );
+    }
+}
+
+/// Parse a `--deny warnings=CATEGORY` value into the categories it denies
+fn denied_categories(value: &str) -> Result<Vec<WarningCategory>> {
+    let categories = WarningCategory::ALL
+        .map(|category| category.as_str())
+        .join(", ");
+    let Some(name) = value.strip_prefix("warnings=") else {
+        return Err(anyhow::anyhow!(
+            "Invalid --deny value '{value}': expected warnings=CATEGORY"
+        ));
+    };
+    if name == "all" {
+        return Ok(WarningCategory::ALL.to_vec());
+    }
+    WarningCategory::parse(name)
+        .map(|category| vec![category])
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown warning category '{name}': expected all or one of {categories}"
+            )
+        })
+}
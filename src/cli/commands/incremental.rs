@@ -45,6 +45,8 @@ pub fn command() -> Command {
                 .value_name("NUM")
                 .default_value("4"),
         )
+        .arg(utils::tags_arg())
+        .arg(utils::prune_lock_arg())
 }
 
 pub async fn run(matches: &ArgMatches) -> Result<()> {
@@ -59,8 +61,9 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
         .parse()
         .map_err(|_| anyhow::anyhow!("Invalid max-workers value"))?;
 
-    let config = utils::load_config(matches)?;
-    let app = utils::create_app(config)?;
+    let mut config = utils::load_config(matches)?;
+    utils::select_tags(matches, &mut config)?;
+    let app = utils::create_app(matches, config)?;
 
     if dry_run {
         info!("Dry run mode - no files will be generated");
@@ -77,6 +80,9 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
                     status.dependent_sources.len()
                 );
                 println!("Estimated time: {}ms", status.estimated_time_ms);
+                for (source, estimate) in &status.source_estimates_ms {
+                    println!("  {source}: ~{estimate}ms");
+                }
 
                 if parallel {
                     println!("Parallel processing would be enabled with {max_workers} workers");
@@ -87,7 +93,7 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
                 println!("This might be due to repository access issues.");
                 println!(
                     "Total sources in configuration: {}",
-                    app.config.sources.len()
+                    app.config().sources.len()
                 );
             }
         }
@@ -96,6 +102,7 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
     }
 
     app.initialize().await?;
+    utils::reconcile_lockfile(matches, &app)?;
 
     if force {
         info!("Force flag set - performing full generation");
@@ -111,7 +118,7 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
             Ok(status) => {
                 if force {
                     // Force mode: process all sources
-                    app.config.sources.iter().collect::<Vec<_>>()
+                    app.config().sources.iter().collect::<Vec<_>>()
                 } else {
                     // Incremental mode: process only changed and dependent sources
                     let mut sources = Vec::new();
@@ -131,7 +138,7 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
             Err(e) => {
                 println!("Could not determine incremental status: {e}");
                 println!("Falling back to processing all sources");
-                app.config.sources.iter().collect::<Vec<_>>()
+                app.config().sources.iter().collect::<Vec<_>>()
             }
         };
 
@@ -154,7 +161,7 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
             0.0
         } else {
             // Try to get the incremental plan to calculate cache hit rate
-            match app.lockfile_manager.get_incremental_plan(
+            match app.lockfile_manager().get_incremental_plan(
                 &sources_to_process
                     .iter()
                     .map(|s| s.name().to_string())
@@ -163,7 +170,7 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
                 Ok(plan) => app.calculate_cache_hit_rate(&plan),
                 Err(_) => {
                     // Fallback calculation based on sources processed
-                    let total_sources = app.config.sources.len();
+                    let total_sources = app.config().sources.len();
                     let processed_sources = sources_to_process.len();
                     if total_sources > 0 {
                         (total_sources - processed_sources) as f64 / total_sources as f64
@@ -175,22 +182,19 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
         };
 
         // Create generation result
-        let total_errors: usize = results.iter().map(|r| r.errors.len()).sum();
-        let total_warnings: usize = results.iter().map(|r| r.warnings.len()).sum();
-        let files_generated: usize = results.iter().map(|r| r.files_generated).sum();
-
+        let total_processing_time_ms = results.iter().map(|r| r.processing_time_ms).sum();
         let result = jsonnet_generator::GenerationResult {
             sources_processed: results.len(),
-            total_sources: app.config.sources.len(),
+            total_sources: app.config().sources.len(),
             results: results.clone(),
-            statistics: jsonnet_generator::result::GenerationStatistics {
-                total_processing_time_ms: results.iter().map(|r| r.processing_time_ms).sum(),
-                sources_processed: results.len(),
-                files_generated,
-                error_count: total_errors,
-                warning_count: total_warnings,
+            statistics: jsonnet_generator::GenerationStatistics {
                 cache_hit_rate,
+                ..jsonnet_generator::result::generation_statistics(
+                    &results,
+                    total_processing_time_ms,
+                )
             },
+            quarantined: Vec::new(),
         };
 
         // Display results
@@ -200,6 +204,7 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
     }
 
     let result = app.generate().await?;
+    app.shutdown_plugins().await?;
 
     println!("Incremental generation completed successfully!");
     println!(
@@ -215,6 +220,10 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
         "Cache hit rate: {:.1}%",
         result.statistics.cache_hit_rate * 100.0
     );
+    println!(
+        "Schema cache hit rate: {:.1}%",
+        result.statistics.schema_cache_hit_rate * 100.0
+    );
 
     if result.statistics.error_count > 0 {
         println!("Errors encountered: {}", result.statistics.error_count);
@@ -330,6 +339,10 @@ fn display_generation_results(result: &jsonnet_generator::GenerationResult) {
         "Cache hit rate: {:.1}%",
         result.statistics.cache_hit_rate * 100.0
     );
+    println!(
+        "Schema cache hit rate: {:.1}%",
+        result.statistics.schema_cache_hit_rate * 100.0
+    );
 
     if result.statistics.error_count > 0 {
         println!("Errors encountered: {}", result.statistics.error_count);
@@ -1,19 +1,45 @@
 //! Info command implementation
 
+use crate::cli::utils;
+use crate::SourceInspection;
 use anyhow::Result;
 use clap::{ArgMatches, Command};
 
 pub fn command() -> Command {
-    Command::new("info").about("Show tool information").arg(
-        clap::Arg::new("detailed")
-            .short('d')
-            .long("detailed")
-            .help("Show detailed information")
-            .action(clap::ArgAction::SetTrue),
-    )
+    Command::new("info")
+        .about("Show tool information, or inspect one configured source")
+        .arg(
+            clap::Arg::new("detailed")
+                .short('d')
+                .long("detailed")
+                .help("Show detailed information")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("source")
+                .short('s')
+                .long("source")
+                .help("Inspect a source: its upstream commit, filters, plugin and output layout")
+                .value_name("NAME"),
+        )
+        .arg(
+            clap::Arg::new("config")
+                .short('c')
+                .long("config")
+                .help("Configuration file path")
+                .value_name("FILE"),
+        )
 }
 
 pub async fn run(matches: &ArgMatches) -> Result<()> {
+    if let Some(source) = matches.get_one::<String>("source") {
+        let config = utils::load_config(matches)?;
+        let app = utils::create_app(matches, config)?;
+        app.initialize_plugins().await?;
+        print_inspection(&app.inspect_source(source).await?);
+        return Ok(());
+    }
+
     let detailed = matches.get_flag("detailed");
 
     println!("JsonnetGen - Type-safe Jsonnet Library Generator");
@@ -36,3 +62,59 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
 
     Ok(())
 }
+
+fn print_inspection(inspection: &SourceInspection) {
+    println!("Source: {} ({})", inspection.name, inspection.source_type);
+    println!("  URL: {}", inspection.url);
+    if let Some(git_ref) = &inspection.git_ref {
+        println!("  Ref: {git_ref}");
+    }
+    match &inspection.upstream {
+        Ok(commit) => println!("  Upstream commit: {commit}"),
+        Err(e) => println!("  Upstream commit: unavailable ({e})"),
+    }
+    println!(
+        "  Locked commit: {}",
+        inspection
+            .pinned
+            .as_deref()
+            .unwrap_or("none (never generated)")
+    );
+    if let Some(binding) = &inspection.plugin_binding {
+        match &inspection.plugin {
+            Some(plugin) => println!("  Plugin: {binding} -> {plugin}"),
+            None => println!("  Plugin: {binding}"),
+        }
+    }
+
+    if let Some(found) = inspection.crds_found {
+        println!("  CRDs found: {found}");
+    }
+    if inspection.filters.is_empty() {
+        println!("  Filters: none (everything is selected)");
+    } else {
+        println!("  Filters:");
+        for filter in &inspection.filters {
+            match filter.matched {
+                Some(matched) => println!("    {}: {} matched", filter.filter, matched),
+                None => println!("    {}", filter.filter),
+            }
+        }
+    }
+    if !inspection.excluded.is_empty() {
+        println!("  Excluded by filters:");
+        for excluded in &inspection.excluded {
+            println!("    {excluded}");
+        }
+    }
+
+    println!("  Output: {}", inspection.output_path.display());
+    if inspection.crds_found.is_some() {
+        if inspection.layout.is_empty() {
+            println!("    (nothing would be generated)");
+        }
+        for file in &inspection.layout {
+            println!("    {file}");
+        }
+    }
+}
@@ -24,15 +24,25 @@ pub fn command() -> Command {
                 .help("Create example configuration")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            clap::Arg::new("demo")
+                .long("demo")
+                .help("Create a configuration generating from built-in fixtures, offline")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("example"),
+        )
 }
 
 pub async fn run(matches: &ArgMatches) -> Result<()> {
     let output_path = PathBuf::from(matches.get_one::<String>("output").unwrap());
     let example = matches.get_flag("example");
+    let demo = matches.get_flag("demo");
 
     info!("Initializing configuration file: {:?}", output_path);
 
-    let config = if example {
+    let config = if demo {
+        create_demo_config()
+    } else if example {
         create_example_config()
     } else {
         Config::default()
@@ -42,7 +52,10 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
 
     info!("Configuration file created: {:?}", output_path);
 
-    if example {
+    if demo {
+        println!("Demo configuration created with built-in fixture sources.");
+        println!("Run `jsonnet-gen generate` to generate libraries without network access.");
+    } else if example {
         println!("Example configuration created with sample CRD sources.");
         println!("Edit the file to customize your sources and settings.");
     } else {
@@ -67,8 +80,43 @@ fn create_example_config() -> Config {
                 auth: None,
             },
             filters: vec!["example.com/v1".to_string()],
+            versions: Default::default(),
             output_path: PathBuf::from("./generated/example"),
+            plugin: Default::default(),
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+            samples: Vec::new(),
         }));
 
     config
 }
+
+fn create_demo_config() -> Config {
+    let mut config = Config::default();
+
+    let fixture_source = |name: &str, fixture| {
+        crate::config::Source::Fixture(crate::config::FixtureSource {
+            name: name.to_string(),
+            fixture,
+            filters: Vec::new(),
+            versions: Default::default(),
+            output_path: PathBuf::from(format!("./generated/{name}")),
+            plugin: Default::default(),
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+        })
+    };
+    config
+        .sources
+        .push(fixture_source("demo-crds", crate::config::FixtureSet::Crds));
+    if cfg!(feature = "openapi") {
+        config.sources.push(fixture_source(
+            "demo-openapi",
+            crate::config::FixtureSet::OpenApi,
+        ));
+    }
+
+    config
+}
@@ -21,15 +21,28 @@ pub fn command() -> Command {
                 .help("Update lockfile")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(crate::cli::utils::tags_arg())
+        .subcommand(
+            Command::new("export")
+                .about("Export the lockfile in a stable format for external tooling")
+                .arg(
+                    clap::Arg::new("json")
+                        .long("json")
+                        .help("Export as JSON instead of YAML")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
 }
 
 pub async fn run(matches: &ArgMatches) -> Result<()> {
     let lockfile_manager = LockfileManager::new(LockfileManager::default_path());
 
-    if matches.get_flag("status") {
+    if let Some(("export", export_matches)) = matches.subcommand() {
+        export_lockfile(&lockfile_manager, export_matches.get_flag("json"))?;
+    } else if matches.get_flag("status") {
         show_lock_status(&lockfile_manager).await?;
     } else if matches.get_flag("update") {
-        update_lockfile(&lockfile_manager).await?;
+        update_lockfile(&lockfile_manager, matches).await?;
     } else {
         println!("Use --status to show lockfile status or --update to update the lockfile");
     }
@@ -59,13 +72,26 @@ async fn show_lock_status(lockfile_manager: &LockfileManager) -> Result<()> {
     for (source_id, entry) in &lockfile.sources {
         println!(
             "  Source {}: {}@{} ({})",
-            source_id, entry.url, entry.ref_name, entry.commit_sha
+            source_id,
+            entry.url,
+            entry.ref_name,
+            entry.revision()
         );
     }
 
     Ok(())
 }
 
+fn export_lockfile(lockfile_manager: &LockfileManager, json: bool) -> Result<()> {
+    let export = lockfile_manager.export()?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&export)?);
+    } else {
+        print!("{}", serde_yaml::to_string(&export)?);
+    }
+    Ok(())
+}
+
 /// Load configuration from file or create default
 fn load_config() -> Result<crate::Config> {
     // Look for default config files
@@ -89,14 +115,21 @@ fn load_config() -> Result<crate::Config> {
     Ok(crate::Config::default())
 }
 
-async fn update_lockfile(lockfile_manager: &LockfileManager) -> Result<()> {
+async fn update_lockfile(lockfile_manager: &LockfileManager, matches: &ArgMatches) -> Result<()> {
     info!("Updating lockfile");
 
     // Load configuration to get current sources
-    let config = load_config()?;
+    let mut config = load_config()?;
+    let all_sources = config.sources.clone();
+    crate::cli::utils::select_tags(matches, &mut config)?;
 
     // Create GitManager for getting commit SHAs
-    let git_manager = crate::GitManager::new()?;
+    let git_manager = crate::GitManager::new()?
+        .with_retry_policy(config.git.retry.clone())
+        .with_max_concurrent_per_host(config.git.max_concurrent_per_host);
+    let url_fetcher = crate::UrlFetcher::new()?.with_retry_policy(config.git.retry.clone());
+    let release_resolver =
+        crate::ReleaseResolver::new().with_retry_policy(config.git.retry.clone());
 
     // Get current commit SHAs for all sources
     let mut current_sources = std::collections::HashMap::new();
@@ -236,6 +269,227 @@ async fn update_lockfile(lockfile_manager: &LockfileManager) -> Result<()> {
                 current_sources.insert(source_name.clone(), commit_sha);
                 source_entries.insert(source_name, entry);
             }
+            crate::config::Source::Url(url_source) => {
+                // Fetch the artifact and record its digest
+                let artifact = match url_fetcher
+                    .fetch(url_source, url_source.sha256.as_deref())
+                    .await
+                {
+                    Ok(artifact) => artifact,
+                    Err(e) => {
+                        warn!("Failed to fetch {}: {}", url_source.url, e);
+                        println!("Skipping source '{source_name}' due to fetch error");
+                        continue;
+                    }
+                };
+
+                // Create lockfile entry
+                let entry = jsonnet_lockfile::LockfileEntry::new(
+                    url_source.url.clone(),
+                    String::new(),
+                    artifact.digest.clone(),
+                    url_source.filters.clone(),
+                );
+
+                current_sources.insert(source_name.clone(), artifact.digest);
+                source_entries.insert(source_name, entry);
+            }
+            crate::config::Source::Fixture(fixture_source) => {
+                // Record the digest of the embedded fixture set
+                let digest = crate::fixture::digest(fixture_source.fixture);
+                let entry = jsonnet_lockfile::LockfileEntry::new(
+                    fixture_source.fixture.url().to_string(),
+                    fixture_source.fixture.as_str().to_string(),
+                    digest.clone(),
+                    fixture_source.filters.clone(),
+                );
+
+                current_sources.insert(source_name.clone(), digest);
+                source_entries.insert(source_name, entry);
+            }
+            crate::config::Source::Jsonnet(jsonnet_source) => {
+                // Record the digest of the library's files
+                let digest = match crate::library::Library::open(jsonnet_source)
+                    .and_then(|library| library.digest())
+                {
+                    Ok(digest) => digest,
+                    Err(e) => {
+                        warn!("Failed to read {}: {}", jsonnet_source.path.display(), e);
+                        println!("Skipping source '{source_name}' due to read error");
+                        continue;
+                    }
+                };
+                let entry = jsonnet_lockfile::LockfileEntry::new(
+                    jsonnet_source.url().to_string(),
+                    String::new(),
+                    digest.clone(),
+                    Vec::new(),
+                );
+
+                current_sources.insert(source_name.clone(), digest);
+                source_entries.insert(source_name, entry);
+            }
+            crate::config::Source::Release(release_source) => {
+                // Resolve the release and record the digest of its asset
+                let (asset, artifact) = match release_resolver.resolve(release_source).await {
+                    Ok(asset) => match url_fetcher.fetch_url(&asset.url, None).await {
+                        Ok(artifact) => (asset, artifact),
+                        Err(e) => {
+                            warn!("Failed to fetch {}: {}", asset.url, e);
+                            println!("Skipping source '{source_name}' due to fetch error");
+                            continue;
+                        }
+                    },
+                    Err(e) => {
+                        warn!(
+                            "Failed to resolve release of {}: {}",
+                            release_source.repository(),
+                            e
+                        );
+                        println!("Skipping source '{source_name}' due to release lookup error");
+                        continue;
+                    }
+                };
+
+                // Create lockfile entry
+                let entry = jsonnet_lockfile::LockfileEntry::new(
+                    release_source.repository().to_string(),
+                    asset.tag,
+                    artifact.digest.clone(),
+                    release_source.filters.clone(),
+                );
+
+                current_sources.insert(source_name.clone(), artifact.digest);
+                source_entries.insert(source_name, entry);
+            }
+            crate::config::Source::Kustomize(kustomize_source) => {
+                // Get repository path and current commit
+                let repo_path = match git_manager.ensure_repository(&kustomize_source.git).await {
+                    Ok(path) => path,
+                    Err(e) => {
+                        warn!(
+                            "Failed to access repository {}: {}",
+                            kustomize_source.git.url, e
+                        );
+                        println!("Skipping source '{source_name}' due to repository access error");
+                        continue;
+                    }
+                };
+                let commit_sha = match git_manager.get_current_commit(&repo_path) {
+                    Ok(sha) => sha,
+                    Err(e) => {
+                        warn!(
+                            "Failed to get commit SHA for {}: {}",
+                            kustomize_source.git.url, e
+                        );
+                        println!("Skipping source '{source_name}' due to commit access error");
+                        continue;
+                    }
+                };
+
+                // Create lockfile entry
+                let entry = jsonnet_lockfile::LockfileEntry::new(
+                    kustomize_source.git.url.clone(),
+                    kustomize_source
+                        .git
+                        .ref_name
+                        .clone()
+                        .unwrap_or_else(|| "main".to_string()),
+                    commit_sha.clone(),
+                    kustomize_source.filters.clone(),
+                );
+
+                current_sources.insert(source_name.clone(), commit_sha);
+                source_entries.insert(source_name, entry);
+            }
+            crate::config::Source::Catalog(catalog_source) => {
+                // Get repository path and current commit
+                let repo_path = match git_manager.ensure_repository(&catalog_source.git).await {
+                    Ok(path) => path,
+                    Err(e) => {
+                        warn!(
+                            "Failed to access repository {}: {}",
+                            catalog_source.git.url, e
+                        );
+                        println!("Skipping source '{source_name}' due to repository access error");
+                        continue;
+                    }
+                };
+                let commit_sha = match git_manager.get_current_commit(&repo_path) {
+                    Ok(sha) => sha,
+                    Err(e) => {
+                        warn!(
+                            "Failed to get commit SHA for {}: {}",
+                            catalog_source.git.url, e
+                        );
+                        println!("Skipping source '{source_name}' due to commit access error");
+                        continue;
+                    }
+                };
+                let catalog_entries =
+                    match crate::catalog::discover(&repo_path, &catalog_source.discovery) {
+                        Ok(entries) => entries,
+                        Err(e) => {
+                            warn!("Failed to discover catalog {}: {}", source_name, e);
+                            println!("Skipping source '{source_name}' due to discovery error");
+                            continue;
+                        }
+                    };
+
+                // Create one lockfile entry per discovered source
+                for catalog_entry in catalog_entries {
+                    let entry = jsonnet_lockfile::LockfileEntry::new(
+                        catalog_source.git.url.clone(),
+                        catalog_source.git.ref_name().to_string(),
+                        commit_sha.clone(),
+                        vec![catalog_entry.path.to_string_lossy().to_string()],
+                    );
+                    let source_id = catalog_entry.source_id(&source_name);
+                    current_sources.insert(source_id.clone(), commit_sha.clone());
+                    source_entries.insert(source_id, entry);
+                }
+            }
+            crate::config::Source::Auto(auto_source) => {
+                // Detection needs the plugins that vote on the repository's files
+                let app = crate::JsonnetGen::new(config.clone())?;
+                app.initialize_plugins().await?;
+                let logical_sources = match app.detect_auto_source(auto_source).await {
+                    Ok(sources) => sources,
+                    Err(e) => {
+                        warn!("Failed to detect contents of {}: {}", source_name, e);
+                        println!("Skipping source '{source_name}' due to detection error");
+                        continue;
+                    }
+                };
+                let commit_sha = match git_manager
+                    .ensure_repository(&auto_source.git)
+                    .await
+                    .and_then(|repo_path| git_manager.get_current_commit(&repo_path))
+                {
+                    Ok(sha) => sha,
+                    Err(e) => {
+                        warn!(
+                            "Failed to get commit SHA for {}: {}",
+                            auto_source.git.url, e
+                        );
+                        println!("Skipping source '{source_name}' due to commit access error");
+                        continue;
+                    }
+                };
+
+                // Create one lockfile entry per detected source
+                for logical_source in logical_sources {
+                    let entry = jsonnet_lockfile::LockfileEntry::new(
+                        auto_source.git.url.clone(),
+                        auto_source.git.ref_name().to_string(),
+                        commit_sha.clone(),
+                        logical_source.filters().to_vec(),
+                    );
+                    let source_id = logical_source.name().to_string();
+                    current_sources.insert(source_id.clone(), commit_sha.clone());
+                    source_entries.insert(source_id, entry);
+                }
+            }
         }
     }
 
@@ -265,6 +519,20 @@ async fn update_lockfile(lockfile_manager: &LockfileManager) -> Result<()> {
         }
     }
 
+    // Sources not selected by `--tags` keep their entries
+    if config.sources.len() < all_sources.len() {
+        let selected = |source_id: &str| {
+            config.sources.iter().any(|source| {
+                source_id == source.name() || source_id.starts_with(&format!("{}/", source.name()))
+            })
+        };
+        for (source_id, entry) in lockfile_manager.load_or_create()?.sources {
+            if !selected(&source_id) {
+                source_entries.entry(source_id).or_insert(entry);
+            }
+        }
+    }
+
     // Update the lockfile
     lockfile_manager.update(source_entries, file_checksums.clone())?;
 
@@ -1,12 +1,22 @@
 //! CLI command modules
 
+pub mod audit;
 pub mod cleanup;
+pub mod config;
+pub mod freeze;
 pub mod generate;
 pub mod incremental;
 pub mod info;
 pub mod init;
 pub mod lock;
 pub mod plugins;
+pub mod profile;
+pub mod selftest;
 pub mod status;
 pub mod test;
+#[cfg(feature = "ui")]
+pub mod ui;
 pub mod validate;
+pub mod watch;
+pub mod workspace;
+pub mod x;
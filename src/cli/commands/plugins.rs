@@ -252,6 +252,12 @@ async fn run_info(args: InfoArgs) -> Result<()> {
         for capability in &plugin.capabilities {
             println!("    - {capability:?}");
         }
+        if !plugin.commands.is_empty() {
+            println!("  Commands:");
+            for command in &plugin.commands {
+                println!("    - {}: {}", command.name, command.about);
+            }
+        }
     } else {
         println!("Plugin '{}' not found.", args.plugin_id);
     }
@@ -358,7 +364,13 @@ async fn create_app() -> Result<JsonnetGen> {
                 auth: None,
             },
             filters: vec![],
+            versions: Default::default(),
             output_path: std::path::PathBuf::from("./dummy"),
+            plugin: Default::default(),
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+            samples: Vec::new(),
         }));
     let app = crate::JsonnetGen::new(config)?;
     app.initialize().await?;
@@ -0,0 +1,141 @@
+//! Profile-lib command implementation
+
+use crate::cli::utils;
+use crate::cluster::find_samples;
+use crate::profile::LibraryProfiler;
+use anyhow::Result;
+use clap::{ArgMatches, Command};
+use std::path::PathBuf;
+use tracing::info;
+
+pub fn command() -> Command {
+    Command::new("profile-lib")
+        .about("Report how long the resources of generated libraries take to evaluate")
+        .arg(
+            clap::Arg::new("config")
+                .short('c')
+                .long("config")
+                .help("Configuration file path")
+                .value_name("FILE"),
+        )
+        .arg(
+            clap::Arg::new("path")
+                .short('p')
+                .long("path")
+                .help("Directory to search for samples instead of the configured output paths")
+                .value_name("DIR"),
+        )
+        .arg(
+            clap::Arg::new("jsonnet")
+                .long("jsonnet")
+                .help("Jsonnet evaluator binary")
+                .value_name("BIN")
+                .default_value("jsonnet"),
+        )
+        .arg(
+            clap::Arg::new("jpath")
+                .short('J')
+                .long("jpath")
+                .help("Additional library search path (e.g. for k.libsonnet)")
+                .value_name("DIR")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            clap::Arg::new("iterations")
+                .long("iterations")
+                .help("Evaluate each resource this many times, keeping the fastest run")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("3"),
+        )
+        .arg(
+            clap::Arg::new("format")
+                .long("format")
+                .help("Output format for the report")
+                .value_parser(["text", "json"])
+                .default_value("text"),
+        )
+}
+
+pub async fn run(matches: &ArgMatches) -> Result<()> {
+    let search_dirs: Vec<PathBuf> = match matches.get_one::<String>("path") {
+        Some(path) => vec![PathBuf::from(path)],
+        None => {
+            let config = utils::load_config(matches)?;
+            let mut dirs: Vec<PathBuf> = config
+                .sources
+                .iter()
+                .map(|source| source.output_path().to_path_buf())
+                .collect();
+            dirs.sort();
+            dirs.dedup();
+            dirs
+        }
+    };
+
+    let files: Vec<PathBuf> = search_dirs
+        .iter()
+        .flat_map(|dir| find_samples(dir))
+        .collect();
+    if files.is_empty() {
+        println!("No sample resources found. Run 'gensonnet generate' first.");
+        return Ok(());
+    }
+
+    let profiler = LibraryProfiler::new()
+        .with_evaluator(matches.get_one::<String>("jsonnet").unwrap().clone())
+        .with_jpaths(
+            matches
+                .get_many::<String>("jpath")
+                .map(|paths| paths.map(PathBuf::from).collect())
+                .unwrap_or_default(),
+        )
+        .with_iterations(*matches.get_one::<usize>("iterations").unwrap());
+
+    info!("Profiling {} libraries", files.len());
+    let mut profiles = Vec::new();
+    for file in &files {
+        profiles.push(profiler.profile(file).await?);
+    }
+
+    if matches.get_one::<String>("format").map(String::as_str) == Some("json") {
+        println!("{}", serde_json::to_string_pretty(&profiles)?);
+        return Ok(());
+    }
+
+    let mut resources = 0;
+    let mut hotspots = 0;
+    for profile in &profiles {
+        println!(
+            "{} (evaluator startup {:.1}ms, not included)",
+            profile.file.display(),
+            profile.baseline_ms
+        );
+        for resource in &profile.resources {
+            resources += 1;
+            let memory = resource
+                .peak_memory_kib
+                .map(|kib| format!("{:.1} MiB", kib as f64 / 1024.0))
+                .unwrap_or_else(|| "-".to_string());
+            let hotspot = if resource.is_validation_hotspot() {
+                hotspots += 1;
+                "  validation hotspot"
+            } else {
+                ""
+            };
+            println!(
+                "  {:<40} {:>9.1}ms  validation {:>9.1}ms ({:>3.0}%)  peak {:>10}{}",
+                resource.resource,
+                resource.eval_ms,
+                resource.validation_ms,
+                resource.validation_share() * 100.0,
+                memory,
+                hotspot
+            );
+        }
+    }
+
+    println!();
+    println!("{resources} resources profiled, {hotspots} validation hotspots");
+    Ok(())
+}
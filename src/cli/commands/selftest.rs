@@ -0,0 +1,101 @@
+//! Selftest command implementation
+
+use crate::cli::utils;
+use crate::selftest::{find_selftests, SelftestRunner};
+use anyhow::{anyhow, Result};
+use clap::{ArgMatches, Command};
+use std::path::PathBuf;
+use tracing::info;
+
+pub fn command() -> Command {
+    Command::new("selftest")
+        .about("Evaluate the self-tests emitted with generated libraries")
+        .arg(
+            clap::Arg::new("config")
+                .short('c')
+                .long("config")
+                .help("Configuration file path")
+                .value_name("FILE"),
+        )
+        .arg(
+            clap::Arg::new("path")
+                .short('p')
+                .long("path")
+                .help("Directory to search for self-tests instead of the configured output paths")
+                .value_name("DIR"),
+        )
+        .arg(
+            clap::Arg::new("jsonnet")
+                .long("jsonnet")
+                .help("Jsonnet evaluator binary")
+                .value_name("BIN")
+                .default_value("jsonnet"),
+        )
+        .arg(
+            clap::Arg::new("jpath")
+                .short('J')
+                .long("jpath")
+                .help("Additional library search path (e.g. for k.libsonnet)")
+                .value_name("DIR")
+                .action(clap::ArgAction::Append),
+        )
+}
+
+pub async fn run(matches: &ArgMatches) -> Result<()> {
+    let search_dirs: Vec<PathBuf> = match matches.get_one::<String>("path") {
+        Some(path) => vec![PathBuf::from(path)],
+        None => {
+            let config = utils::load_config(matches)?;
+            let mut dirs: Vec<PathBuf> = config
+                .sources
+                .iter()
+                .map(|source| source.output_path().to_path_buf())
+                .collect();
+            dirs.sort();
+            dirs.dedup();
+            dirs
+        }
+    };
+
+    let files: Vec<PathBuf> = search_dirs
+        .iter()
+        .flat_map(|dir| find_selftests(dir))
+        .collect();
+    if files.is_empty() {
+        println!("No self-tests found. Run 'gensonnet generate' first.");
+        return Ok(());
+    }
+
+    let runner = SelftestRunner::new()
+        .with_evaluator(matches.get_one::<String>("jsonnet").unwrap().clone())
+        .with_jpaths(
+            matches
+                .get_many::<String>("jpath")
+                .map(|paths| paths.map(PathBuf::from).collect())
+                .unwrap_or_default(),
+        );
+
+    info!("Running {} self-tests", files.len());
+    let mut failed = 0;
+    for file in &files {
+        let outcome = runner.run(file).await?;
+        if outcome.passed {
+            println!("PASS {}", file.display());
+        } else {
+            failed += 1;
+            println!("FAIL {}", file.display());
+            for line in outcome.message.lines() {
+                println!("  {line}");
+            }
+        }
+    }
+
+    println!();
+    println!("{} passed, {} failed", files.len() - failed, failed);
+
+    if failed > 0 {
+        return Err(anyhow!("{} self-tests failed", failed));
+    }
+
+    Ok(())
+}
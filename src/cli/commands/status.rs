@@ -22,13 +22,15 @@ pub fn command() -> Command {
                 .help("Show detailed information")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(utils::tags_arg())
 }
 
 pub async fn run(matches: &ArgMatches) -> Result<()> {
     info!("Checking generation status");
 
-    let config = utils::load_config(matches)?;
-    let app = utils::create_app(config)?;
+    let mut config = utils::load_config(matches)?;
+    utils::select_tags(matches, &mut config)?;
+    let app = utils::create_app(matches, config)?;
 
     let status = app.get_status().await?;
 
@@ -41,13 +43,16 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
     println!("  Sources configured: {}", status.sources_count);
 
     if !status.changed_sources.is_empty() {
-        println!("  Changed sources: {}", status.changed_sources.join(", "));
+        println!(
+            "  Changed sources: {}",
+            with_estimates(&status.changed_sources, &status)
+        );
     }
 
     if !status.dependent_sources.is_empty() {
         println!(
             "  Dependent sources: {}",
-            status.dependent_sources.join(", ")
+            with_estimates(&status.dependent_sources, &status)
         );
     }
 
@@ -81,7 +86,23 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
             "  Cache hit rate: {:.1}%",
             status.statistics.cache_hit_rate * 100.0
         );
+        println!(
+            "  Schema cache hit rate: {:.1}%",
+            status.statistics.schema_cache_hit_rate * 100.0
+        );
     }
 
     Ok(())
 }
+
+/// Source names followed by their estimated time, where one was recorded
+fn with_estimates(sources: &[String], status: &crate::GenerationStatus) -> String {
+    sources
+        .iter()
+        .map(|source| match status.source_estimates_ms.get(source) {
+            Some(estimate) => format!("{source} (~{estimate}ms)"),
+            None => source.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
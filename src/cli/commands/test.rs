@@ -1,8 +1,10 @@
 //! Plugin testing CLI commands
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::{Args, FromArgMatches, Subcommand};
+use std::path::PathBuf;
 
+use crate::cluster::{find_samples, ClusterDryRun};
 use crate::plugin::testing::*;
 
 #[derive(Subcommand)]
@@ -85,8 +87,63 @@ pub struct ReportArgs {
 /// Create the test command
 pub fn command() -> clap::Command {
     clap::Command::new("test")
-        .about("Run plugin tests")
+        .about("Run plugin tests, or check generated libraries against a cluster")
         .subcommand_negates_reqs(true)
+        .arg(
+            clap::Arg::new("cluster-dry-run")
+                .long("cluster-dry-run")
+                .help("Submit the sample resources of generated libraries with server-side dry-run")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("config")
+                .short('c')
+                .long("config")
+                .help("Configuration file path")
+                .value_name("FILE"),
+        )
+        .arg(
+            clap::Arg::new("path")
+                .short('p')
+                .long("path")
+                .help("Directory to search for samples instead of the configured output paths")
+                .value_name("DIR"),
+        )
+        .arg(
+            clap::Arg::new("jsonnet")
+                .long("jsonnet")
+                .help("Jsonnet evaluator binary")
+                .value_name("BIN")
+                .default_value("jsonnet"),
+        )
+        .arg(
+            clap::Arg::new("jpath")
+                .short('J')
+                .long("jpath")
+                .help("Additional library search path (e.g. for k.libsonnet)")
+                .value_name("DIR")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            clap::Arg::new("kubectl")
+                .long("kubectl")
+                .help("kubectl binary")
+                .value_name("BIN")
+                .default_value("kubectl"),
+        )
+        .arg(
+            clap::Arg::new("context")
+                .long("context")
+                .help("Kubeconfig context to submit to")
+                .value_name("NAME"),
+        )
+        .arg(
+            clap::Arg::new("namespace")
+                .short('n')
+                .long("namespace")
+                .help("Namespace for namespaced resources")
+                .value_name("NAME"),
+        )
         .subcommand(RunArgs::augment_args(
             clap::Command::new("run").about("Run plugin tests"),
         ))
@@ -120,6 +177,7 @@ pub async fn run(matches: &clap::ArgMatches) -> Result<()> {
             let args = ReportArgs::from_arg_matches(sub_matches)?;
             generate_test_report(args).await
         }
+        _ if matches.get_flag("cluster-dry-run") => run_cluster_dry_run(matches).await,
         _ => {
             let _ = command().print_help();
             Ok(())
@@ -127,6 +185,84 @@ pub async fn run(matches: &clap::ArgMatches) -> Result<()> {
     }
 }
 
+async fn run_cluster_dry_run(matches: &clap::ArgMatches) -> Result<()> {
+    let search_dirs: Vec<PathBuf> = match matches.get_one::<String>("path") {
+        Some(path) => vec![PathBuf::from(path)],
+        None => {
+            let config = crate::cli::utils::load_config(matches)?;
+            let mut dirs: Vec<PathBuf> = config
+                .sources
+                .iter()
+                .map(|source| source.output_path().to_path_buf())
+                .collect();
+            dirs.sort();
+            dirs.dedup();
+            dirs
+        }
+    };
+
+    let files: Vec<PathBuf> = search_dirs
+        .iter()
+        .flat_map(|dir| find_samples(dir))
+        .collect();
+    if files.is_empty() {
+        println!("No sample resources found. Run 'gensonnet generate' first.");
+        return Ok(());
+    }
+
+    let dry_run = ClusterDryRun::new()
+        .with_evaluator(matches.get_one::<String>("jsonnet").unwrap().clone())
+        .with_jpaths(
+            matches
+                .get_many::<String>("jpath")
+                .map(|paths| paths.map(PathBuf::from).collect())
+                .unwrap_or_default(),
+        )
+        .with_kubectl(matches.get_one::<String>("kubectl").unwrap().clone())
+        .with_context(matches.get_one::<String>("context").cloned())
+        .with_namespace(matches.get_one::<String>("namespace").cloned());
+
+    let mut accepted = 0;
+    let mut rejected = 0;
+    let mut unevaluated = 0;
+    for file in &files {
+        println!("{}", file.display());
+        let outcomes = match dry_run.run(file).await {
+            Ok(outcomes) => outcomes,
+            Err(e) => {
+                unevaluated += 1;
+                println!("  ERROR {e}");
+                continue;
+            }
+        };
+
+        for outcome in outcomes {
+            if outcome.accepted {
+                accepted += 1;
+                println!("  ACCEPTED {}", outcome.resource);
+            } else {
+                rejected += 1;
+                println!("  REJECTED {}", outcome.resource);
+                for line in outcome.message.lines() {
+                    println!("    {line}");
+                }
+            }
+        }
+    }
+
+    println!();
+    println!("{accepted} accepted, {rejected} rejected");
+
+    if unevaluated > 0 {
+        return Err(anyhow!("{} sample files failed to evaluate", unevaluated));
+    }
+    if rejected > 0 {
+        return Err(anyhow!("{} resources were rejected", rejected));
+    }
+
+    Ok(())
+}
+
 async fn run_tests(args: RunArgs) -> Result<()> {
     println!("Running plugin tests...");
 
@@ -621,9 +757,7 @@ async fn discover_plugin_test_suites() -> Result<Vec<TestSuiteInfo>> {
                 for entry in entries.flatten() {
                     let test_suite_path = entry.path().join("test-suite.yaml");
                     if test_suite_path.exists() {
-                        if let Ok(test_suite) =
-                            load_test_suite_from_file(&test_suite_path).await
-                        {
+                        if let Ok(test_suite) = load_test_suite_from_file(&test_suite_path).await {
                             suites.push(TestSuiteInfo {
                                 name: test_suite.name.clone(),
                                 description: test_suite.description.clone(),
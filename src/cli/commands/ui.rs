@@ -0,0 +1,355 @@
+//! UI command implementation
+//!
+//! An interactive dashboard listing every configured source with the commit
+//! pinned in the lockfile, the upstream commit of its ref and the result of
+//! its last generation. Sources can be checked for upstream changes and
+//! regenerated one at a time.
+
+use crate::cli::utils;
+use crate::config::Source;
+use crate::JsonnetGen;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::{ArgMatches, Command};
+use jsonnet_generator::SourceResult;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+
+/// Key bindings shown in the footer
+const HELP: &str =
+    "↑/↓ select  g generate  u check upstream  U check all  r reload lockfile  q quit";
+
+pub fn command() -> Command {
+    Command::new("ui")
+        .about("Interactive dashboard of sources, pinned commits and generation results")
+        .arg(
+            clap::Arg::new("config")
+                .short('c')
+                .long("config")
+                .help("Configuration file path")
+                .value_name("FILE"),
+        )
+}
+
+pub async fn run(matches: &ArgMatches) -> Result<()> {
+    let config = utils::load_config(matches)?;
+    let app = utils::create_app(matches, config)?;
+    app.initialize().await?;
+
+    let mut dashboard = Dashboard::new(app)?;
+    let mut terminal = ratatui::init();
+    let result = dashboard.run(&mut terminal).await;
+    ratatui::restore();
+    result
+}
+
+/// Upstream commit of a source, as last checked
+enum Upstream {
+    /// Not checked yet
+    Unchecked,
+
+    /// Commit SHA (or artifact digest) of the configured ref
+    Commit(String),
+
+    /// The repository or artifact could not be fetched
+    Error(String),
+}
+
+/// A source as shown in the dashboard
+struct SourceRow {
+    name: String,
+    source_type: String,
+    pinned: Option<String>,
+    fetched_at: Option<DateTime<Utc>>,
+    upstream: Upstream,
+    last_result: Option<SourceResult>,
+}
+
+impl SourceRow {
+    /// Whether the pinned commit matches upstream, with the color to show it in
+    fn state(&self) -> (&'static str, Color) {
+        match (&self.pinned, &self.upstream) {
+            (_, Upstream::Error(_)) => ("unreachable", Color::Red),
+            (None, _) => ("not generated", Color::Yellow),
+            (Some(_), Upstream::Unchecked) => ("unchecked", Color::Gray),
+            (Some(pinned), Upstream::Commit(upstream)) if pinned == upstream => {
+                ("up to date", Color::Green)
+            }
+            (Some(_), Upstream::Commit(_)) => ("outdated", Color::Yellow),
+        }
+    }
+
+    /// Summary of the last generation in this session
+    fn last_run(&self) -> String {
+        match &self.last_result {
+            None => "-".to_string(),
+            Some(result) if result.is_failure() => "failed".to_string(),
+            Some(result) if !result.errors.is_empty() => format!(
+                "{} files, {} errors",
+                result.files_generated,
+                result.errors.len()
+            ),
+            Some(result) => format!("{} files", result.files_generated),
+        }
+    }
+}
+
+/// Dashboard state
+struct Dashboard {
+    app: JsonnetGen,
+    rows: Vec<SourceRow>,
+    table: TableState,
+    status: String,
+}
+
+impl Dashboard {
+    fn new(app: JsonnetGen) -> Result<Self> {
+        let rows = app
+            .config()
+            .sources
+            .iter()
+            .map(|source| SourceRow {
+                name: source.name().to_string(),
+                source_type: source.source_type().to_string(),
+                pinned: None,
+                fetched_at: None,
+                upstream: Upstream::Unchecked,
+                last_result: None,
+            })
+            .collect();
+
+        let mut dashboard = Self {
+            app,
+            rows,
+            table: TableState::default().with_selected(Some(0)),
+            status: "Press u to check a source for upstream changes".to_string(),
+        };
+        dashboard.reload_pins()?;
+        Ok(dashboard)
+    }
+
+    /// Read the pinned commits from the lockfile
+    ///
+    /// Catalogs and auto sources are locked per logical source, all at the same commit.
+    fn reload_pins(&mut self) -> Result<()> {
+        let lockfile = self.app.lockfile_manager().load_or_create()?;
+        for (row, source) in self.rows.iter_mut().zip(&self.app.config().sources) {
+            let entry = lockfile
+                .sources
+                .iter()
+                .find(|(source_id, _)| crate::owns_source_id(source, source_id))
+                .map(|(_, entry)| entry);
+            row.pinned = entry.map(|entry| entry.revision().to_string());
+            row.fetched_at = entry.map(|entry| entry.fetched_at);
+        }
+        Ok(())
+    }
+
+    async fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => self.select(1),
+                KeyCode::Up | KeyCode::Char('k') => self.select(-1),
+                KeyCode::Char('g') | KeyCode::Enter => {
+                    if let Some(index) = self.table.selected() {
+                        self.status = format!("Generating {}...", self.rows[index].name);
+                        terminal.draw(|frame| self.draw(frame))?;
+                        self.generate(index).await;
+                    }
+                }
+                KeyCode::Char('u') => {
+                    if let Some(index) = self.table.selected() {
+                        self.status = format!("Checking {}...", self.rows[index].name);
+                        terminal.draw(|frame| self.draw(frame))?;
+                        self.check_upstream(index).await;
+                        self.status = format!("Checked {}", self.rows[index].name);
+                    }
+                }
+                KeyCode::Char('U') => {
+                    for index in 0..self.rows.len() {
+                        self.status = format!("Checking {}...", self.rows[index].name);
+                        terminal.draw(|frame| self.draw(frame))?;
+                        self.check_upstream(index).await;
+                    }
+                    self.status = format!("Checked {} sources", self.rows.len());
+                }
+                KeyCode::Char('r') => {
+                    self.status = match self.reload_pins() {
+                        Ok(()) => "Reloaded lockfile".to_string(),
+                        Err(e) => format!("Failed to reload lockfile: {e}"),
+                    };
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Move the selection by `offset` rows
+    fn select(&mut self, offset: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let current = self.table.selected().unwrap_or(0) as isize;
+        let last = self.rows.len() as isize - 1;
+        self.table
+            .select(Some((current + offset).clamp(0, last) as usize));
+    }
+
+    fn source(&self, index: usize) -> Option<&Source> {
+        self.app.find_source_by_id(&self.rows[index].name)
+    }
+
+    async fn check_upstream(&mut self, index: usize) {
+        let Some(source) = self.source(index) else {
+            return;
+        };
+        self.rows[index].upstream = match self.app.remote_commit(source).await {
+            Ok(commit) => Upstream::Commit(commit),
+            Err(e) => Upstream::Error(e.to_string()),
+        };
+    }
+
+    async fn generate(&mut self, index: usize) {
+        let name = self.rows[index].name.clone();
+        match self.app.generate_source(&name).await {
+            Ok(result) => {
+                self.status = if result.is_failure() {
+                    format!("Generation of {name} failed")
+                } else {
+                    format!(
+                        "Generated {} files for {name} in {}ms",
+                        result.files_generated, result.processing_time_ms
+                    )
+                };
+                self.rows[index].last_result = Some(result);
+            }
+            Err(e) => self.status = format!("Generation of {name} failed: {e}"),
+        }
+
+        if let Err(e) = self.reload_pins() {
+            self.status = format!("Failed to reload lockfile: {e}");
+        }
+        self.check_upstream(index).await;
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [table_area, details_area, status_area] = Layout::vertical([
+            Constraint::Min(5),
+            Constraint::Length(9),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let rows = self.rows.iter().map(|row| {
+            let (state, color) = row.state();
+            let upstream = match &row.upstream {
+                Upstream::Unchecked => "?".to_string(),
+                Upstream::Commit(commit) => short_commit(commit).to_string(),
+                Upstream::Error(_) => "error".to_string(),
+            };
+            Row::new(vec![
+                Cell::from(row.name.clone()),
+                Cell::from(row.source_type.clone()),
+                Cell::from(row.pinned.as_deref().map_or("-", short_commit).to_string()),
+                Cell::from(upstream),
+                Cell::from(state).style(Style::default().fg(color)),
+                Cell::from(row.last_run()),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(2),
+                Constraint::Length(8),
+                Constraint::Length(14),
+                Constraint::Length(14),
+                Constraint::Length(14),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(["Source", "Type", "Pinned", "Upstream", "State", "Last run"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title("Sources"))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, table_area, &mut self.table);
+
+        let details = Paragraph::new(self.details())
+            .block(Block::default().borders(Borders::ALL).title("Details"));
+        frame.render_widget(details, details_area);
+
+        frame.render_widget(
+            Paragraph::new(format!("{}  |  {}", self.status, HELP)),
+            status_area,
+        );
+    }
+
+    /// Detail lines of the selected source
+    fn details(&self) -> Vec<Line<'static>> {
+        let Some(index) = self.table.selected() else {
+            return Vec::new();
+        };
+        let Some(source) = self.source(index) else {
+            return Vec::new();
+        };
+        let row = &self.rows[index];
+
+        let mut lines = vec![
+            Line::from(format!(
+                "{} @ {}",
+                source.git_url(),
+                source.git_ref().unwrap_or("-")
+            )),
+            Line::from(format!("Output: {}", source.output_path().display())),
+            Line::from(match (&row.pinned, &row.fetched_at) {
+                (Some(pinned), Some(fetched_at)) => format!(
+                    "Pinned: {pinned} (locked {})",
+                    fetched_at.format("%Y-%m-%d %H:%M:%S UTC")
+                ),
+                _ => "Pinned: not in lockfile".to_string(),
+            }),
+        ];
+
+        if let Upstream::Error(e) = &row.upstream {
+            lines.push(Line::from(format!("Upstream: {e}")));
+        }
+        if let Some(result) = &row.last_result {
+            lines.push(Line::from(format!(
+                "Last run: {} files generated, {} reused, {} warnings in {}ms",
+                result.files_generated,
+                result.schemas_reused,
+                result.warnings.len(),
+                result.processing_time_ms
+            )));
+            lines.extend(
+                result
+                    .errors
+                    .iter()
+                    .take(2)
+                    .map(|e| Line::from(format!("Error: {e}"))),
+            );
+        }
+
+        lines
+    }
+}
+
+/// Abbreviated commit SHA or artifact digest
+fn short_commit(commit: &str) -> &str {
+    commit.get(..12).unwrap_or(commit)
+}
@@ -35,6 +35,13 @@ pub async fn run(matches: &ArgMatches) -> Result<()> {
                 crate::config::Source::Crd(_) => "CRD",
                 crate::config::Source::GoAst(_) => "Go AST",
                 crate::config::Source::OpenApi(_) => "OpenAPI",
+                crate::config::Source::Url(_) => "URL",
+                crate::config::Source::Release(_) => "Release",
+                crate::config::Source::Fixture(_) => "Fixture",
+                crate::config::Source::Jsonnet(_) => "Jsonnet",
+                crate::config::Source::Kustomize(_) => "Kustomize",
+                crate::config::Source::Catalog(_) => "Catalog",
+                crate::config::Source::Auto(_) => "Auto",
             }
         );
     }
@@ -0,0 +1,100 @@
+//! Watch command implementation
+
+use crate::cli::utils;
+use crate::metrics::{self, GenerationMetrics};
+use anyhow::Result;
+use clap::{ArgMatches, Command};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
+
+pub fn command() -> Command {
+    Command::new("watch")
+        .about("Periodically regenerate libraries, optionally exposing Prometheus metrics")
+        .arg(
+            clap::Arg::new("config")
+                .short('c')
+                .long("config")
+                .help("Configuration file path")
+                .value_name("FILE"),
+        )
+        .arg(
+            clap::Arg::new("interval")
+                .short('i')
+                .long("interval")
+                .help("Seconds to wait between generation runs")
+                .value_name("SECONDS")
+                .default_value("300"),
+        )
+        .arg(
+            clap::Arg::new("metrics-address")
+                .long("metrics-address")
+                .help("Expose Prometheus metrics on this address (overrides config)")
+                .value_name("ADDR"),
+        )
+}
+
+pub async fn run(matches: &ArgMatches) -> Result<()> {
+    let interval: u64 = matches
+        .get_one::<String>("interval")
+        .unwrap()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid interval value"))?;
+
+    let mut config = utils::load_config(matches)?;
+
+    // Override metrics address if specified
+    if let Some(address) = matches.get_one::<String>("metrics-address") {
+        config.metrics.enabled = true;
+        config.metrics.listen_address = address.clone();
+    }
+
+    let metrics_state = Arc::new(GenerationMetrics::new());
+    let metrics_server = if config.metrics.enabled {
+        let addr = config.metrics.socket_addr()?;
+        println!("Serving metrics on http://{addr}/metrics");
+        Some(metrics::serve(Arc::clone(&metrics_state), addr).await?)
+    } else {
+        None
+    };
+
+    let app = utils::create_app(matches, config)?;
+    app.initialize().await?;
+
+    println!("Watching sources, regenerating every {interval}s (Ctrl-C to stop)");
+
+    loop {
+        info!("Starting scheduled generation run");
+        match app.generate().await {
+            Ok(result) => {
+                metrics_state.record_generation(&result);
+                println!(
+                    "Generated {} files from {}/{} sources in {}ms",
+                    result.statistics.files_generated,
+                    result.sources_processed,
+                    result.total_sources,
+                    result.statistics.total_processing_time_ms
+                );
+            }
+            Err(e) => {
+                metrics_state.record_failure();
+                error!("Generation run failed: {}", e);
+                eprintln!("Generation failed: {e}");
+            }
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+            _ = tokio::signal::ctrl_c() => {
+                println!("Stopping watch mode");
+                break;
+            }
+        }
+    }
+
+    if let Some(server) = metrics_server {
+        server.abort();
+    }
+
+    Ok(())
+}
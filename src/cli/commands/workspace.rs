@@ -0,0 +1,186 @@
+//! Workspace command implementation
+//!
+//! Runs a command across every configuration file found below a monorepo
+//! root. Each workspace's relative paths and its own lockfile are resolved
+//! against its directory, as they would be with `cd <dir> && gensonnet ...`.
+
+use crate::cli::utils;
+use anyhow::{anyhow, Result};
+use clap::{ArgMatches, Command};
+use std::path::{Path, PathBuf};
+
+pub fn command() -> Command {
+    let root = clap::Arg::new("root")
+        .short('r')
+        .long("root")
+        .help("Monorepo root to search for configuration files")
+        .value_name("DIR")
+        .default_value(".");
+
+    Command::new("workspace")
+        .about("Run generate, status or outdated across every workspace in a monorepo")
+        .subcommand_required(true)
+        .arg(root.global(true))
+        .subcommand(Command::new("list").about("List discovered workspaces"))
+        .subcommand(Command::new("generate").about("Generate every workspace"))
+        .subcommand(Command::new("status").about("Show the generation status of every workspace"))
+        .subcommand(
+            Command::new("outdated").about("List sources whose upstream moved past the lockfile"),
+        )
+}
+
+/// Outcome of a command in one workspace
+struct Report {
+    /// One-line summary
+    summary: String,
+
+    /// Lines shown below the summary
+    details: Vec<String>,
+
+    failed: bool,
+}
+
+pub async fn run(matches: &ArgMatches) -> Result<()> {
+    let (action, _) = matches
+        .subcommand()
+        .ok_or_else(|| anyhow!("Missing workspace command"))?;
+    let root = std::fs::canonicalize(matches.get_one::<String>("root").unwrap())?;
+
+    let config_paths = crate::Config::discover(&root);
+    if config_paths.is_empty() {
+        println!("No configuration files found under {}", root.display());
+        return Ok(());
+    }
+
+    // Relative to the working directory where possible, so lockfiles record
+    // the same paths on every machine
+    let current_dir = std::fs::canonicalize(std::env::current_dir()?)?;
+    let mut failed = 0;
+    for config_path in &config_paths {
+        let dir = config_path.parent().unwrap_or(&root);
+        let name = dir.strip_prefix(&root).unwrap_or(dir);
+        let name = if name.as_os_str().is_empty() {
+            ".".to_string()
+        } else {
+            name.display().to_string()
+        };
+
+        let base = dir.strip_prefix(&current_dir).unwrap_or(dir);
+        let report = run_in_workspace(matches, action, config_path, base)
+            .await
+            .unwrap_or_else(|e| Report {
+                summary: format!("error: {e}"),
+                details: Vec::new(),
+                failed: true,
+            });
+        if report.failed {
+            failed += 1;
+        }
+
+        println!(
+            "{} {}: {}",
+            if report.failed { "✗" } else { "✓" },
+            name,
+            report.summary
+        );
+        for line in &report.details {
+            println!("    {line}");
+        }
+    }
+
+    println!();
+    println!(
+        "{} workspaces: {} ok, {} failed",
+        config_paths.len(),
+        config_paths.len() - failed,
+        failed
+    );
+
+    if failed > 0 {
+        return Err(anyhow!("{} workspaces failed", failed));
+    }
+
+    Ok(())
+}
+
+/// Run a workspace command with its paths resolved against the workspace's directory
+async fn run_in_workspace(
+    matches: &ArgMatches,
+    action: &str,
+    config_path: &Path,
+    dir: &Path,
+) -> Result<Report> {
+    let mut config = crate::Config::from_file(&PathBuf::from(config_path))?;
+    config.rebase_paths(dir);
+    let sources_count = config.sources.len();
+    let app = utils::create_app(matches, config)?
+        .with_lockfile_path(dir.join(crate::LockfileManager::default_path()));
+
+    match action {
+        "list" => Ok(Report {
+            summary: format!("{} ({} sources)", config_path.display(), sources_count),
+            details: Vec::new(),
+            failed: false,
+        }),
+        "generate" => {
+            app.initialize().await?;
+            let result = app.generate().await?;
+            Ok(Report {
+                summary: format!(
+                    "{}/{} sources, {} files, {} errors",
+                    result.sources_processed,
+                    result.total_sources,
+                    result.statistics.files_generated,
+                    result.statistics.error_count
+                ),
+                details: result
+                    .results
+                    .iter()
+                    .flat_map(|source_result| {
+                        source_result
+                            .errors
+                            .iter()
+                            .map(|e| format!("{}: {}", source_result.source_name, e))
+                    })
+                    .collect(),
+                failed: result.statistics.error_count > 0,
+            })
+        }
+        "status" => {
+            let status = app.get_status().await?;
+            Ok(Report {
+                summary: format!(
+                    "last generated {}, {} sources, {} changed",
+                    status.last_generation.format("%Y-%m-%d %H:%M:%S UTC"),
+                    status.sources_count,
+                    status.changed_sources.len()
+                ),
+                details: status
+                    .changed_sources
+                    .iter()
+                    .map(|source| format!("changed: {source}"))
+                    .collect(),
+                failed: false,
+            })
+        }
+        "outdated" => {
+            let outdated = app.outdated_sources().await?;
+            Ok(Report {
+                summary: format!("{} of {} sources outdated", outdated.len(), sources_count),
+                details: outdated
+                    .iter()
+                    .map(|source| {
+                        format!(
+                            "{}: {} -> {}",
+                            source.name,
+                            source.pinned.as_deref().unwrap_or("not generated"),
+                            source.upstream
+                        )
+                    })
+                    .collect(),
+                failed: false,
+            })
+        }
+        other => Err(anyhow!("Unknown workspace command: {}", other)),
+    }
+}
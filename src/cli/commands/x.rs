@@ -0,0 +1,144 @@
+//! Plugin subcommand dispatch
+//!
+//! Plugins with the Cli capability declare subcommands in their metadata or
+//! manifest. They are mounted as `x <plugin> <command>`, where `<plugin>` is
+//! the plugin id or its type (the part before `:`), and their arguments are
+//! parsed against the declared args schema.
+
+use crate::cli::utils;
+use crate::plugin::CliCommandSpec;
+use anyhow::{anyhow, Result};
+use clap::{ArgMatches, Command};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub fn command() -> Command {
+    Command::new("x")
+        .about("Run a subcommand contributed by a plugin")
+        .arg(
+            clap::Arg::new("config")
+                .short('c')
+                .long("config")
+                .help("Configuration file path")
+                .value_name("FILE"),
+        )
+        .arg(
+            clap::Arg::new("plugin")
+                .help("Plugin id or type, e.g. openapi")
+                .value_name("PLUGIN"),
+        )
+        .arg(
+            clap::Arg::new("args")
+                .help("Plugin subcommand and its arguments")
+                .value_name("COMMAND")
+                .num_args(0..)
+                .trailing_var_arg(true)
+                .allow_hyphen_values(true),
+        )
+}
+
+pub async fn run(matches: &ArgMatches) -> Result<()> {
+    // Plugin commands don't need sources, so a config file is optional
+    let config = match utils::get_config_path(matches) {
+        Ok(path) => crate::Config::from_file(&path)?,
+        Err(_) => crate::Config::default(),
+    };
+    let app = utils::create_app(matches, config)?;
+    app.initialize_plugins().await?;
+    let commands = app.plugin_commands().await;
+
+    let Some(plugin) = matches.get_one::<String>("plugin") else {
+        print_commands(&commands);
+        return Ok(());
+    };
+    let args: Vec<&String> = matches
+        .get_many::<String>("args")
+        .map(|args| args.collect())
+        .unwrap_or_default();
+
+    let (plugin_id, specs) = commands
+        .iter()
+        .find(|(plugin_id, _)| {
+            plugin_id.as_str() == plugin || plugin_id.split(':').next() == Some(plugin)
+        })
+        .ok_or_else(|| anyhow!("No plugin {} provides CLI commands", plugin))?;
+    let Some((command, args)) = args.split_first() else {
+        // Commands clap cannot mount are left out of the help
+        let mut names = BTreeSet::new();
+        Command::new(format!("gensonnet x {plugin}"))
+            .subcommands(
+                specs
+                    .iter()
+                    .filter(|spec| spec.validate().is_ok() && names.insert(&spec.name))
+                    .map(spec_command),
+            )
+            .print_help()?;
+        return Ok(());
+    };
+    let spec = specs
+        .iter()
+        .find(|spec| &spec.name == *command)
+        .ok_or_else(|| anyhow!("Plugin {} has no command {}", plugin_id, command))?;
+    spec.validate()
+        .map_err(|e| anyhow!("Plugin {} declares an unusable command: {}", plugin_id, e))?;
+
+    let sub_matches = spec_command(spec)
+        .bin_name(format!("gensonnet x {plugin} {command}"))
+        .try_get_matches_from(std::iter::once(*command).chain(args.iter().copied()))
+        .unwrap_or_else(|e| e.exit());
+
+    let mut values = BTreeMap::new();
+    for arg in &spec.args {
+        if arg.flag {
+            if sub_matches.get_flag(&arg.name) {
+                values.insert(arg.name.clone(), "true".to_string());
+            }
+        } else if let Some(value) = sub_matches.get_one::<String>(&arg.name) {
+            values.insert(arg.name.clone(), value.clone());
+        }
+    }
+
+    let output = app.run_plugin_command(plugin_id, command, values).await?;
+    print!("{}", output.output);
+
+    if !output.success {
+        return Err(anyhow!("{} {} failed", plugin, command));
+    }
+
+    Ok(())
+}
+
+/// Command line of a plugin subcommand, built from its args schema, which
+/// must have passed [`CliCommandSpec::validate`]
+fn spec_command(spec: &CliCommandSpec) -> Command {
+    Command::new(spec.name.clone())
+        .about(spec.about.clone())
+        .args(spec.args.iter().map(|arg| {
+            let clap_arg = clap::Arg::new(arg.name.clone())
+                .long(arg.name.clone())
+                .help(arg.help.clone());
+            if arg.flag {
+                clap_arg.action(clap::ArgAction::SetTrue)
+            } else {
+                // Defaults are filled in by the plugin manager
+                clap_arg
+                    .value_name("VALUE")
+                    .required(arg.required && arg.default.is_none())
+            }
+        }))
+}
+
+/// List the subcommands of every plugin
+fn print_commands(commands: &BTreeMap<String, Vec<CliCommandSpec>>) {
+    if commands.is_empty() {
+        println!("No plugin provides CLI commands.");
+        return;
+    }
+
+    println!("Plugin commands:");
+    for (plugin_id, specs) in commands {
+        let plugin = plugin_id.split(':').next().unwrap_or(plugin_id);
+        for spec in specs {
+            println!("  x {} {:<12} {}", plugin, spec.name, spec.about);
+        }
+    }
+}
@@ -11,35 +11,71 @@ pub struct CliApp;
 impl CliApp {
     /// Create the CLI application
     pub fn app() -> Command {
-        Command::new("gensonnet")
+        let app = Command::new("gensonnet")
             .version(env!("CARGO_PKG_VERSION"))
             .about("Generate type-safe Jsonnet libraries from schema sources")
             .subcommand_negates_reqs(true)
+            .arg(
+                clap::Arg::new("chaos")
+                    .long("chaos")
+                    .value_name("SPEC")
+                    .help("Randomly inject failures, e.g. 0.2 or 1.0:git,parse,write")
+                    .global(true)
+                    .hide(true),
+            )
             .subcommand(commands::init::command())
+            .subcommand(commands::audit::command())
             .subcommand(commands::generate::command())
             .subcommand(commands::validate::command())
+            .subcommand(commands::config::command())
             .subcommand(commands::lock::command())
+            .subcommand(commands::freeze::command())
             .subcommand(commands::info::command())
             .subcommand(commands::status::command())
             .subcommand(commands::cleanup::command())
             .subcommand(commands::incremental::command())
             .subcommand(commands::plugins::command())
+            .subcommand(commands::profile::command())
+            .subcommand(commands::selftest::command())
             .subcommand(commands::test::command())
+            .subcommand(commands::watch::command())
+            .subcommand(commands::workspace::command())
+            .subcommand(commands::x::command());
+
+        #[cfg(feature = "ui")]
+        let app = app.subcommand(commands::ui::command());
+
+        app
     }
 
     /// Run the CLI application
     pub async fn run(matches: &ArgMatches) -> Result<()> {
+        // Applied by `utils::create_app`; refuse a bad spec before running anything
+        if let Some(spec) = matches.get_one::<String>("chaos") {
+            crate::chaos::ChaosInjector::parse(spec)?;
+        }
+
         match matches.subcommand() {
             Some(("init", sub_matches)) => commands::init::run(sub_matches).await,
+            Some(("audit", sub_matches)) => commands::audit::run(sub_matches).await,
             Some(("generate", sub_matches)) => commands::generate::run(sub_matches).await,
             Some(("validate", sub_matches)) => commands::validate::run(sub_matches).await,
+            Some(("config", sub_matches)) => commands::config::run(sub_matches).await,
             Some(("lock", sub_matches)) => commands::lock::run(sub_matches).await,
+            Some(("freeze", sub_matches)) => commands::freeze::run(sub_matches).await,
             Some(("info", sub_matches)) => commands::info::run(sub_matches).await,
             Some(("status", sub_matches)) => commands::status::run(sub_matches).await,
             Some(("cleanup", sub_matches)) => commands::cleanup::run(sub_matches).await,
             Some(("incremental", sub_matches)) => commands::incremental::run(sub_matches).await,
             Some(("plugins", sub_matches)) => commands::plugins::run(sub_matches).await,
+            Some(("profile-lib", sub_matches)) => commands::profile::run(sub_matches).await,
+            Some(("selftest", sub_matches)) => commands::selftest::run(sub_matches).await,
             Some(("test", sub_matches)) => commands::test::run(sub_matches).await,
+            Some(("watch", sub_matches)) => commands::watch::run(sub_matches).await,
+            Some(("workspace", sub_matches)) => commands::workspace::run(sub_matches).await,
+            Some(("x", sub_matches)) => commands::x::run(sub_matches).await,
+            #[cfg(feature = "ui")]
+            Some(("ui", sub_matches)) => commands::ui::run(sub_matches).await,
             _ => {
                 // No subcommand provided, show help
                 let _ = Self::app().print_help();
@@ -60,19 +96,23 @@ pub mod utils {
             Ok(PathBuf::from(config_path))
         } else {
             // Look for default config files
-            let default_paths = [
-                PathBuf::from(".gensonnet.yaml"),
-                PathBuf::from(".gensonnet.yml"),
-                PathBuf::from("gensonnet.yaml"),
-                PathBuf::from("gensonnet.yml"),
-            ];
-
-            for path in &default_paths {
+            for name in crate::Config::FILE_NAMES {
+                let path = PathBuf::from(name);
                 if path.exists() {
-                    return Ok(path.clone());
+                    return Ok(path);
                 }
             }
 
+            if let Some(legacy) = crate::config::migrate::LEGACY_FILE_NAMES
+                .iter()
+                .find(|name| PathBuf::from(name).exists())
+            {
+                return Err(anyhow!(
+                    "Found {} written for an older version; upgrade it with 'gensonnet config migrate'",
+                    legacy
+                ));
+            }
+
             Err(anyhow!("No configuration file found. Use --config to specify a file or create one with 'gensonnet init'"))
         }
     }
@@ -83,8 +123,84 @@ pub mod utils {
         crate::Config::from_file(&config_path)
     }
 
-    /// Create JsonnetGen instance
-    pub fn create_app(config: crate::Config) -> Result<crate::JsonnetGen> {
-        crate::JsonnetGen::new(config)
+    /// `--tags` argument selecting the sources carrying any of the given tags
+    pub fn tags_arg() -> clap::Arg {
+        clap::Arg::new("tags")
+            .long("tags")
+            .help("Only operate on sources with any of these comma-separated tags")
+            .value_name("TAGS")
+            .value_delimiter(',')
+    }
+
+    /// Restrict the configuration to the sources selected by `--tags`, if given
+    pub fn select_tags(matches: &clap::ArgMatches, config: &mut crate::Config) -> Result<()> {
+        if let Some(tags) = matches.get_many::<String>("tags") {
+            config.select_tags(&tags.cloned().collect::<Vec<_>>())?;
+        }
+        Ok(())
+    }
+
+    /// `--prune-lock` argument removing lockfile entries of removed sources without asking
+    pub fn prune_lock_arg() -> clap::Arg {
+        clap::Arg::new("prune-lock")
+            .long("prune-lock")
+            .help("Remove lockfile entries of sources no longer in the configuration")
+            .action(clap::ArgAction::SetTrue)
+    }
+
+    /// Offer to remove the lockfile entries of sources no longer configured
+    ///
+    /// Entries are removed with `--prune-lock`, or when confirmed on a terminal.
+    /// With `--tags` only part of the configuration is loaded, so nothing is
+    /// reconciled.
+    pub fn reconcile_lockfile(matches: &clap::ArgMatches, app: &crate::JsonnetGen) -> Result<()> {
+        use std::io::{BufRead, IsTerminal, Write};
+
+        if matches.get_many::<String>("tags").is_some() {
+            return Ok(());
+        }
+        let orphans = app.lockfile_orphans()?;
+        if orphans.is_empty() {
+            return Ok(());
+        }
+
+        println!(
+            "The lockfile has {} entries and {} files of sources no longer configured:",
+            orphans.sources.len(),
+            orphans.files.len()
+        );
+        for source_id in &orphans.sources {
+            println!("  {source_id}");
+        }
+        let prune = matches.get_flag("prune-lock")
+            || (std::io::stdin().is_terminal() && {
+                print!("Remove them from the lockfile? [y/N] ");
+                std::io::stdout().flush()?;
+                let mut answer = String::new();
+                std::io::stdin().lock().read_line(&mut answer)?;
+                matches!(answer.trim(), "y" | "Y" | "yes")
+            });
+
+        if prune {
+            app.prune_lockfile()?;
+            println!("Removed them from the lockfile.");
+        } else {
+            println!("Keeping them; run with --prune-lock to remove them.");
+        }
+        Ok(())
+    }
+
+    /// Create JsonnetGen instance, injecting the failures `--chaos` asks for
+    pub fn create_app(
+        matches: &clap::ArgMatches,
+        config: crate::Config,
+    ) -> Result<crate::JsonnetGen> {
+        let app = crate::JsonnetGen::new(config)?;
+        match matches.get_one::<String>("chaos") {
+            Some(spec) => {
+                Ok(app.with_chaos(crate::chaos::ChaosInjector::parse(spec)?.with_env_seed()?))
+            }
+            None => Ok(app),
+        }
     }
 }
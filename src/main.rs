@@ -6,17 +6,27 @@ use gensonnet::cli::CliApp;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Parse command line arguments
+    let matches = CliApp::app().get_matches();
+
+    // The dashboard owns the terminal, so log output would garble it
+    let quiet = matches.subcommand_name() == Some("ui");
+
     // Initialize logging
     tracing_subscriber::fmt()
         .with_env_filter(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "gensonnet=info".into()),
         )
+        .with_writer(move || -> Box<dyn std::io::Write> {
+            if quiet {
+                Box::new(std::io::sink())
+            } else {
+                Box::new(std::io::stdout())
+            }
+        })
         .init();
 
-    // Parse command line arguments
-    let matches = CliApp::app().get_matches();
-
     // Run the CLI application
     CliApp::run(&matches).await
 }
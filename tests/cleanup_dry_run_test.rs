@@ -14,7 +14,13 @@ async fn test_cleanup_dry_run_functionality() {
                 auth: None,
             },
             filters: vec!["test.com/v1".to_string()],
+            versions: Default::default(),
             output_path: PathBuf::from("./test-output"),
+            plugin: Default::default(),
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+            samples: Vec::new(),
         },
     ));
 
@@ -47,7 +53,13 @@ async fn test_cleanup_dry_run_with_different_ages() {
                 auth: None,
             },
             filters: vec!["test.com/v1".to_string()],
+            versions: Default::default(),
             output_path: PathBuf::from("./test-output"),
+            plugin: Default::default(),
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+            samples: Vec::new(),
         },
     ));
 
@@ -82,7 +94,13 @@ async fn test_cleanup_dry_run_result_structure() {
                 auth: None,
             },
             filters: vec!["test.com/v1".to_string()],
+            versions: Default::default(),
             output_path: PathBuf::from("./test-output"),
+            plugin: Default::default(),
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+            samples: Vec::new(),
         },
     ));
 
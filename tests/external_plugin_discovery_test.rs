@@ -51,7 +51,13 @@ config:
                 auth: None,
             },
             filters: vec!["test.com/v1".to_string()],
+            versions: Default::default(),
             output_path: PathBuf::from("./output"),
+            plugin: Default::default(),
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+            samples: Vec::new(),
         },
     ));
 
@@ -100,7 +106,13 @@ async fn test_plugin_discovery_disabled() {
                 auth: None,
             },
             filters: vec!["test.com/v1".to_string()],
+            versions: Default::default(),
             output_path: PathBuf::from("./output"),
+            plugin: Default::default(),
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+            samples: Vec::new(),
         },
     ));
 
@@ -144,7 +156,13 @@ async fn test_plugin_directory_expansion() {
                 auth: None,
             },
             filters: vec!["test.com/v1".to_string()],
+            versions: Default::default(),
             output_path: PathBuf::from("./output"),
+            plugin: Default::default(),
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+            samples: Vec::new(),
         },
     ));
 
@@ -1,8 +1,46 @@
 use gensonnet::LockfileManager;
 use gensonnet::{Config, CrdParser};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
+/// A CRD of `kind` in `group` with an untyped object schema
+fn crd(group: &str, kind: &str) -> String {
+    format!(
+        "apiVersion: apiextensions.k8s.io/v1\nkind: CustomResourceDefinition\nmetadata:\n  name: {plural}.{group}\nspec:\n  group: {group}\n  names:\n    kind: {kind}\n    plural: {plural}\n  versions:\n  - name: v1\n    schema:\n      openAPIV3Schema:\n        type: object\n",
+        plural = format!("{}s", kind.to_lowercase()),
+    )
+}
+
+/// Commit `paths` of the repository in `dir` to its `main` branch, creating
+/// the repository on the first commit
+fn commit_upstream(dir: &Path, paths: &[&str]) -> git2::Oid {
+    let repo = git2::Repository::open(dir)
+        .or_else(|_| git2::Repository::init(dir))
+        .unwrap();
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(paths, git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = git2::Signature::now("test", "test@example.com").unwrap();
+    let parent = repo
+        .find_reference("refs/heads/main")
+        .ok()
+        .map(|reference| reference.peel_to_commit().unwrap());
+    let commit = repo
+        .commit(
+            Some("refs/heads/main"),
+            &signature,
+            &signature,
+            &paths.join(" "),
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )
+        .unwrap();
+    repo.set_head("refs/heads/main").unwrap();
+    commit
+}
+
 #[tokio::test]
 async fn test_full_generation_workflow() {
     // Create a temporary directory for testing
@@ -51,7 +89,13 @@ spec:
                 auth: None,
             },
             filters: vec!["test.com/v1".to_string()],
+            versions: Default::default(),
             output_path: test_dir.join("generated"),
+            plugin: Default::default(),
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+            samples: Vec::new(),
         },
     ));
 
@@ -94,11 +138,18 @@ fn test_crd_schema_validation() {
         group: "test.com".to_string(),
         version: "v1".to_string(),
         api_version: "test.com/v1".to_string(),
+        served: true,
+        storage: true,
         kind: "test".to_string(),
+        categories: Vec::new(),
+        printer_columns: Vec::new(),
         schema: serde_yaml::Value::Null,
         source_path: PathBuf::from("test.yaml"),
         validation_rules: gensonnet::ValidationRules::default(),
         schema_analysis: gensonnet::SchemaAnalysis::default(),
+        conversion: gensonnet::ConversionInfo::default(),
+        field_lines: Default::default(),
+        recovered: Vec::new(),
     };
 
     assert_eq!(schema.kind(), "test");
@@ -123,3 +174,1226 @@ fn test_lockfile_operations() {
     assert_eq!(lockfile.version, "1.0");
     assert_eq!(lockfile.tool_version, env!("CARGO_PKG_VERSION"));
 }
+
+#[test]
+fn test_platform_bundle() {
+    let temp_dir = TempDir::new().unwrap();
+    let generated = temp_dir.path().join("generated");
+
+    let mut config = Config::default();
+    for name in ["cert-manager", "prometheus", "no-index"] {
+        let source = gensonnet::config::CrdSource {
+            name: name.to_string(),
+            git: gensonnet::config::GitSource {
+                url: "https://github.com/example/crds.git".to_string(),
+                ref_name: Some("main".to_string()),
+                auth: None,
+            },
+            filters: Vec::new(),
+            versions: Default::default(),
+            output_path: generated.join(name),
+            plugin: Default::default(),
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+            samples: Vec::new(),
+        };
+        std::fs::create_dir_all(&source.output_path).unwrap();
+        if name != "no-index" {
+            std::fs::write(source.output_path.join("index.libsonnet"), "{}").unwrap();
+        }
+        config.sources.push(gensonnet::config::Source::Crd(source));
+    }
+    config.output.base_path = generated.clone();
+    config
+        .bundle
+        .keys
+        .insert("prometheus".to_string(), "monitoring".to_string());
+
+    let app = gensonnet::JsonnetGen::new(config).unwrap();
+    let bundle_path = app.write_bundle().unwrap();
+    assert_eq!(bundle_path, generated.join("platform.libsonnet"));
+
+    let content = std::fs::read_to_string(bundle_path).unwrap();
+    assert!(content.contains("  certmanager: import \"./cert-manager/index.libsonnet\",\n"));
+    assert!(content.contains("  monitoring: import \"./prometheus/index.libsonnet\",\n"));
+    assert!(!content.contains("no-index"));
+}
+
+#[tokio::test]
+async fn test_chaos_failures_keep_lockfile_consistent() {
+    use gensonnet::chaos::ChaosInjector;
+
+    let temp_dir = TempDir::new().unwrap();
+    let artifact = temp_dir.path().join("crds.yaml");
+    std::fs::write(&artifact, crd("test.com", "Test")).unwrap();
+
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    config.sources.push(gensonnet::config::Source::Url(
+        gensonnet::config::UrlSource {
+            name: "vendored".to_string(),
+            url: format!("file://{}", artifact.display()),
+            sha256: None,
+            filters: Vec::new(),
+            versions: Default::default(),
+            output_path: temp_dir.path().join("generated/vendored"),
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+        },
+    ));
+    let lockfile_path = temp_dir.path().join("gensonnet.lock");
+    let app = |chaos: Option<&str>| {
+        let app = gensonnet::JsonnetGen::new(config.clone())
+            .unwrap()
+            .with_lockfile_path(lockfile_path.clone());
+        match chaos {
+            Some(spec) => app.with_chaos(ChaosInjector::parse(spec).unwrap()),
+            None => app,
+        }
+    };
+    let locked_entry = || {
+        LockfileManager::new(lockfile_path.clone())
+            .load_or_create()
+            .unwrap()
+            .sources
+            .get("vendored")
+            .cloned()
+    };
+
+    // A parse failure becomes a partial result and leaves no lockfile entry
+    let result = app(Some("1.0:parse")).generate().await.unwrap();
+    assert_eq!(result.results.len(), 1);
+    assert!(result.results[0].is_failure());
+    assert!(result.results[0].errors[0].contains("Injected parse failure"));
+    assert!(locked_entry().is_none());
+
+    let result = app(None).generate().await.unwrap();
+    assert!(!result.results[0].is_failure());
+    let entry = locked_entry().unwrap();
+    assert!(!entry.schema_digests.is_empty());
+
+    // A failed write keeps the entry of the last successful generation
+    let result = app(Some("1.0:write")).generate().await.unwrap();
+    assert!(result.results[0].errors[0].contains("Injected write failure"));
+    let kept = locked_entry().unwrap();
+    assert_eq!(kept.commit_sha, entry.commit_sha);
+    assert_eq!(kept.schema_digests, entry.schema_digests);
+}
+
+#[tokio::test]
+async fn test_generate_single_source() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    for kind in ["Widget", "Gadget"] {
+        let name = kind.to_lowercase();
+        let artifact = temp_dir.path().join(format!("{name}.yaml"));
+        std::fs::write(&artifact, crd("test.com", kind)).unwrap();
+        config.sources.push(gensonnet::config::Source::Url(
+            gensonnet::config::UrlSource {
+                name: name.clone(),
+                url: format!("file://{}", artifact.display()),
+                sha256: None,
+                filters: Vec::new(),
+                versions: Default::default(),
+                output_path: temp_dir.path().join("generated").join(&name),
+                timeout_seconds: None,
+                tags: Vec::new(),
+                transforms: Vec::new(),
+            },
+        ));
+    }
+    let lockfile_path = temp_dir.path().join("gensonnet.lock");
+    let app = gensonnet::JsonnetGen::new(config)
+        .unwrap()
+        .with_lockfile_path(lockfile_path.clone());
+
+    let result = app.generate_source("widget").await.unwrap();
+    assert!(result.files_generated > 0);
+    assert!(app.generate_source("missing").await.is_err());
+
+    // Only the generated source is pinned, at its upstream digest
+    let lockfile = LockfileManager::new(lockfile_path)
+        .load_or_create()
+        .unwrap();
+    let widget = app.find_source_by_id("widget").unwrap();
+    assert_eq!(
+        lockfile.sources["widget"].digest,
+        Some(app.upstream_commit(widget).await.unwrap())
+    );
+    assert!(!lockfile.sources.contains_key("gadget"));
+
+    let outdated = app.outdated_sources().await.unwrap();
+    assert_eq!(outdated.len(), 1);
+    assert_eq!(outdated[0].name, "gadget");
+    assert_eq!(outdated[0].pinned, None);
+}
+
+#[tokio::test]
+async fn test_output_path_outside_base_fails_source() {
+    let temp_dir = TempDir::new().unwrap();
+    let artifact = temp_dir.path().join("crds.yaml");
+    std::fs::write(&artifact, "").unwrap();
+
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    config.sources.push(gensonnet::config::Source::Url(
+        gensonnet::config::UrlSource {
+            name: "escaped".to_string(),
+            url: format!("file://{}", artifact.display()),
+            sha256: None,
+            filters: Vec::new(),
+            versions: Default::default(),
+            output_path: temp_dir.path().join("generated/../outside"),
+            timeout_seconds: None,
+            tags: Vec::new(),
+            transforms: Vec::new(),
+        },
+    ));
+    let app = gensonnet::JsonnetGen::new(config)
+        .unwrap()
+        .with_lockfile_path(temp_dir.path().join("gensonnet.lock"));
+
+    let result = app.generate_source("escaped").await.unwrap();
+    assert!(result.is_failure());
+    assert!(result.errors[0].contains("outside output.base_path"));
+    assert!(!temp_dir.path().join("outside").exists());
+}
+
+#[tokio::test]
+async fn test_inspect_source() {
+    let temp_dir = TempDir::new().unwrap();
+    let upstream = temp_dir.path().join("upstream");
+    std::fs::create_dir_all(upstream.join("crds")).unwrap();
+    std::fs::write(
+        upstream.join("crds/widget.yaml"),
+        crd("example.com", "Widget"),
+    )
+    .unwrap();
+    std::fs::write(upstream.join("crds/gadget.yaml"), crd("other.io", "Gadget")).unwrap();
+
+    let commit = commit_upstream(&upstream, &["crds"]);
+
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    config.sources.push(
+        serde_yaml::from_str(&format!(
+            "type: crd\nname: widgets\ngit: {{url: \"{}\", ref: main}}\nfilters: [\"example.com/*\", \"missing.io/*\"]\noutput_path: {}\n",
+            upstream.display(),
+            temp_dir.path().join("generated/widgets").display()
+        ))
+        .unwrap(),
+    );
+    let app = gensonnet::JsonnetGen::new(config)
+        .unwrap()
+        .with_lockfile_path(temp_dir.path().join("gensonnet.lock"));
+
+    let inspection = app.inspect_source("widgets").await.unwrap();
+    assert_eq!(inspection.upstream, Ok(commit.to_string()));
+    assert_eq!(inspection.pinned, None);
+    assert_eq!(inspection.plugin_binding.as_deref(), Some("auto"));
+    assert_eq!(inspection.plugin.as_deref(), Some("built-in parser"));
+    assert_eq!(inspection.crds_found, Some(2));
+    let matched: Vec<Option<usize>> = inspection
+        .filters
+        .iter()
+        .map(|filter| filter.matched)
+        .collect();
+    assert_eq!(matched, [Some(1), Some(0)]);
+    assert_eq!(
+        inspection.excluded,
+        ["other.io/v1 Gadget (crds/gadget.yaml)"]
+    );
+    assert_eq!(
+        inspection.layout,
+        ["example.com_v1/widgets.example.com.libsonnet"]
+    );
+
+    assert!(app.inspect_source("unknown").await.is_err());
+}
+
+#[tokio::test]
+async fn test_openapi_callbacks_and_webhooks() {
+    let temp_dir = TempDir::new().unwrap();
+    let upstream = temp_dir.path().join("upstream");
+    std::fs::create_dir_all(&upstream).unwrap();
+    std::fs::write(
+        upstream.join("api.yaml"),
+        r##"openapi: 3.1.0
+info: {title: Pets, version: 1.0.0}
+servers:
+  - url: https://{region}.pets.example.com/v1
+    description: Production
+    variables:
+      region: {default: eu, enum: [eu, us]}
+paths: {}
+components:
+  parameters:
+    PetId:
+      name: petId
+      in: path
+      required: true
+      schema: {type: string}
+  schemas:
+    Pet:
+      type: object
+      properties:
+        name: {type: string}
+        tag: {type: string}
+  callbacks:
+    onPetAdded:
+      "{$request.body#/callbackUrl}":
+        post:
+          requestBody:
+            content:
+              application/json:
+                schema: {$ref: "#/components/schemas/Pet"}
+webhooks:
+  petDeleted:
+    post:
+      requestBody:
+        content:
+          application/json:
+            schema:
+              type: object
+              properties:
+                id: {type: integer}
+"##,
+    )
+    .unwrap();
+    commit_upstream(&upstream, &["api.yaml"]);
+
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    config.sources.push(
+        serde_yaml::from_str(&format!(
+            "type: open_api\nname: pets\ngit: {{url: \"{}\", ref: main}}\ninclude_patterns: [\"*.yaml\"]\nexclude_patterns: []\noutput_path: {}\ninclude_callbacks: true\n",
+            upstream.display(),
+            temp_dir.path().join("generated/pets").display()
+        ))
+        .unwrap(),
+    );
+    let app = gensonnet::JsonnetGen::new(config)
+        .unwrap()
+        .with_lockfile_path(temp_dir.path().join("gensonnet.lock"));
+    app.initialize_plugins().await.unwrap();
+
+    let result = app.generate_source("pets").await.unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    let output = temp_dir.path().join("generated/pets");
+    assert!(output.join("pet.libsonnet").exists());
+
+    // The callback's payload is the schema it refers to
+    let callback = std::fs::read_to_string(output.join("callbacks/onpetadded.libsonnet")).unwrap();
+    assert!(callback.contains("// Expression: {$request.body#/callbackUrl}\n"));
+    assert!(callback.contains("  withName(value): { name: value },\n"));
+    assert!(callback.contains("  withTag(value): { tag: value },\n"));
+
+    let webhook = std::fs::read_to_string(output.join("webhooks/petdeleted.libsonnet")).unwrap();
+    assert!(webhook.contains("// Method: POST\n"));
+    assert!(webhook.contains("  withId(value): { id: value },\n"));
+
+    // Shared parameters and servers are generated alongside
+    let parameter = std::fs::read_to_string(output.join("parameters/petid.libsonnet")).unwrap();
+    assert!(parameter.contains("// In: path\n"));
+    assert!(parameter.contains("  name: \"petId\",\n"));
+    assert!(parameter.contains("  required: true,\n"));
+    let servers = std::fs::read_to_string(output.join("servers/pets.libsonnet")).unwrap();
+    assert!(servers.contains("  baseUrl: \"https://eu.pets.example.com/v1\",\n"));
+    assert!(servers.contains("\"description\":\"Production\""));
+}
+
+#[tokio::test]
+async fn test_cloudformation_and_crossplane_sources() {
+    let temp_dir = TempDir::new().unwrap();
+    let upstream = temp_dir.path().join("upstream");
+    std::fs::create_dir_all(upstream.join("cfn")).unwrap();
+    std::fs::create_dir_all(upstream.join("crossplane")).unwrap();
+    std::fs::write(
+        upstream.join("cfn/spec.json"),
+        r#"{"ResourceTypes": {
+  "AWS::S3::Bucket": {"Properties": {"BucketName": {"PrimitiveType": "String"}}},
+  "AWS::SQS::Queue": {"Properties": {"QueueName": {"PrimitiveType": "String"}}}
+}}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        upstream.join("crossplane/xrd.yaml"),
+        r#"apiVersion: apiextensions.crossplane.io/v1
+kind: CompositeResourceDefinition
+metadata: {name: xbuckets.storage.example.org}
+spec:
+  group: storage.example.org
+  names: {kind: XBucket, plural: xbuckets}
+  claimNames: {kind: Bucket, plural: buckets}
+  versions:
+    - name: v1alpha1
+      served: true
+      referenceable: true
+      schema:
+        openAPIV3Schema:
+          type: object
+          properties:
+            spec:
+              type: object
+              properties:
+                region: {type: string}
+"#,
+    )
+    .unwrap();
+    commit_upstream(&upstream, &["*"]);
+
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    for (name, extra) in [
+        (
+            "aws",
+            "plugin: cloudformation:builtin\nfilters: [\"AWS::S3::*\"]",
+        ),
+        ("compositions", "filters: []"),
+    ] {
+        config.sources.push(
+            serde_yaml::from_str(&format!(
+                "type: crd\nname: {name}\ngit: {{url: \"{}\", ref: main}}\n{extra}\noutput_path: {}\n",
+                upstream.display(),
+                temp_dir.path().join("generated").join(name).display()
+            ))
+            .unwrap(),
+        );
+    }
+    let app = gensonnet::JsonnetGen::new(config)
+        .unwrap()
+        .with_lockfile_path(temp_dir.path().join("gensonnet.lock"));
+    app.initialize_plugins().await.unwrap();
+
+    let result = app.generate_source("aws").await.unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.plugin.as_deref(), Some("cloudformation:builtin"));
+    let aws = temp_dir.path().join("generated/aws");
+    let bucket = std::fs::read_to_string(aws.join("aws_s3_bucket.libsonnet")).unwrap();
+    assert!(bucket.contains("  withBucketName(value): { Properties+: { BucketName: value } },\n"));
+    assert!(!aws.join("aws_sqs_queue.libsonnet").exists());
+
+    // The XRD gives the composite resource and its claim
+    let result = app.generate_source("compositions").await.unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    let compositions = temp_dir
+        .path()
+        .join("generated/compositions/storage.example.org_v1alpha1");
+    assert!(compositions
+        .join("xbuckets.storage.example.org.libsonnet")
+        .exists());
+    assert!(compositions
+        .join("buckets.storage.example.org.libsonnet")
+        .exists());
+}
+
+#[tokio::test]
+async fn test_field_naming() {
+    let temp_dir = TempDir::new().unwrap();
+    // A repository holding a CRD with the given top-level properties
+    let upstream = |name: &str, properties: &str| {
+        let dir = temp_dir.path().join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("job.yaml"),
+            format!(
+                r#"apiVersion: apiextensions.k8s.io/v1
+kind: CustomResourceDefinition
+metadata: {{name: jobs.example.com}}
+spec:
+  group: example.com
+  names: {{kind: Job, plural: jobs}}
+  versions:
+    - name: v1
+      served: true
+      storage: true
+      schema:
+        openAPIV3Schema:
+          type: object
+          properties: {{{properties}}}
+"#
+            ),
+        )
+        .unwrap();
+        commit_upstream(&dir, &["*"]);
+        dir
+    };
+
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    config.output.field_naming = gensonnet::FieldNaming::SnakeCase;
+    for (name, properties) in [
+        (
+            "jobs",
+            "retry_policy: {type: string}, maxRetries: {type: integer}",
+        ),
+        (
+            "colliding",
+            "retry_policy: {type: string}, retryPolicy: {type: string}",
+        ),
+    ] {
+        config.sources.push(
+            serde_yaml::from_str(&format!(
+                "type: crd\nname: {name}\ngit: {{url: \"{}\", ref: main}}\nfilters: []\noutput_path: {}\n",
+                upstream(name, properties).display(),
+                temp_dir.path().join("generated").join(name).display()
+            ))
+            .unwrap(),
+        );
+    }
+    let app = gensonnet::JsonnetGen::new(config)
+        .unwrap()
+        .with_lockfile_path(temp_dir.path().join("gensonnet.lock"));
+    let result = app.generate_source("jobs").await.unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+    // Setters are normalized, the fields they set keep their wire names
+    let output = temp_dir.path().join("generated/jobs");
+    let library =
+        std::fs::read_to_string(output.join("example.com_v1/jobs.example.com.libsonnet")).unwrap();
+    assert!(library.contains("function(with_retry_policy) {\n  spec +: {\n    retry_policy:"));
+    assert!(library.contains("  with_max_retries: with_max_retries,\n"));
+    let fields: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(output.join("_fields.json")).unwrap())
+            .unwrap();
+    let setters: Vec<&str> = fields
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["setter"].as_str().unwrap())
+        .collect();
+    assert_eq!(setters, ["with_max_retries", "with_retry_policy"]);
+
+    // Fields whose setters would collide fail the source
+    let errors = match app.generate_source("colliding").await {
+        Ok(result) => result.errors.join("\n"),
+        Err(err) => err.to_string(),
+    };
+    assert!(
+        errors.contains("would both get the setter with_retry_policy"),
+        "{errors}"
+    );
+}
+
+#[tokio::test]
+async fn test_fixture_source_generates_offline() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    config.sources.push(
+        serde_yaml::from_str(&format!(
+            "type: fixture\nname: demo\nfixture: crds\noutput_path: {}\n",
+            temp_dir.path().join("generated/demo").display()
+        ))
+        .unwrap(),
+    );
+    let lockfile_path = temp_dir.path().join("gensonnet.lock");
+    let app = gensonnet::JsonnetGen::new(config)
+        .unwrap()
+        .with_lockfile_path(lockfile_path.clone());
+
+    let result = app.generate_source("demo").await.unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    let output = temp_dir.path().join("generated/demo");
+    assert!(output
+        .join("demo.gensonnet.dev_v1/widgets.demo.gensonnet.dev.libsonnet")
+        .exists());
+    assert!(output
+        .join("demo.gensonnet.dev_v1alpha1/gadgets.demo.gensonnet.dev.libsonnet")
+        .exists());
+
+    // The fixture set is pinned by its digest rather than a commit
+    let lockfile = LockfileManager::new(lockfile_path)
+        .load_or_create()
+        .unwrap();
+    let entry = &lockfile.sources["demo"];
+    assert_eq!(entry.url, "fixture://crds");
+    assert!(entry.commit_sha.is_empty());
+    assert_eq!(
+        entry.digest,
+        Some(gensonnet::fixture::digest(
+            gensonnet::config::FixtureSet::Crds
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_resource_limits() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    config.sources.push(
+        serde_yaml::from_str(&format!(
+            "type: fixture\nname: demo\nfixture: crds\noutput_path: {}\n",
+            temp_dir.path().join("generated/demo").display()
+        ))
+        .unwrap(),
+    );
+    config.generation.resource_limits.max_generated_files = Some(1);
+    let app = gensonnet::JsonnetGen::new(config)
+        .unwrap()
+        .with_lockfile_path(temp_dir.path().join("gensonnet.lock"));
+
+    // The run fails before anything is published, instead of recovering the source
+    let err = app.generate().await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("generation.resource_limits.max_generated_files (1)"),
+        "{err}"
+    );
+    assert!(app.resource_usage().generated_files > 1);
+    assert!(!temp_dir.path().join("generated/demo").exists());
+}
+
+#[tokio::test]
+async fn test_generate_check() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    config.bundle.enabled = true;
+    config.sources.push(
+        serde_yaml::from_str(&format!(
+            "type: fixture\nname: demo\nfixture: crds\noutput_path: {}\n",
+            temp_dir.path().join("generated/demo").display()
+        ))
+        .unwrap(),
+    );
+    let lockfile_path = temp_dir.path().join("gensonnet.lock");
+    let app = gensonnet::JsonnetGen::new(config)
+        .unwrap()
+        .with_lockfile_path(lockfile_path.clone());
+    app.generate().await.unwrap();
+    let lockfile = std::fs::read_to_string(&lockfile_path).unwrap();
+
+    let result = app.check().await.unwrap();
+    assert!(result.is_up_to_date(), "{:?}", result.mismatches);
+    assert!(result.files_checked > 2);
+    assert_eq!(std::fs::read_to_string(&lockfile_path).unwrap(), lockfile);
+
+    // Edited and deleted files are reported, and left as they are
+    let library = temp_dir
+        .path()
+        .join("generated/demo/demo.gensonnet.dev_v1/widgets.demo.gensonnet.dev.libsonnet");
+    std::fs::write(&library, "// edited\n").unwrap();
+    let bundle = temp_dir.path().join("generated/platform.libsonnet");
+    std::fs::remove_file(&bundle).unwrap();
+    let result = app.check().await.unwrap();
+    assert!(!result.is_up_to_date());
+    assert_eq!(
+        result
+            .mismatches
+            .iter()
+            .map(|mismatch| (mismatch.path.clone(), mismatch.kind))
+            .collect::<Vec<_>>(),
+        [
+            (library.clone(), gensonnet::check::MismatchKind::Changed),
+            (bundle.clone(), gensonnet::check::MismatchKind::Missing),
+        ]
+    );
+    assert_eq!(std::fs::read_to_string(&library).unwrap(), "// edited\n");
+    assert!(!bundle.exists());
+}
+
+#[tokio::test]
+async fn test_output_collisions() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    for (name, output) in [
+        ("first", "generated/shared"),
+        ("second", "generated/./shared/"),
+    ] {
+        config.sources.push(
+            serde_yaml::from_str(&format!(
+                "type: fixture\nname: {name}\nfixture: crds\noutput_path: {}\n",
+                temp_dir.path().join(output).display()
+            ))
+            .unwrap(),
+        );
+    }
+    let lockfile_path = temp_dir.path().join("gensonnet.lock");
+
+    // Sources sharing an output path are refused before anything is written
+    let app = gensonnet::JsonnetGen::new(config.clone())
+        .unwrap()
+        .with_lockfile_path(lockfile_path.clone());
+    let err = app.generate().await.unwrap_err();
+    assert!(err.to_string().contains("first, second"), "{err}");
+    assert!(app.dry_run().await.is_err());
+    assert!(!temp_dir.path().join("generated/shared").exists());
+
+    // Namespacing moves each of them to a subdirectory named after it
+    config.generation.on_output_collision = gensonnet::config::OutputCollisionPolicy::Namespace;
+    let app = gensonnet::JsonnetGen::new(config)
+        .unwrap()
+        .with_lockfile_path(lockfile_path);
+    let result = app.generate().await.unwrap();
+    assert_eq!(result.sources_processed, 2);
+    for name in ["first", "second"] {
+        assert!(temp_dir
+            .path()
+            .join("generated/shared")
+            .join(name)
+            .join("demo.gensonnet.dev_v1/widgets.demo.gensonnet.dev.libsonnet")
+            .exists());
+    }
+}
+
+#[tokio::test]
+async fn test_library_version_stamp() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    config.bundle.enabled = true;
+    config.sources.push(
+        serde_yaml::from_str(&format!(
+            "type: fixture\nname: demo\nfixture: crds\noutput_path: {}\n",
+            temp_dir.path().join("generated/demo").display()
+        ))
+        .unwrap(),
+    );
+    let app = gensonnet::JsonnetGen::new(config)
+        .unwrap()
+        .with_lockfile_path(temp_dir.path().join("gensonnet.lock"));
+    app.generate().await.unwrap();
+
+    // Fixtures have no tag, so their version is the short digest
+    let digest = gensonnet::fixture::digest(gensonnet::config::FixtureSet::Crds);
+    let version = digest["sha256:".len()..][..7].to_string();
+    let index =
+        std::fs::read_to_string(temp_dir.path().join("generated/demo/index.libsonnet")).unwrap();
+    assert!(
+        index.contains(&format!("  __version__:: \"{version}\",\n")),
+        "{index}"
+    );
+
+    let bundle =
+        std::fs::read_to_string(temp_dir.path().join("generated/platform.libsonnet")).unwrap();
+    assert!(
+        bundle.contains(&format!("version: \"{version}\"")),
+        "{bundle}"
+    );
+}
+
+#[tokio::test]
+async fn test_output_mirrors() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    config.output.mirrors = vec![temp_dir.path().join("artifacts")];
+    config.bundle.enabled = true;
+    config.sources.push(
+        serde_yaml::from_str(&format!(
+            "type: fixture\nname: demo\nfixture: crds\noutput_path: {}\n",
+            temp_dir.path().join("generated/libs/demo").display()
+        ))
+        .unwrap(),
+    );
+    let app = gensonnet::JsonnetGen::new(config.clone())
+        .unwrap()
+        .with_lockfile_path(temp_dir.path().join("gensonnet.lock"));
+    app.generate().await.unwrap();
+
+    for file in [
+        "libs/demo/index.libsonnet",
+        "libs/demo/demo.gensonnet.dev_v1/widgets.demo.gensonnet.dev.libsonnet",
+        "platform.libsonnet",
+    ] {
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("artifacts").join(file)).unwrap(),
+            std::fs::read_to_string(temp_dir.path().join("generated").join(file)).unwrap(),
+            "{file}"
+        );
+    }
+
+    // Files the library no longer has leave the mirror too
+    let stale = temp_dir.path().join("artifacts/libs/demo/stale.libsonnet");
+    std::fs::write(&stale, "{}").unwrap();
+    app.generate().await.unwrap();
+    assert!(!stale.exists());
+    assert!(temp_dir
+        .path()
+        .join("artifacts/libs/demo/index.libsonnet")
+        .exists());
+
+    // Mirrors inside the base path would be mirrored into themselves
+    config.output.mirrors = vec![temp_dir.path().join("generated/mirror")];
+    assert!(config.validate().is_err());
+}
+
+/// Serves a directory of CRDs as every repository, at a fixed commit, or
+/// fails while the upstream is down
+struct DirectoryFetcher {
+    upstream: PathBuf,
+    reachable: std::sync::atomic::AtomicBool,
+}
+
+impl DirectoryFetcher {
+    fn new(upstream: PathBuf) -> Self {
+        Self {
+            upstream,
+            reachable: std::sync::atomic::AtomicBool::new(true),
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl gensonnet::GitFetcher for DirectoryFetcher {
+    async fn ensure_repository(
+        &self,
+        git_source: &gensonnet::config::GitSource,
+    ) -> anyhow::Result<PathBuf> {
+        if !self.reachable.load(std::sync::atomic::Ordering::SeqCst) {
+            anyhow::bail!("Failed to fetch {}: connection refused", git_source.url);
+        }
+        Ok(self.upstream.clone())
+    }
+
+    async fn remote_commit(
+        &self,
+        _git_source: &gensonnet::config::GitSource,
+    ) -> anyhow::Result<Option<String>> {
+        Ok(Some("0123456789abcdef0123456789abcdef01234567".to_string()))
+    }
+
+    fn get_current_commit(&self, _repo_path: &std::path::Path) -> anyhow::Result<String> {
+        Ok("0123456789abcdef0123456789abcdef01234567".to_string())
+    }
+
+    fn pin(&self, _git_source: &gensonnet::config::GitSource, _commit: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_custom_git_fetcher_and_clock() {
+    let temp_dir = TempDir::new().unwrap();
+    let upstream = temp_dir.path().join("upstream");
+    std::fs::create_dir_all(&upstream).unwrap();
+    std::fs::write(upstream.join("widget.yaml"), crd("example.com", "Widget")).unwrap();
+
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    config.sources.push(
+        serde_yaml::from_str(&format!(
+            "type: crd\nname: widgets\ngit:\n  url: https://git.internal/widgets.git\nfilters: []\noutput_path: {}\n",
+            temp_dir.path().join("generated/widgets").display()
+        ))
+        .unwrap(),
+    );
+    let generated_at = chrono::DateTime::parse_from_rfc3339("2026-03-01T12:00:00Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+    let app = gensonnet::JsonnetGen::new(config)
+        .unwrap()
+        .with_lockfile_path(temp_dir.path().join("gensonnet.lock"))
+        .with_git_fetcher(std::sync::Arc::new(DirectoryFetcher::new(upstream)))
+        .with_clock(std::sync::Arc::new(gensonnet::FixedClock(generated_at)));
+    let result = app.generate().await.unwrap();
+    assert!(result.results[0].errors.is_empty(), "{:?}", result.results);
+
+    let output = temp_dir.path().join("generated/widgets");
+    assert!(output
+        .join("example.com_v1/widgets.example.com.libsonnet")
+        .exists());
+    let meta = std::fs::read_to_string(output.join("_meta.libsonnet")).unwrap();
+    assert!(
+        meta.contains("generated_at: \"2026-03-01T12:00:00+00:00\""),
+        "{meta}"
+    );
+    let index = std::fs::read_to_string(output.join("index.libsonnet")).unwrap();
+    assert!(index.contains("__version__:: \"0123456\""), "{index}");
+}
+
+#[tokio::test]
+async fn test_source_quarantine() {
+    let temp_dir = TempDir::new().unwrap();
+    let upstream = temp_dir.path().join("upstream");
+    std::fs::create_dir_all(&upstream).unwrap();
+    std::fs::write(upstream.join("widget.yaml"), crd("example.com", "Widget")).unwrap();
+
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    config.generation.quarantine_after = Some(2);
+    for source in [
+        "type: fixture\nname: demo\nfixture: crds\noutput_path: {}/demo\n",
+        "type: crd\nname: widgets\ngit:\n  url: https://git.internal/widgets.git\nfilters: []\noutput_path: {}/widgets\n",
+    ] {
+        config.sources.push(
+            serde_yaml::from_str(
+                &source.replace("{}", &temp_dir.path().join("generated").display().to_string()),
+            )
+            .unwrap(),
+        );
+    }
+    let fetcher = std::sync::Arc::new(DirectoryFetcher::new(upstream));
+    fetcher
+        .reachable
+        .store(false, std::sync::atomic::Ordering::SeqCst);
+    let lockfile_path = temp_dir.path().join("gensonnet.lock");
+    let app = |retry_quarantined: bool| {
+        gensonnet::JsonnetGen::new(config.clone())
+            .unwrap()
+            .with_lockfile_path(lockfile_path.clone())
+            .with_git_fetcher(fetcher.clone())
+            .with_retry_quarantined(retry_quarantined)
+    };
+    let health = || {
+        gensonnet::Lockfile::from_file(&lockfile_path)
+            .unwrap()
+            .health
+    };
+    let sources = |result: &gensonnet::GenerationResult| {
+        result
+            .results
+            .iter()
+            .map(|source_result| source_result.source_name.clone())
+            .collect::<Vec<_>>()
+    };
+
+    // An unreachable upstream fails its source, not the run
+    let result = app(false).generate().await.unwrap();
+    assert_eq!(sources(&result), ["demo", "widgets"]);
+    assert!(result.results[1].errors[0].contains("connection refused"));
+    assert!(temp_dir
+        .path()
+        .join("generated/demo/index.libsonnet")
+        .exists());
+    assert_eq!(health()["widgets"].consecutive_failures, 1);
+    assert!(!health()["widgets"].quarantined);
+
+    app(false).generate().await.unwrap();
+    assert!(health()["widgets"].quarantined);
+
+    // Quarantined sources are skipped, even once reachable again
+    fetcher
+        .reachable
+        .store(true, std::sync::atomic::Ordering::SeqCst);
+    let result = app(false).generate().await.unwrap();
+    assert_eq!(result.quarantined, ["widgets"]);
+    assert!(!sources(&result).contains(&"widgets".to_string()));
+    assert_eq!(health()["widgets"].consecutive_failures, 2);
+
+    // Retrying a source that succeeds lifts its quarantine
+    let result = app(true).generate().await.unwrap();
+    assert!(result.quarantined.is_empty());
+    assert!(
+        result.results.iter().all(|r| r.errors.is_empty()),
+        "{:?}",
+        result.results
+    );
+    assert!(health().is_empty());
+    assert!(temp_dir
+        .path()
+        .join("generated/widgets/example.com_v1/widgets.example.com.libsonnet")
+        .exists());
+}
+
+#[tokio::test]
+async fn test_hermetic_generation() {
+    // Generates the same upstream from two directories at different times
+    let generate = |generated_at: &str| {
+        let generated_at = chrono::DateTime::parse_from_rfc3339(generated_at)
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        async move {
+            let temp_dir = TempDir::new().unwrap();
+            let upstream = temp_dir.path().join("upstream");
+            std::fs::create_dir_all(upstream.join("crds")).unwrap();
+            std::fs::write(
+                upstream.join("crds/widget.yaml"),
+                "apiVersion: apiextensions.k8s.io/v1\nkind: CustomResourceDefinition\nmetadata:\n  name: widgets.example.com\nspec:\n  group: example.com\n  names:\n    kind: Widget\n    plural: widgets\n  versions:\n  - name: v1\n    schema:\n      openAPIV3Schema:\n        type: object\n        properties:\n          name:\n            type: string\n            maxLength: 63\n",
+            )
+            .unwrap();
+
+            let mut config = Config::default();
+            config.output.base_path = temp_dir.path().join("generated");
+            config.sources.push(
+                serde_yaml::from_str(&format!(
+                    "type: crd\nname: widgets\ngit:\n  url: https://git.internal/widgets.git\nfilters: []\noutput_path: {}\n",
+                    temp_dir.path().join("generated/widgets").display()
+                ))
+                .unwrap(),
+            );
+            // The window of the rename closes between the two runs
+            config.compatibility = serde_yaml::from_str(
+                "window_days: 30\nrenames:\n  - type: kind\n    from: Gadget\n    to: Widget\n    since: 2026-02-15\n",
+            )
+            .unwrap();
+            let hermetic = |config: Config| {
+                gensonnet::JsonnetGen::new(config)
+                    .unwrap()
+                    .with_lockfile_path(temp_dir.path().join("gensonnet.lock"))
+                    .with_git_fetcher(std::sync::Arc::new(DirectoryFetcher::new(upstream.clone())))
+                    .with_clock(std::sync::Arc::new(gensonnet::FixedClock(generated_at)))
+                    .with_hermetic(true)
+            };
+            let err = hermetic(config.clone()).generate().await.unwrap_err();
+            assert!(err.to_string().contains("compatibility.as_of"), "{err}");
+
+            config.compatibility.as_of = Some("2026-03-01".parse().unwrap());
+            hermetic(config).generate().await.unwrap();
+
+            let mut files = std::collections::BTreeMap::new();
+            for entry in walkdir::WalkDir::new(temp_dir.path())
+                .into_iter()
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().is_file())
+            {
+                let path = entry.path().strip_prefix(temp_dir.path()).unwrap();
+                if !path.starts_with("upstream") {
+                    files.insert(
+                        path.to_path_buf(),
+                        std::fs::read_to_string(entry.path()).unwrap(),
+                    );
+                }
+            }
+            files
+        }
+    };
+
+    let first = generate("2026-03-01T12:00:00Z").await;
+    let second = generate("2026-04-15T08:30:00Z").await;
+    assert_eq!(first, second);
+
+    let library = &first
+        [std::path::Path::new("generated/widgets/example.com_v1/widgets.example.com.libsonnet")];
+    assert!(
+        library.contains("// Source: crds/widget.yaml\n"),
+        "{library}"
+    );
+    assert!(
+        library.contains("\"name (crds/widget.yaml:16)\""),
+        "{library}"
+    );
+    let lockfile = &first[std::path::Path::new("gensonnet.lock")];
+    assert!(
+        lockfile.contains("\n  generated/widgets/index.libsonnet:\n"),
+        "{lockfile}"
+    );
+    assert!(!lockfile.contains("2026"), "{lockfile}");
+    assert!(first.contains_key(std::path::Path::new("generated/widgets/_compat.libsonnet")));
+}
+
+#[tokio::test]
+async fn test_api_freeze() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    config.sources.push(
+        serde_yaml::from_str(&format!(
+            "type: fixture\nname: demo\nfixture: crds\noutput_path: {}\n",
+            temp_dir.path().join("generated/demo").display()
+        ))
+        .unwrap(),
+    );
+    let lockfile_path = temp_dir.path().join("gensonnet.lock");
+    let app = gensonnet::JsonnetGen::new(config.clone())
+        .unwrap()
+        .with_lockfile_path(lockfile_path.clone());
+    app.generate().await.unwrap();
+
+    let freeze_path = app.freeze_api().unwrap();
+    assert_eq!(freeze_path, temp_dir.path().join("api-freeze.json"));
+    let mut frozen = gensonnet::freeze::ApiSnapshot::load(&freeze_path)
+        .unwrap()
+        .unwrap();
+    assert!(!frozen.sources["demo"].functions.is_empty());
+    assert!(!frozen.sources["demo"].fields.is_empty());
+
+    // Regenerating the same API passes the check
+    app.generate().await.unwrap();
+
+    // A function missing from the new generation is a breaking change
+    frozen
+        .sources
+        .get_mut("demo")
+        .unwrap()
+        .functions
+        .insert("v1 Widget.withRemoved".to_string(), vec![]);
+    frozen.save(&freeze_path).unwrap();
+    let arguments = temp_dir.path().join("generated/demo/_arguments.json");
+    std::fs::remove_file(&arguments).unwrap();
+    let err = app.generate().await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("function v1 Widget.withRemoved was removed"),
+        "{err}"
+    );
+    // The refused library was not published
+    assert!(!arguments.exists());
+
+    let app = gensonnet::JsonnetGen::new(config)
+        .unwrap()
+        .with_lockfile_path(lockfile_path)
+        .with_allow_breaking(true);
+    app.generate().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_generate_locked() {
+    let temp_dir = TempDir::new().unwrap();
+    let upstream = temp_dir.path().join("upstream");
+    let commit_crd = |kind: &str| {
+        std::fs::create_dir_all(upstream.join("crds")).unwrap();
+        std::fs::write(
+            upstream.join(format!("crds/{}.yaml", kind.to_lowercase())),
+            crd("example.com", kind),
+        )
+        .unwrap();
+        commit_upstream(&upstream, &["crds"]).to_string()
+    };
+    let first = commit_crd("Widget");
+
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    config.sources.push(
+        serde_yaml::from_str(&format!(
+            "type: crd\nname: widgets\ngit: {{url: \"{}\", ref: main}}\nfilters: [\"example.com/*\"]\noutput_path: {}\n",
+            upstream.display(),
+            temp_dir.path().join("generated/widgets").display()
+        ))
+        .unwrap(),
+    );
+    let lockfile_path = temp_dir.path().join("gensonnet.lock");
+    let app = |locked: bool| {
+        gensonnet::JsonnetGen::new(config.clone())
+            .unwrap()
+            .with_lockfile_path(lockfile_path.clone())
+            .with_locked(locked)
+    };
+    let lockfile = || {
+        LockfileManager::new(lockfile_path.clone())
+            .load_or_create()
+            .unwrap()
+    };
+
+    // Nothing is locked before the first generation
+    assert!(app(true).generate().await.is_err());
+    app(false).generate().await.unwrap();
+    assert_eq!(lockfile().sources["widgets"].commit_sha, first);
+
+    // Upstream moves on, but a locked generation stays on the locked commit
+    commit_crd("Gadget");
+    std::fs::remove_dir_all(temp_dir.path().join("generated")).unwrap();
+    app(true).generate().await.unwrap();
+    let output = temp_dir.path().join("generated/widgets/example.com_v1");
+    assert!(output.join("widgets.example.com.libsonnet").exists());
+    assert!(!output.join("gadgets.example.com.libsonnet").exists());
+    assert_eq!(lockfile().sources["widgets"].commit_sha, first);
+
+    // A locked commit missing upstream is reported
+    let mut locked = lockfile();
+    let missing = "0123456789abcdef0123456789abcdef01234567";
+    locked.sources.get_mut("widgets").unwrap().commit_sha = missing.to_string();
+    locked.save_to_file(&lockfile_path).unwrap();
+    let error = app(true).generate().await.unwrap_err();
+    assert!(error.to_string().contains(missing), "{error}");
+    assert!(error.to_string().contains("unreachable"), "{error}");
+}
+
+#[tokio::test]
+async fn test_prune_lockfile_of_removed_sources() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    for name in ["kept", "removed"] {
+        config.sources.push(
+            serde_yaml::from_str(&format!(
+                "type: fixture\nname: {name}\noutput_path: {}\n",
+                temp_dir.path().join("generated").join(name).display()
+            ))
+            .unwrap(),
+        );
+    }
+    let lockfile_path = temp_dir.path().join("gensonnet.lock");
+    let app = |config: Config| {
+        gensonnet::JsonnetGen::new(config)
+            .unwrap()
+            .with_lockfile_path(lockfile_path.clone())
+    };
+    app(config.clone()).generate().await.unwrap();
+
+    config.sources.retain(|source| source.name() == "kept");
+    let app = app(config);
+    let orphans = app.lockfile_orphans().unwrap();
+    assert_eq!(orphans.sources, ["removed"]);
+    assert!(!orphans.files.is_empty());
+    assert!(orphans
+        .files
+        .iter()
+        .all(|file| file.starts_with(temp_dir.path().join("generated/removed"))));
+
+    assert_eq!(app.prune_lockfile().unwrap(), orphans);
+    assert!(app.lockfile_orphans().unwrap().is_empty());
+    let lockfile = LockfileManager::new(lockfile_path.clone())
+        .load_or_create()
+        .unwrap();
+    assert!(lockfile.sources.contains_key("kept"));
+    assert!(!lockfile.sources.contains_key("removed"));
+}
+
+#[tokio::test]
+async fn test_warning_suppression_and_denial() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.output.base_path = temp_dir.path().join("generated");
+    config.sources.push(
+        serde_yaml::from_str(&format!(
+            "type: fixture\nname: demo\nfixture: crds\noutput_path: {}\n",
+            temp_dir.path().join("generated/demo").display()
+        ))
+        .unwrap(),
+    );
+    config.policies = serde_yaml::from_str(
+        "rules:\n  - name: example-group\n    expression: \"self.group == 'example.com'\"\n",
+    )
+    .unwrap();
+    let app = |config: &Config| {
+        gensonnet::JsonnetGen::new(config.clone())
+            .unwrap()
+            .with_lockfile_path(temp_dir.path().join("gensonnet.lock"))
+    };
+
+    // Policy violations are typed warnings of the source
+    let result = app(&config).generate_source("demo").await.unwrap();
+    let paths: Vec<_> = result
+        .warnings
+        .iter()
+        .map(|warning| {
+            assert_eq!(warning.category, gensonnet::WarningCategory::Policy);
+            assert_eq!(warning.code, "policy/example-group");
+            assert_eq!(warning.source, "demo");
+            warning.path.clone().unwrap()
+        })
+        .collect();
+    assert_eq!(paths.len(), 2, "{paths:?}");
+
+    // Suppressed warnings are left out of results
+    config.warnings = serde_yaml::from_str(&format!(
+        "suppress:\n  - code: policy/example-group\n    path: \"{}\"\n",
+        paths[0]
+    ))
+    .unwrap();
+    let result = app(&config).generate_source("demo").await.unwrap();
+    assert_eq!(result.warnings.len(), 1);
+    assert_eq!(result.warnings[0].path.as_deref(), Some(paths[1].as_str()));
+
+    // Denied categories fail the run, which still generates
+    config.warnings.deny = vec![gensonnet::WarningCategory::Policy];
+    std::fs::remove_dir_all(temp_dir.path().join("generated")).unwrap();
+    let err = app(&config).generate().await.unwrap_err().to_string();
+    assert!(err.contains("warnings in denied categories"), "{err}");
+    assert!(
+        err.contains("demo (policy): Policy 'example-group'"),
+        "{err}"
+    );
+    assert!(temp_dir
+        .path()
+        .join("generated/demo/demo.gensonnet.dev_v1/widgets.demo.gensonnet.dev.libsonnet")
+        .exists());
+}